@@ -0,0 +1,30 @@
+#![feature(test)]
+
+extern crate test;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use test::Bencher;
+
+/// Hash with 64 MiB of memory split into `p_cost` lanes, which are filled
+/// on as many threads with the `threads` feature.
+fn argon2id_64m(bh: &mut Bencher, p_cost: u32) {
+    let password = b"my secure password";
+    let salt = b"salty salt";
+    let mut buf = [0u8; 32];
+    let params = Params::new(64 * 1024, 1, p_cost, None).unwrap();
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+    bh.iter(|| {
+        argon2.hash_password_into(password, salt, &mut buf).unwrap();
+        test::black_box(&buf);
+    });
+}
+
+#[bench]
+pub fn argon2id_64m_1_1(bh: &mut Bencher) {
+    argon2id_64m(bh, 1);
+}
+
+#[bench]
+pub fn argon2id_64m_1_4(bh: &mut Bencher) {
+    argon2id_64m(bh, 4);
+}
@@ -0,0 +1,172 @@
+//! Derivation of subkeys from a master key, such as an Argon2 hash.
+//!
+//! [`derive_subkey`] derives any number of independent subkeys from a single
+//! master key, each identified by a context label and a numeric ID, as
+//! libsodium's [`crypto_kdf`][1] does: the subkeys are BLAKE2b of an empty
+//! message, keyed with the master key and personalized with the context and
+//! the ID. For 32-byte master keys and contexts of 8 bytes, the subkeys are
+//! those of `crypto_kdf_derive_from_key`.
+//!
+//! The master key is usually derived from a password with
+//! [`Argon2::hash_password_into`](crate::Argon2::hash_password_into), which is
+//! the costly step: deriving subkeys from it is cheap.
+//!
+#![cfg_attr(feature = "std", doc = "```")]
+#![cfg_attr(not(feature = "std"), doc = "```ignore")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use argon2::{kdf, Argon2};
+//!
+//! let password = b"hunter42"; // Bad password; don't actually use!
+//! let salt = b"example salt"; // Salt should be unique per password
+//!
+//! let mut master_key = [0u8; kdf::KEY_LEN];
+//! Argon2::default().hash_password_into(password, salt, &mut master_key)?;
+//!
+//! let mut encryption_key = [0u8; 32];
+//! let mut mac_key = [0u8; 32];
+//! kdf::derive_subkey(&master_key, "messages", 1, &mut encryption_key)?;
+//! kdf::derive_subkey(&master_key, "messages", 2, &mut mac_key)?;
+//! assert_ne!(encryption_key, mac_key);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [1]: https://doc.libsodium.org/key_derivation
+
+use core::fmt;
+
+use blake2::{
+    digest::{
+        block_buffer::{BlockBuffer, Lazy},
+        core_api::VariableOutputCore,
+        generic_array::GenericArray,
+    },
+    Blake2bVarCore,
+};
+
+/// Recommended length of master keys, that of libsodium's `crypto_kdf`.
+pub const KEY_LEN: usize = 32;
+
+/// Minimum length of master keys.
+pub const MIN_KEY_LEN: usize = 16;
+
+/// Maximum length of master keys.
+pub const MAX_KEY_LEN: usize = 64;
+
+/// Minimum length of subkeys.
+pub const MIN_SUBKEY_LEN: usize = 16;
+
+/// Maximum length of subkeys.
+pub const MAX_SUBKEY_LEN: usize = 64;
+
+/// Maximum length of contexts, in bytes.
+pub const MAX_CONTEXT_LEN: usize = 8;
+
+/// Length of the BLAKE2b salt and personalization.
+const PARAM_LEN: usize = 16;
+
+/// Length of BLAKE2b blocks.
+const BLOCK_LEN: usize = 128;
+
+/// Error of [`derive_subkey`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// Context is too long.
+    ContextTooLong,
+
+    /// Master key is too short.
+    KeyTooShort,
+
+    /// Master key is too long.
+    KeyTooLong,
+
+    /// Subkey is too short.
+    SubkeyTooShort,
+
+    /// Subkey is too long.
+    SubkeyTooLong,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Error::ContextTooLong => "context is too long",
+            Error::KeyTooShort => "master key is too short",
+            Error::KeyTooLong => "master key is too long",
+            Error::SubkeyTooShort => "subkey is too short",
+            Error::SubkeyTooLong => "subkey is too long",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Fill `out` with the subkey `subkey_id` of `master` for `context`.
+///
+/// `context` tells apart the subkeys of different purposes, e.g. the name of
+/// the part of an application using them, and is at most
+/// [`MAX_CONTEXT_LEN`] bytes long. It doesn't need to be secret. Subkeys
+/// differ for every `subkey_id`, and for every length of `out`, which is
+/// between [`MIN_SUBKEY_LEN`] and [`MAX_SUBKEY_LEN`] bytes.
+///
+/// # Returns
+/// - `Err(Error::KeyTooShort)` or `Err(Error::KeyTooLong)` if `master` is
+///   not between [`MIN_KEY_LEN`] and [`MAX_KEY_LEN`] bytes long
+/// - `Err(Error::ContextTooLong)` if `context` is longer than
+///   [`MAX_CONTEXT_LEN`] bytes
+/// - `Err(Error::SubkeyTooShort)` or `Err(Error::SubkeyTooLong)` if `out` is
+///   not between [`MIN_SUBKEY_LEN`] and [`MAX_SUBKEY_LEN`] bytes long
+pub fn derive_subkey(
+    master: &[u8],
+    context: &str,
+    subkey_id: u64,
+    out: &mut [u8],
+) -> Result<(), Error> {
+    if master.len() < MIN_KEY_LEN {
+        return Err(Error::KeyTooShort);
+    }
+
+    if master.len() > MAX_KEY_LEN {
+        return Err(Error::KeyTooLong);
+    }
+
+    if context.len() > MAX_CONTEXT_LEN {
+        return Err(Error::ContextTooLong);
+    }
+
+    if out.len() < MIN_SUBKEY_LEN {
+        return Err(Error::SubkeyTooShort);
+    }
+
+    if out.len() > MAX_SUBKEY_LEN {
+        return Err(Error::SubkeyTooLong);
+    }
+
+    let mut salt = [0u8; PARAM_LEN];
+    salt[..8].copy_from_slice(&subkey_id.to_le_bytes());
+
+    let mut persona = [0u8; PARAM_LEN];
+    persona[..context.len()].copy_from_slice(context.as_bytes());
+
+    let mut core = Blake2bVarCore::new_with_params(&salt, &persona, master.len(), out.len());
+
+    // Keyed BLAKE2b of an empty message is the hash of the key padded to a
+    // block, which is the last block and must stay in the buffer.
+    let mut key_block = [0u8; BLOCK_LEN];
+    key_block[..master.len()].copy_from_slice(master);
+    let mut buffer = BlockBuffer::<_, Lazy>::new(&key_block);
+
+    let mut full = GenericArray::default();
+    core.finalize_variable_core(&mut buffer, &mut full);
+    out.copy_from_slice(&full[..out.len()]);
+
+    #[cfg(feature = "zeroize")]
+    {
+        use zeroize::Zeroize;
+        key_block.zeroize();
+        full.as_mut_slice().zeroize();
+    }
+
+    Ok(())
+}
@@ -95,7 +95,14 @@ mod algorithm;
 mod blake2b_long;
 mod block;
 mod error;
+pub mod kdf;
 mod params;
+// `wasm32-unknown-unknown` has no threads: its lanes are filled in turn
+#[cfg(all(
+    feature = "threads",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod threads;
 mod version;
 
 pub use crate::{
@@ -115,10 +122,14 @@ pub use {
 
 use crate::blake2b_long::blake2b_long;
 use blake2::{digest, Blake2b512, Digest};
+use core::ops::IndexMut;
 
 #[cfg(all(feature = "alloc", feature = "password-hash"))]
 use password_hash::{Decimal, Ident, ParamsString, Salt};
 
+#[cfg(all(feature = "alloc", feature = "password-hash"))]
+use subtle::ConstantTimeEq;
+
 #[cfg(feature = "zeroize")]
 use zeroize::Zeroize;
 
@@ -183,7 +194,12 @@ impl<'key> Argon2<'key> {
         }
     }
 
-    /// Create a new Argon2 context.
+    /// Create a new Argon2 context with a secret key, a.k.a. "pepper".
+    ///
+    /// The secret is used by the `PasswordHasher` and `PasswordVerifier`
+    /// impls as well, but unlike the associated data of [`Params`] it isn't
+    /// encoded in PHC strings: hashes made with a secret only verify with a
+    /// context holding the same secret.
     pub fn new_with_secret(
         secret: &'key [u8],
         algorithm: Algorithm,
@@ -220,11 +236,42 @@ impl<'key> Argon2<'key> {
     /// - `no_std` users on "heapless" targets can use an array of the [`Block`] type
     ///   to stack allocate this buffer.
     pub fn hash_password_into_with_memory(
+        &self,
+        pwd: &[u8],
+        salt: &[u8],
+        out: &mut [u8],
+        memory_blocks: impl AsMut<[Block]>,
+    ) -> Result<()> {
+        self.hash_password_into_with_memory_and_callback(pwd, salt, out, memory_blocks, |_| ())
+    }
+
+    /// Hash a password into `out` as [`Argon2::hash_password_into_with_memory`]
+    /// does, calling `on_pass` with the memory blocks after each pass.
+    ///
+    /// This exposes the intermediate states of the memory, such as the
+    /// blocks printed after each pass by the test vectors of RFC 9106, to
+    /// debug implementations against this one. `on_pass` is given the
+    /// `block_count` blocks in use, and is called `t_cost` times.
+    #[cfg(feature = "debug-internals")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "debug-internals")))]
+    pub fn hash_password_into_with_on_pass(
+        &self,
+        pwd: &[u8],
+        salt: &[u8],
+        out: &mut [u8],
+        memory_blocks: impl AsMut<[Block]>,
+        on_pass: impl FnMut(&[Block]),
+    ) -> Result<()> {
+        self.hash_password_into_with_memory_and_callback(pwd, salt, out, memory_blocks, on_pass)
+    }
+
+    fn hash_password_into_with_memory_and_callback(
         &self,
         pwd: &[u8],
         salt: &[u8],
         out: &mut [u8],
         mut memory_blocks: impl AsMut<[Block]>,
+        on_pass: impl FnMut(&[Block]),
     ) -> Result<()> {
         // Validate output length
         if out.len() < self.params.output_len().unwrap_or(Params::MIN_OUTPUT_LEN) {
@@ -240,7 +287,7 @@ impl<'key> Argon2<'key> {
         // Hashing all inputs
         let initial_hash = self.initial_hash(pwd, salt, out);
 
-        self.fill_blocks(memory_blocks.as_mut(), initial_hash)?;
+        self.fill_blocks(memory_blocks.as_mut(), initial_hash, on_pass)?;
         self.finalize(memory_blocks.as_mut(), out)
     }
 
@@ -259,7 +306,7 @@ impl<'key> Argon2<'key> {
 
         let initial_hash = self.initial_hash(pwd, salt, &[]);
 
-        self.fill_blocks(memory_blocks.as_mut(), initial_hash)
+        self.fill_blocks(memory_blocks.as_mut(), initial_hash, |_| ())
     }
 
     #[allow(clippy::cast_possible_truncation, unused_mut)]
@@ -267,13 +314,13 @@ impl<'key> Argon2<'key> {
         &self,
         memory_blocks: &mut [Block],
         mut initial_hash: digest::Output<Blake2b512>,
+        mut on_pass: impl FnMut(&[Block]),
     ) -> Result<()> {
         let block_count = self.params.block_count();
         let memory_blocks = memory_blocks
             .get_mut(..block_count)
             .ok_or(Error::MemoryTooLittle)?;
 
-        let segment_length = self.params.segment_length();
         let iterations = self.params.t_cost() as usize;
         let lane_length = self.params.lane_length();
         let lanes = self.params.lanes();
@@ -300,137 +347,155 @@ impl<'key> Argon2<'key> {
         // Run passes on blocks
         for pass in 0..iterations {
             for slice in 0..SYNC_POINTS {
-                let data_independent_addressing = self.algorithm == Algorithm::Argon2i
-                    || (self.algorithm == Algorithm::Argon2id
-                        && pass == 0
-                        && slice < SYNC_POINTS / 2);
+                #[cfg(all(
+                    feature = "threads",
+                    not(all(target_arch = "wasm32", target_os = "unknown"))
+                ))]
+                if lanes > 1 {
+                    self.fill_slice_in_threads(memory_blocks, pass, slice);
+                    continue;
+                }
 
                 for lane in 0..lanes {
-                    let mut address_block = Block::default();
-                    let mut input_block = Block::default();
-                    let zero_block = Block::default();
-
-                    if data_independent_addressing {
-                        input_block.as_mut()[..6].copy_from_slice(&[
-                            pass as u64,
-                            lane as u64,
-                            slice as u64,
-                            memory_blocks.len() as u64,
-                            iterations as u64,
-                            self.algorithm as u64,
-                        ]);
-                    }
-
-                    let first_block = if pass == 0 && slice == 0 {
-                        if data_independent_addressing {
-                            // Generate first set of addresses
-                            Self::update_address_block(
-                                &mut address_block,
-                                &mut input_block,
-                                &zero_block,
-                            );
-                        }
-
-                        // The first two blocks of each lane are already initialized
-                        2
-                    } else {
-                        0
-                    };
-
-                    let mut cur_index = lane * lane_length + slice * segment_length + first_block;
-                    let mut prev_index = if slice == 0 && first_block == 0 {
-                        // Last block in current lane
-                        cur_index + lane_length - 1
-                    } else {
-                        // Previous block
-                        cur_index - 1
-                    };
-
-                    // Fill blocks in the segment
-                    for block in first_block..segment_length {
-                        // Extract entropy
-                        let rand = if data_independent_addressing {
-                            let addres_index = block % ADDRESSES_IN_BLOCK;
-
-                            if addres_index == 0 {
-                                Self::update_address_block(
-                                    &mut address_block,
-                                    &mut input_block,
-                                    &zero_block,
-                                );
-                            }
-
-                            address_block.as_ref()[addres_index]
-                        } else {
-                            memory_blocks[prev_index].as_ref()[0]
-                        };
-
-                        // Calculate source block index for compress function
-                        let ref_lane = if pass == 0 && slice == 0 {
-                            // Cannot reference other lanes yet
-                            lane
-                        } else {
-                            (rand >> 32) as usize % lanes
-                        };
-
-                        let reference_area_size = if pass == 0 {
-                            // First pass
-                            if slice == 0 {
-                                // First slice
-                                block - 1 // all but the previous
-                            } else if ref_lane == lane {
-                                // The same lane => add current segment
-                                slice * segment_length + block - 1
-                            } else {
-                                slice * segment_length - if block == 0 { 1 } else { 0 }
-                            }
-                        } else {
-                            // Second pass
-                            if ref_lane == lane {
-                                lane_length - segment_length + block - 1
-                            } else {
-                                lane_length - segment_length - if block == 0 { 1 } else { 0 }
-                            }
-                        };
-
-                        // 1.2.4. Mapping rand to 0..<reference_area_size-1> and produce
-                        // relative position
-                        let mut map = rand & 0xFFFFFFFF;
-                        map = (map * map) >> 32;
-                        let relative_position = reference_area_size
-                            - 1
-                            - ((reference_area_size as u64 * map) >> 32) as usize;
-
-                        // 1.2.5 Computing starting position
-                        let start_position = if pass != 0 && slice != SYNC_POINTS - 1 {
-                            (slice + 1) * segment_length
-                        } else {
-                            0
-                        };
-
-                        let lane_index = (start_position + relative_position) % lane_length;
-                        let ref_index = ref_lane * lane_length + lane_index;
-
-                        // Calculate new block
-                        let result =
-                            Block::compress(&memory_blocks[prev_index], &memory_blocks[ref_index]);
-
-                        if self.version == Version::V0x10 || pass == 0 {
-                            memory_blocks[cur_index] = result;
-                        } else {
-                            memory_blocks[cur_index] ^= &result;
-                        };
-
-                        prev_index = cur_index;
-                        cur_index += 1;
-                    }
+                    self.fill_segment(memory_blocks, pass, slice, lane);
                 }
             }
+
+            on_pass(memory_blocks);
         }
 
         Ok(())
     }
 
+    /// Fill the segment of `lane` in `slice` of `pass`: the part of the
+    /// lane in the slice.
+    ///
+    /// `memory_blocks` is indexed as the whole memory, but only the
+    /// segment itself and the blocks it may reference are accessed.
+    #[allow(clippy::cast_possible_truncation)]
+    fn fill_segment<M>(&self, memory_blocks: &mut M, pass: usize, slice: usize, lane: usize)
+    where
+        M: IndexMut<usize, Output = Block> + ?Sized,
+    {
+        let block_count = self.params.block_count();
+        let segment_length = self.params.segment_length();
+        let iterations = self.params.t_cost() as usize;
+        let lane_length = self.params.lane_length();
+        let lanes = self.params.lanes();
+
+        let data_independent_addressing = self.algorithm == Algorithm::Argon2i
+            || (self.algorithm == Algorithm::Argon2id && pass == 0 && slice < SYNC_POINTS / 2);
+
+        let mut address_block = Block::default();
+        let mut input_block = Block::default();
+        let zero_block = Block::default();
+
+        if data_independent_addressing {
+            input_block.as_mut()[..6].copy_from_slice(&[
+                pass as u64,
+                lane as u64,
+                slice as u64,
+                block_count as u64,
+                iterations as u64,
+                self.algorithm as u64,
+            ]);
+        }
+
+        let first_block = if pass == 0 && slice == 0 {
+            if data_independent_addressing {
+                // Generate first set of addresses
+                Self::update_address_block(&mut address_block, &mut input_block, &zero_block);
+            }
+
+            // The first two blocks of each lane are already initialized
+            2
+        } else {
+            0
+        };
+
+        let mut cur_index = lane * lane_length + slice * segment_length + first_block;
+        let mut prev_index = if slice == 0 && first_block == 0 {
+            // Last block in current lane
+            cur_index + lane_length - 1
+        } else {
+            // Previous block
+            cur_index - 1
+        };
+
+        // Fill blocks in the segment
+        for block in first_block..segment_length {
+            // Extract entropy
+            let rand = if data_independent_addressing {
+                let addres_index = block % ADDRESSES_IN_BLOCK;
+
+                if addres_index == 0 {
+                    Self::update_address_block(&mut address_block, &mut input_block, &zero_block);
+                }
+
+                address_block.as_ref()[addres_index]
+            } else {
+                memory_blocks[prev_index].as_ref()[0]
+            };
+
+            // Calculate source block index for compress function
+            let ref_lane = if pass == 0 && slice == 0 {
+                // Cannot reference other lanes yet
+                lane
+            } else {
+                (rand >> 32) as usize % lanes
+            };
+
+            let reference_area_size = if pass == 0 {
+                // First pass
+                if slice == 0 {
+                    // First slice
+                    block - 1 // all but the previous
+                } else if ref_lane == lane {
+                    // The same lane => add current segment
+                    slice * segment_length + block - 1
+                } else {
+                    slice * segment_length - if block == 0 { 1 } else { 0 }
+                }
+            } else {
+                // Second pass
+                if ref_lane == lane {
+                    lane_length - segment_length + block - 1
+                } else {
+                    lane_length - segment_length - if block == 0 { 1 } else { 0 }
+                }
+            };
+
+            // 1.2.4. Mapping rand to 0..<reference_area_size-1> and produce
+            // relative position
+            let mut map = rand & 0xFFFFFFFF;
+            map = (map * map) >> 32;
+            let relative_position =
+                reference_area_size - 1 - ((reference_area_size as u64 * map) >> 32) as usize;
+
+            // 1.2.5 Computing starting position
+            let start_position = if pass != 0 && slice != SYNC_POINTS - 1 {
+                (slice + 1) * segment_length
+            } else {
+                0
+            };
+
+            let lane_index = (start_position + relative_position) % lane_length;
+            let ref_index = ref_lane * lane_length + lane_index;
+
+            // Calculate new block
+            let result = Block::compress(&memory_blocks[prev_index], &memory_blocks[ref_index]);
+
+            if self.version == Version::V0x10 || pass == 0 {
+                memory_blocks[cur_index] = result;
+            } else {
+                memory_blocks[cur_index] ^= &result;
+            };
+
+            prev_index = cur_index;
+            cur_index += 1;
+        }
+    }
+
     /// Get default configured [`Params`].
     pub fn params(&self) -> &Params {
         &self.params
@@ -573,6 +638,41 @@ impl PasswordHasher for Argon2<'_> {
     }
 }
 
+#[cfg(all(feature = "alloc", feature = "password-hash"))]
+impl Argon2<'_> {
+    /// Verify `password` against a PHC string, comparing the computed output
+    /// with the one of `hash` in constant time with [`ConstantTimeEq`].
+    ///
+    /// [`PasswordVerifier::verify_password`] compares them in constant time
+    /// too, through the [`PartialEq`] impl of [`password_hash::Output`]: this
+    /// method doesn't rely on that impl.
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "password-hash")))]
+    pub fn verify_password_timing_safe(
+        &self,
+        password: &[u8],
+        hash: &PasswordHash<'_>,
+    ) -> password_hash::Result<()> {
+        if let (Some(salt), Some(expected_output)) = (&hash.salt, &hash.hash) {
+            let computed_hash = self.hash_password_customized(
+                password,
+                Some(hash.algorithm),
+                hash.version,
+                Params::try_from(hash)?,
+                *salt,
+            )?;
+
+            if let Some(computed_output) = &computed_hash.hash {
+                if bool::from(computed_output.ct_eq(expected_output)) {
+                    return Ok(());
+                }
+            }
+        }
+
+        Err(password_hash::Error::Password)
+    }
+}
+
 impl<'key> From<Params> for Argon2<'key> {
     fn from(params: Params) -> Self {
         Self::new(Algorithm::default(), Version::default(), params)
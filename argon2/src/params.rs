@@ -81,6 +81,25 @@ impl Params {
     /// Maximum digest size in bytes.
     pub const MAX_OUTPUT_LEN: usize = 0xFFFFFFFF;
 
+    /// Parameters for WebAssembly in browsers, whose memory is scarcer:
+    /// - `m_cost = 12 * 1024` (12 MiB)
+    /// - `t_cost = 3`
+    /// - `p_cost = 1`
+    ///
+    /// This is the configuration of the [OWASP cheat sheet] trading memory
+    /// for iterations, as strong as the defaults with 7 MiB less memory.
+    /// There is a single lane, as `wasm32-unknown-unknown` has no threads.
+    ///
+    /// [OWASP cheat sheet]: https://cheatsheetseries.owasp.org/cheatsheets/Password_Storage_Cheat_Sheet.html
+    pub const DEFAULT_WASM: Params = Params {
+        m_cost: 12 * 1024,
+        t_cost: 3,
+        p_cost: 1,
+        keyid: KeyId::EMPTY,
+        data: AssociatedData::EMPTY,
+        output_len: None,
+    };
+
     /// Create new parameters.
     ///
     /// # Arguments
@@ -116,6 +135,9 @@ impl Params {
 
     /// Degree of parallelism. Between 1 and 255.
     ///
+    /// This is the number of lanes of the memory, filled on as many threads
+    /// with the `threads` feature.
+    ///
     /// Value is an integer in decimal (1 to 3 digits).
     pub fn p_cost(&self) -> u32 {
         self.p_cost
@@ -213,6 +235,11 @@ macro_rules! param_buf {
             /// Maximum length in bytes
             pub const MAX_LEN: usize = $max_len;
 
+            const EMPTY: Self = Self {
+                bytes: [0u8; Self::MAX_LEN],
+                len: 0,
+            };
+
             #[doc = "Create a new"]
             #[doc = $name]
             #[doc = "from a slice."]
@@ -538,6 +565,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn default_wasm_is_valid() {
+        let params = ParamsBuilder::new()
+            .m_cost(12 * 1024)
+            .t_cost(3)
+            .p_cost(1)
+            .build();
+        assert_eq!(params, Ok(Params::DEFAULT_WASM));
+        assert_eq!(Params::DEFAULT_WASM.block_count(), 12 * 1024);
+    }
+
     #[test]
     fn associated_data_too_long() {
         let ret = AssociatedData::new(&[0u8; Params::MAX_DATA_LEN + 1]);
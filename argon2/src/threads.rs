@@ -0,0 +1,78 @@
+//! Filling the lanes on worker threads, one per lane.
+
+use crate::{Argon2, Block, SYNC_POINTS};
+use core::ops::{Index, IndexMut};
+use std::{thread, vec::Vec};
+
+/// Memory as seen by the thread filling a segment.
+///
+/// Only the segment itself is writable. Blocks may only reference blocks of
+/// their own segment, or of any lane in the other slices, which no thread
+/// writes to meanwhile.
+struct SegmentView<'a> {
+    /// Segment being filled.
+    segment: &'a mut [Block],
+    /// Index of the first block of `segment` in the memory.
+    offset: usize,
+    segment_length: usize,
+    /// Segments of the memory by lane, then slice. Those of the slice being
+    /// filled are empty.
+    shared: &'a [&'a [Block]],
+}
+
+impl Index<usize> for SegmentView<'_> {
+    type Output = Block;
+
+    fn index(&self, index: usize) -> &Block {
+        match index.checked_sub(self.offset) {
+            Some(i) if i < self.segment.len() => &self.segment[i],
+            _ => &self.shared[index / self.segment_length][index % self.segment_length],
+        }
+    }
+}
+
+impl IndexMut<usize> for SegmentView<'_> {
+    fn index_mut(&mut self, index: usize) -> &mut Block {
+        &mut self.segment[index - self.offset]
+    }
+}
+
+impl Argon2<'_> {
+    /// Fill the segments of every lane in `slice` of `pass`, each on a
+    /// thread of its own, as the lanes are independent within a slice.
+    pub(crate) fn fill_slice_in_threads(
+        &self,
+        memory_blocks: &mut [Block],
+        pass: usize,
+        slice: usize,
+    ) {
+        let segment_length = self.params.segment_length();
+        let lanes = self.params.lanes();
+
+        let mut segments = Vec::with_capacity(lanes);
+        let mut shared = Vec::with_capacity(lanes * SYNC_POINTS);
+        for (i, segment) in memory_blocks.chunks_exact_mut(segment_length).enumerate() {
+            if i % SYNC_POINTS == slice {
+                segments.push(segment);
+                shared.push(&[][..]);
+            } else {
+                shared.push(&*segment);
+            }
+        }
+
+        let shared = &shared;
+        thread::scope(|scope| {
+            for (lane, segment) in segments.into_iter().enumerate() {
+                scope.spawn(move || {
+                    let mut view = SegmentView {
+                        segment,
+                        offset: (lane * SYNC_POINTS + slice) * segment_length,
+                        segment_length,
+                        shared,
+                    };
+                    self.fill_segment(&mut view, pass, slice, lane);
+                });
+            }
+        });
+    }
+}
@@ -9,6 +9,11 @@ pub enum Version {
     /// Version 16 (0x10 in hex)
     ///
     /// Performs overwrite internally
+    ///
+    /// This is the version of the 1.2.1 reference implementation, supported
+    /// to verify legacy `v=16` hashes. Hash strings without any `v=` field
+    /// are hashed with [`Version::default`], i.e. [`Version::V0x13`], rather
+    /// than with this version.
     V0x10 = 0x10,
 
     /// Version 19 (0x13 in hex, default)
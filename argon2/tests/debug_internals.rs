@@ -0,0 +1,79 @@
+//! Intermediate memory states exposed by the `debug-internals` feature.
+
+#![cfg(feature = "debug-internals")]
+
+use argon2::{Algorithm, Argon2, AssociatedData, Block, ParamsBuilder, Version};
+use hex_literal::hex;
+
+/// Argon2id version number 19 KAT of RFC 9106 Section 5.3.
+const EXPECTED_TAG: [u8; 32] = hex!(
+    "
+    0d 64 0d f5 8d 78 76 6c 08 c0 37 a3 4a 8b 53 c9
+    d0 1e f0 45 2d 75 b6 5e b5 25 20 e9 6b 01 e6 59
+    "
+);
+
+/// First two words of block 0 after each pass, as printed by RFC 9106
+/// Section 5.3.
+const BLOCK_0_WORDS: [[u64; 2]; 3] = [
+    [0x6b2e09f10671bd43, 0xf69f5c27918a21be],
+    [0x3653ec9d01583df9, 0x69ef53a72d1e1fd3],
+    [0x942363968ce597a4, 0xa22448c0bdad5760],
+];
+
+fn example_ctx(secret: &[u8]) -> Argon2<'_> {
+    let params = ParamsBuilder::new()
+        .m_cost(32)
+        .t_cost(3)
+        .p_cost(4)
+        .data(AssociatedData::new(&[0x04; 12]).unwrap())
+        .build()
+        .unwrap();
+    Argon2::new_with_secret(secret, Algorithm::Argon2id, Version::V0x13, params).unwrap()
+}
+
+#[test]
+fn on_pass_sees_rfc9106_blocks() {
+    let ctx = example_ctx(&[0x03; 8]);
+    let mut memory = [Block::default(); 32];
+    let mut out = [0u8; 32];
+    let mut words = vec![];
+    ctx.hash_password_into_with_on_pass(
+        &[0x01; 32],
+        &[0x02; 16],
+        &mut out,
+        &mut memory,
+        |blocks| {
+            assert_eq!(blocks.len(), 32);
+            words.push([blocks[0].as_ref()[0], blocks[0].as_ref()[1]]);
+        },
+    )
+    .unwrap();
+
+    assert_eq!(words, BLOCK_0_WORDS);
+    assert_eq!(out, EXPECTED_TAG);
+}
+
+#[test]
+fn last_pass_is_final_memory() {
+    let ctx = example_ctx(&[0x03; 8]);
+    let mut last = None;
+    let mut out = [0u8; 32];
+    ctx.hash_password_into_with_on_pass(
+        &[0x01; 32],
+        &[0x02; 16],
+        &mut out,
+        [Block::default(); 32],
+        |blocks| last = Some(blocks.to_vec()),
+    )
+    .unwrap();
+
+    let mut memory = [Block::default(); 32];
+    ctx.hash_password_into_with_memory(&[0x01; 32], &[0x02; 16], &mut out, &mut memory)
+        .unwrap();
+    let last = last.unwrap();
+    assert!(last
+        .iter()
+        .zip(&memory)
+        .all(|(a, b)| a.as_ref() == b.as_ref()));
+}
@@ -1,8 +1,8 @@
 //! Argon2 Known Answer Tests (KAT).
 //!
-//! Taken from the Argon2 reference implementation as well as
-//! `draft-irtf-cfrg-argon2-12` Section 5:
-//! <https://datatracker.ietf.org/doc/draft-irtf-cfrg-argon2/>
+//! Taken from the Argon2 reference implementation as well as RFC 9106
+//! Section 5, whose vectors are those of `draft-irtf-cfrg-argon2-12`:
+//! <https://www.rfc-editor.org/rfc/rfc9106#section-5>
 
 #![cfg(all(feature = "alloc", feature = "password-hash"))]
 
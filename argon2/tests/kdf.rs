@@ -0,0 +1,76 @@
+//! Subkey derivation, checked against keyed BLAKE2b with the salt and
+//! personalization of libsodium's `crypto_kdf`, as computed by Python's
+//! `hashlib.blake2b`.
+
+use argon2::kdf::{self, Error};
+use hex_literal::hex;
+
+const MASTER_KEY: [u8; 32] =
+    hex!("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f");
+
+#[test]
+fn subkeys() {
+    let mut subkey = [0u8; 16];
+    kdf::derive_subkey(&MASTER_KEY, "KDF test", 0, &mut subkey).unwrap();
+    assert_eq!(subkey, hex!("e9136a52b9690eb4df4e9665e819a6d3"));
+
+    let mut subkey = [0u8; 32];
+    kdf::derive_subkey(&MASTER_KEY, "KDF test", 1, &mut subkey).unwrap();
+    assert_eq!(
+        subkey,
+        hex!("13fea52bb8cba063f3ed93de27ed07e06d8c6367474e6ae4c9282913ac3c3a03")
+    );
+
+    let mut subkey = [0u8; 64];
+    kdf::derive_subkey(&MASTER_KEY, "KDF test", 2, &mut subkey).unwrap();
+    assert_eq!(
+        subkey,
+        hex!(
+            "
+            1944da61ff18dc2028c3578ac85be904931b83860896598f62468f1cb5471c6a
+            344c945dbc62c9aaf70feb62472d17775ea5db6ed5494c68b7a9a59761f39614
+            "
+        )
+    );
+}
+
+#[test]
+fn short_context_and_long_key() {
+    let master_key: Vec<u8> = (0..64).collect();
+    let mut subkey = [0u8; 48];
+    kdf::derive_subkey(&master_key, "ctx", u64::MAX, &mut subkey).unwrap();
+    assert_eq!(
+        subkey,
+        hex!(
+            "
+            5048fcaa60797bdbe3a4c15591521717e4ba91984f703b182fb42fe43097d007
+            9d8f15fb98e2d86a5077f0e100e034c2
+            "
+        )
+    );
+}
+
+#[test]
+fn invalid_lengths() {
+    let mut subkey = [0u8; 32];
+    assert_eq!(
+        kdf::derive_subkey(&[0; 15], "KDF test", 0, &mut subkey),
+        Err(Error::KeyTooShort)
+    );
+    assert_eq!(
+        kdf::derive_subkey(&[0; 65], "KDF test", 0, &mut subkey),
+        Err(Error::KeyTooLong)
+    );
+    assert_eq!(
+        kdf::derive_subkey(&MASTER_KEY, "KDF tests", 0, &mut subkey),
+        Err(Error::ContextTooLong)
+    );
+    assert_eq!(
+        kdf::derive_subkey(&MASTER_KEY, "KDF test", 0, &mut [0; 15]),
+        Err(Error::SubkeyTooShort)
+    );
+    assert_eq!(
+        kdf::derive_subkey(&MASTER_KEY, "KDF test", 0, &mut [0; 65]),
+        Err(Error::SubkeyTooLong)
+    );
+}
@@ -0,0 +1,63 @@
+//! Hashing with caller-provided memory, which doesn't need the `alloc`
+//! feature.
+
+use argon2::{Algorithm, Argon2, AssociatedData, Block, Error, ParamsBuilder, Version};
+use hex_literal::hex;
+
+/// Argon2id version number 19 KAT of RFC 9106 Section 5.3.
+const EXPECTED_TAG: [u8; 32] = hex!(
+    "
+    0d 64 0d f5 8d 78 76 6c 08 c0 37 a3 4a 8b 53 c9
+    d0 1e f0 45 2d 75 b6 5e b5 25 20 e9 6b 01 e6 59
+    "
+);
+
+fn example_ctx(secret: &[u8]) -> Argon2<'_> {
+    let params = ParamsBuilder::new()
+        .m_cost(32)
+        .t_cost(3)
+        .p_cost(4)
+        .data(AssociatedData::new(&[0x04; 12]).unwrap())
+        .build()
+        .unwrap();
+    Argon2::new_with_secret(secret, Algorithm::Argon2id, Version::V0x13, params).unwrap()
+}
+
+#[test]
+fn stack_memory() {
+    let ctx = example_ctx(&[0x03; 8]);
+    assert_eq!(ctx.params().block_count(), 32);
+
+    let mut memory = [Block::default(); 32];
+    let mut out = [0u8; 32];
+    ctx.hash_password_into_with_memory(&[0x01; 32], &[0x02; 16], &mut out, &mut memory)
+        .unwrap();
+    assert_eq!(out, EXPECTED_TAG);
+
+    // Reusing the memory gives the same tag
+    let mut out = [0u8; 32];
+    ctx.hash_password_into_with_memory(&[0x01; 32], &[0x02; 16], &mut out, &mut memory)
+        .unwrap();
+    assert_eq!(out, EXPECTED_TAG);
+}
+
+#[test]
+fn extra_memory_is_unused() {
+    let ctx = example_ctx(&[0x03; 8]);
+    let mut memory = [Block::default(); 40];
+    let mut out = [0u8; 32];
+    ctx.hash_password_into_with_memory(&[0x01; 32], &[0x02; 16], &mut out, &mut memory)
+        .unwrap();
+    assert_eq!(out, EXPECTED_TAG);
+}
+
+#[test]
+fn too_little_memory() {
+    let ctx = example_ctx(&[0x03; 8]);
+    let mut memory = [Block::default(); 31];
+    let mut out = [0u8; 32];
+    assert_eq!(
+        ctx.hash_password_into_with_memory(&[0x01; 32], &[0x02; 16], &mut out, &mut memory),
+        Err(Error::MemoryTooLittle)
+    );
+}
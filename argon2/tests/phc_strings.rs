@@ -10,7 +10,7 @@ use argon2::{
 };
 use password_hash::{
     errors::{Error, InvalidValue},
-    SaltString,
+    Output, SaltString,
 };
 
 /// Valid password
@@ -54,6 +54,89 @@ fn rejects_incorrect_password() {
     }
 }
 
+#[test]
+fn secret_and_associated_data_round_trip() {
+    let params = ParamsBuilder::new()
+        .m_cost(256)
+        .t_cost(2)
+        .p_cost(2)
+        .data(AssociatedData::new(b"application").unwrap())
+        .build()
+        .unwrap();
+    let pepper = b"pepper";
+    let ctx = Argon2::new_with_secret(pepper, Algorithm::Argon2id, Version::V0x13, params).unwrap();
+
+    let salt = SaltString::encode_b64(b"somesalt").unwrap();
+    let hash_string = ctx
+        .hash_password(VALID_PASSWORD, &salt)
+        .unwrap()
+        .to_string();
+    assert!(hash_string.starts_with("$argon2id$v=19$m=256,t=2,p=2,data=YXBwbGljYXRpb24$"));
+
+    // Parameters, including the associated data, are read back from the
+    // string, whereas the secret has to be provided again
+    let hash = PasswordHash::new(&hash_string).unwrap();
+    let other_params = ParamsBuilder::new().build().unwrap();
+    let verifier = Argon2::new_with_secret(
+        pepper,
+        Algorithm::default(),
+        Version::default(),
+        other_params,
+    )
+    .unwrap();
+    assert_eq!(verifier.verify_password(VALID_PASSWORD, &hash), Ok(()));
+    assert_eq!(
+        verifier.verify_password(INVALID_PASSWORD, &hash),
+        Err(Error::Password)
+    );
+
+    assert_eq!(
+        Argon2::default().verify_password(VALID_PASSWORD, &hash),
+        Err(Error::Password)
+    );
+    let wrong_pepper = Argon2::new_with_secret(
+        b"salt",
+        Algorithm::Argon2id,
+        Version::V0x13,
+        ParamsBuilder::new().build().unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        wrong_pepper.verify_password(VALID_PASSWORD, &hash),
+        Err(Error::Password)
+    );
+}
+
+#[test]
+fn verify_password_timing_safe() {
+    let argon2 = Argon2::default();
+    let hash = PasswordHash::new(VALID_PASSWORD_HASHES[2]).unwrap();
+    assert_eq!(
+        argon2.verify_password_timing_safe(VALID_PASSWORD, &hash),
+        Ok(())
+    );
+    assert_eq!(
+        argon2.verify_password_timing_safe(INVALID_PASSWORD, &hash),
+        Err(Error::Password)
+    );
+
+    // Outputs differing in their first or last byte only are rejected
+    let output = hash.hash.unwrap();
+    for i in [0, output.len() - 1] {
+        let mut bytes = output.as_bytes().to_vec();
+        bytes[i] ^= 1;
+
+        let tampered = PasswordHash {
+            hash: Some(Output::new(&bytes).unwrap()),
+            ..hash.clone()
+        };
+        assert_eq!(
+            argon2.verify_password_timing_safe(VALID_PASSWORD, &tampered),
+            Err(Error::Password)
+        );
+    }
+}
+
 // Test PHC string format according to spec
 // see: https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md#argon2-encoding
 
@@ -8,7 +8,17 @@ use password_hash::{errors::InvalidValue, ParamsString, PasswordHash};
 
 /// Balloon password hash parameters.
 ///
-/// These are parameters which can be encoded into a PHC hash string.
+/// These are parameters which can be encoded into a PHC hash string:
+///
+/// ```text
+/// $balloon$v=1$s=<S_COST>,t=<T_COST>,p=<P_COST>$<SALT>$<HASH>
+/// ```
+///
+/// `s`, `t` and `p` are decimal integers of at least 1, and default to
+/// [`Params::DEFAULT_S_COST`], [`Params::DEFAULT_T_COST`] and
+/// [`Params::DEFAULT_P_COST`] when left out; `$balloon-m$` strings have the
+/// same params. The digest isn't encoded: a hash must be verified with the
+/// digest it was computed with.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Params {
     /// Space cost, expressed in number of blocks.
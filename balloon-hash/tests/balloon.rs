@@ -106,3 +106,24 @@ fn hash_simple_retains_configured_params() {
         );
     }
 }
+
+#[cfg(all(feature = "password-hash", feature = "alloc"))]
+#[test]
+fn phc_string_round_trip() {
+    use balloon_hash::{PasswordHash, PasswordHasher, PasswordVerifier, Salt};
+    use sha2::Sha256;
+
+    let params = Params::new(16, 2, 1).unwrap();
+    let hasher = Balloon::<Sha256>::new(Algorithm::Balloon, params, None);
+    let salt = Salt::from_b64("examplesaltvalue").unwrap();
+    let hash = hasher.hash_password(b"hunter42", salt).unwrap().to_string();
+    assert!(hash.starts_with("$balloon$v=1$s=16,t=2,p=1$examplesaltvalue$"));
+
+    let parsed = PasswordHash::new(&hash).unwrap();
+    assert_eq!(parsed.to_string(), hash);
+    assert_eq!(Params::try_from(&parsed).unwrap(), params);
+
+    let verifier = Balloon::<Sha256>::default();
+    assert!(verifier.verify_password(b"hunter42", &parsed).is_ok());
+    assert!(verifier.verify_password(b"hunter43", &parsed).is_err());
+}
@@ -107,3 +107,27 @@ fn test_vectors() {
         );
     }
 }
+
+#[cfg(all(feature = "password-hash", feature = "alloc"))]
+#[test]
+fn phc_string_round_trip() {
+    use balloon_hash::{PasswordHash, PasswordHasher, PasswordVerifier, Salt};
+    use sha2::Sha256;
+
+    // First test vector, salt "examplesalt"
+    let params = Params::new(1024, 3, 4).unwrap();
+    let hasher = Balloon::<Sha256>::new(Algorithm::BalloonM, params, None);
+    let salt = Salt::from_b64("ZXhhbXBsZXNhbHQ").unwrap();
+    let hash = hasher.hash_password(b"hunter42", salt).unwrap();
+    assert_eq!(hash.hash.unwrap().as_bytes(), TEST_VECTORS[0].output);
+
+    let hash = hash.to_string();
+    assert!(hash.starts_with("$balloon-m$v=1$s=1024,t=3,p=4$ZXhhbXBsZXNhbHQ$"));
+
+    let parsed = PasswordHash::new(&hash).unwrap();
+    assert_eq!(Params::try_from(&parsed).unwrap(), params);
+
+    let verifier = Balloon::<Sha256>::default();
+    assert!(verifier.verify_password(b"hunter42", &parsed).is_ok());
+    assert!(verifier.verify_password(b"hunter43", &parsed).is_err());
+}
@@ -3,26 +3,54 @@ use core::fmt;
 /// `bcrypt_pbkdf` error
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Error {
-    /// An input parameter has an invalid length.
+    /// The passphrase or the salt is empty.
     InvalidParamLen,
+    /// The salt is longer than [`SALT_MAX_LEN`](crate::SALT_MAX_LEN).
+    SaltTooLong,
     /// An invalid number of rounds was specified.
     InvalidRounds,
-    /// The output parameter has an invalid length.
+    /// The output is empty.
     InvalidOutputLen,
+    /// The output is longer than [`OUTPUT_MAX_LEN`](crate::OUTPUT_MAX_LEN).
+    OutputTooLong,
     /// The manually provided memory was not long enough.
     InvalidMemoryLen,
+    /// An OpenSSH `kdfoptions` blob ends in the middle of a field.
+    TruncatedKdfOptions,
+    /// An OpenSSH `kdfoptions` blob has bytes after its rounds.
+    TrailingKdfOptions,
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Error::InvalidParamLen => write!(f, "Invalid parameter length"),
+            Error::SaltTooLong => write!(f, "Salt is too long"),
             Error::InvalidRounds => write!(f, "Invalid number of rounds"),
             Error::InvalidOutputLen => write!(f, "Invalid output length"),
+            Error::OutputTooLong => write!(f, "Output is too long"),
             Error::InvalidMemoryLen => write!(f, "Invalid memory length"),
+            Error::TruncatedKdfOptions => write!(f, "Truncated kdfoptions"),
+            Error::TrailingKdfOptions => write!(f, "Trailing data after kdfoptions"),
         }
     }
 }
 
 #[cfg(feature = "std")]
 impl std::error::Error for Error {}
+
+#[cfg(feature = "simple")]
+impl From<Error> for password_hash::Error {
+    fn from(err: Error) -> password_hash::Error {
+        use password_hash::errors::InvalidValue;
+
+        match err {
+            Error::InvalidParamLen => password_hash::Error::Password,
+            Error::SaltTooLong => password_hash::Error::SaltInvalid(InvalidValue::TooLong),
+            Error::InvalidRounds => InvalidValue::TooShort.param_error(),
+            Error::InvalidOutputLen => InvalidValue::TooShort.param_error(),
+            Error::OutputTooLong => InvalidValue::TooLong.param_error(),
+            _ => password_hash::Error::Crypto,
+        }
+    }
+}
@@ -5,6 +5,7 @@
 //! [OpenSSH]: https://flak.tedunangst.com/post/new-openssh-key-format-and-bcrypt-pbkdf
 
 #![no_std]
+#![cfg_attr(docsrs, feature(doc_cfg))]
 #![doc = include_str!("../README.md")]
 #![doc(
     html_logo_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg",
@@ -17,19 +18,22 @@ extern crate alloc;
 extern crate std;
 
 mod errors;
+pub mod openssh;
+#[cfg(feature = "simple")]
+mod simple;
 
 pub use errors::Error;
 
-use blowfish::Blowfish;
-use sha2::{
-    digest::{
-        crypto_common::{Key, KeyInit, KeySizeUser},
-        generic_array::typenum::U32,
-        FixedOutput, MacMarker, Output, OutputSizeUser, Update,
-    },
-    Digest, Sha512,
+#[cfg(feature = "simple")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub use {
+    password_hash,
+    simple::{BcryptPbkdfHasher, Params, ALG_ID},
 };
 
+use blowfish::Blowfish;
+use sha2::{Digest, Sha512};
+
 #[cfg(feature = "zeroize")]
 use zeroize::Zeroize;
 
@@ -37,20 +41,62 @@ const BHASH_WORDS: usize = 8;
 const BHASH_OUTPUT_SIZE: usize = BHASH_WORDS * 4;
 const BHASH_SEED: &[u8; BHASH_OUTPUT_SIZE] = b"OxychromaticBlowfishSwatDynamite";
 
-fn bhash(sha2_pass: &Output<Sha512>, sha2_salt: &Output<Sha512>) -> Output<Bhash> {
+/// [`BHASH_SEED`] as the big-endian words encrypted by [`bcrypt_hash`].
+const BHASH_SEED_WORDS: [u32; BHASH_WORDS] = {
+    let mut words = [0u32; BHASH_WORDS];
+    let mut i = 0;
+    while i < BHASH_WORDS {
+        words[i] = u32::from_be_bytes([
+            BHASH_SEED[i * 4],
+            BHASH_SEED[i * 4 + 1],
+            BHASH_SEED[i * 4 + 2],
+            BHASH_SEED[i * 4 + 3],
+        ]);
+        i += 1;
+    }
+    words
+};
+
+/// Longest salt accepted by [`bcrypt_pbkdf`], 1 MiB as in OpenBSD.
+///
+/// The big-endian `u32` block index is appended to the salt before it's
+/// hashed, so that no length close to the limits of SHA-512 or of the
+/// caller's memory is ever reached.
+pub const SALT_MAX_LEN: usize = 1 << 20;
+
+/// Longest output of [`bcrypt_pbkdf`], 1024 bytes as in OpenBSD: the
+/// output is striped over at most 32 blocks of 32 bytes.
+///
+/// Longer outputs would still be well-defined, with more blocks, but
+/// OpenBSD's `bcrypt_pbkdf` and so OpenSSH reject them: they're rejected
+/// here too, so that every key derived by this crate can be derived there.
+pub const OUTPUT_MAX_LEN: usize = BHASH_OUTPUT_SIZE * BHASH_OUTPUT_SIZE;
+
+/// The bcrypt_hash function, the PRF of [`bcrypt_pbkdf`].
+///
+/// It is bcrypt with both the key and the salt expanded into the Blowfish
+/// state and `"OxychromaticBlowfishSwatDynamite"` encrypted 64 times,
+/// written to `out` as little-endian words.
+///
+/// # Arguments
+/// - `sha_pass` - The SHA-512 digest of the passphrase.
+/// - `sha_salt` - The SHA-512 digest of the salt: in [`bcrypt_pbkdf`], of
+///   the salt followed by the big-endian block index, then of the previous
+///   output.
+/// - `out` - The 32-byte hash is returned in this array.
+///
+/// Any 64-byte inputs are accepted, but only SHA-512 digests give the
+/// values of [`bcrypt_pbkdf`] and OpenBSD's `bcrypt_hash`.
+pub fn bcrypt_hash(sha_pass: &[u8; 64], sha_salt: &[u8; 64], out: &mut [u8; BHASH_OUTPUT_SIZE]) {
     let mut blowfish = Blowfish::bc_init_state();
 
-    blowfish.salted_expand_key(sha2_salt, sha2_pass);
+    blowfish.salted_expand_key(sha_salt, sha_pass);
     for _ in 0..64 {
-        blowfish.bc_expand_key(sha2_salt);
-        blowfish.bc_expand_key(sha2_pass);
-    }
-
-    let mut cdata = [0u32; BHASH_WORDS];
-    for i in 0..BHASH_WORDS {
-        cdata[i] = u32::from_be_bytes(BHASH_SEED[i * 4..(i + 1) * 4].try_into().unwrap());
+        blowfish.bc_expand_key(sha_salt);
+        blowfish.bc_expand_key(sha_pass);
     }
 
+    let mut cdata = BHASH_SEED_WORDS;
     for _ in 0..64 {
         for i in (0..BHASH_WORDS).step_by(2) {
             let [l, r] = blowfish.bc_encrypt([cdata[i], cdata[i + 1]]);
@@ -59,60 +105,31 @@ fn bhash(sha2_pass: &Output<Sha512>, sha2_salt: &Output<Sha512>) -> Output<Bhash
         }
     }
 
-    let mut output = Output::<Bhash>::default();
     for i in 0..BHASH_WORDS {
-        output[i * 4..(i + 1) * 4].copy_from_slice(&cdata[i].to_le_bytes());
+        out[i * 4..(i + 1) * 4].copy_from_slice(&cdata[i].to_le_bytes());
     }
 
-    output
-}
-
-#[derive(Clone)]
-struct Bhash {
-    sha2_pass: Output<Sha512>,
-    salt: Sha512,
-}
-
-impl MacMarker for Bhash {}
-
-impl KeySizeUser for Bhash {
-    type KeySize = <Sha512 as OutputSizeUser>::OutputSize;
-}
-
-impl KeyInit for Bhash {
-    fn new(key: &Key<Self>) -> Self {
-        Bhash {
-            sha2_pass: *key,
-            salt: Sha512::default(),
-        }
-    }
-}
-
-impl Update for Bhash {
-    fn update(&mut self, data: &[u8]) {
-        Update::update(&mut self.salt, data);
-    }
-}
-
-impl OutputSizeUser for Bhash {
-    type OutputSize = U32;
-}
-
-impl FixedOutput for Bhash {
-    fn finalize_into(mut self, out: &mut Output<Self>) {
-        *out = bhash(&self.sha2_pass, &self.salt.finalize_reset());
-    }
-}
-
-#[cfg(feature = "zeroize")]
-impl Drop for Bhash {
-    fn drop(&mut self) {
-        self.sha2_pass.zeroize();
-    }
+    #[cfg(feature = "zeroize")]
+    cdata.zeroize();
 }
 
 /// The bcrypt_pbkdf function.
 ///
+/// Invalid inputs, such as those of a corrupted OpenSSH key file, are
+/// reported as errors: this function never panics.
+///
+/// It doesn't allocate either: each 32-byte block of PBKDF2 with
+/// [`bcrypt_hash`] as the PRF is computed on the stack and written to its
+/// stripe of `output`. With the `parallel` feature, the blocks of keys
+/// longer than 32 bytes are computed concurrently.
+///
+/// # Limits
+/// - The passphrase can't be empty; it has no maximum length, being hashed
+///   with SHA-512 first.
+/// - The salt can't be empty or longer than [`SALT_MAX_LEN`] bytes.
+/// - The rounds can't be 0.
+/// - The output can't be empty or longer than [`OUTPUT_MAX_LEN`] bytes.
+///
 /// # Arguments
 /// - `passphrase` - The passphrase to process.
 /// - `salt` - The salt value to use as a byte vector.
@@ -122,47 +139,239 @@ impl Drop for Bhash {
 /// # Returns
 /// - `Ok(())` if everything is fine.
 /// - `Err(Error::InvalidParamLen)` if `passphrase.is_empty() || salt.is_empty()`.
+/// - `Err(Error::SaltTooLong)` if `salt.len() > SALT_MAX_LEN`.
 /// - `Err(Error::InvalidRounds)` if `rounds == 0`.
-/// - `Err(Error::InvalidOutputLen)` if `output.is_empty() || output.len() > 1024`.
-#[cfg(feature = "alloc")]
+/// - `Err(Error::InvalidOutputLen)` if `output.is_empty()`.
+/// - `Err(Error::OutputTooLong)` if `output.len() > OUTPUT_MAX_LEN`.
 pub fn bcrypt_pbkdf(
     passphrase: impl AsRef<[u8]>,
     salt: &[u8],
     rounds: u32,
     output: &mut [u8],
 ) -> Result<(), Error> {
-    /// number of strides which will be processed on stack
-    const STACK_STRIDE: usize = 8;
+    BcryptPbkdf::new()
+        .update_passphrase(passphrase)
+        .derive(salt, rounds, output)
+}
+
+/// The bcrypt_pbkdf function, with the passphrase given in pieces.
+///
+/// The passphrase is only ever hashed with SHA-512, so it can be fed to
+/// that hash incrementally, e.g. as it is read from a pipe, instead of
+/// being assembled into one buffer first:
+///
+/// ```
+/// use bcrypt_pbkdf::{bcrypt_pbkdf, BcryptPbkdf};
+///
+/// let mut key = [0u8; 48];
+/// BcryptPbkdf::new()
+///     .update_passphrase("pass")
+///     .update_passphrase("word")
+///     .derive(b"salt", 4, &mut key)?;
+///
+/// let mut expected = [0u8; 48];
+/// bcrypt_pbkdf("password", b"salt", 4, &mut expected)?;
+/// assert_eq!(key, expected);
+/// # Ok::<(), bcrypt_pbkdf::Error>(())
+/// ```
+#[derive(Clone, Default)]
+pub struct BcryptPbkdf {
+    sha2_pass: Sha512,
+    has_passphrase: bool,
+}
+
+impl BcryptPbkdf {
+    /// Start with an empty passphrase.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `chunk` to the passphrase.
+    pub fn update_passphrase(&mut self, chunk: impl AsRef<[u8]>) -> &mut Self {
+        let chunk = chunk.as_ref();
+        Digest::update(&mut self.sha2_pass, chunk);
+        self.has_passphrase |= !chunk.is_empty();
+        self
+    }
+
+    /// Derive a key from the passphrase given so far, see [`bcrypt_pbkdf`].
+    ///
+    /// # Returns
+    /// The same results as [`bcrypt_pbkdf`], with
+    /// `Err(Error::InvalidParamLen)` if no byte of the passphrase was given.
+    pub fn derive(&self, salt: &[u8], rounds: u32, output: &mut [u8]) -> Result<(), Error> {
+        check_params(self.has_passphrase, salt, rounds, output)?;
+
+        let mut sha2_pass = [0u8; 64];
+        sha2_pass.copy_from_slice(&self.sha2_pass.clone().finalize());
+        let sha2_salt = Sha512::new_with_prefix(salt);
+
+        #[cfg(not(feature = "parallel"))]
+        derive_serial(&sha2_pass, &sha2_salt, rounds, output);
+        #[cfg(feature = "parallel")]
+        derive_parallel(&sha2_pass, &sha2_salt, rounds, output);
+
+        #[cfg(feature = "zeroize")]
+        sha2_pass.zeroize();
+
+        Ok(())
+    }
+}
+
+/// Compute the blocks of the key one after the other, writing each one to
+/// its stripe of `output`.
+///
+/// `sha2_salt` is SHA-512 having hashed the salt, so that the salt is hashed
+/// only once, not once per block.
+fn derive_serial(sha2_pass: &[u8; 64], sha2_salt: &Sha512, rounds: u32, output: &mut [u8]) {
+    let stride = (output.len() + BHASH_OUTPUT_SIZE - 1) / BHASH_OUTPUT_SIZE;
+
+    let mut block = [0u8; BHASH_OUTPUT_SIZE];
+    for chunk_num in 0..stride {
+        pbkdf2_block(
+            sha2_pass,
+            sha2_salt,
+            rounds,
+            chunk_num as u32 + 1,
+            &mut block,
+        );
+        stripe(&block, chunk_num, stride, output);
+    }
+
+    #[cfg(feature = "zeroize")]
+    block.zeroize();
+}
+
+/// Compute the blocks of the key concurrently, then write them to their
+/// stripes of `output`.
+///
+/// Keys of a single block are computed by [`derive_serial`].
+#[cfg(feature = "parallel")]
+fn derive_parallel(sha2_pass: &[u8; 64], sha2_salt: &Sha512, rounds: u32, output: &mut [u8]) {
+    use rayon::prelude::*;
 
-    // Allocate a Vec large enough to hold the output we require.
     let stride = (output.len() + BHASH_OUTPUT_SIZE - 1) / BHASH_OUTPUT_SIZE;
+    if stride == 1 {
+        return derive_serial(sha2_pass, sha2_salt, rounds, output);
+    }
 
-    let mut vec_buf;
-    let mut stack_buf = [0u8; STACK_STRIDE * BHASH_OUTPUT_SIZE];
-    let generated = if stride > STACK_STRIDE {
-        vec_buf = alloc::vec![0u8; stride * BHASH_OUTPUT_SIZE];
-        &mut vec_buf[..]
+    // The stripes are interleaved, so the blocks can't be written to
+    // `output` directly; there are at most 32 of them.
+    let mut blocks = [[0u8; BHASH_OUTPUT_SIZE]; BHASH_OUTPUT_SIZE];
+    blocks[..stride]
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(chunk_num, block)| {
+            pbkdf2_block(sha2_pass, sha2_salt, rounds, chunk_num as u32 + 1, block);
+        });
+
+    for (chunk_num, block) in blocks[..stride].iter().enumerate() {
+        stripe(block, chunk_num, stride, output);
+    }
+
+    #[cfg(feature = "zeroize")]
+    blocks.zeroize();
+}
+
+/// Apply the bcrypt_pbkdf non-linear transformation on the output: byte `i`
+/// of block `chunk_num` is byte `i * stride + chunk_num` of the key.
+fn stripe(block: &[u8; BHASH_OUTPUT_SIZE], chunk_num: usize, stride: usize, output: &mut [u8]) {
+    for (out_byte, byte) in output
+        .iter_mut()
+        .skip(chunk_num)
+        .step_by(stride)
+        .zip(block.iter())
+    {
+        *out_byte = *byte;
+    }
+}
+
+/// Validate inputs in same way as OpenSSH implementation.
+fn check_params(
+    has_passphrase: bool,
+    salt: &[u8],
+    rounds: u32,
+    output: &[u8],
+) -> Result<(), Error> {
+    if !has_passphrase || salt.is_empty() {
+        Err(Error::InvalidParamLen)
+    } else if salt.len() > SALT_MAX_LEN {
+        Err(Error::SaltTooLong)
+    } else if rounds == 0 {
+        Err(Error::InvalidRounds)
+    } else if output.is_empty() {
+        Err(Error::InvalidOutputLen)
+    } else if output.len() > OUTPUT_MAX_LEN {
+        Err(Error::OutputTooLong)
     } else {
-        &mut stack_buf[..stride * BHASH_OUTPUT_SIZE]
-    };
+        Ok(())
+    }
+}
+
+/// Block `index` of PBKDF2 with [`bcrypt_hash`] as the PRF, keyed by the
+/// SHA-512 digest of the passphrase.
+///
+/// The work shared by all blocks is done once by the caller: the digest of
+/// the passphrase, and the hashing of the salt into `sha2_salt`, which only
+/// the index is appended to. The constants of [`bcrypt_hash`], the initial
+/// Blowfish state and the seed words, are shared too.
+///
+/// The EksBlowfish key schedule isn't: its first step XORs `sha2_pass` into
+/// the P-array, but every encryption after it, which is all of its cost,
+/// mixes in the salt digest, different for every block and round. So each
+/// call of [`bcrypt_hash`] expands a fresh state, and keys of several
+/// blocks are only faster with the `parallel` feature.
+fn pbkdf2_block(
+    sha2_pass: &[u8; 64],
+    sha2_salt: &Sha512,
+    rounds: u32,
+    index: u32,
+    block: &mut [u8; BHASH_OUTPUT_SIZE],
+) {
+    let mut sha2_salt: [u8; 64] = sha2_salt
+        .clone()
+        .chain_update(index.to_be_bytes())
+        .finalize()
+        .into();
+    let mut tmp = [0u8; BHASH_OUTPUT_SIZE];
+    bcrypt_hash(sha2_pass, &sha2_salt, &mut tmp);
+    *block = tmp;
+
+    for _ in 1..rounds {
+        sha2_salt = Sha512::digest(tmp).into();
+        bcrypt_hash(sha2_pass, &sha2_salt, &mut tmp);
+        for (out, byte) in block.iter_mut().zip(tmp.iter()) {
+            *out ^= byte;
+        }
+    }
 
-    bcrypt_pbkdf_with_memory(passphrase, salt, rounds, output, generated)
+    #[cfg(feature = "zeroize")]
+    {
+        sha2_salt.zeroize();
+        tmp.zeroize();
+    }
 }
 
-/// Like [`bcrypt_pbkdf`], but usable on "heapless" targets.
+/// Like [`bcrypt_pbkdf`], but with caller-provided working memory.
+///
+/// [`bcrypt_pbkdf`] doesn't allocate anymore, so `memory` is only checked
+/// to be long enough, as it was before: this function is kept for
+/// compatibility.
 ///
 /// # Arguments
 /// - `passphrase` - The passphrase to process.
 /// - `salt` - The salt value to use as a byte vector.
 /// - `rounds` - The number of rounds to apply.
 /// - `output` - The resulting derived key is returned in this byte vector.
-/// - `memory` - Buffer space used for internal computation.
+/// - `memory` - Buffer space formerly used for internal computation.
 ///
 /// # Returns
 /// - `Ok(())` if everything is fine.
 /// - `Err(Error::InvalidParamLen)` if `passphrase.is_empty() || salt.is_empty()`.
+/// - `Err(Error::SaltTooLong)` if `salt.len() > SALT_MAX_LEN`.
 /// - `Err(Error::InvalidRounds)` if `rounds == 0`.
-/// - `Err(Error::InvalidOutputLen)` if `output.is_empty() || output.len() > 1024`.
+/// - `Err(Error::InvalidOutputLen)` if `output.is_empty()`.
+/// - `Err(Error::OutputTooLong)` if `output.len() > OUTPUT_MAX_LEN`.
 /// - `Err(Error::InvalidMemoryLen)` if `memory.len() < (output.len() + 32 - 1) / 32 * 32`, i.e.
 ///   `output.len()` rounded up to the nearest multiple of 32.
 pub fn bcrypt_pbkdf_with_memory(
@@ -172,95 +381,33 @@ pub fn bcrypt_pbkdf_with_memory(
     output: &mut [u8],
     memory: &mut [u8],
 ) -> Result<(), Error> {
-    let stride = (output.len() + BHASH_OUTPUT_SIZE - 1) / BHASH_OUTPUT_SIZE;
-
-    // Validate inputs in same way as OpenSSH implementation
     let passphrase = passphrase.as_ref();
-    if passphrase.is_empty() || salt.is_empty() {
-        return Err(errors::Error::InvalidParamLen);
-    } else if rounds == 0 {
-        return Err(errors::Error::InvalidRounds);
-    } else if output.is_empty() || output.len() > BHASH_OUTPUT_SIZE * BHASH_OUTPUT_SIZE {
-        return Err(errors::Error::InvalidOutputLen);
-    } else if memory.len() < stride * BHASH_OUTPUT_SIZE {
-        return Err(errors::Error::InvalidMemoryLen);
-    }
+    check_params(!passphrase.is_empty(), salt, rounds, output)?;
 
-    // Run the regular PBKDF2 algorithm with bhash as the PRF.
-    pbkdf2::pbkdf2::<Bhash>(&Sha512::digest(passphrase), salt, rounds, memory)
-        .expect("Bhash can be initialized with any key length");
-
-    // Apply the bcrypt_pbkdf non-linear transformation on the output.
-    for (i, out_byte) in output.iter_mut().enumerate() {
-        let chunk_num = i % stride;
-        let chunk_index = i / stride;
-        *out_byte = memory[chunk_num * BHASH_OUTPUT_SIZE + chunk_index];
+    let stride = (output.len() + BHASH_OUTPUT_SIZE - 1) / BHASH_OUTPUT_SIZE;
+    if memory.len() < stride * BHASH_OUTPUT_SIZE {
+        return Err(Error::InvalidMemoryLen);
     }
 
-    Ok(())
+    bcrypt_pbkdf(passphrase, salt, rounds, output)
 }
 
-#[cfg(test)]
-mod test {
-    use super::bhash;
-    use hex_literal::hex;
-    use sha2::digest::generic_array::GenericArray;
+#[cfg(all(test, feature = "parallel"))]
+mod parallel_test {
+    use super::{derive_parallel, derive_serial};
+    use sha2::{Digest, Sha512};
 
     #[test]
-    fn test_bhash() {
-        struct Test {
-            hpass: [u8; 64],
-            hsalt: [u8; 64],
-            out: [u8; 32],
-        }
-
-        const TEST_VAL: [u8; 64] = hex!(
-            "000102030405060708090a0b0c0d0e0f"
-            "101112131415161718191a1b1c1d1e1f"
-            "202122232425262728292a2b2c2d2e2f"
-            "303132333435363738393a3b3c3d3e3f"
-        );
-
-        let tests = [
-            Test {
-                hpass: [0; 64],
-                hsalt: [0; 64],
-                out: hex!(
-                    "460286e972fa833f8b1283ad8fa919fa"
-                    "29bde20e23329e774d8422bac0a7926c"
-                ),
-            },
-            Test {
-                hpass: TEST_VAL,
-                hsalt: [0; 64],
-                out: hex!(
-                    "b0b229dbc6badef0e1da2527474a8b28"
-                    "888f8b061476fe80c32256e1142dd00d"
-                ),
-            },
-            Test {
-                hpass: [0; 64],
-                hsalt: TEST_VAL,
-                out: hex!(
-                    "b62b4e367d3157f5c31e4d2cbafb2931"
-                    "494d9d3bdd171d55cf799fa4416042e2"
-                ),
-            },
-            Test {
-                hpass: TEST_VAL,
-                hsalt: TEST_VAL,
-                out: hex!(
-                    "c6a95fe6413115fb57e99f757498e85d"
-                    "a3c6e1df0c3c93aa975c548a344326f8"
-                ),
-            },
-        ];
-
-        for t in tests.iter() {
-            let hpass = GenericArray::from_slice(&t.hpass);
-            let hsalt = GenericArray::from_slice(&t.hsalt);
-            let out = bhash(hpass, hsalt);
-            assert_eq!(out[..], t.out[..]);
+    fn test_parallel_matches_serial() {
+        let sha2_pass = Sha512::digest(b"password").into();
+        let sha2_salt = Sha512::new_with_prefix(b"salt");
+
+        for len in [1, 31, 32, 33, 48, 64, 65, 100, 1024] {
+            let mut serial = [0u8; 1024];
+            let mut parallel = [0u8; 1024];
+            derive_serial(&sha2_pass, &sha2_salt, 2, &mut serial[..len]);
+            derive_parallel(&sha2_pass, &sha2_salt, 2, &mut parallel[..len]);
+            assert_eq!(serial[..], parallel[..], "{}", len);
         }
     }
 }
@@ -0,0 +1,202 @@
+//! Key derivation of OpenSSH private keys encrypted with the `bcrypt` KDF.
+//!
+//! The `kdfoptions` field of such keys holds the salt, as an SSH string with
+//! a big-endian `u32` length prefix, followed by the rounds as a big-endian
+//! `u32`. The key and IV of the cipher are the first and last bytes of a
+//! single [`bcrypt_pbkdf`] output.
+
+use crate::{bcrypt_pbkdf, Error, OUTPUT_MAX_LEN};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+#[cfg(all(feature = "alloc", feature = "zeroize"))]
+use zeroize::Zeroizing;
+
+/// The `kdfoptions` of the `bcrypt` KDF, borrowing the salt from the blob.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KdfOptions<'a> {
+    /// Salt, 16 bytes in the keys written by `ssh-keygen`.
+    pub salt: &'a [u8],
+    /// Number of rounds, 16 by default in `ssh-keygen`.
+    pub rounds: u32,
+}
+
+impl<'a> KdfOptions<'a> {
+    /// Parse a `kdfoptions` blob.
+    ///
+    /// # Returns
+    /// - `Ok(KdfOptions)` if the blob is well-formed; the salt and rounds
+    ///   aren't checked until keys are derived.
+    /// - `Err(Error::TruncatedKdfOptions)` if it ends before the salt or the
+    ///   rounds do.
+    /// - `Err(Error::TrailingKdfOptions)` if there are bytes after the rounds.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, Error> {
+        let (salt_len, rest) = read_u32(bytes)?;
+        let salt_len = usize::try_from(salt_len).map_err(|_| Error::TruncatedKdfOptions)?;
+        if rest.len() < salt_len {
+            return Err(Error::TruncatedKdfOptions);
+        }
+        let (salt, rest) = rest.split_at(salt_len);
+        let (rounds, rest) = read_u32(rest)?;
+
+        if !rest.is_empty() {
+            return Err(Error::TrailingKdfOptions);
+        }
+
+        Ok(KdfOptions { salt, rounds })
+    }
+
+    /// Length of the blob written by `KdfOptions::to_bytes`.
+    pub fn encoded_len(&self) -> usize {
+        4 + self.salt.len() + 4
+    }
+
+    /// Encode these options as a `kdfoptions` blob.
+    #[cfg(feature = "alloc")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.encoded_len());
+        bytes.extend_from_slice(&(self.salt.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(self.salt);
+        bytes.extend_from_slice(&self.rounds.to_be_bytes());
+        bytes
+    }
+
+    /// Derive the key and IV of the cipher from `passphrase` into `key` and
+    /// `iv`, see [`derive_key_iv_into`].
+    pub fn derive_into(
+        &self,
+        passphrase: impl AsRef<[u8]>,
+        key: &mut [u8],
+        iv: &mut [u8],
+    ) -> Result<(), Error> {
+        derive_key_iv_into(passphrase, self.salt, self.rounds, key, iv)
+    }
+
+    /// Derive a `keylen`-byte key and an `ivlen`-byte IV from `passphrase`,
+    /// e.g. 32 and 16 bytes for `aes256-ctr`, see [`derive_key_iv`].
+    #[cfg(all(feature = "alloc", feature = "zeroize"))]
+    #[allow(clippy::type_complexity)]
+    pub fn derive(
+        &self,
+        passphrase: impl AsRef<[u8]>,
+        keylen: usize,
+        ivlen: usize,
+    ) -> Result<(Zeroizing<Vec<u8>>, Zeroizing<Vec<u8>>), Error> {
+        derive_key_iv(passphrase, self.salt, self.rounds, keylen, ivlen)
+    }
+}
+
+/// Lengths of the key and IV of an OpenSSH cipher, which takes the key from
+/// the first bytes of the [`bcrypt_pbkdf`] output and the IV from the next
+/// ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DerivedKeyMaterial {
+    /// Length of the key.
+    pub key_len: usize,
+    /// Length of the IV.
+    pub iv_len: usize,
+}
+
+impl DerivedKeyMaterial {
+    /// `aes256-ctr`, the default cipher of `ssh-keygen`.
+    pub const AES256_CTR: Self = Self::new(32, 16);
+    /// `aes128-cbc`.
+    pub const AES128_CBC: Self = Self::new(16, 16);
+    /// `aes256-gcm@openssh.com`.
+    pub const AES256_GCM: Self = Self::new(32, 12);
+    /// `chacha20-poly1305@openssh.com`: two 32-byte keys and no IV.
+    pub const CHACHA20_POLY1305: Self = Self::new(64, 0);
+
+    /// Key material of `key_len` and `iv_len` bytes.
+    pub const fn new(key_len: usize, iv_len: usize) -> Self {
+        DerivedKeyMaterial { key_len, iv_len }
+    }
+
+    /// Number of bytes derived with [`bcrypt_pbkdf`].
+    pub const fn total_len(&self) -> usize {
+        self.key_len + self.iv_len
+    }
+}
+
+/// Derive the key and IV of an OpenSSH cipher from `passphrase` into `key`
+/// and `iv`, from a single [`bcrypt_pbkdf`] output, without allocating.
+///
+/// # Returns
+/// - `Ok(())` if everything is fine.
+/// - `Err(Error)` if the passphrase, the salt or the rounds are rejected
+///   by [`bcrypt_pbkdf`], or `key` and `iv` together are empty or longer
+///   than [`OUTPUT_MAX_LEN`] bytes.
+pub fn derive_key_iv_into(
+    passphrase: impl AsRef<[u8]>,
+    salt: &[u8],
+    rounds: u32,
+    key: &mut [u8],
+    iv: &mut [u8],
+) -> Result<(), Error> {
+    check_len(key.len(), iv.len())?;
+
+    let mut output = [0u8; OUTPUT_MAX_LEN];
+    let output = &mut output[..key.len() + iv.len()];
+    let result = bcrypt_pbkdf(passphrase, salt, rounds, output);
+
+    if result.is_ok() {
+        let (key_bytes, iv_bytes) = output.split_at(key.len());
+        key.copy_from_slice(key_bytes);
+        iv.copy_from_slice(iv_bytes);
+    }
+
+    #[cfg(feature = "zeroize")]
+    output.zeroize();
+
+    result
+}
+
+/// Derive a `keylen`-byte key and an `ivlen`-byte IV of an OpenSSH cipher
+/// from `passphrase`, see [`derive_key_iv_into`].
+///
+/// ```
+/// use bcrypt_pbkdf::openssh::{derive_key_iv, DerivedKeyMaterial};
+///
+/// let DerivedKeyMaterial { key_len, iv_len } = DerivedKeyMaterial::AES256_CTR;
+/// let (key, iv) = derive_key_iv("passphrase", b"0123456789abcdef", 16, key_len, iv_len)?;
+/// assert_eq!((key.len(), iv.len()), (32, 16));
+/// # Ok::<(), bcrypt_pbkdf::Error>(())
+/// ```
+#[cfg(all(feature = "alloc", feature = "zeroize"))]
+#[allow(clippy::type_complexity)]
+pub fn derive_key_iv(
+    passphrase: impl AsRef<[u8]>,
+    salt: &[u8],
+    rounds: u32,
+    keylen: usize,
+    ivlen: usize,
+) -> Result<(Zeroizing<Vec<u8>>, Zeroizing<Vec<u8>>), Error> {
+    check_len(keylen, ivlen)?;
+
+    let mut key = Zeroizing::new(alloc::vec![0u8; keylen]);
+    let mut iv = Zeroizing::new(alloc::vec![0u8; ivlen]);
+    derive_key_iv_into(passphrase, salt, rounds, &mut key, &mut iv)?;
+    Ok((key, iv))
+}
+
+/// Check that a key and IV can be derived together.
+fn check_len(keylen: usize, ivlen: usize) -> Result<(), Error> {
+    match keylen.checked_add(ivlen) {
+        Some(0) => Err(Error::InvalidOutputLen),
+        Some(len) if len <= OUTPUT_MAX_LEN => Ok(()),
+        _ => Err(Error::OutputTooLong),
+    }
+}
+
+/// Read a big-endian `u32` length prefix or rounds.
+fn read_u32(bytes: &[u8]) -> Result<(u32, &[u8]), Error> {
+    if bytes.len() < 4 {
+        return Err(Error::TruncatedKdfOptions);
+    }
+    let (value, rest) = bytes.split_at(4);
+    Ok((u32::from_be_bytes(value.try_into().unwrap()), rest))
+}
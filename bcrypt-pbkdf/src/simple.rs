@@ -0,0 +1,152 @@
+//! Implementation of the `password-hash` crate API.
+//!
+//! bcrypt_pbkdf has no standard PHC string format, so this crate defines
+//! one, which won't change:
+//!
+//! ```text
+//! $bcrypt-pbkdf$r=<ROUNDS>,l=<LENGTH>$<SALT>$<HASH>
+//! ```
+//!
+//! - `r` is the number of rounds, a decimal integer of at least 1.
+//! - `l` is the length of the hash in bytes, a decimal integer from 10 to
+//!   64. It may be left out, and is then the length of `<HASH>`, or 32 if
+//!   there's no hash.
+//! - `<SALT>` is the salt, at least 4 characters, and `<HASH>` is the output
+//!   of [`bcrypt_pbkdf`], both encoded as in every PHC string: with the
+//!   standard Base64 alphabet, without padding.
+//!
+//! The salt is decoded before being given to [`bcrypt_pbkdf`], as scrypt
+//! and PBKDF2 PHC strings do.
+
+use crate::bcrypt_pbkdf;
+use core::cmp::Ordering;
+use password_hash::{
+    errors::InvalidValue, Decimal, Error, Ident, Output, ParamsString, PasswordHash,
+    PasswordHasher, Result, Salt,
+};
+
+/// Algorithm identifier
+pub const ALG_ID: Ident<'static> = Ident::new_unwrap("bcrypt-pbkdf");
+
+/// bcrypt_pbkdf type for use with [`PasswordHasher`].
+///
+/// It isn't named `BcryptPbkdf`: that's the incremental API,
+/// [`BcryptPbkdf`](crate::BcryptPbkdf).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub struct BcryptPbkdfHasher;
+
+impl PasswordHasher for BcryptPbkdfHasher {
+    type Params = Params;
+
+    fn hash_password_customized<'a>(
+        &self,
+        password: &[u8],
+        alg_id: Option<Ident<'a>>,
+        version: Option<Decimal>,
+        params: Params,
+        salt: impl Into<Salt<'a>>,
+    ) -> Result<PasswordHash<'a>> {
+        if !matches!(alg_id, Some(ALG_ID) | None) {
+            return Err(Error::Algorithm);
+        }
+
+        // Versions unsupported
+        if version.is_some() {
+            return Err(Error::Version);
+        }
+
+        let salt = salt.into();
+        let mut salt_arr = [0u8; 64];
+        let salt_bytes = salt.decode_b64(&mut salt_arr)?;
+
+        let output = Output::init_with(params.output_length, |out| {
+            bcrypt_pbkdf(password, salt_bytes, params.rounds, out).map_err(Into::into)
+        })?;
+
+        Ok(PasswordHash {
+            algorithm: ALG_ID,
+            version: None,
+            params: params.try_into()?,
+            salt: Some(salt),
+            hash: Some(output),
+        })
+    }
+}
+
+/// bcrypt_pbkdf params
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Params {
+    /// Number of rounds
+    pub rounds: u32,
+
+    /// Size of the output (in bytes)
+    pub output_length: usize,
+}
+
+impl Default for Params {
+    /// 16 rounds, the default of `ssh-keygen`, and 32 bytes of output.
+    fn default() -> Params {
+        Params {
+            rounds: 16,
+            output_length: 32,
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a PasswordHash<'a>> for Params {
+    type Error = Error;
+
+    fn try_from(hash: &'a PasswordHash<'a>) -> Result<Self> {
+        let mut params = Params::default();
+        let mut output_length = None;
+
+        if hash.version.is_some() {
+            return Err(Error::Version);
+        }
+
+        for (ident, value) in hash.params.iter() {
+            match ident.as_str() {
+                "r" => params.rounds = value.decimal()?,
+                "l" => {
+                    output_length = Some(
+                        value
+                            .decimal()?
+                            .try_into()
+                            .map_err(|_| InvalidValue::Malformed.param_error())?,
+                    )
+                }
+                _ => return Err(Error::ParamNameInvalid),
+            }
+        }
+
+        if let Some(hash) = &hash.hash {
+            match output_length.map(|len| hash.len().cmp(&len)) {
+                Some(Ordering::Less) => return Err(InvalidValue::TooShort.param_error()),
+                Some(Ordering::Greater) => return Err(InvalidValue::TooLong.param_error()),
+                _ => params.output_length = hash.len(),
+            }
+        } else if let Some(len) = output_length {
+            params.output_length = len;
+        }
+
+        Ok(params)
+    }
+}
+
+impl TryFrom<Params> for ParamsString {
+    type Error = Error;
+
+    fn try_from(input: Params) -> Result<ParamsString> {
+        let output_length = input
+            .output_length
+            .try_into()
+            .map_err(|_| InvalidValue::TooLong.param_error())?;
+
+        let mut output = ParamsString::new();
+        output.add_decimal("r", input.rounds)?;
+        output.add_decimal("l", output_length)?;
+        Ok(output)
+    }
+}
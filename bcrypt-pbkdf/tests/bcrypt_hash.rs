@@ -0,0 +1,92 @@
+use bcrypt_pbkdf::{bcrypt_hash, bcrypt_pbkdf};
+use hex_literal::hex;
+use sha2::{Digest, Sha512};
+
+#[test]
+fn test_bcrypt_hash() {
+    struct Test {
+        hpass: [u8; 64],
+        hsalt: [u8; 64],
+        out: [u8; 32],
+    }
+
+    const TEST_VAL: [u8; 64] = hex!(
+        "000102030405060708090a0b0c0d0e0f"
+        "101112131415161718191a1b1c1d1e1f"
+        "202122232425262728292a2b2c2d2e2f"
+        "303132333435363738393a3b3c3d3e3f"
+    );
+
+    let tests = [
+        Test {
+            hpass: [0; 64],
+            hsalt: [0; 64],
+            out: hex!(
+                "460286e972fa833f8b1283ad8fa919fa"
+                "29bde20e23329e774d8422bac0a7926c"
+            ),
+        },
+        Test {
+            hpass: TEST_VAL,
+            hsalt: [0; 64],
+            out: hex!(
+                "b0b229dbc6badef0e1da2527474a8b28"
+                "888f8b061476fe80c32256e1142dd00d"
+            ),
+        },
+        Test {
+            hpass: [0; 64],
+            hsalt: TEST_VAL,
+            out: hex!(
+                "b62b4e367d3157f5c31e4d2cbafb2931"
+                "494d9d3bdd171d55cf799fa4416042e2"
+            ),
+        },
+        Test {
+            hpass: TEST_VAL,
+            hsalt: TEST_VAL,
+            out: hex!(
+                "c6a95fe6413115fb57e99f757498e85d"
+                "a3c6e1df0c3c93aa975c548a344326f8"
+            ),
+        },
+    ];
+
+    for t in tests.iter() {
+        let mut out = [0u8; 32];
+        bcrypt_hash(&t.hpass, &t.hsalt, &mut out);
+        assert_eq!(out, t.out);
+    }
+}
+
+/// The first block of `bcrypt_pbkdf("password", "salt", 4)`, OpenBSD's first
+/// regression vector, computed from its intermediate bcrypt_hash values.
+#[test]
+fn test_bcrypt_pbkdf_rounds() {
+    let sha_pass = Sha512::digest(b"password").into();
+    let mut sha_salt = Sha512::new()
+        .chain_update(b"salt")
+        .chain_update(1u32.to_be_bytes())
+        .finalize()
+        .into();
+
+    let mut out = [0u8; 32];
+    let mut block = [0u8; 32];
+    for round in 1..=4 {
+        bcrypt_hash(&sha_pass, &sha_salt, &mut out);
+        block.iter_mut().zip(out).for_each(|(b, o)| *b ^= o);
+        sha_salt = Sha512::digest(out).into();
+
+        let mut expected = [0u8; 32];
+        bcrypt_pbkdf("password", b"salt", round, &mut expected).unwrap();
+        assert_eq!(block, expected, "{}", round);
+    }
+
+    assert_eq!(
+        block,
+        hex!(
+            "5bbf0cc293587f1c3635555c27796598"
+            "d47e579071bf427e9d8fbe842aba34d9"
+        )
+    );
+}
@@ -0,0 +1,135 @@
+use bcrypt_pbkdf::{
+    bcrypt_pbkdf, bcrypt_pbkdf_with_memory, BcryptPbkdf, Error, OUTPUT_MAX_LEN, SALT_MAX_LEN,
+};
+
+#[test]
+fn test_invalid_params() {
+    let mut memory = [0u8; 1024];
+
+    for (passphrase, salt, rounds, len, err) in [
+        ("", &b"salt"[..], 4, 32, Error::InvalidParamLen),
+        ("password", &b""[..], 4, 32, Error::InvalidParamLen),
+        ("password", &b"salt"[..], 0, 32, Error::InvalidRounds),
+        ("password", &b"salt"[..], 4, 0, Error::InvalidOutputLen),
+        ("password", &b"salt"[..], 4, 1025, Error::OutputTooLong),
+    ] {
+        let mut output = vec![0u8; len];
+        assert_eq!(
+            bcrypt_pbkdf_with_memory(passphrase, salt, rounds, &mut output, &mut memory),
+            Err(err)
+        );
+        assert_eq!(
+            bcrypt_pbkdf(passphrase, salt, rounds, &mut output),
+            Err(err)
+        );
+    }
+
+    // The memory must hold the output rounded up to 32 bytes
+    let mut output = [0u8; 33];
+    assert_eq!(
+        bcrypt_pbkdf_with_memory("password", b"salt", 4, &mut output, &mut memory[..32]),
+        Err(Error::InvalidMemoryLen)
+    );
+}
+
+#[test]
+fn test_largest_output() {
+    let mut output = [0u8; OUTPUT_MAX_LEN];
+    let mut memory = [0u8; OUTPUT_MAX_LEN];
+    assert_eq!(
+        bcrypt_pbkdf_with_memory("password", b"salt", 1, &mut output, &mut memory),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_valid_params() {
+    let mut output = [0u8; 32];
+    let mut memory = [0u8; 32];
+    bcrypt_pbkdf_with_memory("password", b"salt", 4, &mut output, &mut memory).unwrap();
+    assert_eq!(
+        output,
+        [
+            0x5b, 0xbf, 0x0c, 0xc2, 0x93, 0x58, 0x7f, 0x1c, 0x36, 0x35, 0x55, 0x5c, 0x27, 0x79,
+            0x65, 0x98, 0xd4, 0x7e, 0x57, 0x90, 0x71, 0xbf, 0x42, 0x7e, 0x9d, 0x8f, 0xbe, 0x84,
+            0x2a, 0xba, 0x34, 0xd9
+        ]
+    );
+}
+
+#[test]
+fn test_display() {
+    assert_eq!(
+        Error::InvalidParamLen.to_string(),
+        "Invalid parameter length"
+    );
+    assert_eq!(Error::SaltTooLong.to_string(), "Salt is too long");
+    assert_eq!(Error::InvalidRounds.to_string(), "Invalid number of rounds");
+    assert_eq!(Error::InvalidOutputLen.to_string(), "Invalid output length");
+    assert_eq!(Error::OutputTooLong.to_string(), "Output is too long");
+    assert_eq!(Error::InvalidMemoryLen.to_string(), "Invalid memory length");
+}
+
+#[test]
+fn test_salt_limits() {
+    let salt = vec![0u8; SALT_MAX_LEN + 1];
+    let mut output = [0u8; 32];
+
+    assert_eq!(
+        bcrypt_pbkdf("password", &salt, 1, &mut output),
+        Err(Error::SaltTooLong)
+    );
+    assert_eq!(
+        bcrypt_pbkdf("password", &salt[..SALT_MAX_LEN], 1, &mut output),
+        Ok(())
+    );
+    assert_eq!(bcrypt_pbkdf("password", &salt[..1], 1, &mut output), Ok(()));
+    assert_eq!(
+        bcrypt_pbkdf("password", &[], 1, &mut output),
+        Err(Error::InvalidParamLen)
+    );
+}
+
+#[test]
+fn test_output_limits() {
+    let mut output = [0u8; OUTPUT_MAX_LEN + 1];
+
+    for len in [1, OUTPUT_MAX_LEN] {
+        assert_eq!(
+            bcrypt_pbkdf("password", b"salt", 1, &mut output[..len]),
+            Ok(())
+        );
+    }
+    assert_eq!(
+        bcrypt_pbkdf("password", b"salt", 1, &mut output[..0]),
+        Err(Error::InvalidOutputLen)
+    );
+    assert_eq!(
+        bcrypt_pbkdf("password", b"salt", 1, &mut output),
+        Err(Error::OutputTooLong)
+    );
+    assert_eq!(
+        BcryptPbkdf::new()
+            .update_passphrase("password")
+            .derive(b"salt", 1, &mut output),
+        Err(Error::OutputTooLong)
+    );
+}
+
+#[test]
+fn test_passphrase_limits() {
+    let mut output = [0u8; 32];
+
+    // No maximum length: long passphrases are hashed with SHA-512 first
+    let passphrase = vec![b'p'; 1 << 20];
+    assert_eq!(bcrypt_pbkdf(&passphrase, b"salt", 1, &mut output), Ok(()));
+    assert_eq!(bcrypt_pbkdf(b"p", b"salt", 1, &mut output), Ok(()));
+    assert_eq!(
+        bcrypt_pbkdf(b"", b"salt", 1, &mut output),
+        Err(Error::InvalidParamLen)
+    );
+    assert_eq!(
+        BcryptPbkdf::new().derive(b"salt", 1, &mut output),
+        Err(Error::InvalidParamLen)
+    );
+}
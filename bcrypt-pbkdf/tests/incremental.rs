@@ -0,0 +1,59 @@
+use bcrypt_pbkdf::{bcrypt_pbkdf, BcryptPbkdf, Error};
+
+#[test]
+fn test_chunked_passphrase() {
+    let passphrase = "pässwörd ✓ 🔑 correct horse battery staple";
+    let bytes = passphrase.as_bytes();
+
+    let mut expected = [0u8; 48];
+    bcrypt_pbkdf(passphrase, b"salt", 4, &mut expected).unwrap();
+
+    // Every split, including those inside multi-byte UTF-8 sequences
+    for split in 0..=bytes.len() {
+        let mut key = [0u8; 48];
+        BcryptPbkdf::new()
+            .update_passphrase(&bytes[..split])
+            .update_passphrase(&bytes[split..])
+            .derive(b"salt", 4, &mut key)
+            .unwrap();
+        assert_eq!(key, expected, "{}", split);
+    }
+
+    for chunk_len in 1..=4 {
+        let mut kdf = BcryptPbkdf::new();
+        for chunk in bytes.chunks(chunk_len) {
+            kdf.update_passphrase(chunk);
+        }
+
+        let mut key = [0u8; 48];
+        kdf.derive(b"salt", 4, &mut key).unwrap();
+        assert_eq!(key, expected, "{}", chunk_len);
+    }
+}
+
+#[test]
+fn test_derive_twice() {
+    let mut kdf = BcryptPbkdf::new();
+    kdf.update_passphrase("password");
+
+    let mut first = [0u8; 32];
+    let mut second = [0u8; 32];
+    kdf.derive(b"salt", 4, &mut first).unwrap();
+    kdf.derive(b"salt", 4, &mut second).unwrap();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_empty_passphrase() {
+    let mut key = [0u8; 32];
+    assert_eq!(
+        BcryptPbkdf::new().derive(b"salt", 4, &mut key),
+        Err(Error::InvalidParamLen)
+    );
+    assert_eq!(
+        BcryptPbkdf::new()
+            .update_passphrase("")
+            .derive(b"salt", 4, &mut key),
+        Err(Error::InvalidParamLen)
+    );
+}
@@ -0,0 +1,72 @@
+//! Deriving keys doesn't allocate, unless the blocks are computed by the
+//! `parallel` thread pool.
+
+#![cfg(not(feature = "parallel"))]
+
+use bcrypt_pbkdf::bcrypt_pbkdf;
+use core::{
+    cell::Cell,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use std::alloc::{GlobalAlloc, Layout, System};
+
+/// Counts the allocations of the current test thread.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+std::thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.try_with(|c| c.get()).unwrap_or(false) {
+            ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Number of allocations made by `f` on this thread.
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    COUNTING.with(|c| c.set(true));
+    f();
+    COUNTING.with(|c| c.set(false));
+    ALLOCATIONS.load(Ordering::SeqCst) - before
+}
+
+#[test]
+fn no_allocations() {
+    let mut output = [0u8; 1024];
+
+    for len in [1, 16, 31, 32, 33, 48, 64, 1024] {
+        assert_eq!(
+            allocations(|| bcrypt_pbkdf("password", b"salt", 4, &mut output[..len]).unwrap()),
+            0
+        );
+    }
+
+    // The first vector of OpenBSD's regress tests
+    let mut output = [0u8; 32];
+    assert_eq!(
+        allocations(|| bcrypt_pbkdf("password", b"salt", 4, &mut output).unwrap()),
+        0
+    );
+    assert_eq!(
+        output,
+        [
+            0x5b, 0xbf, 0x0c, 0xc2, 0x93, 0x58, 0x7f, 0x1c, 0x36, 0x35, 0x55, 0x5c, 0x27, 0x79,
+            0x65, 0x98, 0xd4, 0x7e, 0x57, 0x90, 0x71, 0xbf, 0x42, 0x7e, 0x9d, 0x8f, 0xbe, 0x84,
+            0x2a, 0xba, 0x34, 0xd9
+        ]
+    );
+}
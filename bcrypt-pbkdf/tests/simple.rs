@@ -0,0 +1,120 @@
+//! PHC strings of bcrypt_pbkdf.
+#![cfg(feature = "simple")]
+
+use bcrypt_pbkdf::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    BcryptPbkdfHasher, Params, ALG_ID,
+};
+
+/// `password` and `salt` with 4 rounds, as the OpenBSD test vector.
+const ROUNDS_4: &str = "$bcrypt-pbkdf$r=4,l=32$c2FsdA$W78MwpNYfxw2NVVcJ3llmNR+V5Bxv0J+nY++hCq6NNk";
+
+/// `password` and `salt` with 8 rounds and 64 bytes of output.
+const ROUNDS_8: &str = "$bcrypt-pbkdf$r=8,l=64$c2FsdA$4TZ+xRUaM/qsTMHBRM0j+hXVVIST7Mmbm12cDTsnvsdiJ+pmCIuEmyCreqR4AQJG50u6UXI/76n5R01lCIRejQ";
+
+#[test]
+fn hash_password() {
+    let salt = SaltString::from_b64("c2FsdA").unwrap();
+
+    for (params, expected) in [
+        (
+            Params {
+                rounds: 4,
+                output_length: 32,
+            },
+            ROUNDS_4,
+        ),
+        (
+            Params {
+                rounds: 8,
+                output_length: 64,
+            },
+            ROUNDS_8,
+        ),
+    ] {
+        let hash = BcryptPbkdfHasher
+            .hash_password_customized(b"password", None, None, params, &salt)
+            .unwrap();
+        assert_eq!(hash.algorithm, ALG_ID);
+        assert_eq!(hash.to_string(), expected);
+        assert_eq!(Params::try_from(&hash), Ok(params));
+    }
+}
+
+#[test]
+fn verify_password() {
+    for hash in [ROUNDS_4, ROUNDS_8] {
+        let hash = PasswordHash::new(hash).unwrap();
+        assert!(BcryptPbkdfHasher
+            .verify_password(b"password", &hash)
+            .is_ok());
+        assert!(hash
+            .verify_password(&[&BcryptPbkdfHasher], "password")
+            .is_ok());
+        assert!(BcryptPbkdfHasher
+            .verify_password(b"wrong password", &hash)
+            .is_err());
+    }
+}
+
+#[test]
+fn default_params() {
+    let salt = SaltString::from_b64("c2FsdA").unwrap();
+    let hash = BcryptPbkdfHasher.hash_password(b"password", &salt).unwrap();
+    assert_eq!(Params::try_from(&hash), Ok(Params::default()));
+    assert!(hash
+        .to_string()
+        .starts_with("$bcrypt-pbkdf$r=16,l=32$c2FsdA$"));
+}
+
+#[test]
+fn parse_params() {
+    // `l` defaults to the length of the hash
+    let hash =
+        PasswordHash::new("$bcrypt-pbkdf$r=4$c2FsdA$W78MwpNYfxw2NVVcJ3llmNR+V5Bxv0J+nY++hCq6NNk")
+            .unwrap();
+    assert_eq!(
+        Params::try_from(&hash),
+        Ok(Params {
+            rounds: 4,
+            output_length: 32
+        })
+    );
+    assert!(BcryptPbkdfHasher
+        .verify_password(b"password", &hash)
+        .is_ok());
+
+    for invalid in [
+        "$bcrypt-pbkdf$r=4,l=16$c2FsdA$W78MwpNYfxw2NVVcJ3llmNR+V5Bxv0J+nY++hCq6NNk",
+        "$bcrypt-pbkdf$r=4,l=64$c2FsdA$W78MwpNYfxw2NVVcJ3llmNR+V5Bxv0J+nY++hCq6NNk",
+        "$bcrypt-pbkdf$r=4,p=1$c2FsdA$W78MwpNYfxw2NVVcJ3llmNR+V5Bxv0J+nY++hCq6NNk",
+        "$bcrypt-pbkdf$v=1$r=4$c2FsdA$W78MwpNYfxw2NVVcJ3llmNR+V5Bxv0J+nY++hCq6NNk",
+    ] {
+        let hash = PasswordHash::new(invalid).unwrap();
+        assert!(Params::try_from(&hash).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn invalid_params() {
+    let salt = SaltString::from_b64("c2FsdA").unwrap();
+
+    for params in [
+        Params {
+            rounds: 0,
+            output_length: 32,
+        },
+        Params {
+            rounds: 4,
+            output_length: 65,
+        },
+    ] {
+        assert!(BcryptPbkdfHasher
+            .hash_password_customized(b"password", None, None, params, &salt)
+            .is_err());
+    }
+
+    assert!(BcryptPbkdfHasher
+        .hash_password_customized(b"", None, None, Params::default(), &salt)
+        .is_err());
+}
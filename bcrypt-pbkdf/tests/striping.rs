@@ -0,0 +1,65 @@
+use bcrypt_pbkdf::{bcrypt_hash, bcrypt_pbkdf, OUTPUT_MAX_LEN};
+use sha2::{Digest, Sha512};
+
+/// bcrypt_pbkdf as in OpenBSD: PBKDF2 with bcrypt_hash as the PRF into a
+/// buffer of whole 32-byte blocks, then striped into the output.
+fn reference(passphrase: &[u8], salt: &[u8], rounds: u32, output: &mut [u8]) {
+    let stride = (output.len() + 31) / 32;
+    let sha_pass = Sha512::digest(passphrase).into();
+    let mut blocks = vec![0u8; stride * 32];
+
+    for (i, block) in blocks.chunks_mut(32).enumerate() {
+        let mut sha_salt = Sha512::new()
+            .chain_update(salt)
+            .chain_update((i as u32 + 1).to_be_bytes())
+            .finalize()
+            .into();
+
+        let mut tmp = [0u8; 32];
+        for _ in 0..rounds {
+            bcrypt_hash(&sha_pass, &sha_salt, &mut tmp);
+            block.iter_mut().zip(tmp).for_each(|(b, t)| *b ^= t);
+            sha_salt = Sha512::digest(tmp).into();
+        }
+    }
+
+    for (i, out) in output.iter_mut().enumerate() {
+        *out = blocks[(i % stride) * 32 + i / stride];
+    }
+}
+
+#[test]
+fn test_partial_blocks() {
+    for len in [1, 31, 32, 33, 48, 63, 65, 100, OUTPUT_MAX_LEN] {
+        let mut expected = vec![0u8; len];
+        reference(b"password", b"salt", 2, &mut expected);
+
+        let mut output = vec![0u8; len];
+        bcrypt_pbkdf("password", b"salt", 2, &mut output).unwrap();
+        assert_eq!(output, expected, "{}", len);
+    }
+}
+
+#[test]
+fn test_random_lengths() {
+    // xorshift32, so that the lengths and salts are the same on every run
+    let mut state = 0x2545_f491u32;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+
+    for _ in 0..16 {
+        let len = next() as usize % OUTPUT_MAX_LEN + 1;
+        let salt: Vec<u8> = (0..next() % 512 + 1).map(|_| next() as u8).collect();
+
+        let mut expected = vec![0u8; len];
+        reference(b"password", &salt, 1, &mut expected);
+
+        let mut output = vec![0u8; len];
+        bcrypt_pbkdf("password", &salt, 1, &mut output).unwrap();
+        assert_eq!(output, expected, "{} {:?}", len, salt);
+    }
+}
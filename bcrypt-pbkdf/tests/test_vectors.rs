@@ -1,6 +1,6 @@
 extern crate bcrypt_pbkdf;
 
-use bcrypt_pbkdf::bcrypt_pbkdf_with_memory;
+use bcrypt_pbkdf::{bcrypt_pbkdf, bcrypt_pbkdf_with_memory};
 
 #[test]
 fn test_openbsd_vectors() {
@@ -73,5 +73,9 @@ fn test_openbsd_vectors() {
         let mut memory = vec![0; (t.out.len() + 32 - 1) / 32 * 32];
         bcrypt_pbkdf_with_memory(t.password, &t.salt[..], t.rounds, &mut out, &mut memory).unwrap();
         assert_eq!(out, t.out);
+
+        let mut out = vec![0; t.out.len()];
+        bcrypt_pbkdf(t.password, &t.salt[..], t.rounds, &mut out).unwrap();
+        assert_eq!(out, t.out);
     }
 }
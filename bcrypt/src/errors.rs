@@ -0,0 +1,61 @@
+//! Error types.
+
+use core::fmt;
+
+/// Error type.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The cost isn't between [`COST_MIN`](crate::COST_MIN) and
+    /// [`COST_MAX`](crate::COST_MAX).
+    InvalidCost,
+
+    /// The hash string isn't a bcrypt hash string: it doesn't have the
+    /// length of one, or its cost, salt or digest is malformed.
+    InvalidHash,
+
+    /// The hash string is of a version other than `$2a$`, `$2b$` and
+    /// `$2y$`, such as `$2x$`.
+    UnsupportedVersion,
+
+    /// The password doesn't match the hash string.
+    PasswordMismatch,
+
+    /// The random number generator failed.
+    RandomError,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidCost => write!(
+                f,
+                "cost must be between {} and {}",
+                crate::COST_MIN,
+                crate::COST_MAX
+            ),
+            Error::InvalidHash => f.write_str("invalid bcrypt hash string"),
+            Error::UnsupportedVersion => f.write_str("unsupported bcrypt version"),
+            Error::PasswordMismatch => f.write_str("password doesn't match"),
+            Error::RandomError => f.write_str("random number generator failed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(feature = "simple")]
+impl From<Error> for password_hash::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::InvalidCost => password_hash::Error::ParamValueInvalid(
+                password_hash::errors::InvalidValue::Malformed,
+            ),
+            Error::InvalidHash => password_hash::Error::PhcStringField,
+            Error::UnsupportedVersion => password_hash::Error::Version,
+            Error::PasswordMismatch => password_hash::Error::Password,
+            Error::RandomError => password_hash::Error::Crypto,
+        }
+    }
+}
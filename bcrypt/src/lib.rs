@@ -0,0 +1,339 @@
+//! Pure Rust implementation of the [bcrypt password hash][1], the `$2b$`
+//! hashes of OpenBSD, and of the `$2a$` and `$2y$` hashes of older OpenBSD
+//! releases and of PHP.
+//!
+//! # Usage
+//!
+//! ```
+//! # #[cfg(feature = "simple")]
+//! # {
+//! // Hash the password for storage, with a random salt
+//! let hash = bcrypt::hash("hunter2", bcrypt::COST_DEFAULT)?;
+//! assert!(hash.as_str().starts_with("$2b$12$"));
+//!
+//! // Verify a stored hash, of any version
+//! assert!(bcrypt::verify("hunter2", hash.as_str()).is_ok());
+//! assert!(bcrypt::verify("hunter3", hash.as_str()).is_err());
+//! # }
+//! # Ok::<(), bcrypt::Error>(())
+//! ```
+//!
+//! # Hash strings
+//!
+//! Hash strings are 60 characters long, such as
+//! `$2b$12$R9h/cIPz0gi.URNNX3kh2OoLPwTdOAymPHsrvQP3DDZabggtl0m6W`: the
+//! version, the cost as two decimal digits, then the 16-byte salt and the
+//! 23-byte digest encoded in the Base64 of bcrypt. Its alphabet is
+//! `./A-Za-z0-9`, unlike the `./0-9A-Za-z` of the other `crypt(3)` formats
+//! such as SHA-crypt, and it reads bytes most significant bit first.
+//!
+//! The cost is the base-2 logarithm of the number of rounds, between
+//! [`COST_MIN`] and [`COST_MAX`].
+//!
+//! # Passwords
+//!
+//! The key of bcrypt is the password followed by a NUL byte, truncated to
+//! [`PASSWORD_MAX_LEN`] bytes: only the first 72 bytes of a password are
+//! hashed. The versions only differ in how they compute this key:
+//! - `$2b$` (OpenBSD since 2014) and `$2y$` (PHP) compute it as above.
+//! - `$2a$` computes it as above in every current implementation. However,
+//!   OpenBSD stored the length of the key in a byte before 2014, so it
+//!   wrapped around for passwords of 255 bytes or more. [`Compat`] selects
+//!   whether [`verify_with_compat`] reproduces this bug, to verify `$2a$`
+//!   hashes of such passwords computed by OpenBSD.
+//!
+//! The `$2x$` hashes of `crypt_blowfish`, which sign-extended the bytes of
+//! passwords, aren't supported.
+//!
+//! Hashes are verified by comparing digests in constant time.
+//!
+//! # `no_std` support
+//!
+//! Hashing and verifying don't allocate: [`HashString`] is stored on the
+//! stack. The following crate features add conveniences on top of them:
+//! - `std`: `std::error::Error` support
+//! - `rand_core`: salt generation with [`hash_with_rng`]
+//! - `simple` (default): random salts from the OS with [`hash`], and the
+//!   `password-hash` API, implies `rand_core`
+//!
+//! [1]: https://www.usenix.org/legacy/events/usenix99/provos/provos.pdf
+
+#![no_std]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg",
+    html_favicon_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg"
+)]
+#![deny(unsafe_code)]
+#![warn(missing_docs, rust_2018_idioms)]
+
+#[cfg(feature = "std")]
+extern crate std;
+
+mod errors;
+mod string;
+
+#[cfg(feature = "simple")]
+mod simple;
+
+pub use crate::{errors::Error, string::HashString};
+
+#[cfg(feature = "simple")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub use crate::simple::{Bcrypt, Params, ALG_ID};
+
+#[cfg(feature = "simple")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub use password_hash;
+
+#[cfg(feature = "rand_core")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rand_core")))]
+pub use rand_core;
+
+use crate::string::{parse, parse_setting};
+use blowfish::Blowfish;
+use subtle::ConstantTimeEq;
+
+#[cfg(feature = "rand_core")]
+use rand_core::CryptoRngCore;
+
+/// Smallest cost, 2^4 rounds.
+pub const COST_MIN: u32 = 4;
+
+/// Largest cost, 2^31 rounds.
+pub const COST_MAX: u32 = 31;
+
+/// Cost of [`hash`] and of the default [`Params`], 2^12 rounds.
+pub const COST_DEFAULT: u32 = 12;
+
+/// Length of salts in bytes.
+pub const SALT_LEN: usize = 16;
+
+/// Length of digests in bytes.
+pub const DIGEST_LEN: usize = 23;
+
+/// Number of bytes of a password that are hashed, see the
+/// [crate docs](crate#passwords).
+pub const PASSWORD_MAX_LEN: usize = 72;
+
+/// Length of hash strings, such as
+/// `$2b$12$R9h/cIPz0gi.URNNX3kh2OoLPwTdOAymPHsrvQP3DDZabggtl0m6W`.
+pub const HASH_STRING_LEN: usize = 60;
+
+/// Plaintext encrypted by the rounds of bcrypt, as big-endian words.
+const CTEXT: &[u8; 24] = b"OrpheanBeholderScryDoubt";
+
+/// Version of a hash string, its prefix.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Version {
+    /// `$2a$`, whose key wrapped around in OpenBSD before 2014, see
+    /// [`Compat`].
+    V2a,
+
+    /// `$2b$`, the version of OpenBSD since 2014.
+    V2b,
+
+    /// `$2y$`, the version of PHP, identical to `$2b$`.
+    V2y,
+}
+
+impl Default for Version {
+    fn default() -> Self {
+        Self::V2b
+    }
+}
+
+impl Version {
+    /// Prefix of the hash strings of this version, e.g. `"$2b$"`.
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Version::V2a => "$2a$",
+            Version::V2b => "$2b$",
+            Version::V2y => "$2y$",
+        }
+    }
+}
+
+/// How the key of a `$2a$` hash is computed from passwords of 255 bytes or
+/// more, see the [crate docs](crate#passwords). Hashes of other versions
+/// are always computed as with [`Compat::Fixed`].
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Compat {
+    /// As `$2b$` hashes, as in every current implementation.
+    Fixed,
+
+    /// As OpenBSD did before 2014: the length of the key, including its NUL
+    /// byte, is taken modulo 256, so e.g. the key of a 256-byte password is
+    /// its first byte.
+    Wraparound,
+}
+
+impl Default for Compat {
+    fn default() -> Self {
+        Self::Fixed
+    }
+}
+
+/// Compute the bcrypt digest of `password` with `salt` and 2^`cost`
+/// rounds, for `version` and `compat`.
+///
+/// # Returns
+/// - `Ok([u8; DIGEST_LEN])` containing the digest
+/// - `Err(Error::InvalidCost)` if `cost` isn't between [`COST_MIN`] and
+///   [`COST_MAX`].
+pub fn bcrypt(
+    password: impl AsRef<[u8]>,
+    salt: &[u8; SALT_LEN],
+    cost: u32,
+    version: Version,
+    compat: Compat,
+) -> Result<[u8; DIGEST_LEN], Error> {
+    if !(COST_MIN..=COST_MAX).contains(&cost) {
+        return Err(Error::InvalidCost);
+    }
+
+    let mut key_buf = [0u8; PASSWORD_MAX_LEN];
+    let key = key(password.as_ref(), version, compat, &mut key_buf);
+
+    let mut state = Blowfish::bc_init_state();
+    state.salted_expand_key(salt, key);
+    for _ in 0..1u64 << cost {
+        state.bc_expand_key(key);
+        state.bc_expand_key(salt);
+    }
+
+    let mut ctext = [0u32; 6];
+    for (word, bytes) in ctext.iter_mut().zip(CTEXT.chunks_exact(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    for _ in 0..64 {
+        for pair in ctext.chunks_exact_mut(2) {
+            let [l, r] = state.bc_encrypt([pair[0], pair[1]]);
+            pair[0] = l;
+            pair[1] = r;
+        }
+    }
+
+    let mut output = [0u8; 24];
+    for (bytes, word) in output.chunks_exact_mut(4).zip(ctext) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+
+    let mut digest = [0u8; DIGEST_LEN];
+    digest.copy_from_slice(&output[..DIGEST_LEN]);
+    Ok(digest)
+}
+
+/// Hash `password` into a hash string of `version`, with `salt` and
+/// 2^`cost` rounds.
+pub fn hash_with_salt(
+    password: impl AsRef<[u8]>,
+    cost: u32,
+    salt: &[u8; SALT_LEN],
+    version: Version,
+) -> Result<HashString, Error> {
+    let digest = bcrypt(password, salt, cost, version, Compat::Fixed)?;
+    Ok(HashString::new(version, cost, salt, &digest))
+}
+
+/// Hash `password` into a `$2b$` hash string, with a salt generated from
+/// `rng` and 2^`cost` rounds.
+#[cfg(feature = "rand_core")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rand_core")))]
+pub fn hash_with_rng(
+    password: impl AsRef<[u8]>,
+    cost: u32,
+    rng: &mut impl CryptoRngCore,
+) -> Result<HashString, Error> {
+    let mut salt = [0u8; SALT_LEN];
+    rng.try_fill_bytes(&mut salt)
+        .map_err(|_| Error::RandomError)?;
+    hash_with_salt(password, cost, &salt, Version::V2b)
+}
+
+/// Hash `password` into a `$2b$` hash string, with a random salt from the
+/// OS and 2^`cost` rounds.
+#[cfg(feature = "simple")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub fn hash(password: impl AsRef<[u8]>, cost: u32) -> Result<HashString, Error> {
+    hash_with_rng(password, cost, &mut rand_core::OsRng)
+}
+
+/// Hash `password` with `setting`, as `crypt(3)` does.
+///
+/// The setting is `$2b$<COST>$<SALT>`, or the same with the prefix of
+/// another version, and the characters after the salt are ignored: a hash
+/// string is a valid setting as well, which gives back the same hash string
+/// for the right password. `$2a$` hashes are computed with
+/// [`Compat::Fixed`]. To verify passwords, use [`verify`] instead, which
+/// compares the digests in constant time.
+///
+/// The salt must be encoded canonically, with its unused bits zero, as in
+/// hash strings, which `crypt(3)` doesn't require.
+pub fn crypt(password: impl AsRef<[u8]>, setting: &str) -> Result<HashString, Error> {
+    let setting = parse_setting(setting)?;
+    hash_with_salt(password, setting.cost, &setting.salt, setting.version)
+}
+
+/// Verify `password` against the hash string `hash`, of any version.
+///
+/// `$2a$` hashes are verified with [`Compat::Fixed`].
+///
+/// # Returns
+/// - `Ok(())` if the password matches
+/// - `Err(Error::PasswordMismatch)` if it doesn't
+/// - another error if `hash` isn't a valid hash string.
+pub fn verify(password: impl AsRef<[u8]>, hash: &str) -> Result<(), Error> {
+    verify_with_compat(password, hash, Compat::Fixed)
+}
+
+/// Verify `password` against the hash string `hash` as [`verify`] does,
+/// computing the key of `$2a$` hashes as `compat` says.
+pub fn verify_with_compat(
+    password: impl AsRef<[u8]>,
+    hash: &str,
+    compat: Compat,
+) -> Result<(), Error> {
+    let parsed = parse(hash)?;
+    let digest = bcrypt(password, &parsed.salt, parsed.cost, parsed.version, compat)?;
+
+    if digest.ct_eq(&parsed.digest).into() {
+        Ok(())
+    } else {
+        Err(Error::PasswordMismatch)
+    }
+}
+
+/// Write the key of `password` to `buf`, see the
+/// [crate docs](crate#passwords).
+///
+/// Keys longer than [`PASSWORD_MAX_LEN`] are truncated to it, as bcrypt
+/// only reads that many bytes of its key.
+fn key<'a>(
+    password: &[u8],
+    version: Version,
+    compat: Compat,
+    buf: &'a mut [u8; PASSWORD_MAX_LEN],
+) -> &'a [u8] {
+    // Length of the key, including its NUL byte
+    let mut len = password.len().saturating_add(1);
+
+    if version == Version::V2a && compat == Compat::Wraparound {
+        len %= 256;
+
+        // OpenBSD read the first byte of the password again and again
+        if len == 0 {
+            buf[0] = password[0];
+            return &buf[..1];
+        }
+    }
+
+    let len = len.min(PASSWORD_MAX_LEN);
+    let copied = len.min(password.len());
+    buf[..copied].copy_from_slice(&password[..copied]);
+    if copied < len {
+        buf[copied] = 0;
+    }
+
+    &buf[..len]
+}
@@ -0,0 +1,184 @@
+//! Implementation of the `password-hash` crate API.
+//!
+//! bcrypt has no standard PHC string format, so this crate defines one,
+//! which won't change:
+//!
+//! ```text
+//! $bcrypt$cost=<COST>$<SALT>$<HASH>
+//! ```
+//!
+//! - `cost` is the cost, a decimal integer from 4 to 31. It may be left
+//!   out, and is then [`COST_DEFAULT`].
+//! - `<SALT>` is the salt as in bcrypt hash strings: 16 bytes encoded in 22
+//!   characters of the Base64 of bcrypt. Salts generated with
+//!   [`SaltString::generate`] are encoded in the standard Base64 instead,
+//!   so salts are generated with [`Bcrypt::generate_salt`].
+//! - `<HASH>` is the digest, encoded as in every PHC string: with the
+//!   standard Base64 alphabet, without padding.
+//!
+//! These PHC strings hash passwords as bcrypt hash strings of any version
+//! do, which [`McfHasher`] converts to them.
+
+use crate::{
+    bcrypt,
+    string::{decode, parse},
+    Compat, Version, COST_DEFAULT, COST_MAX, COST_MIN, SALT_LEN,
+};
+use base64ct::{Base64Bcrypt, Encoding};
+use password_hash::{
+    errors::InvalidValue, Decimal, Error, Ident, McfHasher, Output, ParamsString, PasswordHash,
+    PasswordHasher, Result, Salt, SaltString,
+};
+use rand_core::CryptoRngCore;
+
+/// Algorithm identifier
+pub const ALG_ID: Ident<'static> = Ident::new_unwrap("bcrypt");
+
+/// Name of the cost parameter in PHC strings.
+const COST_PARAM: &str = "cost";
+
+/// bcrypt type for use with [`PasswordHasher`].
+///
+/// See the [crate docs](crate) for the hash strings of bcrypt itself, such
+/// as `$2b$12$...`, which [`McfHasher`] converts to PHC strings.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub struct Bcrypt;
+
+impl Bcrypt {
+    /// Generate a random salt from `rng`, encoded in the Base64 of bcrypt
+    /// as [`Bcrypt`] expects.
+    pub fn generate_salt(mut rng: impl CryptoRngCore) -> SaltString {
+        let mut salt = [0u8; SALT_LEN];
+        rng.fill_bytes(&mut salt);
+
+        let mut buf = [0u8; 22];
+        let encoded = Base64Bcrypt::encode(&salt, &mut buf).expect("salt buffer length");
+        SaltString::from_b64(encoded).expect("bcrypt salts are valid PHC salts")
+    }
+}
+
+impl PasswordHasher for Bcrypt {
+    type Params = Params;
+
+    fn hash_password_customized<'a>(
+        &self,
+        password: &[u8],
+        alg_id: Option<Ident<'a>>,
+        version: Option<Decimal>,
+        params: Params,
+        salt: impl Into<Salt<'a>>,
+    ) -> Result<PasswordHash<'a>> {
+        if !matches!(alg_id, Some(ALG_ID) | None) {
+            return Err(Error::Algorithm);
+        }
+
+        // Versions unsupported
+        if version.is_some() {
+            return Err(Error::Version);
+        }
+
+        params.check()?;
+
+        let salt = salt.into();
+        let mut salt_bytes = [0u8; SALT_LEN];
+        decode(salt.as_str(), &mut salt_bytes)
+            .map_err(|_| Error::SaltInvalid(InvalidValue::Malformed))?;
+
+        let digest = bcrypt(
+            password,
+            &salt_bytes,
+            params.cost,
+            Version::V2b,
+            Compat::Fixed,
+        )?;
+
+        Ok(PasswordHash {
+            algorithm: ALG_ID,
+            version: None,
+            params: params.try_into()?,
+            salt: Some(salt),
+            hash: Some(Output::new(&digest)?),
+        })
+    }
+}
+
+impl McfHasher for Bcrypt {
+    /// Upgrade a `$2a$`, `$2b$` or `$2y$` hash string to a PHC hash.
+    ///
+    /// `$2a$` hashes are upgraded as if computed with [`Compat::Fixed`].
+    fn upgrade_mcf_hash<'a>(&self, hash: &'a str) -> Result<PasswordHash<'a>> {
+        let parsed = parse(hash).map_err(|err| match err {
+            crate::Error::InvalidHash if !hash.starts_with("$2") => Error::Algorithm,
+            err => err.into(),
+        })?;
+
+        let params = Params { cost: parsed.cost };
+
+        Ok(PasswordHash {
+            algorithm: ALG_ID,
+            version: None,
+            params: params.try_into()?,
+            salt: Some(Salt::from_b64(parsed.salt_b64)?),
+            hash: Some(Output::new(&parsed.digest)?),
+        })
+    }
+}
+
+/// bcrypt params
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Params {
+    /// Cost, between [`COST_MIN`] and [`COST_MAX`]: the base-2 logarithm of
+    /// the number of rounds
+    pub cost: u32,
+}
+
+impl Params {
+    fn check(&self) -> Result<()> {
+        match self.cost {
+            cost if cost < COST_MIN => Err(InvalidValue::TooShort.param_error()),
+            cost if cost > COST_MAX => Err(InvalidValue::TooLong.param_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for Params {
+    /// [`COST_DEFAULT`], 2^12 rounds.
+    fn default() -> Params {
+        Params { cost: COST_DEFAULT }
+    }
+}
+
+impl<'a> TryFrom<&'a PasswordHash<'a>> for Params {
+    type Error = Error;
+
+    fn try_from(hash: &'a PasswordHash<'a>) -> Result<Self> {
+        let mut params = Params::default();
+
+        if hash.version.is_some() {
+            return Err(Error::Version);
+        }
+
+        for (ident, value) in hash.params.iter() {
+            match ident.as_str() {
+                COST_PARAM => params.cost = value.decimal()?,
+                _ => return Err(Error::ParamNameInvalid),
+            }
+        }
+
+        params.check()?;
+        Ok(params)
+    }
+}
+
+impl TryFrom<Params> for ParamsString {
+    type Error = Error;
+
+    fn try_from(input: Params) -> Result<ParamsString> {
+        let mut output = ParamsString::new();
+        output.add_decimal(COST_PARAM, input.cost)?;
+        Ok(output)
+    }
+}
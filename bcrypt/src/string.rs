@@ -0,0 +1,171 @@
+//! Hash strings: encoding and parsing.
+
+use crate::{Error, Version, COST_MAX, COST_MIN, DIGEST_LEN, HASH_STRING_LEN, SALT_LEN};
+use base64ct::{Base64Bcrypt, Encoding};
+use core::{fmt, str};
+
+/// Length of the version prefix and cost, e.g. `$2b$12$`.
+const HEADER_LEN: usize = 7;
+
+/// Length of the encoded salt.
+const SALT_B64_LEN: usize = 22;
+
+/// Length of the encoded digest.
+const DIGEST_B64_LEN: usize = 31;
+
+/// Hash string, such as
+/// `$2b$12$R9h/cIPz0gi.URNNX3kh2OoLPwTdOAymPHsrvQP3DDZabggtl0m6W`, stored
+/// on the stack.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct HashString {
+    buf: [u8; HASH_STRING_LEN],
+}
+
+impl HashString {
+    /// Encode the hash string of `digest`, `cost`, `salt` and `version`.
+    ///
+    /// `cost` must have been checked to be between [`COST_MIN`] and
+    /// [`COST_MAX`].
+    pub(crate) fn new(
+        version: Version,
+        cost: u32,
+        salt: &[u8; SALT_LEN],
+        digest: &[u8; DIGEST_LEN],
+    ) -> Self {
+        debug_assert!((COST_MIN..=COST_MAX).contains(&cost));
+
+        let mut buf = [0u8; HASH_STRING_LEN];
+        buf[..4].copy_from_slice(version.prefix().as_bytes());
+        buf[4] = b'0' + (cost / 10) as u8;
+        buf[5] = b'0' + (cost % 10) as u8;
+        buf[6] = b'$';
+
+        let (salt_buf, digest_buf) = buf[HEADER_LEN..].split_at_mut(SALT_B64_LEN);
+        Base64Bcrypt::encode(salt, salt_buf).expect("salt buffer length");
+        Base64Bcrypt::encode(digest, digest_buf).expect("digest buffer length");
+
+        Self { buf }
+    }
+
+    /// Borrow the hash string as a `str`.
+    pub fn as_str(&self) -> &str {
+        str::from_utf8(&self.buf).expect("hash strings are ASCII")
+    }
+}
+
+impl AsRef<str> for HashString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for HashString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for HashString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("HashString").field(&self.as_str()).finish()
+    }
+}
+
+/// Fields of a hash string.
+#[derive(Debug)]
+pub(crate) struct Parsed<'a> {
+    pub(crate) version: Version,
+    pub(crate) cost: u32,
+    pub(crate) salt: [u8; SALT_LEN],
+    /// The salt as in the hash string, kept by PHC strings.
+    #[cfg_attr(not(feature = "simple"), allow(dead_code))]
+    pub(crate) salt_b64: &'a str,
+    pub(crate) digest: [u8; DIGEST_LEN],
+}
+
+/// Parse the hash string `hash`.
+///
+/// Only canonical encodings of the salt and the digest, whose unused bits
+/// are zero, are accepted, as those are the only ones bcrypt outputs.
+pub(crate) fn parse(hash: &str) -> Result<Parsed<'_>, Error> {
+    let setting = parse_setting(hash)?;
+    if hash.len() != HASH_STRING_LEN {
+        return Err(Error::InvalidHash);
+    }
+
+    // Non-ASCII characters are in the digest and fail to decode
+    let digest_b64 = &hash.as_bytes()[HEADER_LEN + SALT_B64_LEN..];
+    debug_assert_eq!(digest_b64.len(), DIGEST_B64_LEN);
+    let mut digest = [0u8; DIGEST_LEN];
+    decode(digest_b64, &mut digest)?;
+
+    Ok(Parsed {
+        version: setting.version,
+        cost: setting.cost,
+        salt: setting.salt,
+        salt_b64: setting.salt_b64,
+        digest,
+    })
+}
+
+/// Fields of a setting, the start of a hash string.
+#[derive(Debug)]
+pub(crate) struct Setting<'a> {
+    pub(crate) version: Version,
+    pub(crate) cost: u32,
+    pub(crate) salt: [u8; SALT_LEN],
+    pub(crate) salt_b64: &'a str,
+}
+
+/// Parse the setting at the start of `setting`, the version, cost and salt
+/// of a hash string, ignoring the characters after them.
+///
+/// Only canonical encodings of the salt are accepted, as in [`parse`].
+pub(crate) fn parse_setting(setting: &str) -> Result<Setting<'_>, Error> {
+    let version = match setting.get(..4) {
+        Some("$2a$") => Version::V2a,
+        Some("$2b$") => Version::V2b,
+        Some("$2y$") => Version::V2y,
+        _ if setting.starts_with("$2") => return Err(Error::UnsupportedVersion),
+        _ => return Err(Error::InvalidHash),
+    };
+
+    let bytes = setting.as_bytes();
+    if bytes.len() < HEADER_LEN + SALT_B64_LEN || bytes[6] != b'$' {
+        return Err(Error::InvalidHash);
+    }
+
+    let cost = match (bytes[4], bytes[5]) {
+        (tens @ b'0'..=b'9', units @ b'0'..=b'9') => {
+            u32::from(tens - b'0') * 10 + u32::from(units - b'0')
+        }
+        _ => return Err(Error::InvalidHash),
+    };
+    if !(COST_MIN..=COST_MAX).contains(&cost) {
+        return Err(Error::InvalidCost);
+    }
+
+    // Non-ASCII characters aren't on character boundaries, or fail to
+    // decode
+    let salt_b64 = setting
+        .get(HEADER_LEN..HEADER_LEN + SALT_B64_LEN)
+        .ok_or(Error::InvalidHash)?;
+    let mut salt = [0u8; SALT_LEN];
+    decode(salt_b64, &mut salt)?;
+
+    Ok(Setting {
+        version,
+        cost,
+        salt,
+        salt_b64,
+    })
+}
+
+/// Decode exactly `dst.len()` bytes from `src`.
+pub(crate) fn decode(src: impl AsRef<[u8]>, dst: &mut [u8]) -> Result<(), Error> {
+    let len = dst.len();
+    match Base64Bcrypt::decode(src, dst) {
+        Ok(decoded) if decoded.len() == len => Ok(()),
+        _ => Err(Error::InvalidHash),
+    }
+}
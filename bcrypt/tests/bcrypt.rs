@@ -0,0 +1,231 @@
+//! Known-answer tests of bcrypt hash strings.
+//!
+//! The `$2a$06$` ones are the OpenBSD test vectors also used by jBCrypt and
+//! py-bcrypt, and the `$2a$05$` ones those of `crypt_blowfish`.
+
+use bcrypt::{
+    bcrypt, crypt, hash_with_salt, verify, verify_with_compat, Compat, Error, Version,
+    PASSWORD_MAX_LEN,
+};
+
+const TEST_VECTORS: &[(&str, &str)] = &[
+    (
+        "",
+        "$2a$06$DCq7YPn5Rq63x1Lad4cll.TV4S6ytwfsfvkgY8jIucDrjc8deX1s.",
+    ),
+    (
+        "",
+        "$2a$08$HqWuK6/Ng6sg9gQzbLrgb.Tl.ZHfXLhvt/SgVyWhQqgqcZ7ZuUtye",
+    ),
+    (
+        "",
+        "$2a$10$k1wbIrmNyFAPwPVPSVa/zecw2BCEnBwVS2GbrmgzxFUOqW9dk4TCW",
+    ),
+    (
+        "a",
+        "$2a$06$m0CrhHm10qJ3lXRY.5zDGO3rS2KdeeWLuGmsfGlMfOxih58VYVfxe",
+    ),
+    (
+        "abc",
+        "$2a$06$If6bvum7DFjUnE9p2uDeDu0YHzrHM6tf.iqN8.yx.jNN1ILEf7h0i",
+    ),
+    (
+        "abcdefghijklmnopqrstuvwxyz",
+        "$2a$06$.rCVZVOThsIa97pEDOxvGuRRgzG64bvtJ0938xuqzv18d3ZpQhstC",
+    ),
+    (
+        "~!@#$%^&*()      ~!@#$%^&*()PNBFRD",
+        "$2a$06$fPIsBO8qRqkjj273rfaOI.HtSV9jLDpTbZn782DC6/t7qT67P6FfO",
+    ),
+    (
+        "U*U",
+        "$2a$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW",
+    ),
+    (
+        "U*U*",
+        "$2a$05$CCCCCCCCCCCCCCCCCCCCC.VGOzA784oUp/Z0DY336zx7pLYAy0lwK",
+    ),
+    (
+        "U*U*U",
+        "$2a$05$XXXXXXXXXXXXXXXXXXXXXOAcXxm9kjPGEMsLznoKqmqw7tc8WCx4a",
+    ),
+    (
+        "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789chars after 72 are ignored",
+        "$2a$05$abcdefghijklmnopqrstuu5s2v8.iXieOjg/.AySBTTZIIVFJeBui",
+    ),
+    (
+        "",
+        "$2a$05$CCCCCCCCCCCCCCCCCCCCC.7uG0VCzI2bS7j6ymqJi9CdcdxiRTWNy",
+    ),
+    (
+        "password",
+        "$2b$04$EGdrhbKUv8Oc9vGiXX0HQOWV7K1WDpM8mE69zv71Wj9qLVbq..DSy",
+    ),
+];
+
+/// Salt of the `$2a$05$CCCC...` vectors, `CCCCCCCCCCCCCCCCCCCCC.`.
+const SALT_C: [u8; 16] = [
+    0x10, 0x41, 0x04, 0x10, 0x41, 0x04, 0x10, 0x41, 0x04, 0x10, 0x41, 0x04, 0x10, 0x41, 0x04, 0x10,
+];
+
+#[test]
+fn test_vectors() {
+    for &(password, hash) in TEST_VECTORS {
+        assert_eq!(verify(password, hash), Ok(()), "{}", hash);
+        assert_eq!(
+            verify(format!("!{}", password), hash),
+            Err(Error::PasswordMismatch),
+            "{}",
+            hash
+        );
+    }
+}
+
+#[test]
+fn hash_with_salt_matches_vectors() {
+    let hash = hash_with_salt("U*U", 5, &SALT_C, Version::V2a).unwrap();
+    assert_eq!(
+        hash.as_str(),
+        "$2a$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW"
+    );
+}
+
+#[test]
+fn crypt_settings() {
+    for &(password, hash) in TEST_VECTORS {
+        assert_eq!(crypt(password, hash).unwrap().as_str(), hash);
+        assert_eq!(crypt(password, &hash[..29]).unwrap().as_str(), hash);
+    }
+
+    let setting = "$2a$05$CCCCCCCCCCCCCCCCCCCCC.";
+    assert_eq!(crypt("U*U", &setting[..28]), Err(Error::InvalidHash));
+    assert_eq!(
+        crypt("U*U", &setting.replace("$05$", "$03$")),
+        Err(Error::InvalidCost)
+    );
+    // Unused bits of the salt set, which `crypt(3)` clears
+    assert_eq!(
+        crypt("U*U", &setting.replace("CCCC.", "CCCCC")),
+        Err(Error::InvalidHash)
+    );
+}
+
+#[test]
+fn versions_hash_alike() {
+    for version in [Version::V2b, Version::V2y] {
+        let hash = hash_with_salt("U*U", 5, &SALT_C, version).unwrap();
+        assert_eq!(
+            hash.as_str(),
+            format!(
+                "{}05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW",
+                version.prefix()
+            )
+        );
+        assert_eq!(verify("U*U", hash.as_str()), Ok(()));
+    }
+}
+
+#[test]
+fn passwords_are_truncated() {
+    let password = [b'x'; PASSWORD_MAX_LEN + 1];
+    let hash = hash_with_salt(&password[..PASSWORD_MAX_LEN], 4, &SALT_C, Version::V2b).unwrap();
+
+    assert_eq!(verify(password, hash.as_str()), Ok(()));
+    assert_eq!(
+        verify(&password[..PASSWORD_MAX_LEN - 1], hash.as_str()),
+        Err(Error::PasswordMismatch)
+    );
+}
+
+#[test]
+fn wraparound_compat() {
+    let salt = "$2a$05$CCCCCCCCCCCCCCCCCCCCC.";
+
+    // 300 bytes: with the bug, the key is the first 301 - 256 = 45 bytes,
+    // repeated, as for the hash of this 72-byte password.
+    let password = "0123456789".repeat(30);
+    let repeated = format!("{}{}", &password[..45], &password[..27]);
+    let hash = format!("{}Uhul5ER5iwqQJlfnyuzH8POnOa9PtjO", salt);
+    assert_eq!(verify(&repeated, &hash), Ok(()));
+    assert_eq!(
+        verify_with_compat(&password, &hash, Compat::Wraparound),
+        Ok(())
+    );
+    assert_eq!(verify(&password, &hash), Err(Error::PasswordMismatch));
+
+    // Without the bug, this is the hash of the first 72 bytes
+    let hash = format!("{}XxrQqgBi/5Sxuq9soXzDtjIZ7w5pMfK", salt);
+    assert_eq!(verify(&password, &hash), Ok(()));
+    assert_eq!(
+        verify_with_compat(&password, &hash, Compat::Wraparound),
+        Err(Error::PasswordMismatch)
+    );
+
+    // 255 bytes: the key is empty, and OpenBSD read its first byte instead
+    let password = "U*U".repeat(85);
+    let hash = format!("{}SLCdS4XHMsOtK9qWx3PoF/Gb21U28cm", salt);
+    assert_eq!(verify("U".repeat(72), &hash), Ok(()));
+    assert_eq!(
+        verify_with_compat(&password, &hash, Compat::Wraparound),
+        Ok(())
+    );
+
+    // Only `$2a$` hashes had the bug
+    let password = "0123456789".repeat(30);
+    for version in [Version::V2b, Version::V2y] {
+        assert_eq!(
+            bcrypt(&password, &SALT_C, 4, version, Compat::Wraparound),
+            bcrypt(&password[..72], &SALT_C, 4, version, Compat::Fixed)
+        );
+    }
+}
+
+#[test]
+fn cost_bounds() {
+    assert_eq!(
+        bcrypt("", &SALT_C, 3, Version::V2b, Compat::Fixed),
+        Err(Error::InvalidCost)
+    );
+    assert_eq!(
+        bcrypt("", &SALT_C, 32, Version::V2b, Compat::Fixed),
+        Err(Error::InvalidCost)
+    );
+    assert!(hash_with_salt("", 4, &SALT_C, Version::V2b).is_ok());
+
+    for cost in ["03", "32", "99"] {
+        let hash = format!(
+            "$2b${}$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW",
+            cost
+        );
+        assert_eq!(verify("U*U", &hash), Err(Error::InvalidCost), "{}", cost);
+    }
+}
+
+#[test]
+fn invalid_hash_strings() {
+    let valid = "$2a$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW";
+
+    for (hash, err) in [
+        // `crypt_blowfish` sign extension bug, and the original version
+        (valid.replace("$2a$", "$2x$"), Error::UnsupportedVersion),
+        (
+            "$2$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOe".into(),
+            Error::UnsupportedVersion,
+        ),
+        ("$6$rounds=5000$salt$hash".into(), Error::InvalidHash),
+        (valid[..59].into(), Error::InvalidHash),
+        (format!("{}W", valid), Error::InvalidHash),
+        (valid.replace("$05$", "$5$$"), Error::InvalidHash),
+        (valid.replace("$05$", "$05-"), Error::InvalidHash),
+        // Not in the alphabet
+        (valid.replace("CCCC.", "CCCC+"), Error::InvalidHash),
+        // Unused bits of the salt and of the digest set
+        (valid.replace("CCCC.", "CCCC/"), Error::InvalidHash),
+        (valid.replace("OeW", "OeX"), Error::InvalidHash),
+        // Non-ASCII
+        (valid.replace("CCCC.", "CCCé"), Error::InvalidHash),
+        (valid.replace("eW", "é"), Error::InvalidHash),
+    ] {
+        assert_eq!(verify("U*U", &hash), Err(err), "{}", hash);
+    }
+}
@@ -0,0 +1,72 @@
+//! Tests of the `password-hash` API.
+#![cfg(feature = "simple")]
+
+use bcrypt::{
+    password_hash::{rand_core::OsRng, McfHasher, PasswordHash, PasswordHasher, PasswordVerifier},
+    Bcrypt, Params, ALG_ID,
+};
+
+const MCF_HASH: &str = "$2b$04$EGdrhbKUv8Oc9vGiXX0HQOWV7K1WDpM8mE69zv71Wj9qLVbq..DSy";
+const PHC_HASH: &str = "$bcrypt$cost=4$EGdrhbKUv8Oc9vGiXX0HQO$YX9M3YFrO+oG8/1x93Yl/sNXdsAAFU0";
+
+#[test]
+fn upgrade_mcf_hash() {
+    for mcf in [
+        MCF_HASH.to_owned(),
+        MCF_HASH.replace("$2b$", "$2a$"),
+        MCF_HASH.replace("$2b$", "$2y$"),
+    ] {
+        let hash = Bcrypt.upgrade_mcf_hash(&mcf).unwrap();
+        assert_eq!(hash.to_string(), PHC_HASH);
+        assert!(Bcrypt.verify_password(b"password", &hash).is_ok());
+        assert!(Bcrypt.verify_password(b"passwore", &hash).is_err());
+    }
+
+    assert_eq!(
+        Bcrypt.upgrade_mcf_hash("$6$salt$hash"),
+        Err(bcrypt::password_hash::Error::Algorithm)
+    );
+    assert_eq!(
+        Bcrypt.upgrade_mcf_hash(&MCF_HASH.replace("$2b$", "$2x$")),
+        Err(bcrypt::password_hash::Error::Version)
+    );
+}
+
+#[test]
+fn hash_and_verify() {
+    let salt = Bcrypt::generate_salt(&mut OsRng);
+    let hash = Bcrypt
+        .hash_password_customized(b"hunter2", None, None, Params { cost: 5 }, &salt)
+        .unwrap();
+    assert_eq!(hash.algorithm, ALG_ID);
+    assert_eq!(Params::try_from(&hash), Ok(Params { cost: 5 }));
+
+    let hash = PasswordHash::new(&hash.to_string()).unwrap().to_string();
+    let hash = PasswordHash::new(&hash).unwrap();
+    assert!(Bcrypt.verify_password(b"hunter2", &hash).is_ok());
+    assert!(Bcrypt.verify_password(b"hunter3", &hash).is_err());
+}
+
+#[test]
+fn params() {
+    let hash = PasswordHash::new("$bcrypt$EGdrhbKUv8Oc9vGiXX0HQO").unwrap();
+    assert_eq!(Params::try_from(&hash), Ok(Params::default()));
+
+    for params in ["cost=3", "cost=32", "rounds=5"] {
+        let hash = format!("$bcrypt${}$EGdrhbKUv8Oc9vGiXX0HQO", params);
+        let hash = PasswordHash::new(&hash).unwrap();
+        assert!(Params::try_from(&hash).is_err(), "{}", params);
+    }
+}
+
+#[test]
+fn standard_b64_salts_are_rejected() {
+    // Standard Base64 salts may contain `+`, which bcrypt's alphabet lacks
+    let salt = bcrypt::password_hash::SaltString::from_b64("EGdrhbKUv8Oc9vGiXX0H+O").unwrap();
+    assert_eq!(
+        Bcrypt.hash_password(b"password", &salt),
+        Err(bcrypt::password_hash::Error::SaltInvalid(
+            bcrypt::password_hash::errors::InvalidValue::Malformed
+        ))
+    );
+}
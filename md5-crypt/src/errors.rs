@@ -0,0 +1,49 @@
+//! Error types.
+
+use core::fmt;
+
+/// Error type.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The salt is longer than [`SALT_MAX_LEN`](crate::SALT_MAX_LEN), or
+    /// contains `$`.
+    InvalidSalt,
+
+    /// The hash string isn't an MD5-crypt hash string: it doesn't start
+    /// with `$1$` or `$apr1$`, or its digest is malformed.
+    InvalidHash,
+
+    /// The password doesn't match the hash string.
+    PasswordMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidSalt => write!(
+                f,
+                "salt must have at most {} characters, none of them '$'",
+                crate::SALT_MAX_LEN
+            ),
+            Error::InvalidHash => f.write_str("invalid MD5-crypt hash string"),
+            Error::PasswordMismatch => f.write_str("password doesn't match"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(feature = "simple")]
+impl From<Error> for password_hash::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::InvalidSalt => {
+                password_hash::Error::SaltInvalid(password_hash::errors::InvalidValue::Malformed)
+            }
+            Error::InvalidHash => password_hash::Error::PhcStringField,
+            Error::PasswordMismatch => password_hash::Error::Password,
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Pure Rust implementation of the legacy [MD5-crypt password hash][1]: the
+//! `$1$` hashes of `crypt(3)`, and the `$apr1$` hashes of Apache `htpasswd`
+//! files, which only differ from them in their prefix.
+//!
+//! # ⚠️ Legacy algorithm: for migration only
+//!
+//! MD5-crypt is broken for password storage: its 1000 rounds of MD5 are so
+//! fast that its hashes are cracked at billions of passwords per second.
+//! This crate only exists to verify the hashes remaining in old `shadow`
+//! and `htpasswd` files and devices, so that users can be moved to a
+//! modern algorithm as they log in, see [`Md5Crypt`]. Never use it to hash
+//! new passwords.
+//!
+//! # Usage
+//!
+//! ```
+//! // Verify a stored hash, of either variant
+//! let hash = "$apr1$r31.....$HqJZimcKQFAMYayBlzkrA/";
+//! assert!(md5_crypt::verify("myPassword", hash).is_ok());
+//! assert!(md5_crypt::verify("bogus", hash).is_err());
+//! # Ok::<(), md5_crypt::Error>(())
+//! ```
+//!
+//! [`hash`] computes hash strings with a given salt, e.g. to generate test
+//! fixtures, and [`crypt`] with a setting as `crypt(3)` does.
+//!
+//! # Hash strings
+//!
+//! Hash strings are such as `$1$saltstri$YMyguxXMBpd2TEZ.vS/3q1`: the
+//! prefix of the variant, the salt, then the 16-byte digest encoded in 22
+//! characters of the Base64 of `crypt(3)`, the alphabet `./0-9A-Za-z` also
+//! used by SHA-crypt.
+//!
+//! The salt has at most [`SALT_MAX_LEN`] characters, none of them `$`.
+//! `crypt(3)` truncates longer salts, so such salts are never found in hash
+//! strings.
+//!
+//! # Passwords
+//!
+//! Passwords are byte strings: every byte of the password is hashed,
+//! including NUL bytes, whereas `crypt(3)` stops at the first NUL. Hashes of
+//! passwords containing NUL bytes don't match those of `crypt(3)`.
+//!
+//! Hashes are verified by comparing digests in constant time.
+//!
+//! # `no_std` support
+//!
+//! Hashing and verifying don't allocate: [`HashString`] is stored on the
+//! stack. The following crate features add conveniences on top of them:
+//! - `std`: `std::error::Error` support
+//! - `simple` (default): the `password-hash` API, to verify hashes and
+//!   upgrade them to PHC strings
+//!
+//! [1]: https://en.wikipedia.org/wiki/Crypt_(C)#MD5-based_scheme
+
+#![no_std]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg",
+    html_favicon_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg"
+)]
+#![deny(unsafe_code)]
+#![warn(missing_docs, rust_2018_idioms)]
+
+#[cfg(feature = "std")]
+extern crate std;
+
+mod errors;
+mod string;
+
+#[cfg(feature = "simple")]
+mod simple;
+
+pub use crate::{errors::Error, string::HashString};
+
+#[cfg(feature = "simple")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub use crate::simple::Md5Crypt;
+
+#[cfg(feature = "simple")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub use password_hash;
+
+use crate::string::{parse, parse_setting};
+use md5::{Digest, Md5};
+use subtle::ConstantTimeEq;
+
+/// Largest number of characters of salts.
+pub const SALT_MAX_LEN: usize = 8;
+
+/// Length of digests in bytes.
+pub const DIGEST_LEN: usize = 16;
+
+/// Length of the longest hash strings, those of [`Variant::Apr1`] with a
+/// salt of [`SALT_MAX_LEN`] characters.
+pub const HASH_STRING_MAX_LEN: usize = 37;
+
+/// Number of rounds, fixed.
+const ROUNDS: usize = 1000;
+
+/// Variant of a hash string, its prefix.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Variant {
+    /// `$1$`, the variant of `crypt(3)`.
+    Md5Crypt,
+
+    /// `$apr1$`, the variant of Apache `htpasswd` files.
+    Apr1,
+}
+
+impl Variant {
+    /// Prefix of the hash strings of this variant, e.g. `"$1$"`, which is
+    /// hashed along with the password.
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Variant::Md5Crypt => "$1$",
+            Variant::Apr1 => "$apr1$",
+        }
+    }
+}
+
+/// Compute the MD5-crypt digest of `password` with `salt`, for `variant`.
+///
+/// **WARNING: Make sure to compare this value in constant time!**
+///
+/// # Returns
+/// - `Ok([u8; DIGEST_LEN])` containing the digest
+/// - `Err(Error::InvalidSalt)` if `salt` is longer than [`SALT_MAX_LEN`]
+///   or contains `$`.
+pub fn md5_crypt(
+    password: impl AsRef<[u8]>,
+    salt: &str,
+    variant: Variant,
+) -> Result<[u8; DIGEST_LEN], Error> {
+    let password = password.as_ref();
+    let salt = check_salt(salt)?.as_bytes();
+
+    let alternate = Md5::new()
+        .chain_update(password)
+        .chain_update(salt)
+        .chain_update(password)
+        .finalize();
+
+    let mut digest = Md5::new()
+        .chain_update(password)
+        .chain_update(variant.prefix())
+        .chain_update(salt);
+    for chunk in password.chunks(DIGEST_LEN) {
+        digest.update(&alternate[..chunk.len()]);
+    }
+
+    // The bits of the length of the password select a NUL byte or its first
+    // byte, as in the original implementation
+    let mut len = password.len();
+    while len > 0 {
+        if len & 1 == 1 {
+            digest.update([0]);
+        } else {
+            digest.update(&password[..1]);
+        }
+        len >>= 1;
+    }
+    let mut output = digest.finalize();
+
+    for round in 0..ROUNDS {
+        let mut digest = Md5::new();
+        if round % 2 == 1 {
+            digest.update(password);
+        } else {
+            digest.update(output);
+        }
+        if round % 3 != 0 {
+            digest.update(salt);
+        }
+        if round % 7 != 0 {
+            digest.update(password);
+        }
+        if round % 2 == 1 {
+            digest.update(output);
+        } else {
+            digest.update(password);
+        }
+        output = digest.finalize();
+    }
+
+    Ok(output.into())
+}
+
+/// Hash `password` into a hash string of `variant` with `salt`.
+///
+/// **Only for tests and tooling**: MD5-crypt must never be used to store
+/// new passwords, see the [crate docs](crate).
+///
+/// # Returns
+/// - `Ok(HashString)` containing the hash string
+/// - `Err(Error::InvalidSalt)` if `salt` is longer than [`SALT_MAX_LEN`]
+///   or contains `$`.
+pub fn hash(password: impl AsRef<[u8]>, salt: &str, variant: Variant) -> Result<HashString, Error> {
+    let digest = md5_crypt(password, salt, variant)?;
+    Ok(HashString::new(variant, salt, &digest))
+}
+
+/// Hash `password` with `setting`, as `crypt(3)` does.
+///
+/// The setting is the prefix of a variant followed by a salt, which ends at
+/// the next `$` and is truncated to [`SALT_MAX_LEN`] characters: a hash
+/// string is a valid setting as well, which gives back the same hash string
+/// for the right password. To verify passwords, use [`verify`] instead,
+/// which compares the digests in constant time.
+///
+/// **Only for tests and tooling**, as [`hash`].
+///
+/// # Returns
+/// - `Ok(HashString)` containing the hash string
+/// - `Err(Error::InvalidHash)` if `setting` doesn't start with the prefix
+///   of a variant.
+pub fn crypt(password: impl AsRef<[u8]>, setting: &str) -> Result<HashString, Error> {
+    let (variant, salt) = parse_setting(setting)?;
+    hash(password, salt, variant)
+}
+
+/// Verify `password` against the hash string `hash`, of either variant.
+///
+/// # Returns
+/// - `Ok(())` if the password matches
+/// - `Err(Error::PasswordMismatch)` if it doesn't
+/// - `Err(Error::InvalidHash)` or `Err(Error::InvalidSalt)` if `hash` isn't
+///   a valid hash string.
+pub fn verify(password: impl AsRef<[u8]>, hash: &str) -> Result<(), Error> {
+    let parsed = parse(hash)?;
+    let digest = md5_crypt(password, parsed.salt, parsed.variant)?;
+
+    if digest.ct_eq(&parsed.digest).into() {
+        Ok(())
+    } else {
+        Err(Error::PasswordMismatch)
+    }
+}
+
+/// Check that `salt` is a valid salt.
+fn check_salt(salt: &str) -> Result<&str, Error> {
+    if salt.len() > SALT_MAX_LEN || salt.contains('$') {
+        return Err(Error::InvalidSalt);
+    }
+
+    Ok(salt)
+}
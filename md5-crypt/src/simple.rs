@@ -0,0 +1,112 @@
+//! Implementation of the `password-hash` crate API.
+//!
+//! MD5-crypt hashes are verified as PHC strings such as
+//! `$md5-crypt$<SALT>$<HASH>` and `$apr1-crypt$<SALT>$<HASH>`, without
+//! params: the salt is used verbatim as in hash strings, and the digest is
+//! encoded in the standard B64 of PHC strings. [`McfHasher`] converts hash
+//! strings to them.
+//!
+//! [`Md5Crypt`] only verifies hashes and doesn't implement
+//! [`PasswordHasher`](password_hash::PasswordHasher): new passwords must be
+//! hashed with a modern algorithm instead.
+
+use crate::{md5_crypt, string::parse, Variant};
+use password_hash::{
+    Error, Ident, McfHasher, Output, ParamsString, PasswordHash, PasswordVerifier, Result, Salt,
+};
+
+/// MD5-crypt type for use with [`PasswordVerifier`] and [`McfHasher`], to
+/// migrate `$1$` and `$apr1$` hashes to a modern algorithm.
+///
+/// Registered next to the verifiers of modern algorithms, it verifies the
+/// upgraded PHC strings of these hashes, so that they can be replaced by
+/// the hash of the password with a modern algorithm on login.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub struct Md5Crypt;
+
+impl PasswordVerifier for Md5Crypt {
+    fn verify_password(&self, password: &[u8], hash: &PasswordHash<'_>) -> Result<()> {
+        let variant = Variant::try_from(hash.algorithm)?;
+
+        // Versions and params unsupported
+        if hash.version.is_some() {
+            return Err(Error::Version);
+        }
+        if !hash.params.is_empty() {
+            return Err(Error::ParamNameInvalid);
+        }
+
+        if let (Some(salt), Some(expected)) = (&hash.salt, &hash.hash) {
+            let digest = md5_crypt(password, salt.as_str(), variant)?;
+
+            // Constant-time comparison
+            if Output::new(&digest)? == *expected {
+                return Ok(());
+            }
+        }
+
+        Err(Error::Password)
+    }
+}
+
+impl McfHasher for Md5Crypt {
+    /// Upgrade a `$1$` or `$apr1$` hash string to a PHC hash.
+    ///
+    /// Its salt must be a valid PHC salt, which rules out salts shorter than
+    /// 4 characters.
+    fn upgrade_mcf_hash<'a>(&self, hash: &'a str) -> Result<PasswordHash<'a>> {
+        let parsed = parse(hash).map_err(|err| match err {
+            crate::Error::InvalidHash
+                if !hash.starts_with(Variant::Md5Crypt.prefix())
+                    && !hash.starts_with(Variant::Apr1.prefix()) =>
+            {
+                Error::Algorithm
+            }
+            err => err.into(),
+        })?;
+
+        Ok(PasswordHash {
+            algorithm: parsed.variant.ident(),
+            version: None,
+            params: ParamsString::new(),
+            salt: Some(Salt::from_b64(parsed.salt)?),
+            hash: Some(Output::new(&parsed.digest)?),
+        })
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+impl Variant {
+    /// `$1$` algorithm identifier in PHC strings
+    pub const MD5_CRYPT_IDENT: Ident<'static> = Ident::new_unwrap("md5-crypt");
+
+    /// `$apr1$` algorithm identifier in PHC strings
+    pub const APR1_CRYPT_IDENT: Ident<'static> = Ident::new_unwrap("apr1-crypt");
+
+    /// Get the [`Ident`] of this variant in PHC strings.
+    pub fn ident(&self) -> Ident<'static> {
+        match self {
+            Variant::Md5Crypt => Self::MD5_CRYPT_IDENT,
+            Variant::Apr1 => Self::APR1_CRYPT_IDENT,
+        }
+    }
+}
+
+impl From<Variant> for Ident<'static> {
+    fn from(variant: Variant) -> Ident<'static> {
+        variant.ident()
+    }
+}
+
+impl<'a> TryFrom<Ident<'a>> for Variant {
+    type Error = Error;
+
+    fn try_from(ident: Ident<'a>) -> Result<Variant> {
+        match ident {
+            Self::MD5_CRYPT_IDENT => Ok(Variant::Md5Crypt),
+            Self::APR1_CRYPT_IDENT => Ok(Variant::Apr1),
+            _ => Err(Error::Algorithm),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Hash strings: encoding and parsing.
+
+use crate::{check_salt, Error, Variant, DIGEST_LEN, HASH_STRING_MAX_LEN, SALT_MAX_LEN};
+use base64ct::{Base64ShaCrypt, Encoding};
+use core::{fmt, str};
+
+/// Length of the encoded digest.
+const DIGEST_B64_LEN: usize = 22;
+
+/// Order in which the bytes of the digest are encoded, in groups of 3 read
+/// as little-endian integers, as in the original implementation.
+const MAP_MD5: [u8; DIGEST_LEN] = [12, 6, 0, 13, 7, 1, 14, 8, 2, 15, 9, 3, 5, 10, 4, 11];
+
+/// Hash string, such as `$1$saltstri$YMyguxXMBpd2TEZ.vS/3q1`, stored on the
+/// stack.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct HashString {
+    buf: [u8; HASH_STRING_MAX_LEN],
+    len: usize,
+}
+
+impl HashString {
+    /// Encode the hash string of `digest`, `salt` and `variant`.
+    ///
+    /// `salt` must have been checked to be valid.
+    pub(crate) fn new(variant: Variant, salt: &str, digest: &[u8; DIGEST_LEN]) -> Self {
+        let mut buf = [0u8; HASH_STRING_MAX_LEN];
+        let mut len = 0;
+        for part in [variant.prefix(), salt, "$"] {
+            buf[len..len + part.len()].copy_from_slice(part.as_bytes());
+            len += part.len();
+        }
+
+        let mut permuted = [0u8; DIGEST_LEN];
+        for (byte, &index) in permuted.iter_mut().zip(MAP_MD5.iter()) {
+            *byte = digest[usize::from(index)];
+        }
+        Base64ShaCrypt::encode(&permuted, &mut buf[len..len + DIGEST_B64_LEN])
+            .expect("digest buffer length");
+        len += DIGEST_B64_LEN;
+
+        Self { buf, len }
+    }
+
+    /// Borrow the hash string as a `str`.
+    pub fn as_str(&self) -> &str {
+        str::from_utf8(&self.buf[..self.len]).expect("hash strings are UTF-8")
+    }
+}
+
+impl AsRef<str> for HashString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for HashString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for HashString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("HashString").field(&self.as_str()).finish()
+    }
+}
+
+/// Fields of a hash string.
+#[derive(Debug)]
+pub(crate) struct Parsed<'a> {
+    pub(crate) variant: Variant,
+    pub(crate) salt: &'a str,
+    pub(crate) digest: [u8; DIGEST_LEN],
+}
+
+/// Parse the hash string `hash`.
+///
+/// Only canonical encodings of the digest, whose unused bits are zero, are
+/// accepted, as those are the only ones MD5-crypt outputs.
+pub(crate) fn parse(hash: &str) -> Result<Parsed<'_>, Error> {
+    let (variant, rest) = strip_prefix(hash)?;
+    let (salt, digest_b64) = rest.split_once('$').ok_or(Error::InvalidHash)?;
+    let salt = check_salt(salt)?;
+    if digest_b64.len() != DIGEST_B64_LEN {
+        return Err(Error::InvalidHash);
+    }
+
+    let mut permuted = [0u8; DIGEST_LEN];
+    match Base64ShaCrypt::decode(digest_b64, &mut permuted) {
+        Ok(decoded) if decoded.len() == DIGEST_LEN => (),
+        _ => return Err(Error::InvalidHash),
+    }
+
+    let mut digest = [0u8; DIGEST_LEN];
+    for (&byte, &index) in permuted.iter().zip(MAP_MD5.iter()) {
+        digest[usize::from(index)] = byte;
+    }
+
+    Ok(Parsed {
+        variant,
+        salt,
+        digest,
+    })
+}
+
+/// Parse the setting `setting`, a prefix followed by a salt, as `crypt(3)`
+/// does: the salt ends at the next `$` or after [`SALT_MAX_LEN`] bytes,
+/// and the characters after it are ignored.
+pub(crate) fn parse_setting(setting: &str) -> Result<(Variant, &str), Error> {
+    let (variant, rest) = strip_prefix(setting)?;
+    let salt = rest.split('$').next().unwrap_or_default();
+
+    // Non-ASCII characters may not be on character boundaries
+    let salt = salt
+        .get(..salt.len().min(SALT_MAX_LEN))
+        .ok_or(Error::InvalidSalt)?;
+
+    Ok((variant, salt))
+}
+
+/// Split the prefix of a variant off `s`.
+fn strip_prefix(s: &str) -> Result<(Variant, &str), Error> {
+    for variant in [Variant::Md5Crypt, Variant::Apr1] {
+        if let Some(rest) = s.strip_prefix(variant.prefix()) {
+            return Ok((variant, rest));
+        }
+    }
+
+    Err(Error::InvalidHash)
+}
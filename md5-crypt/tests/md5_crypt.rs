@@ -0,0 +1,154 @@
+//! Known-answer tests of MD5-crypt hash strings.
+//!
+//! Besides the vectors of glibc and of the Apache documentation, the `$1$`
+//! hashes were computed with `crypt(3)` of libxcrypt, and the `$apr1$` ones
+//! with `openssl passwd -apr1`, which implements Apache's `htpasswd -m`.
+
+use md5_crypt::{crypt, hash, md5_crypt, verify, Error, Variant, HASH_STRING_MAX_LEN};
+
+const TEST_VECTORS: &[(&str, &str)] = &[
+    // glibc `crypt/md5c-test.c`, whose setting `$1$saltstring` truncates the
+    // salt to 8 characters
+    ("Hello world!", "$1$saltstri$YMyguxXMBpd2TEZ.vS/3q1"),
+    // Apache documentation, "Password Formats"
+    ("myPassword", "$apr1$r31.....$HqJZimcKQFAMYayBlzkrA/"),
+    ("", "$1$$qRPK7m23GJusamGpoGLby/"),
+    ("", "$apr1$$J/S5FGXXjRRxbhIznTb/E1"),
+    ("a", "$1$4xXhAZr2$KiPErOgJtCvBlMBA5.PwN."),
+    ("a", "$apr1$4xXhAZr2$WieWNgOAUui3hGBsZYXXj1"),
+    ("abc", "$1$4xXhAZr2$nw0GQ2Nl.7P8zxwbmWZbQ/"),
+    ("message digest", "$1$4xXhAZr2$01sTc2NxWpYFBODFyDncc/"),
+    (
+        "abcdefghijklmnopqrstuvwxyz",
+        "$1$4xXhAZr2$GvYYAHUx.CnjwLbLOzEWw/",
+    ),
+    (
+        "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+        "$1$4xXhAZr2$D8BfJjk94THLKt.fhNbUI1",
+    ),
+    (
+        "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+        "$apr1$4xXhAZr2$lbhA/Ag5kMIQ4ZBZ3WK/B/",
+    ),
+    ("password", "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0"),
+    ("password", "$apr1$4xXhAZr2$so2j04sphqzWHE/aCcP3c0"),
+    ("passé", "$1$ab$twP0BWQP8veMrh0jhR1RG."),
+    ("test", "$1$abc$B..HicC/afMveWeNyfNsf/"),
+    ("test", "$apr1$abc$ot/gVVIUHfDP8w8ZU4.8z1"),
+];
+
+/// Variant and salt of `hash`.
+fn setting(hash: &str) -> (Variant, &str) {
+    let (variant, rest) = match hash.strip_prefix("$1$") {
+        Some(rest) => (Variant::Md5Crypt, rest),
+        None => (Variant::Apr1, hash.strip_prefix("$apr1$").unwrap()),
+    };
+    (variant, rest.split('$').next().unwrap())
+}
+
+#[test]
+fn test_vectors() {
+    for &(password, expected) in TEST_VECTORS {
+        let (variant, salt) = setting(expected);
+        let hash_string = hash(password, salt, variant).unwrap();
+        assert_eq!(hash_string.as_str(), expected);
+        assert!(hash_string.as_str().len() <= HASH_STRING_MAX_LEN);
+
+        assert_eq!(verify(password, expected), Ok(()));
+        assert_eq!(
+            verify(format!("{}x", password), expected),
+            Err(Error::PasswordMismatch)
+        );
+    }
+}
+
+#[test]
+fn crypt_settings() {
+    for &(password, expected) in TEST_VECTORS {
+        assert_eq!(crypt(password, expected).unwrap().as_str(), expected);
+    }
+
+    // Salts are truncated, or end at the next `$`
+    for setting in ["$1$saltstring", "$1$saltstri", "$1$saltstri$"] {
+        assert_eq!(
+            crypt("Hello world!", setting).unwrap().as_str(),
+            "$1$saltstri$YMyguxXMBpd2TEZ.vS/3q1"
+        );
+    }
+    assert_eq!(
+        crypt("", "$1$").unwrap().as_str(),
+        "$1$$qRPK7m23GJusamGpoGLby/"
+    );
+
+    assert_eq!(crypt("password", "$5$saltsalt"), Err(Error::InvalidHash));
+    assert_eq!(
+        crypt("password", "$1$saltstr\u{e9}"),
+        Err(Error::InvalidSalt)
+    );
+}
+
+#[test]
+fn variants_differ_in_prefix_only() {
+    let hash_1 = hash("password", "4xXhAZr2", Variant::Md5Crypt).unwrap();
+    let hash_apr1 = hash("password", "4xXhAZr2", Variant::Apr1).unwrap();
+    assert_ne!(&hash_1.as_str()[3..], &hash_apr1.as_str()[6..]);
+
+    // The prefix is hashed: a hash doesn't verify as the other variant
+    let swapped = hash_1.as_str().replacen("$1$", "$apr1$", 1);
+    assert_eq!(verify("password", &swapped), Err(Error::PasswordMismatch));
+}
+
+#[test]
+fn non_utf8_password() {
+    let hash_string = hash(b"pass\xe9", "ab", Variant::Md5Crypt).unwrap();
+    assert_eq!(verify(b"pass\xe9", hash_string.as_str()), Ok(()));
+    assert_ne!(hash_string.as_str(), "$1$ab$twP0BWQP8veMrh0jhR1RG.");
+}
+
+#[test]
+fn nul_bytes_are_hashed() {
+    assert_ne!(
+        md5_crypt("pass\0word", "ab", Variant::Md5Crypt),
+        md5_crypt("pass", "ab", Variant::Md5Crypt)
+    );
+}
+
+#[test]
+fn invalid_salt() {
+    for salt in ["saltstring", "ab$c"] {
+        assert_eq!(
+            hash("password", salt, Variant::Md5Crypt),
+            Err(Error::InvalidSalt)
+        );
+    }
+    assert_eq!(
+        verify("password", "$1$saltstring$YMyguxXMBpd2TEZ.vS/3q1"),
+        Err(Error::InvalidSalt)
+    );
+}
+
+#[test]
+fn invalid_hash() {
+    for hash in [
+        "",
+        "$1$",
+        "$1$saltstri",
+        "$2b$saltstri$YMyguxXMBpd2TEZ.vS/3q1",
+        "$6$saltstri$YMyguxXMBpd2TEZ.vS/3q1",
+        "$apr$saltstri$YMyguxXMBpd2TEZ.vS/3q1",
+        // Digest length
+        "$1$saltstri$YMyguxXMBpd2TEZ.vS/3q",
+        "$1$saltstri$YMyguxXMBpd2TEZ.vS/3q1.",
+        // Non-canonical digest, characters outside of the alphabet
+        "$1$saltstri$YMyguxXMBpd2TEZ.vS/3q2",
+        "$1$saltstri$YMyguxXMBpd2TEZ+vS/3q1",
+        "$1$saltstri$YMyguxXMBpd2TEZ.vS/3é",
+    ] {
+        assert_eq!(
+            verify("Hello world!", hash),
+            Err(Error::InvalidHash),
+            "{}",
+            hash
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Tests of the `password-hash` API.
+#![cfg(feature = "simple")]
+
+use md5_crypt::{
+    password_hash::{Error, McfHasher, PasswordHash, PasswordVerifier},
+    Md5Crypt, Variant,
+};
+
+const MCF_HASH: &str = "$1$saltstri$YMyguxXMBpd2TEZ.vS/3q1";
+const PHC_HASH: &str = "$md5-crypt$saltstri$s2ISAhS75j+dVBf2JHpNHw";
+const APR1_MCF_HASH: &str = "$apr1$r31.....$HqJZimcKQFAMYayBlzkrA/";
+
+#[test]
+fn upgrade_mcf_hash() {
+    let hash = Md5Crypt.upgrade_mcf_hash(MCF_HASH).unwrap();
+    assert_eq!(hash.to_string(), PHC_HASH);
+    assert_eq!(hash.algorithm, Variant::MD5_CRYPT_IDENT);
+    assert!(Md5Crypt.verify_password(b"Hello world!", &hash).is_ok());
+    assert_eq!(
+        Md5Crypt.verify_password(b"Hello world?", &hash),
+        Err(Error::Password)
+    );
+
+    let hash = Md5Crypt.upgrade_mcf_hash(APR1_MCF_HASH).unwrap();
+    assert_eq!(hash.algorithm, Variant::APR1_CRYPT_IDENT);
+    assert!(Md5Crypt.verify_password(b"myPassword", &hash).is_ok());
+    assert!(Md5Crypt.verify_password(b"Hello world!", &hash).is_err());
+}
+
+#[test]
+fn verify_mcf_hash() {
+    assert!(Md5Crypt.verify_mcf_hash(b"Hello world!", MCF_HASH).is_ok());
+    assert!(Md5Crypt
+        .verify_mcf_hash(b"myPassword", APR1_MCF_HASH)
+        .is_ok());
+    assert!(Md5Crypt.verify_mcf_hash(b"bogus", APR1_MCF_HASH).is_err());
+}
+
+#[test]
+fn verify_phc_hash() {
+    let hash = PasswordHash::new(PHC_HASH).unwrap();
+    assert!(Md5Crypt.verify_password(b"Hello world!", &hash).is_ok());
+
+    for (phc, err) in [
+        (
+            "$sha512-crypt$saltstri$s2ISAhS75j+dVBf2JHpNHw",
+            Error::Algorithm,
+        ),
+        (
+            "$md5-crypt$rounds=1000$saltstri$s2ISAhS75j+dVBf2JHpNHw",
+            Error::ParamNameInvalid,
+        ),
+        (
+            "$md5-crypt$v=1$saltstri$s2ISAhS75j+dVBf2JHpNHw",
+            Error::Version,
+        ),
+    ] {
+        let hash = PasswordHash::new(phc).unwrap();
+        assert_eq!(Md5Crypt.verify_password(b"Hello world!", &hash), Err(err));
+    }
+}
+
+#[test]
+fn upgrade_invalid_mcf_hash() {
+    assert_eq!(
+        Md5Crypt.upgrade_mcf_hash("$6$salt$hash"),
+        Err(Error::Algorithm)
+    );
+    assert_eq!(
+        Md5Crypt.upgrade_mcf_hash("$1$saltstri$bogus"),
+        Err(Error::PhcStringField)
+    );
+
+    // PHC salts have at least 4 characters
+    assert!(matches!(
+        Md5Crypt.upgrade_mcf_hash("$1$abc$B..HicC/afMveWeNyfNsf/"),
+        Err(Error::SaltInvalid(_))
+    ));
+}
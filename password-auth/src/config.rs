@@ -0,0 +1,463 @@
+//! Portable configuration of the password hashing algorithm, to exchange
+//! through configuration files and admin APIs with serde.
+//!
+//! A [`KdfConfig`] names the algorithm with its `kdf` field, next to its
+//! parameters:
+//!
+//! ```
+//! # #[cfg(feature = "scrypt")]
+//! # {
+//! use password_auth::config::KdfConfig;
+//!
+//! let config: KdfConfig = serde_json::from_str(r#"{"kdf": "scrypt", "log_n": 10, "r": 8, "p": 1}"#)?;
+//!
+//! let hash = config.hash("password").expect("config is valid");
+//! assert!(config.verify("password", &hash).is_ok());
+//! assert!(config.verify("bogus", &hash).is_err());
+//! # }
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+//!
+//! Configs are validated as they are deserialized: unknown algorithms and
+//! fields are rejected, and so are out-of-range values, with an error
+//! naming the offending field, such as ``invalid value for `rounds`:
+//! expected at least 1``.
+//!
+//! # Compatibility
+//!
+//! New algorithms are added as new variants, and new parameters as new
+//! fields with a default: configs of older versions keep deserializing to
+//! the same config. Configs of a new variant are rejected with an
+//! `unknown variant` error by older versions, which must be upgraded first
+//! when rolling out such configs.
+//!
+//! [`KdfConfig`] is `#[non_exhaustive]`, so that adding variants isn't a
+//! breaking change of this crate: `match` a config with a wildcard arm.
+//! Variants only exist with the crate feature of their algorithm.
+
+use crate::VerifyError;
+use alloc::string::{String, ToString};
+use core::fmt;
+use password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use rand_core::OsRng;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "pbkdf2")]
+use pbkdf2::Pbkdf2;
+#[cfg(feature = "scrypt")]
+use scrypt::Scrypt;
+
+/// Password hashing algorithm and its parameters.
+///
+/// Serializes as a map of the fields of the variant, tagged with a `kdf`
+/// field, e.g. `{"kdf": "scrypt", "log_n": 17, "r": 8, "p": 1,
+/// "output_length": 32}`. A missing `algorithm` or `output_length`
+/// deserializes to its default, given in the docs of each field.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum KdfConfig {
+    /// PBKDF2, with `"kdf": "pbkdf2"`.
+    #[cfg(feature = "pbkdf2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "pbkdf2")))]
+    Pbkdf2 {
+        /// PRF of PBKDF2, as its PHC ident: `pbkdf2-sha256` (default) or
+        /// `pbkdf2-sha512`.
+        algorithm: pbkdf2::Algorithm,
+
+        /// Number of rounds, at least 1.
+        rounds: u32,
+
+        /// Length of the hash in bytes, from 10 to 64 (default 32).
+        output_length: usize,
+    },
+
+    /// scrypt, with `"kdf": "scrypt"`.
+    #[cfg(feature = "scrypt")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "scrypt")))]
+    Scrypt {
+        /// log₂ of the CPU/memory cost `N`.
+        log_n: u8,
+
+        /// Block size.
+        r: u32,
+
+        /// Parallelism.
+        p: u32,
+
+        /// Length of the hash in bytes, from 10 to 64 (default 32).
+        output_length: usize,
+    },
+}
+
+impl KdfConfig {
+    /// Check the parameters of the config, as deserializing does.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match *self {
+            #[cfg(feature = "pbkdf2")]
+            KdfConfig::Pbkdf2 { .. } => self.pbkdf2_params().map(drop),
+            #[cfg(feature = "scrypt")]
+            KdfConfig::Scrypt { .. } => self.scrypt_params().map(drop),
+        }
+    }
+
+    /// Hash `password` with the algorithm and parameters of the config and
+    /// a random salt from the OS.
+    ///
+    /// # Returns
+    /// - `Ok(String)` containing the PHC string of the hash
+    /// - `Err(HashError::Config)` if the parameters of the config are invalid
+    /// - `Err(HashError::Hash)` if hashing the password failed.
+    pub fn hash(&self, password: impl AsRef<[u8]>) -> Result<String, HashError> {
+        let password = password.as_ref();
+        let salt = SaltString::generate(OsRng);
+
+        let hash = match *self {
+            #[cfg(feature = "pbkdf2")]
+            KdfConfig::Pbkdf2 { algorithm, .. } => {
+                let params = self.pbkdf2_params()?;
+                Pbkdf2.hash_password_customized(
+                    password,
+                    Some(algorithm.ident()),
+                    None,
+                    params,
+                    &salt,
+                )
+            }
+            #[cfg(feature = "scrypt")]
+            KdfConfig::Scrypt { .. } => {
+                let params = self.scrypt_params()?;
+                Scrypt.hash_password_customized(password, None, None, params, &salt)
+            }
+        };
+
+        Ok(hash.map_err(HashError::Hash)?.to_string())
+    }
+
+    /// Verify `password` against the PHC string `hash` with the algorithm
+    /// of the config, whose parameters are those of `hash`.
+    ///
+    /// Hashes of other algorithms fail to verify: use
+    /// [`verify_password`](crate::verify_password) to verify hashes of any
+    /// enabled algorithm.
+    pub fn verify(&self, password: impl AsRef<[u8]>, hash: &str) -> Result<(), VerifyError> {
+        let password = password.as_ref();
+        let hash = PasswordHash::new(hash).map_err(|_| VerifyError)?;
+
+        let verifier: &dyn PasswordVerifier = match self {
+            #[cfg(feature = "pbkdf2")]
+            KdfConfig::Pbkdf2 { .. } => &Pbkdf2,
+            #[cfg(feature = "scrypt")]
+            KdfConfig::Scrypt { .. } => &Scrypt,
+        };
+
+        verifier
+            .verify_password(password, &hash)
+            .map_err(|_| VerifyError)
+    }
+
+    /// PBKDF2 params of the config.
+    ///
+    /// # Returns
+    /// - `Ok(pbkdf2::Params)` if the config is [`KdfConfig::Pbkdf2`] and
+    ///   its parameters are valid
+    /// - `Err(ConfigError)` naming the invalid field otherwise, or the
+    ///   `kdf` field if the config is of another algorithm.
+    #[cfg(feature = "pbkdf2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "pbkdf2")))]
+    pub fn pbkdf2_params(&self) -> Result<pbkdf2::Params, ConfigError> {
+        #[allow(unreachable_patterns)]
+        let (rounds, output_length) = match *self {
+            KdfConfig::Pbkdf2 {
+                rounds,
+                output_length,
+                ..
+            } => (rounds, output_length),
+            _ => return Err(ConfigError::new("kdf", "pbkdf2")),
+        };
+
+        if rounds == 0 {
+            return Err(ConfigError::new("rounds", "at least 1"));
+        }
+        if !(OUTPUT_LENGTH_MIN..=OUTPUT_LENGTH_MAX).contains(&output_length) {
+            return Err(ConfigError::new("output_length", "from 10 to 64"));
+        }
+
+        Ok(pbkdf2::Params {
+            rounds,
+            output_length,
+        })
+    }
+
+    /// scrypt params of the config.
+    ///
+    /// The parameters must be within [`scrypt::ParamsPolicy::DEFAULT`] and
+    /// [`scrypt::MemoryLimit::DEFAULT`], which [`Scrypt`] enforces when
+    /// hashing passwords.
+    ///
+    /// # Returns
+    /// - `Ok(scrypt::Params)` if the config is [`KdfConfig::Scrypt`] and
+    ///   its parameters are valid
+    /// - `Err(ConfigError)` naming the invalid field otherwise, or the
+    ///   `kdf` field if the config is of another algorithm.
+    #[cfg(feature = "scrypt")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "scrypt")))]
+    pub fn scrypt_params(&self) -> Result<scrypt::Params, ConfigError> {
+        #[allow(unreachable_patterns)]
+        let (log_n, r, p, output_length) = match *self {
+            KdfConfig::Scrypt {
+                log_n,
+                r,
+                p,
+                output_length,
+            } => (log_n, r, p, output_length),
+            _ => return Err(ConfigError::new("kdf", "scrypt")),
+        };
+
+        // The PHC strings of scrypt limit the output length as PBKDF2 does
+        if !(OUTPUT_LENGTH_MIN..=OUTPUT_LENGTH_MAX).contains(&output_length) {
+            return Err(ConfigError::new("output_length", "from 10 to 64"));
+        }
+
+        let params = scrypt::Params::new(log_n, r, p, output_length).map_err(|err| {
+            let field = match err.param() {
+                "len" => "output_length",
+                param => param,
+            };
+            ConfigError::new(field, err.expected())
+        })?;
+
+        // The bounds of `ParamsPolicy::DEFAULT`
+        scrypt::ParamsPolicy::DEFAULT
+            .check(&params)
+            .map_err(|err| match err {
+                scrypt::errors::Error::ParamsPolicyViolation { param: "r", .. } => {
+                    ConfigError::new("r", "at most 32")
+                }
+                scrypt::errors::Error::ParamsPolicyViolation { param: "p", .. } => {
+                    ConfigError::new("p", "at most 16")
+                }
+                _ => ConfigError::new("log_n", "at most 24"),
+            })?;
+
+        scrypt::MemoryLimit::DEFAULT
+            .check(&params)
+            .map_err(|_| ConfigError::new("log_n", "at most 2 GiB of memory with `r` and `p`"))?;
+
+        Ok(params)
+    }
+}
+
+#[cfg(feature = "pbkdf2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "pbkdf2")))]
+impl TryFrom<KdfConfig> for pbkdf2::Params {
+    type Error = ConfigError;
+
+    fn try_from(config: KdfConfig) -> Result<Self, ConfigError> {
+        config.pbkdf2_params()
+    }
+}
+
+#[cfg(feature = "scrypt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "scrypt")))]
+impl TryFrom<KdfConfig> for scrypt::Params {
+    type Error = ConfigError;
+
+    fn try_from(config: KdfConfig) -> Result<Self, ConfigError> {
+        config.scrypt_params()
+    }
+}
+
+#[cfg(feature = "pbkdf2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "pbkdf2")))]
+impl From<pbkdf2::Params> for KdfConfig {
+    /// PBKDF2 config of `params`, with the default PRF.
+    fn from(params: pbkdf2::Params) -> Self {
+        KdfConfig::Pbkdf2 {
+            algorithm: pbkdf2::Algorithm::default(),
+            rounds: params.rounds,
+            output_length: params.output_length,
+        }
+    }
+}
+
+/// Invalid [`KdfConfig`] field, naming the field and the values it
+/// accepts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ConfigError {
+    field: &'static str,
+    expected: &'static str,
+}
+
+impl ConfigError {
+    const fn new(field: &'static str, expected: &'static str) -> Self {
+        Self { field, expected }
+    }
+
+    /// Name of the invalid field, e.g. `rounds` or `log_n`.
+    pub fn field(&self) -> &'static str {
+        self.field
+    }
+
+    /// Description of the values accepted for the field, e.g.
+    /// `at least 1`.
+    pub fn expected(&self) -> &'static str {
+        self.expected
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid value for `{}`: expected {}",
+            self.field, self.expected
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for ConfigError {}
+
+/// Error of [`KdfConfig::hash`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum HashError {
+    /// The parameters of the config are invalid.
+    Config(ConfigError),
+
+    /// Hashing the password failed.
+    Hash(password_hash::Error),
+}
+
+impl From<ConfigError> for HashError {
+    fn from(err: ConfigError) -> Self {
+        HashError::Config(err)
+    }
+}
+
+impl fmt::Display for HashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashError::Config(err) => err.fmt(f),
+            HashError::Hash(err) => write!(f, "password hashing error: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for HashError {}
+
+/// Shortest output length of PHC strings.
+const OUTPUT_LENGTH_MIN: usize = 10;
+
+/// Longest output length of PHC strings.
+const OUTPUT_LENGTH_MAX: usize = 64;
+
+/// Default output length of both algorithms.
+const OUTPUT_LENGTH_DEFAULT: usize = 32;
+
+/// Serialized form of [`KdfConfig`].
+#[derive(Serialize, Deserialize)]
+#[serde(
+    rename = "KdfConfig",
+    tag = "kdf",
+    rename_all = "lowercase",
+    deny_unknown_fields
+)]
+enum KdfConfigRepr {
+    #[cfg(feature = "pbkdf2")]
+    Pbkdf2 {
+        #[serde(default = "default_pbkdf2_algorithm")]
+        algorithm: String,
+        rounds: u32,
+        #[serde(default = "default_output_length")]
+        output_length: usize,
+    },
+    #[cfg(feature = "scrypt")]
+    Scrypt {
+        log_n: u8,
+        r: u32,
+        p: u32,
+        #[serde(default = "default_output_length")]
+        output_length: usize,
+    },
+}
+
+#[cfg(feature = "pbkdf2")]
+fn default_pbkdf2_algorithm() -> String {
+    pbkdf2::Algorithm::default().as_str().into()
+}
+
+fn default_output_length() -> usize {
+    OUTPUT_LENGTH_DEFAULT
+}
+
+impl Serialize for KdfConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr = match *self {
+            #[cfg(feature = "pbkdf2")]
+            KdfConfig::Pbkdf2 {
+                algorithm,
+                rounds,
+                output_length,
+            } => KdfConfigRepr::Pbkdf2 {
+                algorithm: algorithm.as_str().into(),
+                rounds,
+                output_length,
+            },
+            #[cfg(feature = "scrypt")]
+            KdfConfig::Scrypt {
+                log_n,
+                r,
+                p,
+                output_length,
+            } => KdfConfigRepr::Scrypt {
+                log_n,
+                r,
+                p,
+                output_length,
+            },
+        };
+
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for KdfConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<KdfConfig, D::Error> {
+        let config = match KdfConfigRepr::deserialize(deserializer)? {
+            #[cfg(feature = "pbkdf2")]
+            KdfConfigRepr::Pbkdf2 {
+                algorithm,
+                rounds,
+                output_length,
+            } => KdfConfig::Pbkdf2 {
+                algorithm: algorithm.parse().map_err(|_| {
+                    de::Error::custom(ConfigError::new(
+                        "algorithm",
+                        "pbkdf2-sha256 or pbkdf2-sha512",
+                    ))
+                })?,
+                rounds,
+                output_length,
+            },
+            #[cfg(feature = "scrypt")]
+            KdfConfigRepr::Scrypt {
+                log_n,
+                r,
+                p,
+                output_length,
+            } => KdfConfig::Scrypt {
+                log_n,
+                r,
+                p,
+                output_length,
+            },
+        };
+
+        config.validate().map_err(de::Error::custom)?;
+        Ok(config)
+    }
+}
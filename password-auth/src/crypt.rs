@@ -0,0 +1,167 @@
+//! Hashing and verifying the hash strings of `crypt(3)`, such as those of
+//! `/etc/shadow`, dispatched on their `$<ID>$` prefix.
+//!
+//! Unlike the rest of this crate, which works with PHC strings, these
+//! functions take the hash strings of `crypt(3)` as is. Each scheme is
+//! handled by the crate of its algorithm, when its crate feature is
+//! enabled:
+//!
+//! | Prefix                 | Scheme    | Feature     |
+//! |------------------------|-----------|-------------|
+//! | `$1$`, `$apr1$`        | MD5-crypt | `md5-crypt` |
+//! | `$2a$`, `$2b$`, `$2y$` | bcrypt    | `bcrypt`    |
+//! | `$5$`, `$6$`           | SHA-crypt | `sha-crypt` |
+//! | `$7$`                  | scrypt    | `scrypt`    |
+//! | `$y$`                  | yescrypt  | `yescrypt`  |
+//!
+//! Other schemes, and the ones whose feature is disabled, are rejected with
+//! [`CryptError::UnsupportedScheme`]. `$y$` hashes can be verified but not
+//! computed: [`crypt`] rejects `$y$` settings as unsupported.
+//!
+//! ```
+//! # #[cfg(feature = "sha-crypt")]
+//! # {
+//! use password_auth::crypt::{self, CryptError};
+//!
+//! let hash = "$5$rounds=10000$saltsalt$a6WJS3V6B3leg7T3.ELC5.vcUmHOyFDvLaurLBy.mc8";
+//! assert_eq!(crypt::verify("password", hash), Ok(()));
+//! assert_eq!(crypt::verify("bogus", hash), Err(CryptError::PasswordMismatch));
+//!
+//! let hash = "$3$$8846f7eaee8fb117ad06bdd830b7586c";
+//! assert_eq!(
+//!     crypt::verify("password", hash),
+//!     Err(CryptError::UnsupportedScheme("$3$".into()))
+//! );
+//! # }
+//! ```
+
+use alloc::{format, string::String};
+use core::fmt;
+
+#[cfg(any(feature = "bcrypt", feature = "md5-crypt"))]
+use alloc::string::ToString;
+
+/// Error of [`crypt`] and [`verify`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CryptError {
+    /// No enabled backend handles the scheme of the setting or hash string,
+    /// given as its `$<ID>$` prefix, or empty if it has none, as the
+    /// traditional DES hashes.
+    UnsupportedScheme(String),
+
+    /// The setting or hash string is malformed, or invalid for its scheme.
+    InvalidHash,
+
+    /// The password doesn't match the hash string.
+    PasswordMismatch,
+}
+
+impl fmt::Display for CryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptError::UnsupportedScheme(prefix) if prefix.is_empty() => {
+                f.write_str("unsupported crypt scheme without prefix")
+            }
+            CryptError::UnsupportedScheme(prefix) => {
+                write!(f, "unsupported crypt scheme `{}`", prefix)
+            }
+            CryptError::InvalidHash => f.write_str("invalid crypt setting or hash string"),
+            CryptError::PasswordMismatch => f.write_str("password doesn't match"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for CryptError {}
+
+/// Hash `password` with `setting`, as `crypt(3)` does, with the backend of
+/// the scheme of `setting`.
+///
+/// A hash string is a valid setting as well, which gives back the same hash
+/// string for the right password. To verify passwords, use [`verify`]
+/// instead, which compares the hashes in constant time.
+///
+/// # Returns
+/// - `Ok(String)` containing the hash string
+/// - `Err(CryptError::UnsupportedScheme)` if no enabled backend hashes
+///   passwords with the scheme of `setting`
+/// - `Err(CryptError::InvalidHash)` if `setting` is invalid for its scheme.
+// Without backends, the password is unused
+#[allow(unused_variables)]
+pub fn crypt(password: impl AsRef<[u8]>, setting: &str) -> Result<String, CryptError> {
+    match scheme(setting) {
+        #[cfg(feature = "md5-crypt")]
+        Some("1" | "apr1") => md5_crypt::crypt(password, setting)
+            .map(|hash| hash.to_string())
+            .map_err(|_| CryptError::InvalidHash),
+        #[cfg(feature = "bcrypt")]
+        Some("2a" | "2b" | "2y") => bcrypt::crypt(password, setting)
+            .map(|hash| hash.to_string())
+            .map_err(|_| CryptError::InvalidHash),
+        #[cfg(feature = "sha-crypt")]
+        Some("5" | "6") => sha_crypt::crypt(password, setting).map_err(|_| CryptError::InvalidHash),
+        #[cfg(feature = "scrypt")]
+        Some("7") => {
+            scrypt::crypt::crypt(password.as_ref(), setting).map_err(|_| CryptError::InvalidHash)
+        }
+        id => Err(unsupported(id)),
+    }
+}
+
+/// Verify `password` against the hash string `hash`, with the backend of
+/// its scheme.
+///
+/// # Returns
+/// - `Ok(())` if the password matches
+/// - `Err(CryptError::PasswordMismatch)` if it doesn't
+/// - `Err(CryptError::UnsupportedScheme)` if no enabled backend verifies
+///   the scheme of `hash`
+/// - `Err(CryptError::InvalidHash)` if `hash` is invalid for its scheme.
+// Without backends, the password is unused
+#[allow(unused_variables)]
+pub fn verify(password: impl AsRef<[u8]>, hash: &str) -> Result<(), CryptError> {
+    match scheme(hash) {
+        #[cfg(feature = "md5-crypt")]
+        Some("1" | "apr1") => md5_crypt::verify(password, hash).map_err(|err| match err {
+            md5_crypt::Error::PasswordMismatch => CryptError::PasswordMismatch,
+            _ => CryptError::InvalidHash,
+        }),
+        #[cfg(feature = "bcrypt")]
+        Some("2a" | "2b" | "2y") => bcrypt::verify(password, hash).map_err(|err| match err {
+            bcrypt::Error::PasswordMismatch => CryptError::PasswordMismatch,
+            _ => CryptError::InvalidHash,
+        }),
+        #[cfg(feature = "sha-crypt")]
+        Some("5" | "6") => sha_crypt::check(password, hash).map_err(|err| match err {
+            sha_crypt::CheckError::HashMismatch => CryptError::PasswordMismatch,
+            _ => CryptError::InvalidHash,
+        }),
+        #[cfg(feature = "scrypt")]
+        Some("7") => scrypt::crypt::verify(password.as_ref(), hash).map_err(|err| match err {
+            scrypt::errors::CryptError::PasswordMismatch => CryptError::PasswordMismatch,
+            _ => CryptError::InvalidHash,
+        }),
+        #[cfg(feature = "yescrypt")]
+        Some("y") => scrypt::yescrypt::verify(password.as_ref(), hash).map_err(|err| match err {
+            scrypt::errors::YescryptError::PasswordMismatch => CryptError::PasswordMismatch,
+            _ => CryptError::InvalidHash,
+        }),
+        id => Err(unsupported(id)),
+    }
+}
+
+/// `<ID>` of the `$<ID>$` prefix of a setting or hash string, if any.
+fn scheme(s: &str) -> Option<&str> {
+    s.strip_prefix('$')
+        .and_then(|rest| rest.split_once('$'))
+        .map(|(id, _)| id)
+}
+
+/// Error of the scheme `id`.
+fn unsupported(id: Option<&str>) -> CryptError {
+    CryptError::UnsupportedScheme(match id {
+        Some(id) => format!("${}$", id),
+        None => String::new(),
+    })
+}
@@ -1,4 +1,5 @@
 #![no_std]
+#![cfg_attr(docsrs, feature(doc_cfg))]
 #![doc = include_str!("../README.md")]
 #![doc(
     html_logo_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg",
@@ -20,10 +21,26 @@ extern crate alloc;
 #[cfg(feature = "std")]
 extern crate std;
 
+#[cfg(all(feature = "serde", any(feature = "pbkdf2", feature = "scrypt")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod config;
+pub mod crypt;
+pub mod migrate;
+pub mod registry;
+
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub mod offload;
+
+#[cfg(feature = "worker")]
+#[cfg_attr(docsrs, doc(cfg(feature = "worker")))]
+pub mod worker;
+
 use alloc::string::{String, ToString};
 use core::fmt;
-use password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use password_hash::{PasswordHash, PasswordHasher, SaltString};
 use rand_core::OsRng;
+use registry::Registry;
 
 #[cfg(not(any(feature = "argon2", feature = "pbkdf2", feature = "scrypt")))]
 compile_error!(
@@ -80,18 +97,8 @@ fn generate_phc_hash<'a>(
 
 /// Verify the provided password against the provided password hash.
 pub fn verify_password(password: impl AsRef<[u8]>, hash: &str) -> Result<(), VerifyError> {
-    let hash = PasswordHash::new(hash).map_err(|_| VerifyError)?;
-
-    let algs: &[&dyn PasswordVerifier] = &[
-        #[cfg(feature = "argon2")]
-        &Argon2::default(),
-        #[cfg(feature = "pbkdf2")]
-        &Pbkdf2,
-        #[cfg(feature = "scrypt")]
-        &Scrypt,
-    ];
-
-    hash.verify_password(algs, password)
+    Registry::with_enabled_algorithms()
+        .verify(password, hash)
         .map_err(|_| VerifyError)
 }
 
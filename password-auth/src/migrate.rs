@@ -0,0 +1,243 @@
+//! Moving password hashes to another algorithm or params as users log in.
+//!
+//! A password hash can only be recomputed when the password is known, so
+//! hashes are upgraded on login: [`verify_and_rehash`] verifies the stored
+//! hash with a [`Registry`], whatever its algorithm, and hashes the password
+//! again with the target algorithm and params, so that it can be stored
+//! instead.
+
+use crate::registry::{Registry, RegistryError};
+use alloc::string::{String, ToString};
+use password_hash::{Ident, ParamsString, PasswordHash, PasswordHasher, SaltString};
+use rand_core::CryptoRngCore;
+
+/// Verify `password` against the `stored` PHC string with `registry`, and
+/// hash it again if `stored` doesn't use the target algorithm and params.
+///
+/// The new hash is computed by `target` with the `target_alg` ident and
+/// `target_params`, with a salt generated from `rng`.
+///
+/// # Returns
+/// - `Ok(Some(hash))` if the password matches, with the new hash to store
+/// - `Ok(None)` if the password matches and `stored` already uses the
+///   target algorithm and params
+/// - `Err(RegistryError)` if the password doesn't match, or `stored` can't
+///   be verified, in which case nothing is hashed.
+///
+/// The version of `stored` isn't compared: only hashers with a single
+/// version, or hashing with their latest version, should be targets.
+pub fn verify_and_rehash<H: PasswordHasher>(
+    registry: &Registry<'_>,
+    password: impl AsRef<[u8]>,
+    stored: &str,
+    target: &H,
+    target_alg: Ident<'_>,
+    target_params: H::Params,
+    rng: impl CryptoRngCore,
+) -> Result<Option<String>, RegistryError> {
+    let password = password.as_ref();
+    let stored = PasswordHash::new(stored).map_err(RegistryError::InvalidHash)?;
+    registry.verify_hash(password, &stored)?;
+
+    if stored.algorithm == target_alg && has_params(&stored, target_params.clone()) {
+        return Ok(None);
+    }
+
+    let salt = SaltString::generate(rng);
+    target
+        .hash_password_customized(password, Some(target_alg), None, target_params, &salt)
+        .map(|hash| Some(hash.to_string()))
+        .map_err(RegistryError::InvalidHash)
+}
+
+/// Whether the params of `hash` are `params`, written in the same order.
+fn has_params<P>(hash: &PasswordHash<'_>, params: P) -> bool
+where
+    P: for<'a> TryFrom<&'a PasswordHash<'a>, Error = password_hash::Error>
+        + TryInto<ParamsString, Error = password_hash::Error>,
+{
+    let stored: Result<ParamsString, _> = P::try_from(hash).and_then(TryInto::try_into);
+    let target: Result<ParamsString, _> = params.try_into();
+
+    match (stored, target) {
+        (Ok(stored), Ok(target)) => stored == target,
+        _ => false,
+    }
+}
+
+#[cfg(all(test, feature = "pbkdf2", feature = "scrypt"))]
+mod tests {
+    use super::verify_and_rehash;
+    use crate::registry::{Registry, RegistryError};
+    use password_hash::PasswordHash;
+    use pbkdf2::{Algorithm, Pbkdf2};
+    use rand_core::{CryptoRng, RngCore};
+    use scrypt::Scrypt;
+
+    /// PBKDF2 hash for the string "password".
+    const PBKDF2_HASH: &str =
+        "$pbkdf2-sha256$i=4096,l=32$c2FsdA$xeR41ZKIyEGqUw22hFxMjZYok6ABzk4RpJY4c6qYE0o";
+
+    /// scrypt hash for the string "password".
+    const SCRYPT_HASH: &str =
+        "$scrypt$ln=16,r=8,p=1$aM15713r3Xsvxbi31lqr1Q$nFNh2CVHVjNldFVKDHDlm4CbdRSCdEBsjjJxD+iCs5E";
+
+    const PBKDF2_PARAMS: pbkdf2::Params = pbkdf2::Params {
+        rounds: 4096,
+        output_length: 32,
+    };
+
+    /// Fills everything with `0x42`, so that the salt is known.
+    struct FixedRng;
+
+    impl RngCore for FixedRng {
+        fn next_u32(&mut self) -> u32 {
+            0x4242_4242
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            0x4242_4242_4242_4242
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            dest.fill(0x42);
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl CryptoRng for FixedRng {}
+
+    fn scrypt_params() -> scrypt::Params {
+        scrypt::Params::new(4, 8, 1, 32).expect("scrypt params")
+    }
+
+    #[test]
+    fn pbkdf2_to_scrypt() {
+        let registry = Registry::with_enabled_algorithms();
+        let rehashed = verify_and_rehash(
+            &registry,
+            "password",
+            PBKDF2_HASH,
+            &Scrypt,
+            scrypt::ALG_ID,
+            scrypt_params(),
+            FixedRng,
+        )
+        .expect("verified")
+        .expect("rehashed");
+
+        assert!(rehashed.starts_with("$scrypt$ln=4,r=8,p=1$QkJCQkJCQkJCQkJCQkJCQg$"));
+        assert_eq!(registry.verify("password", &rehashed), Ok(()));
+
+        // Already migrated
+        assert_eq!(
+            verify_and_rehash(
+                &registry,
+                "password",
+                &rehashed,
+                &Scrypt,
+                scrypt::ALG_ID,
+                scrypt_params(),
+                FixedRng,
+            ),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn scrypt_to_pbkdf2() {
+        let registry = Registry::with_enabled_algorithms();
+        let rehashed = verify_and_rehash(
+            &registry,
+            "password",
+            SCRYPT_HASH,
+            &Pbkdf2,
+            Algorithm::PBKDF2_SHA256_IDENT,
+            PBKDF2_PARAMS,
+            FixedRng,
+        )
+        .expect("verified")
+        .expect("rehashed");
+
+        let hash = PasswordHash::new(&rehashed).expect("PHC string");
+        assert_eq!(hash.algorithm, Algorithm::PBKDF2_SHA256_IDENT);
+        assert_eq!(registry.verify("password", &rehashed), Ok(()));
+    }
+
+    #[test]
+    fn already_migrated() {
+        let registry = Registry::with_enabled_algorithms();
+        assert_eq!(
+            verify_and_rehash(
+                &registry,
+                "password",
+                PBKDF2_HASH,
+                &Pbkdf2,
+                Algorithm::PBKDF2_SHA256_IDENT,
+                PBKDF2_PARAMS,
+                FixedRng,
+            ),
+            Ok(None)
+        );
+
+        // Other params or algorithm of the same hasher
+        for (alg, rounds) in [
+            (Algorithm::PBKDF2_SHA256_IDENT, 8192),
+            (Algorithm::PBKDF2_SHA512_IDENT, 4096),
+        ] {
+            let params = pbkdf2::Params {
+                rounds,
+                ..PBKDF2_PARAMS
+            };
+            let rehashed = verify_and_rehash(
+                &registry,
+                "password",
+                PBKDF2_HASH,
+                &Pbkdf2,
+                alg,
+                params,
+                FixedRng,
+            )
+            .expect("verified")
+            .expect("rehashed");
+            assert!(rehashed.starts_with(&alloc::format!("${}$i={},", alg, rounds)));
+        }
+    }
+
+    #[test]
+    fn wrong_password_never_rehashes() {
+        let registry = Registry::with_enabled_algorithms();
+
+        for stored in [PBKDF2_HASH, SCRYPT_HASH] {
+            assert_eq!(
+                verify_and_rehash(
+                    &registry,
+                    "bogus",
+                    stored,
+                    &Scrypt,
+                    scrypt::ALG_ID,
+                    scrypt_params(),
+                    FixedRng,
+                ),
+                Err(RegistryError::PasswordMismatch)
+            );
+        }
+
+        assert_eq!(
+            verify_and_rehash(
+                &Registry::new(),
+                "password",
+                PBKDF2_HASH,
+                &Scrypt,
+                scrypt::ALG_ID,
+                scrypt_params(),
+                FixedRng,
+            ),
+            Err(RegistryError::UnknownAlgorithm)
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Hashing and verifying passwords from async code without blocking it.
+//!
+//! Password hashes are slow by design, and computing one in an async task
+//! blocks its executor thread for as long. The functions of this module
+//! compute them on the blocking thread pool of the tokio runtime instead,
+//! with [`tokio::task::spawn_blocking`], and must be called from within a
+//! runtime.
+//!
+//! The password is copied for the blocking task, and the copy is zeroized
+//! once the hash is computed.
+//!
+//! # Cancellation
+//!
+//! Dropping a future of this module before it completes cancels the work if
+//! it hasn't started yet. A hash already being computed can't be stopped:
+//! it runs to completion, and its result is dropped without being returned
+//! to anyone.
+
+use crate::{registry::Registry, VerifyError};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use password_hash::{PasswordHasher, SaltString};
+use rand_core::OsRng;
+use tokio::task::{JoinError, JoinHandle};
+use zeroize::Zeroizing;
+
+/// Hash `password` with `hasher` and `params` on the blocking thread pool,
+/// with a random salt from the OS.
+///
+/// # Returns
+/// - `Ok(String)` containing the PHC string of the hash
+/// - `Err(password_hash::Error)` if `params` are invalid for `hasher`, or
+///   `Err(password_hash::Error::Crypto)` if the runtime shut down before
+///   the hash was computed.
+///
+/// # Panics
+/// Panics if `hasher` panics, or if called outside of a tokio runtime.
+pub async fn hash_password_async<H>(
+    hasher: H,
+    password: impl AsRef<[u8]>,
+    params: H::Params,
+) -> password_hash::Result<String>
+where
+    H: PasswordHasher + Send + 'static,
+    H::Params: Send + 'static,
+{
+    let password = copy(password.as_ref());
+
+    spawn(move || {
+        let salt = SaltString::generate(OsRng);
+        hasher
+            .hash_password_customized(&password, None, None, params, &salt)
+            .map(|hash| hash.to_string())
+    })
+    .await
+    .unwrap_or(Err(password_hash::Error::Crypto))
+}
+
+/// Verify `password` against the PHC string `hash` on the blocking thread
+/// pool, with the algorithms enabled by crate features, as
+/// [`verify_password`](crate::verify_password) does.
+///
+/// # Returns
+/// - `Ok(())` if the password matches
+/// - `Err(VerifyError)` if it doesn't, if `hash` can't be verified, or if
+///   the runtime shut down before the hash was verified.
+///
+/// # Panics
+/// Panics if called outside of a tokio runtime.
+pub async fn verify_password_async(
+    password: impl AsRef<[u8]>,
+    hash: impl Into<String>,
+) -> Result<(), VerifyError> {
+    let password = copy(password.as_ref());
+    let hash = hash.into();
+
+    spawn(move || {
+        Registry::with_enabled_algorithms()
+            .verify(&*password, &hash)
+            .map_err(|_| VerifyError)
+    })
+    .await
+    .unwrap_or(Err(VerifyError))
+}
+
+/// Copy of a password, zeroized when dropped.
+fn copy(password: &[u8]) -> Zeroizing<Vec<u8>> {
+    Zeroizing::new(password.to_vec())
+}
+
+/// Run `f` on the blocking thread pool, resuming its panics.
+///
+/// # Returns
+/// - `Ok(T)` containing the output of `f`
+/// - `Err(JoinError)` if the runtime shut down before running `f`.
+async fn spawn<F, T>(f: F) -> Result<T, JoinError>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let mut task = AbortOnDrop(tokio::task::spawn_blocking(f));
+
+    match (&mut task.0).await {
+        Ok(output) => Ok(output),
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Handle aborting its task when dropped, which only prevents a blocking
+/// task from starting: once started, it always runs to completion.
+struct AbortOnDrop<T>(JoinHandle<T>);
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(all(test, feature = "pbkdf2", feature = "scrypt"))]
+mod tests {
+    use super::{hash_password_async, verify_password_async};
+    use pbkdf2::Pbkdf2;
+    use scrypt::Scrypt;
+    use std::time::{Duration, Instant};
+
+    const EXAMPLE_PASSWORD: &str = "password";
+
+    /// PBKDF2 params taking hundreds of milliseconds.
+    const SLOW_PARAMS: pbkdf2::Params = pbkdf2::Params {
+        rounds: 1_000_000,
+        output_length: 32,
+    };
+
+    #[tokio::test]
+    async fn pbkdf2_round_trip() {
+        let params = pbkdf2::Params {
+            rounds: 1000,
+            output_length: 32,
+        };
+        let hash = hash_password_async(Pbkdf2, EXAMPLE_PASSWORD, params)
+            .await
+            .expect("hash");
+        assert!(hash.starts_with("$pbkdf2-sha256$i=1000,l=32$"));
+
+        assert!(verify_password_async(EXAMPLE_PASSWORD, hash.as_str())
+            .await
+            .is_ok());
+        assert!(verify_password_async("bogus", hash).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn scrypt_round_trip() {
+        let params = scrypt::Params::new(10, 8, 1, 32).expect("params");
+        let hash = hash_password_async(Scrypt, EXAMPLE_PASSWORD, params)
+            .await
+            .expect("hash");
+        assert!(hash.starts_with("$scrypt$ln=10,r=8,p=1$"));
+
+        assert!(verify_password_async(EXAMPLE_PASSWORD, hash.as_str())
+            .await
+            .is_ok());
+        assert!(verify_password_async("bogus", hash).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn invalid_params() {
+        let params = pbkdf2::Params {
+            rounds: 1000,
+            output_length: 0,
+        };
+        assert!(hash_password_async(Pbkdf2, EXAMPLE_PASSWORD, params)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn invalid_hash() {
+        assert!(verify_password_async(EXAMPLE_PASSWORD, "$bogus$")
+            .await
+            .is_err());
+    }
+
+    /// The runtime of `#[tokio::test]` has a single thread, which a hash
+    /// computed on it would block until it completes.
+    #[tokio::test]
+    async fn executor_not_blocked() {
+        let start = Instant::now();
+
+        let (hashed, timer) = tokio::join!(
+            async {
+                hash_password_async(Pbkdf2, EXAMPLE_PASSWORD, SLOW_PARAMS)
+                    .await
+                    .expect("hash");
+                start.elapsed()
+            },
+            async {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                start.elapsed()
+            },
+        );
+
+        assert!(
+            timer < hashed,
+            "timer fired after {timer:?}, hash {hashed:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn cancellation() {
+        let hash = hash_password_async(Pbkdf2, EXAMPLE_PASSWORD, SLOW_PARAMS);
+        assert!(tokio::time::timeout(Duration::from_millis(10), hash)
+            .await
+            .is_err());
+
+        // The runtime is still usable while the dropped hash completes
+        let verified = verify_password_async(
+            EXAMPLE_PASSWORD,
+            "$pbkdf2-sha256$i=4096,l=32$c2FsdA$xeR41ZKIyEGqUw22hFxMjZYok6ABzk4RpJY4c6qYE0o",
+        );
+        assert!(verified.await.is_ok());
+    }
+}
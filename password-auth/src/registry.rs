@@ -0,0 +1,359 @@
+//! Verifiers of several password hashing algorithms, keyed by PHC ident.
+//!
+//! A table of users can hold PHC strings of several algorithms side by
+//! side, e.g. `$pbkdf2-sha256$` strings of old accounts and `$scrypt$`
+//! strings of new ones. A [`Registry`] picks the verifier of each string
+//! from its ident, and tells an unknown algorithm from a wrong password:
+//!
+//! ```
+//! # #[cfg(feature = "pbkdf2")]
+//! # {
+//! use password_auth::registry::{Registry, RegistryError};
+//!
+//! let mut registry = Registry::new();
+//! registry.register_pbkdf2();
+//!
+//! let hash = "$pbkdf2-sha256$i=4096,l=32$c2FsdA$xeR41ZKIyEGqUw22hFxMjZYok6ABzk4RpJY4c6qYE0o";
+//! assert_eq!(registry.verify("password", hash), Ok(()));
+//! assert_eq!(registry.verify("bogus", hash), Err(RegistryError::PasswordMismatch));
+//!
+//! let hash = "$bogus$c2FsdA$xeR41ZKIyEGqUw22hFxMjZYok6ABzk4RpJY4c6qYE0o";
+//! assert_eq!(registry.verify("password", hash), Err(RegistryError::UnknownAlgorithm));
+//! # }
+//! ```
+
+use alloc::vec::Vec;
+use core::fmt;
+use password_hash::{Ident, PasswordHash, PasswordVerifier};
+
+#[cfg(feature = "argon2")]
+use argon2::Argon2;
+
+/// Error of [`Registry::verify`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RegistryError {
+    /// The password hash isn't a valid PHC string, or isn't valid for its
+    /// algorithm, e.g. because of unsupported params.
+    InvalidHash(password_hash::Error),
+
+    /// No verifier is registered for the ident of the password hash.
+    UnknownAlgorithm,
+
+    /// The password doesn't match the password hash.
+    PasswordMismatch,
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::InvalidHash(err) => write!(f, "invalid password hash: {}", err),
+            RegistryError::UnknownAlgorithm => f.write_str("unknown password hash algorithm"),
+            RegistryError::PasswordMismatch => f.write_str("password doesn't match"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for RegistryError {}
+
+/// Verifiers of password hashes, keyed by the ident of their algorithm.
+#[derive(Clone, Default)]
+pub struct Registry<'a> {
+    verifiers: Vec<(Ident<'a>, &'a dyn PasswordVerifier)>,
+}
+
+impl<'a> Registry<'a> {
+    /// Create a registry without any verifier.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with the verifiers of all the algorithms enabled
+    /// by crate features.
+    pub fn with_enabled_algorithms() -> Self {
+        let mut registry = Self::new();
+
+        #[cfg(feature = "argon2")]
+        registry.register_argon2();
+        #[cfg(feature = "bcrypt-pbkdf")]
+        registry.register_bcrypt_pbkdf();
+        #[cfg(feature = "md5-crypt")]
+        registry.register_md5_crypt();
+        #[cfg(feature = "pbkdf2")]
+        registry.register_pbkdf2();
+        #[cfg(feature = "scrypt")]
+        registry.register_scrypt();
+        #[cfg(feature = "sha-crypt")]
+        registry.register_sha_crypt();
+
+        registry
+    }
+
+    /// Register `verifier` for the hashes with `ident`, replacing the
+    /// verifier previously registered for it.
+    pub fn register(&mut self, ident: Ident<'a>, verifier: &'a dyn PasswordVerifier) -> &mut Self {
+        match self.verifiers.iter_mut().find(|(id, _)| *id == ident) {
+            Some((_, registered)) => *registered = verifier,
+            None => self.verifiers.push((ident, verifier)),
+        }
+
+        self
+    }
+
+    /// Verifier registered for the hashes with `ident`.
+    pub fn get(&self, ident: Ident<'_>) -> Option<&'a dyn PasswordVerifier> {
+        self.verifiers
+            .iter()
+            .find(|(id, _)| id.as_str() == ident.as_str())
+            .map(|(_, verifier)| *verifier)
+    }
+
+    /// Verify `password` against the PHC string `hash`.
+    pub fn verify(&self, password: impl AsRef<[u8]>, hash: &str) -> Result<(), RegistryError> {
+        let hash = PasswordHash::new(hash).map_err(RegistryError::InvalidHash)?;
+        self.verify_hash(password, &hash)
+    }
+
+    /// Verify `password` against an already parsed password hash.
+    pub fn verify_hash(
+        &self,
+        password: impl AsRef<[u8]>,
+        hash: &PasswordHash<'_>,
+    ) -> Result<(), RegistryError> {
+        let verifier = self
+            .get(hash.algorithm)
+            .ok_or(RegistryError::UnknownAlgorithm)?;
+
+        verifier
+            .verify_password(password.as_ref(), hash)
+            .map_err(|err| match err {
+                password_hash::Error::Password => RegistryError::PasswordMismatch,
+                err => RegistryError::InvalidHash(err),
+            })
+    }
+
+    /// Register the verifier of `$argon2d$`, `$argon2i$` and `$argon2id$`
+    /// hashes, without secret key.
+    #[cfg(feature = "argon2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "argon2")))]
+    pub fn register_argon2(&mut self) -> &mut Self {
+        self.register(argon2::ARGON2D_IDENT, &Argon2Verifier)
+            .register(argon2::ARGON2I_IDENT, &Argon2Verifier)
+            .register(argon2::ARGON2ID_IDENT, &Argon2Verifier)
+    }
+
+    /// Register the verifier of `$bcrypt-pbkdf$` hashes.
+    #[cfg(feature = "bcrypt-pbkdf")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bcrypt-pbkdf")))]
+    pub fn register_bcrypt_pbkdf(&mut self) -> &mut Self {
+        self.register(bcrypt_pbkdf::ALG_ID, &bcrypt_pbkdf::BcryptPbkdfHasher)
+    }
+
+    /// Register the verifier of the legacy `$md5-crypt$` and `$apr1-crypt$`
+    /// hashes, `$1$` and `$apr1$` hashes upgraded to PHC strings, to
+    /// migrate them.
+    #[cfg(feature = "md5-crypt")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "md5-crypt")))]
+    pub fn register_md5_crypt(&mut self) -> &mut Self {
+        use md5_crypt::{Md5Crypt, Variant};
+
+        self.register(Variant::MD5_CRYPT_IDENT, &Md5Crypt)
+            .register(Variant::APR1_CRYPT_IDENT, &Md5Crypt)
+    }
+
+    /// Register the verifier of `$pbkdf2-sha256$` and `$pbkdf2-sha512$`
+    /// hashes.
+    #[cfg(feature = "pbkdf2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "pbkdf2")))]
+    pub fn register_pbkdf2(&mut self) -> &mut Self {
+        use pbkdf2::{Algorithm, Pbkdf2};
+
+        self.register(Algorithm::PBKDF2_SHA256_IDENT, &Pbkdf2)
+            .register(Algorithm::PBKDF2_SHA512_IDENT, &Pbkdf2)
+    }
+
+    /// Register the verifier of `$scrypt$` hashes.
+    #[cfg(feature = "scrypt")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "scrypt")))]
+    pub fn register_scrypt(&mut self) -> &mut Self {
+        self.register(scrypt::ALG_ID, &scrypt::Scrypt)
+    }
+
+    /// Register the verifier of `$sha256-crypt$` and `$sha512-crypt$`
+    /// hashes, e.g. `$5$` and `$6$` hashes of `crypt(3)` upgraded to PHC
+    /// strings.
+    #[cfg(feature = "sha-crypt")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sha-crypt")))]
+    pub fn register_sha_crypt(&mut self) -> &mut Self {
+        use sha_crypt::{Algorithm, ShaCrypt};
+
+        self.register(Algorithm::SHA256_CRYPT_IDENT, &ShaCrypt)
+            .register(Algorithm::SHA512_CRYPT_IDENT, &ShaCrypt)
+    }
+}
+
+impl fmt::Debug for Registry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.verifiers.iter().map(|(ident, _)| ident))
+            .finish()
+    }
+}
+
+/// [`Argon2`] with its default context, which has a `'static` value unlike
+/// [`Argon2::default`].
+///
+/// The algorithm, version and params are those of the hash being verified.
+#[cfg(feature = "argon2")]
+struct Argon2Verifier;
+
+#[cfg(feature = "argon2")]
+impl PasswordVerifier for Argon2Verifier {
+    fn verify_password(
+        &self,
+        password: &[u8],
+        hash: &PasswordHash<'_>,
+    ) -> password_hash::Result<()> {
+        Argon2::default().verify_password(password, hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Registry, RegistryError};
+
+    /// Hashes of the string "password".
+    #[cfg(feature = "argon2")]
+    const ARGON2_HASH: &str =
+        "$argon2i$v=19$m=65536,t=1,p=1$c29tZXNhbHQAAAAAAAAAAA$+r0d29hqEB0yasKr55ZgICsQGSkl0v0kgwhd+U3wyRo";
+    #[cfg(feature = "pbkdf2")]
+    const PBKDF2_HASH: &str =
+        "$pbkdf2-sha256$i=4096,l=32$c2FsdA$xeR41ZKIyEGqUw22hFxMjZYok6ABzk4RpJY4c6qYE0o";
+    #[cfg(feature = "scrypt")]
+    const SCRYPT_HASH: &str =
+        "$scrypt$ln=16,r=8,p=1$aM15713r3Xsvxbi31lqr1Q$nFNh2CVHVjNldFVKDHDlm4CbdRSCdEBsjjJxD+iCs5E";
+
+    /// Hashes of all enabled algorithms.
+    const HASHES: &[&str] = &[
+        #[cfg(feature = "argon2")]
+        ARGON2_HASH,
+        #[cfg(feature = "pbkdf2")]
+        PBKDF2_HASH,
+        #[cfg(feature = "scrypt")]
+        SCRYPT_HASH,
+    ];
+
+    #[test]
+    fn mixed_algorithms() {
+        let registry = Registry::with_enabled_algorithms();
+
+        for hash in HASHES {
+            assert_eq!(registry.verify("password", hash), Ok(()), "{}", hash);
+            assert_eq!(
+                registry.verify("bogus", hash),
+                Err(RegistryError::PasswordMismatch),
+                "{}",
+                hash
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_algorithm() {
+        let registry = Registry::new();
+        for hash in HASHES {
+            assert_eq!(
+                registry.verify("password", hash),
+                Err(RegistryError::UnknownAlgorithm)
+            );
+        }
+
+        assert_eq!(
+            Registry::with_enabled_algorithms().verify(
+                "password",
+                "$bogus$c2FsdA$xeR41ZKIyEGqUw22hFxMjZYok6ABzk4RpJY4c6qYE0o"
+            ),
+            Err(RegistryError::UnknownAlgorithm)
+        );
+    }
+
+    #[test]
+    fn invalid_hash() {
+        assert!(matches!(
+            Registry::with_enabled_algorithms().verify("password", "password"),
+            Err(RegistryError::InvalidHash(_))
+        ));
+    }
+
+    #[cfg(all(feature = "pbkdf2", feature = "scrypt"))]
+    #[test]
+    fn registered_algorithms_only() {
+        let mut registry = Registry::new();
+        registry.register_scrypt();
+
+        assert_eq!(registry.verify("password", SCRYPT_HASH), Ok(()));
+        assert_eq!(
+            registry.verify("password", PBKDF2_HASH),
+            Err(RegistryError::UnknownAlgorithm)
+        );
+
+        // Registering again replaces the verifier
+        registry.register(scrypt::ALG_ID, &pbkdf2::Pbkdf2);
+        assert!(matches!(
+            registry.verify("password", SCRYPT_HASH),
+            Err(RegistryError::InvalidHash(_))
+        ));
+        assert_eq!(alloc::format!("{:?}", registry), "[Ident(\"scrypt\")]");
+    }
+
+    #[cfg(feature = "md5-crypt")]
+    #[test]
+    fn md5_crypt() {
+        use alloc::string::ToString;
+        use md5_crypt::Md5Crypt;
+        use password_hash::McfHasher;
+
+        let registry = Registry::with_enabled_algorithms();
+        for mcf in [
+            "$1$5pZSV9va$azfrPr6af3Fc7dLblQXVa0",
+            "$apr1$4xXhAZr2$so2j04sphqzWHE/aCcP3c0",
+        ] {
+            let hash = Md5Crypt.upgrade_mcf_hash(mcf).expect("upgrade");
+            assert_eq!(registry.verify("password", &hash.to_string()), Ok(()));
+            assert_eq!(
+                registry.verify("bogus", &hash.to_string()),
+                Err(RegistryError::PasswordMismatch)
+            );
+        }
+    }
+
+    #[cfg(feature = "sha-crypt")]
+    #[test]
+    fn sha_crypt() {
+        use alloc::string::ToString;
+        use password_hash::{PasswordHasher, SaltString};
+        use sha_crypt::ShaCrypt;
+
+        let salt = SaltString::from_b64("saltstring").expect("salt");
+        let hash = ShaCrypt.hash_password(b"password", &salt).expect("hash");
+        let registry = Registry::with_enabled_algorithms();
+        assert_eq!(registry.verify("password", &hash.to_string()), Ok(()));
+        assert_eq!(
+            registry.verify("bogus", &hash.to_string()),
+            Err(RegistryError::PasswordMismatch)
+        );
+    }
+
+    #[cfg(feature = "bcrypt-pbkdf")]
+    #[test]
+    fn bcrypt_pbkdf() {
+        let hash = "$bcrypt-pbkdf$r=4,l=32$c2FsdA$W78MwpNYfxw2NVVcJ3llmNR+V5Bxv0J+nY++hCq6NNk";
+        let registry = Registry::with_enabled_algorithms();
+        assert_eq!(registry.verify("password", hash), Ok(()));
+        assert_eq!(
+            registry.verify("bogus", hash),
+            Err(RegistryError::PasswordMismatch)
+        );
+    }
+}
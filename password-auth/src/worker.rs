@@ -0,0 +1,670 @@
+//! Hashing and verifying passwords on a dedicated pool of threads, with a
+//! bounded queue.
+//!
+//! Password hashes are slow by design: a burst of logins hashed on the
+//! threads serving requests starves the rest of the process. A
+//! [`PasswordWorker`] computes them on its own threads instead, and queues
+//! at most as many jobs as configured: past that, new jobs are rejected
+//! right away with [`WorkerError::QueueFull`], e.g. to answer with a
+//! "503 Service Unavailable", so that logins degrade gracefully.
+//!
+//! Each job gives a [`JobHandle`] to its result, which either blocks until
+//! the job completes with [`JobHandle::wait`], or is awaited as a future
+//! with any async runtime.
+//!
+//! ```
+//! # #[cfg(feature = "pbkdf2")]
+//! # {
+//! use core::num::NonZeroUsize;
+//! use password_auth::worker::PasswordWorker;
+//! use pbkdf2::{Params, Pbkdf2};
+//!
+//! let worker = PasswordWorker::new(NonZeroUsize::new(2).unwrap(), 16)?;
+//!
+//! let params = Params { rounds: 1000, output_length: 32 };
+//! let hash = worker.hash(Pbkdf2, "password", params)?.wait()?.expect("params are valid");
+//!
+//! assert!(worker.verify("password", hash.as_str())?.wait()?.is_ok());
+//! assert!(worker.verify("bogus", hash)?.wait()?.is_err());
+//! # }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! # Passwords
+//!
+//! The password of a job is copied into the queue, and the copy is
+//! zeroized once the job completes, or when the job is dropped without
+//! running, e.g. when rejected because the queue is full.
+//!
+//! # Shutdown
+//!
+//! Dropping a [`PasswordWorker`], or calling [`PasswordWorker::shutdown`],
+//! stops accepting jobs but drains the queue: it blocks until the jobs
+//! already queued complete, and their handles get their results.
+
+use crate::{registry::Registry, VerifyError};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::{
+    fmt, mem,
+    num::NonZeroUsize,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+use password_hash::{PasswordHasher, SaltString};
+use rand_core::OsRng;
+use std::{
+    io,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Condvar, Mutex, MutexGuard, PoisonError,
+    },
+    thread::{self, JoinHandle},
+};
+use zeroize::Zeroizing;
+
+/// Error of the jobs of a [`PasswordWorker`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WorkerError {
+    /// The queue is full: the job was rejected without running.
+    QueueFull,
+
+    /// The job was dropped without running, because the threads of the
+    /// worker stopped.
+    Cancelled,
+}
+
+impl fmt::Display for WorkerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkerError::QueueFull => f.write_str("password worker queue is full"),
+            WorkerError::Cancelled => f.write_str("password worker job was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for WorkerError {}
+
+/// Job queued for the threads of a worker.
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Pool of threads hashing and verifying passwords, with a bounded queue.
+///
+/// See the [module docs](self).
+pub struct PasswordWorker {
+    sender: Option<SyncSender<Job>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl PasswordWorker {
+    /// Start a worker with `threads` threads, queueing at most
+    /// `queue_depth` jobs on top of those running.
+    ///
+    /// With a `queue_depth` of 0, jobs are only accepted when a thread is
+    /// idle.
+    ///
+    /// # Returns
+    /// - `Ok(PasswordWorker)` once its threads are started
+    /// - `Err(io::Error)` if a thread can't be started.
+    pub fn new(threads: NonZeroUsize, queue_depth: usize) -> io::Result<Self> {
+        let (sender, receiver) = mpsc::sync_channel::<Job>(queue_depth);
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let mut worker = Self {
+            sender: Some(sender),
+            threads: Vec::with_capacity(threads.get()),
+        };
+        for index in 0..threads.get() {
+            let receiver = Arc::clone(&receiver);
+            let thread = thread::Builder::new()
+                .name(format!("password-worker-{}", index))
+                .spawn(move || run(&receiver))?;
+            worker.threads.push(thread);
+        }
+
+        Ok(worker)
+    }
+
+    /// Queue hashing `password` with `hasher` and `params`, with a random
+    /// salt from the OS.
+    ///
+    /// # Returns
+    /// - `Ok(JobHandle)` to the PHC string of the hash, or the error of
+    ///   `hasher` if `params` are invalid
+    /// - `Err(WorkerError::QueueFull)` if the queue is full.
+    pub fn hash<H>(
+        &self,
+        hasher: H,
+        password: impl AsRef<[u8]>,
+        params: H::Params,
+    ) -> Result<JobHandle<password_hash::Result<String>>, WorkerError>
+    where
+        H: PasswordHasher + Send + 'static,
+        H::Params: Send + 'static,
+    {
+        let password = Zeroizing::new(password.as_ref().to_vec());
+
+        self.submit(move || {
+            let salt = SaltString::generate(OsRng);
+            hasher
+                .hash_password_customized(&password, None, None, params, &salt)
+                .map(|hash| hash.to_string())
+        })
+    }
+
+    /// Queue verifying `password` against the PHC string `hash`, with the
+    /// algorithms enabled by crate features, as
+    /// [`verify_password`](crate::verify_password) does.
+    ///
+    /// # Returns
+    /// - `Ok(JobHandle)` to the result of the verification
+    /// - `Err(WorkerError::QueueFull)` if the queue is full.
+    pub fn verify(
+        &self,
+        password: impl AsRef<[u8]>,
+        hash: impl Into<String>,
+    ) -> Result<JobHandle<Result<(), VerifyError>>, WorkerError> {
+        let password = Zeroizing::new(password.as_ref().to_vec());
+        let hash = hash.into();
+
+        self.submit(move || {
+            Registry::with_enabled_algorithms()
+                .verify(&*password, &hash)
+                .map_err(|_| VerifyError)
+        })
+    }
+
+    /// Stop accepting jobs, and wait for the queued jobs to complete and
+    /// for the threads to stop, as dropping the worker does.
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    /// Queue `f`, whose captures are dropped with the job if it's rejected.
+    fn submit<F, T>(&self, f: F) -> Result<JobHandle<T>, WorkerError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let slot = Arc::new(Slot::new());
+        let completer = Completer(Arc::clone(&slot));
+        let job: Job = Box::new(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(f));
+            completer.complete(result);
+        });
+
+        match &self.sender {
+            Some(sender) => match sender.try_send(job) {
+                Ok(()) => Ok(JobHandle { slot }),
+                Err(TrySendError::Full(_)) => Err(WorkerError::QueueFull),
+                Err(TrySendError::Disconnected(_)) => Err(WorkerError::Cancelled),
+            },
+            None => Err(WorkerError::Cancelled),
+        }
+    }
+
+    /// Close the queue, and join the threads once they drained it.
+    fn stop(&mut self) {
+        self.sender = None;
+
+        for thread in self.threads.drain(..) {
+            // Panics of jobs are caught and given to their handles
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for PasswordWorker {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl fmt::Debug for PasswordWorker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PasswordWorker")
+            .field("threads", &self.threads.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Run the jobs of `receiver` until it's closed and drained.
+fn run(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        // The lock is released before running the job
+        let job = lock(receiver).recv();
+        match job {
+            Ok(job) => job(),
+            Err(_) => return,
+        }
+    }
+}
+
+/// Handle to the result of a job of a [`PasswordWorker`].
+///
+/// Either block until the job completes with [`JobHandle::wait`], or await
+/// the handle: its result is the output of the job, or
+/// `Err(WorkerError::Cancelled)` if the job was dropped without running.
+/// Dropping the handle doesn't cancel the job.
+///
+/// If the job panicked, waiting for or awaiting its handle resumes the
+/// panic.
+#[derive(Debug)]
+pub struct JobHandle<T> {
+    slot: Arc<Slot<T>>,
+}
+
+impl<T> JobHandle<T> {
+    /// Block until the job completes, and get its result.
+    pub fn wait(self) -> Result<T, WorkerError> {
+        let mut state = lock(&self.slot.state);
+        while let State::Pending(_) = *state {
+            state = self
+                .slot
+                .ready
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+
+        state.take()
+    }
+}
+
+impl<T> core::future::Future for JobHandle<T> {
+    type Output = Result<T, WorkerError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = lock(&self.slot.state);
+        match &mut *state {
+            State::Pending(waker) => {
+                *waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            _ => Poll::Ready(state.take()),
+        }
+    }
+}
+
+/// Result of a job, shared by its handle and the job.
+#[derive(Debug)]
+struct Slot<T> {
+    state: Mutex<State<T>>,
+    ready: Condvar,
+}
+
+impl<T> Slot<T> {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(State::Pending(None)),
+            ready: Condvar::new(),
+        }
+    }
+}
+
+/// State of a job.
+enum State<T> {
+    /// Not completed yet, with the waker of the last poll of its handle.
+    Pending(Option<Waker>),
+
+    /// Completed with its output, or its panic.
+    Done(thread::Result<T>),
+
+    /// Dropped without running.
+    Dropped,
+
+    /// Result taken by the handle.
+    Taken,
+}
+
+impl<T> State<T> {
+    /// Take the result of a job which isn't pending anymore.
+    fn take(&mut self) -> Result<T, WorkerError> {
+        match mem::replace(self, State::Taken) {
+            State::Done(Ok(output)) => Ok(output),
+            State::Done(Err(payload)) => panic::resume_unwind(payload),
+            State::Dropped => Err(WorkerError::Cancelled),
+            State::Pending(_) | State::Taken => {
+                unreachable!("result of a pending or already awaited job")
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for State<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            State::Pending(_) => "Pending",
+            State::Done(_) => "Done",
+            State::Dropped => "Dropped",
+            State::Taken => "Taken",
+        })
+    }
+}
+
+/// Side of a [`Slot`] owned by the job, marking the job dropped if it's
+/// dropped without completing.
+struct Completer<T>(Arc<Slot<T>>);
+
+impl<T> Completer<T> {
+    fn complete(self, result: thread::Result<T>) {
+        self.set(State::Done(result));
+    }
+
+    /// Set the state of a pending job, waking its handle.
+    fn set(&self, new: State<T>) {
+        let mut state = lock(&self.0.state);
+        let waker = match &mut *state {
+            State::Pending(waker) => waker.take(),
+            _ => return,
+        };
+        *state = new;
+        drop(state);
+
+        self.0.ready.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Drop for Completer<T> {
+    fn drop(&mut self) {
+        self.set(State::Dropped);
+    }
+}
+
+/// Lock `mutex`, which jobs never panic while holding.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(all(test, feature = "pbkdf2"))]
+mod tests {
+    use super::{JobHandle, PasswordWorker, WorkerError};
+    use alloc::{format, string::String, vec::Vec};
+    use core::num::NonZeroUsize;
+    use password_hash::{Decimal, Ident, PasswordHash, PasswordHasher, Salt};
+    use pbkdf2::{Params, Pbkdf2};
+    use std::{
+        sync::{mpsc, Arc, Barrier},
+        thread,
+        time::Duration,
+    };
+
+    const EXAMPLE_PASSWORD: &str = "password";
+
+    const PARAMS: Params = Params {
+        rounds: 1000,
+        output_length: 32,
+    };
+
+    fn worker(threads: usize, queue_depth: usize) -> PasswordWorker {
+        PasswordWorker::new(NonZeroUsize::new(threads).expect("threads"), queue_depth)
+            .expect("worker")
+    }
+
+    /// PBKDF2 hasher which tells when it starts, and waits for the barrier
+    /// before hashing.
+    struct Gated {
+        started: mpsc::Sender<()>,
+        barrier: Arc<Barrier>,
+    }
+
+    impl PasswordHasher for Gated {
+        type Params = Params;
+
+        fn hash_password_customized<'a>(
+            &self,
+            password: &[u8],
+            alg_id: Option<Ident<'a>>,
+            version: Option<Decimal>,
+            params: Params,
+            salt: impl Into<Salt<'a>>,
+        ) -> password_hash::Result<PasswordHash<'a>> {
+            self.started.send(()).expect("started");
+            self.barrier.wait();
+            Pbkdf2.hash_password_customized(password, alg_id, version, params, salt)
+        }
+    }
+
+    /// Queue a [`Gated`] job, retrying until a thread is idle for queues of
+    /// depth 0, and wait for it to start.
+    fn start_gated(
+        worker: &PasswordWorker,
+    ) -> (JobHandle<password_hash::Result<String>>, Arc<Barrier>) {
+        loop {
+            let (started, receiver) = mpsc::channel();
+            let barrier = Arc::new(Barrier::new(2));
+            let gated = Gated {
+                started,
+                barrier: Arc::clone(&barrier),
+            };
+
+            match worker.hash(gated, EXAMPLE_PASSWORD, PARAMS) {
+                Ok(handle) => {
+                    receiver.recv().expect("started");
+                    return (handle, barrier);
+                }
+                Err(WorkerError::QueueFull) => thread::yield_now(),
+                Err(err) => unreachable!("{}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn hash_and_verify() {
+        let worker = worker(2, 4);
+        let hash = worker
+            .hash(Pbkdf2, EXAMPLE_PASSWORD, PARAMS)
+            .expect("queued")
+            .wait()
+            .expect("completed")
+            .expect("hash");
+        assert!(hash.starts_with("$pbkdf2-sha256$i=1000,l=32$"));
+
+        let verified = worker
+            .verify(EXAMPLE_PASSWORD, hash.as_str())
+            .expect("queued");
+        assert!(verified.wait().expect("completed").is_ok());
+        let verified = worker.verify("bogus", hash).expect("queued");
+        assert!(verified.wait().expect("completed").is_err());
+    }
+
+    #[test]
+    fn invalid_params() {
+        let params = Params {
+            rounds: 1000,
+            output_length: 0,
+        };
+        let hashed = worker(1, 1).hash(Pbkdf2, EXAMPLE_PASSWORD, params);
+        assert!(hashed.expect("queued").wait().expect("completed").is_err());
+    }
+
+    #[test]
+    fn queue_full() {
+        let worker = worker(1, 1);
+
+        // The job running on the only thread, and the one queued
+        let (running, barrier) = start_gated(&worker);
+        let queued = worker
+            .hash(Pbkdf2, EXAMPLE_PASSWORD, PARAMS)
+            .expect("queued");
+
+        for _ in 0..3 {
+            assert_eq!(
+                worker.verify(EXAMPLE_PASSWORD, "$bogus$").map(drop),
+                Err(WorkerError::QueueFull)
+            );
+        }
+
+        barrier.wait();
+        assert!(running.wait().expect("completed").is_ok());
+        assert!(queued.wait().expect("completed").is_ok());
+
+        // The queue has room again
+        let verified = worker.verify(EXAMPLE_PASSWORD, "$bogus$").expect("queued");
+        assert!(verified.wait().expect("completed").is_err());
+    }
+
+    #[test]
+    fn rendezvous_queue() {
+        let worker = worker(1, 0);
+        let (running, barrier) = start_gated(&worker);
+        assert_eq!(
+            worker.hash(Pbkdf2, EXAMPLE_PASSWORD, PARAMS).map(drop),
+            Err(WorkerError::QueueFull)
+        );
+
+        barrier.wait();
+        assert!(running.wait().expect("completed").is_ok());
+    }
+
+    /// Jobs complete in any order, and each handle gets the result of its
+    /// own job.
+    #[test]
+    fn ordering_independence() {
+        let worker = worker(4, 16);
+
+        let slow = Params {
+            rounds: 100_000,
+            output_length: 32,
+        };
+        let handles = (0..8)
+            .map(|i| {
+                let password = format!("password {}", i);
+                let params = if i % 2 == 0 { slow } else { PARAMS };
+                let handle = worker.hash(Pbkdf2, &password, params).expect("queued");
+                (password, handle)
+            })
+            .collect::<Vec<_>>();
+
+        // Waited for in reverse order of submission
+        for (password, handle) in handles.into_iter().rev() {
+            let hash = handle.wait().expect("completed").expect("hash");
+            let verified = worker.verify(&password, hash.as_str()).expect("queued");
+            assert!(verified.wait().expect("completed").is_ok(), "{}", password);
+            let verified = worker.verify("bogus", hash).expect("queued");
+            assert!(verified.wait().expect("completed").is_err(), "{}", password);
+        }
+    }
+
+    /// Shutting down waits for the queued jobs, which all complete.
+    #[test]
+    fn shutdown_drains_queue() {
+        let worker = worker(1, 4);
+
+        let (running, barrier) = start_gated(&worker);
+        let queued = (0..4)
+            .map(|_| {
+                worker
+                    .hash(Pbkdf2, EXAMPLE_PASSWORD, PARAMS)
+                    .expect("queued")
+            })
+            .collect::<Vec<_>>();
+
+        let shutdown = thread::spawn(move || worker.shutdown());
+        thread::sleep(Duration::from_millis(10));
+        assert!(!shutdown.is_finished());
+
+        barrier.wait();
+        shutdown.join().expect("shutdown");
+        assert!(running.wait().expect("completed").is_ok());
+        for handle in queued {
+            assert!(handle.wait().expect("completed").is_ok());
+        }
+    }
+
+    /// Rejected jobs are dropped right away, with the password copy.
+    #[test]
+    fn rejected_jobs_dropped() {
+        struct Tracked {
+            _count: Arc<()>,
+        }
+
+        impl PasswordHasher for Tracked {
+            type Params = Params;
+
+            fn hash_password_customized<'a>(
+                &self,
+                password: &[u8],
+                alg_id: Option<Ident<'a>>,
+                version: Option<Decimal>,
+                params: Params,
+                salt: impl Into<Salt<'a>>,
+            ) -> password_hash::Result<PasswordHash<'a>> {
+                Pbkdf2.hash_password_customized(password, alg_id, version, params, salt)
+            }
+        }
+
+        let worker = worker(1, 0);
+        let (running, barrier) = start_gated(&worker);
+
+        let tracker = Arc::new(());
+        let rejected = worker.hash(
+            Tracked {
+                _count: Arc::clone(&tracker),
+            },
+            EXAMPLE_PASSWORD,
+            PARAMS,
+        );
+        assert_eq!(rejected.map(drop), Err(WorkerError::QueueFull));
+        assert_eq!(Arc::strong_count(&tracker), 1);
+
+        barrier.wait();
+        assert!(running.wait().expect("completed").is_ok());
+    }
+
+    #[test]
+    fn panics_are_resumed() {
+        struct Panicking;
+
+        impl PasswordHasher for Panicking {
+            type Params = Params;
+
+            #[allow(clippy::panic)]
+            fn hash_password_customized<'a>(
+                &self,
+                _password: &[u8],
+                _alg_id: Option<Ident<'a>>,
+                _version: Option<Decimal>,
+                _params: Params,
+                _salt: impl Into<Salt<'a>>,
+            ) -> password_hash::Result<PasswordHash<'a>> {
+                std::panic::panic_any("hasher panicked")
+            }
+        }
+
+        let worker = worker(1, 1);
+        let handle = worker
+            .hash(Panicking, EXAMPLE_PASSWORD, PARAMS)
+            .expect("queued");
+        let panicked = std::panic::catch_unwind(move || handle.wait());
+        assert!(panicked.is_err());
+
+        // The thread keeps running jobs
+        let hashed = worker
+            .hash(Pbkdf2, EXAMPLE_PASSWORD, PARAMS)
+            .expect("queued");
+        assert!(hashed.wait().expect("completed").is_ok());
+    }
+
+    #[tokio::test]
+    async fn awaited() {
+        let worker = worker(2, 4);
+        let hash = worker
+            .hash(Pbkdf2, EXAMPLE_PASSWORD, PARAMS)
+            .expect("queued")
+            .await
+            .expect("completed")
+            .expect("hash");
+
+        let verified = worker.verify(EXAMPLE_PASSWORD, hash).expect("queued");
+        assert!(verified.await.expect("completed").is_ok());
+    }
+}
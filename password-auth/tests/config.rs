@@ -0,0 +1,242 @@
+//! Serialization of `KdfConfig` with the `serde` feature.
+
+#![cfg(all(feature = "serde", feature = "pbkdf2", feature = "scrypt"))]
+
+use password_auth::config::{HashError, KdfConfig};
+
+const PBKDF2_JSON: &str =
+    r#"{"kdf":"pbkdf2","algorithm":"pbkdf2-sha512","rounds":1000,"output_length":64}"#;
+
+const SCRYPT_JSON: &str = r#"{"kdf":"scrypt","log_n":10,"r":8,"p":1,"output_length":32}"#;
+
+#[test]
+fn json_round_trip() {
+    let config: KdfConfig = serde_json::from_str(PBKDF2_JSON).unwrap();
+    assert_eq!(
+        config,
+        KdfConfig::Pbkdf2 {
+            algorithm: pbkdf2::Algorithm::Pbkdf2Sha512,
+            rounds: 1000,
+            output_length: 64,
+        }
+    );
+    assert_eq!(serde_json::to_string(&config).unwrap(), PBKDF2_JSON);
+
+    let config: KdfConfig = serde_json::from_str(SCRYPT_JSON).unwrap();
+    assert_eq!(
+        config,
+        KdfConfig::Scrypt {
+            log_n: 10,
+            r: 8,
+            p: 1,
+            output_length: 32,
+        }
+    );
+    assert_eq!(serde_json::to_string(&config).unwrap(), SCRYPT_JSON);
+}
+
+#[test]
+fn defaults() {
+    let config: KdfConfig = serde_json::from_str(r#"{"kdf":"pbkdf2","rounds":1000}"#).unwrap();
+    assert_eq!(
+        config,
+        KdfConfig::Pbkdf2 {
+            algorithm: pbkdf2::Algorithm::Pbkdf2Sha256,
+            rounds: 1000,
+            output_length: 32,
+        }
+    );
+
+    let config: KdfConfig =
+        serde_json::from_str(r#"{"kdf":"scrypt","log_n":10,"r":8,"p":1}"#).unwrap();
+    assert_eq!(serde_json::to_string(&config).unwrap(), SCRYPT_JSON);
+}
+
+#[test]
+fn hash_and_verify() {
+    for json in [PBKDF2_JSON, SCRYPT_JSON] {
+        let config: KdfConfig = serde_json::from_str(json).unwrap();
+        let hash = config.hash("password").unwrap();
+        assert!(config.verify("password", &hash).is_ok());
+        assert!(config.verify("bogus", &hash).is_err());
+        assert!(password_auth::verify_password("password", &hash).is_ok());
+    }
+
+    let config: KdfConfig = serde_json::from_str(PBKDF2_JSON).unwrap();
+    assert!(config
+        .hash("password")
+        .unwrap()
+        .starts_with("$pbkdf2-sha512$i=1000,l=64$"));
+
+    let config: KdfConfig = serde_json::from_str(SCRYPT_JSON).unwrap();
+    assert!(config
+        .hash("password")
+        .unwrap()
+        .starts_with("$scrypt$ln=10,r=8,p=1$"));
+}
+
+#[test]
+fn verify_other_kdf() {
+    let pbkdf2: KdfConfig = serde_json::from_str(PBKDF2_JSON).unwrap();
+    let scrypt: KdfConfig = serde_json::from_str(SCRYPT_JSON).unwrap();
+
+    let hash = scrypt.hash("password").unwrap();
+    assert!(pbkdf2.verify("password", &hash).is_err());
+}
+
+#[test]
+fn params_conversions() {
+    let config: KdfConfig = serde_json::from_str(PBKDF2_JSON).unwrap();
+    assert_eq!(
+        pbkdf2::Params::try_from(config),
+        Ok(pbkdf2::Params {
+            rounds: 1000,
+            output_length: 64,
+        })
+    );
+    assert_eq!(scrypt::Params::try_from(config).unwrap_err().field(), "kdf");
+
+    let config: KdfConfig = serde_json::from_str(SCRYPT_JSON).unwrap();
+    let params = scrypt::Params::try_from(config).unwrap();
+    assert_eq!((params.log_n(), params.r(), params.p()), (10, 8, 1));
+    assert_eq!(pbkdf2::Params::try_from(config).unwrap_err().field(), "kdf");
+
+    let params = pbkdf2::Params {
+        rounds: 1000,
+        output_length: 32,
+    };
+    assert_eq!(
+        KdfConfig::from(params),
+        serde_json::from_str(r#"{"kdf":"pbkdf2","rounds":1000}"#).unwrap()
+    );
+}
+
+#[test]
+fn reject_out_of_range_values() {
+    for (json, field) in [
+        (r#"{"kdf":"pbkdf2","rounds":0}"#, "`rounds`"),
+        (
+            r#"{"kdf":"pbkdf2","rounds":1000,"output_length":9}"#,
+            "`output_length`",
+        ),
+        (
+            r#"{"kdf":"pbkdf2","rounds":1000,"output_length":65}"#,
+            "`output_length`",
+        ),
+        (
+            r#"{"kdf":"pbkdf2","algorithm":"sha256","rounds":1000}"#,
+            "`algorithm`",
+        ),
+        (r#"{"kdf":"scrypt","log_n":99,"r":8,"p":1}"#, "`log_n`"),
+        (r#"{"kdf":"scrypt","log_n":10,"r":0,"p":1}"#, "`r`"),
+        (r#"{"kdf":"scrypt","log_n":25,"r":8,"p":1}"#, "`log_n`"),
+        (r#"{"kdf":"scrypt","log_n":10,"r":33,"p":1}"#, "`r`"),
+        (r#"{"kdf":"scrypt","log_n":10,"r":8,"p":17}"#, "`p`"),
+        // 4 GiB of memory
+        (r#"{"kdf":"scrypt","log_n":22,"r":8,"p":1}"#, "`log_n`"),
+        (r#"{"kdf":"scrypt","log_n":10,"r":8,"p":0}"#, "`p`"),
+        (
+            r#"{"kdf":"scrypt","log_n":10,"r":8,"p":1,"output_length":128}"#,
+            "`output_length`",
+        ),
+    ] {
+        let err = serde_json::from_str::<KdfConfig>(json).unwrap_err();
+        assert!(err.to_string().contains(field), "{}: {}", json, err);
+    }
+}
+
+#[test]
+fn reject_malformed() {
+    for (json, message) in [
+        (
+            r#"{"kdf":"argon2","rounds":1000}"#,
+            "unknown variant `argon2`",
+        ),
+        (r#"{"rounds":1000}"#, "missing field `kdf`"),
+        (r#"{"kdf":"pbkdf2"}"#, "missing field `rounds`"),
+        (
+            r#"{"kdf":"pbkdf2","rounds":1000,"log_n":10}"#,
+            "unknown field `log_n`",
+        ),
+        (
+            r#"{"kdf":"scrypt","log_n":10,"r":8,"p":-1}"#,
+            "invalid value",
+        ),
+    ] {
+        let err = serde_json::from_str::<KdfConfig>(json).unwrap_err();
+        assert!(err.to_string().contains(message), "{}: {}", json, err);
+    }
+}
+
+#[test]
+fn validate() {
+    let config = KdfConfig::Scrypt {
+        log_n: 10,
+        r: 0,
+        p: 1,
+        output_length: 32,
+    };
+    assert_eq!(config.validate().unwrap_err().field(), "r");
+    assert!(matches!(
+        config.hash("password").unwrap_err(),
+        HashError::Config(err) if err.field() == "r"
+    ));
+    assert_eq!(
+        config.validate().unwrap_err().to_string(),
+        "invalid value for `r`: expected greater than 0"
+    );
+}
+
+#[test]
+fn validate_scrypt_limits() {
+    // The bounds of `ParamsPolicy::DEFAULT` under which `Scrypt` hashes
+    let config = KdfConfig::Scrypt {
+        log_n: 25,
+        r: 8,
+        p: 1,
+        output_length: 32,
+    };
+    assert_eq!(
+        config.validate().unwrap_err().to_string(),
+        "invalid value for `log_n`: expected at most 24"
+    );
+    assert!(matches!(
+        config.hash("password").unwrap_err(),
+        HashError::Config(err) if err.field() == "log_n"
+    ));
+
+    let config = KdfConfig::Scrypt {
+        log_n: 10,
+        r: 33,
+        p: 1,
+        output_length: 32,
+    };
+    assert_eq!(
+        config.validate().unwrap_err().to_string(),
+        "invalid value for `r`: expected at most 32"
+    );
+
+    let config = KdfConfig::Scrypt {
+        log_n: 10,
+        r: 8,
+        p: 17,
+        output_length: 32,
+    };
+    assert_eq!(
+        config.validate().unwrap_err().to_string(),
+        "invalid value for `p`: expected at most 16"
+    );
+
+    // `MemoryLimit::DEFAULT` is exceeded
+    let config = KdfConfig::Scrypt {
+        log_n: 21,
+        r: 8,
+        p: 1,
+        output_length: 32,
+    };
+    assert_eq!(config.validate().unwrap_err().field(), "log_n");
+    assert!(matches!(
+        config.hash("password").unwrap_err(),
+        HashError::Config(err) if err.field() == "log_n"
+    ));
+}
@@ -0,0 +1,130 @@
+//! Verification of a mixed set of `crypt(3)` hash strings through
+//! [`password_auth::crypt`].
+//!
+//! The hash strings were computed with `crypt(3)` of libxcrypt, except the
+//! `$apr1$` one, computed with `openssl passwd -apr1`.
+
+use password_auth::crypt::{crypt, verify, CryptError};
+
+const PASSWORD: &str = "password";
+
+const MD5_CRYPT_HASHES: &[&str] = &[
+    "$1$saltsalt$qjXMvbEw8oaL.CzflDtaK/",
+    "$apr1$saltsalt$yAAkm4libquA.ZWLHbSBq/",
+];
+
+#[cfg(feature = "bcrypt")]
+const BCRYPT_HASHES: &[&str] = &[
+    "$2a$04$abcdefghijklmnopqrstuughE8Ev8uGFaUgY2cNEySvxngrb/Jzdm",
+    "$2b$04$abcdefghijklmnopqrstuughE8Ev8uGFaUgY2cNEySvxngrb/Jzdm",
+    "$2y$04$abcdefghijklmnopqrstuughE8Ev8uGFaUgY2cNEySvxngrb/Jzdm",
+];
+
+#[cfg(feature = "sha-crypt")]
+const SHA_CRYPT_HASHES: &[&str] = &[
+    "$5$rounds=10000$saltsalt$a6WJS3V6B3leg7T3.ELC5.vcUmHOyFDvLaurLBy.mc8",
+    "$6$saltsalt$qFmFH.bQmmtXzyBY0s9v7Oicd2z4XSIecDzlB5KiA2/jctKu9YterLp8wwnSq.qc.eoxqOmSuNp2xS0ktL3nh/",
+];
+
+#[cfg(feature = "scrypt")]
+const SCRYPT_HASHES: &[&str] =
+    &["$7$AU..../....saltsalt$xzp/5M0lt/aa8jQGyrmWS4ZrBRCjeNVQk/ndo5GGPC9"];
+
+#[cfg(feature = "yescrypt")]
+const YESCRYPT_HASHES: &[&str] =
+    &["$y$j75$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J."];
+
+/// Hashes of the schemes whose feature is enabled.
+fn enabled_hashes() -> Vec<&'static str> {
+    let hashes: &[&[&str]] = &[
+        #[cfg(feature = "md5-crypt")]
+        MD5_CRYPT_HASHES,
+        #[cfg(feature = "bcrypt")]
+        BCRYPT_HASHES,
+        #[cfg(feature = "sha-crypt")]
+        SHA_CRYPT_HASHES,
+        #[cfg(feature = "scrypt")]
+        SCRYPT_HASHES,
+        #[cfg(feature = "yescrypt")]
+        YESCRYPT_HASHES,
+    ];
+    hashes.concat()
+}
+
+#[test]
+fn verify_mixed_hashes() {
+    for hash in enabled_hashes() {
+        assert_eq!(verify(PASSWORD, hash), Ok(()), "{}", hash);
+        assert_eq!(
+            verify("bogus", hash),
+            Err(CryptError::PasswordMismatch),
+            "{}",
+            hash
+        );
+    }
+}
+
+#[test]
+fn crypt_mixed_hashes() {
+    for hash in enabled_hashes() {
+        if hash.starts_with("$y$") {
+            assert_eq!(
+                crypt(PASSWORD, hash),
+                Err(CryptError::UnsupportedScheme("$y$".into()))
+            );
+        } else {
+            assert_eq!(crypt(PASSWORD, hash).as_deref(), Ok(hash));
+        }
+    }
+}
+
+#[cfg(feature = "sha-crypt")]
+#[test]
+fn crypt_setting() {
+    let hash = crypt(PASSWORD, "$6$saltsalt").unwrap();
+    assert_eq!(hash, SHA_CRYPT_HASHES[1]);
+    assert_eq!(verify(PASSWORD, &hash), Ok(()));
+}
+
+#[test]
+fn unsupported_schemes() {
+    for (hash, prefix) in [
+        // Traditional DES, and locked accounts of `/etc/shadow`
+        ("saHW9GdxihkGQ", ""),
+        ("!", ""),
+        ("*", ""),
+        ("", ""),
+        ("$3$$8846f7eaee8fb117ad06bdd830b7586c", "$3$"),
+        ("$gy$j9T$saltsalt$hash", "$gy$"),
+        (
+            "$2x$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW",
+            "$2x$",
+        ),
+    ] {
+        let err = Err(CryptError::UnsupportedScheme(prefix.into()));
+        assert_eq!(verify(PASSWORD, hash), err, "{}", hash);
+        assert_eq!(crypt(PASSWORD, hash).map(drop), err, "{}", hash);
+    }
+}
+
+#[cfg(not(feature = "md5-crypt"))]
+#[test]
+fn disabled_scheme() {
+    assert_eq!(
+        verify(PASSWORD, MD5_CRYPT_HASHES[0]),
+        Err(CryptError::UnsupportedScheme("$1$".into()))
+    );
+}
+
+#[test]
+fn invalid_hashes() {
+    for hash in enabled_hashes() {
+        let truncated = &hash[..hash.len() - 1];
+        assert_eq!(
+            verify(PASSWORD, truncated),
+            Err(CryptError::InvalidHash),
+            "{}",
+            truncated
+        );
+    }
+}
@@ -0,0 +1,102 @@
+#ifndef PASSWORD_CAPI_H
+#define PASSWORD_CAPI_H
+
+/* Generated by cbindgen from src/lib.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Result of the functions of this API.
+//
+// The values are stable: new results are only ever added at the end.
+typedef enum ph_status {
+  // Success.
+  PH_STATUS_OK = 0,
+  // The password doesn't match the hash.
+  PH_STATUS_PASSWORD_MISMATCH = 1,
+  // A pointer argument is null.
+  PH_STATUS_NULL_POINTER = 2,
+  // A string argument isn't valid UTF-8.
+  PH_STATUS_INVALID_UTF8 = 3,
+  // The algorithm isn't one of those of the function.
+  PH_STATUS_UNKNOWN_ALGORITHM = 4,
+  // The params aren't valid params of the algorithm.
+  PH_STATUS_INVALID_PARAMS = 5,
+  // The hash isn't a valid PHC string.
+  PH_STATUS_INVALID_HASH = 6,
+  // The password couldn't be hashed with the params.
+  PH_STATUS_HASH_FAILED = 7,
+  // The function panicked. This is a bug.
+  PH_STATUS_PANIC = 8,
+} ph_status;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Hash `password` with PBKDF2 into a PHC string, with a random salt.
+//
+// - `algorithm` is `"pbkdf2-sha256"` or `"pbkdf2-sha512"`, or null for
+//   `"pbkdf2-sha256"`.
+// - `params` are the params of the PHC string, e.g. `"i=600000,l=32"`, or
+//   null for the defaults of the `pbkdf2` crate.
+//
+// On success, `*out_hash` is set to the PHC string, owned by the caller
+// and freed with `ph_free_string`. Otherwise it's set to null, if
+// `out_hash` isn't null.
+//
+// # Safety
+// - `password` points to `password_len` readable bytes, or is null if
+//   `password_len` is 0.
+// - `algorithm` and `params` are null or point to null-terminated strings.
+// - `out_hash` is null or points to a writable `char *`.
+enum ph_status ph_pbkdf2_hash(const uint8_t *password,
+                              size_t password_len,
+                              const char *algorithm,
+                              const char *params,
+                              char **out_hash);
+
+// Hash `password` with scrypt into a PHC string, with a random salt.
+//
+// `params` are the params of the PHC string, e.g. `"ln=17,r=8,p=1"`, or
+// null for the defaults of the `scrypt` crate.
+//
+// `*out_hash` is set as by `ph_pbkdf2_hash`.
+//
+// # Safety
+// - `password` points to `password_len` readable bytes, or is null if
+//   `password_len` is 0.
+// - `params` is null or points to a null-terminated string.
+// - `out_hash` is null or points to a writable `char *`.
+enum ph_status ph_scrypt_hash(const uint8_t *password,
+                              size_t password_len,
+                              const char *params,
+                              char **out_hash);
+
+// Verify `password` against the PHC string `hash`, of PBKDF2 or scrypt.
+//
+// Returns `PH_STATUS_OK` if the password matches,
+// `PH_STATUS_PASSWORD_MISMATCH` if it doesn't, and
+// `PH_STATUS_UNKNOWN_ALGORITHM` for a hash of another algorithm.
+//
+// # Safety
+// - `password` points to `password_len` readable bytes, or is null if
+//   `password_len` is 0.
+// - `hash` points to a null-terminated string.
+enum ph_status ph_verify(const uint8_t *password, size_t password_len, const char *hash);
+
+// Free a string returned by this API. Does nothing if `string` is null.
+//
+// # Safety
+// `string` is null or a string returned by this API that hasn't been
+// freed yet.
+void ph_free_string(char *string);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PASSWORD_CAPI_H */
@@ -0,0 +1,251 @@
+#![doc = include_str!("../README.md")]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg",
+    html_favicon_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg"
+)]
+#![warn(
+    missing_docs,
+    rust_2018_idioms,
+    unused_lifetimes,
+    unused_qualifications
+)]
+
+use core::{ptr, slice};
+use password_auth::registry::{Registry, RegistryError};
+use password_hash::{Ident, ParamsString, PasswordHash, PasswordHasher, SaltString};
+use pbkdf2::Pbkdf2;
+use rand_core::OsRng;
+use scrypt::Scrypt;
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    panic::{self, AssertUnwindSafe},
+};
+
+/// Result of the functions of this API.
+///
+/// The values are stable: new results are only ever added at the end.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Status {
+    /// Success.
+    Ok = 0,
+
+    /// The password doesn't match the hash.
+    PasswordMismatch = 1,
+
+    /// A pointer argument is null.
+    NullPointer = 2,
+
+    /// A string argument isn't valid UTF-8.
+    InvalidUtf8 = 3,
+
+    /// The algorithm isn't one of those of the function.
+    UnknownAlgorithm = 4,
+
+    /// The params aren't valid params of the algorithm.
+    InvalidParams = 5,
+
+    /// The hash isn't a valid PHC string.
+    InvalidHash = 6,
+
+    /// The password couldn't be hashed with the params.
+    HashFailed = 7,
+
+    /// The function panicked. This is a bug.
+    Panic = 8,
+}
+
+/// Hash `password` with PBKDF2 into a PHC string, with a random salt.
+///
+/// - `algorithm` is `"pbkdf2-sha256"` or `"pbkdf2-sha512"`, or null for
+///   `"pbkdf2-sha256"`.
+/// - `params` are the params of the PHC string, e.g. `"i=600000,l=32"`, or
+///   null for the defaults of the `pbkdf2` crate.
+///
+/// On success, `*out_hash` is set to the PHC string, owned by the caller
+/// and freed with `ph_free_string`. Otherwise it's set to null, if
+/// `out_hash` isn't null.
+///
+/// # Safety
+/// - `password` points to `password_len` readable bytes, or is null if
+///   `password_len` is 0.
+/// - `algorithm` and `params` are null or point to null-terminated strings.
+/// - `out_hash` is null or points to a writable `char *`.
+#[no_mangle]
+pub unsafe extern "C" fn ph_pbkdf2_hash(
+    password: *const u8,
+    password_len: usize,
+    algorithm: *const c_char,
+    params: *const c_char,
+    out_hash: *mut *mut c_char,
+) -> Status {
+    boundary(|| {
+        write_hash(out_hash, || {
+            let algorithm = match opt_str(algorithm)? {
+                None | Some("pbkdf2-sha256") => pbkdf2::Algorithm::PBKDF2_SHA256_IDENT,
+                Some("pbkdf2-sha512") => pbkdf2::Algorithm::PBKDF2_SHA512_IDENT,
+                Some(_) => return Err(Status::UnknownAlgorithm),
+            };
+
+            let password = bytes(password, password_len)?;
+            hash_with(&Pbkdf2, algorithm, opt_str(params)?, password)
+        })
+    })
+}
+
+/// Hash `password` with scrypt into a PHC string, with a random salt.
+///
+/// `params` are the params of the PHC string, e.g. `"ln=17,r=8,p=1"`, or
+/// null for the defaults of the `scrypt` crate.
+///
+/// `*out_hash` is set as by `ph_pbkdf2_hash`.
+///
+/// # Safety
+/// - `password` points to `password_len` readable bytes, or is null if
+///   `password_len` is 0.
+/// - `params` is null or points to a null-terminated string.
+/// - `out_hash` is null or points to a writable `char *`.
+#[no_mangle]
+pub unsafe extern "C" fn ph_scrypt_hash(
+    password: *const u8,
+    password_len: usize,
+    params: *const c_char,
+    out_hash: *mut *mut c_char,
+) -> Status {
+    boundary(|| {
+        write_hash(out_hash, || {
+            let password = bytes(password, password_len)?;
+            hash_with(&Scrypt, scrypt::ALG_ID, opt_str(params)?, password)
+        })
+    })
+}
+
+/// Verify `password` against the PHC string `hash`, of PBKDF2 or scrypt.
+///
+/// Returns `PH_STATUS_OK` if the password matches,
+/// `PH_STATUS_PASSWORD_MISMATCH` if it doesn't, and
+/// `PH_STATUS_UNKNOWN_ALGORITHM` for a hash of another algorithm.
+///
+/// # Safety
+/// - `password` points to `password_len` readable bytes, or is null if
+///   `password_len` is 0.
+/// - `hash` points to a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ph_verify(
+    password: *const u8,
+    password_len: usize,
+    hash: *const c_char,
+) -> Status {
+    boundary(|| {
+        let password = bytes(password, password_len)?;
+        let hash = opt_str(hash)?.ok_or(Status::NullPointer)?;
+
+        Registry::with_enabled_algorithms()
+            .verify(password, hash)
+            .map_err(|err| match err {
+                RegistryError::InvalidHash(_) => Status::InvalidHash,
+                RegistryError::UnknownAlgorithm => Status::UnknownAlgorithm,
+                RegistryError::PasswordMismatch => Status::PasswordMismatch,
+            })
+    })
+}
+
+/// Free a string returned by this API. Does nothing if `string` is null.
+///
+/// # Safety
+/// `string` is null or a string returned by this API that hasn't been
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ph_free_string(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Run `f`, converting a panic into `PH_STATUS_PANIC` rather than
+/// unwinding into C.
+fn boundary(f: impl FnOnce() -> Result<(), Status>) -> Status {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => Status::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => Status::Panic,
+    }
+}
+
+/// Borrow the `len` bytes at `ptr`.
+///
+/// # Safety
+/// `ptr` points to `len` readable bytes, or is null if `len` is 0.
+unsafe fn bytes<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], Status> {
+    if len == 0 {
+        Ok(&[])
+    } else if ptr.is_null() {
+        Err(Status::NullPointer)
+    } else {
+        Ok(slice::from_raw_parts(ptr, len))
+    }
+}
+
+/// Borrow the null-terminated string at `ptr`, or `None` if `ptr` is null.
+///
+/// # Safety
+/// `ptr` is null or points to a null-terminated string.
+unsafe fn opt_str<'a>(ptr: *const c_char) -> Result<Option<&'a str>, Status> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map(Some)
+        .map_err(|_| Status::InvalidUtf8)
+}
+
+/// Set `*out_hash` to the hash computed by `f`, or to null if it fails.
+///
+/// # Safety
+/// `out_hash` is null or points to a writable `char *`.
+unsafe fn write_hash(
+    out_hash: *mut *mut c_char,
+    f: impl FnOnce() -> Result<String, Status>,
+) -> Result<(), Status> {
+    if out_hash.is_null() {
+        return Err(Status::NullPointer);
+    }
+
+    *out_hash = ptr::null_mut();
+    // PHC strings are ASCII without NUL, so `CString::new` never fails
+    let hash = CString::new(f()?).map_err(|_| Status::HashFailed)?;
+    *out_hash = hash.into_raw();
+    Ok(())
+}
+
+/// Hash `password` with `hasher`, reading its params from `params` as from
+/// a PHC string.
+fn hash_with<H: PasswordHasher>(
+    hasher: &H,
+    ident: Ident<'_>,
+    params: Option<&str>,
+    password: &[u8],
+) -> Result<String, Status> {
+    let params = match params {
+        Some(params) => params.parse().map_err(|_| Status::InvalidParams)?,
+        None => ParamsString::new(),
+    };
+
+    let params = H::Params::try_from(&PasswordHash {
+        algorithm: ident,
+        version: None,
+        params,
+        salt: None,
+        hash: None,
+    })
+    .map_err(|_| Status::InvalidParams)?;
+
+    let salt = SaltString::generate(&mut OsRng);
+    hasher
+        .hash_password_customized(password, Some(ident), None, params, &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|_| Status::HashFailed)
+}
@@ -0,0 +1,98 @@
+/* Exercises the C API through the generated header, run by tests/c_api.rs. */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "password_capi.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                     \
+    do {                                                                \
+        if (!(cond)) {                                                  \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,      \
+                    __LINE__, #cond);                                   \
+            failures++;                                                 \
+        }                                                               \
+    } while (0)
+
+static const uint8_t PASSWORD[] = "hunter2";
+static const size_t PASSWORD_LEN = sizeof(PASSWORD) - 1;
+static const uint8_t WRONG_PASSWORD[] = "hunter3";
+
+/* PBKDF2 hash for the string "password". */
+static const char *PBKDF2_HASH =
+    "$pbkdf2-sha256$i=4096,l=32$c2FsdA$xeR41ZKIyEGqUw22hFxMjZYok6ABzk4RpJY4c6qYE0o";
+
+/* The same hash with an algorithm that isn't supported. */
+static const char *MD5_HASH =
+    "$md5$i=4096,l=32$c2FsdA$xeR41ZKIyEGqUw22hFxMjZYok6ABzk4RpJY4c6qYE0o";
+
+/* Takes `out_hash` by pointer, as it's written by the call in the argument
+ * list of `round_trip`. */
+static void round_trip(ph_status status, char **out_hash, const char *prefix) {
+    char *hash = *out_hash;
+
+    CHECK(status == PH_STATUS_OK);
+    CHECK(hash != NULL);
+    if (hash == NULL) {
+        return;
+    }
+
+    CHECK(strncmp(hash, prefix, strlen(prefix)) == 0);
+    CHECK(ph_verify(PASSWORD, PASSWORD_LEN, hash) == PH_STATUS_OK);
+    CHECK(ph_verify(WRONG_PASSWORD, PASSWORD_LEN, hash) ==
+          PH_STATUS_PASSWORD_MISMATCH);
+    ph_free_string(hash);
+}
+
+int main(void) {
+    char *hash = NULL;
+
+    round_trip(ph_pbkdf2_hash(PASSWORD, PASSWORD_LEN, NULL, "i=1000,l=32", &hash),
+               &hash, "$pbkdf2-sha256$i=1000,l=32$");
+    round_trip(ph_pbkdf2_hash(PASSWORD, PASSWORD_LEN, "pbkdf2-sha512",
+                              "i=1000,l=64", &hash),
+               &hash, "$pbkdf2-sha512$i=1000,l=64$");
+    round_trip(ph_scrypt_hash(PASSWORD, PASSWORD_LEN, "ln=4,r=8,p=1", &hash),
+               &hash, "$scrypt$ln=4,r=8,p=1$");
+
+    /* Known hash, with a password given by pointer and length */
+    CHECK(ph_verify((const uint8_t *)"password", 8, PBKDF2_HASH) == PH_STATUS_OK);
+
+    /* Empty passwords may be null */
+    CHECK(ph_scrypt_hash(NULL, 0, "ln=4,r=8,p=1", &hash) == PH_STATUS_OK);
+    CHECK(ph_verify(NULL, 0, hash) == PH_STATUS_OK);
+    ph_free_string(hash);
+
+    /* Errors set the hash to null */
+    hash = (char *)PBKDF2_HASH;
+    CHECK(ph_pbkdf2_hash(PASSWORD, PASSWORD_LEN, "md5", NULL, &hash) ==
+          PH_STATUS_UNKNOWN_ALGORITHM);
+    CHECK(hash == NULL);
+    CHECK(ph_scrypt_hash(PASSWORD, PASSWORD_LEN, "ln=4,r=8,p=1,x=1", &hash) ==
+          PH_STATUS_INVALID_PARAMS);
+    CHECK(hash == NULL);
+    CHECK(ph_scrypt_hash(PASSWORD, PASSWORD_LEN, "ln=4,r=8,p=1 ", &hash) ==
+          PH_STATUS_INVALID_PARAMS);
+    CHECK(ph_scrypt_hash(NULL, 1, NULL, &hash) == PH_STATUS_NULL_POINTER);
+    CHECK(ph_scrypt_hash(PASSWORD, PASSWORD_LEN, NULL, NULL) ==
+          PH_STATUS_NULL_POINTER);
+    CHECK(ph_pbkdf2_hash(PASSWORD, PASSWORD_LEN, "\xff", NULL, &hash) ==
+          PH_STATUS_INVALID_UTF8);
+
+    CHECK(ph_verify(PASSWORD, PASSWORD_LEN, "password") == PH_STATUS_INVALID_HASH);
+    CHECK(ph_verify(PASSWORD, PASSWORD_LEN, MD5_HASH) ==
+          PH_STATUS_UNKNOWN_ALGORITHM);
+    CHECK(ph_verify(PASSWORD, PASSWORD_LEN, NULL) == PH_STATUS_NULL_POINTER);
+
+    ph_free_string(NULL);
+
+    if (failures != 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+
+    printf("all checks passed\n");
+    return 0;
+}
@@ -0,0 +1,40 @@
+//! Compile `tests/c/ffi_test.c` against the header and the shared library,
+//! and run it.
+#![cfg(unix)]
+
+use std::{env, path::PathBuf, process::Command};
+
+#[test]
+fn c_program() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    // Cargo builds the shared library next to the tests, in
+    // `target/<profile>/deps`
+    let exe = env::current_exe().expect("test executable path");
+    let lib_dir = exe.parent().expect("target directory");
+    let program = lib_dir.join("password_capi_ffi_test");
+
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".into()))
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests/c/ffi_test.c"))
+        .arg("-o")
+        .arg(&program)
+        .arg("-L")
+        .arg(lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-lpassword_capi")
+        .status()
+        .expect("run cc");
+    assert!(status.success(), "compiling the C program failed");
+
+    let output = Command::new(&program).output().expect("run the C program");
+    assert!(
+        output.status.success(),
+        "the C program failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
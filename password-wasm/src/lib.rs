@@ -0,0 +1,273 @@
+#![doc = include_str!("../README.md")]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg",
+    html_favicon_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg"
+)]
+#![warn(
+    missing_docs,
+    rust_2018_idioms,
+    unused_lifetimes,
+    unused_qualifications
+)]
+
+#[cfg(not(any(feature = "argon2", feature = "pbkdf2", feature = "scrypt")))]
+compile_error!(
+    "please enable at least one password hash crate feature, e.g. argon2, pbkdf2, scrypt"
+);
+
+use core::fmt;
+use password_auth::registry::{Registry, RegistryError};
+use password_hash::SaltString;
+use rand_core::OsRng;
+use wasm_bindgen::prelude::{wasm_bindgen, JsError};
+
+#[cfg(any(feature = "argon2", feature = "pbkdf2", feature = "scrypt"))]
+use password_hash::PasswordHasher;
+
+/// Error of [`hash`] and [`verify`], thrown by the bindings as a JS `Error`
+/// with the same message.
+#[derive(Debug)]
+pub enum Error {
+    /// The algorithm isn't one of the enabled algorithms.
+    UnknownAlgorithm,
+
+    /// The JSON params aren't valid params of the algorithm.
+    InvalidParams(serde_json::Error),
+
+    /// The password couldn't be hashed with the params.
+    Hash(password_hash::Error),
+
+    /// The password hash isn't a valid PHC string, or its algorithm isn't
+    /// enabled.
+    InvalidHash(RegistryError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownAlgorithm => write!(
+                f,
+                "unknown algorithm, expected one of: {}",
+                ALGORITHMS.join(", ")
+            ),
+            Error::InvalidParams(err) => write!(f, "invalid params: {}", err),
+            Error::Hash(err) => write!(f, "password hashing error: {}", err),
+            Error::InvalidHash(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Names of the enabled algorithms, for [`hash`].
+const ALGORITHMS: &[&str] = &[
+    #[cfg(feature = "argon2")]
+    "argon2id",
+    #[cfg(feature = "argon2")]
+    "argon2i",
+    #[cfg(feature = "argon2")]
+    "argon2d",
+    #[cfg(feature = "pbkdf2")]
+    "pbkdf2",
+    #[cfg(feature = "pbkdf2")]
+    "pbkdf2-sha256",
+    #[cfg(feature = "pbkdf2")]
+    "pbkdf2-sha512",
+    #[cfg(feature = "scrypt")]
+    "scrypt",
+];
+
+/// Maximum length of the keys of [`derive_key`], in bytes.
+#[cfg(feature = "argon2")]
+pub const MAX_KEY_LEN: usize = 1024;
+
+/// Hash `password` with `algorithm` into a PHC string, with a random salt.
+///
+/// `params_json` is a JSON object of the params of the algorithm, or an
+/// empty string for the defaults of its crate, see the crate docs.
+#[wasm_bindgen(js_name = "hash_password")]
+pub fn hash_password(
+    algorithm: &str,
+    password: &[u8],
+    params_json: &str,
+) -> Result<String, JsError> {
+    Ok(hash(algorithm, password, params_json)?)
+}
+
+/// Verify `password` against the PHC string `hash`.
+///
+/// Returns whether the password matches, and throws if the hash is
+/// invalid or of an algorithm that isn't enabled.
+#[wasm_bindgen(js_name = "verify_password")]
+pub fn verify_password(password: &[u8], hash: &str) -> Result<bool, JsError> {
+    Ok(verify(password, hash)?)
+}
+
+/// Derive a key from `password` and `salt` with Argon2id.
+///
+/// `params_json` is a JSON object of the Argon2 params, or an empty string
+/// for [`argon2::Params::DEFAULT_WASM`], and `output_len` is the length of
+/// the key, 32 bytes by default and at most [`MAX_KEY_LEN`].
+#[cfg(feature = "argon2")]
+#[wasm_bindgen(js_name = "derive_key")]
+pub fn derive_key(password: &[u8], salt: &[u8], params_json: &str) -> Result<Vec<u8>, JsError> {
+    Ok(derive(password, salt, params_json)?)
+}
+
+/// Hash `password` with `algorithm` into a PHC string, with a random salt,
+/// as [`hash_password`] does.
+pub fn hash(algorithm: &str, password: &[u8], params_json: &str) -> Result<String, Error> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    let hash = match algorithm {
+        #[cfg(feature = "argon2")]
+        "argon2id" | "argon2i" | "argon2d" => {
+            let algorithm = match algorithm {
+                "argon2i" => argon2::Algorithm::Argon2i,
+                "argon2d" => argon2::Algorithm::Argon2d,
+                _ => argon2::Algorithm::Argon2id,
+            };
+            let params = parse_params::<Argon2Params>(params_json)?
+                .try_into()
+                .map_err(|err: argon2::Error| Error::Hash(err.into()))?;
+            argon2::Argon2::new(algorithm, argon2::Version::V0x13, params)
+                .hash_password(password, &salt)
+        }
+        #[cfg(feature = "pbkdf2")]
+        "pbkdf2" | "pbkdf2-sha256" | "pbkdf2-sha512" => {
+            let algorithm = match algorithm {
+                "pbkdf2-sha512" => pbkdf2::Algorithm::PBKDF2_SHA512_IDENT,
+                _ => pbkdf2::Algorithm::PBKDF2_SHA256_IDENT,
+            };
+            let params = parse_params::<Pbkdf2Params>(params_json)?.into();
+            pbkdf2::Pbkdf2.hash_password_customized(password, Some(algorithm), None, params, &salt)
+        }
+        #[cfg(feature = "scrypt")]
+        "scrypt" => {
+            let params = parse_params::<scrypt::Params>(params_json)?;
+            scrypt::Scrypt.hash_password_customized(password, None, None, params, &salt)
+        }
+        _ => return Err(Error::UnknownAlgorithm),
+    };
+
+    hash.map(|hash| hash.to_string()).map_err(Error::Hash)
+}
+
+/// Verify `password` against the PHC string `hash`, as
+/// [`verify_password`] does.
+pub fn verify(password: &[u8], hash: &str) -> Result<bool, Error> {
+    match Registry::with_enabled_algorithms().verify(password, hash) {
+        Ok(()) => Ok(true),
+        Err(RegistryError::PasswordMismatch) => Ok(false),
+        Err(err) => Err(Error::InvalidHash(err)),
+    }
+}
+
+/// Derive a key from `password` and `salt` with Argon2id, as
+/// [`derive_key`] does.
+#[cfg(feature = "argon2")]
+pub fn derive(password: &[u8], salt: &[u8], params_json: &str) -> Result<Vec<u8>, Error> {
+    let params = parse_params::<Argon2Params>(params_json)?;
+    let output_len = params.output_len;
+
+    // Check the params before allocating the key
+    let params = argon2::Params::try_from(params).map_err(|err| Error::Hash(err.into()))?;
+    if output_len > MAX_KEY_LEN {
+        return Err(Error::Hash(argon2::Error::OutputTooLong.into()));
+    }
+
+    let mut key = vec![0u8; output_len];
+    argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+        .hash_password_into(password, salt, &mut key)
+        .map_err(|err| Error::Hash(err.into()))?;
+
+    Ok(key)
+}
+
+/// Parse JSON params, or give the default params for an empty string.
+#[cfg(any(feature = "argon2", feature = "pbkdf2", feature = "scrypt"))]
+fn parse_params<P>(params_json: &str) -> Result<P, Error>
+where
+    P: Default + for<'de> serde::Deserialize<'de>,
+{
+    if params_json.trim().is_empty() {
+        Ok(P::default())
+    } else {
+        serde_json::from_str(params_json).map_err(Error::InvalidParams)
+    }
+}
+
+/// JSON params of Argon2, whose fields default to those of
+/// [`argon2::Params::DEFAULT_WASM`].
+#[cfg(feature = "argon2")]
+#[derive(serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Argon2Params {
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    output_len: usize,
+}
+
+#[cfg(feature = "argon2")]
+impl Default for Argon2Params {
+    fn default() -> Self {
+        let params = argon2::Params::DEFAULT_WASM;
+
+        Self {
+            m_cost: params.m_cost(),
+            t_cost: params.t_cost(),
+            p_cost: params.p_cost(),
+            output_len: argon2::Params::DEFAULT_OUTPUT_LEN,
+        }
+    }
+}
+
+#[cfg(feature = "argon2")]
+impl TryFrom<Argon2Params> for argon2::Params {
+    type Error = argon2::Error;
+
+    fn try_from(params: Argon2Params) -> Result<Self, argon2::Error> {
+        argon2::Params::new(
+            params.m_cost,
+            params.t_cost,
+            params.p_cost,
+            Some(params.output_len),
+        )
+    }
+}
+
+/// JSON params of PBKDF2, whose fields default to those of
+/// [`pbkdf2::Params`].
+#[cfg(feature = "pbkdf2")]
+#[derive(serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Pbkdf2Params {
+    rounds: u32,
+    output_length: usize,
+}
+
+#[cfg(feature = "pbkdf2")]
+impl Default for Pbkdf2Params {
+    fn default() -> Self {
+        let pbkdf2::Params {
+            rounds,
+            output_length,
+        } = pbkdf2::Params::default();
+
+        Self {
+            rounds,
+            output_length,
+        }
+    }
+}
+
+#[cfg(feature = "pbkdf2")]
+impl From<Pbkdf2Params> for pbkdf2::Params {
+    fn from(params: Pbkdf2Params) -> Self {
+        Self {
+            rounds: params.rounds,
+            output_length: params.output_length,
+        }
+    }
+}
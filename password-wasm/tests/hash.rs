@@ -0,0 +1,162 @@
+//! Hashing and verifying with the Rust API of the bindings.
+
+use password_wasm::{hash, verify, Error};
+
+#[cfg(feature = "argon2")]
+use password_wasm::{derive, MAX_KEY_LEN};
+
+/// PBKDF2 hash for the string "password".
+#[cfg(feature = "pbkdf2")]
+const PBKDF2_HASH: &str =
+    "$pbkdf2-sha256$i=4096,l=32$c2FsdA$xeR41ZKIyEGqUw22hFxMjZYok6ABzk4RpJY4c6qYE0o";
+
+#[cfg(feature = "pbkdf2")]
+#[test]
+fn pbkdf2() {
+    for (algorithm, prefix) in [
+        ("pbkdf2", "$pbkdf2-sha256$i=1000,l=32$"),
+        ("pbkdf2-sha256", "$pbkdf2-sha256$i=1000,l=32$"),
+        ("pbkdf2-sha512", "$pbkdf2-sha512$i=1000,l=32$"),
+    ] {
+        let phc = hash(algorithm, b"hunter2", r#"{"rounds": 1000}"#).unwrap();
+        assert!(phc.starts_with(prefix), "{}", phc);
+        assert!(verify(b"hunter2", &phc).unwrap());
+        assert!(!verify(b"hunter3", &phc).unwrap());
+    }
+
+    assert!(verify(b"password", PBKDF2_HASH).unwrap());
+    assert!(hash("pbkdf2", b"hunter2", "")
+        .unwrap()
+        .starts_with("$pbkdf2-sha256$i=10000,l=32$"));
+}
+
+#[cfg(feature = "argon2")]
+#[test]
+fn argon2() {
+    for algorithm in ["argon2id", "argon2i", "argon2d"] {
+        let phc = hash(algorithm, b"hunter2", r#"{"m_cost": 64, "t_cost": 1}"#).unwrap();
+        assert!(
+            phc.starts_with(&format!("${}$v=19$m=64,t=1,p=1$", algorithm)),
+            "{}",
+            phc
+        );
+        assert!(verify(b"hunter2", &phc).unwrap());
+        assert!(!verify(b"hunter3", &phc).unwrap());
+    }
+
+    assert!(hash("argon2id", b"hunter2", "")
+        .unwrap()
+        .starts_with("$argon2id$v=19$m=12288,t=3,p=1$"));
+}
+
+#[cfg(feature = "argon2")]
+#[test]
+fn argon2_derive() {
+    use argon2::{Algorithm, Argon2, Params, Version};
+
+    let key = derive(
+        b"hunter2",
+        b"somesaltsomesalt",
+        r#"{"m_cost": 64, "t_cost": 1, "output_len": 48}"#,
+    )
+    .unwrap();
+
+    let mut expected = [0u8; 48];
+    Argon2::new(
+        Algorithm::Argon2id,
+        Version::V0x13,
+        Params::new(64, 1, 1, Some(48)).unwrap(),
+    )
+    .hash_password_into(b"hunter2", b"somesaltsomesalt", &mut expected)
+    .unwrap();
+    assert_eq!(key, expected);
+
+    assert_eq!(
+        derive(b"hunter2", b"somesaltsomesalt", "").unwrap().len(),
+        32
+    );
+    assert!(matches!(
+        derive(b"hunter2", b"salt", ""),
+        Err(Error::Hash(_))
+    ));
+    assert!(matches!(
+        derive(b"hunter2", b"somesaltsomesalt", r#"{"m_cost": 1}"#),
+        Err(Error::Hash(_))
+    ));
+
+    let params = |output_len| {
+        format!(
+            r#"{{"m_cost": 64, "t_cost": 1, "output_len": {}}}"#,
+            output_len
+        )
+    };
+    assert_eq!(
+        derive(b"hunter2", b"somesaltsomesalt", &params(MAX_KEY_LEN))
+            .unwrap()
+            .len(),
+        MAX_KEY_LEN
+    );
+    assert!(matches!(
+        derive(b"hunter2", b"somesaltsomesalt", &params(MAX_KEY_LEN + 1)),
+        Err(Error::Hash(_))
+    ));
+    assert!(matches!(
+        derive(
+            b"hunter2",
+            b"somesaltsomesalt",
+            r#"{"m_cost": 1, "output_len": 4294967295}"#
+        ),
+        Err(Error::Hash(_))
+    ));
+}
+
+#[cfg(feature = "scrypt")]
+#[test]
+fn scrypt() {
+    let phc = hash("scrypt", b"hunter2", r#"{"log_n": 4, "r": 8, "p": 1}"#).unwrap();
+    assert!(phc.starts_with("$scrypt$ln=4,r=8,p=1$"), "{}", phc);
+    assert!(verify(b"hunter2", &phc).unwrap());
+    assert!(!verify(b"hunter3", &phc).unwrap());
+}
+
+#[test]
+fn errors() {
+    let err = hash("md5", b"hunter2", "").unwrap_err();
+    assert!(matches!(err, Error::UnknownAlgorithm));
+    assert!(err
+        .to_string()
+        .starts_with("unknown algorithm, expected one of: "));
+
+    #[cfg(feature = "scrypt")]
+    for params in [
+        "{",
+        r#"{"log_n": 4}"#,
+        r#"{"log_n": 4, "r": 8, "p": 1, "x": 1}"#,
+    ] {
+        let err = hash("scrypt", b"hunter2", params).unwrap_err();
+        assert!(matches!(err, Error::InvalidParams(_)), "{}", params);
+        assert!(err.to_string().starts_with("invalid params: "));
+    }
+
+    #[cfg(feature = "pbkdf2")]
+    assert!(matches!(
+        hash(
+            "pbkdf2",
+            b"hunter2",
+            r#"{"rounds": 1000, "output_length": 0}"#
+        ),
+        Err(Error::Hash(_))
+    ));
+
+    for phc in [
+        "password",
+        // Unknown algorithm: not argon2, which other crates of the
+        // workspace enable in `password-auth` when testing it as a whole
+        "$bogus$c29tZXNhbHQAAAAAAAAAAA$+r0d29hqEB0yasKr55ZgICsQGSkl0v0kgwhd+U3wyRo",
+    ] {
+        assert!(matches!(
+            verify(b"password", phc),
+            Err(Error::InvalidHash(_))
+        ));
+    }
+}
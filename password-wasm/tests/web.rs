@@ -0,0 +1,42 @@
+//! Round trips through the JS bindings, run with `wasm-pack test --node`, or with
+//! `wasm-bindgen-test-runner` as the runner of `cargo test --target wasm32-unknown-unknown`.
+#![cfg(target_arch = "wasm32")]
+
+use password_wasm::{hash_password, verify_password};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[cfg(feature = "scrypt")]
+#[wasm_bindgen_test]
+fn scrypt_round_trip() {
+    let hash = hash_password("scrypt", b"hunter2", r#"{"log_n": 4, "r": 8, "p": 1}"#).unwrap();
+    assert!(hash.starts_with("$scrypt$ln=4,r=8,p=1$"));
+    assert!(verify_password(b"hunter2", &hash).unwrap());
+    assert!(!verify_password(b"hunter3", &hash).unwrap());
+}
+
+#[cfg(feature = "argon2")]
+#[wasm_bindgen_test]
+fn argon2_round_trip() {
+    let hash = hash_password("argon2id", b"hunter2", r#"{"m_cost": 64, "t_cost": 1}"#).unwrap();
+    assert!(hash.starts_with("$argon2id$v=19$m=64,t=1,p=1$"));
+    assert!(verify_password(b"hunter2", &hash).unwrap());
+    assert!(!verify_password(b"hunter3", &hash).unwrap());
+
+    let key = password_wasm::derive_key(b"hunter2", b"somesaltsomesalt", "").unwrap();
+    assert_eq!(key.len(), 32);
+}
+
+#[cfg(feature = "pbkdf2")]
+#[wasm_bindgen_test]
+fn pbkdf2_round_trip() {
+    let hash = hash_password("pbkdf2-sha512", b"hunter2", r#"{"rounds": 1000}"#).unwrap();
+    assert!(hash.starts_with("$pbkdf2-sha512$i=1000,l=32$"));
+    assert!(verify_password(b"hunter2", &hash).unwrap());
+    assert!(!verify_password(b"hunter3", &hash).unwrap());
+}
+
+#[wasm_bindgen_test]
+fn errors_are_thrown() {
+    assert!(hash_password("md5", b"hunter2", "").is_err());
+    assert!(verify_password(b"hunter2", "password").is_err());
+}
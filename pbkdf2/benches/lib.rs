@@ -1,3 +1,7 @@
+//! Run with `--features simd` to compare `pbkdf2_hmac_sha256` with the
+//! generic code, and add `sha1/force-soft,sha2/force-soft` to measure the
+//! portable code of the digests instead of the SHA extensions of the CPU.
+
 #![no_std]
 #![feature(test)]
 
@@ -29,6 +33,18 @@ pub fn pbkdf2_hmac_sha256_16384_20(bh: &mut Bencher) {
     });
 }
 
+#[cfg(feature = "simd")]
+#[bench]
+pub fn pbkdf2_hmac_sha256_simd_16384_20(bh: &mut Bencher) {
+    let password = b"my secure password";
+    let salt = b"salty salt";
+    let mut buf = [0u8; 20];
+    bh.iter(|| {
+        pbkdf2::pbkdf2_hmac_sha256(password, salt, 16_384, &mut buf);
+        test::black_box(&buf);
+    });
+}
+
 #[bench]
 pub fn pbkdf2_hmac_sha512_16384_20(bh: &mut Bencher) {
     let password = b"my secure password";
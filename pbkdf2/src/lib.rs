@@ -32,36 +32,35 @@
 //! ```
 //!
 //! If you want to use a different PRF, then you can use [`pbkdf2`][crate::pbkdf2]
-//! and [`pbkdf2_array`] functions.
+//! and [`pbkdf2_array`] functions. [`Pbkdf2Reader`] derives the same key
+//! material incrementally, one block at a time.
 //!
 //! This crates also provides the high-level password-hashing API through
 //! the [`Pbkdf2`] struct and traits defined in the
 //! [`password-hash`][password_hash] crate.
 //!
-//! Add the following to your crate's `Cargo.toml` to import it:
+//! Add the following to your crate's `Cargo.toml` to import it, where the
+//! `getrandom` feature generates salts with the random number generator of
+//! the OS (use `simple` alone to bring your own):
 //!
 //! ```toml
 //! [dependencies]
-//! pbkdf2 = { version = "0.12", features = ["simple"] }
-//! rand_core = { version = "0.6", features = ["std"] }
+//! pbkdf2 = { version = "0.12", features = ["getrandom"] }
 //! ```
 //!
 //! The following example demonstrates the high-level password hashing API:
 //!
 //! ```
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
-//! # #[cfg(all(feature = "simple", feature = "std"))]
+//! # #[cfg(all(feature = "getrandom", feature = "std"))]
 //! # {
 //! use pbkdf2::{
-//!     password_hash::{
-//!         rand_core::OsRng,
-//!         PasswordHash, PasswordHasher, PasswordVerifier, SaltString
-//!     },
+//!     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier},
 //!     Pbkdf2
 //! };
 //!
 //! let password = b"hunter42"; // Bad password; don't actually use!
-//! let salt = SaltString::generate(&mut OsRng);
+//! let salt = Pbkdf2::generate_salt();
 //!
 //! // Hash password to PHC string ($pbkdf2-sha256$...)
 //! let password_hash = Pbkdf2.hash_password(password, &salt)?.to_string();
@@ -73,6 +72,30 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! # Performance
+//!
+//! PBKDF2 spends its time in the compression function of the digest. On x86
+//! and x86-64, `sha1` and `sha2` detect the SHA extensions of the CPU at
+//! runtime and use them when present, which makes PBKDF2-HMAC-SHA256 about
+//! 3.5 times as fast as with their portable code. SHA-512 has no such
+//! extensions.
+//!
+//! The `simd` feature adds `pbkdf2_hmac_sha256`, which [`Pbkdf2`] then
+//! uses for `pbkdf2-sha256`. It runs the PBKDF2 rounds on the SHA extensions
+//! of x86 and x86-64, and on the SHA-2 instructions of AArch64, keeping the
+//! HMAC states in vector registers across rounds, and falls back to the
+//! compression function of `sha2` on CPUs without them: one binary is fast
+//! on every CPU. It is about 1.3 times as fast as [`pbkdf2_hmac::<Sha256>`]
+//! on x86, and on AArch64, where `sha2` only uses the SHA-2 instructions
+//! with its `asm` feature, as fast as they allow. On AArch64, the `simd`
+//! feature needs Rust 1.61.
+//!
+//! The `benches` compare the backends, with `cargo +nightly bench --features
+//! simd`, to which `sha1/force-soft,sha2/force-soft` adds the portable code
+//! of the digests.
+//!
+//! [`pbkdf2_hmac::<Sha256>`]: pbkdf2_hmac
 
 #![no_std]
 #![cfg_attr(docsrs, feature(doc_cfg))]
@@ -91,12 +114,18 @@ extern crate alloc;
 #[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
 pub use password_hash;
 
+mod reader;
+#[cfg(feature = "simd")]
+mod sha256;
 #[cfg(feature = "simple")]
 mod simple;
 
 #[cfg(feature = "hmac")]
 pub use hmac;
 
+pub use crate::reader::Pbkdf2Reader;
+#[cfg(feature = "simd")]
+pub use crate::sha256::pbkdf2_hmac_sha256;
 #[cfg(feature = "simple")]
 pub use crate::simple::{Algorithm, Params, Pbkdf2};
 
@@ -168,26 +197,64 @@ pub fn pbkdf2<PRF>(
     res: &mut [u8],
 ) -> Result<(), InvalidLength>
 where
-    PRF: KeyInit + Update + FixedOutput + Clone + Sync,
+    PRF: KeyInit + Update + FixedOutput + Clone,
 {
     let n = PRF::OutputSize::to_usize();
     // note: HMAC can be initialized with keys of any size,
     // so this panic never happens with it
     let prf = PRF::new_from_slice(password)?;
 
-    #[cfg(not(feature = "parallel"))]
-    {
-        for (i, chunk) in res.chunks_mut(n).enumerate() {
-            pbkdf2_body(i as u32, chunk, &prf, salt, rounds);
-        }
+    for (i, chunk) in res.chunks_mut(n).enumerate() {
+        pbkdf2_body(i as u32, chunk, &prf, salt, rounds);
     }
-    #[cfg(feature = "parallel")]
-    {
-        res.par_chunks_mut(n).enumerate().for_each(|(i, chunk)| {
-            pbkdf2_body(i as u32, chunk, &prf, salt, rounds);
-        });
+
+    Ok(())
+}
+
+/// A variant of the [`pbkdf2`][crate::pbkdf2] function which computes the
+/// blocks of the derived key in parallel, on the rayon thread pool.
+///
+/// The blocks are as long as the output of the PRF, e.g. 32 bytes for
+/// HMAC-SHA256: only keys longer than one block are computed in parallel,
+/// and the others on the calling thread as with [`pbkdf2`][crate::pbkdf2].
+/// The derived key is the same as with [`pbkdf2`][crate::pbkdf2].
+///
+/// ```
+/// use hex_literal::hex;
+/// use pbkdf2::pbkdf2_parallel;
+/// use hmac::Hmac;
+/// use sha2::Sha256;
+///
+/// let mut buf = [0u8; 40];
+/// pbkdf2_parallel::<Hmac<Sha256>>(b"password", b"salt", 4096, &mut buf)
+///     .expect("HMAC can be initialized with any key length");
+/// assert_eq!(
+///     buf,
+///     hex!("c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134af7ad98c1b458ce3f")
+/// );
+/// ```
+#[cfg(feature = "parallel")]
+#[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+pub fn pbkdf2_parallel<PRF>(
+    password: &[u8],
+    salt: &[u8],
+    rounds: u32,
+    res: &mut [u8],
+) -> Result<(), InvalidLength>
+where
+    PRF: KeyInit + Update + FixedOutput + Clone + Sync,
+{
+    let n = PRF::OutputSize::to_usize();
+    if res.len() <= n {
+        return pbkdf2::<PRF>(password, salt, rounds, res);
     }
 
+    let prf = PRF::new_from_slice(password)?;
+
+    res.par_chunks_mut(n).enumerate().for_each(|(i, chunk)| {
+        pbkdf2_body(i as u32, chunk, &prf, salt, rounds);
+    });
+
     Ok(())
 }
 
@@ -211,7 +278,7 @@ pub fn pbkdf2_array<PRF, const N: usize>(
     rounds: u32,
 ) -> Result<[u8; N], InvalidLength>
 where
-    PRF: KeyInit + Update + FixedOutput + Clone + Sync,
+    PRF: KeyInit + Update + FixedOutput + Clone,
 {
     let mut buf = [0u8; N];
     pbkdf2::<PRF>(password, salt, rounds, &mut buf).map(|()| buf)
@@ -234,8 +301,7 @@ where
 pub fn pbkdf2_hmac<D>(password: &[u8], salt: &[u8], rounds: u32, res: &mut [u8])
 where
     D: CoreProxy,
-    D::Core: Sync
-        + HashMarker
+    D::Core: HashMarker
         + UpdateCore
         + FixedOutputCore
         + BufferKindUser<BufferKind = Eager>
@@ -266,8 +332,7 @@ where
 pub fn pbkdf2_hmac_array<D, const N: usize>(password: &[u8], salt: &[u8], rounds: u32) -> [u8; N]
 where
     D: CoreProxy,
-    D::Core: Sync
-        + HashMarker
+    D::Core: HashMarker
         + UpdateCore
         + FixedOutputCore
         + BufferKindUser<BufferKind = Eager>
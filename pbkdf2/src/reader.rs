@@ -0,0 +1,121 @@
+//! Incremental derivation of PBKDF2 key material.
+
+use crate::xor;
+use digest::{FixedOutput, InvalidLength, KeyInit, Output, Update, XofReader};
+
+/// Reader of the PBKDF2 key material, computing it one block at a time.
+///
+/// The concatenation of everything read is the key derived by
+/// [`pbkdf2`][crate::pbkdf2] for the same total length, so any amount of
+/// key material can be read without allocating it at once, e.g. to derive
+/// several subkeys from one password lazily. The blocks are as long as the
+/// output of the PRF, e.g. 32 bytes for HMAC-SHA256, and each of them costs
+/// `rounds` invocations of the PRF.
+///
+/// ```
+/// use hex_literal::hex;
+/// use pbkdf2::Pbkdf2Reader;
+/// use hmac::Hmac;
+/// use sha2::Sha256;
+///
+/// let mut reader = Pbkdf2Reader::<Hmac<Sha256>>::new(b"password", b"salt", 4096)
+///     .expect("HMAC can be initialized with any key length");
+///
+/// let mut key1 = [0u8; 20];
+/// let mut key2 = [0u8; 20];
+/// reader.read(&mut key1);
+/// reader.read(&mut key2);
+/// assert_eq!(key1, hex!("c5e478d59288c841aa530db6845c4c8d962893a0"));
+/// assert_eq!(key2, hex!("01ce4e11a4963873aa98134af7ad98c1b458ce3f"));
+/// ```
+#[derive(Clone)]
+pub struct Pbkdf2Reader<PRF>
+where
+    PRF: KeyInit + Update + FixedOutput + Clone,
+{
+    /// PRF keyed with the password.
+    prf: PRF,
+    /// `prf` already updated with the salt.
+    salted: PRF,
+    rounds: u32,
+    /// Index of the next block to compute, starting at 1 as in RFC 2898.
+    next_index: u64,
+    /// Last computed block.
+    block: Output<PRF>,
+    /// Position of the unread bytes in `block`.
+    pos: usize,
+}
+
+impl<PRF> Pbkdf2Reader<PRF>
+where
+    PRF: KeyInit + Update + FixedOutput + Clone,
+{
+    /// Create a reader of the key material derived from `password` and `salt`
+    /// with `rounds` iterations.
+    ///
+    /// The error is that of the PRF rejecting `password` as its key, which
+    /// never happens with HMAC.
+    pub fn new(password: &[u8], salt: &[u8], rounds: u32) -> Result<Self, InvalidLength> {
+        let prf = PRF::new_from_slice(password)?;
+        let mut salted = prf.clone();
+        salted.update(salt);
+
+        let block = Output::<PRF>::default();
+        Ok(Self {
+            prf,
+            salted,
+            rounds,
+            next_index: 1,
+            pos: block.len(),
+            block,
+        })
+    }
+
+    /// Fill `out` with the next bytes of the key material.
+    ///
+    /// # Panics
+    /// If more than `2^32 - 1` blocks are read, the maximum length of a
+    /// PBKDF2 derived key.
+    pub fn read(&mut self, mut out: &mut [u8]) {
+        while !out.is_empty() {
+            if self.pos == self.block.len() {
+                self.next_block();
+            }
+
+            let n = out.len().min(self.block.len() - self.pos);
+            let (head, tail) = out.split_at_mut(n);
+            head.copy_from_slice(&self.block[self.pos..self.pos + n]);
+            self.pos += n;
+            out = tail;
+        }
+    }
+
+    fn next_block(&mut self) {
+        let index = u32::try_from(self.next_index).expect("PBKDF2 derived key is too long");
+
+        let mut prfc = self.salted.clone();
+        prfc.update(&index.to_be_bytes());
+        let mut salt = prfc.finalize_fixed();
+        self.block.copy_from_slice(&salt);
+
+        for _ in 1..self.rounds {
+            let mut prfc = self.prf.clone();
+            prfc.update(&salt);
+            salt = prfc.finalize_fixed();
+
+            xor(&mut self.block, &salt);
+        }
+
+        self.next_index += 1;
+        self.pos = 0;
+    }
+}
+
+impl<PRF> XofReader for Pbkdf2Reader<PRF>
+where
+    PRF: KeyInit + Update + FixedOutput + Clone,
+{
+    fn read(&mut self, buffer: &mut [u8]) {
+        Pbkdf2Reader::read(self, buffer);
+    }
+}
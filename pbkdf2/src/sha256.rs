@@ -0,0 +1,234 @@
+//! PBKDF2-HMAC-SHA256 built directly on the SHA-256 compression function.
+//!
+//! Each PBKDF2 round is two compressions, of the inner and the outer hash of
+//! HMAC, whose padded 64-byte blocks only differ in the digest they start
+//! with, so that nothing but the compression function runs per round. When
+//! the SHA extensions of the CPU are detected at runtime, the rounds run in
+//! the [`x86`] or [`aarch64`] backend, which keep the states in vector
+//! registers from one round to the next. `sha2` only uses the extensions of
+//! AArch64 with its `asm` feature.
+//!
+//! The portable code compresses with `sha2::compress256`, which uses the SHA
+//! extensions of x86 CPUs as well.
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86;
+
+#[cfg(target_arch = "aarch64")]
+mod aarch64;
+
+use sha2::{compress256, digest::generic_array::GenericArray, Digest, Sha256};
+
+/// Initial state of SHA-256.
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Round constants of SHA-256.
+#[cfg_attr(
+    not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")),
+    allow(dead_code)
+)]
+const K32: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Last two words of the padded block of a message of one digest after one
+/// block of key: the `0x80` byte, then the length in bits, as the words 8 to
+/// 15 of the block.
+const DIGEST_PADDING: [u32; 8] = [0x8000_0000, 0, 0, 0, 0, 0, 0, 768];
+
+/// Length of SHA-256 blocks.
+const BLOCK_LEN: usize = 64;
+
+/// Length of SHA-256 digests.
+const OUTPUT_LEN: usize = 32;
+
+/// HMAC-SHA256 keyed with the password: the SHA-256 states after the inner
+/// and the outer padded key.
+struct Prf {
+    inner: [u32; 8],
+    outer: [u32; 8],
+}
+
+/// State of the PBKDF2 rounds of a block of the derived key.
+struct Rounds {
+    /// Output of the last round.
+    u: [u32; 8],
+    /// XOR of the outputs of every round.
+    acc: [u32; 8],
+}
+
+impl Prf {
+    fn new(password: &[u8], compress: impl Fn(&mut [u32; 8], &[u8; BLOCK_LEN])) -> Self {
+        let mut key = [0u8; BLOCK_LEN];
+        if password.len() > BLOCK_LEN {
+            key[..OUTPUT_LEN].copy_from_slice(&Sha256::digest(password));
+        } else {
+            key[..password.len()].copy_from_slice(password);
+        }
+
+        let pad = |byte: u8| {
+            let mut block = key;
+            block.iter_mut().for_each(|b| *b ^= byte);
+            let mut state = IV;
+            compress(&mut state, &block);
+            state
+        };
+
+        Self {
+            inner: pad(0x36),
+            outer: pad(0x5c),
+        }
+    }
+}
+
+/// Continue hashing `parts` from `state`, after one block of key, and
+/// finalize the digest.
+fn finish(
+    mut state: [u32; 8],
+    parts: &[&[u8]],
+    compress: impl Fn(&mut [u32; 8], &[u8; BLOCK_LEN]),
+) -> [u32; 8] {
+    let mut block = [0u8; BLOCK_LEN];
+    let mut pos = 0;
+    let mut len = BLOCK_LEN as u64;
+
+    for &byte in parts.iter().flat_map(|part| part.iter()) {
+        block[pos] = byte;
+        pos += 1;
+        len += 1;
+        if pos == BLOCK_LEN {
+            compress(&mut state, &block);
+            pos = 0;
+        }
+    }
+
+    block[pos] = 0x80;
+    block[pos + 1..].iter_mut().for_each(|b| *b = 0);
+    if pos + 1 > BLOCK_LEN - 8 {
+        compress(&mut state, &block);
+        block = [0u8; BLOCK_LEN];
+    }
+    block[BLOCK_LEN - 8..].copy_from_slice(&(len * 8).to_be_bytes());
+    compress(&mut state, &block);
+
+    state
+}
+
+/// Padded block of a message of the digest `state` after one block of key.
+fn digest_block(state: &[u32; 8]) -> [u8; BLOCK_LEN] {
+    let mut block = [0u8; BLOCK_LEN];
+    let words = state.iter().chain(&DIGEST_PADDING);
+    for (chunk, word) in block.chunks_exact_mut(4).zip(words) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    block
+}
+
+/// Run `n` more PBKDF2 rounds with `compress` as the compression function.
+fn iterate(
+    prf: &Prf,
+    rounds: &mut Rounds,
+    n: u32,
+    compress: impl Fn(&mut [u32; 8], &[u8; BLOCK_LEN]),
+) {
+    for _ in 0..n {
+        let mut inner = prf.inner;
+        compress(&mut inner, &digest_block(&rounds.u));
+
+        rounds.u = prf.outer;
+        compress(&mut rounds.u, &digest_block(&inner));
+
+        let u = &rounds.u;
+        rounds.acc.iter_mut().zip(u).for_each(|(a, b)| *a ^= b);
+    }
+}
+
+/// PBKDF2-HMAC-SHA256 with `compress` as the compression function, the
+/// rounds after the first one of each block running in `iterate`.
+#[inline(always)]
+fn derive(
+    password: &[u8],
+    salt: &[u8],
+    rounds: u32,
+    res: &mut [u8],
+    compress: impl Fn(&mut [u32; 8], &[u8; BLOCK_LEN]) + Copy,
+    iterate: impl Fn(&Prf, &mut Rounds, u32),
+) {
+    let prf = Prf::new(password, compress);
+
+    for (i, chunk) in res.chunks_mut(OUTPUT_LEN).enumerate() {
+        let index = (i as u32 + 1).to_be_bytes();
+        let inner = finish(prf.inner, &[salt, &index], compress);
+        let u = finish(prf.outer, &[&digest_block(&inner)[..OUTPUT_LEN]], compress);
+
+        let mut state = Rounds { u, acc: u };
+        iterate(&prf, &mut state, rounds.saturating_sub(1));
+
+        let block = digest_block(&state.acc);
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+}
+
+/// The portable compression function.
+fn compress(state: &mut [u32; 8], block: &[u8; BLOCK_LEN]) {
+    compress256(
+        state,
+        core::slice::from_ref(GenericArray::from_slice(block)),
+    );
+}
+
+/// PBKDF2-HMAC-SHA256, the same as [`pbkdf2_hmac::<Sha256>`][1] but
+/// faster, using the SHA extensions of the CPU when they are detected at
+/// runtime, see the [crate docs](crate#performance).
+///
+/// ```
+/// use hex_literal::hex;
+/// use pbkdf2::pbkdf2_hmac_sha256;
+///
+/// let mut buf = [0u8; 20];
+/// pbkdf2_hmac_sha256(b"password", b"salt", 4096, &mut buf);
+/// assert_eq!(buf, hex!("c5e478d59288c841aa530db6845c4c8d962893a0"));
+/// ```
+///
+/// [1]: crate::pbkdf2_hmac
+#[cfg_attr(docsrs, doc(cfg(feature = "simd")))]
+pub fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], rounds: u32, res: &mut [u8]) {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    if x86::detect() {
+        // SAFETY: the SHA extensions are available
+        return derive(
+            password,
+            salt,
+            rounds,
+            res,
+            compress,
+            |prf, state, n| unsafe { x86::iterate(prf, state, n) },
+        );
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    if aarch64::detect() {
+        // SAFETY: the SHA-2 instructions are available
+        return derive(
+            password,
+            salt,
+            rounds,
+            res,
+            |state, block| unsafe { aarch64::compress(state, block) },
+            |prf, state, n| unsafe { aarch64::iterate(prf, state, n) },
+        );
+    }
+
+    derive(password, salt, rounds, res, compress, |prf, state, n| {
+        iterate(prf, state, n, compress)
+    });
+}
@@ -0,0 +1,153 @@
+//! PBKDF2 rounds on the SHA-2 instructions of AArch64.
+//!
+//! The SHA-256 states are held in two registers as `ABCD` and `EFGH`, which
+//! are also the first 8 message words of the next compression, followed by
+//! [`DIGEST_PADDING`], so the digests are never stored to memory during the
+//! rounds.
+//!
+//! The instructions are emitted with inline assembly, as in the `aarch64`
+//! backend of `sha2`, since their intrinsics aren't stable with the MSRV.
+
+use super::{Prf, Rounds, DIGEST_PADDING, K32};
+use core::arch::{aarch64::*, asm};
+
+cpufeatures::new!(sha2_hwcap, "sha2");
+
+/// Whether the CPU has the SHA-2 instructions.
+pub(super) fn detect() -> bool {
+    sha2_hwcap::get()
+}
+
+/// Compress `block` into `state`.
+///
+/// # Safety
+/// The CPU must have the SHA-2 instructions, see [`detect`].
+#[target_feature(enable = "sha2")]
+pub(super) unsafe fn compress(state: &mut [u32; 8], block: &[u8; 64]) {
+    // The words of the block are big-endian
+    let word = |i: usize| vreinterpretq_u32_u8(vrev32q_u8(vld1q_u8(block[16 * i..].as_ptr())));
+    let w = [word(0), word(1), word(2), word(3)];
+
+    let (abcd, efgh) = compress_words(load(state), w);
+    store(state, (abcd, efgh));
+}
+
+/// Run `n` more PBKDF2 rounds.
+///
+/// # Safety
+/// The CPU must have the SHA-2 instructions, see [`detect`].
+#[target_feature(enable = "sha2")]
+pub(super) unsafe fn iterate(prf: &Prf, rounds: &mut Rounds, n: u32) {
+    let inner = load(&prf.inner);
+    let outer = load(&prf.outer);
+    let padding = load(&DIGEST_PADDING);
+
+    let mut u = load(&rounds.u);
+    let mut acc = load(&rounds.acc);
+
+    for _ in 0..n {
+        let digest = compress_words(inner, [u.0, u.1, padding.0, padding.1]);
+        u = compress_words(outer, [digest.0, digest.1, padding.0, padding.1]);
+
+        acc = (veorq_u32(acc.0, u.0), veorq_u32(acc.1, u.1));
+    }
+
+    store(&mut rounds.u, u);
+    store(&mut rounds.acc, acc);
+}
+
+/// Load 8 words, in order.
+#[inline(always)]
+unsafe fn load(words: &[u32; 8]) -> (uint32x4_t, uint32x4_t) {
+    (vld1q_u32(words.as_ptr()), vld1q_u32(words[4..].as_ptr()))
+}
+
+/// Store 8 words, in order.
+#[inline(always)]
+unsafe fn store(words: &mut [u32; 8], (lo, hi): (uint32x4_t, uint32x4_t)) {
+    vst1q_u32(words.as_mut_ptr(), lo);
+    vst1q_u32(words[4..].as_mut_ptr(), hi);
+}
+
+/// Four rounds with the message words `w` of rounds `4 * $i` to
+/// `4 * $i + 3`.
+macro_rules! rounds4 {
+    ($abcd:ident, $efgh:ident, $w:expr, $i:expr) => {{
+        let wk = vaddq_u32($w, vld1q_u32(K32[4 * $i..].as_ptr()));
+        let abcd_prev = $abcd;
+        $abcd = sha256h(abcd_prev, $efgh, wk);
+        $efgh = sha256h2($efgh, abcd_prev, wk);
+    }};
+}
+
+/// Compute the next four message words into `$w0`, then run their rounds.
+macro_rules! schedule_rounds4 {
+    ($abcd:ident, $efgh:ident, $w0:ident, $w1:ident, $w2:ident, $w3:ident, $i:expr) => {{
+        $w0 = sha256su1(sha256su0($w0, $w1), $w2, $w3);
+        rounds4!($abcd, $efgh, $w0, $i);
+    }};
+}
+
+/// SHA-256 compression of the message words `w` from `state`.
+#[inline]
+#[target_feature(enable = "sha2")]
+unsafe fn compress_words(
+    state: (uint32x4_t, uint32x4_t),
+    w: [uint32x4_t; 4],
+) -> (uint32x4_t, uint32x4_t) {
+    let (mut abcd, mut efgh) = state;
+    let [mut w0, mut w1, mut w2, mut w3] = w;
+
+    rounds4!(abcd, efgh, w0, 0);
+    rounds4!(abcd, efgh, w1, 1);
+    rounds4!(abcd, efgh, w2, 2);
+    rounds4!(abcd, efgh, w3, 3);
+    for i in [4, 8, 12] {
+        schedule_rounds4!(abcd, efgh, w0, w1, w2, w3, i);
+        schedule_rounds4!(abcd, efgh, w1, w2, w3, w0, i + 1);
+        schedule_rounds4!(abcd, efgh, w2, w3, w0, w1, i + 2);
+        schedule_rounds4!(abcd, efgh, w3, w0, w1, w2, i + 3);
+    }
+
+    (vaddq_u32(abcd, state.0), vaddq_u32(efgh, state.1))
+}
+
+#[inline(always)]
+unsafe fn sha256h(mut abcd: uint32x4_t, efgh: uint32x4_t, wk: uint32x4_t) -> uint32x4_t {
+    asm!(
+        "SHA256H {:q}, {:q}, {:v}.4S",
+        inout(vreg) abcd, in(vreg) efgh, in(vreg) wk,
+        options(pure, nomem, nostack, preserves_flags)
+    );
+    abcd
+}
+
+#[inline(always)]
+unsafe fn sha256h2(mut efgh: uint32x4_t, abcd: uint32x4_t, wk: uint32x4_t) -> uint32x4_t {
+    asm!(
+        "SHA256H2 {:q}, {:q}, {:v}.4S",
+        inout(vreg) efgh, in(vreg) abcd, in(vreg) wk,
+        options(pure, nomem, nostack, preserves_flags)
+    );
+    efgh
+}
+
+#[inline(always)]
+unsafe fn sha256su0(mut w0_3: uint32x4_t, w4_7: uint32x4_t) -> uint32x4_t {
+    asm!(
+        "SHA256SU0 {:v}.4S, {:v}.4S",
+        inout(vreg) w0_3, in(vreg) w4_7,
+        options(pure, nomem, nostack, preserves_flags)
+    );
+    w0_3
+}
+
+#[inline(always)]
+unsafe fn sha256su1(mut tw0_3: uint32x4_t, w8_11: uint32x4_t, w12_15: uint32x4_t) -> uint32x4_t {
+    asm!(
+        "SHA256SU1 {:v}.4S, {:v}.4S, {:v}.4S",
+        inout(vreg) tw0_3, in(vreg) w8_11, in(vreg) w12_15,
+        options(pure, nomem, nostack, preserves_flags)
+    );
+    tw0_3
+}
@@ -0,0 +1,139 @@
+//! PBKDF2 rounds on the SHA extensions of x86 and x86-64.
+//!
+//! The SHA-256 states are held in two registers as `ABEF` and `CDGH`, the
+//! order the `sha256rnds2` instruction works with, as in the `x86` backend
+//! of `sha2`. The message of each compression is the digest of the previous
+//! one followed by [`DIGEST_PADDING`], so the digests are converted back to
+//! words in order, but never stored to memory during the rounds.
+
+#![allow(clippy::cast_ptr_alignment)]
+
+use super::{Prf, Rounds, DIGEST_PADDING, K32};
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+cpufeatures::new!(shani_cpuid, "sha", "sse2", "ssse3", "sse4.1");
+
+/// Whether the CPU has the SHA extensions.
+pub(super) fn detect() -> bool {
+    shani_cpuid::get()
+}
+
+/// Run `n` more PBKDF2 rounds.
+///
+/// # Safety
+/// The CPU must have the SHA extensions, see [`detect`].
+#[target_feature(enable = "sha,sse2,ssse3,sse4.1")]
+pub(super) unsafe fn iterate(prf: &Prf, rounds: &mut Rounds, n: u32) {
+    let inner = to_abef_cdgh(load(&prf.inner));
+    let outer = to_abef_cdgh(load(&prf.outer));
+    let padding = load(&DIGEST_PADDING);
+
+    let mut u = load(&rounds.u);
+    let mut acc = load(&rounds.acc);
+
+    for _ in 0..n {
+        let digest = from_abef_cdgh(compress(inner, [u.0, u.1, padding.0, padding.1]));
+        u = from_abef_cdgh(compress(outer, [digest.0, digest.1, padding.0, padding.1]));
+
+        acc = (_mm_xor_si128(acc.0, u.0), _mm_xor_si128(acc.1, u.1));
+    }
+
+    store(&mut rounds.u, u);
+    store(&mut rounds.acc, acc);
+}
+
+/// Load 8 words, in order.
+#[inline]
+#[target_feature(enable = "sse2")]
+unsafe fn load(words: &[u32; 8]) -> (__m128i, __m128i) {
+    let ptr = words.as_ptr() as *const __m128i;
+    (_mm_loadu_si128(ptr), _mm_loadu_si128(ptr.add(1)))
+}
+
+/// Store 8 words, in order.
+#[inline]
+#[target_feature(enable = "sse2")]
+unsafe fn store(words: &mut [u32; 8], (lo, hi): (__m128i, __m128i)) {
+    let ptr = words.as_mut_ptr() as *mut __m128i;
+    _mm_storeu_si128(ptr, lo);
+    _mm_storeu_si128(ptr.add(1), hi);
+}
+
+/// Convert a state from the words `ABCD` and `EFGH` to `ABEF` and `CDGH`.
+#[inline]
+#[target_feature(enable = "sse2,ssse3,sse4.1")]
+unsafe fn to_abef_cdgh((dcba, hgfe): (__m128i, __m128i)) -> (__m128i, __m128i) {
+    let cdab = _mm_shuffle_epi32(dcba, 0xB1);
+    let efgh = _mm_shuffle_epi32(hgfe, 0x1B);
+    (
+        _mm_alignr_epi8(cdab, efgh, 8),
+        _mm_blend_epi16(efgh, cdab, 0xF0),
+    )
+}
+
+/// Convert a state from `ABEF` and `CDGH` back to the words `ABCD` and
+/// `EFGH`.
+#[inline]
+#[target_feature(enable = "sse2,ssse3,sse4.1")]
+unsafe fn from_abef_cdgh((abef, cdgh): (__m128i, __m128i)) -> (__m128i, __m128i) {
+    let feba = _mm_shuffle_epi32(abef, 0x1B);
+    let dchg = _mm_shuffle_epi32(cdgh, 0xB1);
+    (
+        _mm_blend_epi16(feba, dchg, 0xF0),
+        _mm_alignr_epi8(dchg, feba, 8),
+    )
+}
+
+/// Four rounds with the message words `w` of rounds `4 * $i` to
+/// `4 * $i + 3`.
+macro_rules! rounds4 {
+    ($abef:ident, $cdgh:ident, $w:expr, $i:expr) => {{
+        let k = _mm_loadu_si128(K32[4 * $i..].as_ptr() as *const __m128i);
+        let wk = _mm_add_epi32($w, k);
+        $cdgh = _mm_sha256rnds2_epu32($cdgh, $abef, wk);
+        $abef = _mm_sha256rnds2_epu32($abef, $cdgh, _mm_shuffle_epi32(wk, 0x0E));
+    }};
+}
+
+/// Compute the next four message words into `$w4`, then run their rounds.
+macro_rules! schedule_rounds4 {
+    ($abef:ident, $cdgh:ident, $w0:ident, $w1:ident, $w2:ident, $w3:ident, $w4:ident, $i:expr) => {{
+        let t1 = _mm_sha256msg1_epu32($w0, $w1);
+        let t2 = _mm_alignr_epi8($w3, $w2, 4);
+        $w4 = _mm_sha256msg2_epu32(_mm_add_epi32(t1, t2), $w3);
+        rounds4!($abef, $cdgh, $w4, $i);
+    }};
+}
+
+/// SHA-256 compression of the message words `w` from `state`, both as
+/// `ABEF` and `CDGH`.
+#[inline]
+#[target_feature(enable = "sha,sse2,ssse3,sse4.1")]
+unsafe fn compress(state: (__m128i, __m128i), w: [__m128i; 4]) -> (__m128i, __m128i) {
+    let (mut abef, mut cdgh) = state;
+    let [mut w0, mut w1, mut w2, mut w3] = w;
+    let mut w4;
+
+    rounds4!(abef, cdgh, w0, 0);
+    rounds4!(abef, cdgh, w1, 1);
+    rounds4!(abef, cdgh, w2, 2);
+    rounds4!(abef, cdgh, w3, 3);
+    schedule_rounds4!(abef, cdgh, w0, w1, w2, w3, w4, 4);
+    schedule_rounds4!(abef, cdgh, w1, w2, w3, w4, w0, 5);
+    schedule_rounds4!(abef, cdgh, w2, w3, w4, w0, w1, 6);
+    schedule_rounds4!(abef, cdgh, w3, w4, w0, w1, w2, 7);
+    schedule_rounds4!(abef, cdgh, w4, w0, w1, w2, w3, 8);
+    schedule_rounds4!(abef, cdgh, w0, w1, w2, w3, w4, 9);
+    schedule_rounds4!(abef, cdgh, w1, w2, w3, w4, w0, 10);
+    schedule_rounds4!(abef, cdgh, w2, w3, w4, w0, w1, 11);
+    schedule_rounds4!(abef, cdgh, w3, w4, w0, w1, w2, 12);
+    schedule_rounds4!(abef, cdgh, w4, w0, w1, w2, w3, 13);
+    schedule_rounds4!(abef, cdgh, w0, w1, w2, w3, w4, 14);
+    schedule_rounds4!(abef, cdgh, w1, w2, w3, w4, w0, 15);
+
+    (_mm_add_epi32(abef, state.0), _mm_add_epi32(cdgh, state.1))
+}
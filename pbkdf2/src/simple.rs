@@ -1,21 +1,128 @@
 //! Implementation of the `password-hash` crate API.
 
 use crate::pbkdf2_hmac;
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::{cmp::Ordering, fmt, str::FromStr};
 use password_hash::{
-    errors::InvalidValue, Decimal, Error, Ident, Output, ParamsString, PasswordHash,
-    PasswordHasher, Result, Salt,
+    errors::InvalidValue, rand_core::CryptoRngCore, Decimal, Encoding, Error, Ident, McfHasher,
+    Output, ParamsString, PasswordHash, PasswordHasher, Result, Salt, SaltString,
 };
-use sha2::{Sha256, Sha512};
+use sha2::Sha512;
+use subtle::ConstantTimeEq;
+
+#[cfg(feature = "getrandom")]
+use password_hash::rand_core::OsRng;
+
+#[cfg(not(feature = "simd"))]
+use sha2::Sha256;
+
+#[cfg(doc)]
+use password_hash::PasswordVerifier;
 
 #[cfg(feature = "sha1")]
 use sha1::Sha1;
 
+#[cfg(feature = "sha3")]
+use sha3::{Sha3_256, Sha3_512};
+
 /// PBKDF2 type for use with [`PasswordHasher`].
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 #[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
 pub struct Pbkdf2;
 
+impl Pbkdf2 {
+    /// Generate a random salt of [`Salt::RECOMMENDED_LENGTH`] bytes with the
+    /// random number generator of the OS.
+    #[cfg(feature = "getrandom")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
+    pub fn generate_salt() -> SaltString {
+        SaltString::generate(OsRng)
+    }
+
+    /// Hash `password` to a PHC string with the default [`Algorithm`] and
+    /// [`Params`], and a random salt generated with the random number
+    /// generator of the OS.
+    #[cfg(feature = "getrandom")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
+    pub fn hash_password_simple(&self, password: &[u8]) -> Result<String> {
+        self.hash_password_simple_with_rng(password, OsRng)
+    }
+
+    /// Hash `password` to a PHC string with the default [`Algorithm`] and
+    /// [`Params`], and a random salt generated with `rng`.
+    pub fn hash_password_simple_with_rng(
+        &self,
+        password: &[u8],
+        rng: impl CryptoRngCore,
+    ) -> Result<String> {
+        let salt = SaltString::generate(rng);
+        Ok(self.hash_password(password, &salt)?.to_string())
+    }
+
+    /// Hash `password` to a PHC string with `salt` taken as is, rather than
+    /// B64-decoded as [`PasswordHasher`] does, for salts stored verbatim by
+    /// other systems, e.g. ASCII salts.
+    ///
+    /// The PHC string holds the B64 encoding of `salt`, so it verifies as
+    /// any other with [`PasswordVerifier`]. `salt` must be 3 to 48 bytes
+    /// long to fit in a PHC string.
+    pub fn hash_password_raw_salt(
+        &self,
+        password: &[u8],
+        salt: &[u8],
+        algorithm: Algorithm,
+        params: Params,
+    ) -> Result<String> {
+        let mut buf = [0u8; Salt::MAX_LENGTH];
+        let salt = Encoding::B64
+            .encode(salt, &mut buf)
+            .map_err(|_| Error::SaltInvalid(InvalidValue::TooLong))?;
+
+        Ok(self
+            .hash_password_customized(
+                password,
+                Some(algorithm.ident()),
+                None,
+                params,
+                Salt::from_b64(salt)?,
+            )?
+            .to_string())
+    }
+
+    /// Verify `password` against a PHC string, comparing the computed output
+    /// with the one of `hash` in constant time with [`ConstantTimeEq`].
+    ///
+    /// [`PasswordVerifier::verify_password`] compares them in constant time
+    /// too, through the [`PartialEq`] impl of [`Output`]: this method doesn't
+    /// rely on that impl.
+    pub fn verify_password_timing_safe(
+        &self,
+        password: &[u8],
+        hash: &PasswordHash<'_>,
+    ) -> Result<()> {
+        if let (Some(salt), Some(expected_output)) = (&hash.salt, &hash.hash) {
+            let computed_hash = self.hash_password_customized(
+                password,
+                Some(hash.algorithm),
+                hash.version,
+                Params::try_from(hash)?,
+                *salt,
+            )?;
+
+            if let Some(computed_output) = &computed_hash.hash {
+                if bool::from(computed_output.ct_eq(expected_output)) {
+                    return Ok(());
+                }
+            }
+        }
+
+        Err(Error::Password)
+    }
+}
+
 impl PasswordHasher for Pbkdf2 {
     type Params = Params;
 
@@ -34,16 +141,25 @@ impl PasswordHasher for Pbkdf2 {
             return Err(Error::Version);
         }
 
+        let params = Params::new(params.rounds, params.output_length)?;
+
         let salt = salt.into();
         let mut salt_arr = [0u8; 64];
-        let salt_bytes = salt.decode_b64(&mut salt_arr)?;
+        let salt_bytes = decode_salt(salt, &mut salt_arr)?;
 
         let output = Output::init_with(params.output_length, |out| {
             let f = match algorithm {
                 #[cfg(feature = "sha1")]
                 Algorithm::Pbkdf2Sha1 => pbkdf2_hmac::<Sha1>,
+                #[cfg(not(feature = "simd"))]
                 Algorithm::Pbkdf2Sha256 => pbkdf2_hmac::<Sha256>,
+                #[cfg(feature = "simd")]
+                Algorithm::Pbkdf2Sha256 => crate::pbkdf2_hmac_sha256,
                 Algorithm::Pbkdf2Sha512 => pbkdf2_hmac::<Sha512>,
+                #[cfg(feature = "sha3")]
+                Algorithm::Pbkdf2Sha3_256 => pbkdf2_hmac::<Sha3_256>,
+                #[cfg(feature = "sha3")]
+                Algorithm::Pbkdf2Sha3_512 => pbkdf2_hmac::<Sha3_512>,
             };
 
             f(password, salt_bytes, params.rounds, out);
@@ -60,6 +176,56 @@ impl PasswordHasher for Pbkdf2 {
     }
 }
 
+impl McfHasher for Pbkdf2 {
+    /// Upgrade a hash string of Python's [passlib] to a PHC string.
+    ///
+    /// passlib writes `$pbkdf2-sha256$<ROUNDS>$<SALT>$<HASH>` strings, and
+    /// the same with `$pbkdf2-sha512$`, or `$pbkdf2$` for PBKDF2-SHA1 (which
+    /// needs the `sha1` feature). The salt and the hash are encoded with an
+    /// adapted Base64 alphabet, using `.` instead of `+`. The salt is kept
+    /// as is in the PHC string, as [`Pbkdf2`] decodes salts in either
+    /// alphabet.
+    ///
+    /// [passlib]: https://passlib.readthedocs.io/en/stable/lib/passlib.hash.pbkdf2_digest.html
+    fn upgrade_mcf_hash<'a>(&self, hash: &'a str) -> Result<PasswordHash<'a>> {
+        let fields: Vec<&'a str> = hash.split('$').collect();
+        let (algorithm, rounds, salt, output) = match fields[..] {
+            ["", algorithm, rounds, salt, output] => (algorithm, rounds, salt, output),
+            _ => return Err(Error::PhcStringField),
+        };
+
+        let algorithm = Algorithm::new(algorithm)?;
+
+        if rounds.is_empty() || !rounds.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(InvalidValue::InvalidFormat.param_error());
+        }
+        let rounds = rounds
+            .parse()
+            .map_err(|_| InvalidValue::TooLong.param_error())?;
+
+        let output = Output::b64_decode(&output.replace('.', "+"))?;
+        let params = Params::new(rounds, output.len())?;
+
+        Ok(PasswordHash {
+            algorithm: algorithm.ident(),
+            version: None,
+            params: params.try_into()?,
+            salt: Some(Salt::from_b64(salt)?),
+            hash: Some(output),
+        })
+    }
+}
+
+/// Decode `salt` into `buf`, in the standard Base64 alphabet or in the one
+/// of passlib, which uses `.` instead of `+`.
+fn decode_salt<'b>(salt: Salt<'_>, buf: &'b mut [u8]) -> Result<&'b [u8]> {
+    if salt.as_str().contains('.') {
+        Salt::from_b64(&salt.as_str().replace('.', "+"))?.decode_b64(buf)
+    } else {
+        salt.decode_b64(buf)
+    }
+}
+
 /// PBKDF2 variants.
 ///
 /// <https://en.wikipedia.org/wiki/PBKDF2>
@@ -77,6 +243,16 @@ pub enum Algorithm {
 
     /// PBKDF2 SHA-512
     Pbkdf2Sha512,
+
+    /// PBKDF2 SHA3-256
+    #[cfg(feature = "sha3")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sha3")))]
+    Pbkdf2Sha3_256,
+
+    /// PBKDF2 SHA3-512
+    #[cfg(feature = "sha3")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sha3")))]
+    Pbkdf2Sha3_512,
 }
 
 impl Default for Algorithm {
@@ -102,6 +278,14 @@ impl Algorithm {
     /// PBKDF2 (SHA-512) algorithm identifier
     pub const PBKDF2_SHA512_IDENT: Ident<'static> = Ident::new_unwrap("pbkdf2-sha512");
 
+    /// PBKDF2 (SHA3-256) algorithm identifier
+    #[cfg(feature = "sha3")]
+    pub const PBKDF2_SHA3_256_IDENT: Ident<'static> = Ident::new_unwrap("pbkdf2-sha3-256");
+
+    /// PBKDF2 (SHA3-512) algorithm identifier
+    #[cfg(feature = "sha3")]
+    pub const PBKDF2_SHA3_512_IDENT: Ident<'static> = Ident::new_unwrap("pbkdf2-sha3-512");
+
     /// Parse an [`Algorithm`] from the provided string.
     pub fn new(id: impl AsRef<str>) -> Result<Self> {
         id.as_ref().parse()
@@ -114,6 +298,10 @@ impl Algorithm {
             Algorithm::Pbkdf2Sha1 => Self::PBKDF2_SHA1_IDENT,
             Algorithm::Pbkdf2Sha256 => Self::PBKDF2_SHA256_IDENT,
             Algorithm::Pbkdf2Sha512 => Self::PBKDF2_SHA512_IDENT,
+            #[cfg(feature = "sha3")]
+            Algorithm::Pbkdf2Sha3_256 => Self::PBKDF2_SHA3_256_IDENT,
+            #[cfg(feature = "sha3")]
+            Algorithm::Pbkdf2Sha3_512 => Self::PBKDF2_SHA3_512_IDENT,
         }
     }
 
@@ -158,6 +346,10 @@ impl<'a> TryFrom<Ident<'a>> for Algorithm {
             Self::PBKDF2_SHA1_IDENT => Ok(Algorithm::Pbkdf2Sha1),
             Self::PBKDF2_SHA256_IDENT => Ok(Algorithm::Pbkdf2Sha256),
             Self::PBKDF2_SHA512_IDENT => Ok(Algorithm::Pbkdf2Sha512),
+            #[cfg(feature = "sha3")]
+            Self::PBKDF2_SHA3_256_IDENT => Ok(Algorithm::Pbkdf2Sha3_256),
+            #[cfg(feature = "sha3")]
+            Self::PBKDF2_SHA3_512_IDENT => Ok(Algorithm::Pbkdf2Sha3_512),
             _ => Err(Error::Algorithm),
         }
     }
@@ -183,6 +375,42 @@ impl Params {
     ///
     /// [OWASP cheat sheet]: https://cheatsheetseries.owasp.org/cheatsheets/Password_Storage_Cheat_Sheet.html
     pub const RECOMMENDED_ROUNDS: usize = 600_000;
+
+    /// Minimum number of rounds: RFC 8018 requires a positive iteration
+    /// count.
+    pub const MIN_ROUNDS: u32 = 1;
+
+    /// Minimum size of the output (in bytes).
+    pub const MIN_OUTPUT_LENGTH: usize = Output::MIN_LENGTH;
+
+    /// Maximum size of the output (in bytes): the longest output which fits
+    /// in a PHC string.
+    ///
+    /// RFC 8018 allows derived keys up to `(2^32 - 1) * hLen` bytes, which
+    /// [`pbkdf2_hmac`] can compute.
+    pub const MAX_OUTPUT_LENGTH: usize = Output::MAX_LENGTH;
+
+    /// Create new params, validating them before any work is done.
+    ///
+    /// # Returns
+    /// - `Ok(Params)` if `rounds` is at least [`Params::MIN_ROUNDS`] and
+    ///   `output_length` is between [`Params::MIN_OUTPUT_LENGTH`] and
+    ///   [`Params::MAX_OUTPUT_LENGTH`]
+    /// - `Err(Error::ParamValueInvalid)` otherwise.
+    pub fn new(rounds: u32, output_length: usize) -> Result<Self> {
+        if rounds < Self::MIN_ROUNDS || output_length < Self::MIN_OUTPUT_LENGTH {
+            return Err(InvalidValue::TooShort.param_error());
+        }
+
+        if output_length > Self::MAX_OUTPUT_LENGTH {
+            return Err(InvalidValue::TooLong.param_error());
+        }
+
+        Ok(Params {
+            rounds,
+            output_length,
+        })
+    }
 }
 
 impl Default for Params {
@@ -232,7 +460,7 @@ impl<'a> TryFrom<&'a PasswordHash<'a>> for Params {
             params.output_length = len;
         }
 
-        Ok(params)
+        Params::new(params.rounds, params.output_length)
     }
 }
 
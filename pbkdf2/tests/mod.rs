@@ -115,3 +115,67 @@ fn gost() {
         ),
     );
 }
+
+/// The parallel computation gives the same keys, of one or more blocks.
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel() {
+    use hmac::Hmac;
+    use pbkdf2::{pbkdf2, pbkdf2_parallel};
+    use sha2::Sha256;
+
+    for len in [0, 1, 31, 32, 33, 64, 100, 1000] {
+        let mut expected = vec![0u8; len];
+        let mut key = vec![0u8; len];
+        pbkdf2::<Hmac<Sha256>>(b"password", b"salt", 1000, &mut expected).unwrap();
+        pbkdf2_parallel::<Hmac<Sha256>>(b"password", b"salt", 1000, &mut key).unwrap();
+        assert_eq!(key, expected, "{}", len);
+    }
+}
+
+/// Reading the key material in pieces gives the key of `pbkdf2`.
+#[test]
+fn reader() {
+    use hmac::Hmac;
+    use pbkdf2::{pbkdf2, Pbkdf2Reader};
+    use sha2::Sha256;
+
+    let mut expected = [0u8; 100];
+    pbkdf2::<Hmac<Sha256>>(b"password", b"salt", 1000, &mut expected).unwrap();
+
+    for piece in [1, 7, 32, 33, 100] {
+        let mut reader = Pbkdf2Reader::<Hmac<Sha256>>::new(b"password", b"salt", 1000).unwrap();
+        let mut key = [0u8; 100];
+        for chunk in key.chunks_mut(piece) {
+            reader.read(chunk);
+        }
+        assert_eq!(key, expected, "{}", piece);
+    }
+}
+
+#[cfg(feature = "simd")]
+#[test]
+fn hmac_sha256() {
+    use pbkdf2::{pbkdf2_hmac, pbkdf2_hmac_sha256};
+    use sha2::Sha256;
+
+    // Passwords and salts around the block length of SHA-256, so that the
+    // padding takes one or two blocks
+    let bytes: Vec<u8> = (0..=255).collect();
+    for len in [0, 1, 31, 32, 55, 56, 59, 60, 63, 64, 65, 119, 128, 200] {
+        let password = &bytes[..len];
+        let salt = &bytes[255 - len..][..len];
+        for (rounds, out_len) in [(1, 20), (2, 32), (3, 33), (5, 100)] {
+            let mut expected = vec![0u8; out_len];
+            pbkdf2_hmac::<Sha256>(password, salt, rounds, &mut expected);
+
+            let mut out = vec![0u8; out_len];
+            pbkdf2_hmac_sha256(password, salt, rounds, &mut out);
+            assert_eq!(out, expected, "len {}, rounds {}", len, rounds);
+        }
+    }
+
+    let mut out = [0u8; 20];
+    pbkdf2_hmac_sha256(b"password", b"salt", 600_000, &mut out);
+    assert_eq!(out, hex!("669cfe52482116fda1aa2cbe409b2f56c8e45637"));
+}
@@ -6,9 +6,13 @@
 
 use hex_literal::hex;
 use pbkdf2::{
-    password_hash::{PasswordHasher, Salt},
+    password_hash::{
+        errors::InvalidValue, Error, McfHasher, Output, PasswordHash, PasswordHasher,
+        PasswordVerifier, Salt,
+    },
     Algorithm, Params, Pbkdf2,
 };
+use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
 
 const PASSWORD: &str = "password";
 const SALT_B64: &str = "c2FsdA"; // "salt"
@@ -39,3 +43,245 @@ fn hash_with_default_algorithm() {
     let expected_output = hex!("c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a");
     assert_eq!(hash.hash.unwrap().as_ref(), expected_output);
 }
+
+/// PBKDF2-SHA3 vectors computed with Python's `hashlib.pbkdf2_hmac`.
+#[cfg(feature = "sha3")]
+#[test]
+fn hash_with_sha3() {
+    let salt = Salt::from_b64(SALT_B64).unwrap();
+
+    for (algorithm, output_length, expected_output) in [
+        (
+            Algorithm::Pbkdf2Sha3_256,
+            32,
+            &hex!("778b6e237a0f49621549ff70d218d2080756b9fb38d71b5d7ef447fa2254af61")[..],
+        ),
+        (
+            Algorithm::Pbkdf2Sha3_512,
+            64,
+            &hex!(
+                "2bfaf2d5ceb6d10f5e262cd902488cfd4489614ecd6709e5ee395dc33f2e9ad7"
+                "f89d31ad6781e90940e9e534ff44b817159ddcd3bdce3373541186b727340231"
+            )[..],
+        ),
+    ] {
+        let params = Params {
+            rounds: 4096,
+            output_length,
+        };
+
+        let hash = Pbkdf2
+            .hash_password_customized(
+                PASSWORD.as_bytes(),
+                Some(algorithm.ident()),
+                None,
+                params,
+                salt,
+            )
+            .unwrap();
+
+        assert_eq!(hash.hash.unwrap().as_ref(), expected_output);
+
+        // The PHC string round-trips
+        let phc = hash.to_string();
+        assert!(phc.starts_with(&format!("${algorithm}$")));
+        let parsed = PasswordHash::new(&phc).unwrap();
+        assert_eq!(Algorithm::try_from(parsed.algorithm), Ok(algorithm));
+        assert_eq!(Pbkdf2.verify_password(PASSWORD.as_bytes(), &parsed), Ok(()));
+    }
+
+    assert_eq!(Algorithm::Pbkdf2Sha3_256.as_str(), "pbkdf2-sha3-256");
+    assert_eq!(Algorithm::Pbkdf2Sha3_512.as_str(), "pbkdf2-sha3-512");
+}
+
+#[test]
+fn hash_password_raw_salt() {
+    let params = Params {
+        rounds: 4096,
+        output_length: 32,
+    };
+
+    let phc = Pbkdf2
+        .hash_password_raw_salt(b"password", b"salt", Algorithm::Pbkdf2Sha256, params)
+        .unwrap();
+    assert_eq!(
+        phc,
+        "$pbkdf2-sha256$i=4096,l=32$c2FsdA$xeR41ZKIyEGqUw22hFxMjZYok6ABzk4RpJY4c6qYE0o"
+    );
+
+    let hash = PasswordHash::new(&phc).unwrap();
+    assert_eq!(Pbkdf2.verify_password(b"password", &hash), Ok(()));
+
+    // Salts too short or too long for a PHC string
+    for salt in [&b"ab"[..], &[b'a'; 49]] {
+        assert!(matches!(
+            Pbkdf2.hash_password_raw_salt(b"password", salt, Algorithm::Pbkdf2Sha256, params),
+            Err(Error::SaltInvalid(_))
+        ));
+    }
+}
+
+#[test]
+fn params_validation() {
+    assert_eq!(
+        Params::new(4096, 32).unwrap(),
+        Params {
+            rounds: 4096,
+            output_length: 32,
+        }
+    );
+    assert!(Params::new(Params::MIN_ROUNDS, Params::MAX_OUTPUT_LENGTH).is_ok());
+
+    let too_short = Err(InvalidValue::TooShort.param_error());
+    let too_long = Err(InvalidValue::TooLong.param_error());
+    assert_eq!(Params::new(0, 32), too_short);
+    assert_eq!(Params::new(4096, Params::MIN_OUTPUT_LENGTH - 1), too_short);
+    assert_eq!(Params::new(4096, Params::MAX_OUTPUT_LENGTH + 1), too_long);
+}
+
+/// Invalid params are rejected before hashing, even if built without
+/// [`Params::new`].
+#[test]
+fn hash_with_invalid_params() {
+    let salt = Salt::from_b64(SALT_B64).unwrap();
+    let params = Params {
+        rounds: 0,
+        output_length: 32,
+    };
+
+    assert_eq!(
+        Pbkdf2.hash_password_customized(PASSWORD.as_bytes(), None, None, params, salt),
+        Err(InvalidValue::TooShort.param_error())
+    );
+}
+
+#[test]
+fn parse_invalid_rounds() {
+    let hash = PasswordHash::new("$pbkdf2-sha256$i=0,l=32$c2FsdA").unwrap();
+    assert_eq!(
+        Params::try_from(&hash),
+        Err(InvalidValue::TooShort.param_error())
+    );
+}
+
+#[test]
+fn verify_password_timing_safe() {
+    let hash = PasswordHash::new(
+        "$pbkdf2-sha256$i=4096,l=32$c2FsdA$xeR41ZKIyEGqUw22hFxMjZYok6ABzk4RpJY4c6qYE0o",
+    )
+    .unwrap();
+    assert_eq!(
+        Pbkdf2.verify_password_timing_safe(b"password", &hash),
+        Ok(())
+    );
+    assert_eq!(
+        Pbkdf2.verify_password_timing_safe(b"wrong", &hash),
+        Err(Error::Password)
+    );
+
+    // Outputs differing in their first or last byte only are rejected
+    let output = hash.hash.unwrap();
+    for i in [0, output.len() - 1] {
+        let mut bytes = output.as_bytes().to_vec();
+        bytes[i] ^= 1;
+
+        let tampered = PasswordHash {
+            hash: Some(Output::new(&bytes).unwrap()),
+            ..hash.clone()
+        };
+        assert_eq!(
+            Pbkdf2.verify_password_timing_safe(b"password", &tampered),
+            Err(Error::Password)
+        );
+    }
+}
+
+#[test]
+fn hash_password_simple_with_rng() {
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let hash = Pbkdf2
+        .hash_password_simple_with_rng(PASSWORD.as_bytes(), &mut rng)
+        .unwrap();
+    let parsed = PasswordHash::new(&hash).unwrap();
+
+    assert_eq!(parsed.algorithm, Algorithm::default().ident());
+    assert_eq!(Params::try_from(&parsed).unwrap(), Params::default());
+    assert_eq!(Pbkdf2.verify_password(PASSWORD.as_bytes(), &parsed), Ok(()));
+}
+
+#[cfg(feature = "getrandom")]
+#[test]
+fn hash_password_simple() {
+    let first = Pbkdf2.hash_password_simple(PASSWORD.as_bytes()).unwrap();
+    let second = Pbkdf2.hash_password_simple(PASSWORD.as_bytes()).unwrap();
+    assert_ne!(first, second, "salts must be random");
+
+    for hash in [first, second] {
+        let parsed = PasswordHash::new(&hash).unwrap();
+        assert_eq!(Pbkdf2.verify_password(PASSWORD.as_bytes(), &parsed), Ok(()));
+    }
+}
+
+#[cfg(feature = "getrandom")]
+#[test]
+fn generate_salt() {
+    let salt = Pbkdf2::generate_salt();
+    let mut buf = [0u8; Salt::RECOMMENDED_LENGTH];
+    assert_eq!(
+        salt.decode_b64(&mut buf).unwrap().len(),
+        Salt::RECOMMENDED_LENGTH
+    );
+    assert_ne!(salt, Pbkdf2::generate_salt());
+}
+
+/// Hashes of "password" in the formats of passlib, with a salt whose
+/// encoding contains `.`.
+const PASSLIB_HASHES: &[&str] = &[
+    #[cfg(feature = "sha1")]
+    "$pbkdf2$131000$....AQIDBAUGBwgJCgsM.w$YmV2f8KP/5hrGqr0I8BaqYilE0g",
+    "$pbkdf2-sha256$29000$....AQIDBAUGBwgJCgsM.w$X9zrVMgrU1jgTGVxjON5x30r01VNH3YlyEMgGELVktU",
+    "$pbkdf2-sha512$25000$....AQIDBAUGBwgJCgsM.w$dAQtPyYyxnyMtlHAJUkhg9hiPGx.oWGwd5knc4Xz1GLDWMGkrftUdfcoFz1GtnnC.PJOcZtgaQuGWLP4BDuvDA",
+];
+
+#[test]
+fn upgrade_passlib_hashes() {
+    for mcf in PASSLIB_HASHES {
+        let hash = Pbkdf2.upgrade_mcf_hash(mcf).unwrap();
+        assert_eq!(Pbkdf2.verify_password(b"password", &hash), Ok(()));
+        assert_eq!(
+            Pbkdf2.verify_password(b"wrong", &hash),
+            Err(Error::Password)
+        );
+
+        // The PHC string round-trips
+        let phc = hash.to_string();
+        let parsed = PasswordHash::new(&phc).unwrap();
+        assert_eq!(Pbkdf2.verify_password(b"password", &parsed), Ok(()));
+    }
+
+    let hash = Pbkdf2
+        .upgrade_mcf_hash(PASSLIB_HASHES[PASSLIB_HASHES.len() - 2])
+        .unwrap();
+    assert_eq!(
+        hash.to_string(),
+        "$pbkdf2-sha256$i=29000,l=32$....AQIDBAUGBwgJCgsM.w$X9zrVMgrU1jgTGVxjON5x30r01VNH3YlyEMgGELVktU"
+    );
+}
+
+#[test]
+fn upgrade_invalid_passlib_hashes() {
+    for (mcf, err) in [
+        ("$6$rounds=5000$salt$hash", Error::Algorithm),
+        ("$pbkdf2-sha256$29000$c2FsdHNhbHQ", Error::PhcStringField),
+        (
+            "$pbkdf2-sha256$+29000$c2FsdHNhbHQ$X9zrVMgrU1jgTGVxjON5x30r01VNH3YlyEMgGELVktU",
+            InvalidValue::InvalidFormat.param_error(),
+        ),
+        (
+            "$pbkdf2-sha256$0$c2FsdHNhbHQ$X9zrVMgrU1jgTGVxjON5x30r01VNH3YlyEMgGELVktU",
+            InvalidValue::TooShort.param_error(),
+        ),
+    ] {
+        assert_eq!(Pbkdf2.upgrade_mcf_hash(mcf), Err(err), "{}", mcf);
+    }
+}
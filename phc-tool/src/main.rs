@@ -0,0 +1,251 @@
+//! Hash passwords into PHC strings and verify them from the command line,
+//! using only the public APIs of the workspace crates.
+//!
+//! ```text
+//! phc-tool hash <ALGORITHM> [--params <PARAMS>] [--stdin]
+//! phc-tool verify <HASH> [--stdin]
+//! ```
+//!
+//! See the README for the algorithms, params and exit codes.
+
+use argon2::Argon2;
+use password_auth::registry::{Registry, RegistryError};
+use password_hash::{Ident, ParamsString, PasswordHash, PasswordHasher, SaltString};
+use pbkdf2::Pbkdf2;
+use rand_core::OsRng;
+use scrypt::Scrypt;
+use sha_crypt::ShaCrypt;
+use std::{
+    env, fmt,
+    io::{self, BufRead},
+    process::ExitCode,
+};
+
+const USAGE: &str = "\
+Usage:
+    phc-tool hash <ALGORITHM> [--params <PARAMS>] [--stdin]
+    phc-tool verify <HASH> [--stdin]
+
+Algorithms:
+    argon2id, argon2i, argon2d, pbkdf2, pbkdf2-sha256, pbkdf2-sha512,
+    scrypt, sha-crypt, sha256-crypt, sha512-crypt
+
+Options:
+    --params <PARAMS>  Params of the PHC string, e.g. `i=600000,l=32`
+    --stdin            Read the password from the first line of stdin
+                       instead of prompting for it
+";
+
+/// Exit code of a wrong password.
+const EXIT_MISMATCH: u8 = 1;
+
+/// Exit code of invalid arguments.
+const EXIT_USAGE: u8 = 2;
+
+/// Exit code of an invalid or unsupported hash, or of a failed hashing.
+const EXIT_INVALID: u8 = 3;
+
+/// Error reported on stderr, with the exit code of the process.
+#[derive(Debug)]
+struct Failure {
+    code: u8,
+    message: String,
+}
+
+impl Failure {
+    fn new(code: u8, message: impl fmt::Display) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+
+    fn usage(message: impl fmt::Display) -> Self {
+        Self::new(EXIT_USAGE, format!("{}\n\n{}", message, USAGE))
+    }
+}
+
+impl From<RegistryError> for Failure {
+    fn from(err: RegistryError) -> Self {
+        match err {
+            RegistryError::PasswordMismatch => Self::new(EXIT_MISMATCH, err),
+            _ => Self::new(EXIT_INVALID, err),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            eprintln!("phc-tool: {}", failure.message);
+            ExitCode::from(failure.code)
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), Failure> {
+    let (command, args) = args
+        .split_first()
+        .ok_or_else(|| Failure::usage("missing command"))?;
+
+    match command.as_str() {
+        "hash" => {
+            let options = Options::parse(args)?;
+            let phc = hash(options.positional, options.params, &options.password()?)?;
+            println!("{}", phc);
+            Ok(())
+        }
+        "verify" => {
+            let options = Options::parse(args)?;
+            if options.params.is_some() {
+                return Err(Failure::usage("`--params` is only for `hash`"));
+            }
+
+            Registry::with_enabled_algorithms().verify(options.password()?, options.positional)?;
+            Ok(())
+        }
+        "-h" | "--help" | "help" => {
+            print!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(Failure::usage(format_args!(
+            "unknown command `{}`",
+            command
+        ))),
+    }
+}
+
+/// Arguments of a command: its positional argument and options.
+struct Options<'a> {
+    positional: &'a str,
+    params: Option<&'a str>,
+    stdin: bool,
+}
+
+impl<'a> Options<'a> {
+    fn parse(args: &'a [String]) -> Result<Self, Failure> {
+        let mut positional = None;
+        let mut params = None;
+        let mut stdin = false;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--params" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| Failure::usage("missing value of `--params`"))?;
+                    params = Some(value.as_str());
+                }
+                "--stdin" => stdin = true,
+                option if option.starts_with("--") => {
+                    return Err(Failure::usage(format_args!("unknown option `{}`", option)))
+                }
+                value if positional.is_none() => positional = Some(value),
+                value => {
+                    return Err(Failure::usage(format_args!(
+                        "unexpected argument `{}`",
+                        value
+                    )))
+                }
+            }
+        }
+
+        Ok(Self {
+            positional: positional.ok_or_else(|| Failure::usage("missing argument"))?,
+            params,
+            stdin,
+        })
+    }
+
+    /// Read the password from stdin, or prompt for it without echo.
+    fn password(&self) -> Result<String, Failure> {
+        let password = if self.stdin {
+            let mut line = String::new();
+            io::stdin().lock().read_line(&mut line).map(|_| {
+                let len = line.trim_end_matches(&['\r', '\n'][..]).len();
+                line.truncate(len);
+                line
+            })
+        } else {
+            rpassword::prompt_password("Password: ")
+        };
+
+        password.map_err(|err| {
+            Failure::new(EXIT_USAGE, format_args!("can't read the password: {}", err))
+        })
+    }
+}
+
+/// Hash `password` with `algorithm` and the PHC `params`, with a random
+/// salt.
+fn hash(algorithm: &str, params: Option<&str>, password: &str) -> Result<String, Failure> {
+    let params = match params {
+        Some(params) => params
+            .parse()
+            .map_err(|err| Failure::usage(format_args!("invalid params: {}", err)))?,
+        None => ParamsString::new(),
+    };
+
+    match algorithm {
+        "argon2id" => hash_with(&Argon2::default(), argon2::ARGON2ID_IDENT, params, password),
+        "argon2i" => hash_with(&Argon2::default(), argon2::ARGON2I_IDENT, params, password),
+        "argon2d" => hash_with(&Argon2::default(), argon2::ARGON2D_IDENT, params, password),
+        "pbkdf2" | "pbkdf2-sha256" => hash_with(
+            &Pbkdf2,
+            pbkdf2::Algorithm::PBKDF2_SHA256_IDENT,
+            params,
+            password,
+        ),
+        "pbkdf2-sha512" => hash_with(
+            &Pbkdf2,
+            pbkdf2::Algorithm::PBKDF2_SHA512_IDENT,
+            params,
+            password,
+        ),
+        "scrypt" => hash_with(&Scrypt, scrypt::ALG_ID, params, password),
+        "sha-crypt" | "sha512-crypt" => hash_with(
+            &ShaCrypt,
+            sha_crypt::Algorithm::SHA512_CRYPT_IDENT,
+            params,
+            password,
+        ),
+        "sha256-crypt" => hash_with(
+            &ShaCrypt,
+            sha_crypt::Algorithm::SHA256_CRYPT_IDENT,
+            params,
+            password,
+        ),
+        _ => Err(Failure::usage(format_args!(
+            "unknown algorithm `{}`",
+            algorithm
+        ))),
+    }
+}
+
+/// Hash `password` with `hasher`, reading its params from `params` as from
+/// a PHC string.
+fn hash_with<H: PasswordHasher>(
+    hasher: &H,
+    ident: Ident<'_>,
+    params: ParamsString,
+    password: &str,
+) -> Result<String, Failure> {
+    let params = H::Params::try_from(&PasswordHash {
+        algorithm: ident,
+        version: None,
+        params,
+        salt: None,
+        hash: None,
+    })
+    .map_err(|err| Failure::usage(format_args!("invalid params: {}", err)))?;
+
+    let salt = SaltString::generate(&mut OsRng);
+    hasher
+        .hash_password_customized(password.as_bytes(), Some(ident), None, params, &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|err| Failure::new(EXIT_INVALID, format_args!("hashing failed: {}", err)))
+}
@@ -0,0 +1,152 @@
+//! Drive the `phc-tool` binary as a script would.
+
+use std::{
+    io::{ErrorKind, Write},
+    process::{Command, Output, Stdio},
+};
+
+/// Run `phc-tool` with `args`, writing `stdin` to it.
+fn phc_tool(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_phc-tool"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // Usage errors exit without reading stdin
+    let written = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    if let Err(err) = written {
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe, "{}", err);
+    }
+    child.wait_with_output().unwrap()
+}
+
+/// Hash `password` with `algorithm` and `params`, returning the PHC string.
+fn hash(algorithm: &str, params: &str, password: &str) -> String {
+    let output = phc_tool(
+        &["hash", algorithm, "--params", params, "--stdin"],
+        &format!("{}\n", password),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    stdout.strip_suffix('\n').unwrap().to_owned()
+}
+
+/// Exit code of `phc-tool verify <hash>` given `password`.
+fn verify(hash: &str, password: &str) -> Option<i32> {
+    phc_tool(&["verify", hash, "--stdin"], &format!("{}\n", password))
+        .status
+        .code()
+}
+
+#[test]
+fn argon2() {
+    for algorithm in ["argon2id", "argon2i", "argon2d"] {
+        let phc = hash(algorithm, "m=64,t=1,p=1", "hunter2");
+        assert!(
+            phc.starts_with(&format!("${}$v=19$m=64,t=1,p=1$", algorithm)),
+            "{}",
+            phc
+        );
+        assert_eq!(verify(&phc, "hunter2"), Some(0));
+        assert_eq!(verify(&phc, "hunter3"), Some(1));
+    }
+
+    // A known hash of "password"
+    let phc = "$argon2i$v=19$m=65536,t=1,p=1$c29tZXNhbHQAAAAAAAAAAA$+r0d29hqEB0yasKr55ZgICsQGSkl0v0kgwhd+U3wyRo";
+    assert_eq!(verify(phc, "password"), Some(0));
+}
+
+#[test]
+fn pbkdf2() {
+    for (algorithm, prefix) in [
+        ("pbkdf2", "$pbkdf2-sha256$i=1000,l=32$"),
+        ("pbkdf2-sha512", "$pbkdf2-sha512$i=1000,l=32$"),
+    ] {
+        let phc = hash(algorithm, "i=1000,l=32", "hunter2");
+        assert!(phc.starts_with(prefix), "{}", phc);
+        assert_eq!(verify(&phc, "hunter2"), Some(0));
+        assert_eq!(verify(&phc, "hunter3"), Some(1));
+    }
+
+    // A known hash of "password"
+    let phc = "$pbkdf2-sha256$i=4096,l=32$c2FsdA$xeR41ZKIyEGqUw22hFxMjZYok6ABzk4RpJY4c6qYE0o";
+    assert_eq!(verify(phc, "password"), Some(0));
+}
+
+#[test]
+fn scrypt() {
+    let phc = hash("scrypt", "ln=4,r=8,p=1", "hunter2");
+    assert!(phc.starts_with("$scrypt$ln=4,r=8,p=1$"), "{}", phc);
+    assert_eq!(verify(&phc, "hunter2"), Some(0));
+    assert_eq!(verify(&phc, "hunter3"), Some(1));
+}
+
+#[test]
+fn sha_crypt() {
+    for (algorithm, prefix) in [
+        ("sha-crypt", "$sha512-crypt$rounds=1000$"),
+        ("sha256-crypt", "$sha256-crypt$rounds=1000$"),
+    ] {
+        let phc = hash(algorithm, "rounds=1000", "hunter2");
+        assert!(phc.starts_with(prefix), "{}", phc);
+        assert_eq!(verify(&phc, "hunter2"), Some(0));
+        assert_eq!(verify(&phc, "hunter3"), Some(1));
+    }
+}
+
+#[test]
+fn default_params() {
+    let phc = phc_tool(&["hash", "sha-crypt", "--stdin"], "hunter2\r\n");
+    assert!(phc.status.success());
+
+    let phc = String::from_utf8(phc.stdout).unwrap();
+    assert!(phc.starts_with("$sha512-crypt$rounds=5000$"), "{}", phc);
+    assert_eq!(verify(phc.trim_end(), "hunter2"), Some(0));
+}
+
+#[test]
+fn invalid_hashes() {
+    // Unknown algorithm, then malformed PHC strings
+    for phc in [
+        "$bogus$c29tZXNhbHQAAAAAAAAAAA$+r0d29hqEB0yasKr55ZgICsQGSkl0v0kgwhd+U3wyRo",
+        "password",
+        "$scrypt$ln=4,r=8,p=1,x=1$c2FsdA$xeR41ZKIyEGqUw22hFxMjZYok6ABzk4RpJY4c6qYE0o",
+    ] {
+        let output = phc_tool(&["verify", phc, "--stdin"], "password\n");
+        assert_eq!(output.status.code(), Some(3), "{}", phc);
+        assert!(!output.stderr.is_empty());
+    }
+}
+
+#[test]
+fn usage_errors() {
+    for args in [
+        &[][..],
+        &["bogus"],
+        &["hash"],
+        &["hash", "md5", "--stdin"],
+        &["hash", "pbkdf2", "--params", "i", "--stdin"],
+        &["hash", "pbkdf2", "--params", "x=1", "--stdin"],
+        &["hash", "pbkdf2", "--bogus"],
+        &["verify", "$scrypt$", "extra", "--stdin"],
+        &["verify", "$scrypt$", "--params", "ln=4", "--stdin"],
+    ] {
+        let output = phc_tool(args, "hunter2\n");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty());
+    }
+
+    let output = phc_tool(&["--help"], "");
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .starts_with("Usage:"));
+}
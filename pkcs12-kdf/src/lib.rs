@@ -0,0 +1,220 @@
+//! Pure Rust implementation of the [PKCS#12 key derivation function][1] of
+//! RFC 7292 Appendix B, which derives the keys, IVs and MAC keys of
+//! `.p12`/`.pfx` files from their password.
+//!
+//! It isn't PBKDF2: it iterates a digest over a diversifier block filled
+//! with an [`Id`] byte, the salt and the password, and derives each kind of
+//! material separately.
+//!
+//! # Usage
+//!
+//! ```
+//! use hex_literal::hex;
+//! use pkcs12_kdf::{bmp_string_into, derive, Id};
+//! use sha1::Sha1;
+//!
+//! let mut password = [0u8; 10];
+//! let password = bmp_string_into("smeg", &mut password)?;
+//! assert_eq!(password, hex!("0073006d006500670000"));
+//!
+//! let mut key = [0u8; 24];
+//! derive::<Sha1>(password, &hex!("0a58cf64530d823f"), Id::Key, 1, &mut key)?;
+//! assert_eq!(key, hex!("8aaae6297b6cb04642ab5b077851284eb7128f1a2a7fbca3"));
+//! # Ok::<(), pkcs12_kdf::Error>(())
+//! ```
+//!
+//! # Passwords
+//!
+//! The function derives material from bytes, which for PKCS#12 are the
+//! password as a BMPString: big-endian UTF-16 followed by two NUL bytes,
+//! see [`bmp_string_into`]. Characters outside of the Basic Multilingual
+//! Plane are encoded as UTF-16 surrogate pairs, as OpenSSL does.
+//!
+//! An empty password and no password differ: the empty password is the
+//! 2-byte BMPString `00 00`, whereas no password is the empty byte string,
+//! which OpenSSL uses when a file is created without a password. Both are
+//! found in files in the wild, so readers may have to try both.
+//!
+//! # `no_std` support
+//!
+//! [`derive()`] and [`bmp_string_into`] don't allocate. The `alloc` feature
+//! adds [`bmp_string`], which returns a `Vec`.
+//!
+//! [1]: https://www.rfc-editor.org/rfc/rfc7292#appendix-B.2
+
+#![no_std]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg",
+    html_favicon_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg"
+)]
+#![deny(unsafe_code)]
+#![warn(missing_docs, rust_2018_idioms)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub use digest;
+
+use core::fmt;
+use digest::{
+    core_api::{Block, BlockSizeUser},
+    Digest,
+};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Kind of material to derive, the byte filling the diversifier block.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[repr(u8)]
+pub enum Id {
+    /// Encryption key.
+    Key = 1,
+
+    /// Initialization vector.
+    Iv = 2,
+
+    /// MAC key.
+    Mac = 3,
+}
+
+/// Error type.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The number of rounds is 0.
+    InvalidRounds,
+
+    /// The output buffer is too small, see [`bmp_string_len`].
+    BufferTooSmall,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidRounds => f.write_str("rounds must be at least 1"),
+            Error::BufferTooSmall => f.write_str("output buffer is too small"),
+        }
+    }
+}
+
+/// Fill `out` with the material of kind `id` derived from `password` and
+/// `salt` with `rounds` iterations of `D`.
+///
+/// `password` is the password as a BMPString, see the
+/// [crate docs](crate#passwords). `D` is usually SHA-1 in legacy files and
+/// SHA-256 in recent ones.
+///
+/// # Returns
+/// - `Ok(())` if `out` was filled
+/// - `Err(Error::InvalidRounds)` if `rounds` is 0.
+pub fn derive<D>(
+    password: &[u8],
+    salt: &[u8],
+    id: Id,
+    rounds: u32,
+    out: &mut [u8],
+) -> Result<(), Error>
+where
+    D: Digest + BlockSizeUser,
+{
+    if rounds == 0 {
+        return Err(Error::InvalidRounds);
+    }
+
+    let diversifier = Block::<D>::from_iter(core::iter::repeat(id as u8));
+
+    // The blocks of I are the salt and the password, each repeated to a
+    // multiple of the block size. RFC 7292 adds `B + 1` to each of them
+    // between the outputs, so rather than storing I, `offset` keeps the sum
+    // of the `B + 1` added so far, which is the same for every block.
+    let salt_blocks = blocks::<D>(salt.len());
+    let password_blocks = blocks::<D>(password.len());
+    let mut offset = Block::<D>::default();
+
+    let mut outputs = out.chunks_mut(<D as Digest>::output_size()).peekable();
+    while let Some(output) = outputs.next() {
+        let mut digest = D::new_with_prefix(&diversifier);
+        for i in 0..salt_blocks {
+            digest.update(i_block::<D>(salt, i, &offset));
+        }
+        for i in 0..password_blocks {
+            digest.update(i_block::<D>(password, i, &offset));
+        }
+
+        let mut a = digest.finalize();
+        for _ in 1..rounds {
+            a = D::digest(&a);
+        }
+        output.copy_from_slice(&a[..output.len()]);
+
+        if outputs.peek().is_some() {
+            let b = Block::<D>::from_iter(a.iter().copied().cycle());
+            add_assign(&mut offset, &b, 1);
+        }
+    }
+
+    Ok(())
+}
+
+/// Length of the BMPString of `password`, including its two NUL bytes.
+pub fn bmp_string_len(password: &str) -> usize {
+    (password.encode_utf16().count() + 1) * 2
+}
+
+/// Write the BMPString of `password` to `buf`: big-endian UTF-16 followed
+/// by two NUL bytes, see the [crate docs](crate#passwords).
+///
+/// # Returns
+/// - `Ok(&[u8])` containing the BMPString, the first [`bmp_string_len`]
+///   bytes of `buf`
+/// - `Err(Error::BufferTooSmall)` if `buf` is too short.
+pub fn bmp_string_into<'a>(password: &str, buf: &'a mut [u8]) -> Result<&'a [u8], Error> {
+    let len = bmp_string_len(password);
+    let out = buf.get_mut(..len).ok_or(Error::BufferTooSmall)?;
+
+    let units = password.encode_utf16().chain(Some(0));
+    for (bytes, unit) in out.chunks_exact_mut(2).zip(units) {
+        bytes.copy_from_slice(&unit.to_be_bytes());
+    }
+
+    Ok(out)
+}
+
+/// The BMPString of `password`, as [`bmp_string_into`] writes it.
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn bmp_string(password: &str) -> Vec<u8> {
+    let mut buf = alloc::vec![0u8; bmp_string_len(password)];
+    bmp_string_into(password, &mut buf).expect("buffer of the BMPString length");
+    buf
+}
+
+/// Number of blocks of `D` of `len` bytes repeated to a multiple of the
+/// block size.
+fn blocks<D: BlockSizeUser>(len: usize) -> usize {
+    (len + D::block_size() - 1) / D::block_size()
+}
+
+/// The `i`-th block of `data` repeated to a multiple of the block size, plus
+/// `offset`.
+fn i_block<D: BlockSizeUser>(data: &[u8], i: usize, offset: &Block<D>) -> Block<D> {
+    let start = i * D::block_size();
+    let mut block = Block::<D>::from_iter(
+        (start..)
+            .map(|index| data[index % data.len()])
+            .take(D::block_size()),
+    );
+    add_assign(&mut block, offset, 0);
+    block
+}
+
+/// `a = (a + b + carry) mod 2^(8 * len)`, as big-endian integers of the same
+/// length.
+fn add_assign(a: &mut [u8], b: &[u8], mut carry: u16) {
+    for (a, b) in a.iter_mut().zip(b).rev() {
+        let sum = u16::from(*a) + u16::from(*b) + carry;
+        *a = sum as u8;
+        carry = sum >> 8;
+    }
+}
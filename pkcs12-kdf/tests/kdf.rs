@@ -0,0 +1,194 @@
+//! Test vectors generated with OpenSSL 3:
+//!
+//! ```text
+//! openssl kdf -keylen <LEN> -kdfopt hexpass:<BMPSTRING> -kdfopt hexsalt:<SALT> \
+//!     -kdfopt iter:<ROUNDS> -kdfopt id:<ID> -kdfopt digest:<DIGEST> PKCS12KDF
+//! ```
+
+use hex_literal::hex;
+use pkcs12_kdf::{bmp_string_into, bmp_string_len, derive, Error, Id};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+
+/// BMPString of "smeg".
+const SMEG: &[u8] = &hex!("0073006d006500670000");
+
+/// BMPString of "queeg".
+const QUEEG: &[u8] = &hex!("007100750065006500670000");
+
+fn check<D>(password: &[u8], salt: &[u8], id: Id, rounds: u32, expected: &[u8])
+where
+    D: digest::Digest + digest::core_api::BlockSizeUser,
+{
+    let mut out = vec![0u8; expected.len()];
+    derive::<D>(password, salt, id, rounds, &mut out).unwrap();
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn sha1() {
+    let salt = hex!("0a58cf64530d823f");
+    check::<Sha1>(
+        SMEG,
+        &salt,
+        Id::Key,
+        1,
+        &hex!("8aaae6297b6cb04642ab5b077851284eb7128f1a2a7fbca3"),
+    );
+    check::<Sha1>(SMEG, &salt, Id::Iv, 1, &hex!("79993dfe048d3b76"));
+    check::<Sha1>(
+        SMEG,
+        &hex!("3d83c0e4546ac140"),
+        Id::Mac,
+        1,
+        &hex!("8d967d88f6caa9d714800ab3d48051d63f73a312"),
+    );
+
+    let salt = hex!("05dec959acff72f7");
+    check::<Sha1>(
+        QUEEG,
+        &salt,
+        Id::Key,
+        1000,
+        &hex!("ed2034e36328830ff09df1e1a07dd357185dac0d4f9eb3d4"),
+    );
+    check::<Sha1>(QUEEG, &salt, Id::Iv, 1000, &hex!("11dedad7758d4860"));
+}
+
+#[test]
+fn sha256() {
+    let salt = hex!("0a58cf64530d823f");
+    check::<Sha256>(
+        SMEG,
+        &salt,
+        Id::Key,
+        2048,
+        &hex!("feed759a437e626593cbd29692bed68812ddc89083cd7e81c5742e278c42c103"),
+    );
+    check::<Sha256>(
+        SMEG,
+        &salt,
+        Id::Iv,
+        2048,
+        &hex!("c263d13876a89cb5950bcf4c29e1642c"),
+    );
+    check::<Sha256>(
+        SMEG,
+        &salt,
+        Id::Mac,
+        2048,
+        &hex!("e4388cd88983083b1d1552867406cb3426e16cbcf4358c7bf5a97cdb2a11db46"),
+    );
+}
+
+#[test]
+fn several_outputs() {
+    // 100 bytes are 4 outputs of SHA-256
+    check::<Sha256>(
+        SMEG,
+        &hex!("0a58cf64530d823f"),
+        Id::Key,
+        3,
+        &hex!(
+            "469e0a6907c9598d58e9611a2b3dceb1c8ae273415360bf8bf510e1ccecc8674"
+            "e28f97d0dbc836d78502a75f6a8e624ce3732b56ba426aedb32930ea60b0ce0f"
+            "4360262c3b860fe5f2278892721b55db1c7b65a1c265e51677c4eeb7f0b90066"
+            "88988fb2"
+        ),
+    );
+}
+
+#[test]
+fn long_password_and_salt() {
+    // Both longer than a block, and not multiples of it
+    let mut password = [0u8; 110];
+    let password = bmp_string_into(
+        "correct horse battery staple, and then some more words",
+        &mut password,
+    )
+    .unwrap();
+    let salt: Vec<u8> = (0..70).collect();
+
+    check::<Sha256>(
+        password,
+        &salt,
+        Id::Key,
+        5,
+        &hex!(
+            "abe9501a63e5f59ab41d255b6410dd40ff011b07114ae1b5be0651f94c88b337"
+            "6517923dfd11284cef272c3234770fdd7c14884627ab09e2ba32f73fc8d5dfd0"
+        ),
+    );
+}
+
+#[test]
+fn sha512() {
+    // 128-byte blocks
+    check::<Sha512>(
+        SMEG,
+        &hex!("0a58cf64530d823f"),
+        Id::Key,
+        2,
+        &hex!(
+            "21bfafd361ae56e2cc37cf6e4ba576cc72cefc1b12e3df355dca1a7e0597d9dc"
+            "5f1dbfdfed6bab57bafb973835f7c39670b5753b4f0965ce4ec5111221fcf3bf"
+        ),
+    );
+}
+
+#[test]
+fn empty_and_no_password() {
+    let salt = hex!("0a58cf64530d823f");
+
+    let mut buf = [0u8; 2];
+    let empty = bmp_string_into("", &mut buf).unwrap();
+    assert_eq!(empty, [0, 0]);
+    check::<Sha1>(
+        empty,
+        &salt,
+        Id::Key,
+        1,
+        &hex!("46dcc1d4aea61e4498f3ea3e6507a7e2572a837acd212182"),
+    );
+
+    check::<Sha1>(
+        &[],
+        &salt,
+        Id::Key,
+        1,
+        &hex!("9985036e1a31d2812085d6cef9db2e03dcbb6b9cd0100fb5"),
+    );
+}
+
+#[test]
+fn bmp_strings() {
+    let mut buf = [0u8; 16];
+    assert_eq!(bmp_string_len("smeg"), SMEG.len());
+    assert_eq!(bmp_string_into("smeg", &mut buf).unwrap(), SMEG);
+
+    // Outside of the BMP: a surrogate pair
+    assert_eq!(
+        bmp_string_into("é🔑", &mut buf).unwrap(),
+        hex!("00e9d83ddd110000")
+    );
+
+    assert_eq!(
+        bmp_string_into("smeg", &mut buf[..9]),
+        Err(Error::BufferTooSmall)
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn bmp_string_vec() {
+    assert_eq!(pkcs12_kdf::bmp_string("smeg"), SMEG);
+}
+
+#[test]
+fn zero_rounds() {
+    let mut out = [0u8; 8];
+    assert_eq!(
+        derive::<Sha1>(SMEG, &[], Id::Key, 0, &mut out),
+        Err(Error::InvalidRounds)
+    );
+}
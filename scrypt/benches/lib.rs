@@ -16,3 +16,17 @@ pub fn scrypt_15_8_1(bh: &mut Bencher) {
         test::black_box(&buf);
     });
 }
+
+/// Small enough for V to stay in the CPU caches, so that the speed of
+/// Salsa20/8 dominates, e.g. to compare the `simd` backends.
+#[bench]
+pub fn scrypt_10_8_1(bh: &mut Bencher) {
+    let password = b"my secure password";
+    let salt = b"salty salt";
+    let mut buf = [0u8; 32];
+    let params = scrypt::Params::new(10, 8, 1, 32).unwrap();
+    bh.iter(|| {
+        scrypt::scrypt(password, salt, &params, &mut buf).unwrap();
+        test::black_box(&buf);
+    });
+}
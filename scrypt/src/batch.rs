@@ -0,0 +1,131 @@
+//! Parallel verification of many PHC strings.
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use password_hash::PasswordHash;
+use std::sync::{Condvar, Mutex};
+
+use crate::{errors::Error, MemoryLimit, Params, ParamsPolicy, Scratch, Scrypt};
+
+impl Scrypt {
+    /// Verify many `(password, hash)` pairs concurrently, returning the
+    /// result of each verification in input order.
+    ///
+    /// Verifications run on the `rayon` thread pool, but are only started
+    /// while the memory they require (as computed from the parameters of
+    /// each hash) fits within `max_concurrent_memory` alongside the ones
+    /// already in flight. Each worker reuses its working memory for the
+    /// entries it processes, so a store with uniform parameters allocates
+    /// once per worker.
+    ///
+    /// Entries needing more than `max_concurrent_memory` on their own fail
+    /// with [`Error::MemoryLimitExceeded`] without being computed.
+    ///
+    /// The result of each entry is the same as
+    /// [`Scrypt::verify_password_with_memory_limit`] returns with
+    /// `max_concurrent_memory` as the limit, so [`ParamsPolicy::DEFAULT`] is
+    /// enforced as well.
+    #[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+    pub fn verify_batch<P>(
+        &self,
+        entries: &[(P, PasswordHash<'_>)],
+        max_concurrent_memory: MemoryLimit,
+    ) -> Vec<Result<(), Error>>
+    where
+        P: AsRef<[u8]> + Sync,
+    {
+        let budget = Budget::new(max_concurrent_memory.bytes());
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(Vec::with_capacity(entries.len()));
+        let workers = rayon::current_num_threads().min(entries.len());
+
+        rayon::scope(|s| {
+            for _ in 0..workers {
+                s.spawn(|_| {
+                    let mut scratch = Scratch::default();
+                    let mut held = 0;
+
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let (password, hash) = match entries.get(i) {
+                            Some(entry) => entry,
+                            None => break,
+                        };
+
+                        let result =
+                            Params::try_from(hash)
+                                .map_err(Error::from)
+                                .and_then(|params| {
+                                    max_concurrent_memory.check(&params)?;
+                                    ParamsPolicy::DEFAULT.check(&params)?;
+
+                                    let required = params.mem_required();
+                                    if required > held {
+                                        // Never wait for memory while holding
+                                        // some, so workers can't deadlock
+                                        drop(core::mem::take(&mut scratch));
+                                        budget.release(held);
+                                        budget.acquire(required);
+                                        held = required;
+                                    } else if scratch.mem_prepared(&params) > held {
+                                        // Buffers only grow, so reusing them
+                                        // could go over the reservation even
+                                        // though `required` fits in it
+                                        drop(core::mem::take(&mut scratch));
+                                    }
+
+                                    self.verify_password_with_scratch(
+                                        password.as_ref(),
+                                        hash,
+                                        ParamsPolicy::DEFAULT,
+                                        max_concurrent_memory,
+                                        &mut scratch,
+                                    )
+                                });
+
+                        results.lock().unwrap().push((i, result));
+                    }
+
+                    drop(scratch);
+                    budget.release(held);
+                });
+            }
+        });
+
+        let mut results = results.into_inner().unwrap();
+        results.sort_unstable_by_key(|&(i, _)| i);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+}
+
+/// Counting semaphore over bytes of memory.
+struct Budget {
+    free: Mutex<u64>,
+    released: Condvar,
+}
+
+impl Budget {
+    fn new(bytes: u64) -> Self {
+        Budget {
+            free: Mutex::new(bytes),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Wait until `bytes` are free and take them.
+    fn acquire(&self, bytes: u64) {
+        let mut free = self.free.lock().unwrap();
+        while *free < bytes {
+            free = self.released.wait(free).unwrap();
+        }
+        *free -= bytes;
+    }
+
+    /// Give back `bytes` taken with [`Budget::acquire`].
+    fn release(&self, bytes: u64) {
+        if bytes > 0 {
+            *self.free.lock().unwrap() += bytes;
+            self.released.notify_all();
+        }
+    }
+}
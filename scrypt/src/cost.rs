@@ -0,0 +1,51 @@
+use core::fmt;
+
+/// Approximate cost of a scrypt computation, see
+/// [`Params::approx_cost`](crate::Params::approx_cost).
+///
+/// Its `Display` impl gives a human-readable summary, e.g.
+/// `64 MiB, ~2^21 block mixes`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ScryptCost {
+    /// Peak memory, in bytes, as given by
+    /// [`Params::mem_required`](crate::Params::mem_required).
+    pub memory: u64,
+
+    /// Number of 64-byte blocks mixed with Salsa20/8: `2 * r` per BlockMix,
+    /// of which each lane runs `2 * N`. The computation time is roughly
+    /// proportional to it.
+    pub blocks_mixed: u64,
+
+    /// Bytes of `V` written and read back: each lane writes and reads
+    /// `N` blocks of `128 * r` bytes.
+    pub bytes_touched: u64,
+}
+
+impl fmt::Display for ScryptCost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+
+        let mut unit = 0;
+        while unit + 1 < UNITS.len() && self.memory >> (10 * (unit + 1)) != 0 {
+            unit += 1;
+        }
+
+        // One decimal, rounded to the nearest tenth
+        let scale = 1u128 << (10 * unit);
+        let tenths = (u128::from(self.memory) * 10 + scale / 2) / scale;
+        if tenths % 10 == 0 {
+            write!(f, "{} {}", tenths / 10, UNITS[unit])?;
+        } else {
+            write!(f, "{}.{} {}", tenths / 10, tenths % 10, UNITS[unit])?;
+        }
+
+        // log₂ of the block mixes, rounded to the nearest integer: up when
+        // they're at least 2^(k + 1/2)
+        let blocks = u128::from(self.blocks_mixed.max(1));
+        let mut log = 127 - blocks.leading_zeros();
+        if blocks * blocks >= 1 << (2 * log + 1) {
+            log += 1;
+        }
+        write!(f, ", ~2^{} block mixes", log)
+    }
+}
@@ -0,0 +1,214 @@
+//! Interoperability with the `$7$` password hashes of `crypt(3)`, the
+//! scrypt hashes of libxcrypt (and of `escrypt` before it).
+//!
+//! These hashes store the parameters and the salt in their setting,
+//! followed by the 32-byte scrypt output:
+//!
+//! ```text
+//! $7$<log_n><r><p><salt>$<hash>
+//! ```
+//!
+//! where `log_n` is one character of the `crypt(3)` Base64 alphabet
+//! `./0-9A-Za-z`, `r` and `p` are five characters each, encoding 30-bit
+//! integers least significant bits first, the salt is used verbatim, and
+//! the hash is encoded in the Base64 of `crypt(3)`, as the SHA-crypt ones.
+//!
+//! ```
+//! let hash = "$7$CU..../....SodiumChloride$siKSmmZgY8HQ/vgp0LEyi18bSR0Rm2AyTRfe12wvPXB";
+//!
+//! assert!(scrypt::crypt::verify(b"password", hash).is_ok());
+//! assert!(scrypt::crypt::verify(b"Password", hash).is_err());
+//! ```
+
+use alloc::string::{String, ToString};
+use base64ct::{Base64ShaCrypt, Encoding};
+use core::fmt;
+use subtle::ConstantTimeEq;
+
+use crate::{errors::CryptError, scrypt, MemoryLimit, Params};
+
+/// Prefix of `$7$` hashes.
+pub const PREFIX: &str = "$7$";
+
+/// Length of the scrypt output of `$7$` hashes.
+const HASH_LEN: usize = 32;
+
+/// Alphabet of the Base64 of `crypt(3)`.
+const ITOA64: &[u8; 64] = b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Number of characters of the `r` and `p` fields.
+const UINT30_LEN: usize = 5;
+
+/// A parsed `$7$<params><salt>$<hash>` string.
+#[derive(Clone, Debug)]
+pub struct Hash {
+    params: Params,
+    salt: String,
+    hash: [u8; HASH_LEN],
+}
+
+impl Hash {
+    /// Parse a `$7$` hash string.
+    ///
+    /// # Return
+    /// `Err(CryptError::Malformed)` if `s` isn't a well-formed `$7$` hash,
+    /// and `Err(CryptError::InvalidParams)` if its parameters are invalid.
+    pub fn parse(s: &str) -> Result<Hash, CryptError> {
+        let (params, salt, hash) = parse_setting(s)?;
+        let hash = hash.ok_or(CryptError::Malformed)?;
+
+        let mut output = [0u8; HASH_LEN];
+        match Base64ShaCrypt::decode(hash, &mut output) {
+            Ok(decoded) if decoded.len() == HASH_LEN => (),
+            _ => return Err(CryptError::Malformed),
+        }
+
+        Ok(Hash {
+            params,
+            salt: salt.to_string(),
+            hash: output,
+        })
+    }
+
+    /// Parameters of the hash. Their key length is always `32`.
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /// Salt of the hash, which `crypt(3)` uses as is.
+    pub fn salt(&self) -> &str {
+        &self.salt
+    }
+
+    /// Hash of the password, decoded.
+    pub fn hash(&self) -> &[u8; HASH_LEN] {
+        &self.hash
+    }
+}
+
+impl fmt::Display for Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut params = [0u8; 1 + 2 * UINT30_LEN];
+        params[0] = ITOA64[usize::from(self.params.log_n)];
+        encode_uint30(self.params.r, &mut params[1..=UINT30_LEN]);
+        encode_uint30(self.params.p, &mut params[1 + UINT30_LEN..]);
+        let params = core::str::from_utf8(&params).expect("ITOA64 is ASCII");
+
+        write!(
+            f,
+            "{}{}{}${}",
+            PREFIX,
+            params,
+            self.salt,
+            Base64ShaCrypt::encode_string(&self.hash)
+        )
+    }
+}
+
+/// Hash `password` with `setting`, as `crypt(3)` does.
+///
+/// The setting is `$7$<log_n><r><p><salt>`, and may be followed by `$` and
+/// a hash, which is ignored: a hash string is a valid setting as well,
+/// which gives back the same hash string for the right password. To verify
+/// passwords, use [`verify`] instead, which limits the memory used.
+///
+/// # Return
+/// `Err(CryptError::Malformed)` if `setting` isn't a well-formed `$7$`
+/// setting, and `Err(CryptError::InvalidParams)` if its parameters are
+/// invalid.
+pub fn crypt(password: &[u8], setting: &str) -> Result<String, CryptError> {
+    let (params, salt, _) = parse_setting(setting)?;
+
+    let mut hash = [0u8; HASH_LEN];
+    scrypt(password, salt.as_bytes(), &params, &mut hash).expect("output length is valid");
+
+    Ok(Hash {
+        params,
+        salt: salt.to_string(),
+        hash,
+    }
+    .to_string())
+}
+
+/// Verify `password` against the `$7$` hash string `hash`, requiring at
+/// most [`MemoryLimit::DEFAULT`].
+///
+/// # Return
+/// `Ok(())` if the password matches, `Err(CryptError::PasswordMismatch)`
+/// if it doesn't, and the errors of [`Hash::parse`] and
+/// [`verify_with_memory_limit`] otherwise.
+pub fn verify(password: &[u8], hash: &str) -> Result<(), CryptError> {
+    verify_with_memory_limit(password, hash, MemoryLimit::DEFAULT)
+}
+
+/// Verify `password` against the `$7$` hash string `hash`, rejecting the
+/// parameters requiring more memory than `limit` with
+/// `Err(CryptError::MemoryLimitExceeded)` before allocating it.
+pub fn verify_with_memory_limit(
+    password: &[u8],
+    hash: &str,
+    limit: MemoryLimit,
+) -> Result<(), CryptError> {
+    let hash = Hash::parse(hash)?;
+
+    let required = hash.params.mem_required();
+    if required > limit.bytes() {
+        return Err(CryptError::MemoryLimitExceeded {
+            required,
+            limit: limit.bytes(),
+        });
+    }
+
+    let mut output = [0u8; HASH_LEN];
+    scrypt(password, hash.salt.as_bytes(), &hash.params, &mut output)
+        .expect("output length is valid");
+
+    if !bool::from(output.ct_eq(&hash.hash)) {
+        return Err(CryptError::PasswordMismatch);
+    }
+
+    Ok(())
+}
+
+/// Split a setting or hash string into its parameters, its salt and its
+/// encoded hash, if any.
+fn parse_setting(s: &str) -> Result<(Params, &str, Option<&str>), CryptError> {
+    let fields = s.strip_prefix(PREFIX).ok_or(CryptError::Malformed)?;
+    let params = fields
+        .get(..1 + 2 * UINT30_LEN)
+        .ok_or(CryptError::Malformed)?
+        .as_bytes();
+
+    let log_n = decode_char(params[0])?;
+    let r = decode_uint30(&params[1..=UINT30_LEN])?;
+    let p = decode_uint30(&params[1 + UINT30_LEN..])?;
+    let params = Params::new(log_n, r, p, HASH_LEN)?;
+
+    let mut fields = fields[1 + 2 * UINT30_LEN..].splitn(2, '$');
+    let salt = fields.next().unwrap_or_default();
+    Ok((params, salt, fields.next()))
+}
+
+/// Value of `c` in [`ITOA64`].
+fn decode_char(c: u8) -> Result<u8, CryptError> {
+    ITOA64
+        .iter()
+        .position(|&a| a == c)
+        .map(|value| value as u8)
+        .ok_or(CryptError::Malformed)
+}
+
+/// Decode a 30-bit integer, least significant 6 bits first.
+fn decode_uint30(src: &[u8]) -> Result<u32, CryptError> {
+    src.iter().rev().try_fold(0u32, |value, &c| {
+        Ok((value << 6) | u32::from(decode_char(c)?))
+    })
+}
+
+/// Encode the 30-bit integer `value`, least significant 6 bits first.
+fn encode_uint30(mut value: u32, dst: &mut [u8]) {
+    for c in dst {
+        *c = ITOA64[(value & 0x3f) as usize];
+        value >>= 6;
+    }
+}
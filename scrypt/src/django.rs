@@ -0,0 +1,231 @@
+//! Interoperability with the `scrypt$` password hashes of Django's
+//! `ScryptPasswordHasher` (Django 4.0 and later).
+//!
+//! Django stores the parameters, the salt and the 64-byte scrypt output
+//! separated by `$`:
+//!
+//! ```text
+//! scrypt$<work_factor>$<salt>$<block_size>$<parallelism>$<hash>
+//! ```
+//!
+//! where `work_factor` is `N` itself (not its log₂), `block_size` is `r`,
+//! `parallelism` is `p`, the salt is used verbatim and the hash is encoded
+//! with padded standard Base64. Django hashes the UTF-8 encoding of
+//! passwords.
+//!
+//! ```
+//! let hash = "scrypt$16384$seasalt$8$1$Qj3+9PPyRjSJIebHnG81TMjsqtaIGxNQG/aEB/NYafTJ7tibgfYz71m0ldQESkXFRkdVCBhhY8mx7rQwite/Pw==";
+//!
+//! assert!(scrypt::django::verify("lètmein".as_bytes(), hash).is_ok());
+//! assert!(scrypt::django::verify(b"letmein", hash).is_err());
+//! ```
+
+use alloc::string::{String, ToString};
+use base64ct::{Base64, Encoding};
+use core::fmt;
+use subtle::ConstantTimeEq;
+
+use crate::{
+    errors::{DjangoError, InvalidParams},
+    scrypt, MemoryLimit, Params,
+};
+
+/// Algorithm name of Django's `ScryptPasswordHasher`, the first field of
+/// its hashes.
+pub const ALGORITHM: &str = "scrypt";
+
+/// Length of the scrypt output of Django's hashes.
+const HASH_LEN: usize = 64;
+
+/// The default parameters of Django's `ScryptPasswordHasher`:
+/// - `work_factor = 16384` (`log_n = 14`)
+/// - `block_size = 8`
+/// - `parallelism = 1`
+pub const DEFAULT_PARAMS: Params = Params {
+    log_n: 14,
+    r: 8,
+    p: 1,
+    len: HASH_LEN,
+};
+
+/// A parsed Django `scrypt$` hash.
+#[derive(Clone, Debug)]
+pub struct Hash {
+    params: Params,
+    salt: String,
+    hash: [u8; HASH_LEN],
+}
+
+impl Hash {
+    /// Parse a Django `scrypt$` hash.
+    ///
+    /// Parameters which Django (or rather OpenSSL) would reject are
+    /// reported with `Err(DjangoError::InvalidParams)`, naming the Django
+    /// field: `work_factor`, `block_size` or `parallelism`.
+    pub fn parse(s: &str) -> Result<Hash, DjangoError> {
+        let mut fields = s.split('$');
+        let mut next = || fields.next().ok_or(DjangoError::Malformed);
+
+        let algorithm = next()?;
+        let work_factor = next()?;
+        let salt = next()?;
+        let block_size = next()?;
+        let parallelism = next()?;
+        let hash = next()?;
+
+        if algorithm != ALGORITHM || fields.next().is_some() {
+            return Err(DjangoError::Malformed);
+        }
+
+        let n = decode_int(work_factor)?;
+        let r = decode_int(block_size)?;
+        let p = decode_int(parallelism)?;
+
+        let params = Params::try_from_django(n, r, p)?;
+
+        let mut output = [0u8; HASH_LEN];
+        match Base64::decode(hash, &mut output) {
+            Ok(decoded) if decoded.len() == HASH_LEN => (),
+            _ => return Err(DjangoError::Malformed),
+        }
+
+        Ok(Hash {
+            params,
+            salt: salt.to_string(),
+            hash: output,
+        })
+    }
+
+    /// Parameters of the hash. Their key length is always `64`.
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /// Salt of the hash, which Django uses as is.
+    pub fn salt(&self) -> &str {
+        &self.salt
+    }
+
+    /// Hash of the password, decoded.
+    pub fn hash(&self) -> &[u8; HASH_LEN] {
+        &self.hash
+    }
+}
+
+impl fmt::Display for Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}${}${}${}${}${}",
+            ALGORITHM,
+            self.params.n(),
+            self.salt,
+            self.params.r,
+            self.params.p,
+            Base64::encode_string(&self.hash)
+        )
+    }
+}
+
+impl Params {
+    fn try_from_django(n: u64, r: u64, p: u64) -> Result<Params, InvalidParams> {
+        if !n.is_power_of_two() || n < 2 {
+            return Err(InvalidParams::new(
+                "work_factor",
+                "a power of two greater than 1",
+            ));
+        }
+
+        let r = u32::try_from(r).map_err(|_| InvalidParams::new("block_size", "less than 2^32"))?;
+        let p =
+            u32::try_from(p).map_err(|_| InvalidParams::new("parallelism", "less than 2^32"))?;
+
+        let log_n = n.trailing_zeros() as u8;
+        Params::new(log_n, r, p, HASH_LEN).map_err(|err| {
+            let param = match err.param() {
+                "log_n" => "work_factor",
+                "r" => "block_size",
+                _ => "parallelism",
+            };
+            InvalidParams::new(param, err.expected())
+        })
+    }
+}
+
+/// Hash `password` with `salt` into a Django `scrypt$` hash.
+///
+/// The output is always 64 bytes long, as in Django: the key length of
+/// `params` is ignored. Django generates salts of 22 random alphanumeric
+/// characters; the salt must not be empty or contain `$`, or
+/// `Err(DjangoError::InvalidSalt)` is returned.
+pub fn encode(password: &[u8], salt: &str, params: &Params) -> Result<String, DjangoError> {
+    if salt.is_empty() || salt.contains('$') {
+        return Err(DjangoError::InvalidSalt);
+    }
+
+    let params = Params {
+        len: HASH_LEN,
+        ..*params
+    };
+
+    let mut hash = [0u8; HASH_LEN];
+    scrypt(password, salt.as_bytes(), &params, &mut hash).expect("output length is valid");
+
+    Ok(Hash {
+        params,
+        salt: salt.to_string(),
+        hash,
+    }
+    .to_string())
+}
+
+/// Verify `password` against the Django `scrypt$` hash `hash`, requiring
+/// at most [`MemoryLimit::DEFAULT`].
+///
+/// # Return
+/// `Ok(())` if the password matches, `Err(DjangoError::PasswordMismatch)`
+/// if it doesn't, and the errors of [`Hash::parse`] and
+/// [`verify_with_memory_limit`] otherwise.
+pub fn verify(password: &[u8], hash: &str) -> Result<(), DjangoError> {
+    verify_with_memory_limit(password, hash, MemoryLimit::DEFAULT)
+}
+
+/// Verify `password` against the Django `scrypt$` hash `hash`, rejecting
+/// the parameters requiring more memory than `limit` with
+/// `Err(DjangoError::MemoryLimitExceeded)` before allocating it.
+pub fn verify_with_memory_limit(
+    password: &[u8],
+    hash: &str,
+    limit: MemoryLimit,
+) -> Result<(), DjangoError> {
+    let hash = Hash::parse(hash)?;
+
+    let required = hash.params.mem_required();
+    if required > limit.bytes() {
+        return Err(DjangoError::MemoryLimitExceeded {
+            required,
+            limit: limit.bytes(),
+        });
+    }
+
+    let mut output = [0u8; HASH_LEN];
+    scrypt(password, hash.salt.as_bytes(), &hash.params, &mut output)
+        .expect("output length is valid");
+
+    if !bool::from(output.ct_eq(&hash.hash)) {
+        return Err(DjangoError::PasswordMismatch);
+    }
+
+    Ok(())
+}
+
+/// Decode one of the integer fields. Django parses them with Python's
+/// `int()`, but only ever writes plain decimal numbers.
+fn decode_int(field: &str) -> Result<u64, DjangoError> {
+    if field.is_empty() || !field.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(DjangoError::Malformed);
+    }
+
+    // Only overflows are left, which no valid parameter reaches
+    Ok(field.parse().unwrap_or(u64::MAX))
+}
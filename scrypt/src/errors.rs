@@ -1,12 +1,221 @@
+use alloc::string::{String, ToString};
 use core::fmt;
 
 /// `scrypt()` error
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct InvalidOutputLen;
 
-/// `ScryptParams` error
+/// `Params` error, naming the invalid parameter and the values it accepts.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub struct InvalidParams;
+pub struct InvalidParams {
+    param: &'static str,
+    expected: &'static str,
+}
+
+impl InvalidParams {
+    pub(crate) const fn new(param: &'static str, expected: &'static str) -> InvalidParams {
+        InvalidParams { param, expected }
+    }
+
+    /// Name of the invalid parameter, e.g. `log_n` or `r`.
+    pub fn param(&self) -> &'static str {
+        self.param
+    }
+
+    /// Description of the values accepted for the parameter, e.g.
+    /// `greater than 0`.
+    pub fn expected(&self) -> &'static str {
+        self.expected
+    }
+}
+
+/// `Params::from_str` error
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseParamsError {
+    token: String,
+    expected: &'static str,
+}
+
+impl ParseParamsError {
+    pub(crate) fn new(token: &str, expected: &'static str) -> ParseParamsError {
+        ParseParamsError {
+            token: token.to_string(),
+            expected,
+        }
+    }
+
+    /// The offending `name=value` token.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+/// `Params::from_n` error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum InvalidN {
+    /// `N` is not a power of two.
+    NotPowerOfTwo(u64),
+
+    /// `N` is less than `2`.
+    TooSmall(u64),
+
+    /// `N` is a power of two, but the parameters are invalid.
+    InvalidParams(InvalidParams),
+}
+
+/// `scryptenc::Header` error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum InvalidHeader {
+    /// The input is shorter than a header.
+    Length,
+
+    /// The input doesn't start with the `scrypt` magic bytes.
+    Magic,
+
+    /// The header version isn't `0`.
+    Version(u8),
+
+    /// The header contains invalid scrypt parameters.
+    Params,
+
+    /// The SHA-256 checksum of the header doesn't match.
+    Checksum,
+
+    /// The HMAC-SHA256 of the header doesn't match: the password is wrong
+    /// or the header is corrupted.
+    Mac,
+}
+
+/// `yescrypt` error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum YescryptError {
+    /// The string isn't a well-formed `$y$` hash.
+    Malformed,
+
+    /// The hash selects a yescrypt flavor which isn't implemented, given as
+    /// its `YESCRYPT_*` flags: only classic scrypt, write-once/read-many and
+    /// the default read-write flavor are.
+    UnsupportedFlags(u32),
+
+    /// The hash uses a yescrypt feature which isn't implemented: hash
+    /// upgrades (`g`) or a ROM (`NROM`).
+    Unsupported(&'static str),
+
+    /// The hash contains invalid yescrypt parameters.
+    InvalidParams(InvalidParams),
+
+    /// The parameters require more memory than the configured
+    /// [`MemoryLimit`](crate::MemoryLimit).
+    MemoryLimitExceeded {
+        /// Memory required by the parameters, in bytes.
+        required: u64,
+
+        /// Configured limit, in bytes.
+        limit: u64,
+    },
+
+    /// The password doesn't match the hash.
+    PasswordMismatch,
+}
+
+/// Django `scrypt$` hash error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum DjangoError {
+    /// The string isn't a well-formed Django `scrypt$` hash: it doesn't
+    /// have exactly six fields, or doesn't start with `scrypt`, or one of
+    /// its fields can't be decoded.
+    Malformed,
+
+    /// The hash contains invalid scrypt parameters.
+    InvalidParams(InvalidParams),
+
+    /// The salt is empty or contains `$`.
+    InvalidSalt,
+
+    /// The parameters require more memory than the configured
+    /// [`MemoryLimit`](crate::MemoryLimit).
+    MemoryLimitExceeded {
+        /// Memory required by the parameters, in bytes.
+        required: u64,
+
+        /// Configured limit, in bytes.
+        limit: u64,
+    },
+
+    /// The password doesn't match the hash.
+    PasswordMismatch,
+}
+
+/// `$7$` hash error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum CryptError {
+    /// The string isn't a well-formed `$7$` setting or hash: it doesn't
+    /// start with `$7$`, or its parameters or hash can't be decoded.
+    Malformed,
+
+    /// The setting contains invalid scrypt parameters.
+    InvalidParams(InvalidParams),
+
+    /// The parameters require more memory than the configured
+    /// [`MemoryLimit`](crate::MemoryLimit).
+    MemoryLimitExceeded {
+        /// Memory required by the parameters, in bytes.
+        required: u64,
+
+        /// Configured limit, in bytes.
+        limit: u64,
+    },
+
+    /// The password doesn't match the hash.
+    PasswordMismatch,
+}
+
+/// Error of the fallible scrypt APIs, such as `scrypt_with_progress()`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Invalid output buffer length, see [`InvalidOutputLen`].
+    InvalidOutputLen,
+
+    /// Invalid parameters.
+    InvalidParams(InvalidParams),
+
+    /// The progress callback cancelled the computation.
+    Cancelled,
+
+    /// The parameters require more memory than the configured
+    /// [`MemoryLimit`](crate::MemoryLimit).
+    MemoryLimitExceeded {
+        /// Memory required by the parameters, in bytes.
+        required: u64,
+
+        /// Configured limit, in bytes.
+        limit: u64,
+    },
+
+    /// A parameter exceeds the configured
+    /// [`ParamsPolicy`](crate::ParamsPolicy).
+    ParamsPolicyViolation {
+        /// Name of the parameter, as in PHC strings (`ln`, `r` or `p`).
+        param: &'static str,
+
+        /// Value of the parameter.
+        value: u32,
+
+        /// Maximum allowed by the policy.
+        max: u32,
+    },
+
+    /// Error from the `password-hash` crate API.
+    #[cfg(feature = "simple")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+    PasswordHash(password_hash::Error),
+}
 
 impl fmt::Display for InvalidOutputLen {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -19,9 +228,248 @@ impl std::error::Error for InvalidOutputLen {}
 
 impl fmt::Display for InvalidParams {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("invalid scrypt parameters")
+        write!(
+            f,
+            "invalid scrypt parameter `{}`: expected {}",
+            self.param, self.expected
+        )
     }
 }
 
 #[cfg(feature = "std")]
 impl std::error::Error for InvalidParams {}
+
+impl fmt::Display for ParseParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid scrypt parameter `{}`: expected {}",
+            self.token, self.expected
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseParamsError {}
+
+impl fmt::Display for InvalidN {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidN::NotPowerOfTwo(n) => {
+                write!(f, "scrypt parameter N must be a power of two, got {}", n)
+            }
+            InvalidN::TooSmall(n) => {
+                write!(f, "scrypt parameter N must be greater than 1, got {}", n)
+            }
+            InvalidN::InvalidParams(err) => err.fmt(f),
+        }
+    }
+}
+
+impl From<InvalidParams> for InvalidN {
+    fn from(err: InvalidParams) -> InvalidN {
+        InvalidN::InvalidParams(err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidN {}
+
+impl fmt::Display for InvalidHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidHeader::Length => f.write_str("scrypt header too short"),
+            InvalidHeader::Magic => f.write_str("not a scrypt encrypted file"),
+            InvalidHeader::Version(version) => {
+                write!(f, "unsupported scrypt header version {}", version)
+            }
+            InvalidHeader::Params => f.write_str("invalid scrypt parameters in header"),
+            InvalidHeader::Checksum => f.write_str("scrypt header checksum mismatch"),
+            InvalidHeader::Mac => f.write_str("scrypt header MAC mismatch"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidHeader {}
+
+impl fmt::Display for YescryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            YescryptError::Malformed => f.write_str("malformed yescrypt hash"),
+            YescryptError::UnsupportedFlags(flags) => {
+                write!(f, "unsupported yescrypt flags {:#x}", flags)
+            }
+            YescryptError::Unsupported(feature) => {
+                write!(f, "unsupported yescrypt feature `{}`", feature)
+            }
+            YescryptError::InvalidParams(err) => err.fmt(f),
+            YescryptError::MemoryLimitExceeded { required, limit } => write!(
+                f,
+                "yescrypt parameters require {} bytes of memory, exceeding the limit of {} bytes",
+                required, limit
+            ),
+            YescryptError::PasswordMismatch => f.write_str("password doesn't match yescrypt hash"),
+        }
+    }
+}
+
+impl From<InvalidParams> for YescryptError {
+    fn from(err: InvalidParams) -> YescryptError {
+        YescryptError::InvalidParams(err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for YescryptError {}
+
+impl fmt::Display for DjangoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DjangoError::Malformed => f.write_str("malformed Django scrypt hash"),
+            DjangoError::InvalidParams(err) => err.fmt(f),
+            DjangoError::InvalidSalt => f.write_str("salt is empty or contains `$`"),
+            DjangoError::MemoryLimitExceeded { required, limit } => write!(
+                f,
+                "scrypt parameters require {} bytes of memory, exceeding the limit of {} bytes",
+                required, limit
+            ),
+            DjangoError::PasswordMismatch => {
+                f.write_str("password doesn't match Django scrypt hash")
+            }
+        }
+    }
+}
+
+impl From<InvalidParams> for DjangoError {
+    fn from(err: InvalidParams) -> DjangoError {
+        DjangoError::InvalidParams(err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DjangoError {}
+
+impl fmt::Display for CryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptError::Malformed => f.write_str("malformed $7$ scrypt hash"),
+            CryptError::InvalidParams(err) => err.fmt(f),
+            CryptError::MemoryLimitExceeded { required, limit } => write!(
+                f,
+                "scrypt parameters require {} bytes of memory, exceeding the limit of {} bytes",
+                required, limit
+            ),
+            CryptError::PasswordMismatch => f.write_str("password doesn't match $7$ scrypt hash"),
+        }
+    }
+}
+
+impl From<InvalidParams> for CryptError {
+    fn from(err: InvalidParams) -> CryptError {
+        CryptError::InvalidParams(err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CryptError {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidOutputLen => InvalidOutputLen.fmt(f),
+            Error::InvalidParams(err) => err.fmt(f),
+            Error::Cancelled => f.write_str("scrypt computation cancelled"),
+            Error::MemoryLimitExceeded { required, limit } => write!(
+                f,
+                "scrypt parameters require {} bytes of memory, exceeding the limit of {} bytes",
+                required, limit
+            ),
+            Error::ParamsPolicyViolation { param, value, max } => write!(
+                f,
+                "scrypt parameter {} = {} exceeds the maximum of {}",
+                param, value, max
+            ),
+            #[cfg(feature = "simple")]
+            Error::PasswordHash(err) => err.fmt(f),
+        }
+    }
+}
+
+impl From<InvalidOutputLen> for Error {
+    fn from(_: InvalidOutputLen) -> Error {
+        Error::InvalidOutputLen
+    }
+}
+
+impl From<InvalidParams> for Error {
+    fn from(err: InvalidParams) -> Error {
+        Error::InvalidParams(err)
+    }
+}
+
+#[cfg(feature = "simple")]
+impl From<password_hash::Error> for Error {
+    fn from(err: password_hash::Error) -> Error {
+        Error::PasswordHash(err)
+    }
+}
+
+#[cfg(feature = "simple")]
+impl From<InvalidOutputLen> for password_hash::Error {
+    fn from(_: InvalidOutputLen) -> password_hash::Error {
+        password_hash::Error::OutputSize {
+            provided: core::cmp::Ordering::Less,
+            expected: 0,
+        }
+    }
+}
+
+#[cfg(feature = "simple")]
+impl From<InvalidParams> for password_hash::Error {
+    fn from(_: InvalidParams) -> password_hash::Error {
+        password_hash::errors::InvalidValue::Malformed.param_error()
+    }
+}
+
+/// For the [`PasswordHasher`](password_hash::PasswordHasher) and
+/// [`PasswordVerifier`](password_hash::PasswordVerifier) APIs: exceeded
+/// limits are reported as
+/// [`InvalidValue::TooLong`](password_hash::errors::InvalidValue::TooLong).
+#[cfg(feature = "simple")]
+impl From<Error> for password_hash::Error {
+    fn from(err: Error) -> password_hash::Error {
+        match err {
+            Error::PasswordHash(err) => err,
+            Error::InvalidOutputLen => InvalidOutputLen.into(),
+            Error::InvalidParams(err) => err.into(),
+            Error::MemoryLimitExceeded { .. } | Error::ParamsPolicyViolation { .. } => {
+                password_hash::errors::InvalidValue::TooLong.param_error()
+            }
+            Error::Cancelled => password_hash::Error::Crypto,
+        }
+    }
+}
+
+/// For the [`PasswordHasher`](password_hash::PasswordHasher) and
+/// [`McfHasher`](password_hash::McfHasher) implementations built on
+/// [`yescrypt`](crate::yescrypt).
+#[cfg(all(feature = "simple", feature = "yescrypt"))]
+impl From<YescryptError> for password_hash::Error {
+    fn from(err: YescryptError) -> password_hash::Error {
+        match err {
+            YescryptError::Malformed => password_hash::Error::PhcStringField,
+            YescryptError::UnsupportedFlags(_) | YescryptError::Unsupported(_) => {
+                password_hash::errors::InvalidValue::Malformed.param_error()
+            }
+            YescryptError::InvalidParams(err) => err.into(),
+            YescryptError::MemoryLimitExceeded { .. } => {
+                password_hash::errors::InvalidValue::TooLong.param_error()
+            }
+            YescryptError::PasswordMismatch => password_hash::Error::Password,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
@@ -0,0 +1,88 @@
+use crate::Params;
+
+#[cfg(feature = "parallel")]
+use {
+    crate::{romix, Scratch},
+    core::ops::ControlFlow,
+    pbkdf2::pbkdf2_hmac,
+    rayon::prelude::*,
+    sha2::Sha256,
+};
+
+/// How the `p` lanes of a scrypt computation are run, see
+/// [`scrypt_with_lanes`](crate::scrypt_with_lanes).
+///
+/// The lanes are independent, so the choice doesn't affect the output: it
+/// trades memory for time.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LaneStrategy {
+    /// Run the lanes one after another, reusing the same `V` for all of them.
+    ///
+    /// Peak memory is that of a single lane (plus the `128 * r * p` bytes of
+    /// `B`), whatever `p` is. This is what [`scrypt`](crate::scrypt) does.
+    SequentialReuse,
+
+    /// Run the lanes concurrently on the `rayon` thread pool, each with its
+    /// own `V`.
+    ///
+    /// This is up to `p` times faster, and needs up to `p` times the memory
+    /// of [`LaneStrategy::SequentialReuse`].
+    #[cfg(feature = "parallel")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+    Parallel,
+}
+
+impl LaneStrategy {
+    /// Peak memory of a computation with `params` using this strategy, in
+    /// bytes.
+    ///
+    /// For `LaneStrategy::Parallel` this is the worst case, where all `p`
+    /// lanes run at the same time.
+    pub fn memory_required(self, params: &Params) -> u64 {
+        match self {
+            LaneStrategy::SequentialReuse => params.mem_required(),
+            #[cfg(feature = "parallel")]
+            LaneStrategy::Parallel => {
+                // B, and V, X and Y for each lane
+                let r128 = 128 * u64::from(params.r);
+                let lane = (1u64 << params.log_n).saturating_add(2);
+                let blocks = lane.saturating_add(1).saturating_mul(u64::from(params.p));
+                r128.saturating_mul(blocks)
+            }
+        }
+    }
+}
+
+impl Default for LaneStrategy {
+    fn default() -> LaneStrategy {
+        LaneStrategy::SequentialReuse
+    }
+}
+
+/// [`scrypt`](crate::scrypt) with [`LaneStrategy::Parallel`].
+///
+/// `output` must already have been validated with `check_output_len`.
+#[cfg(feature = "parallel")]
+pub(crate) fn scrypt_parallel(password: &[u8], salt: &[u8], params: &Params, output: &mut [u8]) {
+    let n = 1 << params.log_n;
+    let r128 = (params.r as usize) * 128;
+
+    // Only `b` is used, so that it gets wiped on drop
+    let mut outer = Scratch::default();
+    outer.b.resize(r128 * params.p as usize, 0);
+
+    pbkdf2_hmac::<Sha256>(password, salt, 1, &mut outer.b);
+
+    outer
+        .b
+        .par_chunks_mut(r128)
+        .for_each_init(Scratch::default, |scratch, chunk| {
+            scratch.prepare_lane(params);
+            let flow = romix::scrypt_ro_mix(chunk, &mut scratch.v, &mut scratch.xy, n, || {
+                ControlFlow::Continue(())
+            });
+            debug_assert!(flow.is_continue());
+        });
+
+    pbkdf2_hmac::<Sha256>(password, &outer.b, 1, output);
+}
@@ -49,23 +49,60 @@
     html_favicon_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg"
 )]
 
-#[macro_use]
 extern crate alloc;
 #[cfg(feature = "std")]
 extern crate std;
 
+use core::ops::ControlFlow;
 use pbkdf2::pbkdf2_hmac;
 use sha2::Sha256;
 
 /// Errors for `scrypt` operations.
 pub mod errors;
+
+#[cfg(feature = "parallel")]
+mod batch;
+mod cost;
+#[cfg(feature = "crypt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "crypt")))]
+pub mod crypt;
+#[cfg(feature = "django")]
+#[cfg_attr(docsrs, doc(cfg(feature = "django")))]
+pub mod django;
+mod lanes;
+mod limits;
 mod params;
+pub mod primitives;
+mod progress;
 mod romix;
+mod salsa;
+mod scratch;
+pub mod session;
+mod vector;
 
+#[cfg(feature = "scryptenc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "scryptenc")))]
+pub mod scryptenc;
 #[cfg(feature = "simple")]
 mod simple;
 
-pub use crate::params::Params;
+#[cfg(feature = "web3")]
+#[cfg_attr(docsrs, doc(cfg(feature = "web3")))]
+pub mod web3;
+
+#[cfg(feature = "yescrypt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "yescrypt")))]
+pub mod yescrypt;
+
+pub use crate::{
+    cost::ScryptCost,
+    lanes::LaneStrategy,
+    limits::{MemoryLimit, ParamsPolicy},
+    params::{Params, ParamsBuilder},
+    progress::Progress,
+};
+
+use crate::scratch::Scratch;
 
 #[cfg(feature = "simple")]
 pub use password_hash;
@@ -86,35 +123,208 @@ pub use crate::simple::{Scrypt, ALG_ID};
 /// `Ok(())` if calculation is successful and `Err(InvalidOutputLen)` if
 /// `output` does not satisfy the following condition:
 /// `output.len() > 0 && output.len() <= (2^32 - 1) * 32`.
+///
+/// `params` can't be invalid, as [`Params`] can only be constructed from
+/// values passing the checks of [`Params::new`] (which fails with
+/// [`InvalidParams`](errors::InvalidParams) otherwise).
+///
+/// # Panics
+/// This function doesn't panic on any input: an `output` of invalid length is
+/// rejected before any work is done. Like any allocation, failing to
+/// allocate the working memory (`128 * r * (N + p + 2)` bytes) aborts.
+///
+/// The working memory, including the `V` and `B` buffers, is overwritten with
+/// zeroes before being freed.
+///
+/// ```
+/// use scrypt::{errors::InvalidOutputLen, scrypt, Params};
+///
+/// let params = Params::new(10, 8, 1, 32).unwrap();
+/// assert_eq!(scrypt(b"password", b"salt", &params, &mut []), Err(InvalidOutputLen));
+/// ```
 pub fn scrypt(
     password: &[u8],
     salt: &[u8],
     params: &Params,
     output: &mut [u8],
 ) -> Result<(), errors::InvalidOutputLen> {
-    // This check required by Scrypt:
-    // check output.len() > 0 && output.len() <= (2^32 - 1) * 32
-    if output.is_empty() || output.len() / 32 > 0xffff_ffff {
+    scrypt_with_scratch(password, salt, params, output, &mut Scratch::default())
+}
+
+/// The scrypt key derivation function, running its `p` lanes as chosen by
+/// `lanes`.
+///
+/// This is the same as [`scrypt`], which uses
+/// [`LaneStrategy::SequentialReuse`]; the output doesn't depend on `lanes`.
+/// See [`LaneStrategy::memory_required`] for the memory each strategy needs.
+pub fn scrypt_with_lanes(
+    password: &[u8],
+    salt: &[u8],
+    params: &Params,
+    output: &mut [u8],
+    lanes: LaneStrategy,
+) -> Result<(), errors::InvalidOutputLen> {
+    match lanes {
+        LaneStrategy::SequentialReuse => {
+            scrypt_with_scratch(password, salt, params, output, &mut Scratch::default())
+        }
+        #[cfg(feature = "parallel")]
+        LaneStrategy::Parallel => {
+            check_output_len(output.len())?;
+            lanes::scrypt_parallel(password, salt, params, output);
+            Ok(())
+        }
+    }
+}
+
+/// The scrypt key derivation function, reporting progress to `callback`.
+///
+/// This is the same as [`scrypt`], except that `callback` is invoked after
+/// every `interval` BlockMix operations (see [`Progress`]) performed by the
+/// two ROMix loops, as well as once the last one completes. An `interval` of
+/// [`Progress::DEFAULT_INTERVAL`] has no measurable effect on throughput;
+/// `0` is treated as `1`.
+///
+/// If `callback` returns [`ControlFlow::Break`], the computation is aborted
+/// promptly and [`errors::Error::Cancelled`] is returned. `output` is left
+/// untouched in that case, and the partially computed working state is
+/// wiped before returning.
+///
+/// # Return
+/// `Ok(())` if calculation is successful, `Err(Error::InvalidOutputLen)` if
+/// `output` does not satisfy the conditions documented on [`scrypt`], and
+/// `Err(Error::Cancelled)` if `callback` aborted the computation.
+pub fn scrypt_with_progress<F>(
+    password: &[u8],
+    salt: &[u8],
+    params: &Params,
+    output: &mut [u8],
+    interval: u64,
+    mut callback: F,
+) -> Result<(), errors::Error>
+where
+    F: FnMut(Progress) -> ControlFlow<()>,
+{
+    check_output_len(output.len())?;
+
+    let interval = interval.max(1);
+    let total = (1u64 << params.log_n)
+        .saturating_mul(2)
+        .saturating_mul(u64::from(params.p));
+    let mut completed = 0;
+    let mut countdown = interval;
+
+    let flow = scrypt_inner(
+        password,
+        salt,
+        params,
+        output,
+        &mut Scratch::default(),
+        || {
+            completed += 1;
+            countdown -= 1;
+
+            if countdown == 0 || completed == total {
+                countdown = interval;
+                callback(Progress { completed, total })
+            } else {
+                ControlFlow::Continue(())
+            }
+        },
+    );
+
+    match flow {
+        ControlFlow::Continue(()) => Ok(()),
+        ControlFlow::Break(()) => Err(errors::Error::Cancelled),
+    }
+}
+
+/// This check required by Scrypt:
+/// check output.len() > 0 && output.len() <= (2^32 - 1) * 32
+fn check_output_len(len: usize) -> Result<(), errors::InvalidOutputLen> {
+    if len == 0 || (len as u64) > 0xffff_ffff * 32 {
         return Err(errors::InvalidOutputLen);
     }
 
-    // The checks in the ScryptParams constructor guarantee
-    // that the following is safe:
+    Ok(())
+}
+
+/// [`scrypt`], reusing the working memory in `scratch`.
+pub(crate) fn scrypt_with_scratch(
+    password: &[u8],
+    salt: &[u8],
+    params: &Params,
+    output: &mut [u8],
+    scratch: &mut Scratch,
+) -> Result<(), errors::InvalidOutputLen> {
+    check_output_len(output.len())?;
+
+    let flow = scrypt_inner(password, salt, params, output, scratch, || {
+        ControlFlow::Continue(())
+    });
+    debug_assert!(flow.is_continue());
+
+    Ok(())
+}
+
+/// Shared implementation of [`scrypt`] and [`scrypt_with_progress`].
+///
+/// `output` must already have been validated with [`check_output_len`].
+/// It is only written to if `tick` never breaks, otherwise `scratch` is
+/// wiped.
+fn scrypt_inner(
+    password: &[u8],
+    salt: &[u8],
+    params: &Params,
+    output: &mut [u8],
+    scratch: &mut Scratch,
+    mut tick: impl FnMut() -> ControlFlow<()>,
+) -> ControlFlow<()> {
     let n = 1 << params.log_n;
     let r128 = (params.r as usize) * 128;
-    let pr128 = (params.p as usize) * r128;
-    let nr128 = n * r128;
 
-    let mut b = vec![0u8; pr128];
-    pbkdf2_hmac::<Sha256>(password, salt, 1, &mut b);
+    scratch.prepare(params);
+    let Scratch { b, v, xy } = scratch;
 
-    let mut v = vec![0u8; nr128];
-    let mut t = vec![0u8; r128];
+    pbkdf2_hmac::<Sha256>(password, salt, 1, b);
 
-    for chunk in &mut b.chunks_mut(r128) {
-        romix::scrypt_ro_mix(chunk, &mut v, &mut t, n);
+    let mut flow = ControlFlow::Continue(());
+
+    for chunk in b.chunks_mut(r128) {
+        flow = romix::scrypt_ro_mix(chunk, v, xy, n, &mut tick);
+        if flow.is_break() {
+            break;
+        }
     }
 
-    pbkdf2_hmac::<Sha256>(password, &b, 1, output);
-    Ok(())
+    if flow.is_continue() {
+        pbkdf2_hmac::<Sha256>(password, b, 1, output);
+    } else {
+        scratch.wipe();
+    }
+
+    flow
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_output_len;
+    use crate::errors::InvalidOutputLen;
+
+    #[test]
+    fn output_len_bounds() {
+        assert_eq!(check_output_len(0), Err(InvalidOutputLen));
+        assert_eq!(check_output_len(1), Ok(()));
+        assert_eq!(check_output_len(64), Ok(()));
+
+        #[cfg(target_pointer_width = "64")]
+        {
+            assert_eq!(check_output_len(0xffff_ffff * 32), Ok(()));
+            assert_eq!(
+                check_output_len(0xffff_ffff * 32 + 1),
+                Err(InvalidOutputLen)
+            );
+            assert_eq!(check_output_len(usize::MAX), Err(InvalidOutputLen));
+        }
+    }
 }
@@ -0,0 +1,123 @@
+use crate::{errors::Error, Params};
+
+/// Upper bound on the memory a single scrypt computation may allocate.
+///
+/// The limit is checked against the parameters before any memory is
+/// allocated, so that a corrupted or malicious stored hash (e.g. `ln=30`)
+/// fails cleanly with [`Error::MemoryLimitExceeded`] instead of getting the
+/// process killed.
+///
+/// The raw [`scrypt`](crate::scrypt) function is unlimited, while the
+/// [`PasswordHasher`](password_hash::PasswordHasher) implementation of
+/// [`Scrypt`](crate::Scrypt), which its `PasswordVerifier` implementation is
+/// built on, enforces [`MemoryLimit::DEFAULT`]. Hashing with another limit
+/// is possible with
+/// [`Scrypt::hash_password_with_memory_limit`](crate::Scrypt::hash_password_with_memory_limit).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct MemoryLimit(u64);
+
+impl MemoryLimit {
+    /// Default limit used when verifying PHC strings: 2 GiB, which allows
+    /// [`Params::SENSITIVE`].
+    pub const DEFAULT: MemoryLimit = MemoryLimit(2 << 30);
+
+    /// No limit.
+    pub const UNLIMITED: MemoryLimit = MemoryLimit(u64::MAX);
+
+    /// Create a limit of `bytes` bytes.
+    pub const fn new(bytes: u64) -> MemoryLimit {
+        MemoryLimit(bytes)
+    }
+
+    /// The limit, in bytes.
+    pub const fn bytes(&self) -> u64 {
+        self.0
+    }
+
+    /// Check that a computation with `params` stays within this limit.
+    pub fn check(&self, params: &Params) -> Result<(), Error> {
+        let required = params.mem_required();
+
+        if required > self.0 {
+            return Err(Error::MemoryLimitExceeded {
+                required,
+                limit: self.0,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for MemoryLimit {
+    fn default() -> MemoryLimit {
+        MemoryLimit::DEFAULT
+    }
+}
+
+/// Upper bounds on the cost parameters of hashes being verified.
+///
+/// A corrupted or malicious stored hash can stay within the
+/// [`MemoryLimit`] and still take minutes to compute, e.g. with a huge `p`.
+/// The policy is checked against the parameters before any memory is
+/// allocated, and rejects them with [`Error::ParamsPolicyViolation`].
+///
+/// The policy only applies to verification: the `PasswordVerifier`
+/// implementation of [`Scrypt`](crate::Scrypt) enforces
+/// [`ParamsPolicy::DEFAULT`], and so does the
+/// [`PasswordHasher`](password_hash::PasswordHasher) implementation it is
+/// built on, while
+/// [`Scrypt::hash_password_with_memory_limit`](crate::Scrypt::hash_password_with_memory_limit)
+/// hashes the caller's own parameters without any policy.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParamsPolicy {
+    /// Maximum log₂ of the Scrypt parameter `N`.
+    pub max_ln: u8,
+
+    /// Maximum Scrypt parameter `r`.
+    pub max_r: u32,
+
+    /// Maximum Scrypt parameter `p`.
+    pub max_p: u32,
+}
+
+impl ParamsPolicy {
+    /// Default policy used when verifying PHC strings: `ln <= 24`, `r <= 32`
+    /// and `p <= 16`, which allows all of the presets and the RFC 7914 test
+    /// vectors.
+    pub const DEFAULT: ParamsPolicy = ParamsPolicy {
+        max_ln: 24,
+        max_r: 32,
+        max_p: 16,
+    };
+
+    /// No limits.
+    pub const UNLIMITED: ParamsPolicy = ParamsPolicy {
+        max_ln: u8::MAX,
+        max_r: u32::MAX,
+        max_p: u32::MAX,
+    };
+
+    /// Check that `params` are within this policy.
+    pub fn check(&self, params: &Params) -> Result<(), Error> {
+        let checks = [
+            ("ln", u32::from(params.log_n), u32::from(self.max_ln)),
+            ("r", params.r, self.max_r),
+            ("p", params.p, self.max_p),
+        ];
+
+        for (param, value, max) in checks {
+            if value > max {
+                return Err(Error::ParamsPolicyViolation { param, value, max });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for ParamsPolicy {
+    fn default() -> ParamsPolicy {
+        ParamsPolicy::DEFAULT
+    }
+}
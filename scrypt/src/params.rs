@@ -1,11 +1,27 @@
-use core::mem::size_of;
+use alloc::{format, string::ToString};
+use core::{fmt, str::FromStr};
 
-use crate::errors::InvalidParams;
+use crate::{
+    errors::{InvalidN, InvalidParams, ParseParamsError},
+    ScryptCost,
+};
 
 #[cfg(feature = "simple")]
 use password_hash::{errors::InvalidValue, Error, ParamsString, PasswordHash};
 
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
 /// The Scrypt parameter values.
+///
+/// With the `serde` feature, [`Params`] serializes as a map of `log_n`, `r`
+/// and `p` (e.g. `{"log_n": 17, "r": 8, "p": 1}`), plus `len` when it differs
+/// from [`Params::RECOMMENDED_LEN`]. A missing `len` deserializes as
+/// [`Params::RECOMMENDED_LEN`]. Deserialized values are checked with
+/// [`Params::new`], and invalid ones are reported with the offending field.
 #[derive(Clone, Copy, Debug)]
 pub struct Params {
     pub(crate) log_n: u8,
@@ -28,6 +44,32 @@ impl Params {
     /// Recommended Scrypt parameter `Key length`.
     pub const RECOMMENDED_LEN: usize = 32;
 
+    /// Parameters matching libsodium's
+    /// `crypto_pwhash_scryptsalsa208sha256_OPSLIMIT_INTERACTIVE` and
+    /// `crypto_pwhash_scryptsalsa208sha256_MEMLIMIT_INTERACTIVE` (16 MiB):
+    /// - `log_n = 14` (`n = 16384`)
+    /// - `r = 8`
+    /// - `p = 1`
+    pub const INTERACTIVE: Params = Params {
+        log_n: 14,
+        r: 8,
+        p: 1,
+        len: Self::RECOMMENDED_LEN,
+    };
+
+    /// Parameters matching libsodium's
+    /// `crypto_pwhash_scryptsalsa208sha256_OPSLIMIT_SENSITIVE` and
+    /// `crypto_pwhash_scryptsalsa208sha256_MEMLIMIT_SENSITIVE` (1 GiB):
+    /// - `log_n = 20` (`n = 1048576`)
+    /// - `r = 8`
+    /// - `p = 1`
+    pub const SENSITIVE: Params = Params {
+        log_n: 20,
+        r: 8,
+        p: 1,
+        len: Self::RECOMMENDED_LEN,
+    };
+
     /// Create a new instance of [`Params`].
     ///
     /// # Arguments
@@ -41,34 +83,85 @@ impl Params {
     /// - `r` must be greater than `0` and less than or equal to `4294967295`
     /// - `p` must be greater than `0` and less than `4294967295`
     /// - `len` must be greater than `9` and less than or equal to `64`
+    ///   ([`Output::MAX_LENGTH`](password_hash::Output::MAX_LENGTH))
+    ///
+    /// `len` is only used by the [`PasswordHasher`](password_hash::PasswordHasher)
+    /// implementation, which records it in PHC strings as the `l` parameter
+    /// when it differs from [`Params::RECOMMENDED_LEN`].
     pub fn new(log_n: u8, r: u32, p: u32, len: usize) -> Result<Params, InvalidParams> {
-        let cond1 = (log_n as usize) < usize::BITS as usize;
-        let cond2 = size_of::<usize>() >= size_of::<u32>();
-        let cond3 = r <= usize::MAX as u32 && p < usize::MAX as u32;
-        let cond4 = (10..=64).contains(&len);
-        if !(r > 0 && p > 0 && cond1 && (cond2 || cond3) && cond4) {
-            return Err(InvalidParams);
+        Self::new_inner(log_n, r, p, len, true)
+    }
+
+    /// Create a new instance of [`Params`] from the Scrypt parameter `N`
+    /// itself rather than its log₂.
+    ///
+    /// `N` must be a power of two greater than `1`; the other conditions of
+    /// [`Params::new`] apply unchanged. The key length is
+    /// [`Params::RECOMMENDED_LEN`].
+    ///
+    /// ```
+    /// use scrypt::{errors::InvalidN, Params};
+    ///
+    /// let params = Params::from_n(16384, 8, 1)?;
+    /// assert_eq!(params.log_n(), 14);
+    ///
+    /// assert_eq!(Params::from_n(14, 8, 1).unwrap_err(), InvalidN::NotPowerOfTwo(14));
+    /// # Ok::<(), InvalidN>(())
+    /// ```
+    pub fn from_n(n: u64, r: u32, p: u32) -> Result<Params, InvalidN> {
+        if !n.is_power_of_two() {
+            return Err(InvalidN::NotPowerOfTwo(n));
         }
 
-        let r = r as usize;
-        let p = p as usize;
+        if n < 2 {
+            return Err(InvalidN::TooSmall(n));
+        }
+
+        let log_n = n.trailing_zeros() as u8;
+        Ok(Params::new(log_n, r, p, Self::RECOMMENDED_LEN)?)
+    }
+
+    /// Create a new instance of [`Params`], optionally skipping the RFC 7914
+    /// requirement that `N < 2^(128 * r / 8)`.
+    ///
+    /// Some formats (e.g. Web3 keystores) permit parameters such as
+    /// `N = 2^18, r = 1` which break that requirement. Without it `log_n` is
+    /// still limited to `32`, as `Integerify` only reads 32 bits.
+    pub(crate) fn new_inner(
+        log_n: u8,
+        r: u32,
+        p: u32,
+        len: usize,
+        rfc_n_bound: bool,
+    ) -> Result<Params, InvalidParams> {
+        let err = InvalidParams::new;
 
-        let n: usize = 1 << log_n;
+        if r == 0 {
+            return Err(err("r", "greater than 0"));
+        }
 
-        // check that r * 128 doesn't overflow
-        let r128 = r.checked_mul(128).ok_or(InvalidParams)?;
+        if p == 0 {
+            return Err(err("p", "greater than 0"));
+        }
 
-        // check that n * r * 128 doesn't overflow
-        r128.checked_mul(n).ok_or(InvalidParams)?;
+        if !(10..=64).contains(&len) {
+            return Err(err("len", "between 10 and 64"));
+        }
 
-        // check that p * r * 128 doesn't overflow
-        r128.checked_mul(p).ok_or(InvalidParams)?;
+        check_addressable(log_n, r, p, usize::BITS)?;
+
+        let r = r as usize;
+        let p = p as usize;
 
         // This check required by Scrypt:
         // check: n < 2^(128 * r / 8)
-        // r * 16 won't overflow since r128 didn't
-        if (log_n as usize) >= r * 16 {
-            return Err(InvalidParams);
+        // r * 16 won't overflow since 128 * r is addressable
+        if rfc_n_bound && (log_n as usize) >= r * 16 {
+            return Err(err("log_n", "less than 16 * r"));
+        }
+
+        if !rfc_n_bound && log_n > 32 {
+            return Err(err("log_n", "at most 32"));
         }
 
         // This check required by Scrypt:
@@ -76,7 +169,7 @@ impl Params {
         // It takes a bit of re-arranging to get the check above into this form,
         // but it is indeed the same.
         if r * p >= 0x4000_0000 {
-            return Err(InvalidParams);
+            return Err(err("p", "such that r * p is less than 2^30"));
         }
 
         Ok(Params {
@@ -87,6 +180,107 @@ impl Params {
         })
     }
 
+    /// Create a new instance of [`Params`] from libsodium's `opslimit` and
+    /// `memlimit`, using the same algorithm as
+    /// `crypto_pwhash_scryptsalsa208sha256`.
+    ///
+    /// `r` is always `8`. If `opslimit` is small relative to `memlimit`, `N`
+    /// is derived from `opslimit` and `p` is `1`; otherwise `N` is the largest
+    /// power of two whose `V` fits in `memlimit`, and the remaining operations
+    /// are spent on `p`.
+    ///
+    /// The key length is [`Params::RECOMMENDED_LEN`].
+    pub fn from_libsodium(opslimit: u64, memlimit: usize) -> Result<Params, InvalidParams> {
+        let opslimit = opslimit.max(32768);
+        let memlimit = memlimit as u64;
+        let r = 8u64;
+
+        let max_n = if opslimit < memlimit / 32 {
+            opslimit / (r * 4)
+        } else {
+            memlimit / (r * 128)
+        };
+
+        let mut log_n = 1;
+        while log_n < 63 && (1u64 << log_n) <= max_n / 2 {
+            log_n += 1;
+        }
+
+        let p = if opslimit < memlimit / 32 {
+            1
+        } else {
+            let max_rp = ((opslimit / 4) / (1u64 << log_n)).min(0x3fff_ffff);
+            max_rp / r
+        };
+
+        Params::new(log_n, r as u32, p as u32, Self::RECOMMENDED_LEN)
+    }
+
+    /// Create a new instance of [`Params`] whose [`scrypt`](crate::scrypt)
+    /// takes about `duration` on this machine.
+    ///
+    /// The machine is benchmarked by hashing with `r = 8`, `p = 1` and
+    /// increasing `N`, until a hash takes a quarter of `duration`, so this
+    /// call itself takes about as long as `duration`. `N` is then the largest
+    /// power of two expected to hash within `duration`, between `2^10`
+    /// (1 MiB) and the `2^20` (1 GiB) of [`Params::SENSITIVE`]. When that
+    /// upper bound is reached, the remaining time is spent on `p`.
+    ///
+    /// The key length is [`Params::RECOMMENDED_LEN`].
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// let params = scrypt::Params::recommended_for(Duration::from_millis(250));
+    /// println!("log_n = {}, r = {}, p = {}", params.log_n(), params.r(), params.p());
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn recommended_for(duration: Duration) -> Params {
+        const MIN_LOG_N: u8 = 10;
+        const MAX_LOG_N: u8 = 20;
+        // Keeps `128 * r * p` addressable on 32-bit platforms
+        const MAX_P: f64 = (1 << 20) as f64;
+
+        let mut output = [0u8; Self::RECOMMENDED_LEN];
+        let mut log_n = MIN_LOG_N;
+        let elapsed = loop {
+            let params = Params {
+                log_n,
+                r: Self::RECOMMENDED_R,
+                p: 1,
+                len: Self::RECOMMENDED_LEN,
+            };
+            let start = Instant::now();
+            crate::scrypt(b"password", b"salt", &params, &mut output)
+                .expect("output length is valid");
+            let elapsed = start.elapsed();
+
+            if log_n == MAX_LOG_N || elapsed >= duration / 4 {
+                break elapsed.as_secs_f64().max(f64::MIN_POSITIVE);
+            }
+            log_n += 1;
+        };
+
+        // The time scales linearly with `N`
+        let budget = duration.as_secs_f64() / elapsed;
+        let target = (f64::from(log_n) + budget.log2()).floor();
+        let target = target.clamp(f64::from(MIN_LOG_N), f64::from(MAX_LOG_N)) as u8;
+        let lane = elapsed * f64::from(1u32 << target) / f64::from(1u32 << log_n);
+        let p = if target == MAX_LOG_N {
+            (duration.as_secs_f64() / lane).floor().clamp(1.0, MAX_P) as u32
+        } else {
+            1
+        };
+
+        Params {
+            log_n: target,
+            r: Self::RECOMMENDED_R,
+            p,
+            len: Self::RECOMMENDED_LEN,
+        }
+    }
+
     /// Recommended values sufficient for most use-cases
     /// - `log_n = 15` (`n = 32768`)
     /// - `r = 8`
@@ -107,6 +301,11 @@ impl Params {
         self.log_n
     }
 
+    /// The Scrypt parameter `N`, i.e. `2^log_n`.
+    pub fn n(&self) -> u64 {
+        1 << self.log_n
+    }
+
     /// `r` parameter: resource usage.
     ///
     /// scrypt iterates 2*r times. Memory and CPU time scale linearly
@@ -119,6 +318,43 @@ impl Params {
     pub fn p(&self) -> u32 {
         self.p
     }
+
+    /// Peak memory of [`scrypt`](crate::scrypt) with these parameters, in
+    /// bytes: `V` (`128 * r * N`), `B` (`128 * r * p`) and the two BlockMix
+    /// buffers `X` and `Y` (`2 * 128 * r`).
+    ///
+    /// This is the memory of [`LaneStrategy::SequentialReuse`], which only
+    /// needs a single `V` whatever `p` is. Running the lanes in parallel
+    /// needs more, see [`LaneStrategy::memory_required`].
+    ///
+    /// [`LaneStrategy::SequentialReuse`]: crate::LaneStrategy::SequentialReuse
+    /// [`LaneStrategy::memory_required`]: crate::LaneStrategy::memory_required
+    pub fn mem_required(&self) -> u64 {
+        let r128 = 128 * u64::from(self.r);
+        let blocks = (1u64 << self.log_n)
+            .saturating_add(u64::from(self.p))
+            .saturating_add(2);
+        r128.saturating_mul(blocks)
+    }
+
+    /// Approximate cost of [`scrypt`](crate::scrypt) with these parameters.
+    ///
+    /// ```
+    /// let params = scrypt::Params::new(16, 8, 1, 32)?;
+    /// assert_eq!(params.approx_cost().to_string(), "64 MiB, ~2^21 block mixes");
+    /// # Ok::<(), scrypt::errors::InvalidParams>(())
+    /// ```
+    pub fn approx_cost(&self) -> ScryptCost {
+        // 2 * N BlockMix per lane, saturating like `mem_required`
+        let block_mixes = u128::from(self.p) << (self.log_n + 1);
+        let saturate = |x: u128| u64::try_from(x).unwrap_or(u64::MAX);
+
+        ScryptCost {
+            memory: self.mem_required(),
+            blocks_mixed: saturate(block_mixes.saturating_mul(2 * u128::from(self.r))),
+            bytes_touched: saturate(block_mixes.saturating_mul(128 * u128::from(self.r))),
+        }
+    }
 }
 
 impl Default for Params {
@@ -127,15 +363,210 @@ impl Default for Params {
     }
 }
 
+/// Builder for scrypt [`Params`].
+///
+/// Parameters left unset get their recommended values, e.g.
+/// [`Params::RECOMMENDED_R`]. [`ParamsBuilder::build`] checks them as
+/// [`Params::new`] does, and its errors name the offending parameter:
+///
+/// ```
+/// use scrypt::ParamsBuilder;
+///
+/// let params = ParamsBuilder::new().log_n(15).r(8).p(1).build()?;
+/// assert_eq!(params.n(), 32768);
+///
+/// let err = ParamsBuilder::new().r(0).build().unwrap_err();
+/// assert_eq!(err.param(), "r");
+/// assert_eq!(err.expected(), "greater than 0");
+/// # Ok::<(), scrypt::errors::InvalidParams>(())
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParamsBuilder {
+    log_n: u8,
+    r: u32,
+    p: u32,
+    len: usize,
+}
+
+impl ParamsBuilder {
+    /// Create a new builder with the recommended parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the log₂ of the Scrypt parameter `N`: CPU/memory cost.
+    pub fn log_n(&mut self, log_n: u8) -> &mut Self {
+        self.log_n = log_n;
+        self
+    }
+
+    /// Set the Scrypt parameter `r`: block size.
+    pub fn r(&mut self, r: u32) -> &mut Self {
+        self.r = r;
+        self
+    }
+
+    /// Set the Scrypt parameter `p`: parallelism.
+    pub fn p(&mut self, p: u32) -> &mut Self {
+        self.p = p;
+        self
+    }
+
+    /// Set the Scrypt parameter `Key length`, used by the
+    /// [`PasswordHasher`](password_hash::PasswordHasher) implementation.
+    pub fn len(&mut self, len: usize) -> &mut Self {
+        self.len = len;
+        self
+    }
+
+    /// Get the finished [`Params`].
+    ///
+    /// The parameters must meet the conditions of [`Params::new`]: the
+    /// error names the first one which doesn't, and the values it accepts.
+    pub fn build(&self) -> Result<Params, InvalidParams> {
+        Params::new(self.log_n, self.r, self.p, self.len)
+    }
+}
+
+impl Default for ParamsBuilder {
+    fn default() -> Self {
+        Self {
+            log_n: Params::RECOMMENDED_LOG_N,
+            r: Params::RECOMMENDED_R,
+            p: Params::RECOMMENDED_P,
+            len: Params::RECOMMENDED_LEN,
+        }
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Params, InvalidParams> {
+        builder.build()
+    }
+}
+
+/// Parses the parameter grammar of PHC strings, e.g. `ln=15,r=8,p=1` or
+/// `ln=15,r=8,p=1,l=64`, as written by [`Params`]'s `Display` impl.
+///
+/// Parameters may be given in any order. A missing parameter gets its
+/// recommended value, like in PHC strings. Errors identify the offending
+/// `name=value` token.
+impl FromStr for Params {
+    type Err = ParseParamsError;
+
+    fn from_str(s: &str) -> Result<Params, ParseParamsError> {
+        const NAMES: [&str; 4] = ["ln", "r", "p", "l"];
+
+        let mut values = [None; 4];
+
+        for token in s.split(',') {
+            let err = |expected| ParseParamsError::new(token, expected);
+
+            let (name, value) = token.split_once('=').ok_or_else(|| err("`name=value`"))?;
+            let i = NAMES
+                .iter()
+                .position(|&n| n == name)
+                .ok_or_else(|| err("one of `ln`, `r`, `p` or `l`"))?;
+
+            if values[i].is_some() {
+                return Err(err("each parameter at most once"));
+            }
+
+            if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(err("a decimal integer"));
+            }
+
+            let value: u32 = value
+                .parse()
+                .map_err(|_| err("a value fitting in 32 bits"))?;
+            values[i] = Some((value, token));
+        }
+
+        let token = |i: usize, value: u64| match values[i] {
+            Some((_, token)) => token.to_string(),
+            None => format!("{}={}", NAMES[i], value),
+        };
+
+        let log_n = match values[0] {
+            Some((log_n, token)) => {
+                u8::try_from(log_n).map_err(|_| ParseParamsError::new(token, "less than 256"))?
+            }
+            None => Self::RECOMMENDED_LOG_N,
+        };
+        let r = values[1].map_or(Self::RECOMMENDED_R, |(r, _)| r);
+        let p = values[2].map_or(Self::RECOMMENDED_P, |(p, _)| p);
+        let len = values[3].map_or(Self::RECOMMENDED_LEN, |(len, _)| len as usize);
+
+        Params::new(log_n, r, p, len).map_err(|err| {
+            let token = match err.param() {
+                "log_n" => token(0, log_n.into()),
+                "r" => token(1, r.into()),
+                "p" => token(2, p.into()),
+                _ => token(3, len as u64),
+            };
+            ParseParamsError::new(&token, err.expected())
+        })
+    }
+}
+
+/// Formats [`Params`] like the parameters of a PHC string, e.g.
+/// `ln=15,r=8,p=1`, with the key length as `l` when it differs from
+/// [`Params::RECOMMENDED_LEN`].
+impl fmt::Display for Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ln={},r={},p={}", self.log_n, self.r, self.p)?;
+
+        if self.len != Self::RECOMMENDED_LEN {
+            write!(f, ",l={}", self.len)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Check that the buffers of a computation with the given parameters can be
+/// allocated on a platform with `pointer_width`-bit pointers, where
+/// allocations are limited to `isize::MAX` bytes: `V` takes `128 * r * N`
+/// bytes, and `B` takes `128 * r * p` bytes.
+///
+/// The math is done on `u128` rather than `usize`, so that the bounds of
+/// 32-bit platforms can be tested everywhere.
+pub(crate) fn check_addressable(
+    log_n: u8,
+    r: u32,
+    p: u32,
+    pointer_width: u32,
+) -> Result<(), InvalidParams> {
+    const EXPECTED_N: &str = "such that 128 * r * N bytes are addressable on this platform";
+    const EXPECTED_P: &str = "such that 128 * r * p bytes are addressable on this platform";
+
+    let max = (1u128 << (pointer_width - 1)) - 1;
+    let r128 = 128 * u128::from(r);
+
+    if u32::from(log_n) >= pointer_width || r128 << log_n > max {
+        return Err(InvalidParams::new("log_n", EXPECTED_N));
+    }
+
+    if r128 * u128::from(p) > max {
+        return Err(InvalidParams::new("p", EXPECTED_P));
+    }
+
+    Ok(())
+}
+
 #[cfg(feature = "simple")]
 #[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
 impl<'a> TryFrom<&'a PasswordHash<'a>> for Params {
     type Error = password_hash::Error;
 
     fn try_from(hash: &'a PasswordHash<'a>) -> Result<Self, password_hash::Error> {
-        let mut log_n = Self::RECOMMENDED_LOG_N;
+        let mut log_n = None;
+        let mut n = None;
         let mut r = Self::RECOMMENDED_R;
         let mut p = Self::RECOMMENDED_P;
+        let mut len = None;
 
         if hash.version.is_some() {
             return Err(Error::Version);
@@ -144,22 +575,55 @@ impl<'a> TryFrom<&'a PasswordHash<'a>> for Params {
         for (ident, value) in hash.params.iter() {
             match ident.as_str() {
                 "ln" => {
-                    log_n = value
-                        .decimal()?
-                        .try_into()
-                        .map_err(|_| InvalidValue::Malformed.param_error())?
+                    log_n = Some(
+                        value
+                            .decimal()?
+                            .try_into()
+                            .map_err(|_| InvalidValue::Malformed.param_error())?,
+                    )
+                }
+                // Some implementations write `N` itself instead of `ln`
+                "n" => {
+                    let value = value.decimal()?;
+                    if !value.is_power_of_two() || value < 2 {
+                        return Err(InvalidValue::Malformed.param_error());
+                    }
+                    n = Some(value.trailing_zeros() as u8);
                 }
                 "r" => r = value.decimal()?,
                 "p" => p = value.decimal()?,
+                "l" => {
+                    len = Some(
+                        value
+                            .decimal()?
+                            .try_into()
+                            .map_err(|_| InvalidValue::Malformed.param_error())?,
+                    )
+                }
                 _ => return Err(password_hash::Error::ParamNameInvalid),
             }
         }
 
-        let len = hash
-            .hash
-            .map(|out| out.len())
-            .unwrap_or(Self::RECOMMENDED_LEN);
-        Params::new(log_n, r, p, len).map_err(|_| InvalidValue::Malformed.param_error())
+        let log_n = match (log_n, n) {
+            (Some(log_n), Some(n)) if log_n != n => {
+                return Err(InvalidValue::Malformed.param_error())
+            }
+            (Some(log_n), _) | (None, Some(log_n)) => log_n,
+            (None, None) => Self::RECOMMENDED_LOG_N,
+        };
+
+        let hash_len = hash.hash.map(|out| out.len());
+
+        // Strings without an `l` parameter use the length of their hash
+        let len = match (len, hash_len) {
+            (Some(len), Some(hash_len)) if len != hash_len => {
+                return Err(InvalidValue::Malformed.param_error())
+            }
+            (Some(len), _) | (None, Some(len)) => len,
+            (None, None) => Self::RECOMMENDED_LEN,
+        };
+
+        Ok(Params::new(log_n, r, p, len)?)
     }
 }
 
@@ -173,6 +637,239 @@ impl TryFrom<Params> for ParamsString {
         output.add_decimal("ln", input.log_n as u32)?;
         output.add_decimal("r", input.r)?;
         output.add_decimal("p", input.p)?;
+
+        if input.len != Params::RECOMMENDED_LEN {
+            output.add_decimal("l", input.len as u32)?;
+        }
+
         Ok(output)
     }
 }
+
+/// Serialized form of [`Params`].
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Params", deny_unknown_fields)]
+struct ParamsRepr {
+    log_n: u8,
+    r: u32,
+    p: u32,
+    #[serde(
+        default = "recommended_len",
+        skip_serializing_if = "is_recommended_len"
+    )]
+    len: usize,
+}
+
+#[cfg(feature = "serde")]
+fn recommended_len() -> usize {
+    Params::RECOMMENDED_LEN
+}
+
+#[cfg(feature = "serde")]
+fn is_recommended_len(len: &usize) -> bool {
+    *len == Params::RECOMMENDED_LEN
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for Params {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ParamsRepr {
+            log_n: self.log_n,
+            r: self.r,
+            p: self.p,
+            len: self.len,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for Params {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Params, D::Error> {
+        let ParamsRepr { log_n, r, p, len } = ParamsRepr::deserialize(deserializer)?;
+
+        let err = match Params::new(log_n, r, p, len) {
+            Ok(params) => return Ok(params),
+            Err(err) => err,
+        };
+
+        let (field, expected) = (err.param(), err.expected());
+        let value: u64 = match field {
+            "log_n" => log_n.into(),
+            "r" => r.into(),
+            "p" => p.into(),
+            _ => len as u64,
+        };
+
+        Err(de::Error::custom(format_args!(
+            "invalid value for `{}`: {}, expected {}",
+            field, value, expected
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_addressable, Params, ParamsBuilder};
+    use crate::ScryptCost;
+    use alloc::string::ToString;
+
+    #[test]
+    fn params_builder_bad_values() {
+        for (builder, param, expected) in [
+            (ParamsBuilder::new().r(0).clone(), "r", "greater than 0"),
+            (ParamsBuilder::new().p(0).clone(), "p", "greater than 0"),
+            (
+                ParamsBuilder::new().len(9).clone(),
+                "len",
+                "between 10 and 64",
+            ),
+            (
+                ParamsBuilder::new().log_n(16).r(1).clone(),
+                "log_n",
+                "less than 16 * r",
+            ),
+            (
+                ParamsBuilder::new().log_n(1).r(1 << 15).p(1 << 15).clone(),
+                "p",
+                "such that r * p is less than 2^30",
+            ),
+        ] {
+            let err = builder.build().unwrap_err();
+            assert_eq!((err.param(), err.expected()), (param, expected));
+        }
+
+        let params = ParamsBuilder::new()
+            .log_n(15)
+            .r(4)
+            .p(2)
+            .len(64)
+            .build()
+            .unwrap();
+        assert_eq!(
+            (params.log_n(), params.r(), params.p(), params.len),
+            (15, 4, 2, 64)
+        );
+        let params = Params::try_from(ParamsBuilder::new()).unwrap();
+        assert_eq!(params.to_string(), Params::recommended().to_string());
+    }
+
+    #[test]
+    fn addressable_32_bit() {
+        // V = 128 * r * N must stay below 2^31
+        assert!(check_addressable(20, 8, 1, 32).is_ok());
+        assert!(check_addressable(21, 8, 1, 32).is_err());
+        assert!(check_addressable(23, 1, 1, 32).is_ok());
+        assert!(check_addressable(24, 1, 1, 32).is_err());
+        assert!(check_addressable(26, 8, 1, 32).is_err());
+        assert!(check_addressable(31, 1, 1, 32).is_err());
+        assert!(check_addressable(32, 1, 1, 32).is_err());
+        assert!(check_addressable(u8::MAX, 1, 1, 32).is_err());
+
+        // B = 128 * r * p too
+        assert!(check_addressable(1, 8, (1 << 21) - 1, 32).is_ok());
+        let err = check_addressable(1, 8, 1 << 21, 32).unwrap_err();
+        assert_eq!(err.param(), "p");
+        assert!(check_addressable(1, u32::MAX, 1, 32).is_err());
+
+        let err = check_addressable(26, 8, 1, 32).unwrap_err();
+        assert_eq!(err.param(), "log_n");
+        assert_eq!(
+            err.expected(),
+            "such that 128 * r * N bytes are addressable on this platform"
+        );
+    }
+
+    #[test]
+    fn addressable_64_bit() {
+        assert!(check_addressable(26, 8, 1, 64).is_ok());
+        assert!(check_addressable(52, 8, 1, 64).is_ok());
+        assert!(check_addressable(53, 8, 1, 64).is_err());
+        assert!(check_addressable(63, 1, 1, 64).is_err());
+        assert!(check_addressable(64, 1, 1, 64).is_err());
+        assert!(check_addressable(1, u32::MAX, 1, 64).is_ok());
+        assert!(check_addressable(1, u32::MAX, u32::MAX, 64).is_err());
+    }
+
+    #[test]
+    fn cost_estimates() {
+        for ((log_n, r, p), memory, blocks_mixed, bytes_touched, summary) in [
+            ((1, 1, 1), 640, 8, 512, "640 B, ~2^3 block mixes"),
+            (
+                (14, 8, 1),
+                (16 << 20) + 3 * 1024,
+                1 << 19,
+                32 << 20,
+                "16 MiB, ~2^19 block mixes",
+            ),
+            (
+                (17, 8, 1),
+                (128 << 20) + 3 * 1024,
+                1 << 22,
+                256 << 20,
+                "128 MiB, ~2^22 block mixes",
+            ),
+            (
+                (10, 8, 16),
+                (1 << 20) + 18 * 1024,
+                1 << 19,
+                32 << 20,
+                "1 MiB, ~2^19 block mixes",
+            ),
+            // 2 * 2^13 * 2 * 6 = 2^17.58 block mixes
+            (
+                (13, 3, 2),
+                (3 << 20) + 4 * 384,
+                3 << 16,
+                12 << 20,
+                "3 MiB, ~2^18 block mixes",
+            ),
+            (
+                (15, 1, 1),
+                (4 << 20) + 384,
+                1 << 17,
+                8 << 20,
+                "4 MiB, ~2^17 block mixes",
+            ),
+            (
+                (11, 5, 1),
+                5 * 128 * (2048 + 3),
+                5 << 13,
+                5 << 19,
+                "1.3 MiB, ~2^15 block mixes",
+            ),
+        ] {
+            let params = Params::new(log_n, r, p, 32).unwrap();
+            assert_eq!(params.mem_required(), memory);
+
+            let cost = params.approx_cost();
+            assert_eq!(
+                cost,
+                ScryptCost {
+                    memory,
+                    blocks_mixed,
+                    bytes_touched,
+                }
+            );
+            assert_eq!(cost.to_string(), summary);
+        }
+    }
+
+    #[test]
+    fn cost_saturates() {
+        let params = Params {
+            log_n: 63,
+            r: u32::MAX,
+            p: u32::MAX,
+            len: 32,
+        };
+        let cost = params.approx_cost();
+        assert_eq!(cost.memory, u64::MAX);
+        assert_eq!(cost.blocks_mixed, u64::MAX);
+        assert_eq!(cost.bytes_touched, u64::MAX);
+        assert_eq!(cost.to_string(), "16384 PiB, ~2^64 block mixes");
+    }
+}
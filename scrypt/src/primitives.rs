@@ -0,0 +1,118 @@
+//! The building blocks of scrypt, as specified in [RFC 7914]: the Salsa20/8
+//! core, `scryptBlockMix` and `scryptROMix`.
+//!
+//! These are exposed for constructions built on top of scrypt's primitives
+//! (e.g. yescrypt-style KDFs). Their semantics follow the RFC exactly and
+//! won't change; most users want the [`scrypt`](crate::scrypt) function
+//! instead.
+//!
+//! All byte strings use the RFC's little-endian encoding of 32-bit words.
+//!
+//! [RFC 7914]: https://www.rfc-editor.org/rfc/rfc7914
+
+use alloc::{vec, vec::Vec};
+use core::ops::ControlFlow;
+
+use crate::{romix, salsa::Backend, vector};
+
+/// The Salsa20/8 core (RFC 7914 §3), applied to `block` in place.
+///
+/// # Example
+/// ```
+/// let mut block = [0u8; 64];
+/// scrypt::primitives::salsa20_8(&mut block);
+/// assert_eq!(block, [0u8; 64]);
+/// ```
+pub fn salsa20_8(block: &mut [u8; 64]) {
+    let mut words = to_words(block);
+    let words: &mut [u32; 16] = words.as_mut_slice().try_into().unwrap();
+
+    let backend = Backend::detect();
+    backend.shuffle(words);
+    backend.salsa20_8(words);
+    backend.unshuffle(words);
+
+    from_words(words, block);
+}
+
+/// `scryptBlockMix` (RFC 7914 §4) of `input`, written to `output`.
+///
+/// # Panics
+/// If `input.len()` isn't a non-zero multiple of `128` (i.e. `128 * r`), or
+/// `output.len() != input.len()`.
+///
+/// # Example
+/// ```
+/// let input = [0u8; 128];
+/// let mut output = [0u8; 128];
+/// scrypt::primitives::block_mix(&input, &mut output);
+/// ```
+pub fn block_mix(input: &[u8], output: &mut [u8]) {
+    assert!(
+        !input.is_empty() && input.len() % 128 == 0,
+        "input length must be a non-zero multiple of 128"
+    );
+    assert_eq!(output.len(), input.len(), "output length must match input");
+
+    let backend = Backend::detect();
+
+    let mut words = to_words(input);
+    backend.shuffle(&mut words);
+
+    let mut out = vec![0u32; words.len()];
+    romix::scrypt_block_mix(backend, &words, None, &mut out);
+
+    backend.unshuffle(&mut out);
+    from_words(&out, output);
+}
+
+/// `scryptROMix` (RFC 7914 §5) of `b` in place, with the CPU/memory cost
+/// parameter `n`.
+///
+/// `v` and `xy` are working memory, exactly as used internally by
+/// [`scrypt`](crate::scrypt): `v` holds the vector `V` (`n` blocks of
+/// `b.len() / 4` words) and `xy` the two alternating BlockMix buffers
+/// (`b.len() / 2` words). Their contents on entry are ignored and on return
+/// are unspecified, so they can be reused across calls.
+///
+/// # Panics
+/// If `b.len()` isn't a non-zero multiple of `128` (i.e. `128 * r`), `n`
+/// isn't a power of two, `v.len() != n * b.len() / 4` or
+/// `xy.len() != b.len() / 2`.
+///
+/// # Example
+/// ```
+/// let mut b = [0u8; 128];
+/// let mut v = vec![0u32; 16 * 128 / 4];
+/// let mut xy = vec![0u32; 128 / 2];
+/// scrypt::primitives::ro_mix(&mut b, &mut v, &mut xy, 16);
+/// ```
+pub fn ro_mix(b: &mut [u8], v: &mut [u32], xy: &mut [u32], n: usize) {
+    assert!(
+        !b.is_empty() && b.len() % 128 == 0,
+        "b length must be a non-zero multiple of 128"
+    );
+    assert!(n.is_power_of_two(), "n must be a power of two");
+    assert_eq!(
+        Some(v.len()),
+        n.checked_mul(b.len() / 4),
+        "v must hold n * b.len() / 4 words"
+    );
+    assert_eq!(xy.len(), b.len() / 2, "xy must hold b.len() / 2 words");
+
+    let flow = vector::scrypt_ro_mix_init(b, v, xy, n, || ControlFlow::Continue(()));
+    debug_assert!(flow.is_continue());
+}
+
+fn to_words(bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .chunks_exact(4)
+        .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+        .collect()
+}
+
+fn from_words(words: &[u32], bytes: &mut [u8]) {
+    for (c, w) in bytes.chunks_exact_mut(4).zip(words.iter()) {
+        c.copy_from_slice(&w.to_le_bytes());
+    }
+}
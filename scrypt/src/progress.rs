@@ -0,0 +1,19 @@
+/// Progress of an ongoing [`scrypt_with_progress`][crate::scrypt_with_progress]
+/// computation.
+///
+/// Progress is measured in BlockMix operations: each of the `p` lanes runs
+/// `N` of them to fill `V` and another `N` to mix it back in, so `total` is
+/// `2 * N * p`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Progress {
+    /// Number of BlockMix operations completed so far.
+    pub completed: u64,
+
+    /// Total number of BlockMix operations the computation will perform.
+    pub total: u64,
+}
+
+impl Progress {
+    /// Default number of BlockMix operations between two progress callbacks.
+    pub const DEFAULT_INTERVAL: u64 = 1024;
+}
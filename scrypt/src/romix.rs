@@ -1,74 +1,290 @@
+use core::{mem, mem::MaybeUninit, ops::ControlFlow};
+
+use crate::{salsa::Backend, vector::Filler};
+
 /// Execute the ROMix operation in-place.
 /// b - the data to operate on
-/// v - a temporary variable to store the vector V
-/// t - a temporary variable to store the result of the xor
+/// v - a temporary variable to store the vector V, as `n` blocks of
+///   `b.len() / 4` words. It doesn't need to be initialized, and is only
+///   ever written initialized words.
+/// xy - a temporary variable of `b.len() / 2` words, holding the two
+///   alternating BlockMix buffers X and Y
 /// n - the scrypt parameter N
+/// tick - called after every BlockMix; returning `Break` aborts the
+///   operation, leaving `b`, `v` and `xy` in an unspecified state
+///
+/// Blocks of `r = 1` are handled by [`scrypt_ro_mix_r1`].
+pub(crate) fn scrypt_ro_mix(
+    b: &mut [u8],
+    v: &mut [MaybeUninit<u32>],
+    xy: &mut [u32],
+    n: usize,
+    tick: impl FnMut() -> ControlFlow<()>,
+) -> ControlFlow<()> {
+    if b.len() == 128 {
+        scrypt_ro_mix_r1(b, v, n, tick)
+    } else {
+        scrypt_ro_mix_generic(b, v, xy, n, tick)
+    }
+}
+
+/// [`scrypt_ro_mix`] for any `r`.
 #[allow(clippy::many_single_char_names)]
-pub(crate) fn scrypt_ro_mix(b: &mut [u8], v: &mut [u8], t: &mut [u8], n: usize) {
-    fn integerify(x: &[u8], n: usize) -> usize {
-        // n is a power of 2, so n - 1 gives us a bitmask that we can use to perform a calculation
-        // mod n using a simple bitwise and.
-        let mask = n - 1;
-        // This cast is safe since we're going to get the value mod n (which is a power of 2), so we
-        // don't have to care about truncating any of the high bits off
-        //let result = (LittleEndian::read_u32(&x[x.len() - 64..x.len() - 60]) as usize) & mask;
-        let t = u32::from_le_bytes(x[x.len() - 64..x.len() - 60].try_into().unwrap());
-        (t as usize) & mask
+fn scrypt_ro_mix_generic(
+    b: &mut [u8],
+    v: &mut [MaybeUninit<u32>],
+    xy: &mut [u32],
+    n: usize,
+    mut tick: impl FnMut() -> ControlFlow<()>,
+) -> ControlFlow<()> {
+    let len = b.len() / 4;
+    let backend = Backend::detect();
+
+    let (mut x, mut y) = xy.split_at_mut(len);
+
+    for (w, c) in x.iter_mut().zip(b.chunks_exact(4)) {
+        *w = u32::from_le_bytes(c.try_into().unwrap());
+    }
+    backend.shuffle(x);
+
+    // V_0 = X, V_i = BlockMix(V_{i-1}), written directly into V
+    let mut filler = Filler::new(v);
+    filler.push(x);
+    for _ in 1..n {
+        filler.push_block_mix(backend, len);
+        if tick().is_break() {
+            return ControlFlow::Break(());
+        }
+    }
+    let v = filler.finish();
+
+    // X = BlockMix(V_{N-1})
+    scrypt_block_mix(backend, &v[(n - 1) * len..], None, x);
+    if tick().is_break() {
+        return ControlFlow::Break(());
+    }
+
+    // X = BlockMix(X ^ V_j), alternating between the X and Y buffers
+    for _ in 0..n {
+        let j = integerify(x, n);
+        scrypt_block_mix(backend, x, Some(&v[j * len..(j + 1) * len]), y);
+        mem::swap(&mut x, &mut y);
+        if tick().is_break() {
+            return ControlFlow::Break(());
+        }
+    }
+
+    backend.unshuffle(x);
+    for (c, w) in b.chunks_exact_mut(4).zip(x.iter()) {
+        c.copy_from_slice(&w.to_le_bytes());
     }
 
-    let len = b.len();
+    ControlFlow::Continue(())
+}
+
+/// [`scrypt_ro_mix`] specialized for `r = 1`, as used by proof-of-work
+/// schemes (e.g. Litecoin's `N = 1024, r = 1, p = 1`).
+///
+/// Blocks are fixed-size arrays, so that BlockMix is unrolled and `X` stays
+/// out of `xy`: the output is the same as [`scrypt_ro_mix_generic`]'s.
+fn scrypt_ro_mix_r1(
+    b: &mut [u8],
+    v: &mut [MaybeUninit<u32>],
+    n: usize,
+    mut tick: impl FnMut() -> ControlFlow<()>,
+) -> ControlFlow<()> {
+    let backend = Backend::detect();
+
+    let mut x = [0u32; 32];
+    for (w, c) in x.iter_mut().zip(b.chunks_exact(4)) {
+        *w = u32::from_le_bytes(c.try_into().unwrap());
+    }
+    backend.shuffle(&mut x);
 
-    for chunk in v.chunks_mut(len) {
-        chunk.copy_from_slice(b);
-        scrypt_block_mix(chunk, b);
+    // V_i = X, X = BlockMix(X)
+    let mut filler = Filler::new(v);
+    for _ in 0..n {
+        filler.push(&x);
+        x = block_mix_r1(backend, &x, None);
+        if tick().is_break() {
+            return ControlFlow::Break(());
+        }
     }
+    let v = filler.finish();
 
+    // X = BlockMix(X ^ V_j)
     for _ in 0..n {
-        let j = integerify(b, n);
-        xor(b, &v[j * len..(j + 1) * len], t);
-        scrypt_block_mix(t, b);
+        let j = integerify(&x, n);
+        let v_j: &[u32; 32] = v[j * 32..(j + 1) * 32].try_into().unwrap();
+        x = block_mix_r1(backend, &x, Some(v_j));
+        if tick().is_break() {
+            return ControlFlow::Break(());
+        }
     }
+
+    backend.unshuffle(&mut x);
+    for (c, w) in b.chunks_exact_mut(4).zip(x.iter()) {
+        c.copy_from_slice(&w.to_le_bytes());
+    }
+
+    ControlFlow::Continue(())
+}
+
+/// [`scrypt_block_mix`] of a single block of `r = 1`.
+#[inline(always)]
+fn block_mix_r1(backend: Backend, input: &[u32; 32], mask: Option<&[u32; 32]>) -> [u32; 32] {
+    let mut b = *input;
+    if let Some(mask) = mask {
+        for (b_i, &m_i) in b.iter_mut().zip(mask.iter()) {
+            *b_i ^= m_i;
+        }
+    }
+
+    let mut y = [0u32; 32];
+    let (y0, y1) = y.split_at_mut(16);
+    let (b0, b1) = b.split_at(16);
+
+    let mut x: [u32; 16] = b1.try_into().unwrap();
+    for (x_i, &b_i) in x.iter_mut().zip(b0.iter()) {
+        *x_i ^= b_i;
+    }
+    backend.salsa20_8(&mut x);
+    y0.copy_from_slice(&x);
+
+    for (x_i, &b_i) in x.iter_mut().zip(b1.iter()) {
+        *x_i ^= b_i;
+    }
+    backend.salsa20_8(&mut x);
+    y1.copy_from_slice(&x);
+
+    y
+}
+
+/// Index of the block of `V` that ROMix mixes into `x`, i.e. `Integerify(X) mod N`.
+pub(crate) fn integerify(x: &[u32], n: usize) -> usize {
+    // n is a power of 2, so n - 1 gives us a bitmask that we can use to perform a calculation
+    // mod n using a simple bitwise and.
+    let mask = n - 1;
+    // This cast is safe since we're going to get the value mod n (which is a power of 2), so we
+    // don't have to care about truncating any of the high bits off
+    (x[x.len() - 16] as usize) & mask
 }
 
 /// Execute the BlockMix operation
-/// input - the input vector. The length must be a multiple of 128.
+/// input - the input vector. The length must be a multiple of 32.
+/// mask - if present, BlockMix is applied to `input ^ mask` instead, without
+///   computing the XOR in a separate pass. Must be the same length as input.
 /// output - the output vector. Must be the same length as input.
-fn scrypt_block_mix(input: &[u8], output: &mut [u8]) {
-    use salsa20::{
-        cipher::{typenum::U4, StreamCipherCore},
-        SalsaCore,
-    };
-
-    type Salsa20_8 = SalsaCore<U4>;
+///
+/// All vectors must be in the word order of `backend`. Output blocks are
+/// written straight to their final position (even blocks to the first half,
+/// odd blocks to the second half), so no final shuffle is needed.
+///
+/// Every word of `output` is written, so it may be uninitialized: `V` is
+/// filled without being initialized first (see [`Filler`]).
+pub(crate) fn scrypt_block_mix<W: Word>(
+    backend: Backend,
+    input: &[u32],
+    mask: Option<&[u32]>,
+    output: &mut [W],
+) {
+    assert_eq!(output.len(), input.len());
 
-    let mut x = [0u8; 64];
-    x.copy_from_slice(&input[input.len() - 64..]);
+    let half = input.len() / 2;
 
-    let mut t = [0u8; 64];
+    let mut x = [0u32; 16];
+    x.copy_from_slice(&input[input.len() - 16..]);
+    if let Some(mask) = mask {
+        for (x_i, &m_i) in x.iter_mut().zip(mask[mask.len() - 16..].iter()) {
+            *x_i ^= m_i;
+        }
+    }
 
-    for (i, chunk) in input.chunks(64).enumerate() {
-        xor(&x, chunk, &mut t);
+    for (i, chunk) in input.chunks_exact(16).enumerate() {
+        match mask {
+            Some(mask) => {
+                let m = &mask[i * 16..(i + 1) * 16];
+                for ((x_i, &c_i), &m_i) in x.iter_mut().zip(chunk.iter()).zip(m.iter()) {
+                    *x_i ^= c_i ^ m_i;
+                }
+            }
+            None => {
+                for (x_i, &c_i) in x.iter_mut().zip(chunk.iter()) {
+                    *x_i ^= c_i;
+                }
+            }
+        }
 
-        let mut t2 = [0u32; 16];
+        backend.salsa20_8(&mut x);
 
-        for (c, b) in t.chunks_exact(4).zip(t2.iter_mut()) {
-            *b = u32::from_le_bytes(c.try_into().unwrap());
+        // The `input.len() / 16` values of `i` map to distinct positions
+        // covering all of `output`
+        let pos = (i / 2) * 16 + (i % 2) * half;
+        for (word, &value) in output[pos..pos + 16].iter_mut().zip(x.iter()) {
+            word.set(value);
         }
+    }
+}
 
-        Salsa20_8::from_raw_state(t2).write_keystream_block((&mut x).into());
+/// Word of a BlockMix output, initialized or not.
+pub(crate) trait Word {
+    /// Overwrite the word with `value`.
+    fn set(&mut self, value: u32);
+}
 
-        let pos = if i % 2 == 0 {
-            (i / 2) * 64
-        } else {
-            (i / 2) * 64 + input.len() / 2
-        };
+impl Word for u32 {
+    #[inline(always)]
+    fn set(&mut self, value: u32) {
+        *self = value;
+    }
+}
 
-        output[pos..pos + 64].copy_from_slice(&x);
+impl Word for MaybeUninit<u32> {
+    #[inline(always)]
+    fn set(&mut self, value: u32) {
+        *self = MaybeUninit::new(value);
     }
 }
 
-fn xor(x: &[u8], y: &[u8], output: &mut [u8]) {
-    for ((out, &x_i), &y_i) in output.iter_mut().zip(x.iter()).zip(y.iter()) {
-        *out = x_i ^ y_i;
+#[cfg(test)]
+mod tests {
+    use super::{scrypt_ro_mix_generic, scrypt_ro_mix_r1};
+    use crate::vector::resize_uninit;
+    use alloc::{vec, vec::Vec};
+    use core::ops::ControlFlow;
+
+    #[test]
+    fn r1_matches_generic() {
+        // xorshift64 so the test is deterministic without pulling in `rand`
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+
+        for n in [2, 16, 1024] {
+            let mut b = [0u8; 128];
+            for byte in b.iter_mut() {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                *byte = seed as u8;
+            }
+
+            let mut v = Vec::new();
+            resize_uninit(&mut v, n * 32);
+            let mut xy = vec![0u32; 64];
+            let mut expected = b;
+            let flow = scrypt_ro_mix_generic(&mut expected, &mut v, &mut xy, n, || {
+                ControlFlow::Continue(())
+            });
+            assert!(flow.is_continue());
+
+            let mut ticks = 0;
+            let flow = scrypt_ro_mix_r1(&mut b, &mut v, n, || {
+                ticks += 1;
+                ControlFlow::Continue(())
+            });
+            assert!(flow.is_continue());
+
+            assert_eq!(b, expected);
+            assert_eq!(ticks, 2 * n);
+        }
     }
 }
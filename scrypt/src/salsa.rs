@@ -0,0 +1,211 @@
+//! Salsa20/8 core used by BlockMix.
+//!
+//! When the `simd` feature is enabled, an SSE2 implementation is selected at
+//! runtime on x86/x86_64 CPUs which support it, falling back to the portable
+//! implementation otherwise. On AArch64 targets, which all have NEON, a NEON
+//! implementation is used instead.
+//!
+//! There is no AVX2 implementation: each Salsa20/8 invocation of BlockMix
+//! depends on the previous one, so only a single 16-word block is ever
+//! processed at a time, which fits in four 128-bit registers.
+//!
+//! A backend may require the words of every 16-word Salsa20 block to be kept
+//! in its own order while ROMix runs. [`Backend::shuffle`] converts the
+//! scrypt working state into that order, and [`Backend::unshuffle`] converts
+//! it back. Every backend keeps word 0 of each block in place, so
+//! `Integerify` and the BlockMix XORs are unaffected by the layout.
+
+#[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+mod sse2;
+
+#[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+cpufeatures::new!(sse2_cpuid, "sse2");
+
+#[cfg(all(feature = "simd", target_arch = "aarch64", target_feature = "neon"))]
+mod neon;
+
+/// Word order of the SIMD backends, along the diagonals of the 4x4 Salsa20
+/// matrix: the standard word index stored at each position of a shuffled
+/// block.
+#[cfg(all(
+    feature = "simd",
+    any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", target_feature = "neon")
+    )
+))]
+const DIAGONAL_LAYOUT: [usize; 16] = [0, 5, 10, 15, 4, 9, 14, 3, 8, 13, 2, 7, 12, 1, 6, 11];
+
+/// Salsa20/8 implementation selected for the current CPU.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Backend {
+    /// Portable implementation, using the standard word order.
+    ///
+    /// Never detected on AArch64 targets with NEON.
+    #[cfg_attr(
+        all(feature = "simd", target_arch = "aarch64", target_feature = "neon"),
+        allow(dead_code)
+    )]
+    Soft,
+
+    /// SSE2 implementation, using the word order of [`DIAGONAL_LAYOUT`].
+    #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+    Sse2,
+
+    /// NEON implementation, using the word order of [`DIAGONAL_LAYOUT`].
+    #[cfg(all(feature = "simd", target_arch = "aarch64", target_feature = "neon"))]
+    Neon,
+}
+
+impl Backend {
+    /// Detect the fastest backend supported by the current CPU.
+    #[inline]
+    pub(crate) fn detect() -> Self {
+        #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+        if sse2_cpuid::get() {
+            return Backend::Sse2;
+        }
+
+        #[cfg(all(feature = "simd", target_arch = "aarch64", target_feature = "neon"))]
+        let backend = Backend::Neon;
+        #[cfg(not(all(feature = "simd", target_arch = "aarch64", target_feature = "neon")))]
+        let backend = Backend::Soft;
+
+        backend
+    }
+
+    /// Word order used by this backend, as the standard word index stored at
+    /// each position of a block, or `None` for the standard order.
+    fn layout(self) -> Option<&'static [usize; 16]> {
+        match self {
+            Backend::Soft => None,
+            #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+            Backend::Sse2 => Some(&DIAGONAL_LAYOUT),
+            #[cfg(all(feature = "simd", target_arch = "aarch64", target_feature = "neon"))]
+            Backend::Neon => Some(&DIAGONAL_LAYOUT),
+        }
+    }
+
+    /// Reorder the words of every 16-word block of `b` from the standard
+    /// order into this backend's order.
+    pub(crate) fn shuffle(self, b: &mut [u32]) {
+        if let Some(layout) = self.layout() {
+            for block in b.chunks_exact_mut(16) {
+                let words: [u32; 16] = (&*block).try_into().unwrap();
+                for (w, &i) in block.iter_mut().zip(layout.iter()) {
+                    *w = words[i];
+                }
+            }
+        }
+    }
+
+    /// Reorder the words of every 16-word block of `b` from this backend's
+    /// order back into the standard order.
+    pub(crate) fn unshuffle(self, b: &mut [u32]) {
+        if let Some(layout) = self.layout() {
+            for block in b.chunks_exact_mut(16) {
+                let words: [u32; 16] = (&*block).try_into().unwrap();
+                for (&w, &i) in words.iter().zip(layout.iter()) {
+                    block[i] = w;
+                }
+            }
+        }
+    }
+
+    /// Apply the Salsa20/8 core to `block` in place, including the final
+    /// feed-forward addition of the input words.
+    ///
+    /// `block` must be in this backend's word order.
+    #[inline(always)]
+    pub(crate) fn salsa20_8(self, block: &mut [u32; 16]) {
+        match self {
+            Backend::Soft => soft_salsa20_8(block),
+            #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+            // SAFETY: this variant is only constructed by `detect` after SSE2
+            // support has been confirmed at runtime.
+            Backend::Sse2 => unsafe { sse2::salsa20_8(block) },
+            #[cfg(all(feature = "simd", target_arch = "aarch64", target_feature = "neon"))]
+            // SAFETY: this variant is only compiled in for targets with NEON.
+            Backend::Neon => unsafe { neon::salsa20_8(block) },
+        }
+    }
+}
+
+/// Portable Salsa20/8 core.
+#[inline(always)]
+fn soft_salsa20_8(block: &mut [u32; 16]) {
+    soft_salsa20::<4>(block);
+}
+
+/// Portable Salsa20 core with `DOUBLE_ROUNDS` double rounds, in the standard
+/// word order: Salsa20/8 has 4 of them, and the Salsa20/2 used by yescrypt 1.
+#[inline(always)]
+pub(crate) fn soft_salsa20<const DOUBLE_ROUNDS: usize>(block: &mut [u32; 16]) {
+    #[inline(always)]
+    fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+        x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+        x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+        x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+        x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+    }
+
+    let mut x = *block;
+
+    for _ in 0..DOUBLE_ROUNDS {
+        // column round
+        quarter_round(&mut x, 0, 4, 8, 12);
+        quarter_round(&mut x, 5, 9, 13, 1);
+        quarter_round(&mut x, 10, 14, 2, 6);
+        quarter_round(&mut x, 15, 3, 7, 11);
+
+        // row round
+        quarter_round(&mut x, 0, 1, 2, 3);
+        quarter_round(&mut x, 5, 6, 7, 4);
+        quarter_round(&mut x, 10, 11, 8, 9);
+        quarter_round(&mut x, 15, 12, 13, 14);
+    }
+
+    for (b, x) in block.iter_mut().zip(x.iter()) {
+        *b = b.wrapping_add(*x);
+    }
+}
+
+#[cfg(all(test, feature = "simd"))]
+mod tests {
+    use super::Backend;
+
+    /// The backend detected for the CPU running the test, SSE2 or NEON,
+    /// matches the portable one.
+    #[test]
+    fn simd_matches_soft() {
+        let backend = Backend::detect();
+        if backend == Backend::Soft {
+            return;
+        }
+
+        // xorshift64 so the test is deterministic without pulling in `rand`
+        let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+
+        for _ in 0..10_000 {
+            let mut block = [0u32; 16];
+            for b in block.iter_mut() {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                *b = seed as u32;
+            }
+
+            let mut expected = block;
+            Backend::Soft.salsa20_8(&mut expected);
+
+            let mut actual = block;
+            backend.shuffle(&mut actual);
+            assert_eq!(actual[0], block[0], "word 0 must not move");
+            backend.salsa20_8(&mut actual);
+            backend.unshuffle(&mut actual);
+
+            assert_eq!(expected, actual);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! NEON implementation of the Salsa20/8 core.
+//!
+//! The 16 state words are held in four 128-bit registers along the diagonals
+//! of the 4x4 Salsa20 matrix, exactly as in the SSE2 implementation, so the
+//! blocks passed to [`salsa20_8`] are stored in the same
+//! [`DIAGONAL_LAYOUT`](super::DIAGONAL_LAYOUT). NEON has no lane-wise
+//! rotation, which is built from a shift and a shift-right-and-insert
+//! instead.
+//!
+//! NEON is part of the baseline of AArch64 targets, so this module is only
+//! built when the `neon` target feature is enabled at compile time, and
+//! needs no runtime detection.
+
+use core::arch::aarch64::*;
+
+/// Apply the Salsa20/8 core to `block` in place.
+///
+/// `block` must be in the word order described by
+/// [`DIAGONAL_LAYOUT`](super::DIAGONAL_LAYOUT); the output is written in the
+/// same order.
+///
+/// # Safety
+/// The caller must ensure the CPU supports NEON, which the `neon` target
+/// feature this module is built with guarantees.
+#[inline]
+pub(super) unsafe fn salsa20_8(block: &mut [u32; 16]) {
+    // `block` is 16 words, so the four 4-word loads and stores below stay
+    // in bounds.
+    let ptr = block.as_mut_ptr();
+
+    let y0 = vld1q_u32(ptr);
+    let y1 = vld1q_u32(ptr.add(4));
+    let y2 = vld1q_u32(ptr.add(8));
+    let y3 = vld1q_u32(ptr.add(12));
+
+    let (mut x0, mut x1, mut x2, mut x3) = (y0, y1, y2, y3);
+
+    for _ in 0..4 {
+        // column round
+        x1 = veorq_u32(x1, rotl::<7, 25>(vaddq_u32(x0, x3)));
+        x2 = veorq_u32(x2, rotl::<9, 23>(vaddq_u32(x1, x0)));
+        x3 = veorq_u32(x3, rotl::<13, 19>(vaddq_u32(x2, x1)));
+        x0 = veorq_u32(x0, rotl::<18, 14>(vaddq_u32(x3, x2)));
+
+        x1 = vextq_u32::<3>(x1, x1);
+        x2 = vextq_u32::<2>(x2, x2);
+        x3 = vextq_u32::<1>(x3, x3);
+
+        // row round
+        x3 = veorq_u32(x3, rotl::<7, 25>(vaddq_u32(x0, x1)));
+        x2 = veorq_u32(x2, rotl::<9, 23>(vaddq_u32(x3, x0)));
+        x1 = veorq_u32(x1, rotl::<13, 19>(vaddq_u32(x2, x3)));
+        x0 = veorq_u32(x0, rotl::<18, 14>(vaddq_u32(x1, x2)));
+
+        x1 = vextq_u32::<1>(x1, x1);
+        x2 = vextq_u32::<2>(x2, x2);
+        x3 = vextq_u32::<3>(x3, x3);
+    }
+
+    vst1q_u32(ptr, vaddq_u32(x0, y0));
+    vst1q_u32(ptr.add(4), vaddq_u32(x1, y1));
+    vst1q_u32(ptr.add(8), vaddq_u32(x2, y2));
+    vst1q_u32(ptr.add(12), vaddq_u32(x3, y3));
+}
+
+/// Rotate each 32-bit lane left by `L` bits (`R` must equal `32 - L`).
+#[inline(always)]
+unsafe fn rotl<const L: i32, const R: i32>(x: uint32x4_t) -> uint32x4_t {
+    vsriq_n_u32::<R>(vshlq_n_u32::<L>(x), x)
+}
@@ -0,0 +1,87 @@
+//! SSE2 implementation of the Salsa20/8 core.
+//!
+//! All of the `unsafe` code of the Salsa20/8 backends lives in this module
+//! and in `neon`, apart from the single call to [`salsa20_8`] in the parent
+//! module, which is only reached after SSE2 support has been detected at
+//! runtime. The only
+//! other `unsafe` code of the crate is in `vector`, which fills ROMix's `V`
+//! without initializing it first.
+//!
+//! The 16 state words are held in four 128-bit registers arranged along the
+//! diagonals of the 4x4 Salsa20 matrix, so that both the column round and the
+//! row round can be computed with lane-wise operations:
+//!
+//! ```text
+//! X0 = (x0,  x5,  x10, x15)
+//! X1 = (x4,  x9,  x14, x3)
+//! X2 = (x8,  x13, x2,  x7)
+//! X3 = (x12, x1,  x6,  x11)
+//! ```
+//!
+//! Between the half-rounds the lanes of `X1`..`X3` are rotated so that the
+//! row round operands line up, and rotated back afterwards.
+//!
+//! Blocks passed to [`salsa20_8`] must already be stored in this order (see
+//! [`DIAGONAL_LAYOUT`](super::DIAGONAL_LAYOUT)), which lets the registers be
+//! loaded and stored directly.
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+/// Apply the Salsa20/8 core to `block` in place.
+///
+/// `block` must be in the word order described by
+/// [`DIAGONAL_LAYOUT`](super::DIAGONAL_LAYOUT); the output is written in the
+/// same order.
+///
+/// # Safety
+/// The caller must ensure the CPU supports SSE2.
+#[target_feature(enable = "sse2")]
+pub(super) unsafe fn salsa20_8(block: &mut [u32; 16]) {
+    // `block` is 16 words, so the four unaligned 4-word loads and stores
+    // below stay in bounds.
+    let ptr = block.as_mut_ptr() as *mut __m128i;
+
+    let y0 = _mm_loadu_si128(ptr);
+    let y1 = _mm_loadu_si128(ptr.add(1));
+    let y2 = _mm_loadu_si128(ptr.add(2));
+    let y3 = _mm_loadu_si128(ptr.add(3));
+
+    let (mut x0, mut x1, mut x2, mut x3) = (y0, y1, y2, y3);
+
+    for _ in 0..4 {
+        // column round
+        x1 = _mm_xor_si128(x1, rotl::<7, 25>(_mm_add_epi32(x0, x3)));
+        x2 = _mm_xor_si128(x2, rotl::<9, 23>(_mm_add_epi32(x1, x0)));
+        x3 = _mm_xor_si128(x3, rotl::<13, 19>(_mm_add_epi32(x2, x1)));
+        x0 = _mm_xor_si128(x0, rotl::<18, 14>(_mm_add_epi32(x3, x2)));
+
+        x1 = _mm_shuffle_epi32::<0x93>(x1);
+        x2 = _mm_shuffle_epi32::<0x4e>(x2);
+        x3 = _mm_shuffle_epi32::<0x39>(x3);
+
+        // row round
+        x3 = _mm_xor_si128(x3, rotl::<7, 25>(_mm_add_epi32(x0, x1)));
+        x2 = _mm_xor_si128(x2, rotl::<9, 23>(_mm_add_epi32(x3, x0)));
+        x1 = _mm_xor_si128(x1, rotl::<13, 19>(_mm_add_epi32(x2, x3)));
+        x0 = _mm_xor_si128(x0, rotl::<18, 14>(_mm_add_epi32(x1, x2)));
+
+        x1 = _mm_shuffle_epi32::<0x39>(x1);
+        x2 = _mm_shuffle_epi32::<0x4e>(x2);
+        x3 = _mm_shuffle_epi32::<0x93>(x3);
+    }
+
+    _mm_storeu_si128(ptr, _mm_add_epi32(x0, y0));
+    _mm_storeu_si128(ptr.add(1), _mm_add_epi32(x1, y1));
+    _mm_storeu_si128(ptr.add(2), _mm_add_epi32(x2, y2));
+    _mm_storeu_si128(ptr.add(3), _mm_add_epi32(x3, y3));
+}
+
+/// Rotate each 32-bit lane left by `L` bits (`R` must equal `32 - L`).
+#[inline]
+#[target_feature(enable = "sse2")]
+unsafe fn rotl<const L: i32, const R: i32>(x: __m128i) -> __m128i {
+    _mm_or_si128(_mm_slli_epi32::<L>(x), _mm_srli_epi32::<R>(x))
+}
@@ -0,0 +1,104 @@
+use alloc::vec::Vec;
+use core::mem::MaybeUninit;
+
+use crate::{vector, Params};
+
+/// Working memory of a scrypt computation, which can be reused across
+/// computations to avoid reallocating it every time.
+///
+/// Buffers only ever grow, so reusing a [`Scratch`] for parameters needing
+/// less memory than a previous computation doesn't reallocate.
+///
+/// The buffers are wiped on drop, up to their capacity.
+#[derive(Default)]
+pub(crate) struct Scratch {
+    /// `B`: `p` lanes of `128 * r` bytes
+    pub(crate) b: Vec<u8>,
+
+    /// `V`: `N` blocks of `32 * r` words, which are never initialized before
+    /// ROMix writes them (see [`vector::Filler`])
+    pub(crate) v: Vec<MaybeUninit<u32>>,
+
+    /// `X` and `Y`: two blocks of `32 * r` words
+    pub(crate) xy: Vec<u32>,
+}
+
+impl Scratch {
+    /// Resize the buffers for a computation with `params`.
+    ///
+    /// Contents are left as they are, and `V` is grown without being
+    /// initialized: every buffer is written before being read by the
+    /// computation.
+    pub(crate) fn prepare(&mut self, params: &Params) {
+        // The checks in the ScryptParams constructor guarantee
+        // that the following is safe:
+        let pr128 = (params.p as usize) * (params.r as usize) * 128;
+
+        self.b.reserve_exact(pr128.saturating_sub(self.b.len()));
+        self.b.resize(pr128, 0);
+        self.prepare_lane(params);
+    }
+
+    /// Resize `v` and `xy` for a single lane of a computation with `params`,
+    /// leaving `b` as it is.
+    pub(crate) fn prepare_lane(&mut self, params: &Params) {
+        let n = 1 << params.log_n;
+        let r32 = (params.r as usize) * 32;
+
+        vector::resize_uninit(&mut self.v, n * r32);
+        self.xy
+            .reserve_exact((2 * r32).saturating_sub(self.xy.len()));
+        self.xy.resize(2 * r32, 0);
+    }
+
+    /// Bytes allocated by the buffers once prepared for `params`, which is
+    /// more than [`Params::mem_required`] if some of them grew larger for
+    /// previous computations.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn mem_prepared(&self, params: &Params) -> u64 {
+        let r128 = 128 * u64::from(params.r);
+        let words = |len: usize| 4 * len as u64;
+
+        (self.b.capacity() as u64).max(r128 * u64::from(params.p))
+            + words(self.v.capacity()).max(r128 << params.log_n)
+            + words(self.xy.capacity()).max(2 * r128)
+    }
+
+    /// Overwrite the buffers with zeroes, including their spare capacity.
+    pub(crate) fn wipe(&mut self) {
+        vector::wipe_vec(&mut self.b, 0);
+        vector::wipe_vec(&mut self.v, MaybeUninit::new(0));
+        vector::wipe_vec(&mut self.xy, 0);
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        self.wipe();
+    }
+}
+
+#[cfg(all(test, feature = "parallel"))]
+mod tests {
+    use super::Scratch;
+    use crate::Params;
+
+    #[test]
+    fn mem_prepared() {
+        let wide = Params::new(4, 1, 64, 32).unwrap();
+        let deep = Params::new(10, 8, 1, 32).unwrap();
+
+        let mut scratch = Scratch::default();
+        assert_eq!(scratch.mem_prepared(&wide), wide.mem_required());
+
+        scratch.prepare(&wide);
+        assert_eq!(scratch.mem_prepared(&wide), wide.mem_required());
+
+        // `b` stays as large as `wide` needs
+        scratch.prepare(&deep);
+        assert_eq!(
+            scratch.mem_prepared(&deep),
+            deep.mem_required() + 64 * 128 - 8 * 128
+        );
+    }
+}
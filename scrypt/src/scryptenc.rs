@@ -0,0 +1,173 @@
+//! Headers of the files written by Tarsnap's [`scrypt`] encryption utility
+//! (`scrypt enc`).
+//!
+//! Encrypted files start with a 96-byte header holding the scrypt parameters
+//! and salt used to derive their key:
+//!
+//! | Offset | Length | Content                                                 |
+//! |--------|--------|---------------------------------------------------------|
+//! | 0      | 6      | `"scrypt"`                                              |
+//! | 6      | 1      | Version, `0`                                            |
+//! | 7      | 1      | log₂ `N`                                                |
+//! | 8      | 4      | `r`, big-endian                                         |
+//! | 12     | 4      | `p`, big-endian                                         |
+//! | 16     | 32     | Salt                                                    |
+//! | 48     | 16     | First 16 bytes of the SHA-256 of bytes `0..48`          |
+//! | 64     | 32     | HMAC-SHA256 of bytes `0..64`, see [`Header::check_mac`] |
+//!
+//! The 64-byte key derived from the password ([`Header::derive_key`]) is
+//! split in two: the first half is the AES-256-CTR key of the data, and the
+//! second half the HMAC-SHA256 key of both the header and the whole file.
+//! Only the header is handled here; encrypting and decrypting the data is
+//! left to the caller.
+//!
+//! Like `scrypt enc`, the RFC 7914 requirement that `N < 2^(128 * r / 8)` is
+//! not enforced, but `N` must be between `2` and `2^32`.
+//!
+//! [`scrypt`]: https://www.tarsnap.com/scrypt.html
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    errors::{Error, InvalidHeader},
+    scrypt, MemoryLimit, Params,
+};
+
+const MAGIC: &[u8; 6] = b"scrypt";
+
+/// Length of derived keys: the AES-256 key followed by the HMAC-SHA256 key.
+const KEY_LEN: usize = 64;
+
+/// Header of a file encrypted with `scrypt enc`.
+#[derive(Clone, Copy, Debug)]
+pub struct Header {
+    params: Params,
+    salt: [u8; 32],
+}
+
+impl Header {
+    /// Length of an encoded header in bytes.
+    pub const LEN: usize = 96;
+
+    /// Create a new [`Header`] for the given parameters and salt.
+    ///
+    /// The key length of `params` is ignored, as keys are always 64 bytes.
+    /// Parameters which [`Header::parse`] would reject, e.g. `N = 1`, are
+    /// rejected with [`InvalidHeader::Params`].
+    pub fn new(params: &Params, salt: [u8; 32]) -> Result<Header, InvalidHeader> {
+        Ok(Header {
+            params: header_params(params.log_n, params.r, params.p)?,
+            salt,
+        })
+    }
+
+    /// Parse the header at the start of `bytes`.
+    ///
+    /// This checks the format of the header and its SHA-256 checksum, but not
+    /// its HMAC, which requires the key: see [`Header::check_mac`].
+    pub fn parse(bytes: &[u8]) -> Result<Header, InvalidHeader> {
+        let bytes = bytes.get(..Self::LEN).ok_or(InvalidHeader::Length)?;
+
+        if &bytes[..6] != MAGIC {
+            return Err(InvalidHeader::Magic);
+        }
+
+        if bytes[6] != 0 {
+            return Err(InvalidHeader::Version(bytes[6]));
+        }
+
+        if Sha256::digest(&bytes[..48])[..16] != bytes[48..64] {
+            return Err(InvalidHeader::Checksum);
+        }
+
+        let r = u32::from_be_bytes(bytes[8..12].try_into().unwrap());
+        let p = u32::from_be_bytes(bytes[12..16].try_into().unwrap());
+
+        Ok(Header {
+            params: header_params(bytes[7], r, p)?,
+            salt: bytes[16..48].try_into().unwrap(),
+        })
+    }
+
+    /// The scrypt parameters of the key, with a key length of 64 bytes.
+    pub fn params(&self) -> Params {
+        self.params
+    }
+
+    /// The salt of the key.
+    pub fn salt(&self) -> &[u8; 32] {
+        &self.salt
+    }
+
+    /// Derive the 64-byte key of the file from `password`, if the parameters
+    /// of the header require at most [`MemoryLimit::DEFAULT`].
+    ///
+    /// See [`Header::derive_key_with_memory_limit`] otherwise.
+    pub fn derive_key(&self, password: &[u8]) -> Result<[u8; 64], Error> {
+        self.derive_key_with_memory_limit(password, MemoryLimit::DEFAULT)
+    }
+
+    /// Same as [`Header::derive_key`], but rejects the parameters requiring
+    /// more memory than `limit` with `Err(Error::MemoryLimitExceeded)`
+    /// before allocating it.
+    pub fn derive_key_with_memory_limit(
+        &self,
+        password: &[u8],
+        limit: MemoryLimit,
+    ) -> Result<[u8; 64], Error> {
+        limit.check(&self.params)?;
+
+        let mut key = [0u8; KEY_LEN];
+        scrypt(password, &self.salt, &self.params, &mut key).expect("key length is valid");
+        Ok(key)
+    }
+
+    /// Check the HMAC of the header at the start of `bytes` with `key`,
+    /// as returned by [`Header::derive_key`].
+    ///
+    /// The comparison is constant-time. A mismatch means that the password
+    /// the key was derived from is wrong, or that the header was modified.
+    pub fn check_mac(bytes: &[u8], key: &[u8; 64]) -> Result<(), InvalidHeader> {
+        let bytes = bytes.get(..Self::LEN).ok_or(InvalidHeader::Length)?;
+
+        let mut mac = hmac_sha256(key);
+        mac.update(&bytes[..64]);
+        mac.verify_slice(&bytes[64..])
+            .map_err(|_| InvalidHeader::Mac)
+    }
+
+    /// Encode the header, authenticated with `key` as returned by
+    /// [`Header::derive_key`].
+    pub fn encode(&self, key: &[u8; 64]) -> [u8; 96] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..6].copy_from_slice(MAGIC);
+        bytes[7] = self.params.log_n;
+        bytes[8..12].copy_from_slice(&self.params.r.to_be_bytes());
+        bytes[12..16].copy_from_slice(&self.params.p.to_be_bytes());
+        bytes[16..48].copy_from_slice(&self.salt);
+
+        let checksum = Sha256::digest(&bytes[..48]);
+        bytes[48..64].copy_from_slice(&checksum[..16]);
+
+        let mut mac = hmac_sha256(key);
+        mac.update(&bytes[..64]);
+        bytes[64..].copy_from_slice(&mac.finalize().into_bytes());
+
+        bytes
+    }
+}
+
+/// Parameters as accepted by `scrypt enc`, with 64-byte keys.
+fn header_params(log_n: u8, r: u32, p: u32) -> Result<Params, InvalidHeader> {
+    if log_n == 0 {
+        return Err(InvalidHeader::Params);
+    }
+
+    Params::new_inner(log_n, r, p, KEY_LEN, false).map_err(|_| InvalidHeader::Params)
+}
+
+/// HMAC-SHA256 keyed with the second half of `key`.
+fn hmac_sha256(key: &[u8; 64]) -> Hmac<Sha256> {
+    Hmac::<Sha256>::new_from_slice(&key[32..]).expect("HMAC accepts keys of any length")
+}
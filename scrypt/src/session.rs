@@ -0,0 +1,220 @@
+//! Resumable scrypt computations.
+//!
+//! A [`ScryptSession`] performs the same computation as [`scrypt`](crate::scrypt),
+//! but the caller decides how much of it runs at a time: [`ScryptSession::run_for`]
+//! advances it by a given number of BlockMix operations and returns, so the
+//! work can be interleaved with other tasks on a single thread (e.g. a browser
+//! event loop).
+//!
+//! ```
+//! use scrypt::{session::{ScryptSession, Status}, Params};
+//!
+//! let params = Params::new(10, 8, 1, 32).unwrap();
+//! let mut session = ScryptSession::new(b"password", b"salt", &params);
+//! while let Status::Pending(progress) = session.run_for(100) {
+//!     // Yield to the event loop, report `progress`...
+//!     assert!(progress.completed < progress.total);
+//! }
+//!
+//! let mut output = [0u8; 32];
+//! session.finish(&mut output).unwrap();
+//!
+//! let mut expected = [0u8; 32];
+//! scrypt::scrypt(b"password", b"salt", &params, &mut expected).unwrap();
+//! assert_eq!(output, expected);
+//! ```
+
+use alloc::{vec, vec::Vec};
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha256;
+
+use crate::{errors::InvalidOutputLen, romix, salsa::Backend, vector, Params, Progress};
+
+/// State of a [`ScryptSession`] after [`ScryptSession::run_for`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Status {
+    /// Some BlockMix operations remain to be performed.
+    Pending(Progress),
+
+    /// All BlockMix operations have been performed: the output is available
+    /// from [`ScryptSession::finish`].
+    Done,
+}
+
+/// A scrypt computation which runs in caller-controlled slices.
+///
+/// The session owns all of its working memory (`128 * r * (N + p + 2)`
+/// bytes, allocated by [`ScryptSession::new`]) and a copy of the password,
+/// needed by the final PBKDF2 step. They are overwritten with zeroes on
+/// drop, including when the session is dropped before completing.
+pub struct ScryptSession {
+    password: Vec<u8>,
+    backend: Backend,
+    n: usize,
+
+    /// `B`: `p` lanes of `128 * r` bytes
+    b: Vec<u8>,
+
+    /// `V`: `N` blocks of `32 * r` words
+    v: Vec<u32>,
+
+    /// `X` and `Y`: two blocks of `32 * r` words
+    xy: Vec<u32>,
+
+    /// Whether `X` is currently the second half of `xy`
+    x_swapped: bool,
+
+    /// Lane of `B` being mixed
+    lane: usize,
+
+    /// Number of BlockMix operations performed on `lane`, up to `2 * N`
+    step: usize,
+
+    progress: Progress,
+}
+
+impl ScryptSession {
+    /// Start a computation of scrypt, running the initial PBKDF2 step.
+    ///
+    /// No BlockMix operation is performed until [`ScryptSession::run_for`]
+    /// or [`ScryptSession::finish`] is called.
+    pub fn new(password: &[u8], salt: &[u8], params: &Params) -> ScryptSession {
+        // The checks in the Params constructor guarantee that these don't
+        // overflow.
+        let n = 1 << params.log_n;
+        let r32 = (params.r as usize) * 32;
+        let p = params.p as usize;
+
+        let mut b = vec![0u8; p * r32 * 4];
+        pbkdf2_hmac::<Sha256>(password, salt, 1, &mut b);
+
+        ScryptSession {
+            password: password.to_vec(),
+            backend: Backend::detect(),
+            n,
+            b,
+            v: vec![0u32; n * r32],
+            xy: vec![0u32; 2 * r32],
+            x_swapped: false,
+            lane: 0,
+            step: 0,
+            progress: Progress {
+                completed: 0,
+                total: (n as u64)
+                    .saturating_mul(2)
+                    .saturating_mul(u64::from(params.p)),
+            },
+        }
+    }
+
+    /// Perform up to `blocks` more BlockMix operations.
+    ///
+    /// Returns [`Status::Done`] once the computation has performed all of
+    /// them (see [`Progress`]), after which calling this again has no effect.
+    pub fn run_for(&mut self, blocks: u64) -> Status {
+        let remaining = self.progress.total - self.progress.completed;
+        for _ in 0..blocks.min(remaining) {
+            self.block_mix();
+        }
+
+        self.status()
+    }
+
+    /// Current state of the computation, without advancing it.
+    pub fn status(&self) -> Status {
+        if self.progress.completed == self.progress.total {
+            Status::Done
+        } else {
+            Status::Pending(self.progress)
+        }
+    }
+
+    /// Complete the computation, writing the derived key to `output`.
+    ///
+    /// Any remaining BlockMix operations are performed first, without
+    /// returning in between.
+    ///
+    /// # Return
+    /// `Err(InvalidOutputLen)` if `output` does not satisfy the conditions
+    /// documented on [`scrypt`](crate::scrypt), which is checked before
+    /// performing any remaining work.
+    pub fn finish(mut self, output: &mut [u8]) -> Result<(), InvalidOutputLen> {
+        crate::check_output_len(output.len())?;
+
+        self.run_for(u64::MAX);
+        pbkdf2_hmac::<Sha256>(&self.password, &self.b, 1, output);
+
+        Ok(())
+    }
+
+    /// Perform the next BlockMix operation of ROMix on the current lane.
+    ///
+    /// This is [`romix::scrypt_ro_mix`] unrolled into single steps.
+    fn block_mix(&mut self) {
+        let n = self.n;
+        let len = self.xy.len() / 2;
+        let backend = self.backend;
+        let lane = &mut self.b[self.lane * len * 4..(self.lane + 1) * len * 4];
+
+        if self.step == 0 {
+            // X = B_i, V_0 = X
+            let x = &mut self.xy[..len];
+            for (w, c) in x.iter_mut().zip(lane.chunks_exact(4)) {
+                *w = u32::from_le_bytes(c.try_into().unwrap());
+            }
+            backend.shuffle(x);
+            self.v[..len].copy_from_slice(x);
+            self.x_swapped = false;
+        }
+
+        let step = self.step;
+        if step + 1 < n {
+            // V_i = BlockMix(V_{i-1})
+            let (prev, next) = self.v.split_at_mut((step + 1) * len);
+            romix::scrypt_block_mix(backend, &prev[step * len..], None, &mut next[..len]);
+        } else if step + 1 == n {
+            // X = BlockMix(V_{N-1})
+            let x = &mut self.xy[..len];
+            romix::scrypt_block_mix(backend, &self.v[step * len..], None, x);
+        } else {
+            // X = BlockMix(X ^ V_j)
+            let (first, second) = self.xy.split_at_mut(len);
+            let (x, y) = if self.x_swapped {
+                (second, first)
+            } else {
+                (first, second)
+            };
+            let j = romix::integerify(x, n);
+            romix::scrypt_block_mix(backend, x, Some(&self.v[j * len..(j + 1) * len]), y);
+            self.x_swapped = !self.x_swapped;
+        }
+
+        self.step += 1;
+        self.progress.completed += 1;
+
+        if self.step == 2 * n {
+            // B_i = X
+            let x = if self.x_swapped {
+                &mut self.xy[len..]
+            } else {
+                &mut self.xy[..len]
+            };
+            backend.unshuffle(x);
+            for (c, w) in lane.chunks_exact_mut(4).zip(x.iter()) {
+                c.copy_from_slice(&w.to_le_bytes());
+            }
+
+            self.lane += 1;
+            self.step = 0;
+        }
+    }
+}
+
+impl Drop for ScryptSession {
+    fn drop(&mut self) {
+        vector::wipe_vec(&mut self.password, 0);
+        vector::wipe_vec(&mut self.b, 0);
+        vector::wipe_vec(&mut self.v, 0);
+        vector::wipe_vec(&mut self.xy, 0);
+    }
+}
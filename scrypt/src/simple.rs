@@ -1,8 +1,16 @@
 //! Implementation of the `password-hash` crate API.
 
-use crate::{scrypt, Params};
+use crate::{errors, scrypt_with_scratch, MemoryLimit, Params, ParamsPolicy, Scratch};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::cmp::Ordering;
 use password_hash::{Decimal, Error, Ident, Output, PasswordHash, PasswordHasher, Result, Salt};
+use subtle::ConstantTimeEq;
+
+#[cfg(doc)]
+use password_hash::PasswordVerifier;
 
 /// Algorithm identifier
 pub const ALG_ID: Ident = Ident::new_unwrap("scrypt");
@@ -14,32 +22,205 @@ pub const ALG_ID: Ident = Ident::new_unwrap("scrypt");
 #[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
 pub struct Scrypt;
 
-impl PasswordHasher for Scrypt {
-    type Params = Params;
+impl Scrypt {
+    /// Verify `password` against a PHC string, refusing to start the
+    /// computation if its parameters need more memory than `limit`.
+    ///
+    /// [`ParamsPolicy::DEFAULT`] is enforced as well.
+    ///
+    /// [`PasswordVerifier::verify_password`] behaves like this method with
+    /// [`MemoryLimit::DEFAULT`], except that it reports an exceeded limit as
+    /// [`InvalidValue::TooLong`](password_hash::errors::InvalidValue::TooLong)
+    /// rather than [`errors::Error::MemoryLimitExceeded`].
+    pub fn verify_password_with_memory_limit(
+        &self,
+        password: &[u8],
+        hash: &PasswordHash<'_>,
+        limit: MemoryLimit,
+    ) -> core::result::Result<(), errors::Error> {
+        self.verify_password_with_scratch(
+            password,
+            hash,
+            ParamsPolicy::DEFAULT,
+            limit,
+            &mut Scratch::default(),
+        )
+    }
 
-    fn hash_password_customized<'a>(
+    /// Verify `password` against a PHC string, comparing the computed output
+    /// with the one of `hash` in constant time with
+    /// [`ConstantTimeEq`].
+    ///
+    /// This behaves like [`PasswordVerifier::verify_password`], which
+    /// compares them in constant time too, through the [`PartialEq`] impl of
+    /// [`Output`]: this method doesn't rely on that impl, and neither do the
+    /// other verification methods of [`Scrypt`].
+    pub fn verify_password_timing_safe(
+        &self,
+        password: &[u8],
+        hash: &PasswordHash<'_>,
+    ) -> Result<()> {
+        self.verify_password_with_scratch(
+            password,
+            hash,
+            ParamsPolicy::DEFAULT,
+            MemoryLimit::DEFAULT,
+            &mut Scratch::default(),
+        )
+        .map_err(Into::into)
+    }
+
+    /// Verify `password` against a PHC string, refusing to start the
+    /// computation if its parameters exceed `policy`.
+    ///
+    /// [`MemoryLimit::DEFAULT`] is enforced as well.
+    ///
+    /// [`PasswordVerifier::verify_password`] behaves like this method with
+    /// [`ParamsPolicy::DEFAULT`], except that it reports a policy violation
+    /// as [`InvalidValue::TooLong`](password_hash::errors::InvalidValue::TooLong)
+    /// rather than [`errors::Error::ParamsPolicyViolation`].
+    pub fn verify_password_with_policy(
+        &self,
+        password: &[u8],
+        hash: &PasswordHash<'_>,
+        policy: ParamsPolicy,
+    ) -> core::result::Result<(), errors::Error> {
+        self.verify_password_with_scratch(
+            password,
+            hash,
+            policy,
+            MemoryLimit::DEFAULT,
+            &mut Scratch::default(),
+        )
+    }
+
+    /// Hash `password` with `params` and `salt`, refusing to start the
+    /// computation if the parameters need more memory than `limit`.
+    ///
+    /// [`PasswordHasher::hash_password_customized`] enforces
+    /// [`ParamsPolicy::DEFAULT`] and [`MemoryLimit::DEFAULT`], since
+    /// [`PasswordVerifier::verify_password`] is implemented on top of it and
+    /// has to guard against untrusted stored hashes. This method is meant
+    /// for parameters chosen by the caller, so no [`ParamsPolicy`] applies;
+    /// use [`MemoryLimit::UNLIMITED`] to hash without any check.
+    pub fn hash_password_with_memory_limit<'a>(
+        &self,
+        password: &[u8],
+        params: Params,
+        salt: impl Into<Salt<'a>>,
+        limit: MemoryLimit,
+    ) -> core::result::Result<PasswordHash<'a>, errors::Error> {
+        self.hash_password_with_scratch(
+            password,
+            None,
+            None,
+            params,
+            salt.into(),
+            ParamsPolicy::UNLIMITED,
+            limit,
+            &mut Scratch::default(),
+        )
+    }
+
+    /// Verify `password` against an unparsed PHC string, also accepting the
+    /// strings written by Python's [passlib].
+    ///
+    /// passlib encodes the salt and hash of its `$scrypt$` strings with an
+    /// adapted Base64 alphabet using `.` instead of `+`, which
+    /// [`PasswordHash::new`] rejects. Both alphabets are accepted here; the
+    /// strings produced by this crate always use the standard one.
+    ///
+    /// Apart from parsing, this behaves like
+    /// [`Scrypt::verify_password_with_memory_limit`] with
+    /// [`MemoryLimit::DEFAULT`].
+    ///
+    /// [passlib]: https://passlib.readthedocs.io/en/stable/lib/passlib.hash.scrypt.html
+    pub fn verify_password_str(
+        &self,
+        password: &[u8],
+        hash: &str,
+    ) -> core::result::Result<(), errors::Error> {
+        let hash = match hash.rsplitn(3, '$').collect::<Vec<_>>()[..] {
+            [output, salt, rest] if hash.starts_with("$scrypt$") => {
+                let mut translated = String::with_capacity(hash.len());
+                translated.push_str(rest);
+
+                for segment in [salt, output] {
+                    translated.push('$');
+                    translated.extend(segment.chars().map(|c| if c == '.' { '+' } else { c }));
+                }
+
+                translated
+            }
+            _ => hash.to_string(),
+        };
+
+        let hash = PasswordHash::new(&hash)?;
+        self.verify_password_with_memory_limit(password, &hash, MemoryLimit::DEFAULT)
+    }
+
+    /// Verify `password` against a PHC string within `policy` and `limit`,
+    /// reusing the working memory in `scratch`.
+    pub(crate) fn verify_password_with_scratch(
+        &self,
+        password: &[u8],
+        hash: &PasswordHash<'_>,
+        policy: ParamsPolicy,
+        limit: MemoryLimit,
+        scratch: &mut Scratch,
+    ) -> core::result::Result<(), errors::Error> {
+        if let (Some(salt), Some(expected_output)) = (&hash.salt, &hash.hash) {
+            let computed_hash = self.hash_password_with_scratch(
+                password,
+                Some(hash.algorithm),
+                hash.version,
+                Params::try_from(hash)?,
+                *salt,
+                policy,
+                limit,
+                scratch,
+            )?;
+
+            if let Some(computed_output) = &computed_hash.hash {
+                if bool::from(computed_output.ct_eq(expected_output)) {
+                    return Ok(());
+                }
+            }
+        }
+
+        Err(Error::Password.into())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn hash_password_with_scratch<'a>(
         &self,
         password: &[u8],
         alg_id: Option<Ident<'a>>,
         version: Option<Decimal>,
         params: Params,
-        salt: impl Into<Salt<'a>>,
-    ) -> Result<PasswordHash<'a>> {
+        salt: Salt<'a>,
+        policy: ParamsPolicy,
+        limit: MemoryLimit,
+        scratch: &mut Scratch,
+    ) -> core::result::Result<PasswordHash<'a>, errors::Error> {
         if !matches!(alg_id, Some(ALG_ID) | None) {
-            return Err(Error::Algorithm);
+            return Err(Error::Algorithm.into());
         }
 
         // Versions unsupported
         if version.is_some() {
-            return Err(Error::Version);
+            return Err(Error::Version.into());
         }
 
-        let salt = salt.into();
+        // Must happen before anything is allocated
+        limit.check(&params)?;
+        policy.check(&params)?;
+
         let mut salt_arr = [0u8; 64];
         let salt_bytes = salt.decode_b64(&mut salt_arr)?;
 
         let output = Output::init_with(params.len, |out| {
-            scrypt(password, salt_bytes, &params, out).map_err(|_| {
+            scrypt_with_scratch(password, salt_bytes, &params, out, scratch).map_err(|_| {
                 let provided = if out.is_empty() {
                     Ordering::Less
                 } else {
@@ -62,3 +243,34 @@ impl PasswordHasher for Scrypt {
         })
     }
 }
+
+impl PasswordHasher for Scrypt {
+    type Params = Params;
+
+    /// Parameters exceeding [`ParamsPolicy::DEFAULT`] or needing more memory
+    /// than [`MemoryLimit::DEFAULT`] are rejected with
+    /// [`InvalidValue::TooLong`](password_hash::errors::InvalidValue::TooLong),
+    /// as [`PasswordVerifier::verify_password`] goes through this method.
+    /// [`Scrypt::hash_password_with_memory_limit`] hashes the caller's own
+    /// parameters without a policy and with a chosen memory limit.
+    fn hash_password_customized<'a>(
+        &self,
+        password: &[u8],
+        alg_id: Option<Ident<'a>>,
+        version: Option<Decimal>,
+        params: Params,
+        salt: impl Into<Salt<'a>>,
+    ) -> Result<PasswordHash<'a>> {
+        self.hash_password_with_scratch(
+            password,
+            alg_id,
+            version,
+            params,
+            salt.into(),
+            ParamsPolicy::DEFAULT,
+            MemoryLimit::DEFAULT,
+            &mut Scratch::default(),
+        )
+        .map_err(Into::into)
+    }
+}
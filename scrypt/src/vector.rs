@@ -0,0 +1,226 @@
+//! Filling the vector `V` of ROMix without initializing it first.
+//!
+//! `V` takes `128 * r * N` bytes (1 GiB for [`Params::SENSITIVE`]), and the
+//! first ROMix loop writes every one of its blocks before the second loop
+//! reads any of them. Zeroing it when allocating would be a wasted pass over
+//! all of that memory, so [`Scratch`](crate::Scratch) allocates it
+//! uninitialized and [`Filler`] keeps track of the blocks written so far.
+//! Once a computation is over, [`wipe`] overwrites the working memory with
+//! writes the compiler can't elide.
+//!
+//! Apart from the SIMD Salsa20/8 backends (`salsa::sse2`, `salsa::neon` and
+//! their single call sites in `salsa`), this is the only module containing
+//! `unsafe` code, and
+//! none of its functions let safe code observe uninitialized memory: words
+//! of `V` can only be read as `u32` once [`Filler`] has written them.
+//!
+//! [`Params::SENSITIVE`]: crate::Params::SENSITIVE
+
+use core::{mem::MaybeUninit, ops::ControlFlow, ptr, sync::atomic};
+
+use crate::{romix, salsa::Backend};
+
+/// `V` being filled block by block, in order.
+pub(crate) struct Filler<'a> {
+    /// Invariant: `v[..filled]` is initialized.
+    v: &'a mut [MaybeUninit<u32>],
+    filled: usize,
+}
+
+impl<'a> Filler<'a> {
+    /// Start filling `v` from the beginning.
+    pub(crate) fn new(v: &'a mut [MaybeUninit<u32>]) -> Self {
+        Filler { v, filled: 0 }
+    }
+
+    /// Append a copy of `block`.
+    ///
+    /// # Panics
+    /// If `block` doesn't fit in the rest of `v`.
+    pub(crate) fn push(&mut self, block: &[u32]) {
+        let next = &mut self.v[self.filled..self.filled + block.len()];
+        for (word, &value) in next.iter_mut().zip(block.iter()) {
+            *word = MaybeUninit::new(value);
+        }
+
+        // Every word of `next` was written, as it's as long as `block`
+        self.filled += block.len();
+    }
+
+    /// Append the BlockMix of the last `len` words.
+    ///
+    /// # Panics
+    /// If fewer than `len` words were filled, or `len` more don't fit in
+    /// `v`.
+    pub(crate) fn push_block_mix(&mut self, backend: Backend, len: usize) {
+        assert!(len <= self.filled && len <= self.v.len() - self.filled);
+
+        let (init, rest) = self.v.split_at_mut(self.filled);
+        // SAFETY: `init` is `v[..filled]`, which is initialized.
+        let prev = unsafe { assume_init(&init[self.filled - len..]) };
+        romix::scrypt_block_mix(backend, prev, None, &mut rest[..len]);
+
+        // BlockMix writes every word of its output, which is as long as
+        // `prev`
+        self.filled += len;
+    }
+
+    /// Finish filling `v`, returning it initialized.
+    ///
+    /// # Panics
+    /// If `v` isn't completely filled.
+    pub(crate) fn finish(self) -> &'a [u32] {
+        assert_eq!(self.filled, self.v.len(), "V isn't filled");
+
+        let v: &'a [MaybeUninit<u32>] = self.v;
+        // SAFETY: `v[..filled]` is initialized, and `filled == v.len()`.
+        unsafe { assume_init(v) }
+    }
+}
+
+/// Run ROMix with an initialized `V`, e.g. one provided by the caller of
+/// [`primitives::ro_mix`](crate::primitives::ro_mix).
+pub(crate) fn scrypt_ro_mix_init(
+    b: &mut [u8],
+    v: &mut [u32],
+    xy: &mut [u32],
+    n: usize,
+    tick: impl FnMut() -> ControlFlow<()>,
+) -> ControlFlow<()> {
+    // SAFETY: ROMix only writes to `V` through a `Filler`, which only writes
+    // initialized words.
+    romix::scrypt_ro_mix(b, unsafe { as_uninit(v) }, xy, n, tick)
+}
+
+/// View `v` as possibly uninitialized words, to fill it with a [`Filler`].
+///
+/// # Safety
+/// The caller must only write initialized values to the returned slice, as
+/// [`Filler`] does, so that `v` stays initialized.
+unsafe fn as_uninit(v: &mut [u32]) -> &mut [MaybeUninit<u32>] {
+    // `MaybeUninit<u32>` has the same layout as `u32`.
+    &mut *(v as *mut [u32] as *mut [MaybeUninit<u32>])
+}
+
+/// # Safety
+/// Every word of `v` must be initialized.
+unsafe fn assume_init(v: &[MaybeUninit<u32>]) -> &[u32] {
+    // `MaybeUninit<u32>` has the same layout as `u32`.
+    &*(v as *const [MaybeUninit<u32>] as *const [u32])
+}
+
+/// Resize `v` to `len` words, leaving any new words uninitialized.
+pub(crate) fn resize_uninit(v: &mut alloc::vec::Vec<MaybeUninit<u32>>, len: usize) {
+    v.reserve_exact(len.saturating_sub(v.len()));
+
+    // SAFETY: the capacity of `v` is at least `len`, and `MaybeUninit` words
+    // don't need to be initialized.
+    unsafe { v.set_len(len) }
+}
+
+/// Overwrite every element of `buf` with `zero`, even though `buf` may never
+/// be read again.
+pub(crate) fn wipe<T: Copy>(buf: &mut [T], zero: T) {
+    for x in buf.iter_mut() {
+        // SAFETY: `x` is a valid, aligned and exclusive reference.
+        unsafe { ptr::write_volatile(x, zero) };
+    }
+
+    // Keep the writes from being reordered after memory is freed
+    atomic::compiler_fence(atomic::Ordering::SeqCst);
+}
+
+/// [`wipe`] every element of `buf`, and its spare capacity as well: buffers
+/// shrunk for a smaller computation still hold the end of the previous one.
+pub(crate) fn wipe_vec<T: Copy>(buf: &mut alloc::vec::Vec<T>, zero: T) {
+    wipe(buf, zero);
+    wipe(buf.spare_capacity_mut(), MaybeUninit::new(zero));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{vec, vec::Vec};
+
+    /// Meant to be run under Miri as well, which reports any read of
+    /// uninitialized memory. Covers both `r = 1`, which ROMix handles
+    /// separately, and `r = 2`.
+    #[test]
+    fn ro_mix_uninit_matches_init() {
+        let n = 8;
+
+        for len in [32, 64] {
+            let mut expected = vec![0x5au8; 4 * len];
+            let mut v = vec![0u32; n * len];
+            let mut xy = vec![0u32; 2 * len];
+            let flow = scrypt_ro_mix_init(&mut expected, &mut v, &mut xy, n, || {
+                ControlFlow::Continue(())
+            });
+            assert!(flow.is_continue());
+
+            // Grow an uninitialized `V` twice, as a reused scratch buffer would
+            let mut v = Vec::new();
+            for n in [n / 2, n] {
+                let mut b = vec![0x5au8; 4 * len];
+                resize_uninit(&mut v, n * len);
+                assert_eq!(v.len(), n * len);
+
+                let flow =
+                    romix::scrypt_ro_mix(&mut b, &mut v, &mut xy, n, || ControlFlow::Continue(()));
+                assert!(flow.is_continue());
+
+                if n == 8 {
+                    assert_eq!(b, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wipe_zeroes() {
+        let mut v = vec![0x5au32; 64];
+        wipe(&mut v, 0);
+        assert_eq!(v, [0; 64]);
+    }
+
+    #[test]
+    fn wipe_vec_spare_capacity() {
+        let mut v = vec![0x5au32; 64];
+        v.truncate(16);
+        wipe_vec(&mut v, 0);
+        assert_eq!(v, [0; 16]);
+
+        // SAFETY: the capacity of `v` is 64, and every word up to it was
+        // initialized by `vec!`.
+        unsafe { v.set_len(64) };
+        assert_eq!(v, [0; 64]);
+    }
+
+    #[test]
+    fn filler() {
+        let mut v = Vec::new();
+        resize_uninit(&mut v, 64);
+
+        let mut filler = Filler::new(&mut v);
+        filler.push(&[1; 32]);
+        filler.push_block_mix(Backend::Soft, 32);
+
+        let mut expected = [0u32; 32];
+        romix::scrypt_block_mix(Backend::Soft, &[1; 32], None, &mut expected);
+
+        let v = filler.finish();
+        assert_eq!(v[..32], [1; 32]);
+        assert_eq!(v[32..], expected);
+    }
+
+    #[test]
+    #[should_panic = "V isn't filled"]
+    fn filler_unfinished() {
+        let mut v = Vec::new();
+        resize_uninit(&mut v, 64);
+
+        let mut filler = Filler::new(&mut v);
+        filler.push(&[1; 32]);
+        filler.finish();
+    }
+}
@@ -0,0 +1,132 @@
+//! Interoperability with the scrypt KDF of [Web3 Secret Storage] (Ethereum V3
+//! keystore) files.
+//!
+//! Keystores describe the KDF in their `crypto.kdfparams` object:
+//!
+//! ```json
+//! {
+//!     "dklen": 32,
+//!     "n": 262144,
+//!     "p": 8,
+//!     "r": 1,
+//!     "salt": "ab0c7876052600dd703518d6fc3fe8984592145b591fc8fb5c6d43190334ba19"
+//! }
+//! ```
+//!
+//! [`KdfParams`] can be deserialized from that object, and [`derive_key`]
+//! produces the `dklen`-byte key used to check the keystore MAC and decrypt
+//! its ciphertext.
+//!
+//! Note that Web3 keystores commonly use parameters such as `n = 262144,
+//! r = 1` which are larger than RFC 7914 permits (`N < 2^(128 * r / 8)`), so
+//! that requirement is not enforced here. `n` must still be a power of two no
+//! larger than `2^32`.
+//!
+//! [Web3 Secret Storage]: https://ethereum.org/en/developers/docs/data-structures-and-encoding/web3-secret-storage/
+
+use alloc::{string::String, vec, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::{Error, InvalidParams},
+    scrypt, MemoryLimit, Params,
+};
+
+/// scrypt parameters of a Web3 keystore's `crypto.kdfparams` object.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(docsrs, doc(cfg(feature = "web3")))]
+pub struct KdfParams {
+    /// Length of the derived key in bytes.
+    pub dklen: usize,
+
+    /// The Scrypt parameter `N` (not its log₂).
+    pub n: u64,
+
+    /// The Scrypt parameter `p`.
+    pub p: u32,
+
+    /// The Scrypt parameter `r`.
+    pub r: u32,
+
+    /// Salt, serialized as a hex string.
+    #[serde(with = "hex")]
+    pub salt: Vec<u8>,
+}
+
+impl KdfParams {
+    /// Create [`KdfParams`] from scrypt [`Params`] and a salt.
+    ///
+    /// `dklen` is taken from the key length of `params`.
+    pub fn new(params: &Params, salt: impl Into<Vec<u8>>) -> KdfParams {
+        KdfParams {
+            dklen: params.len,
+            n: 1 << params.log_n,
+            p: params.p,
+            r: params.r,
+            salt: salt.into(),
+        }
+    }
+}
+
+impl TryFrom<&KdfParams> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(kdf: &KdfParams) -> Result<Params, InvalidParams> {
+        if !kdf.n.is_power_of_two() {
+            return Err(InvalidParams::new("n", "a power of two"));
+        }
+
+        let log_n = kdf.n.trailing_zeros() as u8;
+        Params::new_inner(log_n, kdf.r, kdf.p, kdf.dklen, false).map_err(|err| {
+            if err.param() == "len" {
+                InvalidParams::new("dklen", err.expected())
+            } else {
+                err
+            }
+        })
+    }
+}
+
+/// Derive the `dklen`-byte key of a Web3 keystore from `password`, if the
+/// parameters require at most [`MemoryLimit::DEFAULT`].
+///
+/// Invalid parameters are rejected with `Err(Error::InvalidParams)`, see
+/// [`derive_key_with_memory_limit`] for other limits.
+///
+/// **WARNING: Make sure to compare any value computed from this key in
+/// constant time!**
+pub fn derive_key(password: &[u8], kdf: &KdfParams) -> Result<Vec<u8>, Error> {
+    derive_key_with_memory_limit(password, kdf, MemoryLimit::DEFAULT)
+}
+
+/// Same as [`derive_key`], but rejects the parameters requiring more memory
+/// than `limit` with `Err(Error::MemoryLimitExceeded)` before allocating it.
+pub fn derive_key_with_memory_limit(
+    password: &[u8],
+    kdf: &KdfParams,
+    limit: MemoryLimit,
+) -> Result<Vec<u8>, Error> {
+    let params = Params::try_from(kdf)?;
+    limit.check(&params)?;
+
+    let mut key = vec![0u8; kdf.dklen];
+    scrypt(password, &kdf.salt, &params, &mut key).expect("dklen is valid");
+    Ok(key)
+}
+
+/// Serialize bytes as a lowercase hex string.
+mod hex {
+    use super::{String, Vec};
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base16ct::lower::encode_string(bytes))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        base16ct::mixed::decode_vec(s).map_err(|_| de::Error::custom("invalid hex string"))
+    }
+}
@@ -0,0 +1,660 @@
+//! Computation and verification of yescrypt `$y$` hashes, the default
+//! `crypt(3)` hashes of libxcrypt (and so of most current Linux
+//! distributions).
+//!
+//! yescrypt is a superset of scrypt. This module implements the modes which
+//! libxcrypt accepts, as selected by the flags of a hash (see [`Mode`]):
+//! classic scrypt, write-once/read-many, and the default read-write flavor.
+//! Hashes using other flavors, hash upgrades or a ROM are rejected with
+//! [`YescryptError::UnsupportedFlags`] or [`YescryptError::Unsupported`].
+//!
+//! ```
+//! let hash = "$y$j75$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J.";
+//!
+//! assert!(scrypt::yescrypt::verify(b"password", hash).is_ok());
+//! assert!(scrypt::yescrypt::verify(b"Password", hash).is_err());
+//! ```
+//!
+//! [`Hash::new`] computes hashes, which [`Hash`](struct@Hash)'s `Display` impl writes as
+//! `$y$` strings:
+//!
+//! ```
+//! use scrypt::yescrypt::{self, Hash, Mode, Params};
+//!
+//! let salt = yescrypt::decode_salt("saltsaltsaltsalt")?;
+//! let params = Params::new(Mode::ReadWrite, 10, 8, 1, 0)?;
+//! let hash = Hash::new(b"password", &salt, params)?;
+//! assert_eq!(
+//!     hash.to_string(),
+//!     "$y$j75$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J."
+//! );
+//! # Ok::<(), scrypt::errors::YescryptError>(())
+//! ```
+
+mod encoding;
+mod smix;
+
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt;
+use hmac::{Hmac, Mac};
+use pbkdf2::pbkdf2_hmac;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+use crate::{
+    errors::{InvalidOutputLen, InvalidParams, YescryptError},
+    vector, MemoryLimit,
+};
+
+#[cfg(feature = "simple")]
+use password_hash::{errors::InvalidValue, Error, ParamsString, PasswordHash};
+
+/// Prefix of yescrypt hashes.
+const PREFIX: &str = "$y$";
+
+/// Length of the hashes of `crypt(3)`.
+const HASH_LEN: usize = 32;
+
+/// Mode of operation of yescrypt, selected by the flags of a hash.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    /// Classic scrypt: the output is the same as [`scrypt`](crate::scrypt)'s
+    /// (flags `0`, encoded as `.`).
+    Classic,
+
+    /// Write-once/read-many: scrypt with yescrypt's pre- and
+    /// post-processing of the password and output (flags `1`, encoded as
+    /// `/`).
+    Worm,
+
+    /// Read-write, with the default flavor of pwxform (`YESCRYPT_DEFAULTS`,
+    /// encoded as `j`), as used by libxcrypt.
+    ReadWrite,
+}
+
+/// yescrypt parameters.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Params {
+    mode: Mode,
+    log_n: u8,
+    r: u32,
+    p: u32,
+    t: u32,
+}
+
+impl Params {
+    /// Create a new instance of [`Params`].
+    ///
+    /// # Arguments
+    /// - `mode` - The mode of operation
+    /// - `log_n` - The log₂ of the parameter `N`
+    /// - `r` - The parameter `r`
+    /// - `p` - The parameter `p`
+    /// - `t` - The parameter `t`, increasing the computation time without
+    ///   increasing the memory usage
+    ///
+    /// # Conditions
+    /// - `log_n` must be between `1` and `63`
+    /// - `r` and `p` must be greater than `0`, and `r * p` less than `2^30`
+    /// - `128 * r * N` bytes must be addressable
+    /// - `N * (t + 1)` must fit in 64 bits
+    /// - in the read-write mode, `N / p` must be at least `2`
+    pub fn new(mode: Mode, log_n: u8, r: u32, p: u32, t: u32) -> Result<Params, InvalidParams> {
+        let err = InvalidParams::new;
+
+        if !(1..=63).contains(&log_n) {
+            return Err(err("log_n", "between 1 and 63"));
+        }
+
+        if r == 0 {
+            return Err(err("r", "greater than 0"));
+        }
+
+        if p == 0 {
+            return Err(err("p", "greater than 0"));
+        }
+
+        if u64::from(r) * u64::from(p) >= 1 << 30 {
+            return Err(err("p", "such that r * p is less than 2^30"));
+        }
+
+        crate::params::check_addressable(log_n, r, p, usize::BITS)?;
+
+        let n = 1u64 << log_n;
+        if n.checked_mul(u64::from(t) + 1).is_none() {
+            return Err(err("t", "such that N * (t + 1) fits in 64 bits"));
+        }
+
+        if mode == Mode::ReadWrite && n / u64::from(p) < 2 {
+            return Err(err("p", "at most N / 2"));
+        }
+
+        Ok(Params {
+            mode,
+            log_n,
+            r,
+            p,
+            t,
+        })
+    }
+
+    /// Mode of operation.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// log₂ of the parameter `N`.
+    pub fn log_n(&self) -> u8 {
+        self.log_n
+    }
+
+    /// `r` parameter.
+    pub fn r(&self) -> u32 {
+        self.r
+    }
+
+    /// `p` parameter.
+    pub fn p(&self) -> u32 {
+        self.p
+    }
+
+    /// `t` parameter.
+    pub fn t(&self) -> u32 {
+        self.t
+    }
+
+    /// Memory required by a computation, in bytes: `V`, `B`, `X` and `Y`,
+    /// and the S-boxes of the read-write mode.
+    pub fn memory_required(&self) -> u64 {
+        let r128 = 128 * u64::from(self.r);
+        let sboxes = match self.mode {
+            Mode::ReadWrite => u64::from(self.p) * 4 * smix::SWORDS as u64,
+            Mode::Classic | Mode::Worm => 0,
+        };
+
+        (1u64 << self.log_n)
+            .saturating_add(u64::from(self.p) + 2)
+            .saturating_mul(r128)
+            .saturating_add(sboxes)
+    }
+
+    /// Parse the parameters field of a `$y$` hash, e.g. `j9T`.
+    fn decode(src: &[u8]) -> Result<Params, YescryptError> {
+        let decode = |src, min| encoding::decode64_uint32(src, min).ok_or(YescryptError::Malformed);
+
+        let (flavor, src) = decode(src, 0)?;
+        let mode = match flavor {
+            0 => Mode::Classic,
+            1 => Mode::Worm,
+            47 => Mode::ReadWrite,
+            // YESCRYPT_RW, with the other flags shifted
+            2..=257 => return Err(YescryptError::UnsupportedFlags(2 + ((flavor - 2) << 2))),
+            _ => return Err(YescryptError::Malformed),
+        };
+
+        let (log_n, src) = decode(src, 1)?;
+        let log_n = u8::try_from(log_n).map_err(|_| YescryptError::Malformed)?;
+        let (r, mut src) = decode(src, 1)?;
+
+        let (mut p, mut t) = (1, 0);
+        if !src.is_empty() {
+            let (have, rest) = decode(src, 1)?;
+            src = rest;
+
+            if have & !0xf != 0 {
+                return Err(YescryptError::Malformed);
+            }
+            if have & 0x1 != 0 {
+                (p, src) = decode(src, 2)?;
+            }
+            if have & 0x2 != 0 {
+                (t, src) = decode(src, 1)?;
+            }
+            if have & 0x4 != 0 {
+                return Err(YescryptError::Unsupported("g"));
+            }
+            if have & 0x8 != 0 {
+                return Err(YescryptError::Unsupported("NROM"));
+            }
+        }
+
+        if !src.is_empty() {
+            return Err(YescryptError::Malformed);
+        }
+
+        Ok(Params::new(mode, log_n, r, p, t)?)
+    }
+
+    /// Encode the parameters field of a `$y$` hash, the inverse of
+    /// [`Params::decode`].
+    ///
+    /// Returns `None` if `t` is too large to be encoded, the other
+    /// parameters being bounded by the checks of [`Params::new`].
+    fn encode(&self) -> Option<String> {
+        let mut dst = String::new();
+        let flavor = match self.mode {
+            Mode::Classic => 0,
+            Mode::Worm => 1,
+            Mode::ReadWrite => 47,
+        };
+        encoding::encode64_uint32(&mut dst, flavor, 0)?;
+        encoding::encode64_uint32(&mut dst, self.log_n.into(), 1)?;
+        encoding::encode64_uint32(&mut dst, self.r, 1)?;
+
+        let have = u32::from(self.p != 1) | u32::from(self.t != 0) << 1;
+        if have != 0 {
+            encoding::encode64_uint32(&mut dst, have, 1)?;
+        }
+        if self.p != 1 {
+            encoding::encode64_uint32(&mut dst, self.p, 2)?;
+        }
+        if self.t != 0 {
+            encoding::encode64_uint32(&mut dst, self.t, 1)?;
+        }
+
+        Some(dst)
+    }
+}
+
+impl Default for Params {
+    /// The parameters of libxcrypt's default cost of `5`, encoded as `j9T`:
+    /// the read-write mode, `log_n = 12`, `r = 32`, `p = 1` and `t = 0`.
+    fn default() -> Params {
+        Params {
+            mode: Mode::ReadWrite,
+            log_n: 12,
+            r: 32,
+            p: 1,
+            t: 0,
+        }
+    }
+}
+
+/// A parsed `$y$<params>$<salt>$<hash>` string.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Hash {
+    params: Params,
+    salt: Vec<u8>,
+    hash: [u8; HASH_LEN],
+}
+
+impl Hash {
+    /// Hash `password` with the decoded `salt` and `params`.
+    ///
+    /// # Return
+    /// `Err(YescryptError::InvalidParams)` if `t` is too large to be encoded
+    /// in a `$y$` hash: above `1091060272`.
+    pub fn new(password: &[u8], salt: &[u8], params: Params) -> Result<Hash, YescryptError> {
+        if params.encode().is_none() {
+            return Err(InvalidParams::new("t", "encodable in a `$y$` hash").into());
+        }
+
+        let mut hash = [0u8; HASH_LEN];
+        yescrypt(password, salt, &params, &mut hash).expect("output length is valid");
+
+        Ok(Hash {
+            params,
+            salt: salt.to_vec(),
+            hash,
+        })
+    }
+
+    /// Parse a `$y$` hash string.
+    ///
+    /// # Return
+    /// `Err(YescryptError::Malformed)` if `s` isn't a well-formed `$y$` hash,
+    /// `Err(YescryptError::UnsupportedFlags)` or
+    /// `Err(YescryptError::Unsupported)` if it uses a yescrypt feature which
+    /// isn't implemented, and `Err(YescryptError::InvalidParams)` if its
+    /// parameters are invalid.
+    pub fn parse(s: &str) -> Result<Hash, YescryptError> {
+        let fields = s.strip_prefix(PREFIX).ok_or(YescryptError::Malformed)?;
+
+        let mut fields = fields.split('$');
+        let (params, salt, hash) =
+            match (fields.next(), fields.next(), fields.next(), fields.next()) {
+                (Some(params), Some(salt), Some(hash), None) => (params, salt, hash),
+                _ => return Err(YescryptError::Malformed),
+            };
+
+        let params = Params::decode(params.as_bytes())?;
+        let salt = encoding::decode64(salt.as_bytes()).ok_or(YescryptError::Malformed)?;
+        let hash = encoding::decode64(hash.as_bytes())
+            .and_then(|hash| hash.try_into().ok())
+            .ok_or(YescryptError::Malformed)?;
+
+        Ok(Hash { params, salt, hash })
+    }
+
+    /// Parameters of the hash.
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /// Salt of the hash, decoded.
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
+    }
+
+    /// Hash of the password, decoded.
+    pub fn hash(&self) -> &[u8; HASH_LEN] {
+        &self.hash
+    }
+}
+
+impl fmt::Display for Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Parsed and computed hashes have encodable parameters
+        let params = self.params.encode().ok_or(fmt::Error)?;
+        let mut hash = String::new();
+        encoding::encode64(&mut hash, &self.hash);
+
+        write!(
+            f,
+            "{}{}${}${}",
+            PREFIX,
+            params,
+            encode_salt(&self.salt),
+            hash
+        )
+    }
+}
+
+/// Encode `salt` as in `$y$` hashes, in the little-endian Base64 of
+/// yescrypt.
+pub fn encode_salt(salt: &[u8]) -> String {
+    let mut dst = String::new();
+    encoding::encode64(&mut dst, salt);
+    dst
+}
+
+/// Decode a salt encoded as in `$y$` hashes, the inverse of
+/// [`encode_salt`].
+///
+/// # Return
+/// `Err(YescryptError::Malformed)` if `salt` isn't the canonical encoding
+/// of bytes.
+pub fn decode_salt(salt: &str) -> Result<Vec<u8>, YescryptError> {
+    encoding::decode64(salt.as_bytes()).ok_or(YescryptError::Malformed)
+}
+
+/// Parses the params of PHC strings: `mode` (`classic`, `worm` or `rw`),
+/// `ln`, `r`, `p` and `t`. Those left out are those of [`Params::default`],
+/// which [`ParamsString`] leaves out for `mode`, `p` and `t`.
+#[cfg(feature = "simple")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+impl<'a> TryFrom<&'a PasswordHash<'a>> for Params {
+    type Error = Error;
+
+    fn try_from(hash: &'a PasswordHash<'a>) -> Result<Self, Error> {
+        let Params {
+            mut mode,
+            mut log_n,
+            mut r,
+            mut p,
+            mut t,
+        } = Params::default();
+
+        if hash.version.is_some() {
+            return Err(Error::Version);
+        }
+
+        for (ident, value) in hash.params.iter() {
+            match ident.as_str() {
+                "mode" => {
+                    mode = match value.as_str() {
+                        "classic" => Mode::Classic,
+                        "worm" => Mode::Worm,
+                        "rw" => Mode::ReadWrite,
+                        _ => return Err(InvalidValue::Malformed.param_error()),
+                    }
+                }
+                "ln" => {
+                    log_n = value
+                        .decimal()?
+                        .try_into()
+                        .map_err(|_| InvalidValue::Malformed.param_error())?
+                }
+                "r" => r = value.decimal()?,
+                "p" => p = value.decimal()?,
+                "t" => t = value.decimal()?,
+                _ => return Err(Error::ParamNameInvalid),
+            }
+        }
+
+        let params = Params::new(mode, log_n, r, p, t)?;
+        if params.encode().is_none() {
+            return Err(InvalidValue::TooLong.param_error());
+        }
+        Ok(params)
+    }
+}
+
+#[cfg(feature = "simple")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+impl TryFrom<Params> for ParamsString {
+    type Error = Error;
+
+    fn try_from(input: Params) -> Result<ParamsString, Error> {
+        let mut output = ParamsString::new();
+        match input.mode {
+            Mode::Classic => output.add_str("mode", "classic")?,
+            Mode::Worm => output.add_str("mode", "worm")?,
+            Mode::ReadWrite => (),
+        }
+        output.add_decimal("ln", input.log_n.into())?;
+        output.add_decimal("r", input.r)?;
+        if input.p != 1 {
+            output.add_decimal("p", input.p)?;
+        }
+        if input.t != 0 {
+            output.add_decimal("t", input.t)?;
+        }
+        Ok(output)
+    }
+}
+
+/// Verify `password` against the `$y$` hash string `hash`, requiring at
+/// most [`MemoryLimit::DEFAULT`].
+///
+/// # Return
+/// `Ok(())` if the password matches, `Err(YescryptError::PasswordMismatch)`
+/// if it doesn't, and the errors of [`Hash::parse`] and
+/// [`verify_with_memory_limit`] otherwise.
+pub fn verify(password: &[u8], hash: &str) -> Result<(), YescryptError> {
+    verify_with_memory_limit(password, hash, MemoryLimit::DEFAULT)
+}
+
+/// Verify `password` against the `$y$` hash string `hash`, rejecting the
+/// parameters requiring more memory than `limit` with
+/// `Err(YescryptError::MemoryLimitExceeded)` before allocating it.
+pub fn verify_with_memory_limit(
+    password: &[u8],
+    hash: &str,
+    limit: MemoryLimit,
+) -> Result<(), YescryptError> {
+    let hash = Hash::parse(hash)?;
+
+    let required = hash.params.memory_required();
+    if required > limit.bytes() {
+        return Err(YescryptError::MemoryLimitExceeded {
+            required,
+            limit: limit.bytes(),
+        });
+    }
+
+    let mut output = [0u8; HASH_LEN];
+    yescrypt(password, &hash.salt, &hash.params, &mut output).expect("output length is valid");
+
+    if !bool::from(output.ct_eq(&hash.hash)) {
+        return Err(YescryptError::PasswordMismatch);
+    }
+
+    Ok(())
+}
+
+/// The yescrypt key derivation function.
+///
+/// # Return
+/// `Ok(())` if calculation is successful and `Err(InvalidOutputLen)` if
+/// `output` does not satisfy the conditions documented on
+/// [`scrypt`](crate::scrypt).
+pub fn yescrypt(
+    password: &[u8],
+    salt: &[u8],
+    params: &Params,
+    output: &mut [u8],
+) -> Result<(), InvalidOutputLen> {
+    crate::check_output_len(output.len())?;
+
+    let n = 1u64 << params.log_n;
+    let n_per_lane = n / u64::from(params.p);
+
+    // Large read-write computations first derive the password from a
+    // computation using 1/64th of the memory
+    let mut prehashed = [0u8; 32];
+    let password = if params.mode == Mode::ReadWrite
+        && n_per_lane >= 0x100
+        && n_per_lane.saturating_mul(u64::from(params.r)) >= 0x20000
+    {
+        let prehash_params = Params {
+            log_n: params.log_n - 6,
+            t: 0,
+            ..*params
+        };
+        yescrypt_body(password, salt, &prehash_params, true, &mut prehashed);
+        &prehashed[..]
+    } else {
+        password
+    };
+
+    yescrypt_body(password, salt, params, false, output);
+
+    Ok(())
+}
+
+/// Working memory of a yescrypt computation, overwritten with zeroes on
+/// drop.
+struct Buffers {
+    b: Vec<u8>,
+    v: Vec<u32>,
+    xy: Vec<u32>,
+    sboxes: Vec<u32>,
+}
+
+impl Drop for Buffers {
+    fn drop(&mut self) {
+        vector::wipe_vec(&mut self.b, 0);
+        vector::wipe_vec(&mut self.v, 0);
+        vector::wipe_vec(&mut self.xy, 0);
+        vector::wipe_vec(&mut self.sboxes, 0);
+    }
+}
+
+/// A single yescrypt computation, either the prehash of the password or
+/// the final one.
+fn yescrypt_body(password: &[u8], salt: &[u8], params: &Params, prehash: bool, output: &mut [u8]) {
+    // The checks of `Params::new` guarantee that these don't overflow
+    let n = 1u64 << params.log_n;
+    let r = params.r as usize;
+    let p = params.p as usize;
+
+    let mut buffers = Buffers {
+        b: vec![0; 128 * r * p],
+        v: vec![0; 32 * r * n as usize],
+        xy: vec![0; 64 * r],
+        sboxes: match params.mode {
+            Mode::ReadWrite => vec![0; p * smix::SWORDS],
+            Mode::Classic | Mode::Worm => Vec::new(),
+        },
+    };
+    let Buffers { b, v, xy, sboxes } = &mut buffers;
+
+    if params.mode == Mode::Classic {
+        pbkdf2_hmac::<Sha256>(password, salt, 1, b);
+        for lane in b.chunks_exact_mut(128 * r) {
+            smix::smix(
+                lane,
+                r,
+                n,
+                1,
+                params.t,
+                params.mode,
+                v,
+                xy,
+                &mut [],
+                &mut [0; 32],
+            );
+        }
+        pbkdf2_hmac::<Sha256>(password, b, 1, output);
+        return;
+    }
+
+    let key: &[u8] = if prehash {
+        b"yescrypt-prehash"
+    } else {
+        b"yescrypt"
+    };
+    let mut passwd = hmac_sha256(key, password);
+
+    pbkdf2_hmac::<Sha256>(&passwd, salt, 1, b);
+    passwd.copy_from_slice(&b[..32]);
+
+    match params.mode {
+        Mode::ReadWrite => {
+            smix::smix(
+                b,
+                r,
+                n,
+                params.p,
+                params.t,
+                params.mode,
+                v,
+                xy,
+                sboxes,
+                &mut passwd,
+            );
+        }
+        _ => {
+            for lane in b.chunks_exact_mut(128 * r) {
+                smix::smix(
+                    lane,
+                    r,
+                    n,
+                    1,
+                    params.t,
+                    params.mode,
+                    v,
+                    xy,
+                    &mut [],
+                    &mut passwd,
+                );
+            }
+        }
+    }
+
+    let mut dk = [0u8; 32];
+    if output.len() < 32 {
+        pbkdf2_hmac::<Sha256>(&passwd, b, 1, &mut dk);
+    }
+    pbkdf2_hmac::<Sha256>(&passwd, b, 1, output);
+
+    if !prehash {
+        // The final steps match SCRAM (RFC 5802): the output is the
+        // StoredKey of the ClientKey derived from the key
+        if output.len() >= 32 {
+            dk.copy_from_slice(&output[..32]);
+        }
+        let client_key = hmac_sha256(&dk, b"Client Key");
+        let stored_key = Sha256::digest(client_key);
+
+        let len = output.len().min(32);
+        output[..len].copy_from_slice(&stored_key[..len]);
+    }
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
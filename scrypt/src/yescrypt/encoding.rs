@@ -0,0 +1,151 @@
+//! The Base64 encodings of `$y$` hashes.
+//!
+//! Both use the `crypt(3)` alphabet, but unlike the usual Base64 the bits
+//! are packed little-endian: the first character holds the lowest 6 bits.
+
+use alloc::{string::String, vec::Vec};
+
+const ITOA64: &[u8; 64] = b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+fn atoi64(c: u8) -> Option<u32> {
+    ITOA64.iter().position(|&a| a == c).map(|i| i as u32)
+}
+
+/// Decode one of the variable-length integers of the parameters field,
+/// which encode values of at least `min`, returning it and the rest of
+/// `src`.
+///
+/// The first character determines how many characters follow it: values of
+/// its first range (`.` to `j`) are complete, and each following range
+/// (shorter than the previous one) adds one more character.
+pub(super) fn decode64_uint32(src: &[u8], min: u32) -> Option<(u32, &[u8])> {
+    let (&first, mut rest) = src.split_first()?;
+    let c = atoi64(first)?;
+
+    let (mut start, mut end, mut chars, mut bits) = (0, 47, 1, 0);
+    let mut value = min;
+    while c > end {
+        value += (end + 1 - start) << bits;
+        start = end + 1;
+        end = start + (62 - end) / 2;
+        chars += 1;
+        bits += 6;
+    }
+    value += (c - start) << bits;
+
+    for _ in 1..chars {
+        let (&next, tail) = rest.split_first()?;
+        bits -= 6;
+        value += atoi64(next)? << bits;
+        rest = tail;
+    }
+
+    Some((value, rest))
+}
+
+/// Encode `value`, at least `min`, as one of the variable-length integers
+/// of the parameters field, the inverse of [`decode64_uint32`].
+///
+/// Returns `None` for the values too large to be encoded: those from
+/// `min + 1091060272`, past which the character ranges run out.
+pub(super) fn encode64_uint32(dst: &mut String, value: u32, min: u32) -> Option<()> {
+    let mut value = value.checked_sub(min)?;
+
+    let (mut start, mut end, mut chars, mut bits) = (0, 47, 1, 0);
+    loop {
+        let count = (end + 1 - start) << bits;
+        if value < count {
+            break;
+        }
+        if start >= 63 {
+            return None;
+        }
+        value -= count;
+        start = end + 1;
+        end = start + (62 - end) / 2;
+        chars += 1;
+        bits += 6;
+    }
+
+    dst.push(ITOA64[(start + (value >> bits)) as usize] as char);
+    for _ in 1..chars {
+        bits -= 6;
+        dst.push(ITOA64[((value >> bits) & 0x3f) as usize] as char);
+    }
+
+    Some(())
+}
+
+/// Encode `src` as the inverse of [`decode64`], each group of 3 bytes
+/// giving 4 characters.
+pub(super) fn encode64(dst: &mut String, src: &[u8]) {
+    for group in src.chunks(3) {
+        let mut bytes = [0u8; 4];
+        bytes[..group.len()].copy_from_slice(group);
+        let mut value = u32::from_le_bytes(bytes);
+
+        for _ in 0..(group.len() * 8 + 5) / 6 {
+            dst.push(ITOA64[(value & 0x3f) as usize] as char);
+            value >>= 6;
+        }
+    }
+}
+
+/// Decode `src` as bytes, each group of 4 characters holding 3 bytes.
+///
+/// Like `crypt(3)`, the encoding must be canonical: a trailing group can't
+/// be a single character, and its unused bits must be zero.
+pub(super) fn decode64(src: &[u8]) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(src.len() * 3 / 4);
+
+    for group in src.chunks(4) {
+        if group.len() == 1 {
+            return None;
+        }
+
+        let mut value = 0u32;
+        for (i, &c) in group.iter().enumerate() {
+            value |= atoi64(c)? << (6 * i);
+        }
+
+        let len = group.len() * 6 / 8;
+        bytes.extend_from_slice(&value.to_le_bytes()[..len]);
+        if value >> (8 * len) != 0 {
+            return None;
+        }
+    }
+
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode64_uint32, encode64_uint32};
+    use alloc::string::String;
+
+    #[test]
+    fn uint32_round_trip() {
+        for (value, min, encoded) in [
+            (0, 0, "."),
+            (47, 0, "j"),
+            (48, 0, "k."),
+            (1, 1, "."),
+            (32, 1, "T"),
+            (2, 2, "."),
+            (1_091_060_271, 0, "zzzzzz"),
+            (1_091_060_272, 1, "zzzzzz"),
+        ] {
+            let mut dst = String::new();
+            encode64_uint32(&mut dst, value, min).unwrap();
+            assert_eq!(dst, encoded);
+            assert_eq!(
+                decode64_uint32(dst.as_bytes(), min),
+                Some((value, &b""[..]))
+            );
+        }
+
+        let mut dst = String::new();
+        assert_eq!(encode64_uint32(&mut dst, 1_091_060_272, 0), None);
+        assert_eq!(encode64_uint32(&mut dst, 0, 1), None);
+    }
+}
@@ -0,0 +1,339 @@
+//! yescrypt's SMix and its BlockMix functions, following the yescrypt
+//! reference implementation.
+//!
+//! Blocks are kept in the word order of the reference implementation while
+//! they're mixed: within every 16-word sub-block, position `i` holds the
+//! standard word `i * 5 % 16`. Unlike in scrypt this isn't only a matter of
+//! performance, as pwxform and `Integerify` are defined on that order.
+
+use alloc::vec::Vec;
+
+use super::{hmac_sha256, Mode};
+use crate::salsa::soft_salsa20;
+
+/// Number of 64-bit lanes per "simple SIMD" lane of pwxform.
+const PWXSIMPLE: usize = 2;
+
+/// Number of "simple SIMD" lanes per "gather SIMD" vector of pwxform.
+const PWXGATHER: usize = 4;
+
+/// Number of rounds of pwxform.
+const PWXROUNDS: usize = 6;
+
+/// Number of S-box index bits.
+const SWIDTH: usize = 8;
+
+/// Mask of the byte offsets of S-box elements.
+const SMASK: usize = ((1 << SWIDTH) - 1) * PWXSIMPLE * 8;
+
+/// Words of a single S-box.
+const SBOX_WORDS: usize = (1 << SWIDTH) * PWXSIMPLE * 2;
+
+/// Words of the three S-boxes of a lane.
+pub(super) const SWORDS: usize = 3 * SBOX_WORDS;
+
+/// The pwxform state of a lane: its S-boxes `S0`, `S1` and `S2`, stored
+/// as word offsets into `s` since they rotate after every pwxform, and the
+/// write position `w` in `S2`.
+struct Pwxform<'a> {
+    s: &'a mut [u32],
+    s0: usize,
+    s1: usize,
+    s2: usize,
+    w: usize,
+}
+
+impl<'a> Pwxform<'a> {
+    /// Use `s` (filled by SMix1) as S-boxes: `S2`, `S1` and `S0`, in order.
+    fn new(s: &'a mut [u32]) -> Self {
+        Pwxform {
+            s,
+            s0: 2 * SBOX_WORDS,
+            s1: SBOX_WORDS,
+            s2: 0,
+            w: 0,
+        }
+    }
+
+    /// pwxform of a 16-word sub-block, i.e. `PWXGATHER` lanes of
+    /// `PWXSIMPLE` 64-bit words, each stored as its low and high halves.
+    fn pwxform(&mut self, b: &mut [u32; 16]) {
+        let mut w = self.w;
+
+        for round in 0..PWXROUNDS {
+            for lane in b.chunks_exact_mut(16 / PWXGATHER) {
+                let p0 = self.s0 + (lane[0] as usize & SMASK) / 4;
+                let p1 = self.s1 + (lane[1] as usize & SMASK) / 4;
+
+                for k in 0..PWXSIMPLE {
+                    let s0 =
+                        u64::from(self.s[p0 + 2 * k]) | u64::from(self.s[p0 + 2 * k + 1]) << 32;
+                    let s1 =
+                        u64::from(self.s[p1 + 2 * k]) | u64::from(self.s[p1 + 2 * k + 1]) << 32;
+
+                    let x = u64::from(lane[2 * k + 1]).wrapping_mul(u64::from(lane[2 * k]));
+                    let x = x.wrapping_add(s0) ^ s1;
+                    lane[2 * k] = x as u32;
+                    lane[2 * k + 1] = (x >> 32) as u32;
+
+                    if round != 0 && round != PWXROUNDS - 1 {
+                        self.s[self.s2 + 2 * w] = x as u32;
+                        self.s[self.s2 + 2 * w + 1] = (x >> 32) as u32;
+                        w += 1;
+                    }
+                }
+            }
+        }
+
+        // (S0, S1, S2) <-- (S2, S0, S1)
+        let s2 = self.s2;
+        self.s2 = self.s1;
+        self.s1 = self.s0;
+        self.s0 = s2;
+
+        self.w = w % (SBOX_WORDS / 2);
+    }
+
+    /// `BlockMix_pwxform` of `b`, in place.
+    ///
+    /// With the default flavor a pwxform block is a single sub-block, so
+    /// every sub-block goes through pwxform, and only the last one is then
+    /// post-processed with Salsa20/2.
+    fn block_mix(&mut self, b: &mut [u32]) {
+        let mut x: [u32; 16] = b[b.len() - 16..].try_into().unwrap();
+
+        for block in b.chunks_exact_mut(16) {
+            xor(&mut x, block);
+            self.pwxform(&mut x);
+            block.copy_from_slice(&x);
+        }
+
+        let last = b.len() - 16;
+        salsa20::<1>((&mut b[last..]).try_into().unwrap());
+    }
+}
+
+/// `SMix` of the `p` lanes of `b`, with `N = n`.
+///
+/// `v` must hold `N` blocks, `xy` two blocks and `sboxes` `p * SWORDS`
+/// words in the read-write mode (and may be empty otherwise). In the
+/// read-write mode, `passwd` is updated with the HMAC of the first lane.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn smix(
+    b: &mut [u8],
+    r: usize,
+    n: u64,
+    p: u32,
+    t: u32,
+    mode: Mode,
+    v: &mut [u32],
+    xy: &mut [u32],
+    sboxes: &mut [u32],
+    passwd: &mut [u8; 32],
+) {
+    let rw = mode == Mode::ReadWrite;
+    let s = 32 * r;
+    let p = u64::from(p);
+
+    let mut nchunk = n / p;
+
+    let mut nloop_all = nchunk;
+    if rw {
+        if t <= 1 {
+            if t != 0 {
+                nloop_all *= 2;
+            }
+            nloop_all = (nloop_all + 2) / 3;
+        } else {
+            nloop_all *= u64::from(t) - 1;
+        }
+    } else if t != 0 {
+        if t == 1 {
+            nloop_all += (nloop_all + 1) / 2;
+        }
+        nloop_all *= u64::from(t);
+    }
+
+    let mut nloop_rw = if rw { nloop_all / p } else { 0 };
+
+    nchunk &= !1;
+    nloop_all = (nloop_all + 1) & !1;
+    nloop_rw = (nloop_rw + 1) & !1;
+
+    let mut sboxes = sboxes.chunks_exact_mut(SWORDS);
+    let mut ctxs = Vec::with_capacity(p as usize);
+
+    let mut u = 0;
+    for (i, lane) in b.chunks_exact_mut(4 * s).enumerate() {
+        let np = if (i as u64) < p - 1 { nchunk } else { n - u };
+
+        let mut ctx = if rw {
+            let sbox = sboxes.next().unwrap();
+            smix1(lane, 1, SWORDS / 32, false, sbox, xy, None);
+
+            if i == 0 {
+                *passwd = hmac_sha256(&lane[4 * s - 64..], passwd);
+            }
+
+            Some(Pwxform::new(sbox))
+        } else {
+            None
+        };
+
+        // The checks of `Params::new` guarantee that V is addressable
+        let vp = &mut v[u as usize * s..(u + np) as usize * s];
+        smix1(lane, r, np as usize, rw, vp, xy, ctx.as_mut());
+        smix2(
+            lane,
+            r,
+            prev_power_of_two(np),
+            nloop_rw,
+            rw,
+            vp,
+            xy,
+            ctx.as_mut(),
+        );
+
+        ctxs.push(ctx);
+        u += nchunk;
+    }
+
+    for (lane, ctx) in b.chunks_exact_mut(4 * s).zip(ctxs.iter_mut()) {
+        smix2(lane, r, n, nloop_all - nloop_rw, false, v, xy, ctx.as_mut());
+    }
+}
+
+/// First loop of `SMix`: fill the first `n` blocks of `v`.
+fn smix1(
+    b: &mut [u8],
+    r: usize,
+    n: usize,
+    rw: bool,
+    v: &mut [u32],
+    xy: &mut [u32],
+    mut ctx: Option<&mut Pwxform<'_>>,
+) {
+    let s = 32 * r;
+    let (x, y) = xy[..2 * s].split_at_mut(s);
+    load(b, x);
+
+    for i in 0..n {
+        v[i * s..(i + 1) * s].copy_from_slice(x);
+
+        if rw && i > 1 {
+            let m = prev_power_of_two(i as u64);
+            let j = (integerify(x) & (m - 1)) + (i as u64 - m);
+            xor(x, &v[j as usize * s..(j as usize + 1) * s]);
+        }
+
+        block_mix(x, y, ctx.as_deref_mut());
+    }
+
+    store(x, b);
+}
+
+/// Second loop of `SMix`: `nloop` rounds of mixing the first `n` blocks of
+/// `v` into `b`, writing them back if `rw`.
+#[allow(clippy::too_many_arguments)]
+fn smix2(
+    b: &mut [u8],
+    r: usize,
+    n: u64,
+    nloop: u64,
+    rw: bool,
+    v: &mut [u32],
+    xy: &mut [u32],
+    mut ctx: Option<&mut Pwxform<'_>>,
+) {
+    let s = 32 * r;
+    let (x, y) = xy[..2 * s].split_at_mut(s);
+    load(b, x);
+
+    for _ in 0..nloop {
+        let j = (integerify(x) & (n - 1)) as usize;
+        let v_j = &mut v[j * s..(j + 1) * s];
+
+        xor(x, v_j);
+        if rw {
+            v_j.copy_from_slice(x);
+        }
+
+        block_mix(x, y, ctx.as_deref_mut());
+    }
+
+    store(x, b);
+}
+
+/// `BlockMix_pwxform` with `ctx`, or scrypt's BlockMix without it, of `x`
+/// in place, using `y` as temporary storage.
+fn block_mix(x: &mut [u32], y: &mut [u32], ctx: Option<&mut Pwxform<'_>>) {
+    match ctx {
+        Some(ctx) => ctx.block_mix(x),
+        None => {
+            let half = x.len() / 2;
+            let mut t: [u32; 16] = x[x.len() - 16..].try_into().unwrap();
+
+            for (i, block) in x.chunks_exact(16).enumerate() {
+                xor(&mut t, block);
+                salsa20::<4>(&mut t);
+
+                let pos = (i / 2) * 16 + (i % 2) * half;
+                y[pos..pos + 16].copy_from_slice(&t);
+            }
+
+            x.copy_from_slice(y);
+        }
+    }
+}
+
+/// Salsa20 core with `DOUBLE_ROUNDS` double rounds of a sub-block in the
+/// reference word order.
+fn salsa20<const DOUBLE_ROUNDS: usize>(b: &mut [u32; 16]) {
+    let mut x = [0u32; 16];
+    for (i, &w) in b.iter().enumerate() {
+        x[i * 5 % 16] = w;
+    }
+
+    soft_salsa20::<DOUBLE_ROUNDS>(&mut x);
+
+    for (i, w) in b.iter_mut().enumerate() {
+        *w = x[i * 5 % 16];
+    }
+}
+
+/// `Integerify(X)`: the first 64 bits of the last sub-block of `x`.
+fn integerify(x: &[u32]) -> u64 {
+    let last = &x[x.len() - 16..];
+    u64::from(last[0]) | u64::from(last[13]) << 32
+}
+
+/// Largest power of two which isn't greater than `x`.
+fn prev_power_of_two(x: u64) -> u64 {
+    1 << (63 - x.leading_zeros())
+}
+
+fn xor(x: &mut [u32], y: &[u32]) {
+    for (x, y) in x.iter_mut().zip(y.iter()) {
+        *x ^= y;
+    }
+}
+
+/// Read the lane `b` into `x`, in the reference word order.
+fn load(b: &[u8], x: &mut [u32]) {
+    for (block, bytes) in x.chunks_exact_mut(16).zip(b.chunks_exact(64)) {
+        for (i, w) in block.iter_mut().enumerate() {
+            let k = i * 5 % 16;
+            *w = u32::from_le_bytes(bytes[4 * k..4 * k + 4].try_into().unwrap());
+        }
+    }
+}
+
+/// Write `x` back to the lane `b`, in the standard word order.
+fn store(x: &[u32], b: &mut [u8]) {
+    for (block, bytes) in x.chunks_exact(16).zip(b.chunks_exact_mut(64)) {
+        for (i, w) in block.iter().enumerate() {
+            let k = i * 5 % 16;
+            bytes[4 * k..4 * k + 4].copy_from_slice(&w.to_le_bytes());
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! `$7$` hashes of `crypt(3)`.
+
+#![cfg(feature = "crypt")]
+
+use scrypt::{
+    crypt::{self, Hash},
+    errors::CryptError,
+    MemoryLimit,
+};
+
+/// Hashes computed by `crypt(3)` of libxcrypt, as `(password, hash)`. The
+/// `SodiumChloride` one is from the test suites of libxcrypt and
+/// libsodium.
+const HASHES: &[(&str, &str)] = &[
+    (
+        "pleaseletmein",
+        "$7$C6..../....SodiumChloride$kBGj9fHznVYFQMEn/qDCfrDevf9YDtcDdKvEqHJLV8D",
+    ),
+    (
+        "password",
+        "$7$CU..../....SodiumChloride$siKSmmZgY8HQ/vgp0LEyi18bSR0Rm2AyTRfe12wvPXB",
+    ),
+    // log_n = 12, r = 1, p = 2
+    (
+        "password",
+        "$7$A/....0....seasalt$NRCWtVVRgqRtofurEcKXnLdKcYIbLc8twn4roB0vkXD",
+    ),
+    (
+        "",
+        "$7$9/..../....salt$hhRKEMe.oafSi4tol6f4K7btBvgj.8RiwOHNPE5l4t2",
+    ),
+    (
+        "lètmein",
+        "$7$7/..../....abcdefghijklmnopqrstuvwxyz$i6QdfxxRrOtrePTVuVDJyGAfn3XMQaKAnjLoVragShB",
+    ),
+    // Empty salt
+    (
+        "password",
+        "$7$9/....0....$Po0XVOUowX1BZNaMw2ohdLvC34.o6nWqnTOL3NvZDyC",
+    ),
+];
+
+#[test]
+fn verify_hashes() {
+    for (password, hash) in HASHES {
+        assert_eq!(crypt::verify(password.as_bytes(), hash), Ok(()), "{}", hash);
+        assert_eq!(
+            crypt::verify(b"bogus", hash),
+            Err(CryptError::PasswordMismatch),
+            "{}",
+            hash
+        );
+    }
+}
+
+#[test]
+fn crypt_settings() {
+    for (password, hash) in HASHES {
+        let setting = &hash[..hash.rfind('$').unwrap()];
+        assert_eq!(&crypt::crypt(password.as_bytes(), setting).unwrap(), hash);
+
+        // A hash string is a setting as well
+        assert_eq!(&crypt::crypt(password.as_bytes(), hash).unwrap(), hash);
+    }
+}
+
+#[test]
+fn parse() {
+    let hash = Hash::parse(HASHES[2].1).unwrap();
+    assert_eq!(hash.params().log_n(), 12);
+    assert_eq!(hash.params().r(), 1);
+    assert_eq!(hash.params().p(), 2);
+    assert_eq!(hash.salt(), "seasalt");
+    assert_eq!(hash.to_string(), HASHES[2].1);
+}
+
+#[test]
+fn malformed() {
+    for hash in [
+        "",
+        "$7$",
+        "$7$C6..../....",
+        "$7$C6..../...",
+        "$7$C6..../....SodiumChloride",
+        "$y$C6..../....SodiumChloride$kBGj9fHznVYFQMEn/qDCfrDevf9YDtcDdKvEqHJLV8D",
+        // Characters outside of the alphabet
+        "$7$C6...-/....SodiumChloride$kBGj9fHznVYFQMEn/qDCfrDevf9YDtcDdKvEqHJLV8D",
+        "$7$C6..../....SodiumChloride$kBGj9fHznVYFQMEn/qDCfrDevf9YDtcDdKvEqHJLV8+",
+        // Hash length
+        "$7$C6..../....SodiumChloride$kBGj9fHznVYFQMEn/qDCfrDevf9YDtcDdKvEqHJLV8",
+        "$7$C6..../....SodiumChloride$kBGj9fHznVYFQMEn/qDCfrDevf9YDtcDdKvEqHJLV8D.",
+    ] {
+        assert_eq!(
+            crypt::verify(b"pleaseletmein", hash),
+            Err(CryptError::Malformed),
+            "{}",
+            hash
+        );
+    }
+
+    assert_eq!(
+        crypt::crypt(b"password", "$7$C6..../"),
+        Err(CryptError::Malformed)
+    );
+}
+
+#[test]
+fn invalid_params() {
+    // r = 0, p = 0
+    for hash in [
+        "$7$C.....0....SodiumChloride$kBGj9fHznVYFQMEn/qDCfrDevf9YDtcDdKvEqHJLV8D",
+        "$7$C6.........SodiumChloride$kBGj9fHznVYFQMEn/qDCfrDevf9YDtcDdKvEqHJLV8D",
+    ] {
+        assert!(matches!(
+            crypt::verify(b"pleaseletmein", hash),
+            Err(CryptError::InvalidParams(_))
+        ));
+    }
+}
+
+#[test]
+fn memory_limit() {
+    let (password, hash) = HASHES[0];
+    assert!(matches!(
+        crypt::verify_with_memory_limit(password.as_bytes(), hash, MemoryLimit::new(1 << 20)),
+        Err(CryptError::MemoryLimitExceeded { .. })
+    ));
+    assert_eq!(
+        crypt::verify_with_memory_limit(password.as_bytes(), hash, MemoryLimit::new(1 << 30)),
+        Ok(())
+    );
+}
@@ -0,0 +1,168 @@
+//! Hashes from Django's `ScryptPasswordHasher`.
+
+#![cfg(feature = "django")]
+
+use scrypt::{
+    django::{self, Hash, DEFAULT_PARAMS},
+    errors::DjangoError,
+    MemoryLimit, Params,
+};
+
+/// The hash of `make_password("lètmein", "seasalt", "scrypt")` from
+/// Django's test suite.
+const DJANGO_HASH: &str = "scrypt$16384$seasalt$8$1$Qj3+9PPyRjSJIebHnG81TMjsqtaIGxNQG/aEB/NYafTJ7tibgfYz71m0ldQESkXFRkdVCBhhY8mx7rQwite/Pw==";
+
+/// Hashes of `make_password` with custom settings, as `(password, hash)`.
+const CUSTOM_HASHES: &[(&str, &str)] = &[
+    (
+        "password",
+        "scrypt$16384$Qcp7PymXpkYopWNjmDmoTJ$8$1$tdHSuE9RquMcPOwhuYZPJML3dJKuBoU5mzDwwa981cdFMQw0LLVp0384NPNB39alY3vGr5QzRJus/LJBHKLozw==",
+    ),
+    (
+        "",
+        "scrypt$16384$Qcp7PymXpkYopWNjmDmoTJ$8$1$pV6ApZAE7ABP6RHhViXGDpvDzBFzqm8CvvlEDctJGytcy+liqBuXv+bZCVIA6ST1WNCtTWar+HJHsqKEO2sW5w==",
+    ),
+    // work_factor = 1024, block_size = 4, parallelism = 2
+    (
+        "lètmein",
+        "scrypt$1024$seasalt$4$2$0UO6jDjqZaIvkdpYu2g/92y9Jw18qq3FovdMh+WWXGOcfFGm6Cr8x1TtM/Z6m7KsFfreohqzsWDbDVsYfdF2Ug==",
+    ),
+];
+
+#[test]
+fn verify_hashes() {
+    assert_eq!(django::verify("lètmein".as_bytes(), DJANGO_HASH), Ok(()));
+    assert_eq!(
+        django::verify(b"letmein", DJANGO_HASH),
+        Err(DjangoError::PasswordMismatch)
+    );
+
+    for (password, hash) in CUSTOM_HASHES {
+        assert_eq!(
+            django::verify(password.as_bytes(), hash),
+            Ok(()),
+            "{}",
+            hash
+        );
+        assert_eq!(
+            django::verify(b"wrong", hash),
+            Err(DjangoError::PasswordMismatch)
+        );
+    }
+}
+
+#[test]
+fn encode_hashes() {
+    assert_eq!(
+        django::encode("lètmein".as_bytes(), "seasalt", &DEFAULT_PARAMS).unwrap(),
+        DJANGO_HASH
+    );
+
+    // The key length of the parameters is ignored
+    let params = Params::new(10, 4, 2, 32).unwrap();
+    assert_eq!(
+        django::encode("lètmein".as_bytes(), "seasalt", &params).unwrap(),
+        CUSTOM_HASHES[2].1
+    );
+
+    for salt in ["", "sea$salt"] {
+        assert_eq!(
+            django::encode(b"password", salt, &DEFAULT_PARAMS),
+            Err(DjangoError::InvalidSalt)
+        );
+    }
+}
+
+#[test]
+fn parse_hash() {
+    let hash = Hash::parse(CUSTOM_HASHES[2].1).unwrap();
+    let params = hash.params();
+    assert_eq!((params.log_n(), params.r(), params.p()), (10, 4, 2));
+    assert_eq!(hash.salt(), "seasalt");
+    assert_eq!(hash.to_string(), CUSTOM_HASHES[2].1);
+}
+
+#[test]
+fn malformed_hashes() {
+    let hash =
+        "Qj3+9PPyRjSJIebHnG81TMjsqtaIGxNQG/aEB/NYafTJ7tibgfYz71m0ldQESkXFRkdVCBhhY8mx7rQwite/Pw==";
+
+    for encoded in [
+        String::new(),
+        "scrypt".to_string(),
+        // Missing and extra fields
+        format!("scrypt$16384$seasalt$8${}", hash),
+        format!("scrypt$16384$seasalt$8$1${}$", hash),
+        format!("scrypt$16384$sea$salt$8$1${}", hash),
+        // Other algorithm
+        format!("pbkdf2_sha256$16384$seasalt$8$1${}", hash),
+        // Non-decimal parameters
+        format!("scrypt$$seasalt$8$1${}", hash),
+        format!("scrypt$+16384$seasalt$8$1${}", hash),
+        format!("scrypt$16384$seasalt$0x8$1${}", hash),
+        format!("scrypt$16384$seasalt$8$-1${}", hash),
+        // Unpadded, truncated and non-canonical hash
+        format!("scrypt$16384$seasalt$8$1${}", hash.trim_end_matches('=')),
+        format!("scrypt$16384$seasalt$8$1${}", &hash[4..]),
+        format!(
+            "scrypt$16384$seasalt$8$1${}",
+            hash.replace("/Pw==", "/Px==")
+        ),
+    ] {
+        assert_eq!(
+            Hash::parse(&encoded).unwrap_err(),
+            DjangoError::Malformed,
+            "{}",
+            encoded
+        );
+    }
+}
+
+#[test]
+fn invalid_params() {
+    let hash =
+        "Qj3+9PPyRjSJIebHnG81TMjsqtaIGxNQG/aEB/NYafTJ7tibgfYz71m0ldQESkXFRkdVCBhhY8mx7rQwite/Pw==";
+
+    for (fields, param) in [
+        ("16383$seasalt$8$1", "work_factor"),
+        ("1$seasalt$8$1", "work_factor"),
+        ("0$seasalt$8$1", "work_factor"),
+        ("99999999999999999999$seasalt$8$1", "work_factor"),
+        // N must be less than 2^(16 * r)
+        ("65536$seasalt$1$1", "work_factor"),
+        ("16384$seasalt$0$1", "block_size"),
+        ("16384$seasalt$4294967296$1", "block_size"),
+        ("16384$seasalt$8$0", "parallelism"),
+        ("16384$seasalt$8$1073741824", "parallelism"),
+    ] {
+        let encoded = format!("scrypt${}${}", fields, hash);
+        match Hash::parse(&encoded) {
+            Err(DjangoError::InvalidParams(err)) => assert_eq!(err.param(), param, "{}", encoded),
+            res => panic!("unexpected result {:?} for {}", res.map(|_| ()), encoded),
+        }
+    }
+}
+
+#[test]
+fn memory_limit() {
+    let required = 128 * 8 * (16384 + 3);
+    assert_eq!(
+        django::verify_with_memory_limit(
+            "lètmein".as_bytes(),
+            DJANGO_HASH,
+            MemoryLimit::new(required - 1)
+        ),
+        Err(DjangoError::MemoryLimitExceeded {
+            required,
+            limit: required - 1
+        })
+    );
+    assert_eq!(
+        django::verify_with_memory_limit(
+            "lètmein".as_bytes(),
+            DJANGO_HASH,
+            MemoryLimit::new(required)
+        ),
+        Ok(())
+    );
+}
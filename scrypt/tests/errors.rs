@@ -0,0 +1,143 @@
+//! Error messages.
+
+use scrypt::{
+    errors::{Error, InvalidN, InvalidOutputLen},
+    Params,
+};
+
+#[test]
+fn invalid_params() {
+    for ((log_n, r, p, len), param, message) in [
+        (
+            (10, 0, 1, 32),
+            "r",
+            "invalid scrypt parameter `r`: expected greater than 0",
+        ),
+        (
+            (10, 8, 0, 32),
+            "p",
+            "invalid scrypt parameter `p`: expected greater than 0",
+        ),
+        (
+            (10, 8, 1, 9),
+            "len",
+            "invalid scrypt parameter `len`: expected between 10 and 64",
+        ),
+        (
+            (10, 8, 1, 65),
+            "len",
+            "invalid scrypt parameter `len`: expected between 10 and 64",
+        ),
+        (
+            (16, 1, 1, 32),
+            "log_n",
+            "invalid scrypt parameter `log_n`: expected less than 16 * r",
+        ),
+        (
+            (10, 8, 1 << 27, 32),
+            "p",
+            "invalid scrypt parameter `p`: expected such that r * p is less than 2^30",
+        ),
+    ] {
+        let err = Params::new(log_n, r, p, len).unwrap_err();
+        assert_eq!(err.param(), param);
+        assert_eq!(err.to_string(), message);
+        assert_eq!(Error::from(err).to_string(), message);
+    }
+}
+
+#[cfg(target_pointer_width = "64")]
+#[test]
+fn invalid_params_usize() {
+    let err = Params::new(64, 8, 1, 32).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid scrypt parameter `log_n`: expected such that 128 * r * N bytes are addressable \
+         on this platform"
+    );
+
+    let err = Params::new(60, 8, 1, 32).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid scrypt parameter `log_n`: expected such that 128 * r * N bytes are addressable \
+         on this platform"
+    );
+}
+
+#[test]
+fn error() {
+    assert_eq!(
+        Error::from(InvalidOutputLen).to_string(),
+        "invalid output buffer length"
+    );
+    assert_eq!(Error::Cancelled.to_string(), "scrypt computation cancelled");
+    assert_eq!(
+        Error::MemoryLimitExceeded {
+            required: 2048,
+            limit: 1024
+        }
+        .to_string(),
+        "scrypt parameters require 2048 bytes of memory, exceeding the limit of 1024 bytes"
+    );
+    assert_eq!(
+        Error::ParamsPolicyViolation {
+            param: "ln",
+            value: 30,
+            max: 24
+        }
+        .to_string(),
+        "scrypt parameter ln = 30 exceeds the maximum of 24"
+    );
+}
+
+#[test]
+fn invalid_n() {
+    assert_eq!(
+        InvalidN::TooSmall(1).to_string(),
+        "scrypt parameter N must be greater than 1, got 1"
+    );
+    assert_eq!(
+        Params::from_n(1 << 20, 1, 1).unwrap_err().to_string(),
+        "invalid scrypt parameter `log_n`: expected less than 16 * r"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn std_error() {
+    fn boxed<E: std::error::Error + 'static>(err: E) -> Box<dyn std::error::Error> {
+        Box::new(err)
+    }
+
+    let err = boxed(Params::new(10, 0, 1, 32).unwrap_err());
+    assert_eq!(
+        err.to_string(),
+        "invalid scrypt parameter `r`: expected greater than 0"
+    );
+    assert_eq!(
+        boxed(Error::Cancelled).to_string(),
+        "scrypt computation cancelled"
+    );
+}
+
+#[cfg(feature = "simple")]
+#[test]
+fn into_password_hash_error() {
+    use password_hash::errors::InvalidValue;
+
+    assert_eq!(
+        password_hash::Error::from(Params::new(10, 0, 1, 32).unwrap_err()),
+        InvalidValue::Malformed.param_error()
+    );
+    assert_eq!(
+        password_hash::Error::from(Error::MemoryLimitExceeded {
+            required: 2048,
+            limit: 1024
+        }),
+        InvalidValue::TooLong.param_error()
+    );
+    assert_eq!(
+        password_hash::Error::from(Error::PasswordHash(password_hash::Error::Password)),
+        password_hash::Error::Password
+    );
+}
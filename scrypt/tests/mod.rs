@@ -1,8 +1,13 @@
-use scrypt::{scrypt, Params};
+use core::ops::ControlFlow;
+use hex_literal::hex;
+use scrypt::{
+    errors::{Error, InvalidN, InvalidOutputLen},
+    scrypt, scrypt_with_lanes, scrypt_with_progress, LaneStrategy, Params, Progress,
+};
 
 #[cfg(feature = "simple")]
 use {
-    password_hash::{PasswordHash, PasswordVerifier},
+    password_hash::{Output, PasswordHash, PasswordVerifier},
     scrypt::Scrypt,
 };
 
@@ -81,6 +86,149 @@ fn test_scrypt() {
     }
 }
 
+/// The proof-of-work hash of the Litecoin genesis block: scrypt of its
+/// 80-byte header, salted with itself, with `N = 1024, r = 1, p = 1`.
+#[test]
+fn litecoin_genesis_block() {
+    let header = hex!(
+        "01000000 00000000 00000000 00000000 00000000 00000000 00000000 00000000"
+        "00000000 d9ced4ed 1130f7b7 faad9be2 5323ffaf a33232a1 7c3edf6c fd97bee6"
+        "bafbdd97 b9aa8e4e f0ff0f1e cd513f7c"
+    );
+
+    let params = Params::new(10, 1, 1, 32).unwrap();
+    let mut hash = [0u8; 32];
+    scrypt(&header, &header, &params, &mut hash).unwrap();
+    assert_eq!(
+        hash,
+        hex!("001e67b013726fd7382e9acb69165b4b6316227fb3156b5b414ba6340c050000")
+    );
+}
+
+#[test]
+fn scrypt_invalid_output_len() {
+    let params = Params::new(4, 1, 1, 32).unwrap();
+
+    assert_eq!(
+        scrypt(b"password", b"salt", &params, &mut []),
+        Err(InvalidOutputLen)
+    );
+    assert_eq!(
+        scrypt_with_progress(b"password", b"salt", &params, &mut [], 1, |_| {
+            panic!("no work should be done")
+        }),
+        Err(Error::InvalidOutputLen)
+    );
+    assert_eq!(
+        Error::from(InvalidOutputLen).to_string(),
+        "invalid output buffer length"
+    );
+}
+
+#[test]
+fn lane_strategies() {
+    let params = Params::new(10, 8, 3, 32).unwrap();
+    let mut expected = [0u8; 32];
+    scrypt(b"password", b"salt", &params, &mut expected).unwrap();
+
+    let strategies = [
+        LaneStrategy::SequentialReuse,
+        #[cfg(feature = "parallel")]
+        LaneStrategy::Parallel,
+    ];
+
+    for lanes in strategies {
+        let mut output = [0u8; 32];
+        scrypt_with_lanes(b"password", b"salt", &params, &mut output, lanes).unwrap();
+        assert_eq!(output, expected, "{:?}", lanes);
+    }
+
+    // Sequential lanes only need the memory of one lane (V, X and Y), plus B
+    let one_lane = Params::new(10, 8, 1, 32).unwrap();
+    let b = |p: u64| 1024 * p;
+    assert_eq!(
+        LaneStrategy::SequentialReuse.memory_required(&params) - b(3),
+        LaneStrategy::SequentialReuse.memory_required(&one_lane) - b(1)
+    );
+    assert_eq!(
+        LaneStrategy::SequentialReuse.memory_required(&params),
+        1024 * (1024 + 2) + b(3)
+    );
+
+    #[cfg(feature = "parallel")]
+    {
+        assert_eq!(
+            LaneStrategy::Parallel.memory_required(&params),
+            3 * 1024 * (1024 + 2) + b(3)
+        );
+        assert_eq!(
+            LaneStrategy::Parallel.memory_required(&one_lane),
+            LaneStrategy::SequentialReuse.memory_required(&one_lane)
+        );
+    }
+}
+
+#[test]
+fn params_string_round_trip() {
+    for (s, (log_n, r, p)) in [
+        ("ln=15,r=8,p=1", (15, 8, 1)),
+        ("ln=1,r=1,p=1", (1, 1, 1)),
+        ("ln=10,r=8,p=16", (10, 8, 16)),
+        ("ln=14,r=8,p=1,l=64", (14, 8, 1)),
+    ] {
+        let params: Params = s.parse().unwrap();
+        assert_eq!((params.log_n(), params.r(), params.p()), (log_n, r, p));
+        assert_eq!(params.to_string(), s);
+    }
+
+    assert_eq!(Params::INTERACTIVE.to_string(), "ln=14,r=8,p=1");
+    assert_eq!(
+        Params::new(14, 8, 1, 16).unwrap().to_string(),
+        "ln=14,r=8,p=1,l=16"
+    );
+
+    // any order, recommended values for missing parameters
+    let params: Params = "p=2,ln=12".parse().unwrap();
+    assert_eq!(params.to_string(), "ln=12,r=8,p=2");
+}
+
+#[test]
+fn params_string_invalid() {
+    for (s, token) in [
+        ("ln=64,r=8,p=1", "ln=64"),
+        ("ln=300,r=8,p=1", "ln=300"),
+        ("ln=16,r=1,p=1", "ln=16"),
+        ("ln=15,r=0,p=1", "r=0"),
+        ("ln=15,r=8,p=0", "p=0"),
+        ("ln=15,r=8,p=1,l=9", "l=9"),
+        ("ln=15;r=8,p=1", "ln=15;r=8"),
+        ("ln=15,,r=8,p=1", ""),
+        ("ln=15, r=8,p=1", " r=8"),
+        ("ln=15,r=8,p=1,", ""),
+        ("ln=15,r=+8,p=1", "r=+8"),
+        ("ln=15,r=8,p=99999999999", "p=99999999999"),
+        ("ln=15,r=8,p=1,ln=16", "ln=16"),
+        ("ln=15,r=8,x=1", "x=1"),
+        ("", ""),
+    ] {
+        let err = s.parse::<Params>().unwrap_err();
+        assert_eq!(err.token(), token, "{}", s);
+    }
+
+    // defaulted parameters are reported with their value
+    assert_eq!("ln=17,r=1".parse::<Params>().unwrap_err().token(), "ln=17");
+    assert_eq!("r=1".parse::<Params>().unwrap_err().token(), "ln=17");
+
+    assert_eq!(
+        "ln=15,r=0,p=1".parse::<Params>().unwrap_err().to_string(),
+        "invalid scrypt parameter `r=0`: expected greater than 0"
+    );
+    assert_eq!(
+        "ln=15;r=8".parse::<Params>().unwrap_err().to_string(),
+        "invalid scrypt parameter `ln=15;r=8`: expected a decimal integer"
+    );
+}
+
 /// Test vector from passlib:
 /// <https://passlib.readthedocs.io/en/stable/lib/passlib.hash.scrypt.html>
 #[cfg(feature = "simple")]
@@ -95,9 +243,557 @@ fn simple_verify_password() {
     assert_eq!(Scrypt.verify_password(password.as_bytes(), &hash), Ok(()));
 }
 
+#[cfg(feature = "simple")]
+#[test]
+fn simple_verify_password_timing_safe() {
+    let hash = PasswordHash::new(EXAMPLE_PASSWORD_HASH).unwrap();
+    assert_eq!(
+        Scrypt.verify_password_timing_safe(b"password", &hash),
+        Ok(())
+    );
+    assert_eq!(
+        Scrypt.verify_password_timing_safe(b"wrong", &hash),
+        Err(password_hash::Error::Password)
+    );
+
+    // Outputs differing in their first or last byte only are rejected
+    let output = hash.hash.unwrap();
+    for i in [0, output.len() - 1] {
+        let mut bytes = output.as_bytes().to_vec();
+        bytes[i] ^= 1;
+
+        let tampered = PasswordHash {
+            hash: Some(Output::new(&bytes).unwrap()),
+            ..hash.clone()
+        };
+        assert_eq!(
+            Scrypt.verify_password_timing_safe(b"password", &tampered),
+            Err(password_hash::Error::Password)
+        );
+    }
+}
+
+/// [`EXAMPLE_PASSWORD_HASH`] with `N` given as `n` instead of `ln`.
+#[cfg(feature = "simple")]
+const EXAMPLE_PASSWORD_HASH_N: &str =
+    "$scrypt$n=65536,r=8,p=1$aM15713r3Xsvxbi31lqr1Q$nFNh2CVHVjNldFVKDHDlm4CbdRSCdEBsjjJxD+iCs5E";
+
+#[cfg(feature = "simple")]
+#[test]
+fn simple_verify_password_n() {
+    let hash = PasswordHash::new(EXAMPLE_PASSWORD_HASH_N).unwrap();
+    assert_eq!(Params::try_from(&hash).unwrap().log_n(), 16);
+    assert_eq!(Scrypt.verify_password(b"password", &hash), Ok(()));
+    assert!(Scrypt.verify_password(b"wrong", &hash).is_err());
+}
+
+#[cfg(feature = "simple")]
+#[test]
+fn simple_params_n() {
+    use password_hash::{PasswordHasher, SaltString};
+
+    fn params(s: &str) -> password_hash::Result<Params> {
+        Params::try_from(&PasswordHash::new(s).unwrap())
+    }
+
+    assert_eq!(
+        params("$scrypt$n=1024,ln=10,r=8,p=1$c2FsdA")
+            .unwrap()
+            .log_n(),
+        10
+    );
+    assert_eq!(params("$scrypt$r=8,p=1,n=2$c2FsdA").unwrap().log_n(), 1);
+
+    // conflicting `ln` and `n`
+    assert!(params("$scrypt$ln=10,n=2048,r=8,p=1$c2FsdA").is_err());
+
+    // `n` must be a power of two greater than 1
+    for n in ["0", "1", "1000", "4294967295"] {
+        assert!(params(&format!("$scrypt$n={},r=8,p=1$c2FsdA", n)).is_err());
+    }
+
+    // `ln` is emitted on output
+    let salt = SaltString::from_b64("aM15713r3Xsvxbi31lqr1Q").unwrap();
+    let params = params("$scrypt$n=1024,r=8,p=1$c2FsdA").unwrap();
+    let hash = Scrypt
+        .hash_password_customized(b"password", None, None, params, &salt)
+        .unwrap();
+    assert!(hash.to_string().starts_with("$scrypt$ln=10,r=8,p=1$"));
+}
+
+/// Hashes generated by passlib, which encodes `+` as `.` in salts and
+/// hashes. The first one is [`EXAMPLE_PASSWORD_HASH`] as output by passlib
+/// itself and the second one is from passlib's test suite.
+#[cfg(feature = "simple")]
+const PASSLIB_HASHES: [(&str, &str); 2] = [
+    (
+        "password",
+        "$scrypt$ln=16,r=8,p=1$aM15713r3Xsvxbi31lqr1Q$nFNh2CVHVjNldFVKDHDlm4CbdRSCdEBsjjJxD.iCs5E",
+    ),
+    (
+        "test",
+        "$scrypt$ln=8,r=8,p=1$wlhLyXmP8b53bm1NKYVQqg$mTpvG8lzuuDk.DWz8HZIB6Vum6erDuUm0As5yU.VxWA",
+    ),
+];
+
+#[cfg(feature = "simple")]
+#[test]
+fn simple_verify_passlib() {
+    for (password, hash) in PASSLIB_HASHES {
+        assert!(PasswordHash::new(hash).is_err());
+        assert_eq!(
+            Scrypt.verify_password_str(password.as_bytes(), hash),
+            Ok(())
+        );
+        assert_eq!(
+            Scrypt.verify_password_str(b"wrong", hash),
+            Err(Error::PasswordHash(password_hash::Error::Password))
+        );
+    }
+
+    // standard alphabet and parameters in any order
+    assert_eq!(
+        Scrypt.verify_password_str(b"password", EXAMPLE_PASSWORD_HASH),
+        Ok(())
+    );
+    assert_eq!(
+        Scrypt.verify_password_str(
+            b"test",
+            "$scrypt$p=1,ln=8,r=8$wlhLyXmP8b53bm1NKYVQqg$mTpvG8lzuuDk+DWz8HZIB6Vum6erDuUm0As5yU+VxWA"
+        ),
+        Ok(())
+    );
+}
+
+#[cfg(feature = "simple")]
+#[test]
+fn simple_reject_corrupted_passlib() {
+    // one character of the hash changed
+    let hash =
+        "$scrypt$ln=8,r=8,p=1$wlhLyXmP8b53bm1NKYVQqg$mTpvG9lzuuDk.DWz8HZIB6Vum6erDuUm0As5yU.VxWA";
+    assert_eq!(
+        Scrypt.verify_password_str(b"test", hash),
+        Err(Error::PasswordHash(password_hash::Error::Password))
+    );
+
+    // too short, non-Base64 and missing hashes
+    for hash in [
+        "$scrypt$ln=8,r=8,p=1$wlhLyXmP8b53bm1NKYVQqg$mTpvG8lz",
+        "$scrypt$ln=8,r=8,p=1$wlhLyXmP8b53bm1NKYVQqg$mTpvG8lzuuDk*DWz8HZIB6Vum6erDuUm0As5yU.VxWA",
+        "$scrypt$ln=8,r=8,p=1$wlhLyXmP8b53bm1NKYVQqg",
+    ] {
+        assert!(
+            Scrypt.verify_password_str(b"test", hash).is_err(),
+            "{}",
+            hash
+        );
+    }
+}
+
 #[cfg(feature = "simple")]
 #[test]
 fn simple_reject_incorrect_password() {
     let hash = PasswordHash::new(EXAMPLE_PASSWORD_HASH).unwrap();
     assert!(Scrypt.verify_password(b"invalid", &hash).is_err());
 }
+
+#[test]
+fn progress_matches_scrypt() {
+    let params = Params::new(10, 8, 2, 32).unwrap();
+    let mut expected = [0u8; 32];
+    scrypt(b"password", b"salt", &params, &mut expected).unwrap();
+
+    let mut calls = 0;
+    let mut last = None;
+    let mut output = [0u8; 32];
+    scrypt_with_progress(
+        b"password",
+        b"salt",
+        &params,
+        &mut output,
+        100,
+        |progress| {
+            calls += 1;
+            last = Some(progress);
+            ControlFlow::Continue(())
+        },
+    )
+    .unwrap();
+
+    assert_eq!(output, expected);
+
+    // 2 * N * p = 4096 BlockMix operations, reported every 100 plus at the end
+    assert_eq!(calls, 41);
+    assert_eq!(
+        last,
+        Some(Progress {
+            completed: 4096,
+            total: 4096
+        })
+    );
+}
+
+#[test]
+fn progress_cancel() {
+    let params = Params::new(14, 8, 1, 32).unwrap();
+    let mut calls = 0;
+    let mut output = [0u8; 32];
+    let res = scrypt_with_progress(
+        b"password",
+        b"salt",
+        &params,
+        &mut output,
+        Progress::DEFAULT_INTERVAL,
+        |progress| {
+            calls += 1;
+            assert_eq!(progress.completed, Progress::DEFAULT_INTERVAL);
+            ControlFlow::Break(())
+        },
+    );
+
+    assert_eq!(res, Err(Error::Cancelled));
+    assert_eq!(calls, 1);
+    assert_eq!(output, [0u8; 32]);
+}
+
+#[test]
+fn libsodium_presets() {
+    let presets = [
+        (Params::INTERACTIVE, 524288, 16777216, 14),
+        (Params::SENSITIVE, 33554432, 1073741824, 20),
+    ];
+
+    for (preset, opslimit, memlimit, log_n) in presets {
+        assert_eq!((preset.log_n(), preset.r(), preset.p()), (log_n, 8, 1));
+
+        let params = Params::from_libsodium(opslimit, memlimit).unwrap();
+        assert_eq!(
+            (params.log_n(), params.r(), params.p()),
+            (preset.log_n(), preset.r(), preset.p())
+        );
+    }
+}
+
+#[test]
+fn libsodium_limits() {
+    // (opslimit, memlimit) => (log_n, r, p), as computed by libsodium's `pickparams`
+    let cases = [
+        (0, 16777216, (10, 8, 1)),
+        (32768, 16777216, (10, 8, 1)),
+        (1048576, 1048576, (10, 8, 32)),
+        (524288, 1 << 30, (14, 8, 1)),
+        (1 << 30, 16777216, (14, 8, 2048)),
+    ];
+
+    for (opslimit, memlimit, expected) in cases {
+        let params = Params::from_libsodium(opslimit, memlimit).unwrap();
+        assert_eq!((params.log_n(), params.r(), params.p()), expected);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn recommended_for_duration() {
+    use std::time::Duration;
+
+    let params = Params::recommended_for(Duration::ZERO);
+    assert_eq!((params.log_n(), params.r(), params.p()), (10, 8, 1));
+
+    let params = Params::recommended_for(Duration::from_millis(50));
+    assert!((10..=20).contains(&params.log_n()));
+    assert_eq!(params.r(), 8);
+    assert!(params.p() >= 1);
+    assert!(Params::new(
+        params.log_n(),
+        params.r(),
+        params.p(),
+        Params::RECOMMENDED_LEN
+    )
+    .is_ok());
+}
+
+#[test]
+fn params_from_n() {
+    let params = Params::from_n(16384, 8, 1).unwrap();
+    let expected = Params::new(14, 8, 1, Params::RECOMMENDED_LEN).unwrap();
+    assert_eq!(params.log_n(), expected.log_n());
+    assert_eq!((params.n(), params.r(), params.p()), (16384, 8, 1));
+
+    let mut output = [0u8; 32];
+    let mut expected_output = [0u8; 32];
+    scrypt(b"password", b"salt", &params, &mut output).unwrap();
+    scrypt(b"password", b"salt", &expected, &mut expected_output).unwrap();
+    assert_eq!(output, expected_output);
+
+    assert_eq!(Params::from_n(2, 1, 1).unwrap().log_n(), 1);
+    assert_eq!(Params::new(20, 8, 1, 32).unwrap().n(), 1 << 20);
+}
+
+#[test]
+fn params_from_n_invalid() {
+    assert_eq!(
+        Params::from_n(0, 8, 1).unwrap_err(),
+        InvalidN::NotPowerOfTwo(0)
+    );
+    assert_eq!(Params::from_n(1, 8, 1).unwrap_err(), InvalidN::TooSmall(1));
+    assert_eq!(
+        Params::from_n(3, 8, 1).unwrap_err(),
+        InvalidN::NotPowerOfTwo(3)
+    );
+    assert_eq!(
+        Params::from_n(16383, 8, 1).unwrap_err().to_string(),
+        "scrypt parameter N must be a power of two, got 16383"
+    );
+
+    // N < 2^(16 * r) and the other conditions of `Params::new` still apply
+    assert_eq!(
+        Params::from_n(1 << 16, 1, 1).unwrap_err(),
+        InvalidN::InvalidParams(Params::new(16, 1, 1, 32).unwrap_err())
+    );
+    assert_eq!(
+        Params::from_n(1024, 0, 1).unwrap_err(),
+        InvalidN::InvalidParams(Params::new(10, 0, 1, 32).unwrap_err())
+    );
+
+    // the top of the u64 range
+    assert_eq!(
+        Params::from_n(1 << 63, 8, 1).unwrap_err(),
+        InvalidN::InvalidParams(Params::new(63, 8, 1, 32).unwrap_err())
+    );
+    for n in [(1 << 63) + 1, u64::MAX - 1, u64::MAX] {
+        assert_eq!(
+            Params::from_n(n, 8, 1).unwrap_err(),
+            InvalidN::NotPowerOfTwo(n)
+        );
+    }
+}
+
+#[cfg(feature = "simple")]
+#[test]
+fn simple_output_len_round_trip() {
+    use password_hash::{PasswordHasher, SaltString};
+
+    let salt = SaltString::from_b64("aM15713r3Xsvxbi31lqr1Q").unwrap();
+
+    for len in [16, 32, 64] {
+        let params = Params::new(4, 8, 1, len).unwrap();
+        let hash = Scrypt
+            .hash_password_customized(b"password", None, None, params, &salt)
+            .unwrap()
+            .to_string();
+
+        assert_eq!(hash.contains(",l="), len != Params::RECOMMENDED_LEN);
+
+        let parsed = PasswordHash::new(&hash).unwrap();
+        assert_eq!(parsed.hash.unwrap().len(), len);
+        assert_eq!(Scrypt.verify_password(b"password", &parsed), Ok(()));
+        assert!(Scrypt.verify_password(b"invalid", &parsed).is_err());
+    }
+}
+
+#[cfg(feature = "simple")]
+#[test]
+fn simple_reject_invalid_output_len() {
+    // `l` larger than `Output::MAX_LENGTH`
+    let hash = PasswordHash::new("$scrypt$ln=4,r=8,p=1,l=1000$aM15713r3Xsvxbi31lqr1Q").unwrap();
+    assert!(Params::try_from(&hash).is_err());
+
+    // `l` disagreeing with the length of the stored hash
+    let hash = PasswordHash::new(
+        "$scrypt$ln=16,r=8,p=1,l=64$aM15713r3Xsvxbi31lqr1Q$nFNh2CVHVjNldFVKDHDlm4CbdRSCdEBsjjJxD+iCs5E",
+    )
+    .unwrap();
+    assert!(Params::try_from(&hash).is_err());
+    assert!(Scrypt.verify_password(b"password", &hash).is_err());
+}
+
+#[cfg(feature = "simple")]
+#[test]
+fn simple_memory_limit() {
+    use password_hash::{PasswordHasher, SaltString};
+    use scrypt::MemoryLimit;
+
+    // ln=30 would need 128 GiB: rejected before allocating anything
+    let hash = PasswordHash::new(
+        "$scrypt$ln=30,r=8,p=1$aM15713r3Xsvxbi31lqr1Q$nFNh2CVHVjNldFVKDHDlm4CbdRSCdEBsjjJxD+iCs5E",
+    )
+    .unwrap();
+    assert_eq!(
+        Scrypt.verify_password(b"password", &hash),
+        Err(password_hash::Error::ParamValueInvalid(
+            password_hash::errors::InvalidValue::TooLong
+        ))
+    );
+    assert_eq!(
+        Scrypt.verify_password_with_memory_limit(b"password", &hash, MemoryLimit::DEFAULT),
+        Err(Error::MemoryLimitExceeded {
+            required: (1 << 30) * 1024 + 3 * 1024,
+            limit: 2 << 30,
+        })
+    );
+
+    let hash = PasswordHash::new(EXAMPLE_PASSWORD_HASH).unwrap();
+    assert_eq!(
+        Scrypt.verify_password_with_memory_limit(b"password", &hash, MemoryLimit::new(1024)),
+        Err(Error::MemoryLimitExceeded {
+            required: (1 << 16) * 1024 + 3 * 1024,
+            limit: 1024,
+        })
+    );
+    assert_eq!(
+        Scrypt.verify_password_with_memory_limit(b"password", &hash, MemoryLimit::DEFAULT),
+        Ok(())
+    );
+    assert_eq!(
+        Scrypt.verify_password_with_memory_limit(b"invalid", &hash, MemoryLimit::UNLIMITED),
+        Err(Error::PasswordHash(password_hash::Error::Password))
+    );
+
+    // Hashing takes its limit from the caller
+    let salt = SaltString::from_b64("aM15713r3Xsvxbi31lqr1Q").unwrap();
+    let params = Params::new(10, 8, 1, 32).unwrap();
+    assert_eq!(
+        Scrypt.hash_password_with_memory_limit(b"password", params, &salt, MemoryLimit::new(1024)),
+        Err(Error::MemoryLimitExceeded {
+            required: (1 << 10) * 1024 + 3 * 1024,
+            limit: 1024,
+        })
+    );
+    assert_eq!(
+        Scrypt
+            .hash_password_with_memory_limit(b"password", params, &salt, MemoryLimit::UNLIMITED)
+            .unwrap(),
+        Scrypt
+            .hash_password_customized(b"password", None, None, params, &salt)
+            .unwrap()
+    );
+}
+
+#[cfg(feature = "simple")]
+#[test]
+fn simple_params_policy() {
+    use password_hash::SaltString;
+    use scrypt::{MemoryLimit, ParamsPolicy};
+    use std::time::{Duration, Instant};
+
+    // p=2^20 only needs 132 MiB, but would take minutes to compute
+    let hash = PasswordHash::new(
+        "$scrypt$ln=15,r=1,p=1048576$aM15713r3Xsvxbi31lqr1Q$nFNh2CVHVjNldFVKDHDlm4CbdRSCdEBsjjJxD+iCs5E",
+    )
+    .unwrap();
+
+    let start = Instant::now();
+    assert_eq!(
+        Scrypt.verify_password(b"password", &hash),
+        Err(password_hash::Error::ParamValueInvalid(
+            password_hash::errors::InvalidValue::TooLong
+        ))
+    );
+    let err = Scrypt
+        .verify_password_with_policy(b"password", &hash, ParamsPolicy::DEFAULT)
+        .unwrap_err();
+    assert!(start.elapsed() < Duration::from_millis(50));
+
+    assert_eq!(
+        err,
+        Error::ParamsPolicyViolation {
+            param: "p",
+            value: 1 << 20,
+            max: 16,
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "scrypt parameter p = 1048576 exceeds the maximum of 16"
+    );
+
+    let hash = PasswordHash::new(EXAMPLE_PASSWORD_HASH).unwrap();
+    let policy = ParamsPolicy {
+        max_ln: 15,
+        ..ParamsPolicy::DEFAULT
+    };
+    assert_eq!(
+        Scrypt.verify_password_with_policy(b"password", &hash, policy),
+        Err(Error::ParamsPolicyViolation {
+            param: "ln",
+            value: 16,
+            max: 15,
+        })
+    );
+
+    let policy = ParamsPolicy {
+        max_r: 4,
+        ..ParamsPolicy::UNLIMITED
+    };
+    assert!(matches!(
+        Scrypt.verify_password_with_policy(b"password", &hash, policy),
+        Err(Error::ParamsPolicyViolation { param: "r", .. })
+    ));
+
+    assert_eq!(
+        Scrypt.verify_password_with_policy(b"password", &hash, ParamsPolicy::DEFAULT),
+        Ok(())
+    );
+
+    // The policy doesn't apply to the caller's own parameters
+    let salt = SaltString::from_b64("aM15713r3Xsvxbi31lqr1Q").unwrap();
+    let params = Params::new(4, 1, 17, 32).unwrap();
+    let hash = Scrypt
+        .hash_password_with_memory_limit(b"password", params, &salt, MemoryLimit::DEFAULT)
+        .unwrap();
+    assert!(matches!(
+        Scrypt.verify_password_with_policy(b"password", &hash, ParamsPolicy::DEFAULT),
+        Err(Error::ParamsPolicyViolation { param: "p", .. })
+    ));
+    assert_eq!(
+        Scrypt.verify_password_with_policy(b"password", &hash, ParamsPolicy::UNLIMITED),
+        Ok(())
+    );
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn verify_batch() {
+    use password_hash::{PasswordHasher, SaltString};
+    use scrypt::MemoryLimit;
+
+    let salt = SaltString::from_b64("aM15713r3Xsvxbi31lqr1Q").unwrap();
+    let hashes: Vec<String> = [(4, 1, 1), (10, 8, 1), (6, 4, 3), (12, 8, 1), (8, 2, 2)]
+        .iter()
+        .map(|&(log_n, r, p)| {
+            let params = Params::new(log_n, r, p, 32).unwrap();
+            Scrypt
+                .hash_password_customized(b"password", None, None, params, &salt)
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+
+    let mut entries = Vec::new();
+    for (i, hash) in hashes.iter().enumerate() {
+        let hash = PasswordHash::new(hash).unwrap();
+        let password: &[u8] = if i % 2 == 0 { b"password" } else { b"invalid" };
+        entries.push((password, hash.clone()));
+        entries.push((b"password".as_slice(), hash));
+    }
+
+    // An entry which doesn't fit the limit on its own, even though the other ones do
+    let too_big = PasswordHash::new(EXAMPLE_PASSWORD_HASH).unwrap();
+    entries.push((b"password".as_slice(), too_big));
+
+    // Enough for only one `ln=12, r=8` entry at a time
+    let limit = MemoryLimit::new(6 << 20);
+    let results = Scrypt.verify_batch(&entries, limit);
+    assert_eq!(results.len(), entries.len());
+
+    for ((password, hash), result) in entries.iter().zip(&results) {
+        let expected = Scrypt.verify_password_with_memory_limit(password, hash, limit);
+        assert_eq!(*result, expected);
+    }
+
+    assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 8);
+    assert!(matches!(
+        results.last(),
+        Some(Err(Error::MemoryLimitExceeded { .. }))
+    ));
+}
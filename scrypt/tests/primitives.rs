@@ -0,0 +1,86 @@
+//! Intermediate test vectors from RFC 7914 §8-§10.
+//!
+//! The BlockMix and ROMix vectors are the first steps of the scrypt test
+//! vector `P = "", S = "", N = 16, r = 1, p = 1`.
+
+use hex_literal::hex;
+use scrypt::primitives::{block_mix, ro_mix, salsa20_8};
+
+/// Input of the BlockMix and ROMix vectors: `B[0] || B[1]`
+const B: [u8; 128] = hex!(
+    "f7ce0b653d2d72a4108cf5abe912ffdd777616dbbb27a70e8204f3ae2d0f6fad"
+    "89f68f4811d1e87bcc3bd7400a9ffd29094f0184639574f39ae5a1315217bcd7"
+    "894991447213bb226c25b54da86370fbcd984380374666bb8ffcb5bf40c254b0"
+    "67d27c51ce4ad5fed829c90b505a571b7f4d1cad6a523cda770e67bceaaf7e89"
+);
+
+#[test]
+fn rfc7914_salsa20_8() {
+    let mut block = hex!(
+        "7e879a214f3ec9867ca940e641718f26baee555b8c61c1b50df846116dcd3b1d"
+        "ee24f319df9b3d8514121e4b5ac5aa3276021d2909c74829edebc68db8b8c25e"
+    );
+    salsa20_8(&mut block);
+    assert_eq!(
+        block,
+        hex!(
+            "a41f859c6608cc993b81cacb020cef05044b2181a2fd337dfd7b1c6396682f29"
+            "b4393168e3c9e6bcfe6bc5b7a06d96bae424cc102c91745c24ad673dc7618f81"
+        )
+    );
+}
+
+#[test]
+fn rfc7914_block_mix() {
+    let mut output = [0u8; 128];
+    block_mix(&B, &mut output);
+    assert_eq!(
+        output,
+        hex!(
+            "a41f859c6608cc993b81cacb020cef05044b2181a2fd337dfd7b1c6396682f29"
+            "b4393168e3c9e6bcfe6bc5b7a06d96bae424cc102c91745c24ad673dc7618f81"
+            "20edc975323881a80540f64c162dcd3c21077cfe5f8d5fe2b1a4168f953678b7"
+            "7d3b3d803b60e4ab920996e59b4d53b65d2a225877d5edf5842cb9f14eefe425"
+        )
+    );
+}
+
+#[test]
+fn rfc7914_ro_mix() {
+    let mut b = B;
+    let mut v = vec![0u32; 16 * 128 / 4];
+    let mut xy = vec![0u32; 128 / 2];
+    ro_mix(&mut b, &mut v, &mut xy, 16);
+    assert_eq!(
+        b,
+        hex!(
+            "79ccc193629debca047f0b70604bf6b62ce3dd4a9626e355fafc6198e6ea2b46"
+            "d58413673b99b029d665c357601fb426a0b2f4bba200ee9f0a43d19b571a9c71"
+            "ef1142e65d5a266fddca832ce59faa7cac0b9cf1be2bffca300d01ee387619c4"
+            "ae12fd4438f203a0e4e1c47ec314861f4e9087cb33396a6873e8f9d2539a4b8e"
+        )
+    );
+
+    // working memory can be reused without clearing it
+    let mut b = B;
+    ro_mix(&mut b, &mut v, &mut xy, 16);
+    assert_eq!(b[..4], hex!("79ccc193"));
+}
+
+#[test]
+#[should_panic]
+fn block_mix_rejects_partial_block() {
+    block_mix(&[0u8; 64], &mut [0u8; 64]);
+}
+
+#[test]
+#[should_panic]
+fn ro_mix_rejects_short_v() {
+    let mut b = B;
+    ro_mix(
+        &mut b,
+        &mut vec![0u32; 15 * 128 / 4],
+        &mut vec![0u32; 128 / 2],
+        16,
+    );
+}
@@ -0,0 +1,96 @@
+//! Compare against a straightforward, byte-oriented implementation of
+//! RFC 7914 built on the `salsa20` crate.
+
+use pbkdf2::pbkdf2_hmac;
+use salsa20::{
+    cipher::{typenum::U4, StreamCipherCore},
+    SalsaCore,
+};
+use scrypt::{scrypt, Params};
+use sha2::Sha256;
+
+fn salsa20_8(input: &[u8]) -> [u8; 64] {
+    let mut state = [0u32; 16];
+    for (c, s) in input.chunks_exact(4).zip(state.iter_mut()) {
+        *s = u32::from_le_bytes(c.try_into().unwrap());
+    }
+
+    let mut output = [0u8; 64];
+    SalsaCore::<U4>::from_raw_state(state).write_keystream_block((&mut output).into());
+    output
+}
+
+fn block_mix(input: &[u8]) -> Vec<u8> {
+    let blocks = input.len() / 64;
+    let mut x = input[input.len() - 64..].to_vec();
+    let mut y = Vec::with_capacity(input.len());
+
+    for chunk in input.chunks(64) {
+        let t: Vec<u8> = x.iter().zip(chunk).map(|(a, b)| a ^ b).collect();
+        x = salsa20_8(&t).to_vec();
+        y.push(x.clone());
+    }
+
+    let even = (0..blocks).step_by(2);
+    let odd = (1..blocks).step_by(2);
+    even.chain(odd).flat_map(|i| y[i].clone()).collect()
+}
+
+fn ro_mix(b: &[u8], n: usize) -> Vec<u8> {
+    let mut x = b.to_vec();
+    let mut v = Vec::with_capacity(n);
+
+    for _ in 0..n {
+        v.push(x.clone());
+        x = block_mix(&x);
+    }
+
+    for _ in 0..n {
+        let j = u32::from_le_bytes(x[x.len() - 64..x.len() - 60].try_into().unwrap()) as usize;
+        let t: Vec<u8> = x.iter().zip(&v[j % n]).map(|(a, b)| a ^ b).collect();
+        x = block_mix(&t);
+    }
+
+    x
+}
+
+fn reference_scrypt(password: &[u8], salt: &[u8], log_n: u8, r: u32, p: u32, output: &mut [u8]) {
+    let mut b = vec![0u8; 128 * r as usize * p as usize];
+    pbkdf2_hmac::<Sha256>(password, salt, 1, &mut b);
+
+    let b: Vec<u8> = b
+        .chunks(128 * r as usize)
+        .flat_map(|chunk| ro_mix(chunk, 1 << log_n))
+        .collect();
+
+    pbkdf2_hmac::<Sha256>(password, &b, 1, output);
+}
+
+#[test]
+fn matches_reference() {
+    // xorshift64 so the test is deterministic without pulling in `rand`
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+
+    for _ in 0..32 {
+        let log_n = 1 + (next() % 10) as u8;
+        let r = 1 + (next() % 4) as u32;
+        let p = 1 + (next() % 3) as u32;
+        let password = next().to_le_bytes();
+        let salt = next().to_be_bytes();
+
+        let params = Params::new(log_n, r, p, 32).unwrap();
+        let mut expected = [0u8; 32];
+        reference_scrypt(&password, &salt, log_n, r, p, &mut expected);
+
+        let mut actual = [0u8; 32];
+        scrypt(&password, &salt, &params, &mut actual).unwrap();
+
+        assert_eq!(expected, actual, "log_n={} r={} p={}", log_n, r, p);
+    }
+}
@@ -0,0 +1,151 @@
+//! Headers of files encrypted with `scrypt enc`.
+
+#![cfg(feature = "scryptenc")]
+
+use hex_literal::hex;
+use scrypt::{
+    errors::{Error, InvalidHeader},
+    scryptenc::Header,
+    MemoryLimit, Params,
+};
+
+/// Header of a file encrypted with password `password` by `scrypt enc`
+/// version 1.3.1 (`log_n = 10`, `r = 8`, `p = 1`).
+const HEADER: [u8; 96] = hex!(
+    "736372797074000a0000000800000001"
+    "e2240d3c238f8a117dd67848a2d6ecc91fcee8f23db29fd0bfc5053afedc2333"
+    "9960c418d027b74d088519d455a7a56b"
+    "745d21c892379a9dd3c1b1f1616e15e2caa13cf39a50d06ee88f21e650bf4932"
+);
+
+#[test]
+fn parse() {
+    let header = Header::parse(&HEADER).unwrap();
+    let params = header.params();
+    assert_eq!((params.log_n(), params.r(), params.p()), (10, 8, 1));
+    assert_eq!(header.salt()[..], HEADER[16..48]);
+
+    // trailing data is ignored
+    let mut file = HEADER.to_vec();
+    file.extend_from_slice(b"ciphertext");
+    assert!(Header::parse(&file).is_ok());
+}
+
+#[test]
+fn check_mac() {
+    let header = Header::parse(&HEADER).unwrap();
+
+    let key = header.derive_key(b"password").unwrap();
+    assert_eq!(Header::check_mac(&HEADER, &key), Ok(()));
+
+    let key = header.derive_key(b"wrong").unwrap();
+    assert_eq!(Header::check_mac(&HEADER, &key), Err(InvalidHeader::Mac));
+}
+
+#[test]
+fn encode() {
+    let header = Header::parse(&HEADER).unwrap();
+    let key = header.derive_key(b"password").unwrap();
+    assert_eq!(header.encode(&key), HEADER);
+
+    let params = Params::new(4, 2, 3, 16).unwrap();
+    let header = Header::new(&params, [0x42; 32]).unwrap();
+    let key = header.derive_key(b"password").unwrap();
+    let bytes = header.encode(&key);
+
+    let decoded = Header::parse(&bytes).unwrap();
+    let params = decoded.params();
+    assert_eq!((params.log_n(), params.r(), params.p()), (4, 2, 3));
+    assert_eq!(decoded.salt(), &[0x42; 32]);
+    assert_eq!(decoded.derive_key(b"password").unwrap(), key);
+    assert_eq!(Header::check_mac(&bytes, &key), Ok(()));
+}
+
+#[test]
+fn derive_key_memory_limit() {
+    let header = Header::parse(&HEADER).unwrap();
+    let required = header.params().mem_required();
+
+    let key = header
+        .derive_key_with_memory_limit(b"password", MemoryLimit::new(required))
+        .unwrap();
+    assert_eq!(header.derive_key(b"password"), Ok(key));
+
+    assert_eq!(
+        header.derive_key_with_memory_limit(b"password", MemoryLimit::new(required - 1)),
+        Err(Error::MemoryLimitExceeded {
+            required,
+            limit: required - 1
+        })
+    );
+
+    // `N = 2^32` requires 4 TiB with `r = 8`
+    let header = Header::parse(&modified(7, &[32])).unwrap();
+    assert!(matches!(
+        header.derive_key(b"password"),
+        Err(Error::MemoryLimitExceeded { .. })
+    ));
+}
+
+#[test]
+fn new_invalid_params() {
+    for (log_n, r) in [(0, 8), (33, 8)] {
+        let params = Params::new(log_n, r, 1, 32).unwrap();
+        assert_eq!(
+            Header::new(&params, [0x42; 32]).unwrap_err(),
+            InvalidHeader::Params
+        );
+    }
+}
+
+/// Replace `bytes[range]` of [`HEADER`] and fix up its checksum.
+fn modified(offset: usize, bytes: &[u8]) -> [u8; 96] {
+    use sha2::{Digest, Sha256};
+
+    let mut header = HEADER;
+    header[offset..offset + bytes.len()].copy_from_slice(bytes);
+    let checksum = Sha256::digest(&header[..48]);
+    header[48..64].copy_from_slice(&checksum[..16]);
+    header
+}
+
+#[test]
+fn reject_invalid() {
+    assert_eq!(
+        Header::parse(&HEADER[..95]).unwrap_err(),
+        InvalidHeader::Length
+    );
+    assert_eq!(
+        Header::parse(&modified(0, b"Scrypt")).unwrap_err(),
+        InvalidHeader::Magic
+    );
+    assert_eq!(
+        Header::parse(&modified(6, &[1])).unwrap_err(),
+        InvalidHeader::Version(1)
+    );
+
+    // log_n = 0, log_n = 33, r = 0, p = 0
+    for (offset, bytes) in [
+        (7, &[0][..]),
+        (7, &[33][..]),
+        (8, &[0, 0, 0, 0][..]),
+        (12, &[0, 0, 0, 0][..]),
+    ] {
+        assert_eq!(
+            Header::parse(&modified(offset, bytes)).unwrap_err(),
+            InvalidHeader::Params
+        );
+    }
+
+    let mut header = HEADER;
+    header[20] ^= 1;
+    assert_eq!(Header::parse(&header).unwrap_err(), InvalidHeader::Checksum);
+
+    // a consistent header whose salt no longer matches the MAC
+    let header = modified(20, &[0]);
+    let key = Header::parse(&header)
+        .unwrap()
+        .derive_key(b"password")
+        .unwrap();
+    assert_eq!(Header::check_mac(&header, &key), Err(InvalidHeader::Mac));
+}
@@ -0,0 +1,83 @@
+//! Serialization of `Params` with the `serde` feature.
+
+#![cfg(feature = "serde")]
+
+use scrypt::Params;
+
+fn assert_params_eq(a: &Params, b: &Params) {
+    assert_eq!((a.log_n(), a.r(), a.p()), (b.log_n(), b.r(), b.p()));
+}
+
+#[test]
+fn json_round_trip() {
+    let params = Params::new(15, 8, 2, Params::RECOMMENDED_LEN).unwrap();
+
+    let json = serde_json::to_string(&params).unwrap();
+    assert_eq!(json, r#"{"log_n":15,"r":8,"p":2}"#);
+    assert_params_eq(&serde_json::from_str(&json).unwrap(), &params);
+}
+
+#[test]
+fn json_round_trip_len() {
+    let params = Params::new(15, 8, 1, 64).unwrap();
+
+    let json = serde_json::to_string(&params).unwrap();
+    assert_eq!(json, r#"{"log_n":15,"r":8,"p":1,"len":64}"#);
+
+    let decoded: Params = serde_json::from_str(&json).unwrap();
+    assert_params_eq(&decoded, &params);
+    assert_eq!(serde_json::to_string(&decoded).unwrap(), json);
+}
+
+#[test]
+fn yaml_round_trip() {
+    let params = Params::INTERACTIVE;
+
+    let yaml = serde_yaml::to_string(&params).unwrap();
+    assert_eq!(yaml, "log_n: 14\nr: 8\np: 1\n");
+    assert_params_eq(&serde_yaml::from_str(&yaml).unwrap(), &params);
+}
+
+#[test]
+fn yaml_config() {
+    #[derive(serde::Deserialize)]
+    struct Config {
+        scrypt: Params,
+    }
+
+    let config: Config = serde_yaml::from_str("scrypt:\n  log_n: 17\n  r: 8\n  p: 1\n").unwrap();
+    assert_params_eq(&config.scrypt, &Params::recommended());
+}
+
+#[test]
+fn reject_log_n() {
+    let err = serde_json::from_str::<Params>(r#"{"log_n":99,"r":8,"p":1}"#).unwrap_err();
+    assert!(err.to_string().contains("`log_n`: 99"), "{}", err);
+
+    let err = serde_yaml::from_str::<Params>("log_n: 99\nr: 8\np: 1\n").unwrap_err();
+    assert!(err.to_string().contains("`log_n`: 99"), "{}", err);
+
+    // RFC 7914 requires N < 2^(16 * r)
+    let err = serde_json::from_str::<Params>(r#"{"log_n":16,"r":1,"p":1}"#).unwrap_err();
+    assert!(err.to_string().contains("`log_n`: 16"), "{}", err);
+}
+
+#[test]
+fn reject_other_fields() {
+    for (json, field) in [
+        (r#"{"log_n":10,"r":0,"p":1}"#, "`r`"),
+        (r#"{"log_n":10,"r":8,"p":0}"#, "`p`"),
+        (r#"{"log_n":10,"r":8,"p":1,"len":9}"#, "`len`"),
+        (r#"{"log_n":10,"r":8,"p":134217728}"#, "`p`"),
+    ] {
+        let err = serde_json::from_str::<Params>(json).unwrap_err();
+        assert!(err.to_string().contains(field), "{}: {}", json, err);
+    }
+}
+
+#[test]
+fn reject_malformed() {
+    assert!(serde_json::from_str::<Params>(r#"{"log_n":10,"r":8}"#).is_err());
+    assert!(serde_json::from_str::<Params>(r#"{"log_n":10,"r":8,"p":1,"n":1024}"#).is_err());
+    assert!(serde_json::from_str::<Params>(r#"{"log_n":300,"r":8,"p":1}"#).is_err());
+}
@@ -0,0 +1,88 @@
+use scrypt::{
+    errors::InvalidOutputLen,
+    scrypt,
+    session::{ScryptSession, Status},
+    Params,
+};
+
+/// Irregular slice sizes, including empty slices and slices crossing the
+/// boundaries between the two ROMix loops and between lanes.
+const SLICES: &[u64] = &[1, 7, 0, 129, 3, 1000, 2, 31, 0, 64, 5, 513];
+
+fn expected(params: &Params) -> [u8; 64] {
+    let mut output = [0u8; 64];
+    scrypt(b"password", b"NaCl", params, &mut output).unwrap();
+    output
+}
+
+#[test]
+fn matches_one_shot() {
+    for &(log_n, r, p) in &[(1, 1, 1), (4, 1, 3), (6, 2, 2), (10, 8, 1), (5, 3, 5)] {
+        let params = Params::new(log_n, r, p, 64).unwrap();
+        let mut session = ScryptSession::new(b"password", b"NaCl", &params);
+
+        let total = (1u64 << log_n) * 2 * u64::from(p);
+        let mut completed = 0;
+        for &blocks in SLICES.iter().cycle() {
+            match session.run_for(blocks) {
+                Status::Pending(progress) => {
+                    assert_eq!(progress.total, total);
+                    assert_eq!(progress.completed, completed + blocks);
+                    completed = progress.completed;
+                }
+                Status::Done => break,
+            }
+        }
+        assert_eq!(session.status(), Status::Done);
+        assert_eq!(session.run_for(1), Status::Done);
+
+        let mut output = [0u8; 64];
+        session.finish(&mut output).unwrap();
+        assert_eq!(output, expected(&params), "{:?}", (log_n, r, p));
+    }
+}
+
+#[test]
+fn finish_completes_remaining_work() {
+    let params = Params::new(6, 2, 2, 64).unwrap();
+
+    let mut output = [0u8; 64];
+    ScryptSession::new(b"password", b"NaCl", &params)
+        .finish(&mut output)
+        .unwrap();
+    assert_eq!(output, expected(&params));
+
+    let mut session = ScryptSession::new(b"password", b"NaCl", &params);
+    assert!(matches!(session.run_for(200), Status::Pending(_)));
+    let mut output = [0u8; 64];
+    session.finish(&mut output).unwrap();
+    assert_eq!(output, expected(&params));
+}
+
+#[test]
+fn initial_status() {
+    let params = Params::new(4, 1, 3, 64).unwrap();
+    let session = ScryptSession::new(b"password", b"NaCl", &params);
+    match session.status() {
+        Status::Pending(progress) => {
+            assert_eq!(progress.completed, 0);
+            assert_eq!(progress.total, 96);
+        }
+        Status::Done => panic!("session done before running"),
+    }
+}
+
+#[test]
+fn invalid_output_len() {
+    let params = Params::new(4, 1, 1, 64).unwrap();
+    let session = ScryptSession::new(b"password", b"NaCl", &params);
+    assert_eq!(session.finish(&mut []), Err(InvalidOutputLen));
+}
+
+#[test]
+fn drop_before_completion() {
+    let params = Params::new(6, 2, 2, 64).unwrap();
+    let mut session = ScryptSession::new(b"password", b"NaCl", &params);
+    session.run_for(77);
+    drop(session);
+}
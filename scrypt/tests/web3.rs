@@ -0,0 +1,138 @@
+#![cfg(feature = "web3")]
+
+use hex_literal::hex;
+use scrypt::{
+    errors::Error,
+    web3::{derive_key, derive_key_with_memory_limit, KdfParams},
+    MemoryLimit, Params,
+};
+use sha3::{Digest, Keccak256};
+
+/// Test vector from the Web3 Secret Storage Definition:
+/// <https://ethereum.org/en/developers/docs/data-structures-and-encoding/web3-secret-storage/#scrypt>
+const KEYSTORE: &str = r#"{
+    "crypto" : {
+        "cipher" : "aes-128-ctr",
+        "cipherparams" : {
+            "iv" : "83dbcc02d8ccb40e466191a123791e0e"
+        },
+        "ciphertext" : "d172bf743a674da9cdad04534d56926ef8358534d458fffccd4e6ad2fbde479c",
+        "kdf" : "scrypt",
+        "kdfparams" : {
+            "dklen" : 32,
+            "n" : 262144,
+            "p" : 8,
+            "r" : 1,
+            "salt" : "ab0c7876052600dd703518d6fc3fe8984592145b591fc8fb5c6d43190334ba19"
+        },
+        "mac" : "2103ac29920d71da29f15d75b4a16dbe95cfd7ff8faea1056c33131d846e3097"
+    },
+    "id" : "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+    "version" : 3
+}"#;
+
+const CIPHERTEXT: [u8; 32] =
+    hex!("d172bf743a674da9cdad04534d56926ef8358534d458fffccd4e6ad2fbde479c");
+const MAC: [u8; 32] = hex!("2103ac29920d71da29f15d75b4a16dbe95cfd7ff8faea1056c33131d846e3097");
+
+fn kdf_params() -> KdfParams {
+    let keystore: serde_json::Value = serde_json::from_str(KEYSTORE).unwrap();
+    serde_json::from_value(keystore["crypto"]["kdfparams"].clone()).unwrap()
+}
+
+/// The keystore MAC is `keccak256(key[16..32] || ciphertext)`.
+fn mac(key: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(&key[16..32]);
+    hasher.update(CIPHERTEXT);
+    hasher.finalize().into()
+}
+
+#[test]
+fn parse_kdf_params() {
+    let kdf = kdf_params();
+    assert_eq!(kdf.dklen, 32);
+    assert_eq!(kdf.n, 262144);
+    assert_eq!(kdf.p, 8);
+    assert_eq!(kdf.r, 1);
+    assert_eq!(
+        kdf.salt,
+        hex!("ab0c7876052600dd703518d6fc3fe8984592145b591fc8fb5c6d43190334ba19")
+    );
+
+    let params = Params::try_from(&kdf).unwrap();
+    assert_eq!((params.log_n(), params.r(), params.p()), (18, 1, 8));
+    assert_eq!(KdfParams::new(&params, kdf.salt.clone()), kdf);
+}
+
+#[test]
+fn derive_keystore_key() {
+    let kdf = kdf_params();
+
+    let key = derive_key(b"testpassword", &kdf).unwrap();
+    assert_eq!(key.len(), 32);
+    assert_eq!(mac(&key), MAC);
+
+    let key = derive_key(b"wrongpassword", &kdf).unwrap();
+    assert_ne!(mac(&key), MAC);
+}
+
+#[test]
+fn derive_keystore_key_memory_limit() {
+    let mut kdf = kdf_params();
+    kdf.n = 1 << 10;
+    let required = Params::try_from(&kdf).unwrap().mem_required();
+
+    let key = derive_key_with_memory_limit(b"testpassword", &kdf, MemoryLimit::new(required));
+    assert_eq!(key, derive_key(b"testpassword", &kdf));
+
+    assert_eq!(
+        derive_key_with_memory_limit(b"testpassword", &kdf, MemoryLimit::new(required - 1)),
+        Err(Error::MemoryLimitExceeded {
+            required,
+            limit: required - 1
+        })
+    );
+
+    // `n = 2^32` requires 4 TiB with `r = 8`
+    let mut kdf = kdf_params();
+    kdf.n = 1 << 32;
+    kdf.r = 8;
+    assert!(matches!(
+        derive_key(b"testpassword", &kdf),
+        Err(Error::MemoryLimitExceeded { .. })
+    ));
+}
+
+#[test]
+fn serialize_round_trip() {
+    let kdf = kdf_params();
+    let json = serde_json::to_string(&kdf).unwrap();
+    assert_eq!(
+        json,
+        r#"{"dklen":32,"n":262144,"p":8,"r":1,"salt":"ab0c7876052600dd703518d6fc3fe8984592145b591fc8fb5c6d43190334ba19"}"#
+    );
+    assert_eq!(serde_json::from_str::<KdfParams>(&json).unwrap(), kdf);
+}
+
+#[test]
+fn reject_invalid_kdf_params() {
+    let mut kdf = kdf_params();
+    kdf.n = 262143;
+    assert!(Params::try_from(&kdf).is_err());
+    assert!(matches!(
+        derive_key(b"testpassword", &kdf),
+        Err(Error::InvalidParams(_))
+    ));
+
+    let mut kdf = kdf_params();
+    kdf.n = 0;
+    assert!(Params::try_from(&kdf).is_err());
+
+    let mut kdf = kdf_params();
+    kdf.n = 1 << 33;
+    assert!(Params::try_from(&kdf).is_err());
+
+    let json = r#"{"dklen":32,"n":16384,"p":1,"r":8,"salt":"not hex"}"#;
+    assert!(serde_json::from_str::<KdfParams>(json).is_err());
+}
@@ -0,0 +1,289 @@
+//! `$y$` hashes generated by `crypt(3)` with libxcrypt 4.4.
+
+#![cfg(feature = "yescrypt")]
+
+use scrypt::{
+    errors::YescryptError,
+    yescrypt::{self, Hash, Mode, Params},
+    MemoryLimit,
+};
+
+/// Hashes of `password` with the settings of `crypt_gensalt("$y$", cost)`,
+/// for costs 1, 3, 5 (the default) and 7.
+const GENSALT_HASHES: &[&str] = &[
+    "$y$j75$/6k.2IU/5UE08g.1Bsk1E.$1fLhRx4S3PyIgtIxm2Yk0xKxaPETdqETPd3X3ZJikE9",
+    "$y$j7T$1EE/4Q.07ck0AoU1D.F2G.$LxY4pdeTDjl1z13GkX77JH5OZ/s8tUFry8NR0Yzx0H1",
+    "$y$j9T$3Mk/6YU09kE1Cw.2F6l2I.$GkUg7vJQR9/LyAdoJAOoJV1kbNLN2ohaF./mLqN9ouC",
+    "$y$jBT$5UE08g.1Bsk1E2V2HEF3K.$q9.4HJN9euEey3V3k1aBpgk.pA0wqFPJysADL3yLp/D",
+];
+
+/// Hashes with custom settings, as `(password, hash)`.
+const CUSTOM_HASHES: &[(&str, &str)] = &[
+    // Read-write: N = 1024, r = 8
+    (
+        "",
+        "$y$j75$saltsaltsaltsalt$be0TEvbyQ3RpHnd6nIEtraxfwH8NQ7boFpMFINFVi83",
+    ),
+    (
+        "password",
+        "$y$j75$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J.",
+    ),
+    (
+        "pleaseletmein",
+        "$y$j75$saltsaltsaltsalt$SOtLmIMY0nn6qOAL5eyazzIh4PBQ9yMBZQ/IECMh.T3",
+    ),
+    // p = 4
+    (
+        "password",
+        "$y$j75.0$saltsaltsaltsalt$tiPKXhvRaM2qFGPBmA1vO0i5A2pTQQ4IhhsLOcxtlL7",
+    ),
+    // N = 256, t = 2
+    (
+        "password",
+        "$y$j55//$saltsaltsaltsalt$p8J.mhyOcl7k3zafAEa6sOrrjXGEP70j.GbjbAxQ/O4",
+    ),
+    // N = 2048, r = 4, p = 3, t = 1
+    (
+        "password",
+        "$y$j810/.$saltsaltsaltsalt$JpaeYumdLA6Qod7pDSpgdnY1eLY6Y4ozCqx0P23Coa6",
+    ),
+    // N = 16, r = 1
+    (
+        "password",
+        "$y$j1.$saltsaltsaltsalt$Q77ZfgI3d5yB649QGSHse9VRSPrMJZ8K7xKyyKjwrJ0",
+    ),
+    // Classic: N = 1024, r = 8
+    (
+        "",
+        "$y$.75$saltsaltsaltsalt$JGkS7/t0sTEtjFQPMPC1d1.89MdLE1qIwDFTp81e/z7",
+    ),
+    (
+        "password",
+        "$y$.75$saltsaltsaltsalt$htRE.RgnnyJvLmIBls.xMqAJWqQLqZrpM8.Zksar5H.",
+    ),
+    // Classic: N = 256, r = 2, p = 3
+    (
+        "pleaseletmein",
+        "$y$.5/./$saltsaltsaltsalt$UYSKKR8eIvmRFUN5ZeOU45q2tP9sP7g5MpeVAqwlm4D",
+    ),
+    // Write-once/read-many: N = 1024, r = 8
+    (
+        "",
+        "$y$/75$saltsaltsaltsalt$lnjdmkVua0QwTldz32F43kUe3jPe0uI/h6RHDwybSU4",
+    ),
+    (
+        "password",
+        "$y$/75$saltsaltsaltsalt$ajDS8nt1YQa5qYzZoBxnzBW5fej7mTblixS1htlz7L4",
+    ),
+    // Write-once/read-many: p = 2, t = 1
+    (
+        "pleaseletmein",
+        "$y$/750..$saltsaltsaltsalt$gFjPan2K0Wxp0Dblh1CFL4nMf2uav6D0QiUEXgyxR92",
+    ),
+];
+
+#[test]
+fn verify_gensalt_hashes() {
+    for hash in GENSALT_HASHES {
+        assert_eq!(yescrypt::verify(b"password", hash), Ok(()), "{}", hash);
+        assert_eq!(
+            yescrypt::verify(b"passwore", hash),
+            Err(YescryptError::PasswordMismatch)
+        );
+    }
+}
+
+#[test]
+fn verify_custom_hashes() {
+    for (password, hash) in CUSTOM_HASHES {
+        assert_eq!(
+            yescrypt::verify(password.as_bytes(), hash),
+            Ok(()),
+            "{}",
+            hash
+        );
+        assert_eq!(
+            yescrypt::verify(b"wrong", hash),
+            Err(YescryptError::PasswordMismatch)
+        );
+    }
+}
+
+#[test]
+fn parse_params() {
+    let hash = Hash::parse(GENSALT_HASHES[2]).unwrap();
+    let params = hash.params();
+    assert_eq!(params.mode(), Mode::ReadWrite);
+    assert_eq!(params.log_n(), 12);
+    assert_eq!(params.r(), 32);
+    assert_eq!(params.p(), 1);
+    assert_eq!(params.t(), 0);
+    assert_eq!(hash.salt().len(), 16);
+
+    let hash = Hash::parse(CUSTOM_HASHES[5].1).unwrap();
+    assert_eq!(
+        *hash.params(),
+        Params::new(Mode::ReadWrite, 11, 4, 3, 1).unwrap()
+    );
+    assert_eq!(
+        hash.salt(),
+        &b"\xb8\x19\xe7\xb8\x19\xe7\xb8\x19\xe7\xb8\x19\xe7"[..]
+    );
+}
+
+/// Classic hashes are scrypt of the decoded salt.
+#[test]
+fn classic_is_scrypt() {
+    let hash = Hash::parse(CUSTOM_HASHES[8].1).unwrap();
+    let params = scrypt::Params::new(10, 8, 1, 32).unwrap();
+
+    let mut expected = [0u8; 32];
+    scrypt::scrypt(b"password", hash.salt(), &params, &mut expected).unwrap();
+    assert_eq!(hash.hash(), &expected);
+}
+
+/// libxcrypt rejects these flavors as well.
+#[test]
+fn unsupported_flags() {
+    assert_eq!(
+        Hash::parse("$y$i75$saltsaltsaltsalt$"),
+        Err(YescryptError::UnsupportedFlags(0xb2))
+    );
+    assert_eq!(
+        Hash::parse("$y$k.75$saltsaltsaltsalt$"),
+        Err(YescryptError::UnsupportedFlags(0xba))
+    );
+}
+
+#[test]
+fn unsupported_features() {
+    // g = 1
+    assert_eq!(
+        Hash::parse("$y$j751.$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J."),
+        Err(YescryptError::Unsupported("g"))
+    );
+    // NROM = 2^10
+    assert_eq!(
+        Hash::parse("$y$j7558$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J."),
+        Err(YescryptError::Unsupported("NROM"))
+    );
+}
+
+#[test]
+fn malformed_hashes() {
+    let valid = CUSTOM_HASHES[1].1;
+
+    for hash in [
+        "",
+        "$y$",
+        "$7$j75$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J.",
+        // Missing, truncated, extended and non-canonical hash
+        "$y$j75$saltsaltsaltsalt",
+        "$y$j75$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J",
+        "$y$j75$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J..",
+        "$y$j75$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646JE",
+        // Extra field
+        "$y$j75$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J.$",
+        // Salt with a single trailing character, and not in the alphabet
+        "$y$j75$saltsaltsaltsalts$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J.",
+        "$y$j75$salt+saltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J.",
+        // Truncated and trailing parameters
+        "$y$j7$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J.",
+        "$y$j75.$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J.",
+        "$y$j75/./$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J.",
+    ] {
+        assert_eq!(Hash::parse(hash), Err(YescryptError::Malformed), "{}", hash);
+        assert_eq!(
+            yescrypt::verify(b"password", hash),
+            Err(YescryptError::Malformed)
+        );
+    }
+
+    assert!(Hash::parse(valid).is_ok());
+}
+
+#[test]
+fn invalid_params() {
+    // r = 0 can't be encoded, but p = N can
+    let err = Hash::parse("$y$j/5.0$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J.")
+        .unwrap_err();
+    match err {
+        YescryptError::InvalidParams(err) => assert_eq!(err.param(), "p"),
+        err => panic!("unexpected error {:?}", err),
+    }
+}
+
+#[test]
+fn memory_limit() {
+    let hash = GENSALT_HASHES[3];
+    let required = Hash::parse(hash).unwrap().params().memory_required();
+    assert_eq!(required, 128 * 32 * (16384 + 3) + 12288);
+
+    assert_eq!(
+        yescrypt::verify_with_memory_limit(b"password", hash, MemoryLimit::new(required - 1)),
+        Err(YescryptError::MemoryLimitExceeded {
+            required,
+            limit: required - 1
+        })
+    );
+    assert_eq!(
+        yescrypt::verify_with_memory_limit(b"password", hash, MemoryLimit::new(required)),
+        Ok(())
+    );
+}
+
+#[test]
+fn kdf_output_lengths() {
+    let hash = Hash::parse(CUSTOM_HASHES[1].1).unwrap();
+
+    // Outputs shorter than 32 bytes are prefixes of the 32-byte output
+    let mut short = [0u8; 16];
+    yescrypt::yescrypt(b"password", hash.salt(), hash.params(), &mut short).unwrap();
+    assert_eq!(short, hash.hash()[..16]);
+
+    assert!(yescrypt::yescrypt(b"password", hash.salt(), hash.params(), &mut []).is_err());
+}
+
+#[test]
+fn compute_hashes() {
+    let hashes = GENSALT_HASHES
+        .iter()
+        .map(|hash| ("password", *hash))
+        .chain(CUSTOM_HASHES.iter().copied());
+
+    for (password, expected) in hashes {
+        let parsed = Hash::parse(expected).unwrap();
+        let hash = Hash::new(password.as_bytes(), parsed.salt(), *parsed.params()).unwrap();
+        assert_eq!(hash, parsed);
+        assert_eq!(hash.to_string(), expected);
+    }
+}
+
+#[test]
+fn salt_encoding() {
+    let salt = yescrypt::decode_salt("saltsaltsaltsalt").unwrap();
+    assert_eq!(salt, b"\xb8\x19\xe7\xb8\x19\xe7\xb8\x19\xe7\xb8\x19\xe7");
+    assert_eq!(yescrypt::encode_salt(&salt), "saltsaltsaltsalt");
+
+    for len in 0..=16 {
+        let salt: Vec<u8> = (0..len).map(|i| 0xf0 ^ i).collect();
+        let encoded = yescrypt::encode_salt(&salt);
+        assert_eq!(yescrypt::decode_salt(&encoded).unwrap(), salt);
+    }
+
+    assert_eq!(
+        yescrypt::decode_salt("saltsaltsaltsalts"),
+        Err(YescryptError::Malformed)
+    );
+}
+
+#[test]
+fn encode_large_params() {
+    // `t` above the largest value of 6 characters
+    let salt = yescrypt::decode_salt("saltsaltsaltsalt").unwrap();
+    let params = Params::new(Mode::Worm, 1, 1, 1, 1_091_060_273).unwrap();
+    match Hash::new(b"password", &salt, params).unwrap_err() {
+        YescryptError::InvalidParams(err) => assert_eq!(err.param(), "t"),
+        err => panic!("unexpected error {:?}", err),
+    }
+}
@@ -1,14 +1,71 @@
-//! Base64 encoding support
+//! Base64 encoding of SHA-crypt, and of other `crypt(3)` formats.
+//!
+//! `crypt(3)` hashes use their own Base64 variant: the alphabet
+//! `./0-9A-Za-z`, no padding, and groups of 3 bytes read as little-endian
+//! 24-bit integers, their least significant 6 bits first. On top of it,
+//! each format permutes the bytes of its digest before encoding them,
+//! which [`MAP_SHA256`] and [`MAP_SHA512`] describe for `$5$` and `$6$`.
+//!
+//! [`encode_into`] and [`decode`] implement the Base64 variant itself:
+//! decoding the output of [`encode_into`] gives back the input, and only
+//! canonical encodings, whose unused bits are zero, are decoded, so
+//! encoding the output of [`decode`] gives back the input as well.
+//!
+//! ```
+//! use sha_crypt::b64;
+//!
+//! let mut buf = [0u8; 8];
+//! let encoded = b64::encode_into(b"\x00\x01\xff", &mut buf)?;
+//! assert_eq!(encoded, ".2kz");
+//!
+//! let mut decoded = [0u8; 3];
+//! assert_eq!(b64::decode(encoded, &mut decoded)?, b"\x00\x01\xff");
+//! # Ok::<(), sha_crypt::B64Error>(())
+//! ```
 
-use crate::defs::{
-    BLOCK_SIZE_SHA256, BLOCK_SIZE_SHA512, MAP_SHA256, MAP_SHA512, PW_SIZE_SHA256, PW_SIZE_SHA512,
-};
+pub use crate::defs::{MAP_SHA256, MAP_SHA512};
+
+use crate::defs::{BLOCK_SIZE_SHA256, BLOCK_SIZE_SHA512, PW_SIZE_SHA256, PW_SIZE_SHA512};
+use crate::errors::B64Error;
 use base64ct::{Base64ShaCrypt, Encoding};
 
-#[cfg(feature = "simple")]
-use crate::errors::DecodeError;
+/// Length of the encoding of `len` bytes.
+pub const fn encoded_len(len: usize) -> usize {
+    (len * 4 + 2) / 3
+}
+
+/// Encode `src` into `dst`.
+///
+/// # Returns
+/// - `Ok(&str)` containing the encoding, the first [`encoded_len`] bytes of
+///   `dst`
+/// - `Err(B64Error::InvalidLength)` if `dst` is too short.
+pub fn encode_into<'a>(src: &[u8], dst: &'a mut [u8]) -> Result<&'a str, B64Error> {
+    Base64ShaCrypt::encode(src, dst).map_err(|_| B64Error::InvalidLength)
+}
+
+/// Decode `src` into `dst`.
+///
+/// # Returns
+/// - `Ok(&[u8])` containing the decoded bytes, the first bytes of `dst`
+/// - `Err(B64Error::InvalidEncoding)` if `src` contains characters outside
+///   of the alphabet, or isn't canonical
+/// - `Err(B64Error::InvalidLength)` if no input has the length of `src`,
+///   which is the case when it's 1 modulo 4, or if `dst` is too short.
+pub fn decode(src: impl AsRef<[u8]>, dst: &mut [u8]) -> Result<&[u8], B64Error> {
+    let src = src.as_ref();
+    if src.len() % 4 == 1 {
+        return Err(B64Error::InvalidLength);
+    }
+
+    Base64ShaCrypt::decode(src, dst).map_err(|err| match err {
+        base64ct::Error::InvalidEncoding => B64Error::InvalidEncoding,
+        base64ct::Error::InvalidLength => B64Error::InvalidLength,
+    })
+}
 
-pub fn encode_sha512(source: &[u8]) -> [u8; PW_SIZE_SHA512] {
+/// Encode a SHA512-crypt digest, permuted with [`MAP_SHA512`].
+pub fn encode_sha512(source: &[u8; BLOCK_SIZE_SHA512]) -> [u8; PW_SIZE_SHA512] {
     let mut transposed = [0u8; BLOCK_SIZE_SHA512];
     for (i, &ti) in MAP_SHA512.iter().enumerate() {
         transposed[i] = source[ti as usize];
@@ -18,7 +75,8 @@ pub fn encode_sha512(source: &[u8]) -> [u8; PW_SIZE_SHA512] {
     buf
 }
 
-pub fn encode_sha256(source: &[u8]) -> [u8; PW_SIZE_SHA256] {
+/// Encode a SHA256-crypt digest, permuted with [`MAP_SHA256`].
+pub fn encode_sha256(source: &[u8; BLOCK_SIZE_SHA256]) -> [u8; PW_SIZE_SHA256] {
     let mut transposed = [0u8; BLOCK_SIZE_SHA256];
     for (i, &ti) in MAP_SHA256.iter().enumerate() {
         transposed[i] = source[ti as usize];
@@ -28,11 +86,17 @@ pub fn encode_sha256(source: &[u8]) -> [u8; PW_SIZE_SHA256] {
     buf
 }
 
-#[cfg(feature = "simple")]
-pub fn decode_sha512(source: &[u8]) -> Result<[u8; BLOCK_SIZE_SHA512], DecodeError> {
-    const BUF_SIZE: usize = 86;
-    let mut buf = [0u8; BUF_SIZE];
-    Base64ShaCrypt::decode(source, &mut buf).map_err(|_| DecodeError)?;
+/// Decode a SHA512-crypt digest, the inverse of [`encode_sha512`].
+///
+/// `source` must be exactly 86 characters long.
+pub fn decode_sha512(source: &[u8]) -> Result<[u8; BLOCK_SIZE_SHA512], B64Error> {
+    if source.len() != PW_SIZE_SHA512 {
+        return Err(B64Error::InvalidLength);
+    }
+
+    let mut buf = [0u8; BLOCK_SIZE_SHA512];
+    decode(source, &mut buf)?;
+
     let mut transposed = [0u8; BLOCK_SIZE_SHA512];
     for (i, &ti) in MAP_SHA512.iter().enumerate() {
         transposed[ti as usize] = buf[i];
@@ -40,10 +104,16 @@ pub fn decode_sha512(source: &[u8]) -> Result<[u8; BLOCK_SIZE_SHA512], DecodeErr
     Ok(transposed)
 }
 
-#[cfg(feature = "simple")]
-pub fn decode_sha256(source: &[u8]) -> Result<[u8; BLOCK_SIZE_SHA256], DecodeError> {
-    let mut buf = [0u8; PW_SIZE_SHA256];
-    Base64ShaCrypt::decode(source, &mut buf).unwrap();
+/// Decode a SHA256-crypt digest, the inverse of [`encode_sha256`].
+///
+/// `source` must be exactly 43 characters long.
+pub fn decode_sha256(source: &[u8]) -> Result<[u8; BLOCK_SIZE_SHA256], B64Error> {
+    if source.len() != PW_SIZE_SHA256 {
+        return Err(B64Error::InvalidLength);
+    }
+
+    let mut buf = [0u8; BLOCK_SIZE_SHA256];
+    decode(source, &mut buf)?;
 
     let mut transposed = [0u8; BLOCK_SIZE_SHA256];
     for (i, &ti) in MAP_SHA256.iter().enumerate() {
@@ -11,11 +11,9 @@ pub const PW_SIZE_SHA256: usize = 43;
 pub const PW_SIZE_SHA512: usize = 86;
 
 /// Maximum length of a salt
-#[cfg(feature = "simple")]
 pub const SALT_MAX_LEN: usize = 16;
 
 /// Encoding table.
-#[cfg(feature = "simple")]
 pub static TAB: &[u8] = b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
 
 /// Inverse encoding map for SHA512.
@@ -1,17 +1,18 @@
 //! Error types.
 
-use alloc::string;
+use core::fmt;
 
-#[cfg(feature = "simple")]
-use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::string;
 
 #[cfg(feature = "std")]
 use std::io;
 
-/// Error type.
+/// Error type of the hashing functions.
 #[derive(Debug)]
 pub enum CryptError {
-    /// Should be within range defs::ROUNDS_MIN < defs::ROUNDS_MIN
+    /// Rounds outside of [`ROUNDS_MIN`](crate::ROUNDS_MIN) and
+    /// [`ROUNDS_MAX`](crate::ROUNDS_MAX).
     RoundsError,
 
     /// RNG failed.
@@ -23,7 +24,24 @@ pub enum CryptError {
     IoError(io::Error),
 
     /// UTF-8 error.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
     StringError(string::FromUtf8Error),
+
+    /// Salt contains characters other than `[a-zA-Z0-9./]`.
+    SaltError,
+
+    /// Salt is longer than defs::SALT_MAX_LEN, see
+    /// [`SaltMode`](crate::SaltMode).
+    SaltTooLong,
+
+    /// The output buffer is too small for the hash string, see
+    /// [`HASH_STRING_MAX_LEN`](crate::HASH_STRING_MAX_LEN).
+    BufferTooSmall,
+
+    /// The setting passed to [`crypt_hash_string`](crate::crypt_hash_string) is
+    /// malformed.
+    ParseError(ParseError),
 }
 
 #[cfg(feature = "std")]
@@ -33,30 +51,330 @@ impl From<io::Error> for CryptError {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl From<string::FromUtf8Error> for CryptError {
     fn from(e: string::FromUtf8Error) -> Self {
         CryptError::StringError(e)
     }
 }
 
-#[cfg(feature = "simple")]
-#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+impl From<ParseError> for CryptError {
+    fn from(e: ParseError) -> Self {
+        CryptError::ParseError(e)
+    }
+}
+
+impl fmt::Display for CryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptError::RoundsError => write!(
+                f,
+                "rounds must be between {} and {}",
+                crate::ROUNDS_MIN,
+                crate::ROUNDS_MAX
+            ),
+            CryptError::RandomError => f.write_str("random number generator failed"),
+            #[cfg(feature = "std")]
+            CryptError::IoError(err) => write!(f, "I/O error: {}", err),
+            #[cfg(feature = "alloc")]
+            CryptError::StringError(err) => write!(f, "invalid UTF-8: {}", err),
+            CryptError::SaltError => {
+                f.write_str("salt contains characters other than '[a-zA-Z0-9./]'")
+            }
+            CryptError::SaltTooLong => f.write_str(ParseError::SaltTooLong.as_str()),
+            CryptError::BufferTooSmall => f.write_str("output buffer is too small"),
+            CryptError::ParseError(err) => write!(f, "invalid setting: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CryptError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CryptError::IoError(err) => Some(err),
+            CryptError::StringError(err) => Some(err),
+            CryptError::ParseError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Error type of [`parse`](crate::parse).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ParseError {
+    /// The hash string doesn't start with `$<ID>$`, or, for
+    /// [`sha256_check`](crate::sha256_check) and
+    /// [`sha512_check`](crate::sha512_check), with the identifier of their
+    /// variant.
+    InvalidPrefix,
+
+    /// The hash string starts with `$<ID>$` of another algorithm than
+    /// SHA-crypt, such as `$1$` or `$y$`.
+    UnsupportedAlgorithm,
+
+    /// The number of rounds isn't a decimal integer.
+    InvalidRounds,
+
+    /// The salt is longer than defs::SALT_MAX_LEN.
+    SaltTooLong,
+
+    /// The encoded digest doesn't have the length of the variant.
+    InvalidHashLength,
+
+    /// The encoded digest isn't valid B64, see [`b64`](crate::b64).
+    InvalidHash,
+
+    /// The salt or the digest is missing.
+    MissingField,
+
+    /// There are characters after the digest.
+    TrailingData,
+
+    /// The hash string is longer than [`INPUT_MAX_LEN`](crate::INPUT_MAX_LEN).
+    TooLong,
+}
+
+impl ParseError {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ParseError::InvalidPrefix => "does not contain a SHA-crypt identifier: '$5$' or '$6$'",
+            ParseError::UnsupportedAlgorithm => "unsupported algorithm identifier",
+            ParseError::InvalidRounds => "rounds= specifier need to be a number",
+            ParseError::SaltTooLong => "salt is longer than 16 characters",
+            ParseError::InvalidHashLength => "hash string has an invalid length",
+            ParseError::InvalidHash => "invalid B64",
+            ParseError::MissingField => "does not contain a salt and hash string",
+            ParseError::TrailingData => "Trailing characters present",
+            ParseError::TooLong => "hash string is too long",
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+impl From<ParseError> for CheckError {
+    fn from(err: ParseError) -> CheckError {
+        match err {
+            ParseError::UnsupportedAlgorithm => CheckError::UnsupportedAlgorithm,
+            _ => CheckError::InvalidFormat(err),
+        }
+    }
+}
+
+/// Error type of the check functions.
 #[derive(Debug)]
 pub enum CheckError {
-    InvalidFormat(String),
+    /// The hash string is malformed.
+    InvalidFormat(ParseError),
+
+    /// The hash string is of another algorithm than SHA-crypt, such as
+    /// `$1$` or `$y$`.
+    UnsupportedAlgorithm,
+
+    /// The hash string has invalid parameters.
     Crypt(CryptError),
+
+    /// The password doesn't match the hash.
     HashMismatch,
 }
 
-/// Decoding errors.
-#[cfg(feature = "simple")]
-#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckError::InvalidFormat(err) => write!(f, "invalid hash string: {}", err),
+            CheckError::UnsupportedAlgorithm => {
+                f.write_str(ParseError::UnsupportedAlgorithm.as_str())
+            }
+            CheckError::Crypt(err) => fmt::Display::fmt(err, f),
+            CheckError::HashMismatch => f.write_str("password doesn't match the hash"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CheckError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CheckError::InvalidFormat(err) => Some(err),
+            CheckError::Crypt(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of [`check_outcome`](crate::check_outcome), telling apart the
+/// reasons a verification fails.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum VerifyOutcome<'a> {
+    /// The password matches the hash string.
+    Match,
+
+    /// The password doesn't match the hash string.
+    PasswordMismatch,
+
+    /// The hash string is of another algorithm than SHA-crypt: its
+    /// `$<ID>$` prefix, such as `$1$` or `$y$`.
+    UnsupportedVariant(&'a str),
+
+    /// The hash string is malformed.
+    Malformed(ParseError),
+}
+
+impl<'a> VerifyOutcome<'a> {
+    /// Outcome of a check of `hashed_value`.
+    pub(crate) fn new(result: Result<(), CheckError>, hashed_value: &'a str) -> VerifyOutcome<'a> {
+        match result {
+            Ok(()) => VerifyOutcome::Match,
+            Err(CheckError::HashMismatch) => VerifyOutcome::PasswordMismatch,
+            Err(CheckError::UnsupportedAlgorithm) => {
+                // `$<ID>$`, found by the parser
+                let end = hashed_value[1..].find('$').map_or(0, |i| i + 2);
+                VerifyOutcome::UnsupportedVariant(&hashed_value[..end])
+            }
+            Err(CheckError::InvalidFormat(err)) => VerifyOutcome::Malformed(err),
+            Err(CheckError::Crypt(CryptError::SaltTooLong)) => {
+                VerifyOutcome::Malformed(ParseError::SaltTooLong)
+            }
+            Err(CheckError::Crypt(_)) => VerifyOutcome::Malformed(ParseError::InvalidRounds),
+        }
+    }
+
+    /// Whether the password matches the hash string.
+    pub fn is_match(&self) -> bool {
+        matches!(self, VerifyOutcome::Match)
+    }
+}
+
+impl fmt::Display for VerifyOutcome<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyOutcome::Match => f.write_str("password matches the hash"),
+            VerifyOutcome::PasswordMismatch => fmt::Display::fmt(&CheckError::HashMismatch, f),
+            VerifyOutcome::UnsupportedVariant(prefix) => {
+                write!(f, "unsupported algorithm identifier: '{}'", prefix)
+            }
+            VerifyOutcome::Malformed(err) => write!(f, "invalid hash string: {}", err),
+        }
+    }
+}
+
+/// Error type of the [`b64`](crate::b64) functions.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum B64Error {
+    /// Characters outside of the alphabet, or a non-canonical encoding.
+    InvalidEncoding,
+
+    /// The input or the output buffer has an invalid length.
+    InvalidLength,
+}
+
+impl fmt::Display for B64Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            B64Error::InvalidEncoding => f.write_str("invalid B64 encoding"),
+            B64Error::InvalidLength => f.write_str("invalid B64 length"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for B64Error {}
+
+impl From<B64Error> for CheckError {
+    fn from(_: B64Error) -> CheckError {
+        CheckError::InvalidFormat(ParseError::InvalidHash)
+    }
+}
+
+/// Error type of the [`shadow`](crate::shadow) functions.
 #[derive(Debug)]
-pub struct DecodeError;
+pub enum ShadowError {
+    /// The line doesn't have the fields of a shadow or htpasswd line, or
+    /// its username is empty.
+    InvalidLine,
+
+    /// The account is locked: its hash field is `*` or starts with `!`.
+    Locked,
+
+    /// The account has no password: its hash field is empty.
+    NoPassword,
+
+    /// The hash field is of another scheme than SHA-crypt, such as `$2y$`,
+    /// `$apr1$` or `{SHA}`.
+    UnsupportedScheme,
+
+    /// The SHA-crypt hash string is malformed, or the password doesn't
+    /// match it.
+    Check(CheckError),
+}
+
+impl fmt::Display for ShadowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShadowError::InvalidLine => f.write_str("invalid shadow or htpasswd line"),
+            ShadowError::Locked => f.write_str("account is locked"),
+            ShadowError::NoPassword => f.write_str("account has no password"),
+            ShadowError::UnsupportedScheme => f.write_str("unsupported password hash scheme"),
+            ShadowError::Check(err) => fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ShadowError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ShadowError::Check(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<CheckError> for ShadowError {
+    fn from(err: CheckError) -> ShadowError {
+        match err {
+            CheckError::UnsupportedAlgorithm => ShadowError::UnsupportedScheme,
+            _ => ShadowError::Check(err),
+        }
+    }
+}
+
+#[cfg(feature = "simple")]
+impl From<CryptError> for password_hash::Error {
+    fn from(err: CryptError) -> password_hash::Error {
+        match err {
+            CryptError::RoundsError => password_hash::errors::InvalidValue::Malformed.param_error(),
+            CryptError::SaltTooLong => {
+                password_hash::Error::SaltInvalid(password_hash::errors::InvalidValue::TooLong)
+            }
+            _ => password_hash::Error::Crypto,
+        }
+    }
+}
+
+#[cfg(feature = "simple")]
+impl From<CheckError> for password_hash::Error {
+    fn from(err: CheckError) -> password_hash::Error {
+        match err {
+            CheckError::InvalidFormat(_) => password_hash::Error::PhcStringField,
+            CheckError::UnsupportedAlgorithm => password_hash::Error::Algorithm,
+            CheckError::Crypt(err) => err.into(),
+            CheckError::HashMismatch => password_hash::Error::Password,
+        }
+    }
+}
 
 #[cfg(feature = "simple")]
-impl From<DecodeError> for CheckError {
-    fn from(_: DecodeError) -> CheckError {
-        CheckError::InvalidFormat("invalid B64".into())
+impl From<B64Error> for password_hash::Error {
+    fn from(_: B64Error) -> password_hash::Error {
+        password_hash::Error::PhcStringField
     }
 }
@@ -2,7 +2,8 @@
 //! a legacy password hashing scheme supported by the [POSIX crypt C library][2].
 //!
 //! Password hashes using this algorithm start with `$6$` when encoded using the
-//! [PHC string format][3].
+//! [PHC string format][3]. The SHA-256 variant, whose hashes start with `$5$`,
+//! is supported as well, and [`check`] verifies hashes of either variant.
 //!
 //! # Usage
 //!
@@ -24,6 +25,40 @@
 //! # }
 //! ```
 //!
+//! # Passwords
+//!
+//! Passwords are byte strings, as in `crypt(3)`: the functions taking one
+//! accept `&[u8]` as well as `&str`, so passwords which aren't valid UTF-8,
+//! such as Latin-1 ones, can be hashed and verified. Every byte of the
+//! password is hashed, including NUL bytes, whereas `crypt(3)` takes a C
+//! string and stops at the first NUL: hashes of passwords containing NUL
+//! bytes don't match those of `crypt(3)`.
+//!
+//! # `no_std` support
+//!
+//! Hashing and verifying hash strings don't need `std` or even `alloc`:
+//! [`sha512_hash_string`] returns a [`HashString`] stored on the stack,
+//! [`sha512_hash_into`] writes the hash string to a caller buffer, and
+//! [`check`] verifies hash strings without allocating.
+//!
+//! Computing the digest doesn't allocate either, for passwords of any
+//! length: the byte sequences derived from the password and the salt are
+//! kept in fixed arrays of 256 bytes, and those of passwords longer than
+//! that are fed to the digests in pieces.
+//!
+//! The following crate features add conveniences on top of them:
+//! - `alloc`: the functions returning a `String`, such as
+//!   `sha512_crypt_b64` and `sha512_simple_with_salt`
+//! - `std`: `std::io::Error` support and the `std::error::Error` impls of
+//!   the error types, implies `alloc`
+//! - `rand_core`: salt generation with `gen_salt_with_rng`, implies `alloc`
+//! - `simple` (default): random salts with `sha512_simple` and the
+//!   `password-hash` API, implies `rand_core` and `std`
+//!
+//! Without `std`, the error types still implement `Display`, but not
+//! `core::error::Error`, which is only stable since Rust 1.81, past the
+//! MSRV of this crate.
+//!
 //! [1]: https://www.akkadia.org/drepper/SHA-crypt.txt
 //! [2]: https://en.wikipedia.org/wiki/Crypt_(C)
 //! [3]: https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md
@@ -37,51 +72,65 @@
 #![deny(unsafe_code)]
 #![warn(missing_docs, rust_2018_idioms)]
 
-// TODO(tarcieri): heapless support
-#[macro_use]
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
 #[cfg(feature = "std")]
 extern crate std;
 
-mod b64;
+pub mod b64;
 mod defs;
 mod errors;
 mod params;
+mod parse;
+pub mod shadow;
+#[cfg(feature = "simple")]
+mod simple;
+mod string;
 
 pub use crate::{
     defs::{BLOCK_SIZE_SHA256, BLOCK_SIZE_SHA512},
-    errors::CryptError,
-    params::{Sha256Params, Sha512Params, ROUNDS_DEFAULT, ROUNDS_MAX, ROUNDS_MIN},
+    errors::{B64Error, CheckError, CryptError, ParseError, ShadowError, VerifyOutcome},
+    params::{
+        ParamsBuilder, RoundsMode, SaltMode, Sha256Params, Sha512Params, ROUNDS_DEFAULT,
+        ROUNDS_MAX, ROUNDS_MIN,
+    },
+    parse::{parse, parse_with_salt_mode, ParsedShaCrypt, Variant, INPUT_MAX_LEN},
+    string::{HashString, HASH_STRING_MAX_LEN},
 };
 
-use alloc::{string::String, vec::Vec};
-use sha2::{Digest, Sha256, Sha512};
-
 #[cfg(feature = "simple")]
-use {
-    crate::{
-        defs::{SALT_MAX_LEN, TAB},
-        errors::CheckError,
-    },
-    alloc::string::ToString,
-    rand::{distributions::Distribution, thread_rng, Rng},
+pub use {
+    crate::simple::{Algorithm, ShaCrypt, ShaCryptParams},
+    password_hash,
 };
 
-#[cfg(feature = "simple")]
+#[cfg(feature = "rand_core")]
+pub use rand_core;
+
+use crate::{
+    defs::{SALT_MAX_LEN, TAB},
+    parse::parse_setting,
+};
+use sha2::{Digest, Sha256, Sha512};
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+#[cfg(feature = "rand_core")]
+use rand_core::{CryptoRng, RngCore};
+
 static SHA256_SALT_PREFIX: &str = "$5$";
-#[cfg(feature = "simple")]
-static SHA256_ROUNDS_PREFIX: &str = "rounds=";
 
-#[cfg(feature = "simple")]
 static SHA512_SALT_PREFIX: &str = "$6$";
-#[cfg(feature = "simple")]
-static SHA512_ROUNDS_PREFIX: &str = "rounds=";
+
+static ROUNDS_PREFIX: &str = "rounds=";
 
 /// The SHA512 crypt function returned as byte vector
 ///
-/// If the provided hash is longer than defs::SALT_MAX_LEN character, it will
-/// be stripped down to defs::SALT_MAX_LEN characters.
+/// Salts longer than defs::SALT_MAX_LEN characters are rejected with
+/// `Err(CryptError::SaltTooLong)`, or truncated to defs::SALT_MAX_LEN
+/// characters as in `crypt(3)` with [`SaltMode::Truncate`].
 ///
 /// # Arguments
 /// - `password` - The password to process as a byte vector
@@ -97,94 +146,15 @@ pub fn sha512_crypt(
     salt: &[u8],
     params: &Sha512Params,
 ) -> Result<[u8; BLOCK_SIZE_SHA512], CryptError> {
-    let pw_len = password.len();
-
-    let salt_len = salt.len();
-    let salt = match salt_len {
-        0..=15 => &salt[0..salt_len],
-        _ => &salt[0..16],
-    };
-    let salt_len = salt.len();
-
-    if params.rounds < ROUNDS_MIN || params.rounds > ROUNDS_MAX {
-        return Err(CryptError::RoundsError);
-    }
-
-    let digest_a = sha512crypt_intermediate(password, salt);
-
-    // 13.
-    let mut hasher_alt = Sha512::default();
-
-    // 14.
-    for _ in 0..pw_len {
-        hasher_alt.update(password);
-    }
-
-    // 15.
-    let dp = hasher_alt.finalize();
-
-    // 16.
-    // Create byte sequence P.
-    let p_vec = produce_byte_seq(pw_len, &dp);
-
-    // 17.
-    hasher_alt = Sha512::default();
-
-    // 18.
-    // For every character in the password add the entire password.
-    for _ in 0..(16 + digest_a[0] as usize) {
-        hasher_alt.update(salt);
-    }
-
-    // 19.
-    // Finish the digest.
-    let ds = hasher_alt.finalize();
-
-    // 20.
-    // Create byte sequence S.
-    let s_vec = produce_byte_seq(salt_len, &ds);
-
-    let mut digest_c = digest_a;
-    // Repeatedly run the collected hash value through SHA512 to burn
-    // CPU cycles
-    for i in 0..params.rounds {
-        // new hasher
-        let mut hasher = Sha512::default();
-
-        // Add key or last result
-        if (i & 1) != 0 {
-            hasher.update(&p_vec);
-        } else {
-            hasher.update(digest_c);
-        }
-
-        // Add salt for numbers not divisible by 3
-        if i % 3 != 0 {
-            hasher.update(&s_vec);
-        }
-
-        // Add key for numbers not divisible by 7
-        if i % 7 != 0 {
-            hasher.update(&p_vec);
-        }
-
-        // Add key or last result
-        if (i & 1) != 0 {
-            hasher.update(digest_c);
-        } else {
-            hasher.update(&p_vec);
-        }
-
-        digest_c.clone_from_slice(&hasher.finalize());
-    }
-
-    Ok(digest_c)
+    let salt = params.salt_mode.apply(salt)?;
+    sha_crypt::<Sha512, BLOCK_SIZE_SHA512>(password, salt, params.rounds)
 }
 
 /// The SHA256 crypt function returned as byte vector
 ///
-/// If the provided hash is longer than defs::SALT_MAX_LEN character, it will
-/// be stripped down to defs::SALT_MAX_LEN characters.
+/// Salts longer than defs::SALT_MAX_LEN characters are rejected with
+/// `Err(CryptError::SaltTooLong)`, or truncated to defs::SALT_MAX_LEN
+/// characters as in `crypt(3)` with [`SaltMode::Truncate`].
 ///
 /// # Arguments
 /// - `password` - The password to process as a byte vector
@@ -200,88 +170,45 @@ pub fn sha256_crypt(
     salt: &[u8],
     params: &Sha256Params,
 ) -> Result<[u8; BLOCK_SIZE_SHA256], CryptError> {
-    let pw_len = password.len();
-
-    let salt_len = salt.len();
-    let salt = match salt_len {
-        0..=15 => &salt[0..salt_len],
-        _ => &salt[0..16],
-    };
-    let salt_len = salt.len();
-
-    if params.rounds < ROUNDS_MIN || params.rounds > ROUNDS_MAX {
-        return Err(CryptError::RoundsError);
-    }
-
-    let digest_a = sha256crypt_intermediate(password, salt);
-
-    // 13.
-    let mut hasher_alt = Sha256::default();
-
-    // 14.
-    for _ in 0..pw_len {
-        hasher_alt.update(password);
-    }
-
-    // 15.
-    let dp = hasher_alt.finalize();
-
-    // 16.
-    // Create byte sequence P.
-    let p_vec = produce_byte_seq(pw_len, &dp);
-
-    // 17.
-    hasher_alt = Sha256::default();
-
-    // 18.
-    // For every character in the password add the entire password.
-    for _ in 0..(16 + digest_a[0] as usize) {
-        hasher_alt.update(salt);
-    }
-
-    // 19.
-    // Finish the digest.
-    let ds = hasher_alt.finalize();
-
-    // 20.
-    // Create byte sequence S.
-    let s_vec = produce_byte_seq(salt_len, &ds);
-
-    let mut digest_c = digest_a;
-    // Repeatedly run the collected hash value through SHA256 to burn
-    // CPU cycles
-    for i in 0..params.rounds {
-        // new hasher
-        let mut hasher = Sha256::default();
-
-        // Add key or last result
-        if (i & 1) != 0 {
-            hasher.update(&p_vec);
-        } else {
-            hasher.update(digest_c);
-        }
-
-        // Add salt for numbers not divisible by 3
-        if i % 3 != 0 {
-            hasher.update(&s_vec);
-        }
-
-        // Add key for numbers not divisible by 7
-        if i % 7 != 0 {
-            hasher.update(&p_vec);
-        }
-
-        // Add key or last result
-        if (i & 1) != 0 {
-            hasher.update(digest_c);
-        } else {
-            hasher.update(&p_vec);
-        }
+    let salt = params.salt_mode.apply(salt)?;
+    sha_crypt::<Sha256, BLOCK_SIZE_SHA256>(password, salt, params.rounds)
+}
 
-        digest_c.clone_from_slice(&hasher.finalize());
-    }
+/// Raw digest of `password` for the hash string with `salt` and `rounds`,
+/// for storing it in another format than a hash string.
+///
+/// This is the final SHA-512 digest of the algorithm, before the bytes are
+/// permuted and encoded by [`b64::encode_sha512`], which gives the digest
+/// field of the hash string: the hash string functions such as
+/// [`sha512_hash_string`] encode this same digest.
+///
+/// The salt is checked as by [`sha512_hash_string`] with
+/// [`SaltMode::Strict`], and `rounds` must be between [`ROUNDS_MIN`] and
+/// [`ROUNDS_MAX`].
+///
+/// **WARNING: Make sure to compare this value in constant time!**
+pub fn sha512_crypt_raw(
+    password: impl AsRef<[u8]>,
+    salt: &str,
+    rounds: usize,
+) -> Result<[u8; BLOCK_SIZE_SHA512], CryptError> {
+    let params = Sha512Params::new(rounds)?;
+    let salt = crypt_salt(salt, params.salt_mode)?;
+    sha512_crypt(password.as_ref(), salt, &params)
+}
 
-    Ok(digest_c)
+/// Same as sha512_crypt_raw for SHA256-crypt, permuted and encoded by
+/// [`b64::encode_sha256`] in hash strings.
+///
+/// **WARNING: Make sure to compare this value in constant time!**
+pub fn sha256_crypt_raw(
+    password: impl AsRef<[u8]>,
+    salt: &str,
+    rounds: usize,
+) -> Result<[u8; BLOCK_SIZE_SHA256], CryptError> {
+    let params = Sha256Params::new(rounds)?;
+    let salt = crypt_salt(salt, params.salt_mode)?;
+    sha256_crypt(password.as_ref(), salt, &params)
 }
 
 /// Same as sha512_crypt except base64 representation will be returned.
@@ -295,6 +222,8 @@ pub fn sha256_crypt(
 /// # Returns
 /// - `Ok(())` if calculation was successful
 /// - `Err(errors::CryptError)` otherwise
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
 pub fn sha512_crypt_b64(
     password: &[u8],
     salt: &[u8],
@@ -316,6 +245,8 @@ pub fn sha512_crypt_b64(
 /// # Returns
 /// - `Ok(())` if calculation was successful
 /// - `Err(errors::CryptError)` otherwise
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
 pub fn sha256_crypt_b64(
     password: &[u8],
     salt: &[u8],
@@ -339,27 +270,75 @@ pub fn sha256_crypt_b64(
 /// [1]: https://www.akkadia.org/drepper/SHA-crypt.txt
 #[cfg(feature = "simple")]
 #[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
-pub fn sha512_simple(password: &str, params: &Sha512Params) -> Result<String, CryptError> {
-    let rng = thread_rng();
+pub fn sha512_simple(
+    password: impl AsRef<[u8]>,
+    params: &Sha512Params,
+) -> Result<String, CryptError> {
+    sha512_simple_with_salt(password, &random_salt(), params)
+}
 
-    let salt: String = rng
-        .sample_iter(&ShaCryptDistribution)
-        .take(SALT_MAX_LEN)
-        .collect();
+/// Same as sha512_simple except the given salt will be used.
+///
+/// The output is the same as that of `crypt(3)` for the setting
+/// `$6$rounds=<ROUNDS>$<SALT>`, or `$6$<SALT>` for the default rounds.
+/// The salt must consist of `[a-zA-Z0-9./]`, which `gen_salt_with_rng`
+/// generates, or `Err(CryptError::SaltError)` is returned. Salts longer than
+/// defs::SALT_MAX_LEN characters are handled according to the
+/// [`SaltMode`] of `params`.
+///
+/// # Returns
+/// - `Ok(String)` containing the full SHA512 password hash format on success
+/// - `Err(CryptError)` if something went wrong.
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn sha512_simple_with_salt(
+    password: impl AsRef<[u8]>,
+    salt: &str,
+    params: &Sha512Params,
+) -> Result<String, CryptError> {
+    Ok(sha512_hash_string(password, salt, params)?.as_str().into())
+}
 
-    let out = sha512_crypt(password.as_bytes(), salt.as_bytes(), params)?;
+/// Same as sha512_simple_with_salt except the hash string is returned on
+/// the stack, without allocating.
+///
+/// # Returns
+/// - `Ok(HashString)` containing the full SHA512 password hash format on
+///   success
+/// - `Err(CryptError)` if something went wrong.
+pub fn sha512_hash_string(
+    password: impl AsRef<[u8]>,
+    salt: &str,
+    params: &Sha512Params,
+) -> Result<HashString, CryptError> {
+    let salt = crypt_salt(salt, params.salt_mode)?;
+    let out = sha512_crypt(password.as_ref(), salt, params)?;
+    Ok(HashString::new(
+        SHA512_SALT_PREFIX,
+        default_rounds(params.rounds),
+        salt,
+        &b64::encode_sha512(&out),
+    ))
+}
 
-    let mut result = String::new();
-    result.push_str(SHA512_SALT_PREFIX);
-    if params.rounds != ROUNDS_DEFAULT {
-        result.push_str(&format!("{}{}", SHA512_ROUNDS_PREFIX, params.rounds));
-        result.push('$');
-    }
-    result.push_str(&salt);
-    result.push('$');
-    let s = String::from_utf8(b64::encode_sha512(&out).to_vec())?;
-    result.push_str(&s);
-    Ok(result)
+/// Same as sha512_hash_string except the hash string is written to `out`,
+/// without allocating.
+///
+/// A buffer of [`HASH_STRING_MAX_LEN`] bytes is large enough for any hash
+/// string.
+///
+/// # Returns
+/// - `Ok(usize)` containing the length of the hash string written to the
+///   start of `out` on success
+/// - `Err(CryptError::BufferTooSmall)` if `out` is too small for it
+/// - `Err(CryptError)` if something else went wrong.
+pub fn sha512_hash_into(
+    password: impl AsRef<[u8]>,
+    salt: &str,
+    params: &Sha512Params,
+    out: &mut [u8],
+) -> Result<usize, CryptError> {
+    sha512_hash_string(password, salt, params)?.copy_to(out)
 }
 
 /// Simple interface for generating a SHA256 password hash.
@@ -375,224 +354,635 @@ pub fn sha512_simple(password: &str, params: &Sha512Params) -> Result<String, Cr
 /// [1]: https://www.akkadia.org/drepper/SHA-crypt.txt
 #[cfg(feature = "simple")]
 #[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
-pub fn sha256_simple(password: &str, params: &Sha256Params) -> Result<String, CryptError> {
-    let rng = thread_rng();
+pub fn sha256_simple(
+    password: impl AsRef<[u8]>,
+    params: &Sha256Params,
+) -> Result<String, CryptError> {
+    sha256_simple_with_salt(password, &random_salt(), params)
+}
 
-    let salt: String = rng
-        .sample_iter(&ShaCryptDistribution)
-        .take(SALT_MAX_LEN)
-        .collect();
+/// Same as sha256_simple except the given salt will be used.
+///
+/// The output is the same as that of `crypt(3)` for the setting
+/// `$5$rounds=<ROUNDS>$<SALT>`, or `$5$<SALT>` for the default rounds.
+/// The salt must consist of `[a-zA-Z0-9./]`, which `gen_salt_with_rng`
+/// generates, or `Err(CryptError::SaltError)` is returned. Salts longer than
+/// defs::SALT_MAX_LEN characters are handled according to the
+/// [`SaltMode`] of `params`.
+///
+/// # Returns
+/// - `Ok(String)` containing the full SHA256 password hash format on success
+/// - `Err(CryptError)` if something went wrong.
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn sha256_simple_with_salt(
+    password: impl AsRef<[u8]>,
+    salt: &str,
+    params: &Sha256Params,
+) -> Result<String, CryptError> {
+    Ok(sha256_hash_string(password, salt, params)?.as_str().into())
+}
+
+/// Same as sha256_simple_with_salt except the hash string is returned on
+/// the stack, without allocating.
+///
+/// # Returns
+/// - `Ok(HashString)` containing the full SHA256 password hash format on
+///   success
+/// - `Err(CryptError)` if something went wrong.
+pub fn sha256_hash_string(
+    password: impl AsRef<[u8]>,
+    salt: &str,
+    params: &Sha256Params,
+) -> Result<HashString, CryptError> {
+    let salt = crypt_salt(salt, params.salt_mode)?;
+    let out = sha256_crypt(password.as_ref(), salt, params)?;
+    Ok(HashString::new(
+        SHA256_SALT_PREFIX,
+        default_rounds(params.rounds),
+        salt,
+        &b64::encode_sha256(&out),
+    ))
+}
+
+/// Same as sha256_hash_string except the hash string is written to `out`,
+/// without allocating.
+///
+/// A buffer of [`HASH_STRING_MAX_LEN`] bytes is large enough for any hash
+/// string.
+///
+/// # Returns
+/// - `Ok(usize)` containing the length of the hash string written to the
+///   start of `out` on success
+/// - `Err(CryptError::BufferTooSmall)` if `out` is too small for it
+/// - `Err(CryptError)` if something else went wrong.
+pub fn sha256_hash_into(
+    password: impl AsRef<[u8]>,
+    salt: &str,
+    params: &Sha256Params,
+    out: &mut [u8],
+) -> Result<usize, CryptError> {
+    sha256_hash_string(password, salt, params)?.copy_to(out)
+}
 
-    let out = sha256_crypt(password.as_bytes(), salt.as_bytes(), params)?;
+/// Hash `password` with `setting`, as `crypt(3)` does.
+///
+/// The setting is `$<ID>$rounds=<ROUNDS>$<SALT>` or `$<ID>$<SALT>`, as
+/// generated by `gensalt`. The salt ends at the first `$` and is truncated
+/// to defs::SALT_MAX_LEN characters, so a hash string is a valid setting as
+/// well, which gives back the same hash string for the right password. To
+/// verify passwords, use [`check`] instead, which compares the digests in
+/// constant time.
+///
+/// The hash string has a `rounds=` field if and only if the setting has
+/// one, which may be `rounds=5000`. Rounds outside of [`ROUNDS_MIN`] and
+/// [`ROUNDS_MAX`] are clamped as with [`RoundsMode::Clamp`], as glibc does,
+/// and the field has the clamped rounds.
+///
+/// # Returns
+/// - `Ok(String)` containing the full password hash format on success
+/// - `Err(CryptError::ParseError)` if the setting is malformed
+/// - `Err(CryptError::SaltError)` if the salt contains characters other than
+///   `[a-zA-Z0-9./]`.
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn crypt(password: impl AsRef<[u8]>, setting: &str) -> Result<String, CryptError> {
+    Ok(crypt_hash_string(password, setting)?.as_str().into())
+}
 
-    let mut result = String::new();
-    result.push_str(SHA256_SALT_PREFIX);
-    if params.rounds != ROUNDS_DEFAULT {
-        result.push_str(&format!("{}{}", SHA256_ROUNDS_PREFIX, params.rounds));
-        result.push('$');
+/// Same as crypt except the hash string is returned on the stack, without
+/// allocating.
+///
+/// # Returns
+/// - `Ok(HashString)` containing the full password hash format on success
+/// - `Err(CryptError)` if something went wrong, see `crypt`.
+pub fn crypt_hash_string(
+    password: impl AsRef<[u8]>,
+    setting: &str,
+) -> Result<HashString, CryptError> {
+    let setting = parse_setting(setting)?;
+    let salt = crypt_salt(setting.salt, SaltMode::Truncate)?;
+    let rounds = RoundsMode::Clamp.apply(setting.rounds.unwrap_or(ROUNDS_DEFAULT))?;
+    let rounds_field = setting.rounds.map(|_| rounds);
+
+    Ok(match setting.variant {
+        Variant::Sha256Crypt => {
+            let out = sha256_crypt(password.as_ref(), salt, &Sha256Params::new(rounds)?)?;
+            HashString::new(
+                SHA256_SALT_PREFIX,
+                rounds_field,
+                salt,
+                &b64::encode_sha256(&out),
+            )
+        }
+        Variant::Sha512Crypt => {
+            let out = sha512_crypt(password.as_ref(), salt, &Sha512Params::new(rounds)?)?;
+            HashString::new(
+                SHA512_SALT_PREFIX,
+                rounds_field,
+                salt,
+                &b64::encode_sha512(&out),
+            )
+        }
+    })
+}
+
+/// The rounds field of hash strings: none for the default rounds, as
+/// `crypt(3)` does.
+fn default_rounds(rounds: usize) -> Option<usize> {
+    if rounds == ROUNDS_DEFAULT {
+        None
+    } else {
+        Some(rounds)
     }
-    result.push_str(&salt);
-    result.push('$');
-    let s = String::from_utf8(b64::encode_sha256(&out).to_vec())?;
-    result.push_str(&s);
-    Ok(result)
 }
 
 /// Checks that given password matches provided hash.
 ///
+/// The digests are compared in constant time with [`sha512_verify`].
+/// Malformed hashes are reported only after hashing the password with the
+/// variant and rounds found in them, like mismatching passwords.
+///
 /// # Arguments
-/// - `password` - expected password
+/// - `password` - expected password, as bytes or a `str`
 /// - `hashed_value` - the hashed value which should be used for checking,
-/// should be of format mentioned in [1]: `$6$<SALT>$<PWD>`
+///   should be of format mentioned in [1]: `$6$<SALT>$<PWD>`
 ///
 /// # Return
 /// `OK(())` if password matches otherwise Err(CheckError) in case of invalid
 /// format or password mismatch.
 ///
 /// [1]: https://www.akkadia.org/drepper/SHA-crypt.txt
+pub fn sha512_check(password: impl AsRef<[u8]>, hashed_value: &str) -> Result<(), CheckError> {
+    check_variant(
+        password.as_ref(),
+        hashed_value,
+        Some(Variant::Sha512Crypt),
+        SaltMode::Strict,
+    )
+}
+
+/// Verify that `password` hashes to the raw digest `hash` with `salt` and
+/// `params`, comparing the digests in constant time.
+///
+/// Long salts are handled according to the [`SaltMode`] of `params` as in
+/// sha512_crypt, and [`sha512_check`] uses this function once it has
+/// parsed the hash string.
+///
+/// # Return
+/// `Ok(())` if the password matches, `Err(CheckError::HashMismatch)`
+/// otherwise.
+pub fn sha512_verify(
+    password: &[u8],
+    salt: &[u8],
+    params: &Sha512Params,
+    hash: &[u8; BLOCK_SIZE_SHA512],
+) -> Result<(), CheckError> {
+    let output = sha512_crypt(password, salt, params).map_err(CheckError::Crypt)?;
+    verify(&output, hash)
+}
+
+/// Checks that given password matches provided hash.
+///
+/// The digests are compared in constant time with [`sha256_verify`].
+/// Malformed hashes are reported only after hashing the password with the
+/// variant and rounds found in them, like mismatching passwords.
+///
+/// # Arguments
+/// - `password` - expected password, as bytes or a `str`
+/// - `hashed_value` - the hashed value which should be used for checking,
+///   should be of format mentioned in [1]: `$5$<SALT>$<PWD>`
+///
+/// # Return
+/// `OK(())` if password matches otherwise Err(CheckError) in case of invalid
+/// format or password mismatch.
+///
+/// [1]: https://www.akkadia.org/drepper/SHA-crypt.txt
+pub fn sha256_check(password: impl AsRef<[u8]>, hashed_value: &str) -> Result<(), CheckError> {
+    check_variant(
+        password.as_ref(),
+        hashed_value,
+        Some(Variant::Sha256Crypt),
+        SaltMode::Strict,
+    )
+}
+
+/// Verify that `password` hashes to the raw digest `hash` with `salt` and
+/// `params`, comparing the digests in constant time.
+///
+/// Long salts are handled according to the [`SaltMode`] of `params` as in
+/// sha256_crypt, and [`sha256_check`] uses this function once it has
+/// parsed the hash string.
+///
+/// # Return
+/// `Ok(())` if the password matches, `Err(CheckError::HashMismatch)`
+/// otherwise.
+pub fn sha256_verify(
+    password: &[u8],
+    salt: &[u8],
+    params: &Sha256Params,
+    hash: &[u8; BLOCK_SIZE_SHA256],
+) -> Result<(), CheckError> {
+    let output = sha256_crypt(password, salt, params).map_err(CheckError::Crypt)?;
+    verify(&output, hash)
+}
+
+/// Checks that given password matches provided hash, using SHA256 or SHA512
+/// depending on its identifier.
+///
+/// # Arguments
+/// - `password` - expected password, as bytes or a `str`
+/// - `hashed_value` - the hashed value which should be used for checking,
+///   should be of format mentioned in [1]: `$5$<SALT>$<PWD>` or
+///   `$6$<SALT>$<PWD>`
+///
+/// The hash string is parsed with [`parse`], and the digests are compared in
+/// constant time.
+///
+/// # Return
+/// - `Ok(())` if the password matches
+/// - `Err(CheckError::HashMismatch)` if it doesn't
+/// - `Err(CheckError::UnsupportedAlgorithm)` if the hash string is of another
+///   algorithm, such as `$1$` or `$y$`
+/// - `Err(CheckError::InvalidFormat)` if the hash string is malformed.
+///
+/// [1]: https://www.akkadia.org/drepper/SHA-crypt.txt
+pub fn check(password: impl AsRef<[u8]>, hashed_value: &str) -> Result<(), CheckError> {
+    check_variant(password.as_ref(), hashed_value, None, SaltMode::Strict)
+}
+
+/// Same as [`check`], except the reason the verification fails is reported
+/// as a [`VerifyOutcome`]: a mismatching password, the `$<ID>$` prefix of
+/// another algorithm, or the way the hash string is malformed.
+///
+/// The digests are still compared in constant time, and malformed hash
+/// strings are still only reported after hashing the password.
+pub fn check_outcome<'a>(password: impl AsRef<[u8]>, hashed_value: &'a str) -> VerifyOutcome<'a> {
+    VerifyOutcome::new(check(password, hashed_value), hashed_value)
+}
+
+/// Whether `password` matches a `$5$` or `$6$` hash string, for callers who
+/// don't need to know why it doesn't: see [`check_outcome`].
+pub fn password_matches(password: impl AsRef<[u8]>, hashed_value: &str) -> bool {
+    check(password, hashed_value).is_ok()
+}
+
+/// Same as check except salts longer than defs::SALT_MAX_LEN characters are
+/// handled according to `salt_mode`.
+///
+/// `crypt(3)` never writes such salts to hash strings, but some
+/// implementations do, and truncate them when hashing. Their hash strings
+/// can be verified with [`SaltMode::Truncate`], while [`check`] rejects them
+/// as malformed.
+pub fn check_with_salt_mode(
+    password: impl AsRef<[u8]>,
+    hashed_value: &str,
+    salt_mode: SaltMode,
+) -> Result<(), CheckError> {
+    check_variant(password.as_ref(), hashed_value, None, salt_mode)
+}
+
+/// Whether a hash string should be replaced by one of the `variant` with at
+/// least `min_rounds` rounds, e.g. after a successful login.
+///
+/// Hash strings without a `rounds=` field have [`ROUNDS_DEFAULT`] rounds,
+/// and out of range rounds count as clamped, as in [`check`]. The password
+/// isn't verified: see `verify_and_rehash` for that.
+///
+/// # Return
+/// - `Ok(true)` if the hash string is of another variant, or has fewer
+///   rounds
+/// - `Ok(false)` if it's strong enough
+/// - `Err(ParseError)` if it's malformed, see [`parse`].
+pub fn needs_rehash(
+    hashed_value: &str,
+    variant: Variant,
+    min_rounds: usize,
+) -> Result<bool, ParseError> {
+    let parsed = parse(hashed_value)?;
+    let rounds = parsed.rounds().clamp(ROUNDS_MIN, ROUNDS_MAX);
+
+    Ok(parsed.variant() != variant || rounds < min_rounds)
+}
+
+/// Check `password` against a hash string, and if it matches but
+/// [`needs_rehash`] with the rounds of `params`, hash it again as a
+/// SHA512-crypt hash string with those parameters and a random salt.
+///
+/// Nothing is hashed again unless the password matches.
+///
+/// # Return
+/// - `Ok(None)` if the password matches and the hash string is strong
+///   enough
+/// - `Ok(Some(String))` containing the new hash string if the password
+///   matches and the hash string needs to be replaced
+/// - `Err(CheckError)` as returned by [`check`] otherwise.
 #[cfg(feature = "simple")]
 #[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
-pub fn sha512_check(password: &str, hashed_value: &str) -> Result<(), CheckError> {
-    let mut iter = hashed_value.split('$');
-
-    // Check that there are no characters before the first "$"
-    if iter.next() != Some("") {
-        return Err(CheckError::InvalidFormat(
-            "Should start with '$".to_string(),
-        ));
+pub fn verify_and_rehash(
+    password: impl AsRef<[u8]>,
+    hashed_value: &str,
+    params: &Sha512Params,
+) -> Result<Option<String>, CheckError> {
+    let password = password.as_ref();
+    check(password, hashed_value)?;
+
+    if !needs_rehash(hashed_value, Variant::Sha512Crypt, params.rounds())? {
+        return Ok(None);
     }
 
-    if iter.next() != Some("6") {
-        return Err(CheckError::InvalidFormat(format!(
-            "does not contain SHA512 identifier: '{SHA512_SALT_PREFIX}'",
-        )));
+    sha512_simple(password, params)
+        .map(Some)
+        .map_err(CheckError::Crypt)
+}
+
+/// Check a hash string of the `expected` variant, or of either variant.
+fn check_variant(
+    password: &[u8],
+    hashed_value: &str,
+    expected: Option<Variant>,
+    salt_mode: SaltMode,
+) -> Result<(), CheckError> {
+    let parsed = match parse_with_salt_mode(hashed_value, salt_mode) {
+        Ok(parsed) if expected.map_or(true, |variant| variant == parsed.variant()) => parsed,
+        Ok(_) => {
+            return malformed(password, hashed_value, ParseError::InvalidPrefix.into());
+        }
+        Err(err) => return malformed(password, hashed_value, err.into()),
+    };
+
+    let salt = parsed.salt().as_bytes();
+    let hash = parsed.hash().as_bytes();
+
+    // The rounds of hash strings are clamped as with `RoundsMode::Clamp`,
+    // as `crypt(3)` does
+    let rounds = parsed.rounds().clamp(ROUNDS_MIN, ROUNDS_MAX);
+
+    match parsed.variant() {
+        Variant::Sha256Crypt => match b64::decode_sha256(hash) {
+            Ok(hash) => {
+                let params = Sha256Params { rounds, salt_mode };
+                sha256_verify(password, salt, &params, &hash)
+            }
+            Err(err) => malformed(password, hashed_value, err.into()),
+        },
+        Variant::Sha512Crypt => match b64::decode_sha512(hash) {
+            Ok(hash) => {
+                let params = Sha512Params { rounds, salt_mode };
+                sha512_verify(password, salt, &params, &hash)
+            }
+            Err(err) => malformed(password, hashed_value, err.into()),
+        },
     }
+}
 
-    let mut next = iter.next().ok_or_else(|| {
-        CheckError::InvalidFormat("Does not contain a rounds or salt nor hash string".to_string())
-    })?;
-    let rounds = if next.starts_with(SHA512_ROUNDS_PREFIX) {
-        let rounds = next;
-        next = iter.next().ok_or_else(|| {
-            CheckError::InvalidFormat("Does not contain a salt nor hash string".to_string())
-        })?;
-
-        rounds[SHA512_ROUNDS_PREFIX.len()..].parse().map_err(|_| {
-            CheckError::InvalidFormat(format!(
-                "{SHA512_ROUNDS_PREFIX} specifier need to be a number",
-            ))
-        })?
-    } else {
-        ROUNDS_DEFAULT
+/// Hash `password` as for a mismatching password, so that malformed hashes
+/// take about as long to reject, and return `err`.
+///
+/// The variant, rounds and salt are read from `hashed_value` as far as
+/// possible, and default to those of SHA512-crypt hashes otherwise.
+fn malformed(password: &[u8], hashed_value: &str, err: CheckError) -> Result<(), CheckError> {
+    let mut fields = hashed_value.split('$').skip(1);
+    let variant = match fields.next() {
+        Some("5") => Variant::Sha256Crypt,
+        _ => Variant::Sha512Crypt,
     };
 
-    let salt = next;
+    let mut salt = fields.next().unwrap_or("");
+    let mut rounds = ROUNDS_DEFAULT;
+    if let Some(digits) = salt.strip_prefix(ROUNDS_PREFIX) {
+        rounds = digits.parse().unwrap_or(ROUNDS_DEFAULT);
+        salt = fields.next().unwrap_or("");
+    }
+    let rounds = rounds.clamp(ROUNDS_MIN, ROUNDS_MAX);
+    let salt = &salt.as_bytes()[..salt.len().min(SALT_MAX_LEN)];
 
-    let hash = iter
-        .next()
-        .ok_or_else(|| CheckError::InvalidFormat("Does not contain a hash string".to_string()))?;
+    match variant {
+        Variant::Sha256Crypt => {
+            let params = Sha256Params::new(rounds).map_err(CheckError::Crypt)?;
+            let _ = sha256_verify(password, salt, &params, &[0u8; BLOCK_SIZE_SHA256]);
+        }
+        Variant::Sha512Crypt => {
+            let params = Sha512Params::new(rounds).map_err(CheckError::Crypt)?;
+            let _ = sha512_verify(password, salt, &params, &[0u8; BLOCK_SIZE_SHA512]);
+        }
+    }
+    Err(err)
+}
 
-    // Make sure there is no trailing data after the final "$"
-    if iter.next().is_some() {
-        return Err(CheckError::InvalidFormat(
-            "Trailing characters present".to_string(),
-        ));
+/// Generate a salt of `len` characters from `rng`, using only the
+/// characters `[a-zA-Z0-9./]` accepted by the hashing functions.
+///
+/// # Returns
+/// - `Ok(String)` containing the salt on success
+/// - `Err(CryptError::SaltTooLong)` if `len` is greater than
+///   defs::SALT_MAX_LEN.
+#[cfg(feature = "rand_core")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rand_core")))]
+pub fn gen_salt_with_rng(
+    rng: &mut (impl CryptoRng + RngCore),
+    len: usize,
+) -> Result<String, CryptError> {
+    if len > SALT_MAX_LEN {
+        return Err(CryptError::SaltTooLong);
     }
 
-    let params = Sha512Params { rounds };
+    const RANGE: u32 = 26 + 26 + 10 + 2; // 2 == "./"
+    let mut salt = String::with_capacity(len);
+    while salt.len() < len {
+        let var = rng.next_u32() >> (32 - 6);
+        if var < RANGE {
+            salt.push(TAB[var as usize] as char);
+        }
+    }
+    Ok(salt)
+}
 
-    let output = match sha512_crypt(password.as_bytes(), salt.as_bytes(), &params) {
-        Ok(v) => v,
-        Err(e) => return Err(CheckError::Crypt(e)),
+/// Generate a setting for [`crypt`] from `rng`, as `crypt_gensalt(3)` does.
+///
+/// The setting is `$<ID>$rounds=<ROUNDS>$<SALT>` with a salt of
+/// defs::SALT_MAX_LEN characters. `rounds` of 0 selects [`ROUNDS_DEFAULT`],
+/// which is left out of the setting, and other rounds are clamped to
+/// [`ROUNDS_MIN`] and [`ROUNDS_MAX`].
+#[cfg(feature = "rand_core")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rand_core")))]
+pub fn gensalt(variant: Variant, rounds: usize, rng: &mut (impl CryptoRng + RngCore)) -> String {
+    use core::fmt::Write;
+
+    let rounds = match rounds {
+        0 => ROUNDS_DEFAULT,
+        _ => rounds.clamp(ROUNDS_MIN, ROUNDS_MAX),
     };
 
-    let hash = b64::decode_sha512(hash.as_bytes())?;
+    let mut setting = String::from(variant.prefix());
+    if let Some(rounds) = default_rounds(rounds) {
+        write!(setting, "{}{}$", ROUNDS_PREFIX, rounds).expect("writing to a String");
+    }
+    setting.push_str(&gen_salt_with_rng(rng, SALT_MAX_LEN).expect("salt length is valid"));
+    setting
+}
+
+/// Generate a salt of the maximum length from the thread RNG.
+#[cfg(feature = "simple")]
+fn random_salt() -> String {
+    gen_salt_with_rng(&mut rand::thread_rng(), SALT_MAX_LEN).expect("salt length is valid")
+}
+
+/// Check that `salt` consists of `[a-zA-Z0-9./]`, as `crypt(3)` stops at
+/// the first other character, and apply `salt_mode` to it.
+fn crypt_salt(salt: &str, salt_mode: SaltMode) -> Result<&[u8], CryptError> {
+    let salt = salt.as_bytes();
+    if !salt.iter().all(|c| TAB.contains(c)) {
+        return Err(CryptError::SaltError);
+    }
+    salt_mode.apply(salt)
+}
 
+/// Compare a computed hash with the expected one in constant time.
+fn verify(output: &[u8], hash: &[u8]) -> Result<(), CheckError> {
     use subtle::ConstantTimeEq;
-    if output.ct_eq(&hash).into() {
+    if output.ct_eq(hash).into() {
         Ok(())
     } else {
         Err(CheckError::HashMismatch)
     }
 }
 
-/// Checks that given password matches provided hash.
-///
-/// # Arguments
-/// - `password` - expected password
-/// - `hashed_value` - the hashed value which should be used for checking,
-/// should be of format mentioned in [1]: `$6$<SALT>$<PWD>`
-///
-/// # Return
-/// `OK(())` if password matches otherwise Err(CheckError) in case of invalid
-/// format or password mismatch.
-///
-/// [1]: https://www.akkadia.org/drepper/SHA-crypt.txt
-#[cfg(feature = "simple")]
-#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
-pub fn sha256_check(password: &str, hashed_value: &str) -> Result<(), CheckError> {
-    let mut iter = hashed_value.split('$');
-
-    // Check that there are no characters before the first "$"
-    if iter.next() != Some("") {
-        return Err(CheckError::InvalidFormat(
-            "Should start with '$".to_string(),
-        ));
+/// The SHA-crypt algorithm with the digest `D` of `N` bytes, shared by
+/// SHA256-crypt and SHA512-crypt, which only differ by their digest.
+fn sha_crypt<D: Digest, const N: usize>(
+    password: &[u8],
+    salt: &[u8],
+    rounds: usize,
+) -> Result<[u8; N], CryptError> {
+    let pw_len = password.len();
+
+    let salt_len = salt.len();
+
+    if !(ROUNDS_MIN..=ROUNDS_MAX).contains(&rounds) {
+        return Err(CryptError::RoundsError);
     }
 
-    if iter.next() != Some("5") {
-        return Err(CheckError::InvalidFormat(format!(
-            "does not contain SHA256 identifier: '{SHA256_SALT_PREFIX}'",
-        )));
+    let digest_a = sha_crypt_intermediate::<D, N>(password, salt);
+
+    // 13.
+    let mut hasher_alt = D::new();
+
+    // 14.
+    for _ in 0..pw_len {
+        hasher_alt.update(password);
     }
 
-    let mut next = iter.next().ok_or_else(|| {
-        CheckError::InvalidFormat("Does not contain a rounds or salt nor hash string".to_string())
-    })?;
-    let rounds = if next.starts_with(SHA256_ROUNDS_PREFIX) {
-        let rounds = next;
-        next = iter.next().ok_or_else(|| {
-            CheckError::InvalidFormat("Does not contain a salt nor hash string".to_string())
-        })?;
-
-        rounds[SHA256_ROUNDS_PREFIX.len()..].parse().map_err(|_| {
-            CheckError::InvalidFormat(format!(
-                "{SHA256_ROUNDS_PREFIX} specifier need to be a number",
-            ))
-        })?
-    } else {
-        ROUNDS_DEFAULT
-    };
+    // 15.
+    let dp = hasher_alt.finalize();
 
-    let salt = next;
+    // 16.
+    // Create byte sequence P.
+    let p_seq = ByteSeq::new(pw_len, &dp);
 
-    let hash = iter
-        .next()
-        .ok_or_else(|| CheckError::InvalidFormat("Does not contain a hash string".to_string()))?;
+    // 17.
+    hasher_alt = D::new();
 
-    // Make sure there is no trailing data after the final "$"
-    if iter.next().is_some() {
-        return Err(CheckError::InvalidFormat(
-            "Trailing characters present".to_string(),
-        ));
+    // 18.
+    // For every character in the password add the entire password.
+    for _ in 0..(16 + digest_a[0] as usize) {
+        hasher_alt.update(salt);
     }
 
-    let params = Sha256Params { rounds };
+    // 19.
+    // Finish the digest.
+    let ds = hasher_alt.finalize();
+
+    // 20.
+    // Create byte sequence S.
+    let s_seq = ByteSeq::new(salt_len, &ds);
 
-    let output = match sha256_crypt(password.as_bytes(), salt.as_bytes(), &params) {
-        Ok(v) => v,
-        Err(e) => return Err(CheckError::Crypt(e)),
-    };
+    let mut digest_c = digest_a;
+    // Repeatedly run the collected hash value through the digest to burn
+    // CPU cycles
+    for i in 0..rounds {
+        // new hasher
+        let mut hasher = D::new();
 
-    let hash = b64::decode_sha256(hash.as_bytes())?;
+        // Add key or last result
+        if (i & 1) != 0 {
+            p_seq.update(&mut hasher);
+        } else {
+            hasher.update(digest_c);
+        }
 
-    use subtle::ConstantTimeEq;
-    if output.ct_eq(&hash).into() {
-        Ok(())
-    } else {
-        Err(CheckError::HashMismatch)
+        // Add salt for numbers not divisible by 3
+        if i % 3 != 0 {
+            s_seq.update(&mut hasher);
+        }
+
+        // Add key for numbers not divisible by 7
+        if i % 7 != 0 {
+            p_seq.update(&mut hasher);
+        }
+
+        // Add key or last result
+        if (i & 1) != 0 {
+            hasher.update(digest_c);
+        } else {
+            p_seq.update(&mut hasher);
+        }
+
+        digest_c.clone_from_slice(&hasher.finalize());
     }
+
+    Ok(digest_c)
 }
 
-#[cfg(feature = "simple")]
-#[derive(Debug)]
-struct ShaCryptDistribution;
+/// Longest byte sequence P or S stored on the stack: passwords longer than
+/// this are rare, and their P is fed to the digests in pieces instead.
+const BYTE_SEQ_MAX_LEN: usize = 256;
 
-#[cfg(feature = "simple")]
-impl Distribution<char> for ShaCryptDistribution {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> char {
-        const RANGE: u32 = 26 + 26 + 10 + 2; // 2 == "./"
-        loop {
-            let var = rng.next_u32() >> (32 - 6);
-            if var < RANGE {
-                return TAB[var as usize] as char;
+/// Byte sequence P or S: `len` bytes of the digest `fill_from` repeated,
+/// built once and reused by every round.
+struct ByteSeq<'a> {
+    buf: [u8; BYTE_SEQ_MAX_LEN],
+    len: usize,
+    fill_from: &'a [u8],
+}
+
+impl<'a> ByteSeq<'a> {
+    fn new(len: usize, fill_from: &'a [u8]) -> Self {
+        let mut buf = [0u8; BYTE_SEQ_MAX_LEN];
+        if len <= BYTE_SEQ_MAX_LEN {
+            for (b, &f) in buf[..len].iter_mut().zip(fill_from.iter().cycle()) {
+                *b = f;
             }
         }
+        ByteSeq {
+            buf,
+            len,
+            fill_from,
+        }
     }
-}
 
-fn produce_byte_seq(len: usize, fill_from: &[u8]) -> Vec<u8> {
-    let bs = fill_from.len();
-    let mut seq: Vec<u8> = vec![0; len];
-    let mut offset: usize = 0;
-    for _ in 0..(len / bs) {
-        seq[offset..offset + bs].clone_from_slice(fill_from);
-        offset += bs;
+    fn update<D: Digest>(&self, hasher: &mut D) {
+        if self.len <= BYTE_SEQ_MAX_LEN {
+            hasher.update(&self.buf[..self.len]);
+        } else {
+            let bs = self.fill_from.len();
+            for _ in 0..(self.len / bs) {
+                hasher.update(self.fill_from);
+            }
+            hasher.update(&self.fill_from[..(self.len % bs)]);
+        }
     }
-    let from_slice = &fill_from[..(len % bs)];
-    seq[offset..offset + (len % bs)].clone_from_slice(from_slice);
-    seq
 }
 
-fn sha512crypt_intermediate(password: &[u8], salt: &[u8]) -> [u8; BLOCK_SIZE_SHA512] {
+fn sha_crypt_intermediate<D: Digest, const N: usize>(password: &[u8], salt: &[u8]) -> [u8; N] {
     let pw_len = password.len();
 
-    let mut hasher = Sha512::default();
+    let mut hasher = D::new();
     hasher.update(password);
     hasher.update(salt);
 
     // 4.
-    let mut hasher_alt = Sha512::default();
+    let mut hasher_alt = D::new();
     // 5.
     hasher_alt.update(password);
     // 6.
@@ -603,11 +993,11 @@ fn sha512crypt_intermediate(password: &[u8], salt: &[u8]) -> [u8; BLOCK_SIZE_SHA
     let digest_b = hasher_alt.finalize();
 
     // 9.
-    for _ in 0..(pw_len / BLOCK_SIZE_SHA512) {
-        hasher.update(digest_b);
+    for _ in 0..(pw_len / N) {
+        hasher.update(&digest_b);
     }
     // 10.
-    hasher.update(&digest_b[..(pw_len % BLOCK_SIZE_SHA512)]);
+    hasher.update(&digest_b[..(pw_len % N)]);
 
     // 11
     let mut n = pw_len;
@@ -616,7 +1006,7 @@ fn sha512crypt_intermediate(password: &[u8], salt: &[u8]) -> [u8; BLOCK_SIZE_SHA
             break;
         }
         if (n & 1) != 0 {
-            hasher.update(digest_b);
+            hasher.update(&digest_b);
         } else {
             hasher.update(password);
         }
@@ -627,45 +1017,89 @@ fn sha512crypt_intermediate(password: &[u8], salt: &[u8]) -> [u8; BLOCK_SIZE_SHA
     hasher.finalize().as_slice().try_into().unwrap()
 }
 
-fn sha256crypt_intermediate(password: &[u8], salt: &[u8]) -> [u8; BLOCK_SIZE_SHA256] {
-    let pw_len = password.len();
-
-    let mut hasher = Sha256::default();
-    hasher.update(password);
-    hasher.update(salt);
+#[cfg(test)]
+mod tests {
+    use super::{sha_crypt, sha_crypt_intermediate};
+    use crate::defs::SALT_MAX_LEN;
+    use rand_chacha::{
+        rand_core::{RngCore, SeedableRng},
+        ChaCha8Rng,
+    };
+    use sha2::{Digest, Sha256, Sha512};
+
+    /// SHA-crypt with the byte sequences P and S fed to the digests one byte
+    /// at a time.
+    fn sha_crypt_bytewise<D: Digest, const N: usize>(
+        password: &[u8],
+        salt: &[u8],
+        rounds: usize,
+    ) -> [u8; N] {
+        let digest_a = sha_crypt_intermediate::<D, N>(password, salt);
+
+        let mut hasher = D::new();
+        for _ in 0..password.len() {
+            hasher.update(password);
+        }
+        let dp = hasher.finalize();
 
-    // 4.
-    let mut hasher_alt = Sha256::default();
-    // 5.
-    hasher_alt.update(password);
-    // 6.
-    hasher_alt.update(salt);
-    // 7.
-    hasher_alt.update(password);
-    // 8.
-    let digest_b = hasher_alt.finalize();
+        let mut hasher = D::new();
+        for _ in 0..(16 + digest_a[0] as usize) {
+            hasher.update(salt);
+        }
+        let ds = hasher.finalize();
 
-    // 9.
-    for _ in 0..(pw_len / BLOCK_SIZE_SHA256) {
-        hasher.update(digest_b);
+        let update_seq = |hasher: &mut D, len: usize, digest: &[u8]| {
+            for i in 0..len {
+                hasher.update([digest[i % N]]);
+            }
+        };
+
+        let mut digest_c = digest_a;
+        for i in 0..rounds {
+            let mut hasher = D::new();
+            if (i & 1) != 0 {
+                update_seq(&mut hasher, password.len(), &dp);
+            } else {
+                hasher.update(digest_c);
+            }
+            if i % 3 != 0 {
+                update_seq(&mut hasher, salt.len(), &ds);
+            }
+            if i % 7 != 0 {
+                update_seq(&mut hasher, password.len(), &dp);
+            }
+            if (i & 1) != 0 {
+                hasher.update(digest_c);
+            } else {
+                update_seq(&mut hasher, password.len(), &dp);
+            }
+            digest_c.copy_from_slice(&hasher.finalize());
+        }
+        digest_c
     }
-    // 10.
-    hasher.update(&digest_b[..(pw_len % BLOCK_SIZE_SHA256)]);
 
-    // 11
-    let mut n = pw_len;
-    for _ in 0..pw_len {
-        if n == 0 {
-            break;
+    #[test]
+    fn byte_seqs_match_bytewise() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let mut password = [0u8; 600];
+        let mut salt = [0u8; SALT_MAX_LEN + 1];
+
+        // Passwords up to and past the stack buffer
+        for &pw_len in &[0, 1, 31, 32, 33, 63, 64, 65, 255, 256, 257, 320, 600] {
+            let salt_len = rng.next_u32() as usize % salt.len();
+            rng.fill_bytes(&mut password[..pw_len]);
+            rng.fill_bytes(&mut salt[..salt_len]);
+            let (password, salt) = (&password[..pw_len], &salt[..salt_len]);
+            let rounds = 1000 + rng.next_u32() as usize % 100;
+
+            assert_eq!(
+                sha_crypt::<Sha256, 32>(password, salt, rounds).unwrap(),
+                sha_crypt_bytewise::<Sha256, 32>(password, salt, rounds),
+            );
+            assert_eq!(
+                sha_crypt::<Sha512, 64>(password, salt, rounds).unwrap(),
+                sha_crypt_bytewise::<Sha512, 64>(password, salt, rounds),
+            );
         }
-        if (n & 1) != 0 {
-            hasher.update(digest_b);
-        } else {
-            hasher.update(password);
-        }
-        n >>= 1;
     }
-
-    // 12.
-    hasher.finalize().as_slice().try_into().unwrap()
 }
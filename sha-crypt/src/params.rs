@@ -1,6 +1,6 @@
 //! Algorithm parameters.
 
-use crate::errors;
+use crate::{defs::SALT_MAX_LEN, errors, parse, Variant};
 use core::default::Default;
 
 /// Default number of rounds.
@@ -12,52 +12,295 @@ pub const ROUNDS_MIN: usize = 1_000;
 /// Maximum number of rounds allowed.
 pub const ROUNDS_MAX: usize = 999_999_999;
 
+/// How rounds outside of [`ROUNDS_MIN`]..=[`ROUNDS_MAX`] are handled.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RoundsMode {
+    /// Reject them with `Err(CryptError::RoundsError)`, the default of
+    /// [`ParamsBuilder`].
+    Strict,
+
+    /// Raise rounds below [`ROUNDS_MIN`] to it and lower those above
+    /// [`ROUNDS_MAX`] to it, as the specification and `crypt(3)` do. The
+    /// check functions use this mode for the rounds of hash strings.
+    Clamp,
+}
+
+impl RoundsMode {
+    /// Apply this mode to `rounds`.
+    pub fn apply(self, rounds: usize) -> Result<usize, errors::CryptError> {
+        match self {
+            RoundsMode::Strict if !(ROUNDS_MIN..=ROUNDS_MAX).contains(&rounds) => {
+                Err(errors::CryptError::RoundsError)
+            }
+            RoundsMode::Strict => Ok(rounds),
+            RoundsMode::Clamp => Ok(rounds.clamp(ROUNDS_MIN, ROUNDS_MAX)),
+        }
+    }
+}
+
+impl Default for RoundsMode {
+    fn default() -> Self {
+        RoundsMode::Strict
+    }
+}
+
+/// How salts longer than 16 characters are handled.
+///
+/// The specification and `crypt(3)` truncate them, so hashing with such a
+/// salt gives a hash that other implementations may or may not verify,
+/// depending on whether they truncate it in turn.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SaltMode {
+    /// Reject them with `Err(CryptError::SaltTooLong)`, the default.
+    Strict,
+
+    /// Truncate them to 16 characters, as the specification and `crypt(3)`
+    /// do.
+    Truncate,
+}
+
+impl SaltMode {
+    /// Apply this mode to `salt`.
+    pub fn apply(self, salt: &[u8]) -> Result<&[u8], errors::CryptError> {
+        match self {
+            SaltMode::Strict if salt.len() > SALT_MAX_LEN => Err(errors::CryptError::SaltTooLong),
+            SaltMode::Strict => Ok(salt),
+            SaltMode::Truncate => Ok(&salt[..salt.len().min(SALT_MAX_LEN)]),
+        }
+    }
+}
+
+impl Default for SaltMode {
+    fn default() -> Self {
+        SaltMode::Strict
+    }
+}
+
 /// Algorithm parameters.
 #[derive(Debug, Clone)]
 pub struct Sha512Params {
     pub(crate) rounds: usize,
+    pub(crate) salt_mode: SaltMode,
 }
 
 impl Default for Sha512Params {
     fn default() -> Self {
         Sha512Params {
             rounds: ROUNDS_DEFAULT,
+            salt_mode: SaltMode::default(),
         }
     }
 }
 
 impl Sha512Params {
-    /// Create new algorithm parameters.
+    /// Create new algorithm parameters, see also [`ParamsBuilder`].
     pub fn new(rounds: usize) -> Result<Sha512Params, errors::CryptError> {
         if (ROUNDS_MIN..=ROUNDS_MAX).contains(&rounds) {
-            Ok(Sha512Params { rounds })
+            Ok(Sha512Params {
+                rounds,
+                salt_mode: SaltMode::default(),
+            })
         } else {
             Err(errors::CryptError::RoundsError)
         }
     }
+
+    /// Parameters of the `$6$` hash string `hashed_value`, e.g. that of a
+    /// shadow file, to hash new passwords alike.
+    ///
+    /// Hash strings without a `rounds=` field have [`ROUNDS_DEFAULT`]
+    /// rounds, and rounds outside of [`ROUNDS_MIN`]..=[`ROUNDS_MAX`] are
+    /// clamped with [`RoundsMode::Clamp`], as `crypt(3)` does.
+    ///
+    /// # Return
+    /// - `Ok(Sha512Params)` if `hashed_value` is well-formed, see
+    ///   [`parse`](crate::parse)
+    /// - `Err(ParseError::InvalidPrefix)` if it's the hash string of another
+    ///   variant
+    /// - `Err(ParseError)` if it's malformed otherwise.
+    pub fn from_hash(hashed_value: &str) -> Result<Sha512Params, errors::ParseError> {
+        let parsed = parse(hashed_value)?;
+        if parsed.variant() != Variant::Sha512Crypt {
+            return Err(errors::ParseError::InvalidPrefix);
+        }
+
+        Ok(Sha512Params {
+            rounds: parsed.rounds().clamp(ROUNDS_MIN, ROUNDS_MAX),
+            salt_mode: SaltMode::default(),
+        })
+    }
+
+    /// Number of rounds.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// How salts longer than 16 characters are handled.
+    pub fn salt_mode(&self) -> SaltMode {
+        self.salt_mode
+    }
 }
 
 /// Algorithm parameters.
 #[derive(Debug, Clone)]
 pub struct Sha256Params {
     pub(crate) rounds: usize,
+    pub(crate) salt_mode: SaltMode,
 }
 
 impl Default for Sha256Params {
     fn default() -> Self {
         Sha256Params {
             rounds: ROUNDS_DEFAULT,
+            salt_mode: SaltMode::default(),
         }
     }
 }
 
 impl Sha256Params {
-    /// Create new algorithm parameters.
+    /// Create new algorithm parameters, see also [`ParamsBuilder`].
     pub fn new(rounds: usize) -> Result<Sha256Params, errors::CryptError> {
         if (ROUNDS_MIN..=ROUNDS_MAX).contains(&rounds) {
-            Ok(Sha256Params { rounds })
+            Ok(Sha256Params {
+                rounds,
+                salt_mode: SaltMode::default(),
+            })
         } else {
             Err(errors::CryptError::RoundsError)
         }
     }
+
+    /// Parameters of the `$5$` hash string `hashed_value`, e.g. that of a
+    /// shadow file, to hash new passwords alike.
+    ///
+    /// Hash strings without a `rounds=` field have [`ROUNDS_DEFAULT`]
+    /// rounds, and rounds outside of [`ROUNDS_MIN`]..=[`ROUNDS_MAX`] are
+    /// clamped with [`RoundsMode::Clamp`], as `crypt(3)` does.
+    ///
+    /// # Return
+    /// - `Ok(Sha256Params)` if `hashed_value` is well-formed, see
+    ///   [`parse`](crate::parse)
+    /// - `Err(ParseError::InvalidPrefix)` if it's the hash string of another
+    ///   variant
+    /// - `Err(ParseError)` if it's malformed otherwise.
+    pub fn from_hash(hashed_value: &str) -> Result<Sha256Params, errors::ParseError> {
+        let parsed = parse(hashed_value)?;
+        if parsed.variant() != Variant::Sha256Crypt {
+            return Err(errors::ParseError::InvalidPrefix);
+        }
+
+        Ok(Sha256Params {
+            rounds: parsed.rounds().clamp(ROUNDS_MIN, ROUNDS_MAX),
+            salt_mode: SaltMode::default(),
+        })
+    }
+
+    /// Number of rounds.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// How salts longer than 16 characters are handled.
+    pub fn salt_mode(&self) -> SaltMode {
+        self.salt_mode
+    }
+}
+
+/// Builder for [`Sha256Params`] and [`Sha512Params`].
+///
+/// ```
+/// use sha_crypt::{ParamsBuilder, Sha512Params};
+///
+/// let params: Sha512Params = ParamsBuilder::new().rounds(10_000).build_sha512()?;
+/// # Ok::<(), sha_crypt::CryptError>(())
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParamsBuilder {
+    rounds: u32,
+    rounds_mode: RoundsMode,
+    salt_mode: SaltMode,
+}
+
+impl ParamsBuilder {
+    /// Create a new builder with the default parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of rounds, between [`ROUNDS_MIN`] and [`ROUNDS_MAX`]
+    /// unless they're clamped, see [`ParamsBuilder::rounds_mode`].
+    ///
+    /// Hash strings only include the rounds when they differ from
+    /// [`ROUNDS_DEFAULT`].
+    pub fn rounds(&mut self, rounds: u32) -> &mut Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Set how rounds outside of [`ROUNDS_MIN`]..=[`ROUNDS_MAX`] are
+    /// handled, [`RoundsMode::Strict`] by default.
+    pub fn rounds_mode(&mut self, rounds_mode: RoundsMode) -> &mut Self {
+        self.rounds_mode = rounds_mode;
+        self
+    }
+
+    /// Set how salts longer than 16 characters are handled,
+    /// [`SaltMode::Strict`] by default.
+    pub fn salt_mode(&mut self, salt_mode: SaltMode) -> &mut Self {
+        self.salt_mode = salt_mode;
+        self
+    }
+
+    /// Get the finished [`Sha256Params`].
+    ///
+    /// This checks or clamps the rounds according to the
+    /// [`RoundsMode`].
+    pub fn build_sha256(&self) -> Result<Sha256Params, errors::CryptError> {
+        Ok(Sha256Params {
+            salt_mode: self.salt_mode,
+            ..Sha256Params::new(self.checked_rounds()?)?
+        })
+    }
+
+    /// Get the finished [`Sha512Params`].
+    ///
+    /// This checks or clamps the rounds according to the
+    /// [`RoundsMode`].
+    pub fn build_sha512(&self) -> Result<Sha512Params, errors::CryptError> {
+        Ok(Sha512Params {
+            salt_mode: self.salt_mode,
+            ..Sha512Params::new(self.checked_rounds()?)?
+        })
+    }
+
+    fn checked_rounds(&self) -> Result<usize, errors::CryptError> {
+        let rounds = usize::try_from(self.rounds).unwrap_or(usize::MAX);
+        self.rounds_mode.apply(rounds)
+    }
+}
+
+impl Default for ParamsBuilder {
+    fn default() -> Self {
+        Self {
+            rounds: ROUNDS_DEFAULT as u32,
+            rounds_mode: RoundsMode::default(),
+            salt_mode: SaltMode::default(),
+        }
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Sha256Params {
+    type Error = errors::CryptError;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Sha256Params, errors::CryptError> {
+        builder.build_sha256()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Sha512Params {
+    type Error = errors::CryptError;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Sha512Params, errors::CryptError> {
+        builder.build_sha512()
+    }
 }
@@ -0,0 +1,226 @@
+//! Parser of `$5$` and `$6$` hash strings.
+
+use crate::{
+    b64,
+    defs::{PW_SIZE_SHA256, PW_SIZE_SHA512, SALT_MAX_LEN},
+    errors::ParseError,
+    SaltMode, ROUNDS_DEFAULT, ROUNDS_PREFIX,
+};
+
+/// Length of the longest input accepted by [`parse`], far longer than any
+/// hash string written by `crypt(3)`.
+///
+/// Longer inputs are rejected with [`ParseError::TooLong`] without being
+/// looked at.
+pub const INPUT_MAX_LEN: usize = 256;
+
+/// SHA-crypt variants of hash strings.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Variant {
+    /// SHA256-crypt, the `$5$` hashes
+    Sha256Crypt,
+
+    /// SHA512-crypt, the `$6$` hashes
+    Sha512Crypt,
+}
+
+impl Variant {
+    /// Prefix of the hash strings of this variant: `$5$` or `$6$`.
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Variant::Sha256Crypt => crate::SHA256_SALT_PREFIX,
+            Variant::Sha512Crypt => crate::SHA512_SALT_PREFIX,
+        }
+    }
+
+    /// Length of the encoded digest of this variant.
+    fn encoded_len(&self) -> usize {
+        match self {
+            Variant::Sha256Crypt => PW_SIZE_SHA256,
+            Variant::Sha512Crypt => PW_SIZE_SHA512,
+        }
+    }
+}
+
+/// A parsed hash string `$<ID>$rounds=<ROUNDS>$<SALT>$<HASH>`, see [`parse`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ParsedShaCrypt<'a> {
+    variant: Variant,
+    rounds: usize,
+    rounds_explicit: bool,
+    salt: &'a str,
+    hash: &'a str,
+}
+
+impl<'a> ParsedShaCrypt<'a> {
+    /// Variant of the hash string.
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Number of rounds, [`ROUNDS_DEFAULT`] if the hash string has no
+    /// `rounds=` field.
+    ///
+    /// They aren't checked against [`ROUNDS_MIN`](crate::ROUNDS_MIN) and
+    /// [`ROUNDS_MAX`](crate::ROUNDS_MAX): the check functions clamp them with
+    /// [`RoundsMode::Clamp`](crate::RoundsMode::Clamp).
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// Whether the hash string has a `rounds=` field, which may be
+    /// `rounds=5000`.
+    pub fn rounds_explicit(&self) -> bool {
+        self.rounds_explicit
+    }
+
+    /// Salt, at most defs::SALT_MAX_LEN characters.
+    pub fn salt(&self) -> &'a str {
+        self.salt
+    }
+
+    /// Digest, still encoded.
+    pub fn hash(&self) -> &'a str {
+        self.hash
+    }
+}
+
+/// Parse a `$5$` or `$6$` hash string, without verifying a password.
+///
+/// The check functions use this parser as well.
+///
+/// # Return
+/// - `Ok(ParsedShaCrypt)` if the hash string is well-formed
+/// - `Err(ParseError::UnsupportedAlgorithm)` if it's the hash string of
+///   another algorithm, such as `$1$` or `$y$`
+/// - `Err(ParseError::SaltTooLong)` if its salt is longer than
+///   defs::SALT_MAX_LEN characters, see [`parse_with_salt_mode`]
+/// - `Err(ParseError::TooLong)` if it's longer than [`INPUT_MAX_LEN`]
+/// - `Err(ParseError)` if it's malformed otherwise.
+///
+/// The salt may be empty. The rounds are read as by glibc's `crypt(3)`:
+/// leading zeros are ignored, so that `rounds=0999` is 999 rounds, and too
+/// many rounds to be represented are `usize::MAX`.
+pub fn parse(hashed_value: &str) -> Result<ParsedShaCrypt<'_>, ParseError> {
+    parse_with_salt_mode(hashed_value, SaltMode::Strict)
+}
+
+/// Same as [`parse`] except salts longer than defs::SALT_MAX_LEN characters
+/// are handled according to `salt_mode`: with [`SaltMode::Truncate`], the
+/// salt of the parsed hash string is truncated.
+pub fn parse_with_salt_mode(
+    hashed_value: &str,
+    salt_mode: SaltMode,
+) -> Result<ParsedShaCrypt<'_>, ParseError> {
+    if hashed_value.len() > INPUT_MAX_LEN {
+        return Err(ParseError::TooLong);
+    }
+
+    let mut fields = hashed_value.split('$');
+    let variant = parse_variant(&mut fields)?;
+
+    let mut salt = fields.next().ok_or(ParseError::MissingField)?;
+    let mut rounds = ROUNDS_DEFAULT;
+    let rounds_explicit = salt.starts_with(ROUNDS_PREFIX);
+    if let Some(digits) = salt.strip_prefix(ROUNDS_PREFIX) {
+        rounds = parse_rounds(digits)?;
+        salt = fields.next().ok_or(ParseError::MissingField)?;
+    }
+
+    if salt.len() > SALT_MAX_LEN {
+        match salt_mode {
+            SaltMode::Strict => return Err(ParseError::SaltTooLong),
+            // Non-ASCII salts can't be truncated to 16 characters
+            SaltMode::Truncate => salt = salt.get(..SALT_MAX_LEN).ok_or(ParseError::SaltTooLong)?,
+        }
+    }
+
+    let hash = fields.next().ok_or(ParseError::MissingField)?;
+
+    // No trailing data after the final "$"
+    if fields.next().is_some() {
+        return Err(ParseError::TrailingData);
+    }
+
+    if hash.len() != variant.encoded_len() {
+        return Err(ParseError::InvalidHashLength);
+    }
+
+    let decoded = match variant {
+        Variant::Sha256Crypt => b64::decode_sha256(hash.as_bytes()).map(drop),
+        Variant::Sha512Crypt => b64::decode_sha512(hash.as_bytes()).map(drop),
+    };
+    decoded.map_err(|_| ParseError::InvalidHash)?;
+
+    Ok(ParsedShaCrypt {
+        variant,
+        rounds,
+        rounds_explicit,
+        salt,
+        hash,
+    })
+}
+
+/// Parse the `$<ID>$` prefix of a hash string or setting.
+fn parse_variant<'a>(fields: &mut impl Iterator<Item = &'a str>) -> Result<Variant, ParseError> {
+    // No characters before the first "$"
+    if fields.next() != Some("") {
+        return Err(ParseError::InvalidPrefix);
+    }
+
+    match fields.next() {
+        Some("5") => Ok(Variant::Sha256Crypt),
+        Some("6") => Ok(Variant::Sha512Crypt),
+        // `$<ID>$` of another `crypt(3)` algorithm
+        Some(id) if !id.is_empty() && fields.next().is_some() => {
+            Err(ParseError::UnsupportedAlgorithm)
+        }
+        _ => Err(ParseError::InvalidPrefix),
+    }
+}
+
+/// A parsed setting `$<ID>$rounds=<ROUNDS>$<SALT>`, see [`parse_setting`].
+pub(crate) struct Setting<'a> {
+    pub(crate) variant: Variant,
+    pub(crate) rounds: Option<usize>,
+    pub(crate) salt: &'a str,
+}
+
+/// Parse a setting as `crypt(3)` does: the salt ends at the first `$`, and
+/// anything after it, such as the digest of a hash string, is ignored.
+///
+/// The rounds are read as by [`parse`], but the salt isn't truncated to
+/// defs::SALT_MAX_LEN characters yet.
+pub(crate) fn parse_setting(setting: &str) -> Result<Setting<'_>, ParseError> {
+    if setting.len() > INPUT_MAX_LEN {
+        return Err(ParseError::TooLong);
+    }
+
+    let mut fields = setting.split('$');
+    let variant = parse_variant(&mut fields)?;
+
+    let mut salt = fields.next().ok_or(ParseError::MissingField)?;
+    let mut rounds = None;
+    if let Some(digits) = salt.strip_prefix(ROUNDS_PREFIX) {
+        rounds = Some(parse_rounds(digits)?);
+        salt = fields.next().ok_or(ParseError::MissingField)?;
+    }
+
+    Ok(Setting {
+        variant,
+        rounds,
+        salt,
+    })
+}
+
+/// Parse the digits of a `rounds=` field, which must be a decimal integer.
+///
+/// Too many rounds to be represented are `usize::MAX`, as glibc's `strtoul`
+/// saturates: they are clamped anyway.
+fn parse_rounds(digits: &str) -> Result<usize, ParseError> {
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(ParseError::InvalidRounds);
+    }
+
+    Ok(digits.parse().unwrap_or(usize::MAX))
+}
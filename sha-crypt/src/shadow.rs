@@ -0,0 +1,275 @@
+//! Lines of `/etc/shadow` and htpasswd files.
+//!
+//! [`ShadowEntry`] and [`HtpasswdEntry`] split a line into its fields,
+//! borrowing them from the line, and write them back unchanged with their
+//! `Display` implementations. The hash field is verified with [`check`], so
+//! both `$5$` and `$6$` hash strings are accepted.
+//!
+//! ```
+//! use sha_crypt::shadow::{self, PasswordField, ShadowEntry};
+//!
+//! let line = "alice:$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1:19000:0:99999:7:::";
+//!
+//! let entry = ShadowEntry::parse(line)?;
+//! assert_eq!(entry.username(), "alice");
+//! assert_eq!(entry.max_age(), "99999");
+//! assert!(matches!(entry.password(), PasswordField::Hash(_)));
+//!
+//! assert!(shadow::verify_user(line, "Hello world!").is_ok());
+//! # Ok::<(), sha_crypt::ShadowError>(())
+//! ```
+
+use crate::{check, errors::ShadowError};
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+
+/// Number of fields of a shadow line after the hash field.
+const AGING_FIELDS: usize = 7;
+
+/// State of the account given by a hash field.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PasswordField<'a> {
+    /// A hash string, not necessarily of SHA-crypt.
+    Hash(&'a str),
+
+    /// The account is locked: the field is `*`, or starts with `!` as
+    /// written by `passwd -l`, which keeps the hash string after it.
+    Locked,
+
+    /// The account has no password: the field is empty.
+    Empty,
+}
+
+impl<'a> PasswordField<'a> {
+    fn new(field: &'a str) -> PasswordField<'a> {
+        if field.is_empty() {
+            PasswordField::Empty
+        } else if field.starts_with('!') || field.starts_with('*') {
+            PasswordField::Locked
+        } else {
+            PasswordField::Hash(field)
+        }
+    }
+
+    /// Verify `password` against the hash string with [`check`].
+    fn verify(&self, password: &[u8]) -> Result<(), ShadowError> {
+        match *self {
+            PasswordField::Hash(hash) if hash.starts_with('$') => Ok(check(password, hash)?),
+            // DES-crypt, or the `{SCHEME}` hashes of htpasswd
+            PasswordField::Hash(_) => Err(ShadowError::UnsupportedScheme),
+            PasswordField::Locked => Err(ShadowError::Locked),
+            PasswordField::Empty => Err(ShadowError::NoPassword),
+        }
+    }
+}
+
+/// A line of `/etc/shadow`:
+/// `<USER>:<HASH>:<LASTCHG>:<MIN>:<MAX>:<WARN>:<INACTIVE>:<EXPIRE>:<RESERVED>`.
+///
+/// The fields after the hash field are kept as they are, often empty.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ShadowEntry<'a> {
+    username: &'a str,
+    hash: &'a str,
+    aging: [&'a str; AGING_FIELDS],
+}
+
+impl<'a> ShadowEntry<'a> {
+    /// Parse a line of `/etc/shadow`, which may end with a newline.
+    ///
+    /// # Return
+    /// - `Ok(ShadowEntry)` if the line has 9 fields and a username; the hash
+    ///   field isn't parsed yet
+    /// - `Err(ShadowError::InvalidLine)` otherwise.
+    pub fn parse(line: &'a str) -> Result<ShadowEntry<'a>, ShadowError> {
+        let mut fields = split_line(line);
+        let username = parse_username(fields.next())?;
+        let hash = fields.next().ok_or(ShadowError::InvalidLine)?;
+
+        let mut aging = [""; AGING_FIELDS];
+        for field in aging.iter_mut() {
+            *field = fields.next().ok_or(ShadowError::InvalidLine)?;
+        }
+
+        if fields.next().is_some() {
+            return Err(ShadowError::InvalidLine);
+        }
+
+        Ok(ShadowEntry {
+            username,
+            hash,
+            aging,
+        })
+    }
+
+    /// Login name.
+    pub fn username(&self) -> &'a str {
+        self.username
+    }
+
+    /// Hash field, as it is in the line.
+    pub fn hash_field(&self) -> &'a str {
+        self.hash
+    }
+
+    /// State of the account given by the hash field.
+    pub fn password(&self) -> PasswordField<'a> {
+        PasswordField::new(self.hash)
+    }
+
+    /// Date of the last password change, in days since the epoch.
+    pub fn last_change(&self) -> &'a str {
+        self.aging[0]
+    }
+
+    /// Minimum password age, in days.
+    pub fn min_age(&self) -> &'a str {
+        self.aging[1]
+    }
+
+    /// Maximum password age, in days.
+    pub fn max_age(&self) -> &'a str {
+        self.aging[2]
+    }
+
+    /// Password warning period, in days.
+    pub fn warn_period(&self) -> &'a str {
+        self.aging[3]
+    }
+
+    /// Password inactivity period, in days.
+    pub fn inactivity_period(&self) -> &'a str {
+        self.aging[4]
+    }
+
+    /// Account expiration date, in days since the epoch.
+    pub fn expiration(&self) -> &'a str {
+        self.aging[5]
+    }
+
+    /// Reserved field.
+    pub fn reserved(&self) -> &'a str {
+        self.aging[6]
+    }
+
+    /// Verify `password` against the hash field, see [`verify_user`].
+    pub fn verify(&self, password: impl AsRef<[u8]>) -> Result<(), ShadowError> {
+        self.password().verify(password.as_ref())
+    }
+
+    /// Write the entry as a line, without newline.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn to_line(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for ShadowEntry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.username, self.hash)?;
+        for field in self.aging {
+            write!(f, ":{}", field)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A line of an htpasswd file: `<USER>:<HASH>`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct HtpasswdEntry<'a> {
+    username: &'a str,
+    hash: &'a str,
+}
+
+impl<'a> HtpasswdEntry<'a> {
+    /// Parse a line of an htpasswd file, which may end with a newline.
+    ///
+    /// # Return
+    /// - `Ok(HtpasswdEntry)` if the line has 2 fields and a username; the
+    ///   hash field isn't parsed yet
+    /// - `Err(ShadowError::InvalidLine)` otherwise.
+    pub fn parse(line: &'a str) -> Result<HtpasswdEntry<'a>, ShadowError> {
+        let mut fields = split_line(line);
+        let username = parse_username(fields.next())?;
+        let hash = fields.next().ok_or(ShadowError::InvalidLine)?;
+
+        if fields.next().is_some() {
+            return Err(ShadowError::InvalidLine);
+        }
+
+        Ok(HtpasswdEntry { username, hash })
+    }
+
+    /// Login name.
+    pub fn username(&self) -> &'a str {
+        self.username
+    }
+
+    /// Hash field, as it is in the line.
+    pub fn hash_field(&self) -> &'a str {
+        self.hash
+    }
+
+    /// State of the account given by the hash field.
+    pub fn password(&self) -> PasswordField<'a> {
+        PasswordField::new(self.hash)
+    }
+
+    /// Verify `password` against the hash field, see
+    /// [`verify_htpasswd_user`].
+    pub fn verify(&self, password: impl AsRef<[u8]>) -> Result<(), ShadowError> {
+        self.password().verify(password.as_ref())
+    }
+
+    /// Write the entry as a line, without newline.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn to_line(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for HtpasswdEntry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.username, self.hash)
+    }
+}
+
+/// Verify `password` against the hash field of a line of `/etc/shadow`.
+///
+/// # Return
+/// - `Ok(())` if the password matches
+/// - `Err(ShadowError::Locked)` or `Err(ShadowError::NoPassword)` if the
+///   account is locked or has no password, without hashing anything
+/// - `Err(ShadowError::UnsupportedScheme)` if the hash field isn't a
+///   SHA-crypt hash string
+/// - `Err(ShadowError::Check)` if the hash string is malformed or the
+///   password doesn't match, see [`check`]
+/// - `Err(ShadowError::InvalidLine)` if the line is malformed.
+pub fn verify_user(line: &str, password: impl AsRef<[u8]>) -> Result<(), ShadowError> {
+    ShadowEntry::parse(line)?.verify(password)
+}
+
+/// Verify `password` against the hash field of a line of an htpasswd file,
+/// with the same results as [`verify_user`].
+pub fn verify_htpasswd_user(line: &str, password: impl AsRef<[u8]>) -> Result<(), ShadowError> {
+    HtpasswdEntry::parse(line)?.verify(password)
+}
+
+/// Split a line into its fields, leaving out its newline.
+fn split_line(line: &str) -> core::str::Split<'_, char> {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    line.split(':')
+}
+
+fn parse_username(field: Option<&str>) -> Result<&str, ShadowError> {
+    match field {
+        Some(username) if !username.is_empty() => Ok(username),
+        _ => Err(ShadowError::InvalidLine),
+    }
+}
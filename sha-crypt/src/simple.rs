@@ -0,0 +1,252 @@
+//! Implementation of the `password-hash` crate API.
+
+use crate::{
+    b64, parse, sha256_crypt, sha512_crypt, CheckError, ParseError, SaltMode, Sha256Params,
+    Sha512Params, Variant, ROUNDS_DEFAULT, ROUNDS_MAX, ROUNDS_MIN,
+};
+use core::{fmt, str::FromStr};
+use password_hash::{
+    errors::InvalidValue, Decimal, Error, Ident, McfHasher, Output, ParamsString, PasswordHash,
+    PasswordHasher, Result, Salt,
+};
+
+/// Name of the rounds parameter in PHC strings, the same as in MCF hashes.
+const ROUNDS_PARAM: &str = "rounds";
+
+/// SHA-crypt type for use with [`PasswordHasher`].
+///
+/// Hashes are PHC strings such as `$sha512-crypt$rounds=5000$<SALT>$<HASH>`,
+/// where the salt is used verbatim as in `crypt(3)` and the hash is encoded
+/// in the standard B64 of PHC strings. Hashes in the `$5$` and `$6$` MCF
+/// formats of `crypt(3)` can be converted to them with [`McfHasher`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub struct ShaCrypt;
+
+impl PasswordHasher for ShaCrypt {
+    type Params = ShaCryptParams;
+
+    fn hash_password_customized<'a>(
+        &self,
+        password: &[u8],
+        alg_id: Option<Ident<'a>>,
+        version: Option<Decimal>,
+        params: ShaCryptParams,
+        salt: impl Into<Salt<'a>>,
+    ) -> Result<PasswordHash<'a>> {
+        let algorithm = Algorithm::try_from(alg_id.unwrap_or(Algorithm::default().ident()))?;
+
+        // Versions unsupported
+        if version.is_some() {
+            return Err(Error::Version);
+        }
+
+        let salt = salt.into();
+        let salt_bytes = salt.as_str().as_bytes();
+        let rounds = params.rounds as usize;
+
+        // PHC strings keep the whole salt, and their hashes have always been
+        // computed with it truncated as in `crypt(3)`
+        let salt_mode = SaltMode::Truncate;
+
+        let output = match algorithm {
+            Algorithm::Sha256Crypt => Output::new(&sha256_crypt(
+                password,
+                salt_bytes,
+                &Sha256Params { rounds, salt_mode },
+            )?),
+            Algorithm::Sha512Crypt => Output::new(&sha512_crypt(
+                password,
+                salt_bytes,
+                &Sha512Params { rounds, salt_mode },
+            )?),
+        }?;
+
+        Ok(PasswordHash {
+            algorithm: algorithm.ident(),
+            version: None,
+            params: params.try_into()?,
+            salt: Some(salt),
+            hash: Some(output),
+        })
+    }
+}
+
+impl McfHasher for ShaCrypt {
+    /// Upgrade a `$5$` or `$6$` hash of `crypt(3)` to a PHC hash.
+    ///
+    /// Its salt must be a valid PHC salt, which rules out salts shorter than
+    /// 4 characters.
+    fn upgrade_mcf_hash<'a>(&self, hash: &'a str) -> Result<PasswordHash<'a>> {
+        let parsed = parse(hash).map_err(|err| match err {
+            ParseError::InvalidPrefix | ParseError::UnsupportedAlgorithm => Error::Algorithm,
+            err => CheckError::from(err).into(),
+        })?;
+        let encoded = parsed.hash().as_bytes();
+
+        let (algorithm, output) = match parsed.variant() {
+            Variant::Sha256Crypt => (
+                Algorithm::Sha256Crypt,
+                Output::new(&b64::decode_sha256(encoded)?)?,
+            ),
+            Variant::Sha512Crypt => (
+                Algorithm::Sha512Crypt,
+                Output::new(&b64::decode_sha512(encoded)?)?,
+            ),
+        };
+
+        let params = ShaCryptParams {
+            rounds: u32::try_from(parsed.rounds())
+                .map_err(|_| InvalidValue::TooLong.param_error())?,
+        };
+        params.check()?;
+
+        Ok(PasswordHash {
+            algorithm: algorithm.ident(),
+            version: None,
+            params: params.try_into()?,
+            salt: Some(Salt::from_b64(parsed.salt())?),
+            hash: Some(output),
+        })
+    }
+}
+
+/// SHA-crypt variants.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+#[non_exhaustive]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub enum Algorithm {
+    /// SHA256-crypt, the `$5$` hashes of `crypt(3)`
+    Sha256Crypt,
+
+    /// SHA512-crypt, the `$6$` hashes of `crypt(3)`
+    Sha512Crypt,
+}
+
+impl Default for Algorithm {
+    fn default() -> Self {
+        Self::Sha512Crypt
+    }
+}
+
+impl Algorithm {
+    /// SHA256-crypt algorithm identifier
+    pub const SHA256_CRYPT_IDENT: Ident<'static> = Ident::new_unwrap("sha256-crypt");
+
+    /// SHA512-crypt algorithm identifier
+    pub const SHA512_CRYPT_IDENT: Ident<'static> = Ident::new_unwrap("sha512-crypt");
+
+    /// Parse an [`Algorithm`] from the provided string.
+    pub fn new(id: impl AsRef<str>) -> Result<Self> {
+        id.as_ref().parse()
+    }
+
+    /// Get the [`Ident`] that corresponds to this SHA-crypt [`Algorithm`].
+    pub fn ident(&self) -> Ident<'static> {
+        match self {
+            Algorithm::Sha256Crypt => Self::SHA256_CRYPT_IDENT,
+            Algorithm::Sha512Crypt => Self::SHA512_CRYPT_IDENT,
+        }
+    }
+
+    /// Get the identifier string for this SHA-crypt [`Algorithm`].
+    pub fn as_str(&self) -> &str {
+        self.ident().as_str()
+    }
+}
+
+impl AsRef<str> for Algorithm {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Algorithm> {
+        Ident::try_from(s)?.try_into()
+    }
+}
+
+impl From<Algorithm> for Ident<'static> {
+    fn from(alg: Algorithm) -> Ident<'static> {
+        alg.ident()
+    }
+}
+
+impl<'a> TryFrom<Ident<'a>> for Algorithm {
+    type Error = Error;
+
+    fn try_from(ident: Ident<'a>) -> Result<Algorithm> {
+        match ident {
+            Self::SHA256_CRYPT_IDENT => Ok(Algorithm::Sha256Crypt),
+            Self::SHA512_CRYPT_IDENT => Ok(Algorithm::Sha512Crypt),
+            _ => Err(Error::Algorithm),
+        }
+    }
+}
+
+/// SHA-crypt params of [`ShaCrypt`], shared by both algorithms.
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ShaCryptParams {
+    /// Number of rounds, between [`ROUNDS_MIN`] and [`ROUNDS_MAX`]
+    pub rounds: u32,
+}
+
+impl ShaCryptParams {
+    fn check(&self) -> Result<()> {
+        match self.rounds as usize {
+            rounds if rounds < ROUNDS_MIN => Err(InvalidValue::TooShort.param_error()),
+            rounds if rounds > ROUNDS_MAX => Err(InvalidValue::TooLong.param_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for ShaCryptParams {
+    fn default() -> ShaCryptParams {
+        ShaCryptParams {
+            rounds: ROUNDS_DEFAULT as u32,
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a PasswordHash<'a>> for ShaCryptParams {
+    type Error = Error;
+
+    fn try_from(hash: &'a PasswordHash<'a>) -> Result<Self> {
+        let mut params = ShaCryptParams::default();
+
+        if hash.version.is_some() {
+            return Err(Error::Version);
+        }
+
+        for (ident, value) in hash.params.iter() {
+            match ident.as_str() {
+                ROUNDS_PARAM => params.rounds = value.decimal()?,
+                _ => return Err(Error::ParamNameInvalid),
+            }
+        }
+
+        params.check()?;
+        Ok(params)
+    }
+}
+
+impl TryFrom<ShaCryptParams> for ParamsString {
+    type Error = Error;
+
+    fn try_from(input: ShaCryptParams) -> Result<ParamsString> {
+        let mut output = ParamsString::new();
+        output.add_decimal(ROUNDS_PARAM, input.rounds)?;
+        Ok(output)
+    }
+}
@@ -0,0 +1,115 @@
+//! Hash strings stored on the stack.
+
+use crate::{
+    defs::{PW_SIZE_SHA512, SALT_MAX_LEN},
+    errors::CryptError,
+};
+use core::{fmt, ops::Deref, str};
+
+/// Length of `rounds=` and the largest number of rounds, 999999999.
+const ROUNDS_MAX_LEN: usize = 7 + 9;
+
+/// Maximum length of a hash string:
+/// `$6$rounds=<ROUNDS>$<SALT>$<HASH>` with the longest rounds and salt.
+pub const HASH_STRING_MAX_LEN: usize = 3 + ROUNDS_MAX_LEN + 1 + SALT_MAX_LEN + 1 + PW_SIZE_SHA512;
+
+/// A `$5$` or `$6$` hash string, using no heap allocation.
+///
+/// It dereferences to `str`.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct HashString {
+    bytes: [u8; HASH_STRING_MAX_LEN],
+    len: usize,
+}
+
+impl HashString {
+    /// Format a hash as `$<ID>$rounds=<ROUNDS>$<SALT>$<HASH>`, leaving out
+    /// the rounds when they're `None`.
+    ///
+    /// The salt must be valid, see `crypt_salt`.
+    pub(crate) fn new(prefix: &str, rounds: Option<usize>, salt: &[u8], hash: &[u8]) -> HashString {
+        let mut string = HashString {
+            bytes: [0u8; HASH_STRING_MAX_LEN],
+            len: 0,
+        };
+
+        string.push(prefix.as_bytes());
+        if let Some(rounds) = rounds {
+            string.push(crate::ROUNDS_PREFIX.as_bytes());
+            string.push_decimal(rounds);
+            string.push(b"$");
+        }
+        string.push(salt);
+        string.push(b"$");
+        string.push(hash);
+        string
+    }
+
+    /// Borrow the hash string as a `str`.
+    pub fn as_str(&self) -> &str {
+        // Hash strings are ASCII
+        str::from_utf8(self.as_bytes()).expect("hash string is valid UTF-8")
+    }
+
+    /// Borrow the hash string as bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    /// Copy the hash string to the start of `out`.
+    ///
+    /// # Returns
+    /// - `Ok(usize)` containing the length of the hash string on success
+    /// - `Err(CryptError::BufferTooSmall)` if `out` is too small for it.
+    pub fn copy_to(&self, out: &mut [u8]) -> Result<usize, CryptError> {
+        out.get_mut(..self.len)
+            .ok_or(CryptError::BufferTooSmall)?
+            .copy_from_slice(self.as_bytes());
+        Ok(self.len)
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.bytes[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    fn push_decimal(&mut self, mut n: usize) {
+        let mut digits = [0u8; 20];
+        let mut i = digits.len();
+        loop {
+            i -= 1;
+            digits[i] = b'0' + (n % 10) as u8;
+            n /= 10;
+            if n == 0 {
+                break;
+            }
+        }
+        self.push(&digits[i..]);
+    }
+}
+
+impl AsRef<str> for HashString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Deref for HashString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for HashString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for HashString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
@@ -0,0 +1,125 @@
+//! The `crypt(3)` Base64 encoding.
+
+use rand_chacha::{
+    rand_core::{RngCore, SeedableRng},
+    ChaCha8Rng,
+};
+use sha_crypt::{
+    b64::{self, MAP_SHA256, MAP_SHA512},
+    sha256_crypt, sha512_crypt, B64Error, Sha256Params, Sha512Params,
+};
+
+/// Hash fields of the `crypt(3)` hashes of "Hello world!" salted with
+/// "saltstring".
+const SHA512_HASH: &str =
+    "svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1";
+const SHA256_HASH: &str = "5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc5";
+
+#[test]
+fn round_trip() {
+    let mut rng = ChaCha8Rng::seed_from_u64(137);
+    let mut src = [0u8; 200];
+    let mut encoded = [0u8; 267];
+    let mut decoded = [0u8; 200];
+
+    for len in 0..src.len() {
+        for _ in 0..10 {
+            rng.fill_bytes(&mut src[..len]);
+            let e = b64::encode_into(&src[..len], &mut encoded).unwrap();
+            assert_eq!(e.len(), b64::encoded_len(len));
+
+            let e = e.to_owned();
+            let d = b64::decode(&e, &mut decoded).unwrap();
+            assert_eq!(d, &src[..len]);
+        }
+    }
+}
+
+#[test]
+fn encode_into_short_buffer() {
+    let mut buf = [0u8; 3];
+    assert_eq!(
+        b64::encode_into(b"abc", &mut buf),
+        Err(B64Error::InvalidLength)
+    );
+    assert_eq!(b64::encode_into(b"ab", &mut buf), Ok("V74"));
+}
+
+#[test]
+fn decode_invalid() {
+    let mut buf = [0u8; 64];
+
+    for src in ["a", "abcde", "abcdefghi"] {
+        assert_eq!(b64::decode(src, &mut buf), Err(B64Error::InvalidLength));
+    }
+    assert_eq!(
+        b64::decode("abcd", &mut buf[..2]),
+        Err(B64Error::InvalidLength)
+    );
+
+    // Outside of the alphabet
+    for src in ["ab=", "ab+d", "ab-d", "ab d", "ab$d", "\u{e9}ab"] {
+        assert_eq!(b64::decode(src, &mut buf), Err(B64Error::InvalidEncoding));
+    }
+
+    // Unused bits set
+    assert_eq!(b64::decode("V74", &mut buf), Ok(&b"ab"[..]));
+    assert_eq!(b64::decode("V7k", &mut buf), Err(B64Error::InvalidEncoding));
+    assert_eq!(b64::decode("..", &mut buf), Ok(&b"\x00"[..]));
+    assert_eq!(b64::decode(".2", &mut buf), Err(B64Error::InvalidEncoding));
+}
+
+#[test]
+fn decode_crypt_hashes() {
+    let params = Sha512Params::default();
+    let hash = sha512_crypt(b"Hello world!", b"saltstring", &params).unwrap();
+    assert_eq!(b64::decode_sha512(SHA512_HASH.as_bytes()).unwrap(), hash);
+    assert_eq!(&b64::encode_sha512(&hash)[..], SHA512_HASH.as_bytes());
+
+    let params = Sha256Params::default();
+    let hash = sha256_crypt(b"Hello world!", b"saltstring", &params).unwrap();
+    assert_eq!(b64::decode_sha256(SHA256_HASH.as_bytes()).unwrap(), hash);
+    assert_eq!(&b64::encode_sha256(&hash)[..], SHA256_HASH.as_bytes());
+}
+
+#[test]
+fn decode_crypt_hashes_invalid() {
+    assert_eq!(
+        b64::decode_sha512(&SHA512_HASH.as_bytes()[1..]),
+        Err(B64Error::InvalidLength)
+    );
+    assert_eq!(
+        b64::decode_sha256(SHA512_HASH.as_bytes()),
+        Err(B64Error::InvalidLength)
+    );
+
+    let mut hash = SHA256_HASH.as_bytes().to_owned();
+    hash[10] = b'_';
+    assert_eq!(b64::decode_sha256(&hash), Err(B64Error::InvalidEncoding));
+}
+
+/// The permutations apply to the digest before the Base64 encoding.
+#[test]
+fn permutation_tables() {
+    for map in [&MAP_SHA256[..], &MAP_SHA512[..]] {
+        let mut seen = vec![false; map.len()];
+        for &i in map {
+            assert!(!seen[i as usize]);
+            seen[i as usize] = true;
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (i, b) in digest.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    let mut permuted = [0u8; 32];
+    for (i, &ti) in MAP_SHA256.iter().enumerate() {
+        permuted[i] = digest[ti as usize];
+    }
+    let mut buf = [0u8; 43];
+    assert_eq!(
+        b64::encode_into(&permuted, &mut buf).unwrap().as_bytes(),
+        &b64::encode_sha256(&digest)[..]
+    );
+}
@@ -0,0 +1,188 @@
+//! `crypt(3)` and `crypt_gensalt(3)` equivalents.
+
+use sha_crypt::{crypt_hash_string, CryptError, ParseError};
+
+#[cfg(feature = "alloc")]
+use sha_crypt::{check, crypt};
+
+#[cfg(feature = "rand_core")]
+use {
+    rand_chacha::{rand_core::SeedableRng, ChaCha20Rng},
+    sha_crypt::{gensalt, Variant},
+};
+
+/// Outputs of libxcrypt's `crypt("Hello world!", setting)`.
+const CRYPT_OUTPUTS: &[(&str, &str)] = &[
+    (
+        "$6$saltstring",
+        "$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1",
+    ),
+    (
+        "$5$saltstring",
+        "$5$saltstring$5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc5",
+    ),
+    (
+        "$6$rounds=5000$saltstring",
+        "$6$rounds=5000$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1",
+    ),
+    (
+        "$5$rounds=1000$0123456789abcdef",
+        "$5$rounds=1000$0123456789abcdef$6OM5l0Q5NhAtk73ktmlIelBKjLsTgvx5QdA0jQuKsr4",
+    ),
+    (
+        "$6$rounds=10000$0123456789abcdefXYZ",
+        "$6$rounds=10000$0123456789abcdef$YhFsu9pac4sb3iWRtTNSuC/rn/ij1XKJEc29udu2eIbeoE.rDALd.7Dq4a2p1Ia4wZKSF3PswU1iqTf/pCVN..",
+    ),
+    ("$5$", "$5$$mAwMsDaqjtxAtGqstEIf7OBR15rgcx.jSKGM94IKRj/"),
+    (
+        "$6$rounds=1000$",
+        "$6$rounds=1000$$.iDmP65p8twH4xRklgLM6b3KeMLRiE5OWGUiqIhHf/NGBlIiq.8G4dF5pUKl4NZyp6LUp47BEfZtAmJZfmV07/",
+    ),
+    (
+        "$6$rounds=77777$short$ignored",
+        "$6$rounds=77777$short$fAcEHgO6o21rSt1lqI8a7KCuvcwcIb0rZEkfuCqxcQy3nmhFH64SF1VuIpnGOTo7/l5kUqrxyVoxZWtv7ywdg/",
+    ),
+];
+
+#[test]
+fn crypt_settings() {
+    for &(setting, hash) in CRYPT_OUTPUTS {
+        assert_eq!(
+            crypt_hash_string("Hello world!", setting).unwrap().as_str(),
+            hash
+        );
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn crypt_string() {
+    for &(setting, hash) in CRYPT_OUTPUTS {
+        assert_eq!(crypt("Hello world!", setting).unwrap(), hash);
+    }
+}
+
+/// Hash strings are settings giving back the same hash string.
+#[test]
+fn crypt_hash_as_setting() {
+    for &(_, hash) in CRYPT_OUTPUTS {
+        assert_eq!(
+            crypt_hash_string("Hello world!", hash).unwrap().as_str(),
+            hash
+        );
+        assert_ne!(
+            crypt_hash_string("Hello world", hash).unwrap().as_str(),
+            hash
+        );
+    }
+}
+
+/// Hash strings accepted by `check` are settings as well, with the rounds
+/// read the same way.
+#[cfg(feature = "alloc")]
+#[test]
+fn crypt_checked_hash_as_setting() {
+    let hash = "$6$rounds=05000$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1";
+    assert!(check("Hello world!", hash).is_ok());
+    assert_eq!(
+        crypt("Hello world!", hash).unwrap(),
+        "$6$rounds=5000$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1"
+    );
+
+    let hash = "$5$rounds=0010$roundstoolow$yfvwcWrQ8l/K0DAWyuPMDNHpIVlTQebY9l/gL972bIC";
+    assert!(check("the minimum number is still observed", hash).is_ok());
+    assert_eq!(
+        crypt("the minimum number is still observed", hash).unwrap(),
+        "$5$rounds=1000$roundstoolow$yfvwcWrQ8l/K0DAWyuPMDNHpIVlTQebY9l/gL972bIC"
+    );
+}
+
+/// Settings `crypt(3)` rejects as well.
+#[test]
+fn crypt_invalid_settings() {
+    for (setting, err) in [
+        ("", ParseError::InvalidPrefix),
+        ("$", ParseError::InvalidPrefix),
+        ("$6", ParseError::MissingField),
+        ("$1$saltstring", ParseError::UnsupportedAlgorithm),
+        ("6$saltstring", ParseError::InvalidPrefix),
+        ("$6$rounds=1000", ParseError::MissingField),
+        ("$6$rounds=$saltstring", ParseError::InvalidRounds),
+        ("$6$rounds=abc$saltstring", ParseError::InvalidRounds),
+        ("$6$rounds=+5000$saltstring", ParseError::InvalidRounds),
+    ] {
+        match crypt_hash_string("Hello world!", setting) {
+            Err(CryptError::ParseError(e)) => assert_eq!(e, err, "{}", setting),
+            res => panic!("{}: {:?}", setting, res),
+        }
+    }
+
+    for setting in ["$6$ab:c", "$6$salt string", "$6$0123456789abcdef:"] {
+        assert!(matches!(
+            crypt_hash_string("Hello world!", setting),
+            Err(CryptError::SaltError)
+        ));
+    }
+}
+
+/// Out of range rounds are clamped, as glibc does.
+#[test]
+fn crypt_clamps_rounds() {
+    // glibc's output, which is libxcrypt's for `rounds=1000`
+    for (password, setting, hash) in [
+        (
+            "Hello world!",
+            "$6$rounds=999$saltstring",
+            "$6$rounds=1000$saltstring$Zu2Vknok2/f53APfN687ADnzeNBLcsEgTwvcBHMD2./07rZQAt8vsuKVufD15dyZh.LOLB/uZKf6I3GyON4bp/",
+        ),
+        (
+            "the minimum number is still observed",
+            "$5$rounds=10$roundstoolow",
+            "$5$rounds=1000$roundstoolow$yfvwcWrQ8l/K0DAWyuPMDNHpIVlTQebY9l/gL972bIC",
+        ),
+    ] {
+        assert_eq!(
+            crypt_hash_string(password, setting).unwrap().as_str(),
+            hash
+        );
+    }
+}
+
+#[cfg(feature = "rand_core")]
+#[test]
+fn gensalt_settings() {
+    let mut rng = ChaCha20Rng::seed_from_u64(138);
+    for (variant, rounds, prefix) in [
+        (Variant::Sha512Crypt, 0, "$6$"),
+        (Variant::Sha512Crypt, 5_000, "$6$"),
+        (Variant::Sha512Crypt, 50_000, "$6$rounds=50000$"),
+        (Variant::Sha256Crypt, 1_000, "$5$rounds=1000$"),
+        (Variant::Sha256Crypt, 10, "$5$rounds=1000$"),
+        (Variant::Sha256Crypt, usize::MAX, "$5$rounds=999999999$"),
+    ] {
+        let setting = gensalt(variant, rounds, &mut rng);
+        assert!(setting.starts_with(prefix), "{}", setting);
+        assert_eq!(setting.len(), prefix.len() + 16);
+    }
+}
+
+/// Settings from `gensalt` hashed by libxcrypt.
+#[cfg(feature = "rand_core")]
+#[test]
+fn gensalt_crypt() {
+    let mut rng = ChaCha20Rng::seed_from_u64(138);
+
+    let setting = gensalt(Variant::Sha512Crypt, 0, &mut rng);
+    assert_eq!(setting, "$6$3qXxR3vmMnRwq92n");
+    assert_eq!(
+        crypt("Hello world!", &setting).unwrap(),
+        "$6$3qXxR3vmMnRwq92n$nc04odF1Kv9CsGY0QTLCan71FvE0xCVb7mYYkjTHMP4uZsppQxIoOxm244AEgZiAtyzU2Xve3MzCvAKy0o5bt0"
+    );
+
+    let setting = gensalt(Variant::Sha256Crypt, 2_000, &mut rng);
+    assert_eq!(setting, "$5$rounds=2000$bKsWH6XvAFUvqYIQ");
+    assert_eq!(
+        crypt("Hello world!", &setting).unwrap(),
+        "$5$rounds=2000$bKsWH6XvAFUvqYIQ$nhu8hGnP82.Wz9F9RwyAUbDGjuAMugoatgmo/Gw68s0"
+    );
+}
@@ -0,0 +1,195 @@
+//! Error variants and their messages.
+
+use sha_crypt::{
+    check, check_outcome, crypt_hash_string, password_matches, sha256_check, sha512_check,
+    sha512_crypt, sha512_hash_string, CheckError, CryptError, ParamsBuilder, ParseError,
+    Sha512Params, VerifyOutcome,
+};
+
+const SHA512_HASH: &str = "$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1";
+
+#[test]
+fn crypt_error_variants() {
+    let mut builder = ParamsBuilder::new();
+    assert!(matches!(
+        builder.rounds(999).build_sha512(),
+        Err(CryptError::RoundsError)
+    ));
+    assert!(matches!(
+        Sha512Params::new(1_000_000_000),
+        Err(CryptError::RoundsError)
+    ));
+
+    let params = Sha512Params::default();
+    assert!(matches!(
+        sha512_hash_string("password", "salt:", &params),
+        Err(CryptError::SaltError)
+    ));
+    assert!(matches!(
+        sha512_hash_string("password", "toolongsaltstring", &params),
+        Err(CryptError::SaltTooLong)
+    ));
+    assert!(matches!(
+        sha512_crypt(b"password", b"toolongsaltstring", &params),
+        Err(CryptError::SaltTooLong)
+    ));
+
+    for (setting, err) in [
+        ("$6", ParseError::MissingField),
+        ("6$saltstring", ParseError::InvalidPrefix),
+        ("$1$saltstring", ParseError::UnsupportedAlgorithm),
+        ("$6$rounds=ten$saltstring", ParseError::InvalidRounds),
+    ] {
+        assert!(
+            matches!(crypt_hash_string("password", setting), Err(CryptError::ParseError(e)) if e == err),
+            "{}",
+            setting
+        );
+    }
+}
+
+#[test]
+fn check_error_variants() {
+    assert!(matches!(
+        check("wrong", SHA512_HASH),
+        Err(CheckError::HashMismatch)
+    ));
+    assert!(matches!(
+        check("Hello world!", "$1$saltstring$OH4IDuTlsuTYPdED1gsuiRMy"),
+        Err(CheckError::UnsupportedAlgorithm)
+    ));
+    assert!(matches!(
+        sha256_check("Hello world!", SHA512_HASH),
+        Err(CheckError::InvalidFormat(ParseError::InvalidPrefix))
+    ));
+    assert!(sha512_check("Hello world!", SHA512_HASH).is_ok());
+
+    let invalid_b64 = SHA512_HASH.replace("svn8", "svn!");
+    let trailing = format!("{}$", SHA512_HASH);
+    for (hash, err) in [
+        ("", ParseError::InvalidPrefix),
+        ("$6$saltstring", ParseError::MissingField),
+        ("$6$rounds=ten$saltstring$x", ParseError::InvalidRounds),
+        ("$6$toolongsaltstring$x", ParseError::SaltTooLong),
+        ("$6$saltstring$svn8", ParseError::InvalidHashLength),
+        (&invalid_b64, ParseError::InvalidHash),
+        (&trailing, ParseError::TrailingData),
+    ] {
+        assert!(
+            matches!(check("Hello world!", hash), Err(CheckError::InvalidFormat(e)) if e == err),
+            "{}",
+            hash
+        );
+    }
+}
+
+#[test]
+fn verify_outcomes() {
+    let sha256_hash = "$5$saltstring$5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc5";
+    for hash in [SHA512_HASH, sha256_hash] {
+        assert_eq!(check_outcome("Hello world!", hash), VerifyOutcome::Match);
+        assert_eq!(
+            check_outcome("wrong", hash),
+            VerifyOutcome::PasswordMismatch
+        );
+    }
+
+    for (hash, prefix) in [
+        ("$1$saltstring$OH4IDuTlsuTYPdED1gsuiRMy", "$1$"),
+        (
+            "$y$j9T$F5Jx5fExrKuPp53xLKQ..1$X3DX6M94c7o.9agCG9G317fhZg9SqC.5i5rd.RhAtQ7",
+            "$y$",
+        ),
+        (
+            "$2b$05$c4WoMPo3SXsafkva.HHa6uXQZWr7oboPiC2bT/r7q1BB8I2s0BRqC",
+            "$2b$",
+        ),
+    ] {
+        assert_eq!(
+            check_outcome("Hello world!", hash),
+            VerifyOutcome::UnsupportedVariant(prefix)
+        );
+    }
+
+    for (hash, err) in [
+        ("", ParseError::InvalidPrefix),
+        ("$6$saltstring", ParseError::MissingField),
+        ("$6$rounds=ten$saltstring$x", ParseError::InvalidRounds),
+        ("$6$toolongsaltstring$x", ParseError::SaltTooLong),
+        ("$6$saltstring$svn8", ParseError::InvalidHashLength),
+    ] {
+        assert_eq!(
+            check_outcome("Hello world!", hash),
+            VerifyOutcome::Malformed(err),
+            "{}",
+            hash
+        );
+    }
+
+    assert!(check_outcome("Hello world!", SHA512_HASH).is_match());
+    assert!(!check_outcome("wrong", SHA512_HASH).is_match());
+    assert!(password_matches("Hello world!", SHA512_HASH));
+    assert!(!password_matches("wrong", SHA512_HASH));
+    assert!(!password_matches("Hello world!", "$6$saltstring"));
+
+    assert_eq!(
+        VerifyOutcome::UnsupportedVariant("$y$").to_string(),
+        "unsupported algorithm identifier: '$y$'"
+    );
+    assert_eq!(
+        VerifyOutcome::Malformed(ParseError::InvalidHash).to_string(),
+        CheckError::InvalidFormat(ParseError::InvalidHash).to_string()
+    );
+}
+
+#[test]
+fn display() {
+    assert_eq!(
+        CryptError::RoundsError.to_string(),
+        "rounds must be between 1000 and 999999999"
+    );
+    assert_eq!(
+        CryptError::SaltTooLong.to_string(),
+        "salt is longer than 16 characters"
+    );
+    assert_eq!(
+        CryptError::ParseError(ParseError::InvalidRounds).to_string(),
+        "invalid setting: rounds= specifier need to be a number"
+    );
+    assert_eq!(
+        CheckError::InvalidFormat(ParseError::InvalidHash).to_string(),
+        "invalid hash string: invalid B64"
+    );
+    assert_eq!(
+        CheckError::Crypt(CryptError::SaltError).to_string(),
+        CryptError::SaltError.to_string()
+    );
+    assert_eq!(
+        CheckError::HashMismatch.to_string(),
+        "password doesn't match the hash"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn error_sources() {
+    use std::error::Error;
+
+    let err = CheckError::InvalidFormat(ParseError::SaltTooLong);
+    assert_eq!(
+        err.source().unwrap().to_string(),
+        ParseError::SaltTooLong.to_string()
+    );
+
+    let err = CheckError::Crypt(CryptError::ParseError(ParseError::MissingField));
+    let source = err.source().unwrap();
+    assert_eq!(
+        source.source().unwrap().to_string(),
+        ParseError::MissingField.to_string()
+    );
+    assert!(CheckError::HashMismatch.source().is_none());
+
+    // Usable in application error chains
+    let err: Box<dyn Error> = Box::new(CryptError::RoundsError);
+    assert_eq!(err.to_string(), CryptError::RoundsError.to_string());
+}
@@ -1,9 +1,21 @@
 use sha_crypt::{
-    sha256_crypt_b64, sha512_crypt_b64, Sha256Params, Sha512Params, ROUNDS_MAX, ROUNDS_MIN,
+    b64, check, check_with_salt_mode, parse, sha256_check, sha256_crypt, sha256_crypt_raw,
+    sha256_hash_into, sha256_hash_string, sha256_verify, sha512_check, sha512_crypt,
+    sha512_crypt_raw, sha512_hash_into, sha512_hash_string, sha512_verify, CheckError, CryptError,
+    ParamsBuilder, ParseError, RoundsMode, SaltMode, Sha256Params, Sha512Params,
+    HASH_STRING_MAX_LEN, ROUNDS_DEFAULT, ROUNDS_MAX, ROUNDS_MIN,
 };
 
+#[cfg(feature = "alloc")]
+use sha_crypt::{
+    sha256_crypt_b64, sha256_simple_with_salt, sha512_crypt_b64, sha512_simple_with_salt,
+};
+
+#[cfg(feature = "rand_core")]
+use sha_crypt::gen_salt_with_rng;
+
 #[cfg(feature = "simple")]
-use sha_crypt::{sha256_check, sha256_simple, sha512_check, sha512_simple};
+use sha_crypt::{sha256_simple, sha512_simple};
 
 struct TestVector {
     input: &'static str,
@@ -96,19 +108,28 @@ const TEST_VECTORS: &[TestVector] = &[
     },
 ];
 
+/// Parameters truncating salts, as the vectors of the specification assume.
+fn truncating_params(rounds: usize) -> ParamsBuilder {
+    let mut builder = ParamsBuilder::new();
+    builder.rounds(rounds as u32).salt_mode(SaltMode::Truncate);
+    builder
+}
+
+#[cfg(feature = "alloc")]
 #[test]
 fn test_sha512_crypt() {
     for t in TEST_VECTORS {
-        let params = Sha512Params::new(t.rounds).expect("Rounds error");
+        let params = truncating_params(t.rounds).build_sha512().unwrap();
         let result = sha512_crypt_b64(t.input.as_bytes(), t.salt.as_bytes(), &params).unwrap();
         assert!(result == t.result_sha512);
     }
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn test_sha256_crypt() {
     for t in TEST_VECTORS {
-        let params = Sha256Params::new(t.rounds).expect("Rounds error");
+        let params = truncating_params(t.rounds).build_sha256().unwrap();
         let result = sha256_crypt_b64(t.input.as_bytes(), t.salt.as_bytes(), &params).unwrap();
         println!("result  {:?}", result);
         println!("correct {:?}", t.result_sha256);
@@ -134,7 +155,6 @@ fn test_sha256_crypt_invalid_rounds() {
     assert!(params.is_err());
 }
 
-#[cfg(feature = "simple")]
 #[test]
 fn test_sha512_check() {
     let pw = "foobar";
@@ -142,7 +162,6 @@ fn test_sha512_check() {
     assert!(sha512_check(pw, s).is_ok());
 }
 
-#[cfg(feature = "simple")]
 #[test]
 fn test_sha256_check() {
     let pw = "foobar";
@@ -150,7 +169,6 @@ fn test_sha256_check() {
     assert!(sha256_check(pw, s).is_ok());
 }
 
-#[cfg(feature = "simple")]
 #[test]
 fn test_sha512_check_with_rounds() {
     let pw = "foobar";
@@ -158,7 +176,6 @@ fn test_sha512_check_with_rounds() {
     assert!(sha512_check(pw, s).is_ok());
 }
 
-#[cfg(feature = "simple")]
 #[test]
 fn test_sha256_check_with_rounds() {
     let pw = "foobar";
@@ -194,7 +211,6 @@ fn test_sha256_simple_check_roundtrip() {
     assert!(c_r.is_ok());
 }
 
-#[cfg(feature = "simple")]
 #[test]
 fn test_sha512_unexpected_prefix() {
     let pw = "foobar";
@@ -202,7 +218,6 @@ fn test_sha512_unexpected_prefix() {
     assert!(sha512_check(pw, s).is_err());
 }
 
-#[cfg(feature = "simple")]
 #[test]
 fn test_sha256_unexpected_prefix() {
     let pw = "foobar";
@@ -210,7 +225,6 @@ fn test_sha256_unexpected_prefix() {
     assert!(sha256_check(pw, s).is_err());
 }
 
-#[cfg(feature = "simple")]
 #[test]
 fn test_sha512_wrong_id() {
     // wrong id '7'
@@ -219,7 +233,6 @@ fn test_sha512_wrong_id() {
     assert!(sha512_check(pw, s).is_err());
 }
 
-#[cfg(feature = "simple")]
 #[test]
 fn test_sha256_wrong_id() {
     // wrong id '7'
@@ -228,7 +241,6 @@ fn test_sha256_wrong_id() {
     assert!(sha256_check(pw, s).is_err());
 }
 
-#[cfg(feature = "simple")]
 #[test]
 fn test_sha512_missing_trailing_slash() {
     // Missing trailing slash
@@ -237,7 +249,6 @@ fn test_sha512_missing_trailing_slash() {
     assert!(sha512_check(pw, s).is_err());
 }
 
-#[cfg(feature = "simple")]
 #[test]
 fn test_sha256_missing_trailing_slash() {
     // Missing trailing slash
@@ -245,3 +256,730 @@ fn test_sha256_missing_trailing_slash() {
     let s = "$6$rounds=656000$Ykk6fjI2sU3/uprV$Z6yV/9Z741lfroSSzB9MwxSRnGeI9Z74hBkgNsHuojQJxZ9XjPkHg9jqqGLvWZ586wqnSSx5vrXZdhrMSZZE4";
     assert!(sha256_check(pw, s).is_err());
 }
+
+/// The SHA-256 test vectors of the specification, as `(setting, password,
+/// hash)`: <https://www.akkadia.org/drepper/SHA-crypt.txt>
+const SHA256_SPEC_VECTORS: &[(&str, &str, &str)] = &[
+    (
+        "$5$saltstring",
+        "Hello world!",
+        "$5$saltstring$5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc5",
+    ),
+    (
+        "$5$rounds=10000$saltstringsaltstring",
+        "Hello world!",
+        "$5$rounds=10000$saltstringsaltst$3xv.VbSHBb41AL9AvLeujZkZRBAwqFMz2.opqey6IcA",
+    ),
+    (
+        "$5$rounds=5000$toolongsaltstring",
+        "This is just a test",
+        "$5$rounds=5000$toolongsaltstrin$Un/5jzAHMgOGZ5.mWJpuVolil07guHPvOW8mGRcvxa5",
+    ),
+    (
+        "$5$rounds=1400$anotherlongsaltstring",
+        "a very much longer text to encrypt.  This one even stretches over morethan one line.",
+        "$5$rounds=1400$anotherlongsalts$Rx.j8H.h8HjEDGomFU8bDkXm3XIUnzyxf12oP84Bnq1",
+    ),
+    (
+        "$5$rounds=77777$short",
+        "we have a short salt string but not a short password",
+        "$5$rounds=77777$short$JiO1O3ZpDAxGJeaDIuqCoEFysAe1mZNJRs3pw0KQRd/",
+    ),
+    (
+        "$5$rounds=123456$asaltof16chars..",
+        "a short string",
+        "$5$rounds=123456$asaltof16chars..$gP3VQ/6X7UUEW3HkBn2w1/Ptq2jxPyzV/cZKmF/wJvD",
+    ),
+    // The specification clamps `rounds=10` to the minimum
+    (
+        "$5$rounds=1000$roundstoolow",
+        "the minimum number is still observed",
+        "$5$rounds=1000$roundstoolow$yfvwcWrQ8l/K0DAWyuPMDNHpIVlTQebY9l/gL972bIC",
+    ),
+];
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_sha256_crypt_spec_vectors() {
+    for (setting, password, hash) in SHA256_SPEC_VECTORS {
+        let mut fields = setting.split('$').skip(2);
+        let mut salt = fields.next().unwrap();
+        let mut rounds = 5_000;
+        if let Some(rounds_str) = salt.strip_prefix("rounds=") {
+            rounds = rounds_str.parse().unwrap();
+            salt = fields.next().unwrap();
+        }
+
+        let params = truncating_params(rounds).build_sha256().unwrap();
+        let result = sha256_crypt_b64(password.as_bytes(), salt.as_bytes(), &params).unwrap();
+        assert_eq!(result, hash.rsplit('$').next().unwrap());
+    }
+}
+
+#[test]
+fn test_sha256_check_spec_vectors() {
+    for (_, password, hash) in SHA256_SPEC_VECTORS {
+        assert!(sha256_check(password, hash).is_ok(), "{}", hash);
+        assert!(sha256_check("wrong password", hash).is_err());
+        assert!(sha512_check(password, hash).is_err());
+    }
+}
+
+#[test]
+fn test_check_dispatch() {
+    let sha256 = "$5$rounds=100000$PhW/wpSsmgIMKsTW$d9kDD8dQNu3r0Ky.xcOEhdin6EQRebrHfNKDRwWP/pB";
+    let sha512 = "$6$bbe605c2cce4c642$BiBOywFAm9kdv6ZPpj2GaKVqeh/.c21pf1uFBaq.e59KEE2Ej74iJleXaLXURYV6uh5LF4K7dDc4vtRtPiiKB/";
+
+    for hash in [
+        sha256,
+        sha512,
+        "$5$saltstring$iZgG3n1rjQNkqL0EnaKWdZmcBNfcGgerMvMgPcUXmbD",
+        "$6$rounds=1000$saltstring$LGEBPq5hwP7C5ZrEb9xaj32DMnZIksBqZWZoP/cIs7oNBg.hnGPvpIqMzDMz5myaZ209ZeyzmkFwJRREo3EPx1",
+    ] {
+        assert!(check("foobar", hash).is_ok(), "{}", hash);
+        assert!(
+            matches!(check("barfoo", hash), Err(CheckError::HashMismatch)),
+            "{}",
+            hash
+        );
+    }
+
+    // Hashes of other algorithms, by libxcrypt
+    for hash in [
+        "$1$k2XAnEHB$tqfMVNcACCWeZ94pOHmRz/",
+        "$2b$05$KBCwKxOzLha2MUDgW0PjXeH3xHZVMguPL02EE0VkPbUAhX1hKfdPW",
+        "$y$j9T$k2XAnEHBqQ1Ct2aMXFKNa/$Hg/rWWoSlv3EqZ1liqditsanypNpXbqhLXgmpP/N9W0",
+        "$7$CU..../....k2XAnEHBqQ1Ct2aMXFKNa/$gZVjKFN0HTii6.2FB59umgifeuCQVKxs0pA0ujHi2m4",
+        "$gy$j9T$k2XAnEHBqQ1Ct2aMXFKNa/$GScEh6nIrT.KJ1SbBW69ePykdJp8QXhbGHpbQidC465",
+        "$md5,rounds=45105$mA1BpMnB$$9kn9.c1hhcUGj.4l9Qsb51",
+        "$sha1$249552$qI1BtUnBX7KM$m9BmyH5OX6KmMLf5MUYdbACb6zKf",
+        "$56$rounds=100000$PhW/wpSsmgIMKsTW$d9kDD8dQNu3r0Ky.xcOEhdin6EQRebrHfNKDRwWP/pB",
+    ] {
+        for result in [
+            check("foobar", hash),
+            sha256_check("foobar", hash),
+            sha512_check("foobar", hash),
+        ] {
+            assert!(
+                matches!(result, Err(CheckError::UnsupportedAlgorithm)),
+                "{}",
+                hash
+            );
+        }
+    }
+
+    for hash in [
+        "5$rounds=100000$PhW/wpSsmgIMKsTW$d9kDD8dQNu3r0Ky.xcOEhdin6EQRebrHfNKDRwWP/pB",
+        "$5$rounds=100000$PhW/wpSsmgIMKsTW$d9kDD8dQNu3r0Ky.xcOEhdin6EQRebrHfNKDRwWP/p",
+        "$6$bbe605c2cce4c642",
+        "$6",
+        "$",
+        "",
+    ] {
+        assert!(
+            matches!(check("foobar", hash), Err(CheckError::InvalidFormat(_))),
+            "{}",
+            hash
+        );
+    }
+}
+
+#[test]
+fn test_sha256_check_invalid_b64() {
+    let s = "$5$9aEeVXnCiCNHUjO/$FrVBcjyJukRaE6inMYazyQv1DBnwaKfom.71ebgQR/!";
+    assert!(matches!(
+        sha256_check("foobar", s),
+        Err(CheckError::InvalidFormat(_))
+    ));
+}
+
+#[test]
+fn test_params_builder() {
+    let params = ParamsBuilder::new().build_sha512().unwrap();
+    assert_eq!(params.rounds(), ROUNDS_DEFAULT);
+
+    let params = ParamsBuilder::new().rounds(10_000).build_sha256().unwrap();
+    assert_eq!(params.rounds(), 10_000);
+
+    let mut builder = ParamsBuilder::new();
+    builder.rounds(ROUNDS_MAX as u32);
+    assert!(Sha512Params::try_from(builder.clone()).is_ok());
+
+    for rounds in [0, ROUNDS_MIN as u32 - 1, ROUNDS_MAX as u32 + 1, u32::MAX] {
+        builder.rounds(rounds);
+        assert!(builder.build_sha256().is_err());
+        assert!(builder.build_sha512().is_err());
+    }
+}
+
+/// Hashes of `Hello world!` generated by `crypt(3)`, as `(setting, hash)`.
+const CRYPT_HASHES: &[(&str, &str)] = &[
+    (
+        "$6$saltstring",
+        "$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1",
+    ),
+    (
+        "$6$rounds=10000$saltstring",
+        "$6$rounds=10000$saltstring$buk9gc9MDdd3Z11.ZzxK8sKnFNbxNdTnCf.XHjjiTHcgFuFgkKvBQPLIaUn4Ixl3TLN8ZgCk52MPgbWjATwhH0",
+    ),
+    (
+        "$5$saltstring",
+        "$5$saltstring$5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc5",
+    ),
+    (
+        "$5$rounds=1000$saltstring",
+        "$5$rounds=1000$saltstring$z/y8l95GSjij6uHx2xAJer7YCODLtrhIxItWC13D4g5",
+    ),
+    (
+        "$5$rounds=77777$short",
+        "$5$rounds=77777$short$sd8LJlDQasDpKy5eS6nr3n2KyNibTG1RJFeRcgM3D74",
+    ),
+];
+
+/// Split a setting into its identifier, salt and parameters.
+fn parse_setting(setting: &str) -> (&str, &str, ParamsBuilder) {
+    let mut fields = setting.split('$').skip(1);
+    let id = fields.next().unwrap();
+    let mut salt = fields.next().unwrap();
+    let mut builder = ParamsBuilder::new();
+    if let Some(rounds) = salt.strip_prefix("rounds=") {
+        builder.rounds(rounds.parse().unwrap());
+        salt = fields.next().unwrap();
+    }
+    (id, salt, builder)
+}
+
+#[test]
+fn test_hash_string_matches_crypt() {
+    for (setting, hash) in CRYPT_HASHES {
+        let (id, salt, builder) = parse_setting(setting);
+        let result = match id {
+            "5" => sha256_hash_string("Hello world!", salt, &builder.build_sha256().unwrap()),
+            _ => sha512_hash_string("Hello world!", salt, &builder.build_sha512().unwrap()),
+        };
+        assert_eq!(result.unwrap().as_str(), *hash);
+        assert!(check("Hello world!", hash).is_ok());
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_simple_with_salt_matches_crypt() {
+    for (setting, hash) in CRYPT_HASHES {
+        let (id, salt, builder) = parse_setting(setting);
+        let result = match id {
+            "5" => sha256_simple_with_salt("Hello world!", salt, &builder.build_sha256().unwrap()),
+            _ => sha512_simple_with_salt("Hello world!", salt, &builder.build_sha512().unwrap()),
+        };
+        assert_eq!(&result.unwrap(), hash);
+    }
+}
+
+/// The vectors hashed and checked with the `no_std` API.
+#[test]
+fn test_hash_string_vectors() {
+    for t in TEST_VECTORS {
+        let salt = &t.salt[..t.salt.len().min(16)];
+        let rounds = match t.rounds {
+            ROUNDS_DEFAULT => String::new(),
+            rounds => format!("rounds={}$", rounds),
+        };
+
+        let params = Sha512Params::new(t.rounds).unwrap();
+        let result = sha512_hash_string(t.input, salt, &params).unwrap();
+        let expected = format!("$6${}{}${}", rounds, salt, t.result_sha512);
+        assert_eq!(result.as_str(), expected);
+        assert!(sha512_check(t.input, &result).is_ok());
+
+        let params = Sha256Params::new(t.rounds).unwrap();
+        let result = sha256_hash_string(t.input, salt, &params).unwrap();
+        let expected = format!("$5${}{}${}", rounds, salt, t.result_sha256);
+        assert_eq!(result.as_str(), expected);
+        assert!(sha256_check(t.input, &result).is_ok());
+    }
+
+    for (setting, password, hash) in SHA256_SPEC_VECTORS {
+        let (_, salt, builder) = parse_setting(setting);
+        let salt = &salt[..salt.len().min(16)];
+        let result = sha256_hash_string(password, salt, &builder.build_sha256().unwrap()).unwrap();
+
+        // One of the settings has an explicit `rounds=5000`
+        assert_eq!(result.rsplit('$').next(), hash.rsplit('$').next());
+        assert!(check(password, &result).is_ok());
+    }
+}
+
+/// `crypt(3)` cuts these salts, so they're rejected rather than giving
+/// hashes with a different salt.
+#[test]
+fn test_hash_string_invalid_salt() {
+    let params = Sha512Params::default();
+    for salt in [
+        "saltstring$",
+        "salt string",
+        "salt:",
+        "salt-string",
+        "saltsaltsaltsal\u{e9}",
+    ] {
+        assert!(
+            matches!(
+                sha512_hash_string("password", salt, &params),
+                Err(CryptError::SaltError)
+            ),
+            "{}",
+            salt
+        );
+        assert!(matches!(
+            sha256_hash_string("password", salt, &Sha256Params::default()),
+            Err(CryptError::SaltError)
+        ));
+    }
+
+    assert!(matches!(
+        sha512_hash_string("password", "toolongsaltstring", &params),
+        Err(CryptError::SaltTooLong)
+    ));
+
+    // The longest salt, and the empty one
+    assert!(sha512_hash_string("password", "./09AZaz./09AZaz", &params).is_ok());
+    assert!(sha512_hash_string("password", "", &params).is_ok());
+
+    // Hashes of `crypt(3)` with cut salts still verify
+    let hash = "$6$toolongsaltstrin$iGlL7EUUfzNQx59x3ydJZ.zXPMUu1dOynSEl/vcNhLlas77qD0DzRswhhB6LdrXTz250at0syAfUXra.XrxAI1";
+    assert!(check("Hello world!", hash).is_ok());
+}
+
+/// The raw digests are those encoded in the hash strings.
+#[test]
+fn test_crypt_raw() {
+    for t in TEST_VECTORS {
+        if !(ROUNDS_MIN..=ROUNDS_MAX).contains(&t.rounds) {
+            continue;
+        }
+        let salt = &t.salt[..t.salt.len().min(16)];
+
+        let raw = sha512_crypt_raw(t.input, salt, t.rounds).unwrap();
+        assert_eq!(raw, b64::decode_sha512(t.result_sha512.as_bytes()).unwrap());
+        assert_eq!(&b64::encode_sha512(&raw)[..], t.result_sha512.as_bytes());
+        let params = Sha512Params::new(t.rounds).unwrap();
+        let hash = sha512_hash_string(t.input, salt, &params).unwrap();
+        assert!(hash.ends_with(t.result_sha512));
+
+        let raw = sha256_crypt_raw(t.input, salt, t.rounds).unwrap();
+        assert_eq!(raw, b64::decode_sha256(t.result_sha256.as_bytes()).unwrap());
+        assert_eq!(&b64::encode_sha256(&raw)[..], t.result_sha256.as_bytes());
+    }
+
+    assert!(matches!(
+        sha512_crypt_raw("password", "saltstring", ROUNDS_MIN - 1),
+        Err(CryptError::RoundsError)
+    ));
+    assert!(matches!(
+        sha256_crypt_raw("password", "salt$string", ROUNDS_DEFAULT),
+        Err(CryptError::SaltError)
+    ));
+    assert!(matches!(
+        sha256_crypt_raw("password", "toolongsaltstring", ROUNDS_DEFAULT),
+        Err(CryptError::SaltTooLong)
+    ));
+}
+
+/// The hash strings written to buffers are those of the allocating API.
+#[test]
+fn test_hash_into() {
+    let mut buf = [0u8; HASH_STRING_MAX_LEN];
+    for t in TEST_VECTORS {
+        let salt = &t.salt[..t.salt.len().min(16)];
+
+        let params = Sha512Params::new(t.rounds).unwrap();
+        let len = sha512_hash_into(t.input, salt, &params, &mut buf).unwrap();
+        let expected = sha512_hash_string(t.input, salt, &params).unwrap();
+        assert_eq!(&buf[..len], expected.as_bytes());
+        #[cfg(feature = "alloc")]
+        assert_eq!(
+            &buf[..len],
+            sha512_simple_with_salt(t.input, salt, &params)
+                .unwrap()
+                .as_bytes()
+        );
+
+        let params = Sha256Params::new(t.rounds).unwrap();
+        let len = sha256_hash_into(t.input, salt, &params, &mut buf).unwrap();
+        let expected = sha256_hash_string(t.input, salt, &params).unwrap();
+        assert_eq!(&buf[..len], expected.as_bytes());
+        #[cfg(feature = "alloc")]
+        assert_eq!(
+            &buf[..len],
+            sha256_simple_with_salt(t.input, salt, &params)
+                .unwrap()
+                .as_bytes()
+        );
+    }
+
+    // The longest hash string fits
+    assert_eq!(
+        HASH_STRING_MAX_LEN,
+        "$6$rounds=999999999$0123456789abcdef$".len() + 86
+    );
+
+    // Buffers of the exact length, and too small ones
+    let params = Sha256Params::default();
+    let hash = sha256_hash_string("password", "saltstring", &params).unwrap();
+    let mut buf = [0xffu8; 64];
+    assert_eq!(
+        sha256_hash_into("password", "saltstring", &params, &mut buf[..hash.len()]).unwrap(),
+        hash.len()
+    );
+    assert_eq!(&buf[..hash.len()], hash.as_bytes());
+    assert_eq!(buf[hash.len()], 0xff);
+    assert!(matches!(
+        sha256_hash_into(
+            "password",
+            "saltstring",
+            &params,
+            &mut buf[..hash.len() - 1]
+        ),
+        Err(CryptError::BufferTooSmall)
+    ));
+    assert!(matches!(
+        sha512_hash_into("password", "saltstring", &Sha512Params::default(), &mut []),
+        Err(CryptError::BufferTooSmall)
+    ));
+}
+
+/// Salts longer than 16 characters are rejected unless truncated as in
+/// `crypt(3)`, whose hash strings have the truncated salt.
+#[test]
+fn test_long_salt() {
+    let salt = "0123456789abcdefXYZ";
+    // By libxcrypt, with the setting `$6$rounds=10000$0123456789abcdefXYZ`
+    let hash = "$6$rounds=10000$0123456789abcdef$YhFsu9pac4sb3iWRtTNSuC/rn/ij1XKJEc29udu2eIbeoE.rDALd.7Dq4a2p1Ia4wZKSF3PswU1iqTf/pCVN..";
+    let untruncated = hash.replace("0123456789abcdef", salt);
+
+    let strict = Sha512Params::new(10_000).unwrap();
+    assert!(matches!(
+        sha512_crypt(b"Hello world!", salt.as_bytes(), &strict),
+        Err(CryptError::SaltTooLong)
+    ));
+    assert!(matches!(
+        sha512_hash_string("Hello world!", salt, &strict),
+        Err(CryptError::SaltTooLong)
+    ));
+    assert!(matches!(
+        check("Hello world!", &untruncated),
+        Err(CheckError::InvalidFormat(_))
+    ));
+    assert!(matches!(
+        check_with_salt_mode("Hello world!", &untruncated, SaltMode::Strict),
+        Err(CheckError::InvalidFormat(_))
+    ));
+
+    let truncate = truncating_params(10_000).build_sha512().unwrap();
+    let result = sha512_hash_string("Hello world!", salt, &truncate).unwrap();
+    assert_eq!(result.as_str(), hash);
+    assert!(check("Hello world!", hash).is_ok());
+    assert!(check_with_salt_mode("Hello world!", &untruncated, SaltMode::Truncate).is_ok());
+    assert!(matches!(
+        check_with_salt_mode("Hello world", &untruncated, SaltMode::Truncate),
+        Err(CheckError::HashMismatch)
+    ));
+
+    let truncate = truncating_params(ROUNDS_DEFAULT).build_sha256().unwrap();
+    assert_eq!(
+        sha256_crypt(b"Hello world!", b"saltstringsaltstring", &truncate).unwrap(),
+        sha256_crypt(
+            b"Hello world!",
+            b"saltstringsaltst",
+            &Sha256Params::default()
+        )
+        .unwrap()
+    );
+    assert!(matches!(
+        sha256_crypt(
+            b"Hello world!",
+            b"saltstringsaltstring",
+            &Sha256Params::default()
+        ),
+        Err(CryptError::SaltTooLong)
+    ));
+}
+
+#[cfg(feature = "rand_core")]
+#[test]
+fn test_gen_salt_with_rng() {
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let salt = gen_salt_with_rng(&mut rng, 16).unwrap();
+    assert_eq!(salt.len(), 16);
+    assert!(sha512_hash_string("password", &salt, &Sha512Params::default()).is_ok());
+
+    // Deterministic for a given seed
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    assert_eq!(gen_salt_with_rng(&mut rng, 16).unwrap(), salt);
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    assert_eq!(gen_salt_with_rng(&mut rng, 8).unwrap(), salt[..8]);
+
+    for len in 0..=16 {
+        let salt = gen_salt_with_rng(&mut rng, len).unwrap();
+        assert_eq!(salt.len(), len);
+        assert!(salt
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || c == b'.' || c == b'/'));
+    }
+
+    assert!(matches!(
+        gen_salt_with_rng(&mut rng, 17),
+        Err(CryptError::SaltTooLong)
+    ));
+}
+
+/// `crypt(3)` keeps `rounds=5000` when the setting includes it, but it is
+/// left out of generated hashes.
+#[test]
+fn test_default_rounds_omitted() {
+    let explicit = "$6$rounds=5000$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1";
+    assert!(sha512_check("Hello world!", explicit).is_ok());
+
+    let params = ParamsBuilder::new().rounds(5_000).build_sha512().unwrap();
+    assert_eq!(
+        sha512_hash_string("Hello world!", "saltstring", &params)
+            .unwrap()
+            .as_str(),
+        CRYPT_HASHES[0].1
+    );
+}
+
+#[cfg(feature = "simple")]
+#[test]
+fn test_simple_default_rounds_omitted() {
+    let hash = sha256_simple("Hello world!", &Sha256Params::default()).unwrap();
+    assert!(!hash.contains("rounds="));
+}
+
+#[test]
+fn test_verify() {
+    for t in TEST_VECTORS {
+        let (password, salt) = (t.input.as_bytes(), t.salt.as_bytes());
+
+        let params = truncating_params(t.rounds).build_sha512().unwrap();
+        let mut hash = sha512_crypt(password, salt, &params).unwrap();
+        assert!(sha512_verify(password, salt, &params, &hash).is_ok());
+        assert!(matches!(
+            sha512_verify(b"wrong", salt, &params, &hash),
+            Err(CheckError::HashMismatch)
+        ));
+        hash[63] ^= 1;
+        assert!(matches!(
+            sha512_verify(password, salt, &params, &hash),
+            Err(CheckError::HashMismatch)
+        ));
+
+        let params = truncating_params(t.rounds).build_sha256().unwrap();
+        let mut hash = sha256_crypt(password, salt, &params).unwrap();
+        assert!(sha256_verify(password, salt, &params, &hash).is_ok());
+        hash[0] ^= 0x80;
+        assert!(matches!(
+            sha256_verify(password, salt, &params, &hash),
+            Err(CheckError::HashMismatch)
+        ));
+    }
+}
+
+/// Malformed hashes are errors other than `HashMismatch`.
+#[test]
+fn test_check_malformed() {
+    for hash in [
+        "",
+        "$6$",
+        "$6$saltstring",
+        "$6$saltstring$",
+        "$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz",
+        "$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1$",
+        "$6$rounds=$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1",
+        "$5$saltstring$5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc",
+        "$5$saltstring$5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc5=",
+        // Dummy hashes with the rounds and salt found, as far as possible
+        "$5$rounds=2000$saltstring$5B8v",
+        "$5$rounds=ten$saltstring$5B8v",
+        "$6$rounds=10$toolongsaltstring$svn8",
+        "$6$rounds=99999999999999999999999$saltstring$svn8",
+    ] {
+        assert!(
+            matches!(check("Hello world!", hash), Err(CheckError::InvalidFormat(_))),
+            "{}",
+            hash
+        );
+    }
+}
+
+#[test]
+fn test_rounds_modes() {
+    let mut builder = ParamsBuilder::new();
+    for (rounds, strict, clamped) in [
+        (0, None, 1_000),
+        (999, None, 1_000),
+        (1_000, Some(1_000), 1_000),
+        (999_999_999, Some(999_999_999), 999_999_999),
+        (1_000_000_000, None, 999_999_999),
+        (u32::MAX, None, 999_999_999),
+    ] {
+        builder.rounds(rounds).rounds_mode(RoundsMode::Strict);
+        assert_eq!(builder.build_sha256().ok().map(|p| p.rounds()), strict);
+        assert_eq!(builder.build_sha512().ok().map(|p| p.rounds()), strict);
+        assert_eq!(RoundsMode::Strict.apply(rounds as usize).ok(), strict);
+
+        builder.rounds_mode(RoundsMode::Clamp);
+        assert_eq!(builder.build_sha256().unwrap().rounds(), clamped);
+        assert_eq!(builder.build_sha512().unwrap().rounds(), clamped);
+        assert_eq!(RoundsMode::Clamp.apply(rounds as usize).unwrap(), clamped);
+    }
+
+    assert_eq!(RoundsMode::default(), RoundsMode::Strict);
+    assert!(matches!(
+        ParamsBuilder::new().rounds(999).build_sha512(),
+        Err(CryptError::RoundsError)
+    ));
+}
+
+/// The check functions clamp the rounds of hash strings like `crypt(3)`.
+#[test]
+fn test_check_clamps_rounds() {
+    let params = Sha512Params::new(1_000).unwrap();
+    let hash = sha512_hash_string("Hello world!", "saltstring", &params).unwrap();
+    assert!(hash.starts_with("$6$rounds=1000$"));
+
+    for rounds in ["0", "10", "999", "0999"] {
+        let clamped = hash.replacen("1000", rounds, 1);
+        assert!(
+            sha512_check("Hello world!", &clamped).is_ok(),
+            "{}",
+            clamped
+        );
+        assert!(matches!(
+            sha512_check("Hello world?", &clamped),
+            Err(CheckError::HashMismatch)
+        ));
+    }
+
+    // The specification vector with `rounds=10`
+    let hash = "$5$rounds=10$roundstoolow$yfvwcWrQ8l/K0DAWyuPMDNHpIVlTQebY9l/gL972bIC";
+    assert!(check("the minimum number is still observed", hash).is_ok());
+}
+
+/// Hashes of non-UTF-8 passwords generated by `crypt(3)`, as `(password,
+/// hash)`.
+const CRYPT_BYTES_HASHES: &[(&[u8], &str)] = &[
+    (
+        b"M\xfcller caf\xe9",
+        "$6$rounds=1000$latin1$D0iVNxP0K.6hi0YROM7h4IzC9PBmmeVSYbHJaneLRtS8/L8BAAes0.KRFCxJOlkbKt9fAnSvVscqZO60DIEzX1",
+    ),
+    (
+        b"M\xfcller caf\xe9",
+        "$5$latin1$bE2rRbBVhimv0vPQO.txxdvtCsSd338LAA6vB.puCM.",
+    ),
+];
+
+/// Hashes of the bytes 0x80 to 0xFF generated by `crypt(3)`, as `(setting,
+/// hash)`.
+const CRYPT_HIGH_BYTES_HASHES: &[(&str, &str)] = &[
+    (
+        "$6$saltstring",
+        "$6$saltstring$mSvxJxrk23eo9opAe5oNx4RFZBQ4Z6/xdCgHXPrscdE1.Of201G1/xRHuat.gETk.IgkYIVTr4Gsdz6HcwG6J0",
+    ),
+    (
+        "$5$rounds=1000$saltstring",
+        "$5$rounds=1000$saltstring$ZKUE9ZxDaUq5nX1gFYYtJ8IFJwZodv6gGdAKT0Wmiq7",
+    ),
+    (
+        "$6$rounds=1000$latin1",
+        "$6$rounds=1000$latin1$YYOd19hKoRdhjeCqWarVLFlJ8NhEhsTQKJVOz89DWwXl7z8HavDlNxL85AY3I7GasWTyTwJ8DA2dqvHdTh6Wf/",
+    ),
+];
+
+#[test]
+fn test_non_utf8_passwords() {
+    for (password, hash) in CRYPT_BYTES_HASHES {
+        assert!(check(password, hash).is_ok(), "{}", hash);
+        assert!(check(b"Muller cafe", hash).is_err());
+    }
+
+    let password: Vec<u8> = (0x80..=0xff).collect();
+    for (setting, hash) in CRYPT_HIGH_BYTES_HASHES {
+        let (id, salt, builder) = parse_setting(setting);
+        let result = match id {
+            "5" => sha256_hash_string(&password, salt, &builder.build_sha256().unwrap()),
+            _ => sha512_hash_string(&password, salt, &builder.build_sha512().unwrap()),
+        };
+        assert_eq!(result.unwrap().as_str(), *hash);
+        assert!(check(&password, hash).is_ok());
+        assert!(check(&password[1..], hash).is_err());
+    }
+}
+
+/// NUL bytes are hashed like any other byte.
+#[test]
+fn test_nul_bytes_hashed() {
+    let params = Sha512Params::default();
+    let hash = sha512_hash_string(b"pass\0word", "saltstring", &params).unwrap();
+    assert!(sha512_check(b"pass\0word", &hash).is_ok());
+    assert!(sha512_check(b"pass", &hash).is_err());
+    assert!(sha512_check(b"pass\0wore", &hash).is_err());
+}
+
+#[test]
+fn test_sha512_params_from_hash() {
+    let hash = "$6$rounds=656000$Ykk6fjI2sU3/uprV$Z6yV/9Z741lfroSSzB9MwxSRnGeI9Z74hBkgNsHuojQJxZ9XjPkHg9jqqGLvWZ586wqnSSx5vrXZdhrMSZZE4/";
+    let params = Sha512Params::from_hash(hash).unwrap();
+    assert_eq!(params.rounds(), 656_000);
+    assert!(sha512_check("abc", hash).is_ok());
+
+    // Rehashing with the parameters and salt of a hash string gives it back
+    let salt = parse(hash).unwrap().salt();
+    assert_eq!(
+        sha512_hash_string("abc", salt, &params).unwrap().as_str(),
+        hash
+    );
+
+    let params = Sha512Params::from_hash("$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1").unwrap();
+    assert_eq!(params.rounds(), ROUNDS_DEFAULT);
+
+    assert_eq!(
+        Sha512Params::from_hash(
+            "$5$rounds=1000$roundstoolow$yfvwcWrQ8l/K0DAWyuPMDNHpIVlTQebY9l/gL972bIC"
+        )
+        .unwrap_err(),
+        ParseError::InvalidPrefix
+    );
+    assert_eq!(
+        Sha512Params::from_hash("$6$rounds=656000$Ykk6fjI2sU3/uprV").unwrap_err(),
+        ParseError::MissingField
+    );
+}
+
+#[test]
+fn test_sha256_params_from_hash() {
+    // Rounds are clamped as by `crypt(3)`
+    let hash = "$5$rounds=10$roundstoolow$yfvwcWrQ8l/K0DAWyuPMDNHpIVlTQebY9l/gL972bIC";
+    let params = Sha256Params::from_hash(hash).unwrap();
+    assert_eq!(params.rounds(), ROUNDS_MIN);
+    assert_eq!(
+        sha256_hash_string(
+            "the minimum number is still observed",
+            "roundstoolow",
+            &params
+        )
+        .unwrap()
+        .as_str(),
+        "$5$rounds=1000$roundstoolow$yfvwcWrQ8l/K0DAWyuPMDNHpIVlTQebY9l/gL972bIC"
+    );
+
+    let hash = "$5$rounds=1000000000$saltstring$5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc5";
+    assert_eq!(Sha256Params::from_hash(hash).unwrap().rounds(), ROUNDS_MAX);
+
+    assert_eq!(
+        Sha256Params::from_hash("$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1")
+            .unwrap_err(),
+        ParseError::InvalidPrefix
+    );
+}
@@ -0,0 +1,73 @@
+//! Hashing and verifying don't allocate.
+
+use core::{
+    cell::Cell,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use sha_crypt::{
+    check, sha256_hash_into, sha512_hash_into, Sha256Params, Sha512Params, HASH_STRING_MAX_LEN,
+};
+use std::alloc::{GlobalAlloc, Layout, System};
+
+/// Counts the allocations of the current test thread.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+std::thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.try_with(|c| c.get()).unwrap_or(false) {
+            ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Number of allocations made by `f` on this thread.
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    COUNTING.with(|c| c.set(true));
+    f();
+    COUNTING.with(|c| c.set(false));
+    ALLOCATIONS.load(Ordering::SeqCst) - before
+}
+
+#[test]
+fn no_allocations() {
+    let mut buf = [0u8; HASH_STRING_MAX_LEN];
+    let long_password = [b'p'; 1000];
+
+    for password in [&b""[..], b"password", &long_password[..300], &long_password] {
+        let mut len = 0;
+        assert_eq!(
+            allocations(|| {
+                len = sha512_hash_into(password, "saltstring", &Sha512Params::default(), &mut buf)
+                    .unwrap();
+            }),
+            0
+        );
+        let hash = core::str::from_utf8(&buf[..len]).unwrap().to_owned();
+        assert_eq!(allocations(|| check(password, &hash).unwrap()), 0);
+
+        assert_eq!(
+            allocations(|| {
+                len = sha256_hash_into(password, "saltstring", &Sha256Params::default(), &mut buf)
+                    .unwrap();
+            }),
+            0
+        );
+        let hash = core::str::from_utf8(&buf[..len]).unwrap().to_owned();
+        assert_eq!(allocations(|| check(password, &hash).unwrap()), 0);
+    }
+}
@@ -0,0 +1,219 @@
+//! Parsing hash strings generated by `crypt(3)`.
+
+use sha_crypt::{parse, parse_with_salt_mode, ParseError, SaltMode, Variant};
+
+#[test]
+fn parse_crypt_hashes() {
+    let parsed = parse("$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1").unwrap();
+    assert_eq!(parsed.variant(), Variant::Sha512Crypt);
+    assert_eq!(parsed.rounds(), 5_000);
+    assert!(!parsed.rounds_explicit());
+    assert_eq!(parsed.salt(), "saltstring");
+    assert_eq!(
+        parsed.hash(),
+        "svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1"
+    );
+
+    let parsed = parse("$6$rounds=5000$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1").unwrap();
+    assert_eq!(parsed.rounds(), 5_000);
+    assert!(parsed.rounds_explicit());
+
+    let parsed = parse("$6$rounds=100000$exn6tVc2j/MZD8uG$BI1Xh8qQSK9J4m14uwy7abn.ctj/TIAzlaVCto0MQrOFIeTXsc1iwzH16XEWo/a7c7Y9eVJvufVzYAs4EsPOy0").unwrap();
+    assert_eq!(parsed.rounds(), 100_000);
+    assert!(parsed.rounds_explicit());
+    assert_eq!(parsed.salt().len(), 16);
+
+    let parsed =
+        parse("$5$rounds=77777$short$sd8LJlDQasDpKy5eS6nr3n2KyNibTG1RJFeRcgM3D74").unwrap();
+    assert_eq!(parsed.variant(), Variant::Sha256Crypt);
+    assert_eq!(parsed.rounds(), 77_777);
+    assert_eq!(parsed.salt(), "short");
+    assert_eq!(parsed.hash(), "sd8LJlDQasDpKy5eS6nr3n2KyNibTG1RJFeRcgM3D74");
+
+    // Empty salt
+    let hash = "$5$$mAwMsDaqjtxAtGqstEIf7OBR15rgcx.jSKGM94IKRj/";
+    assert_eq!(parse(hash).unwrap().salt(), "");
+    assert!(sha_crypt::check("Hello world!", hash).is_ok());
+}
+
+#[test]
+fn parse_malformed() {
+    let sha512 =
+        "svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1";
+    let sha256 = "5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc5";
+
+    for (hash, err) in [
+        (String::new(), ParseError::InvalidPrefix),
+        ("$".into(), ParseError::InvalidPrefix),
+        ("$$".into(), ParseError::InvalidPrefix),
+        ("$7".into(), ParseError::InvalidPrefix),
+        (
+            format!("6$saltstring${}", sha512),
+            ParseError::InvalidPrefix,
+        ),
+        (
+            format!(" $6$saltstring${}", sha512),
+            ParseError::InvalidPrefix,
+        ),
+        (
+            format!("$7$saltstring${}", sha512),
+            ParseError::UnsupportedAlgorithm,
+        ),
+        (
+            format!("$56$saltstring${}", sha512),
+            ParseError::UnsupportedAlgorithm,
+        ),
+        (
+            format!("$y$saltstring${}", sha512),
+            ParseError::UnsupportedAlgorithm,
+        ),
+        ("$6$".into(), ParseError::MissingField),
+        ("$6$saltstring".into(), ParseError::MissingField),
+        ("$6$rounds=10000".into(), ParseError::MissingField),
+        (
+            "$6$rounds=10000$saltstring".into(),
+            ParseError::MissingField,
+        ),
+        (
+            format!("$6$rounds=$saltstring${}", sha512),
+            ParseError::InvalidRounds,
+        ),
+        (
+            format!("$6$rounds=abc$saltstring${}", sha512),
+            ParseError::InvalidRounds,
+        ),
+        (
+            format!("$6$rounds=+5000$saltstring${}", sha512),
+            ParseError::InvalidRounds,
+        ),
+        (
+            format!("$6$rounds=-1$saltstring${}", sha512),
+            ParseError::InvalidRounds,
+        ),
+        (
+            format!("$6$rounds=5000 $saltstring${}", sha512),
+            ParseError::InvalidRounds,
+        ),
+        (
+            format!("$6${}${}", "a".repeat(1 << 20), sha512),
+            ParseError::TooLong,
+        ),
+        (
+            format!("$6$toolongsaltstring${}", sha512),
+            ParseError::SaltTooLong,
+        ),
+        (
+            format!("$6$saltstring${}$", sha512),
+            ParseError::TrailingData,
+        ),
+        (
+            format!("$6$saltstring${}$extra", sha512),
+            ParseError::TrailingData,
+        ),
+        ("$6$saltstring$".into(), ParseError::InvalidHashLength),
+        (
+            format!("$6$saltstring${}", &sha512[1..]),
+            ParseError::InvalidHashLength,
+        ),
+        (
+            format!("$6$saltstring${}a", sha512),
+            ParseError::InvalidHashLength,
+        ),
+        (
+            format!("$6$saltstring${}", sha256),
+            ParseError::InvalidHashLength,
+        ),
+        (
+            format!("$5$saltstring${}", sha512),
+            ParseError::InvalidHashLength,
+        ),
+    ] {
+        assert_eq!(parse(&hash), Err(err), "{}", hash);
+    }
+}
+
+/// Malformed and adversarial strings are rejected without panicking, by the
+/// parser and by the functions built on it.
+#[test]
+fn parse_corpus() {
+    let sha512 =
+        "svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1";
+    let huge = "a".repeat(4 << 20);
+
+    let corpus = [
+        "$".to_string(),
+        "$$$$$$$$".into(),
+        "$5".into(),
+        "$6$".into(),
+        "$6$$".into(),
+        "$6$$$".into(),
+        "$6$rounds=".into(),
+        "$6$rounds=$".into(),
+        "$6$rounds=$salt$".into(),
+        "$6$rounds=5000".into(),
+        "$6$rounds=5000$".into(),
+        "$6$rounds=00005000$salt$".into(),
+        "$6$rounds=0$salt$".into(),
+        "$6$rounds=5000$rounds=5000$salt$".into(),
+        "$6$rounds=\u{e9}$salt$".into(),
+        "$6$\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}$".into(),
+        "$6$saltstring$\u{e9}".into(),
+        format!("$6$saltstring${}", &sha512[..85]),
+        format!("$6$saltstring$\u{e9}{}", &sha512[2..]),
+        format!("$6$saltstring${}\0", sha512),
+        format!("$6${}${}", huge, sha512),
+        format!("$6$rounds={}$salt${}", "9".repeat(4 << 20), sha512),
+        format!("$6${}", "$".repeat(1 << 20)),
+        huge.clone(),
+    ];
+
+    for hash in &corpus {
+        assert!(parse(hash).is_err(), "{:?}", hash);
+        assert!(
+            parse_with_salt_mode(hash, SaltMode::Truncate).is_err(),
+            "{:?}",
+            hash
+        );
+        assert!(sha_crypt::check("password", hash).is_err(), "{:?}", hash);
+        let _ = sha_crypt::crypt_hash_string("password", hash);
+    }
+}
+
+/// Out of range rounds are parsed as they are, and clamped by the check
+/// functions.
+#[test]
+fn parse_rounds_out_of_range() {
+    let hash = "$5$rounds=10$roundstoolow$yfvwcWrQ8l/K0DAWyuPMDNHpIVlTQebY9l/gL972bIC";
+    assert_eq!(parse(hash).unwrap().rounds(), 10);
+    assert!(sha_crypt::check("the minimum number is still observed", hash).is_ok());
+
+    // Leading zeros are ignored
+    let hash = "$5$rounds=0010$roundstoolow$yfvwcWrQ8l/K0DAWyuPMDNHpIVlTQebY9l/gL972bIC";
+    assert_eq!(parse(hash).unwrap().rounds(), 10);
+
+    let hash = "$6$rounds=1000000000$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1";
+    assert_eq!(parse(hash).unwrap().rounds(), 1_000_000_000);
+
+    // Too many rounds to be represented saturate
+    let hash = "$6$rounds=99999999999999999999999$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1";
+    assert_eq!(parse(hash).unwrap().rounds(), usize::MAX);
+}
+
+/// Long salts are truncated with `SaltMode::Truncate`.
+#[test]
+fn parse_long_salt() {
+    let hash = "$6$toolongsaltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1";
+    assert_eq!(
+        parse_with_salt_mode(hash, SaltMode::Strict),
+        Err(ParseError::SaltTooLong)
+    );
+    let parsed = parse_with_salt_mode(hash, SaltMode::Truncate).unwrap();
+    assert_eq!(parsed.salt(), "toolongsaltstrin");
+
+    let hash = "$6$toolongsaltstri\u{e9}$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1";
+    // Non-ASCII salts can't always be truncated
+    assert_eq!(
+        parse_with_salt_mode(hash, SaltMode::Truncate),
+        Err(ParseError::SaltTooLong)
+    );
+}
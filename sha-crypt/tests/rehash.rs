@@ -0,0 +1,116 @@
+//! Detecting and upgrading weak hash strings.
+
+use sha_crypt::{needs_rehash, ParseError, Variant};
+
+/// `Hello world!` with the default rounds.
+const SHA512_DEFAULT: &str = "$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1";
+
+/// `the minimum number is still observed` with 10 rounds, clamped to 1000.
+const SHA256_CLAMPED: &str =
+    "$5$rounds=10$roundstoolow$yfvwcWrQ8l/K0DAWyuPMDNHpIVlTQebY9l/gL972bIC";
+
+#[test]
+fn implicit_default_rounds() {
+    assert_eq!(
+        needs_rehash(SHA512_DEFAULT, Variant::Sha512Crypt, 5_000),
+        Ok(false)
+    );
+    assert_eq!(
+        needs_rehash(SHA512_DEFAULT, Variant::Sha512Crypt, 5_001),
+        Ok(true)
+    );
+    assert_eq!(
+        needs_rehash(SHA512_DEFAULT, Variant::Sha256Crypt, 1_000),
+        Ok(true)
+    );
+}
+
+#[test]
+fn explicit_rounds() {
+    let hash = "$6$rounds=100000$exn6tVc2j/MZD8uG$BI1Xh8qQSK9J4m14uwy7abn.ctj/TIAzlaVCto0MQrOFIeTXsc1iwzH16XEWo/a7c7Y9eVJvufVzYAs4EsPOy0";
+    assert_eq!(needs_rehash(hash, Variant::Sha512Crypt, 100_000), Ok(false));
+    assert_eq!(needs_rehash(hash, Variant::Sha512Crypt, 50_000), Ok(false));
+    assert_eq!(needs_rehash(hash, Variant::Sha512Crypt, 100_001), Ok(true));
+
+    assert_eq!(
+        needs_rehash(SHA256_CLAMPED, Variant::Sha256Crypt, 1_000),
+        Ok(false)
+    );
+    assert_eq!(
+        needs_rehash(SHA256_CLAMPED, Variant::Sha512Crypt, 1_000),
+        Ok(true)
+    );
+}
+
+#[test]
+fn malformed() {
+    assert_eq!(
+        needs_rehash("$6$saltstring$", Variant::Sha512Crypt, 5_000),
+        Err(ParseError::InvalidHashLength)
+    );
+    assert_eq!(
+        needs_rehash(
+            "$1$saltstring$OH4IDuTlsuTYPdED1gsuiRMy",
+            Variant::Sha512Crypt,
+            5_000
+        ),
+        Err(ParseError::UnsupportedAlgorithm)
+    );
+}
+
+#[cfg(feature = "simple")]
+mod verify_and_rehash {
+    use super::{SHA256_CLAMPED, SHA512_DEFAULT};
+    use sha_crypt::{check, needs_rehash, verify_and_rehash, CheckError, Sha512Params, Variant};
+
+    const HASHES: [(&str, &str); 2] = [
+        ("Hello world!", SHA512_DEFAULT),
+        ("the minimum number is still observed", SHA256_CLAMPED),
+    ];
+
+    #[test]
+    fn rehashes_weak_hashes() {
+        let params = Sha512Params::new(6_000).unwrap();
+
+        for (password, hash) in HASHES {
+            let rehashed = verify_and_rehash(password, hash, &params).unwrap().unwrap();
+            assert!(rehashed.starts_with("$6$rounds=6000$"), "{}", rehashed);
+            assert!(check(password, &rehashed).is_ok());
+            assert_eq!(
+                needs_rehash(&rehashed, Variant::Sha512Crypt, 6_000),
+                Ok(false)
+            );
+
+            // Upgraded hash strings are kept
+            assert_eq!(
+                verify_and_rehash(password, &rehashed, &params).unwrap(),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_strong_hashes() {
+        let params = Sha512Params::new(5_000).unwrap();
+        assert_eq!(
+            verify_and_rehash("Hello world!", SHA512_DEFAULT, &params).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn wrong_password_never_rehashes() {
+        let params = Sha512Params::new(6_000).unwrap();
+
+        for (_, hash) in HASHES {
+            assert!(matches!(
+                verify_and_rehash("wrong password", hash, &params),
+                Err(CheckError::HashMismatch)
+            ));
+        }
+        assert!(matches!(
+            verify_and_rehash("Hello world!", "$6$saltstring$", &params),
+            Err(CheckError::InvalidFormat(_))
+        ));
+    }
+}
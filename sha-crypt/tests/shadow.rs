@@ -0,0 +1,188 @@
+//! Lines of `/etc/shadow` and htpasswd files.
+
+use sha_crypt::{
+    shadow::{verify_htpasswd_user, verify_user, HtpasswdEntry, PasswordField, ShadowEntry},
+    CheckError, ShadowError,
+};
+
+/// `Hello world!` with SHA512-crypt.
+const SHA512_LINE: &str = "alice:$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1:19000:0:99999:7:::";
+
+/// `Hello world!` with SHA256-crypt.
+const SHA256_LINE: &str =
+    "bob:$5$saltstring$5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc5:19345:1:90:14:30:20000:";
+
+const SHADOW_LINES: &[&str] = &[
+    SHA512_LINE,
+    SHA256_LINE,
+    "root:*:19000:0:99999:7:::",
+    "daemon:!:19000::::::",
+    "carol:!$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1:19000:0:99999:7:::",
+    "guest::19000:0:99999:7:::",
+];
+
+#[test]
+fn parse_shadow() {
+    let entry = ShadowEntry::parse(SHA256_LINE).unwrap();
+    assert_eq!(entry.username(), "bob");
+    assert_eq!(
+        entry.hash_field(),
+        "$5$saltstring$5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc5"
+    );
+    assert_eq!(entry.password(), PasswordField::Hash(entry.hash_field()));
+    assert_eq!(entry.last_change(), "19345");
+    assert_eq!(entry.min_age(), "1");
+    assert_eq!(entry.max_age(), "90");
+    assert_eq!(entry.warn_period(), "14");
+    assert_eq!(entry.inactivity_period(), "30");
+    assert_eq!(entry.expiration(), "20000");
+    assert_eq!(entry.reserved(), "");
+
+    let line = format!("{}\n", SHA512_LINE);
+    let parsed = ShadowEntry::parse(&line).unwrap();
+    assert_eq!(parsed, ShadowEntry::parse(SHA512_LINE).unwrap());
+}
+
+#[test]
+fn parse_shadow_password_field() {
+    let fields = SHADOW_LINES
+        .iter()
+        .map(|line| ShadowEntry::parse(line).unwrap().password());
+
+    let expected = [true, true, false, false, false, false];
+    for (field, is_hash) in fields.zip(expected) {
+        assert_eq!(matches!(field, PasswordField::Hash(_)), is_hash);
+    }
+
+    let locked = ShadowEntry::parse(SHADOW_LINES[4]).unwrap();
+    assert_eq!(locked.password(), PasswordField::Locked);
+    let empty = ShadowEntry::parse(SHADOW_LINES[5]).unwrap();
+    assert_eq!(empty.password(), PasswordField::Empty);
+}
+
+#[test]
+fn parse_shadow_invalid() {
+    for line in [
+        "",
+        ":*:19000:0:99999:7:::",
+        "root:*:19000:0:99999:7::",
+        "root:*:19000:0:99999:7::::",
+        "root",
+    ] {
+        assert!(
+            matches!(ShadowEntry::parse(line), Err(ShadowError::InvalidLine)),
+            "{:?}",
+            line
+        );
+    }
+}
+
+#[test]
+fn shadow_round_trip() {
+    for line in SHADOW_LINES {
+        let entry = ShadowEntry::parse(line).unwrap();
+        assert_eq!(entry.to_string(), *line);
+        #[cfg(feature = "alloc")]
+        assert_eq!(entry.to_line(), *line);
+        assert_eq!(ShadowEntry::parse(&entry.to_string()).unwrap(), entry);
+    }
+}
+
+#[test]
+fn verify_shadow() {
+    assert!(verify_user(SHA512_LINE, "Hello world!").is_ok());
+    assert!(verify_user(SHA256_LINE, b"Hello world!").is_ok());
+    assert!(ShadowEntry::parse(SHA512_LINE)
+        .unwrap()
+        .verify("Hello world!")
+        .is_ok());
+
+    assert!(matches!(
+        verify_user(SHA512_LINE, "wrong password"),
+        Err(ShadowError::Check(CheckError::HashMismatch))
+    ));
+    assert!(matches!(
+        verify_user("root:*:19000:0:99999:7:::", "Hello world!"),
+        Err(ShadowError::Locked)
+    ));
+    assert!(matches!(
+        verify_user(SHADOW_LINES[4], "Hello world!"),
+        Err(ShadowError::Locked)
+    ));
+    assert!(matches!(
+        verify_user(SHADOW_LINES[5], ""),
+        Err(ShadowError::NoPassword)
+    ));
+    assert!(matches!(
+        verify_user("dave:$y$j9T$F5Jx5fExrKuPp53xLKQ..1$X3DX6M94c7o.9agCG9G317fhZg9SqC.5i5rd.RhAtQ7:19000:0:99999:7:::", "Hello world!"),
+        Err(ShadowError::UnsupportedScheme)
+    ));
+    assert!(matches!(
+        verify_user("erin:$6$saltstring$:19000:0:99999:7:::", "Hello world!"),
+        Err(ShadowError::Check(CheckError::InvalidFormat(_)))
+    ));
+    assert!(matches!(
+        verify_user("erin:$6$saltstring$", "Hello world!"),
+        Err(ShadowError::InvalidLine)
+    ));
+}
+
+#[test]
+fn parse_htpasswd() {
+    let entry =
+        HtpasswdEntry::parse("bob:$5$saltstring$5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc5\r\n")
+            .unwrap();
+    assert_eq!(entry.username(), "bob");
+    assert_eq!(
+        entry.hash_field(),
+        "$5$saltstring$5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc5"
+    );
+    assert_eq!(
+        entry.to_string(),
+        "bob:$5$saltstring$5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc5"
+    );
+    #[cfg(feature = "alloc")]
+    assert_eq!(entry.to_line(), entry.to_string());
+
+    for line in ["", "bob", ":hash", "bob:hash:extra"] {
+        assert!(
+            matches!(HtpasswdEntry::parse(line), Err(ShadowError::InvalidLine)),
+            "{:?}",
+            line
+        );
+    }
+}
+
+#[test]
+fn verify_htpasswd() {
+    let sha512 = "alice:$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1";
+    assert!(verify_htpasswd_user(sha512, "Hello world!").is_ok());
+    assert!(matches!(
+        verify_htpasswd_user(sha512, "wrong password"),
+        Err(ShadowError::Check(CheckError::HashMismatch))
+    ));
+
+    for line in [
+        "carol:$apr1$9Cv/OMGj$TstyeGloJH4v0PWnMiBOm/",
+        "carol:$2y$05$c4WoMPo3SXsafkva.HHa6uXQZWr7oboPiC2bT/r7q1BB8I2s0BRqC",
+        "carol:{SHA}0qPPGhhxUlQyk0/X6AjverCtMIQ=",
+        "carol:rqXexS6ZhobKA",
+    ] {
+        assert!(
+            matches!(
+                verify_htpasswd_user(line, "Hello world!"),
+                Err(ShadowError::UnsupportedScheme)
+            ),
+            "{:?}",
+            line
+        );
+    }
+
+    assert!(matches!(
+        verify_htpasswd_user(
+            "carol:!$apr1$9Cv/OMGj$TstyeGloJH4v0PWnMiBOm/",
+            "Hello world!"
+        ),
+        Err(ShadowError::Locked)
+    ));
+}
@@ -0,0 +1,112 @@
+//! Tests for `password-hash` crate integration.
+
+#![cfg(feature = "simple")]
+
+use sha_crypt::{
+    password_hash::{McfHasher, PasswordHash, PasswordHasher, PasswordVerifier, Salt},
+    Algorithm, ShaCrypt, ShaCryptParams,
+};
+
+const PASSWORD: &str = "password";
+
+/// Hashes of `PASSWORD` generated by `crypt(3)` (libxcrypt 4.4).
+const CRYPT_HASHES: &[(&str, Algorithm, u32)] = &[
+    (
+        "$5$rounds=11858$WH1ABM5sKhxbkgCK$UKhequMIvBldRTKCYbdBB3MSUvOjO4R85g/4Ti2Kso3",
+        Algorithm::Sha256Crypt,
+        11858,
+    ),
+    (
+        "$5$saltstring$OH4IDuTlsuTYPdED1gsuiRMyTAwNlRWyA6Xr3I4/dQ5",
+        Algorithm::Sha256Crypt,
+        5000,
+    ),
+    (
+        "$6$rounds=11531$G/gkPn17kHYo0gTF$80ssgAlF91Hm9xH2BcGk3H.FkurSyybHPdr0Uw9zz.p6F8M2DxStJnIjiRMme1ILz83GhPh6w4RZzi0G/fwCw.",
+        Algorithm::Sha512Crypt,
+        11531,
+    ),
+    (
+        "$6$bbe605c2cce4c642$C2TZeePUtLENBMAl.nb2PKELcHyS5IVvH6DUyRzAc73vIhTu/hYLfI.JF6sn9rl/0zW.O1P6d1ZGq0sVPMmvl/",
+        Algorithm::Sha512Crypt,
+        5000,
+    ),
+];
+
+#[test]
+fn upgrade_and_verify_crypt_hashes() {
+    for &(mcf_hash, algorithm, rounds) in CRYPT_HASHES {
+        let hash = ShaCrypt.upgrade_mcf_hash(mcf_hash).unwrap();
+        assert_eq!(hash.algorithm, algorithm.ident());
+        assert_eq!(
+            ShaCryptParams::try_from(&hash).unwrap(),
+            ShaCryptParams { rounds }
+        );
+        assert_eq!(
+            hash.salt.unwrap().as_str(),
+            mcf_hash.split('$').rev().nth(1).unwrap()
+        );
+
+        assert!(hash.verify_password(&[&ShaCrypt], PASSWORD).is_ok());
+        assert!(hash.verify_password(&[&ShaCrypt], "wrong").is_err());
+
+        // Through PHC strings
+        let phc_string = hash.to_string();
+        let hash = PasswordHash::new(&phc_string).unwrap();
+        assert!(ShaCrypt.verify_password(PASSWORD.as_bytes(), &hash).is_ok());
+
+        assert!(ShaCrypt
+            .verify_mcf_hash(PASSWORD.as_bytes(), mcf_hash)
+            .is_ok());
+    }
+}
+
+#[test]
+fn hash_and_verify() {
+    let salt = Salt::from_b64("saltstring").unwrap();
+
+    for algorithm in [Algorithm::Sha256Crypt, Algorithm::Sha512Crypt] {
+        let params = ShaCryptParams { rounds: 1000 };
+        let hash = ShaCrypt
+            .hash_password_customized(
+                PASSWORD.as_bytes(),
+                Some(algorithm.ident()),
+                None,
+                params,
+                salt,
+            )
+            .unwrap();
+
+        assert_eq!(hash.algorithm, algorithm.ident());
+        assert_eq!(ShaCryptParams::try_from(&hash).unwrap(), params);
+        assert!(hash.verify_password(&[&ShaCrypt], PASSWORD).is_ok());
+    }
+
+    // The default is SHA512-crypt with 5000 rounds, as for `crypt(3)`
+    let hash = ShaCrypt.hash_password(PASSWORD.as_bytes(), salt).unwrap();
+    let crypt_hash = ShaCrypt
+        .upgrade_mcf_hash("$6$saltstring$adDbXsJjcDlq2662QPgd.tkSOVmnG9Tt3oXl4HR60SusC3AGjirnDenVZp3DGwLwqy6iYKCzannhaX9DR72nN1")
+        .unwrap();
+    assert_eq!(hash, crypt_hash);
+}
+
+#[test]
+fn invalid_hashes() {
+    for mcf_hash in [
+        "$1$saltstring$OH4IDuTlsuTYPdED1gsuiRMyTAwNlRWyA6Xr3I4/dQ5",
+        // Salt too short for PHC strings
+        "$5$abc$OH4IDuTlsuTYPdED1gsuiRMyTAwNlRWyA6Xr3I4/dQ5",
+        // Rounds out of range
+        "$5$rounds=999$saltstring$OH4IDuTlsuTYPdED1gsuiRMyTAwNlRWyA6Xr3I4/dQ5",
+        // Invalid hash
+        "$5$saltstring$OH4IDuTlsuTYPdED1gsuiRMyTAwNlRWyA6Xr3I4",
+        "$6$saltstring$OH4IDuTlsuTYPdED1gsuiRMyTAwNlRWyA6Xr3I4/dQ5",
+    ] {
+        assert!(ShaCrypt.upgrade_mcf_hash(mcf_hash).is_err(), "{}", mcf_hash);
+    }
+
+    let hash = PasswordHash::new("$sha512-crypt$rounds=999$saltstring$AAAAAAAAAAAAAA").unwrap();
+    assert!(ShaCryptParams::try_from(&hash).is_err());
+    let hash = PasswordHash::new("$sha512-crypt$i=5000$saltstring$AAAAAAAAAAAAAA").unwrap();
+    assert!(ShaCryptParams::try_from(&hash).is_err());
+}
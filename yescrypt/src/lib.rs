@@ -0,0 +1,111 @@
+//! Pure Rust implementation of the [yescrypt password hash][1]: the `$y$`
+//! hashes of `crypt(3)`, which libxcrypt, and so most current Linux
+//! distributions, use by default for new passwords.
+//!
+//! yescrypt is a superset of scrypt, and this crate is built on the
+//! yescrypt implementation of the [`scrypt`] crate: the KDF itself is
+//! [`yescrypt()`], and this crate adds the `$y$` hash strings on top of it.
+//!
+//! # Usage
+//!
+//! ```
+//! use yescrypt::{Mode, Params};
+//!
+//! // Verify a stored hash
+//! let hash = "$y$j75$saltsaltsaltsalt$hI02SdBpr3mSssvBRd05Dwe0nTFc/hsy01KTxh646J.";
+//! assert!(yescrypt::verify("password", hash).is_ok());
+//! assert!(yescrypt::verify("bogus", hash).is_err());
+//!
+//! // Hash a password with a salt and parameters
+//! let salt = yescrypt::decode_salt("saltsaltsaltsalt")?;
+//! let params = Params::new(Mode::ReadWrite, 10, 8, 1, 0)?;
+//! assert_eq!(yescrypt::hash("password", &salt, params)?, hash);
+//! # Ok::<(), yescrypt::Error>(())
+//! ```
+//!
+//! # Hash strings
+//!
+//! Hash strings are such as `$y$j9T$<SALT>$<HASH>`, where `j9T` encodes the
+//! [`Params`], here those of [`Params::default`], and the salt and the
+//! 32-byte hash are encoded in the little-endian Base64 of yescrypt, see
+//! [`encode_salt`]. The modes of [`Mode`] are supported, which are the ones
+//! libxcrypt accepts: hashes using other flavors of yescrypt, hash upgrades
+//! or a ROM are rejected.
+//!
+//! Hashes are verified by comparing them in constant time, and
+//! [`verify_with_memory_limit`] refuses to allocate more memory than a
+//! [`MemoryLimit`] for the parameters of a hash string.
+//!
+//! # Features
+//!
+//! - `std`: `std::error::Error` support
+//! - `simple` (default): the `password-hash` API, to hash passwords to PHC
+//!   strings and upgrade `$y$` hashes to them, see [`Yescrypt`]
+//!
+//! [1]: https://www.openwall.com/yescrypt/
+
+#![no_std]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg",
+    html_favicon_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg"
+)]
+#![deny(unsafe_code)]
+#![warn(missing_docs, rust_2018_idioms)]
+
+extern crate alloc;
+
+#[cfg(feature = "simple")]
+mod simple;
+
+pub use scrypt::{
+    errors::YescryptError as Error,
+    yescrypt::{decode_salt, encode_salt, yescrypt, Hash, Mode, Params},
+    MemoryLimit,
+};
+
+#[cfg(feature = "simple")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub use crate::simple::{Yescrypt, ALG_ID};
+
+#[cfg(feature = "simple")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub use password_hash;
+
+use alloc::string::{String, ToString};
+
+/// Length of the salts of generated hashes, in bytes.
+pub const RECOMMENDED_SALT_LEN: usize = 16;
+
+/// Hash `password` with the decoded `salt` and `params`.
+///
+/// # Returns
+/// - `Ok(String)` containing the `$y$` hash string
+/// - `Err(Error::InvalidParams)` if `params` can't be encoded in a hash
+///   string, see [`Hash::new`].
+pub fn hash(password: impl AsRef<[u8]>, salt: &[u8], params: Params) -> Result<String, Error> {
+    Hash::new(password.as_ref(), salt, params).map(|hash| hash.to_string())
+}
+
+/// Verify `password` against the `$y$` hash string `hash`, requiring at
+/// most [`MemoryLimit::DEFAULT`].
+///
+/// # Returns
+/// - `Ok(())` if the password matches
+/// - `Err(Error::PasswordMismatch)` if it doesn't
+/// - the errors of [`Hash::parse`] and [`verify_with_memory_limit`]
+///   otherwise.
+pub fn verify(password: impl AsRef<[u8]>, hash: &str) -> Result<(), Error> {
+    scrypt::yescrypt::verify(password.as_ref(), hash)
+}
+
+/// Verify `password` against the `$y$` hash string `hash`, rejecting the
+/// parameters requiring more memory than `limit` with
+/// `Err(Error::MemoryLimitExceeded)` before allocating it.
+pub fn verify_with_memory_limit(
+    password: impl AsRef<[u8]>,
+    hash: &str,
+    limit: MemoryLimit,
+) -> Result<(), Error> {
+    scrypt::yescrypt::verify_with_memory_limit(password.as_ref(), hash, limit)
+}
@@ -0,0 +1,117 @@
+//! Implementation of the `password-hash` crate API.
+
+use crate::{decode_salt, encode_salt, Hash, MemoryLimit, Params, RECOMMENDED_SALT_LEN};
+use password_hash::{
+    errors::InvalidValue, rand_core::CryptoRngCore, Decimal, Error, Ident, McfHasher, Output,
+    PasswordHash, PasswordHasher, Result, Salt, SaltString,
+};
+
+/// Algorithm identifier
+pub const ALG_ID: Ident<'static> = Ident::new_unwrap("yescrypt");
+
+/// Prefix of `$y$` hash strings.
+const PREFIX: &str = "$y$";
+
+/// yescrypt type for use with [`PasswordHasher`].
+///
+/// yescrypt has no standard PHC string format, so [`Yescrypt`] hashes
+/// passwords to PHC strings of its own, which won't change:
+///
+/// ```text
+/// $yescrypt$[mode=<MODE>,]ln=<LOG_N>,r=<R>[,p=<P>][,t=<T>]$<SALT>$<HASH>
+/// ```
+///
+/// - `mode` is `classic`, `worm` or `rw`, see [`Mode`](crate::Mode). It is
+///   left out for `rw`, and the same goes for `p = 1` and `t = 0`. Left-out
+///   params are those of [`Params::default`].
+/// - `<SALT>` is the salt as in `$y$` hash strings, encoded in the Base64 of
+///   yescrypt. Salts generated with [`SaltString::generate`] are encoded in
+///   the standard Base64 instead, so salts are generated with
+///   [`Yescrypt::generate_salt`].
+/// - `<HASH>` is the 32-byte hash, encoded as in every PHC string: with the
+///   standard Base64 alphabet, without padding.
+///
+/// These PHC strings hash passwords as the `$y$` hash strings of the
+/// [crate docs](crate) do, which [`McfHasher`] converts to them.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub struct Yescrypt;
+
+impl Yescrypt {
+    /// Generate a random salt of [`RECOMMENDED_SALT_LEN`] bytes from `rng`,
+    /// encoded in the Base64 of yescrypt as [`Yescrypt`] expects.
+    pub fn generate_salt(mut rng: impl CryptoRngCore) -> SaltString {
+        let mut salt = [0u8; RECOMMENDED_SALT_LEN];
+        rng.fill_bytes(&mut salt);
+
+        SaltString::from_b64(&encode_salt(&salt)).expect("yescrypt salts are valid PHC salts")
+    }
+}
+
+impl PasswordHasher for Yescrypt {
+    type Params = Params;
+
+    /// Parameters needing more memory than [`MemoryLimit::DEFAULT`] are
+    /// rejected with [`InvalidValue::TooLong`], as
+    /// [`PasswordVerifier::verify_password`](password_hash::PasswordVerifier::verify_password)
+    /// goes through this method.
+    fn hash_password_customized<'a>(
+        &self,
+        password: &[u8],
+        alg_id: Option<Ident<'a>>,
+        version: Option<Decimal>,
+        params: Params,
+        salt: impl Into<Salt<'a>>,
+    ) -> Result<PasswordHash<'a>> {
+        if !matches!(alg_id, Some(ALG_ID) | None) {
+            return Err(Error::Algorithm);
+        }
+
+        // Versions unsupported
+        if version.is_some() {
+            return Err(Error::Version);
+        }
+
+        // Must happen before anything is allocated
+        if params.memory_required() > MemoryLimit::DEFAULT.bytes() {
+            return Err(InvalidValue::TooLong.param_error());
+        }
+
+        let salt = salt.into();
+        let salt_bytes =
+            decode_salt(salt.as_str()).map_err(|_| Error::SaltInvalid(InvalidValue::Malformed))?;
+
+        let hash = Hash::new(password, &salt_bytes, params)?;
+
+        Ok(PasswordHash {
+            algorithm: ALG_ID,
+            version: None,
+            params: params.try_into()?,
+            salt: Some(salt),
+            hash: Some(Output::new(hash.hash())?),
+        })
+    }
+}
+
+impl McfHasher for Yescrypt {
+    /// Upgrade a `$y$` hash string to a PHC hash.
+    ///
+    /// Its salt must be a valid PHC salt, which rules out salts shorter than
+    /// 4 characters.
+    fn upgrade_mcf_hash<'a>(&self, hash: &'a str) -> Result<PasswordHash<'a>> {
+        if !hash.starts_with(PREFIX) {
+            return Err(Error::Algorithm);
+        }
+
+        let parsed = Hash::parse(hash)?;
+        let salt = hash.split('$').nth(3).ok_or(Error::PhcStringField)?;
+
+        Ok(PasswordHash {
+            algorithm: ALG_ID,
+            version: None,
+            params: (*parsed.params()).try_into()?,
+            salt: Some(Salt::from_b64(salt)?),
+            hash: Some(Output::new(parsed.hash())?),
+        })
+    }
+}
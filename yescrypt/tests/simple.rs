@@ -0,0 +1,68 @@
+//! Tests for the `password-hash` crate API.
+
+#![cfg(feature = "simple")]
+
+use yescrypt::{
+    password_hash::{rand_core::OsRng, McfHasher, PasswordHash, PasswordHasher, PasswordVerifier},
+    Mode, Params, Yescrypt,
+};
+
+const PASSWORD: &str = "hunter2";
+
+const MCF_HASH: &str = "$y$/A5.0$saltsaltsaltsalt$RxA2QzzzJ94BvQP/0J7ucBQlVHOT80oRATay6GKWDvB";
+
+const PHC_HASH: &str =
+    "$yescrypt$mode=worm,ln=13,r=8,p=4$saltsaltsaltsalt$Xc8Q3P//1WI0O7cFQpXoaMPF4aR9ikB3zGf6iGSJz94";
+
+#[test]
+fn hash_and_verify() {
+    let salt = Yescrypt::generate_salt(&mut OsRng);
+    assert_eq!(salt.len(), 22);
+
+    let hash = Yescrypt.hash_password(PASSWORD.as_bytes(), &salt).unwrap();
+    assert!(hash.to_string().starts_with("$yescrypt$ln=12,r=32$"));
+    assert_eq!(Params::try_from(&hash).unwrap(), Params::default());
+
+    assert!(Yescrypt.verify_password(PASSWORD.as_bytes(), &hash).is_ok());
+    assert!(Yescrypt.verify_password(b"hunter3", &hash).is_err());
+}
+
+#[test]
+fn upgrade_mcf_hash() {
+    let hash = Yescrypt.upgrade_mcf_hash(MCF_HASH).unwrap();
+    assert_eq!(hash.to_string(), PHC_HASH);
+    assert_eq!(
+        Params::try_from(&hash).unwrap(),
+        Params::new(Mode::Worm, 13, 8, 4, 0).unwrap()
+    );
+
+    assert!(Yescrypt
+        .verify_mcf_hash(PASSWORD.as_bytes(), MCF_HASH)
+        .is_ok());
+    assert!(Yescrypt.verify_mcf_hash(b"hunter3", MCF_HASH).is_err());
+
+    assert!(Yescrypt
+        .upgrade_mcf_hash("$6$saltsalt$qFmFH.bQmmtXzyBY0s9v7Oicd2z4XSIecDzlB5KiA2")
+        .is_err());
+}
+
+#[test]
+fn verify_phc_hash() {
+    let hash = PasswordHash::new(PHC_HASH).unwrap();
+    assert!(Yescrypt.verify_password(PASSWORD.as_bytes(), &hash).is_ok());
+    assert!(Yescrypt.verify_password(b"hunter3", &hash).is_err());
+}
+
+#[test]
+fn reject_invalid_params() {
+    for hash in [
+        "$yescrypt$mode=rom,ln=12,r=32$saltsaltsaltsalt$Xc8Q3P//1WI0O7cFQpXoaMPF4aR9ikB3zGf6iGSJz94",
+        "$yescrypt$ln=12,r=0$saltsaltsaltsalt$Xc8Q3P//1WI0O7cFQpXoaMPF4aR9ikB3zGf6iGSJz94",
+        "$yescrypt$ln=12,r=32,n=4$saltsaltsaltsalt$Xc8Q3P//1WI0O7cFQpXoaMPF4aR9ikB3zGf6iGSJz94",
+        // 128 GiB
+        "$yescrypt$ln=30,r=1024$saltsaltsaltsalt$Xc8Q3P//1WI0O7cFQpXoaMPF4aR9ikB3zGf6iGSJz94",
+    ] {
+        let hash = PasswordHash::new(hash).unwrap();
+        assert!(Yescrypt.verify_password(PASSWORD.as_bytes(), &hash).is_err());
+    }
+}
@@ -0,0 +1,64 @@
+//! `$y$` hash strings computed with `crypt(3)` of libxcrypt.
+
+use yescrypt::{Error, Mode, Params};
+
+const PASSWORD: &str = "hunter2";
+
+/// Hashes of [`PASSWORD`] with the salt `saltsaltsaltsalt`, as
+/// `(mode, log_n, r, p, hash)`.
+const HASHES: &[(Mode, u8, u32, u32, &str)] = &[
+    (
+        Mode::ReadWrite,
+        12,
+        32,
+        1,
+        "$y$j9T$saltsaltsaltsalt$pQKob88sNg1.ktD6ni0OAUKcK4w22JpXSbU9UtU38wC",
+    ),
+    (
+        Mode::Worm,
+        13,
+        8,
+        4,
+        "$y$/A5.0$saltsaltsaltsalt$RxA2QzzzJ94BvQP/0J7ucBQlVHOT80oRATay6GKWDvB",
+    ),
+    (
+        Mode::Classic,
+        12,
+        8,
+        1,
+        "$y$.95$saltsaltsaltsalt$WAzgJ/mE9Q29clXdv/TyvuDqdiRHFhLjOMuCK2QYNV8",
+    ),
+];
+
+#[test]
+fn hash() {
+    let salt = yescrypt::decode_salt("saltsaltsaltsalt").unwrap();
+
+    for &(mode, log_n, r, p, expected) in HASHES {
+        let params = Params::new(mode, log_n, r, p, 0).unwrap();
+        assert_eq!(yescrypt::hash(PASSWORD, &salt, params).unwrap(), expected);
+    }
+}
+
+#[test]
+fn default_params() {
+    let salt = yescrypt::decode_salt("saltsaltsaltsalt").unwrap();
+    let hash = yescrypt::hash(PASSWORD, &salt, Params::default()).unwrap();
+    assert_eq!(hash, HASHES[0].4);
+}
+
+#[test]
+fn verify() {
+    for &(.., hash) in HASHES {
+        assert_eq!(yescrypt::verify(PASSWORD, hash), Ok(()));
+        assert_eq!(
+            yescrypt::verify("hunter3", hash),
+            Err(Error::PasswordMismatch)
+        );
+    }
+
+    assert_eq!(
+        yescrypt::verify(PASSWORD, "$y$j9T$saltsaltsaltsalt$"),
+        Err(Error::Malformed)
+    );
+}